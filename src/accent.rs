@@ -0,0 +1,51 @@
+//! Accent colours
+//!
+//! Colours from `[accents]` for remotes, or `remote:project`, so that prod
+//! looks nothing like the laptop; a project's colour wins over its remote's.
+
+use log::warn;
+use ratatui::style::Color;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// The project LXTUI lists instances from on every server
+pub const PROJECT: &str = "default";
+
+#[derive(Debug, Clone, Default)]
+pub struct Accents {
+    colors: HashMap<String, Color>, // By remote or remote:project
+}
+
+impl Accents {
+    /// Read the configured accents, leaving out (with a warning) any that
+    /// aren't colours
+    pub fn new(config: &BTreeMap<String, String>) -> Self {
+        let colors = config
+            .iter()
+            .filter_map(|(key, value)| match Color::from_str(value.trim()) {
+                Ok(color) => Some((key.trim().to_string(), color)),
+                Err(_) => {
+                    warn!("Ignoring accent '{}' of {}: not a colour", value, key);
+                    None
+                }
+            })
+            .collect();
+        Accents { colors }
+    }
+
+    /// Accent of `project` on `remote`, or else of the remote
+    pub fn of(&self, remote: &str, project: &str) -> Option<Color> {
+        self.colors
+            .get(&format!("{}:{}", remote, project))
+            .or_else(|| self.colors.get(remote))
+            .copied()
+    }
+
+    /// Accent of the remote and project instances are listed from
+    pub fn of_remote(&self, remote: &str) -> Option<Color> {
+        self.of(remote, PROJECT)
+    }
+}
+
+#[cfg(test)]
+mod tests;
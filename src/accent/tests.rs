@@ -0,0 +1,22 @@
+//! Tests of accent colours per remote and project
+
+use super::Accents;
+use ratatui::style::Color;
+use std::collections::BTreeMap;
+
+#[test]
+fn projects_take_precedence_over_their_remote() {
+    let accents = Accents::new(&BTreeMap::from([
+        ("prod".to_string(), "red".to_string()),
+        ("prod:staging".to_string(), "yellow".to_string()),
+        ("local".to_string(), " #00ff00 ".to_string()),
+        ("lab".to_string(), "not-a-colour".to_string()),
+    ]));
+
+    assert_eq!(accents.of("prod", "staging"), Some(Color::Yellow));
+    assert_eq!(accents.of("prod", "billing"), Some(Color::Red));
+    assert_eq!(accents.of_remote("prod"), Some(Color::Red));
+    assert_eq!(accents.of_remote("local"), Some(Color::Rgb(0, 255, 0)));
+    assert_eq!(accents.of_remote("lab"), None);
+    assert_eq!(accents.of_remote("dev"), None);
+}
@@ -0,0 +1,1172 @@
+//! Actions and the update function
+//!
+//! Key presses are turned into `Action`s by the input module; `update` is
+//! the only place that applies them to `App`. Rendering reads `App` and
+//! never changes it, so each part can be followed and tested on its own.
+
+use crate::app::{
+    App, CommandMenu, ConfirmAction, InputCallback, InputMode, PendingAction, WizardState,
+};
+use crate::boot;
+use crate::command::{self, Source};
+use crate::config::Permission;
+use crate::field::FieldEdit;
+use crate::filter::ContainerFilter;
+use crate::report::ErrorReport;
+use crate::tags;
+use crate::top::RankBy;
+use crate::workspace::SortKey;
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    // Application
+    Quit,
+    ToggleDebug,
+    TogglePerf,
+    Resize { log_page: u16 }, // Lines the log view now shows
+
+    // Container list and menus
+    SelectNext,
+    SelectPrevious,
+    OpenMenu(CommandMenu),
+    MenuNext(usize), // Number of items in the open menu
+    MenuPrevious(usize),
+    CloseView, // Back to the container list
+    Refresh,
+    ReloadLxd,
+    ControlService,  // Start or restart the local LXD service
+    ExportInventory, // Of the active server, into the configured directory
+    ExportUsage,     // Of the instances sampled this session, as CSV
+    CheckDrift,      // Against an exported inventory
+    ToggleSidebar,
+    ToggleEvents,
+    ToggleGroupByTag,
+    ToggleHighContrast,
+    SwitchWorkspace(usize), // Number key; 0 shows everything
+    Undo,                   // The last change to the filter, sort, grouping, marks or layout
+    Redo,
+    ToggleAllRemotes,
+    TogglePreviewRequests,
+    ShowHelp,
+    StartTour,
+    TourNext,
+    TourPrevious,
+    EndTour,
+    OpenLog,
+    OpenTop,
+    OpenDashboard,
+    CloseDashboard, // Into the container list
+    OpenBootOrder,
+    OpenAudit,
+    OpenZones,
+    OpenImageStore,
+    OpenCapacity,
+    OpenPools,
+    OpenTemplates,
+    OpenVmSettings,
+    OpenIdmap,
+    OpenRecreate,
+    SkipPowerSchedule, // The selected container's next scheduled start or stop
+    HoldPowerSchedule,
+    PublishSelected, // As an image, described in the user's editor
+    NewContainer,
+    OpenCommandLine,
+
+    // The selected container
+    StartSelected,
+    StopSelected,
+    RestartSelected,
+    DeleteSelected,
+    QuickDelete,    // d on the list, refused for protected containers
+    ToggleSelected, // Start if stopped, stop if running
+    CloneSelected,
+    RefreshCopySelected,
+    OpenBackups,
+    EditTags,
+    EditConfig,
+    EditNotes,
+    ExecSelected,
+    SshSelected,
+    HostShell,        // $SHELL on this machine, with the TUI suspended
+    CopyEditorRemote, // code --remote command and SSH config for the selected container
+    AttachMedia,      // An ISO on the selected VM's install device
+    ToggleMediaBoot,
+    DetachMedia,
+    WatchSelected,
+    FollowConsole,
+    ShowCrashLog, // Console log captured when the selected container crashed
+    ToggleCompareMark,
+    CompareMarked, // The two marked containers, or the marked one and the selected one
+    TogglePick,    // For batch changes
+    ClearPicks,
+    OpenProfiles, // Of the picked instances, or the selected one
+    OpenQuick,    // Likewise
+    SearchConfigKey,
+    RunCustom(usize), // Index into the configured custom actions
+
+    // Commands from the command line and scripts
+    Request(ConfirmAction), // Confirm as the policy requires, then run
+    Run(PendingAction),     // Run without confirmation
+    SetFilter(Option<ContainerFilter>),
+    SetSort(Option<SortKey>),
+    OpenWorkspace(String),
+    SaveWorkspace(String),
+    SelectContainer(String), // Name as listed
+    // Confirmations and request previews
+    Confirm(ConfirmAction),
+    CancelDialog,
+    RemoveProtection(String), // Then ask again to delete it
+    SendPreview(PendingAction),
+    CancelPreview(PendingAction),
+
+    // Text input in prompts, the wizard and typed confirmations
+    EditInput(FieldEdit),
+    RecallInput { forward: bool }, // Step through the prompt's history
+    SubmitInput(InputCallback),
+    CancelInput,
+
+    // Adding a remote
+    AddRemote,
+    AuthWithToken,
+    AuthWithOidc,
+    AuthWithCertificate,
+    CancelAddRemote,
+
+    // New container wizard
+    WizardNameDone,
+    WizardUseDefaults, // Skip to the confirm step with the creation defaults
+    WizardBackToName,
+    WizardGoTo(WizardState),
+    WizardNextImage,
+    WizardPreviousImage,
+    WizardImageChar(char),
+    WizardImagePaste(String),
+    WizardImageBackspace,
+    WizardImageDone,
+    WizardSetVm(bool),
+    WizardTypeDone,
+    WizardMediaDone,
+    WizardBackToType, // From the ISO step, dropping what was typed
+    WizardBackFromConfirm,
+    CreateContainer,
+
+    // Clone form
+    CloneFieldNext,
+    CloneFieldPrevious,
+    CloneToggle,      // Flip a checkbox or step a picker
+    CloneCycle(bool), // Step a picker forward or back
+    SubmitClone,
+
+    // Backups view, for the named container
+    BackupNext,
+    BackupPrevious,
+    CreateBackup(String),
+    AskDeleteBackup(String),
+    DeleteBackup(String),
+    CancelDeleteBackup,
+    DownloadBackup(String),
+    ReloadBackups(String),
+
+    // Help screen
+    ScrollHelp(i32),
+    HelpTop,
+    HelpBottom,
+    StartHelpSearch,
+    HelpSearchChar(char),
+    HelpSearchPaste(String),
+    HelpSearchBackspace,
+    EndHelpSearch,
+    CancelHelpSearch,
+    ClearHelpQuery,
+
+    // Log view
+    ScrollLog { delta: i32, page: u16 },
+    LogTop,
+    LogBottom,
+
+    // Watch view
+    ClearWatchEvents,
+
+    // Compare view
+    ScrollCompare(i32),
+    ToggleCompareDifferences,
+
+    // Ranking view
+    RankTopBy(RankBy),
+    ResizeTopWindow(i32), // Samples averaged
+
+    // Boot order view
+    BootOrderNext,
+    BootOrderPrevious,
+    CycleAutostart,
+    EditBootPriority,
+    EditBootDelay,
+    CycleBootSort,
+    ReloadBootOrder,
+
+    // Base-image audit view
+    AuditNext,
+    AuditPrevious,
+    AskRebuild,
+    RebuildSelected,
+    CancelRebuild,
+    ReloadAudit,
+
+    // Network zones view
+    ZoneNext,
+    ZonePrevious,
+    NewZone,
+    NewZoneRecord,
+    EditZoneRecord,
+    AskDeleteZoneEntry,
+    DeleteZoneEntry, // The selected zone or record
+    CancelZoneDelete,
+    ReloadZones,
+
+    // Image store view
+    ImageNext,
+    ImagePrevious,
+    ToggleImageAutoUpdate,
+    RefreshImage, // Pull the latest build now
+    ReloadImageStore,
+
+    // Capacity view
+    CapacityNext,
+    CapacityPrevious,
+    ReloadCapacity,
+    PoolNext,
+    PoolPrevious,
+    AskScrubPool,
+    ScrubPool, // The selected pool, as root
+    CancelScrubPool,
+    ReloadPools,
+
+    // Templates view
+    TemplateNext,
+    TemplatePrevious,
+    EditTemplate,
+    EditMetadata,
+    NewTemplate,
+    ReloadTemplates,
+
+    // VM settings view
+    VmSettingNext,
+    VmSettingPrevious,
+    ChangeVmSetting,
+    ReloadVmSettings,
+
+    // ID mapping view
+    IdmapNext,
+    IdmapPrevious,
+    AddIdmapLine,
+    EditIdmapLine,
+    DeleteIdmapLine,
+    ToggleIdmapIsolated,
+    ToggleDiskShift,
+    ReloadIdmap,
+
+    // Profile view
+    ProfileNext,
+    ProfilePrevious,
+    AddProfile,
+    RemoveProfile,
+    ReloadProfiles,
+
+    // Quick exec view
+    QuickNext,
+    QuickPrevious,
+    ScrollQuick(i32),
+    AskQuick(usize), // Action in the library
+    RunQuick(usize),
+    CancelQuick,
+    AskQuickCommand, // A one-off command
+    RerunQuick,
+    StopQuick, // Stop waiting on the instances still running the command
+    ToggleQuickOutput,
+
+    // Key search view
+    KeySearchNext,
+    KeySearchPrevious,
+    ShowKeySearchHit,
+    ReloadKeySearch,
+
+    // Drift view
+    DriftNext,
+    DriftPrevious,
+    ReloadDrift,
+
+    // Recreate view
+    RenameRecreate,
+    ChangeRecreateImage,
+    ConfirmRecreate,
+
+    // Console view; `page` is the number of lines it shows
+    ScrollConsole { delta: i32, page: u16 },
+    ConsoleTop,
+    ConsoleBottom,
+    ToggleConsolePause,
+    StartConsoleSearch,
+    ConsoleSearchChar(char),
+    ConsoleSearchPaste(String),
+    ConsoleSearchBackspace,
+    EndConsoleSearch { page: u16 },
+    CancelConsoleSearch,
+    ConsoleFind { forward: bool, page: u16 },
+
+    // Operations sidebar
+    FocusOperations,
+    OperationNext,
+    OperationPrevious,
+    ShowOperationDetails,
+    ScrollOperationDetails(i32),
+    RetryOperation, // The one selected in the sidebar
+    FilterOperations,
+    GroupOperations,
+    ClearFinishedOperations,
+
+    // Status modals
+    RunInBackground,
+    AskCancelProgress,
+    KeepProgress,           // Don't cancel after all
+    CancelProgress(String), // UI operation id
+    CopyErrorReport(Box<ErrorReport>),
+    SaveErrorReport(Box<ErrorReport>),
+}
+
+impl Action {
+    /// Whether the action changes an instance or the server, or opens a
+    /// shell or command that could. These are disabled in read-only mode.
+    pub fn mutates(&self) -> bool {
+        self.permission().is_some()
+            || matches!(self, Action::ToggleSelected | Action::RetryOperation)
+    }
+
+    /// What a deployment must permit for the action to run. Toggling and
+    /// retrying depend on the container and the operation, so they are
+    /// checked when they run.
+    pub fn permission(&self) -> Option<Permission> {
+        Some(match self {
+            Action::ControlService => Permission::Service,
+            Action::NewContainer
+            | Action::CreateContainer
+            | Action::OpenRecreate
+            | Action::ConfirmRecreate => Permission::Create,
+            Action::StartSelected => Permission::Start,
+            Action::StopSelected => Permission::Stop,
+            Action::RestartSelected => Permission::Restart,
+            Action::DeleteSelected | Action::QuickDelete => Permission::Delete,
+            Action::CloneSelected | Action::RefreshCopySelected | Action::SubmitClone => {
+                Permission::Clone
+            }
+            Action::EditTags
+            | Action::EditConfig
+            | Action::EditNotes
+            | Action::AttachMedia
+            | Action::ToggleMediaBoot
+            | Action::DetachMedia
+            | Action::ChangeVmSetting
+            | Action::AddIdmapLine
+            | Action::EditIdmapLine
+            | Action::DeleteIdmapLine
+            | Action::ToggleIdmapIsolated
+            | Action::ToggleDiskShift
+            | Action::AddProfile
+            | Action::RemoveProfile
+            | Action::CycleAutostart
+            | Action::EditBootPriority
+            | Action::EditBootDelay
+            | Action::EditTemplate
+            | Action::EditMetadata
+            | Action::NewTemplate
+            | Action::NewZone
+            | Action::NewZoneRecord
+            | Action::EditZoneRecord
+            | Action::AskDeleteZoneEntry
+            | Action::DeleteZoneEntry
+            | Action::RemoveProtection(_) => Permission::Edit,
+            Action::ExecSelected
+            | Action::SshSelected
+            | Action::AskQuick(_)
+            | Action::RunQuick(_)
+            | Action::AskQuickCommand
+            | Action::RerunQuick => Permission::Exec,
+            Action::RunCustom(_) => Permission::Custom,
+            Action::HostShell => Permission::Shell,
+            Action::AskScrubPool | Action::ScrubPool => Permission::Storage,
+            Action::Request(action) | Action::Confirm(action) => action.permission(),
+            Action::Run(action) | Action::SendPreview(action) => action.permission(),
+            Action::CreateBackup(_) | Action::AskDeleteBackup(_) | Action::DeleteBackup(_) => {
+                Permission::Backup
+            }
+            Action::AskRebuild | Action::RebuildSelected => Permission::Rebuild,
+            Action::ToggleImageAutoUpdate | Action::RefreshImage | Action::PublishSelected => {
+                Permission::Images
+            }
+            _ => return None,
+        })
+    }
+
+    /// Whether the screen should be redrawn as soon as this action is applied,
+    /// so the progress modal of a confirmed action appears without delay
+    pub fn redraws_immediately(&self) -> bool {
+        matches!(self, Action::Confirm(_) | Action::SendPreview(_))
+    }
+}
+
+/// Apply `action` to the app
+pub async fn update(app: &mut App, action: Action) {
+    if app.read_only && action.mutates() {
+        app.refuse_read_only();
+        return;
+    }
+    if let Some(permission) = action.permission() {
+        if !app.permits(permission) {
+            return;
+        }
+    }
+
+    // Undo and redo restore views rather than leaving one to undo
+    let before = (!matches!(action, Action::Undo | Action::Redo)).then(|| app.view_snapshot());
+    dispatch(app, action).await;
+    if let Some(before) = before {
+        app.record_view_change(before);
+    }
+}
+
+async fn dispatch(app: &mut App, action: Action) {
+    match action {
+        Action::Quit => app.should_quit = true,
+        Action::ToggleDebug => app.show_debug = !app.show_debug,
+        Action::TogglePerf => app.toggle_perf(),
+        Action::Resize { log_page } => app.handle_resize(log_page),
+
+        Action::SelectNext => app.next().await,
+        Action::SelectPrevious => app.previous().await,
+        Action::OpenMenu(CommandMenu::Container) => {
+            // The container menu needs a container to act on
+            if app.get_selected_container().await.is_some() {
+                app.show_command_menu(CommandMenu::Container);
+            }
+        }
+        Action::OpenMenu(menu) => app.show_command_menu(menu),
+        Action::MenuNext(items) => app.menu_next(items),
+        Action::MenuPrevious(items) => app.menu_previous(items),
+        Action::CloseView => app.input_mode = InputMode::Normal,
+        Action::Refresh => {
+            app.input_mode = InputMode::Normal;
+            app.show_info("Refreshing container list...".to_string(), true);
+            let _ = app.refresh_containers().await;
+        }
+        Action::ReloadLxd => {
+            app.input_mode = InputMode::Normal;
+            app.ensure_lxd_and_refresh().await;
+        }
+        Action::ControlService => {
+            app.input_mode = InputMode::Normal;
+            app.control_service();
+        }
+        Action::ExportInventory => {
+            app.input_mode = InputMode::Normal;
+            app.export_inventory().await;
+        }
+        Action::ExportUsage => {
+            app.input_mode = InputMode::Normal;
+            app.export_usage().await;
+        }
+        Action::CheckDrift => app.start_drift_check(),
+        Action::ToggleSidebar => {
+            app.input_mode = InputMode::Normal;
+            app.show_operation_sidebar = !app.show_operation_sidebar;
+        }
+        Action::ToggleEvents => app.toggle_events(),
+        Action::ToggleGroupByTag => app.toggle_group_by_tag(),
+        Action::ToggleHighContrast => app.toggle_high_contrast(),
+        Action::SwitchWorkspace(number) => app.switch_workspace(number),
+        Action::Undo => app.undo_view(),
+        Action::Redo => app.redo_view(),
+        Action::FocusOperations => app.focus_operations(),
+        Action::OperationNext => app.select_operation(true),
+        Action::OperationPrevious => app.select_operation(false),
+        Action::FilterOperations => {
+            app.arrange_operations(app.operation_filter.next(), app.operation_grouping)
+        }
+        Action::GroupOperations => {
+            app.arrange_operations(app.operation_filter, app.operation_grouping.next())
+        }
+        Action::ClearFinishedOperations => app.clear_finished_operations(),
+        Action::ShowOperationDetails => {
+            if let Some(operation) = app.selected_operation() {
+                app.input_mode = InputMode::OperationDetails {
+                    id: operation.id.clone(),
+                    scroll: 0,
+                };
+            }
+        }
+        Action::RetryOperation => app.retry_selected_operation().await,
+        Action::ScrollOperationDetails(delta) => {
+            if let InputMode::OperationDetails { scroll, .. } = &mut app.input_mode {
+                *scroll = (*scroll as i32 + delta).max(0) as u16;
+            }
+        }
+        Action::ToggleAllRemotes => {
+            app.input_mode = InputMode::Normal;
+            app.toggle_all_remotes().await;
+        }
+        Action::TogglePreviewRequests => {
+            app.input_mode = InputMode::Normal;
+            app.toggle_preview_requests();
+        }
+        Action::ShowHelp => app.show_help(),
+        Action::StartTour => app.start_tour(),
+        Action::TourNext => app.next_tour_step(),
+        Action::TourPrevious => app.previous_tour_step(),
+        Action::EndTour => app.end_tour(),
+        Action::OpenLog => app.open_log(),
+        Action::NewContainer => {
+            app.start_new_container_wizard();
+            app.load_image_aliases().await;
+        }
+        Action::OpenCommandLine => app.start_command_line(),
+
+        Action::StartSelected => {
+            app.input_mode = InputMode::Normal;
+            app.start_selected().await;
+        }
+        Action::StopSelected => {
+            app.input_mode = InputMode::Normal;
+            app.stop_selected().await;
+        }
+        Action::RestartSelected => {
+            app.input_mode = InputMode::Normal;
+            app.restart_selected().await;
+        }
+        Action::DeleteSelected => {
+            app.input_mode = InputMode::Normal;
+            app.delete_selected().await;
+        }
+        Action::QuickDelete => app.quick_delete().await,
+        Action::ToggleSelected => {
+            app.input_mode = InputMode::Normal;
+            if let Some(container) = app.get_selected_container().await {
+                if container.status == "Running" {
+                    if app.permits(Permission::Stop) {
+                        app.stop_selected().await;
+                    }
+                } else if app.permits(Permission::Start) {
+                    app.start_selected().await;
+                }
+            }
+        }
+        Action::CloneSelected => {
+            app.input_mode = InputMode::Normal;
+            app.start_clone().await;
+        }
+        Action::RefreshCopySelected => {
+            app.input_mode = InputMode::Normal;
+            app.start_refresh_copy().await;
+        }
+        Action::OpenBackups => {
+            app.input_mode = InputMode::Normal;
+            app.open_backups().await;
+        }
+        Action::EditTags => {
+            app.input_mode = InputMode::Normal;
+            app.start_edit_tags().await;
+        }
+        Action::EditConfig => {
+            app.input_mode = InputMode::Normal;
+            app.start_edit_config().await;
+        }
+        Action::EditNotes => {
+            app.input_mode = InputMode::Normal;
+            app.start_edit_notes().await;
+        }
+        Action::ExecSelected => {
+            app.input_mode = InputMode::Normal;
+            app.exec_selected().await;
+        }
+        Action::SshSelected => {
+            app.input_mode = InputMode::Normal;
+            app.ssh_selected().await;
+        }
+        Action::HostShell => app.open_host_shell(),
+        Action::CopyEditorRemote => {
+            app.input_mode = InputMode::Normal;
+            app.copy_editor_remote().await;
+        }
+        Action::AttachMedia => {
+            app.input_mode = InputMode::Normal;
+            app.start_attach_media().await;
+        }
+        Action::ToggleMediaBoot => {
+            app.input_mode = InputMode::Normal;
+            app.toggle_media_boot().await;
+        }
+        Action::DetachMedia => {
+            app.input_mode = InputMode::Normal;
+            app.detach_media().await;
+        }
+        Action::WatchSelected => {
+            app.input_mode = InputMode::Normal;
+            app.open_watch().await;
+        }
+        Action::FollowConsole => {
+            app.input_mode = InputMode::Normal;
+            app.open_console().await;
+        }
+        Action::ShowCrashLog => app.open_crash_log().await,
+        Action::OpenTop => app.open_top().await,
+        Action::OpenDashboard => app.open_dashboard().await,
+        Action::CloseDashboard => app.close_dashboard(),
+        Action::OpenBootOrder => app.open_boot_order().await,
+        Action::OpenAudit => app.open_audit().await,
+        Action::OpenZones => app.open_zones().await,
+        Action::OpenImageStore => app.open_image_store().await,
+        Action::OpenCapacity => app.open_capacity().await,
+        Action::OpenPools => app.open_pools().await,
+        Action::OpenTemplates => app.open_templates().await,
+        Action::OpenVmSettings => {
+            app.input_mode = InputMode::Normal;
+            app.open_vm_settings().await;
+        }
+        Action::OpenIdmap => {
+            app.input_mode = InputMode::Normal;
+            app.open_idmap().await;
+        }
+        Action::OpenRecreate => {
+            app.input_mode = InputMode::Normal;
+            app.open_recreate().await;
+        }
+        Action::SkipPowerSchedule => {
+            app.input_mode = InputMode::Normal;
+            app.skip_power_schedule().await;
+        }
+        Action::HoldPowerSchedule => {
+            app.input_mode = InputMode::Normal;
+            app.hold_power_schedule().await;
+        }
+        Action::PublishSelected => {
+            app.input_mode = InputMode::Normal;
+            app.start_publish().await;
+        }
+        Action::ToggleCompareMark => app.toggle_compare_mark().await,
+        Action::CompareMarked => app.open_compare().await,
+        Action::TogglePick => app.toggle_pick().await,
+        Action::ClearPicks => app.picked.clear(),
+        Action::OpenProfiles => app.open_profiles().await,
+        Action::OpenQuick => app.open_quick().await,
+        Action::SearchConfigKey => app.start_key_search(),
+        Action::RunCustom(index) => {
+            app.input_mode = InputMode::Normal;
+            app.run_custom_action(index).await;
+        }
+
+        Action::Request(action) => {
+            app.input_mode = InputMode::Normal;
+            let message = format!("{} container '{}'?", action.verb(), action.container_name());
+            app.request_action(message, action).await;
+        }
+        Action::Run(action) => {
+            app.input_mode = InputMode::Normal;
+            app.run_or_preview(action).await;
+        }
+        Action::SetFilter(filter) => app.set_filter(filter),
+        Action::SetSort(sort) => app.set_sort(sort),
+        Action::OpenWorkspace(name) => app.open_workspace(&name),
+        Action::SaveWorkspace(name) => app.save_workspace(name),
+        Action::SelectContainer(name) => {
+            app.select_container(&name).await;
+        }
+
+        Action::Confirm(action) => {
+            app.input_buffer.clear();
+            app.run_or_preview(PendingAction::Container(action)).await;
+        }
+        Action::CancelDialog => {
+            app.input_buffer.clear();
+            app.cancel_dialog();
+        }
+        Action::RemoveProtection(container) => {
+            app.input_buffer.clear();
+            app.remove_protection(&container).await;
+        }
+        Action::SendPreview(action) => {
+            app.pending_action = None;
+            app.run_action(action).await;
+        }
+        Action::CancelPreview(action) => app.cancel_preview(&action),
+
+        Action::EditInput(edit) => app.input_buffer.apply(edit),
+        Action::RecallInput { forward } => app.recall_input(forward),
+        Action::SubmitInput(callback) => submit_input(app, callback).await,
+        Action::CancelInput => {
+            if let InputMode::Input {
+                callback_action: InputCallback::AddRemoteToken,
+                ..
+            } = app.input_mode
+            {
+                app.pending_remote = None;
+            }
+            if let Some(history) = &mut app.prompt_history {
+                history.reset();
+            }
+            app.cancel_input();
+        }
+
+        Action::AddRemote => app.start_add_remote(),
+        Action::AuthWithToken => app.prompt_trust_token(),
+        Action::AuthWithOidc => app.add_remote_with_oidc().await,
+        Action::AuthWithCertificate => app.add_remote_with_certificate().await,
+        Action::CancelAddRemote => app.cancel_add_remote(),
+
+        Action::WizardNameDone => {
+            app.wizard_data.name = app.input_buffer.take();
+            app.input_mode = InputMode::Wizard(WizardState::SelectImage);
+        }
+        Action::WizardUseDefaults => {
+            app.wizard_data.name = app.input_buffer.take();
+            app.input_mode = InputMode::Wizard(WizardState::Confirm);
+        }
+        Action::WizardBackToName => {
+            app.input_buffer.set(&app.wizard_data.name);
+            app.input_mode = InputMode::Wizard(WizardState::Name);
+        }
+        Action::WizardGoTo(state) => app.input_mode = InputMode::Wizard(state),
+        Action::WizardNextImage => app.next_wizard_image(),
+        Action::WizardPreviousImage => app.previous_wizard_image(),
+        Action::WizardImageChar(c) => app.wizard_image_char(c),
+        Action::WizardImagePaste(text) => app.wizard_image_paste(&text),
+        Action::WizardImageBackspace => app.wizard_image_backspace(),
+        Action::WizardImageDone => app.choose_wizard_image(),
+        Action::WizardSetVm(is_vm) => app.wizard_data.is_vm = is_vm,
+        Action::WizardTypeDone => app.finish_wizard_type(),
+        Action::WizardMediaDone => app.choose_wizard_media(),
+        Action::WizardBackToType => {
+            app.input_buffer.clear();
+            app.input_mode = InputMode::Wizard(WizardState::SelectType);
+        }
+        Action::WizardBackFromConfirm => app.wizard_back_from_confirm(),
+        Action::CreateContainer => app.run_checking_space(PendingAction::CreateContainer).await,
+
+        Action::CloneFieldNext | Action::CloneFieldPrevious => {
+            if let InputMode::CloneOptions(form) = &mut app.input_mode {
+                form.move_field(matches!(action, Action::CloneFieldNext));
+            }
+        }
+        Action::CloneToggle => {
+            if let InputMode::CloneOptions(form) = &mut app.input_mode {
+                form.toggle();
+            }
+        }
+        Action::CloneCycle(forward) => {
+            if let InputMode::CloneOptions(form) = &mut app.input_mode {
+                form.cycle(forward);
+            }
+        }
+        Action::SubmitClone => {
+            if let InputMode::CloneOptions(form) =
+                std::mem::replace(&mut app.input_mode, InputMode::Normal)
+            {
+                app.run_checking_space(PendingAction::Clone {
+                    source: form.source,
+                    destination: form.destination,
+                    options: form.options,
+                })
+                .await;
+            }
+        }
+
+        Action::BackupNext => app.backup_next(),
+        Action::BackupPrevious => app.backup_previous(),
+        Action::CreateBackup(container) => {
+            app.run_or_preview(PendingAction::CreateBackup(container))
+                .await
+        }
+        Action::AskDeleteBackup(container) => {
+            app.input_mode = InputMode::Backups {
+                container,
+                confirm_delete: true,
+            };
+        }
+        Action::DeleteBackup(container) => {
+            if let Some(backup) = app.backups.get(app.backup_selected) {
+                let backup = backup.name.clone();
+                app.run_or_preview(PendingAction::DeleteBackup { container, backup })
+                    .await;
+            }
+            cancel_backup_delete(app);
+        }
+        Action::CancelDeleteBackup => cancel_backup_delete(app),
+        Action::DownloadBackup(container) => app.download_selected_backup(&container).await,
+        Action::ReloadBackups(container) => {
+            app.reload_backups(&container).await;
+        }
+
+        Action::ScrollHelp(delta) => app.scroll_help(delta),
+        Action::HelpTop => app.help_scroll = 0,
+        Action::HelpBottom => app.scroll_help(i32::MAX / 2),
+        Action::StartHelpSearch
+        | Action::HelpSearchChar(_)
+        | Action::HelpSearchPaste(_)
+        | Action::HelpSearchBackspace
+        | Action::EndHelpSearch
+        | Action::CancelHelpSearch
+        | Action::ClearHelpQuery => edit_help_search(app, action),
+
+        Action::ScrollLog { delta, page } => app.scroll_log(delta, page),
+        Action::LogTop => app.log_scroll = Some(0),
+        Action::LogBottom => app.log_scroll = None,
+
+        Action::ClearWatchEvents => {
+            if let Some(watch) = &mut app.watch {
+                watch.events.clear();
+            }
+        }
+
+        Action::ScrollCompare(delta) => {
+            if let Some(compare) = &mut app.compare {
+                compare.scroll_by(delta);
+            }
+        }
+        Action::ToggleCompareDifferences => {
+            if let Some(compare) = &mut app.compare {
+                compare.toggle_differences();
+            }
+        }
+
+        Action::RankTopBy(rank_by) => {
+            if let Some(top) = &mut app.top {
+                top.rank_by = rank_by;
+            }
+        }
+        Action::ResizeTopWindow(delta) => {
+            if let Some(top) = &mut app.top {
+                top.resize_window(delta);
+            }
+        }
+
+        Action::BootOrderNext | Action::BootOrderPrevious => {
+            if let Some(order) = &mut app.boot_order {
+                order.select(matches!(action, Action::BootOrderNext));
+            }
+        }
+        Action::CycleAutostart => app.cycle_autostart().await,
+        Action::EditBootPriority => app.start_edit_boot_setting(boot::PRIORITY_KEY),
+        Action::EditBootDelay => app.start_edit_boot_setting(boot::DELAY_KEY),
+        Action::CycleBootSort => {
+            if let Some(order) = &mut app.boot_order {
+                order.sort_by(order.sort.next());
+            }
+        }
+        Action::ReloadBootOrder => app.reload_boot_order().await,
+
+        Action::AuditNext | Action::AuditPrevious => {
+            if let Some(audit) = &mut app.audit {
+                audit.select(matches!(action, Action::AuditNext));
+            }
+        }
+        Action::AskRebuild => app.ask_rebuild(),
+        Action::RebuildSelected => app.rebuild_selected().await,
+        Action::CancelRebuild => {
+            app.input_mode = InputMode::Audit {
+                confirm_rebuild: false,
+            };
+        }
+        Action::ReloadAudit => app.reload_audit().await,
+
+        Action::ZoneNext | Action::ZonePrevious => {
+            if let Some(view) = &mut app.zones {
+                view.select(matches!(action, Action::ZoneNext));
+            }
+        }
+        Action::NewZone => app.start_new_zone(),
+        Action::NewZoneRecord => app.start_new_zone_record(),
+        Action::EditZoneRecord => app.start_edit_zone_record(),
+        Action::AskDeleteZoneEntry => app.ask_delete_zone_entry(),
+        Action::DeleteZoneEntry => app.delete_selected_zone_entry().await,
+        Action::CancelZoneDelete => {
+            app.input_mode = InputMode::Zones {
+                confirm_delete: false,
+            };
+        }
+        Action::ReloadZones => app.reload_zones().await,
+
+        Action::ImageNext | Action::ImagePrevious => {
+            if let Some(view) = &mut app.image_store {
+                view.select(matches!(action, Action::ImageNext));
+            }
+        }
+        Action::ToggleImageAutoUpdate => app.toggle_image_auto_update().await,
+        Action::RefreshImage => app.refresh_selected_image().await,
+        Action::ReloadImageStore => app.reload_image_store().await,
+
+        Action::CapacityNext | Action::CapacityPrevious => {
+            if let Some(view) = &mut app.capacity {
+                view.select(matches!(action, Action::CapacityNext));
+            }
+        }
+        Action::ReloadCapacity => app.reload_capacity().await,
+
+        Action::PoolNext | Action::PoolPrevious => {
+            if let Some(view) = &mut app.pools {
+                view.select(matches!(action, Action::PoolNext));
+            }
+        }
+        Action::AskScrubPool => app.ask_scrub_pool(),
+        Action::ScrubPool => app.scrub_pool(),
+        Action::CancelScrubPool => {
+            app.input_mode = InputMode::Pools {
+                confirm_scrub: false,
+            };
+        }
+        Action::ReloadPools => app.reload_pools().await,
+
+        Action::TemplateNext | Action::TemplatePrevious => {
+            if let Some(view) = &mut app.templates {
+                view.select(matches!(action, Action::TemplateNext));
+            }
+        }
+        Action::EditTemplate => app.start_edit_template().await,
+        Action::EditMetadata => app.start_edit_metadata(),
+        Action::NewTemplate => app.start_new_template(),
+        Action::ReloadTemplates => {
+            if let Some(view) = &mut app.templates {
+                view.status = None;
+            }
+            app.reload_templates().await;
+        }
+
+        Action::VmSettingNext | Action::VmSettingPrevious => {
+            if let Some(view) = &mut app.vm_settings {
+                view.select(matches!(action, Action::VmSettingNext));
+            }
+        }
+        Action::ChangeVmSetting => app.change_vm_setting().await,
+        Action::ReloadVmSettings => {
+            if let Some(view) = &mut app.vm_settings {
+                view.status = None;
+            }
+            app.reload_vm_settings().await;
+        }
+
+        Action::IdmapNext | Action::IdmapPrevious => {
+            if let Some(view) = &mut app.idmap {
+                view.select(matches!(action, Action::IdmapNext));
+            }
+        }
+        Action::AddIdmapLine => app.start_idmap_line(false),
+        Action::EditIdmapLine => app.start_idmap_line(true),
+        Action::DeleteIdmapLine => app.delete_idmap_line().await,
+        Action::ToggleIdmapIsolated => app.toggle_idmap_isolated().await,
+        Action::ToggleDiskShift => app.toggle_disk_shift().await,
+        Action::ProfileNext | Action::ProfilePrevious => {
+            if let Some(view) = &mut app.profile_batch {
+                view.select(matches!(action, Action::ProfileNext));
+            }
+        }
+        Action::AddProfile => app.batch_profile(true).await,
+        Action::RemoveProfile => app.batch_profile(false).await,
+        Action::ReloadProfiles => {
+            if let Some(view) = &mut app.profile_batch {
+                view.status = None;
+            }
+            app.reload_profile_batch().await;
+        }
+        Action::QuickNext | Action::QuickPrevious => {
+            if let Some(view) = &mut app.quick {
+                view.select(matches!(action, Action::QuickNext));
+            }
+        }
+        Action::ScrollQuick(delta) => {
+            if let Some(view) = &mut app.quick {
+                view.scroll_by(delta);
+            }
+        }
+        Action::AskQuick(index) => app.ask_quick(index).await,
+        Action::RunQuick(index) => app.run_quick(index),
+        Action::CancelQuick => app.input_mode = InputMode::Quick { confirm: None },
+        Action::AskQuickCommand => app.ask_quick_command(),
+        Action::RerunQuick => app.rerun_quick(),
+        Action::StopQuick => app.stop_quick(),
+        Action::ToggleQuickOutput => {
+            if let Some(view) = &mut app.quick {
+                view.expanded = !view.expanded;
+            }
+        }
+        Action::KeySearchNext | Action::KeySearchPrevious => {
+            if let Some(view) = &mut app.key_search {
+                view.select(matches!(action, Action::KeySearchNext));
+            }
+        }
+        Action::ShowKeySearchHit => app.show_key_search_hit().await,
+        Action::ReloadKeySearch => app.reload_key_search().await,
+        Action::DriftNext | Action::DriftPrevious => {
+            if let Some(view) = &mut app.drift {
+                view.select(matches!(action, Action::DriftNext));
+            }
+        }
+        Action::ReloadDrift => app.reload_drift().await,
+        Action::RenameRecreate => app.start_recreate_field(false),
+        Action::ChangeRecreateImage => app.start_recreate_field(true),
+        Action::ConfirmRecreate => app.confirm_recreate().await,
+        Action::ReloadIdmap => {
+            if let Some(view) = &mut app.idmap {
+                view.status = None;
+            }
+            app.reload_idmap().await;
+        }
+
+        Action::ScrollConsole { .. }
+        | Action::ConsoleTop
+        | Action::ConsoleBottom
+        | Action::ToggleConsolePause
+        | Action::StartConsoleSearch
+        | Action::ConsoleSearchChar(_)
+        | Action::ConsoleSearchPaste(_)
+        | Action::ConsoleSearchBackspace
+        | Action::EndConsoleSearch { .. }
+        | Action::CancelConsoleSearch
+        | Action::ConsoleFind { .. } => edit_console(app, action),
+
+        Action::RunInBackground => app.run_in_background(),
+        Action::AskCancelProgress => app.confirm_cancel_progress(true),
+        Action::KeepProgress => app.confirm_cancel_progress(false),
+        Action::CancelProgress(operation_id) => app.cancel_progress(&operation_id).await,
+        Action::CopyErrorReport(report) => app.copy_error_report(&report),
+        Action::SaveErrorReport(report) => app.save_error_report(&report),
+    }
+}
+
+/// Act on the text entered at a prompt
+async fn submit_input(app: &mut App, callback: InputCallback) {
+    let text = app.input_buffer.as_str().to_string();
+    app.record_input(&callback, &text);
+    match callback {
+        InputCallback::CloneContainer(source) => {
+            app.input_mode = InputMode::Normal;
+            app.open_clone_options(source, text).await;
+        }
+        InputCallback::RefreshCopy(source) => {
+            app.input_mode = InputMode::Normal;
+            app.run_or_preview(PendingAction::RefreshCopy {
+                source,
+                destination: text,
+            })
+            .await;
+        }
+        InputCallback::SetTags(container) => {
+            app.input_mode = InputMode::Normal;
+            app.run_or_preview(PendingAction::SetTags {
+                container,
+                tags: tags::parse(&text),
+            })
+            .await;
+        }
+        InputCallback::AttachMedia(container) => {
+            app.input_mode = InputMode::Normal;
+            app.submit_media(container, &text).await;
+        }
+        InputCallback::SetVmSetting { container, knob } => {
+            app.submit_vm_setting(container, knob, &text).await;
+        }
+        InputCallback::RecreateName => app.submit_recreate_field(false, &text),
+        InputCallback::RecreateImage => app.submit_recreate_field(true, &text),
+        InputCallback::SearchConfigKey => app.submit_key_search(&text).await,
+        InputCallback::DriftFile => app.submit_drift_file(&text).await,
+        InputCallback::QuickCommand => app.submit_quick_command(&text),
+        InputCallback::IdmapLine { container, index } => {
+            app.submit_idmap_line(container, index, &text).await;
+        }
+        InputCallback::SetBootConfig { container, key } => {
+            app.submit_boot_setting(container, key, &text).await;
+        }
+        InputCallback::NewTemplate(container) => app.create_template(&container, &text),
+        callback @ (InputCallback::NewZone
+        | InputCallback::NewZoneRecord(_)
+        | InputCallback::EditZoneRecord { .. }) => app.submit_zone_input(callback, &text).await,
+        InputCallback::CreateContainer => {
+            // This would be handled in wizard flow
+        }
+        InputCallback::AddRemoteName => app.prompt_remote_url(text),
+        InputCallback::AddRemoteUrl(name) => app.begin_add_remote(name, text).await,
+        InputCallback::AddRemoteToken => app.add_remote_with_token(text).await,
+        InputCallback::Command => {
+            app.input_mode = InputMode::Normal;
+            app.input_buffer.clear();
+            match command::parse(&text, Source::CommandLine) {
+                Ok(action) => Box::pin(update(app, action)).await,
+                Err(e) => app.show_error(
+                    "Invalid command".to_string(),
+                    e.to_string(),
+                    vec!["Press ? to list the available commands".to_string()],
+                ),
+            }
+        }
+    }
+}
+
+fn cancel_backup_delete(app: &mut App) {
+    if let InputMode::Backups { confirm_delete, .. } = &mut app.input_mode {
+        *confirm_delete = false;
+    }
+}
+
+fn edit_help_search(app: &mut App, action: Action) {
+    let InputMode::Help { query, searching } = &mut app.input_mode else {
+        return;
+    };
+
+    match action {
+        Action::StartHelpSearch => {
+            *searching = true;
+            return;
+        }
+        Action::HelpSearchChar(c) => query.push(c),
+        Action::HelpSearchPaste(text) => query.push_str(&text),
+        Action::HelpSearchBackspace => {
+            query.pop();
+        }
+        Action::EndHelpSearch => *searching = false,
+        Action::CancelHelpSearch => {
+            query.clear();
+            *searching = false;
+        }
+        Action::ClearHelpQuery => query.clear(),
+        _ => return,
+    }
+    app.help_scroll = 0;
+}
+
+fn edit_console(app: &mut App, action: Action) {
+    let Some(console) = &mut app.console else {
+        return;
+    };
+
+    match action {
+        Action::ScrollConsole { delta, page } => console.scroll_by(delta, page as usize),
+        Action::ConsoleTop => console.scroll = Some(0),
+        Action::ConsoleBottom => console.scroll = None,
+        Action::ToggleConsolePause => console.paused = !console.paused,
+        Action::StartConsoleSearch => {
+            console.query.clear();
+            console.searching = true;
+        }
+        Action::ConsoleSearchChar(c) => console.query.push(c),
+        Action::ConsoleSearchPaste(text) => console.query.push_str(&text),
+        Action::ConsoleSearchBackspace => {
+            console.query.pop();
+        }
+        Action::EndConsoleSearch { page } => {
+            console.searching = false;
+            console.find(true, page as usize);
+        }
+        Action::CancelConsoleSearch => {
+            console.query.clear();
+            console.searching = false;
+        }
+        Action::ConsoleFind { forward, page } => {
+            console.find(forward, page as usize);
+        }
+        _ => {}
+    }
+}
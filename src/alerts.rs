@@ -0,0 +1,179 @@
+//! Usage alerts
+//!
+//! Checks each refreshed container list against the thresholds in the
+//! `[alerts]` config section. An alert stays raised while its condition
+//! holds: memory above the threshold, or a container that stopped without
+//! LXTUI stopping it and has not been started again.
+
+use crate::config::AlertsConfig;
+use crate::lxc::Container;
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use std::collections::{BTreeMap, HashMap};
+use tokio::time::{Duration, Instant};
+
+/// How long a stop requested from LXTUI is expected to show up in the list
+pub const EXPECTED_STOP_WINDOW: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+    Memory,
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub message: String,
+    pub since: DateTime<Local>,
+}
+
+#[derive(Debug, Default)]
+pub struct Alerts {
+    config: AlertsConfig,
+    active: BTreeMap<String, Alert>,   // By qualified container name
+    statuses: HashMap<String, String>, // As of the previous check
+    expected_stops: HashMap<String, Instant>,
+}
+
+impl Alerts {
+    pub fn new(config: AlertsConfig) -> Self {
+        Alerts {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Note that LXTUI is about to stop, restart or delete `container`, so
+    /// seeing it stopped is no surprise
+    pub fn expect_stop(&mut self, container: &str) {
+        self.expected_stops
+            .insert(container.to_string(), Instant::now());
+    }
+
+    /// Check a refreshed container list. Returns the alerts newly raised.
+    pub fn check(&mut self, containers: &[Container], now: Instant) -> Vec<(String, Alert)> {
+        self.expected_stops
+            .retain(|_, at| now.duration_since(*at) < EXPECTED_STOP_WINDOW);
+
+        let mut raised = Vec::new();
+        let mut active = BTreeMap::new();
+        let mut statuses = HashMap::new();
+        for container in containers {
+            let name = container.qualified_name();
+            let previous = self.statuses.get(&name).map(String::as_str);
+            let alert = self
+                .memory_alert(container)
+                .or_else(|| self.stop_alert(&name, previous, &container.status));
+            statuses.insert(name.clone(), container.status.clone());
+
+            let Some(alert) = alert else {
+                continue;
+            };
+            match self.active.remove(&name) {
+                // Still the same problem; keep when it started
+                Some(old) if old.kind == alert.kind => {
+                    active.insert(
+                        name,
+                        Alert {
+                            since: old.since,
+                            ..alert
+                        },
+                    );
+                }
+                _ => {
+                    info!("Alert for {}: {}", name, alert.message);
+                    raised.push((name.clone(), alert.clone()));
+                    active.insert(name, alert);
+                }
+            }
+        }
+
+        self.active = active;
+        self.statuses = statuses;
+        if self.config.notify {
+            for (name, alert) in &raised {
+                notify(name, &alert.message);
+            }
+        }
+        raised
+    }
+
+    fn memory_alert(&self, container: &Container) -> Option<Alert> {
+        let threshold = self.config.memory_percent?;
+        let percent = container.memory_usage? as f64 * 100.0 / container.memory_limit? as f64;
+        (percent >= f64::from(threshold)).then(|| Alert {
+            kind: AlertKind::Memory,
+            message: format!("memory at {:.0}% of limit", percent),
+            since: Local::now(),
+        })
+    }
+
+    fn stop_alert(&self, name: &str, previous: Option<&str>, status: &str) -> Option<Alert> {
+        if !self.config.unexpected_stop || status != "Stopped" {
+            return None;
+        }
+        match self.active.get(name) {
+            // Stays raised until the container runs again
+            Some(alert) if alert.kind == AlertKind::Stopped => Some(alert.clone()),
+            _ if previous == Some("Running") && !self.expected_stops.contains_key(name) => {
+                Some(Alert {
+                    kind: AlertKind::Stopped,
+                    message: "stopped unexpectedly".to_string(),
+                    since: Local::now(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, container: &str) -> Option<&Alert> {
+        self.active.get(container)
+    }
+
+    pub fn count(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Raised alerts, by qualified container name
+    pub fn active(&self) -> impl Iterator<Item = (&String, &Alert)> {
+        self.active.iter()
+    }
+}
+
+/// Show a desktop notification; failures only reach the log
+fn notify(container: &str, message: &str) {
+    let result = std::process::Command::new("notify-send")
+        .args(["--app-name=LXTUI", &format!("{}: {}", container, message)])
+        .spawn();
+    if let Err(e) = result {
+        warn!("Failed to send desktop notification: {}", e);
+    }
+}
+
+/// Bytes in an LXD size such as "512MiB", "2GB" or "1073741824".
+/// Percentages of host memory give None.
+pub fn parse_size(size: &str) -> Option<i64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: f64 = match unit.trim() {
+        "" | "B" => 1.0,
+        "kB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier) as i64)
+}
+
+#[cfg(test)]
+mod tests;
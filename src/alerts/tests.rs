@@ -0,0 +1,98 @@
+//! Tests of raising and clearing usage alerts
+
+use super::{parse_size, AlertKind, Alerts};
+use crate::command::tests;
+use crate::config::AlertsConfig;
+use crate::lxc::Container;
+use tokio::time::{Duration, Instant};
+
+fn container(name: &str, status: &str, memory_mib: Option<i64>) -> Container {
+    Container {
+        memory_usage: memory_mib.map(|mib| mib << 20),
+        memory_limit: Some(1 << 30),
+        ..tests::container(name, status, "container")
+    }
+}
+
+fn alerts() -> Alerts {
+    Alerts::new(AlertsConfig {
+        memory_percent: Some(90),
+        unexpected_stop: true,
+        notify: false,
+    })
+}
+
+#[test]
+fn memory_over_the_threshold() {
+    let mut alerts = alerts();
+    let now = Instant::now();
+
+    let raised = alerts.check(&[container("web1", "Running", Some(950))], now);
+    assert_eq!(raised.len(), 1);
+    assert_eq!(raised[0].1.kind, AlertKind::Memory);
+    assert_eq!(raised[0].1.message, "memory at 93% of limit");
+
+    // Only raised once while it lasts
+    let raised = alerts.check(&[container("web1", "Running", Some(960))], now);
+    assert!(raised.is_empty());
+    assert_eq!(alerts.count(), 1);
+
+    alerts.check(&[container("web1", "Running", Some(500))], now);
+    assert!(alerts.get("web1").is_none());
+}
+
+#[test]
+fn unexpected_stop_lasts_until_started() {
+    let mut alerts = alerts();
+    let now = Instant::now();
+
+    alerts.check(&[container("web1", "Running", None)], now);
+    let raised = alerts.check(&[container("web1", "Stopped", None)], now);
+    assert_eq!(raised[0].1.kind, AlertKind::Stopped);
+
+    alerts.check(&[container("web1", "Stopped", None)], now);
+    assert_eq!(alerts.count(), 1);
+
+    alerts.check(&[container("web1", "Running", None)], now);
+    assert_eq!(alerts.count(), 0);
+}
+
+#[test]
+fn stops_from_lxtui_are_expected() {
+    let mut alerts = alerts();
+    let now = Instant::now();
+
+    alerts.check(&[container("web1", "Running", None)], now);
+    alerts.expect_stop("web1");
+    assert!(alerts
+        .check(&[container("web1", "Stopped", None)], now)
+        .is_empty());
+
+    // The expectation runs out
+    alerts.check(&[container("web1", "Running", None)], now);
+    let later = now + Duration::from_secs(600);
+    assert_eq!(
+        alerts
+            .check(&[container("web1", "Stopped", None)], later)
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn nothing_without_thresholds() {
+    let mut alerts = Alerts::default();
+    let now = Instant::now();
+    alerts.check(&[container("web1", "Running", Some(1000))], now);
+    alerts.check(&[container("web1", "Stopped", None)], now);
+    assert_eq!(alerts.count(), 0);
+}
+
+#[test]
+fn parses_lxd_sizes() {
+    assert_eq!(parse_size("512MiB"), Some(512 << 20));
+    assert_eq!(parse_size("2GB"), Some(2_000_000_000));
+    assert_eq!(parse_size("1.5GiB"), Some(3 << 29));
+    assert_eq!(parse_size("4096"), Some(4096));
+    assert_eq!(parse_size("50%"), None);
+}
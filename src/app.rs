@@ -3,21 +3,109 @@
 //! This module contains the core application state management and business logic
 //! for LXTUI. It handles container operations, UI state, and background tasks.
 
-use crate::lxc::{Container, Image, LxcClient, Operation};
-use anyhow::Result;
+use crate::accent::Accents;
+use crate::action::Action;
+use crate::alerts::Alerts;
+use crate::audit::{Audit, Freshness};
+use crate::auth::{self, AuthError, OidcTokens, TrustToken};
+use crate::batch::{ProfileBatch, ProfileChange};
+use crate::boot::{self, BootOrder};
+use crate::capacity::Capacity;
+use crate::clone::{CloneForm, CloneOptions};
+use crate::compare::Comparison;
+use crate::config::{
+    AuthType, Config, ConfirmPolicy, Confirmations, CreateDefaults, CustomAction, DisplayConfig,
+    ImagesConfig, InventoryConfig, Permission, PermissionsConfig, ProbesConfig, QuickAction,
+    QuickConfig, RemoteConfig, ScheduleConfig, ScheduledAction, ServerConfig, SpaceConfig,
+    SpaceGuard, TourConfig, UsageConfig,
+};
+use crate::console::ConsoleView;
+use crate::convert::Conversion;
+use crate::crash::{Capture, Crashes};
+use crate::dashboard::Dashboard;
+use crate::drift::{self, DriftView};
+use crate::edit::{ConfigEdit, EditKind, InstanceConfig};
+use crate::estimate::{self, Estimate};
+use crate::events::{EventFeed, LifecycleEvent};
+use crate::features::{Feature, Unavailable};
+use crate::field::{FieldEdit, TextField};
+use crate::filter::ContainerFilter;
+use crate::flash::Flashes;
+use crate::health::Heartbeat;
+use crate::history::{History, OperationRecord, Outcome};
+use crate::hooks::{self, ExternalCommand};
+use crate::idmap::{self, IdmapChange, IdmapView};
+use crate::images::{self, ImageCatalog, ImageChoice, ImageSource};
+use crate::keymap;
+use crate::logging;
+use crate::lxc::{Backup, Container, Image, LxcClient, LxcError, Operation};
+use crate::lxd_api::{self, ApiRequest, LxdApiClient, LxdOperation, LxdZoneRecord};
+use crate::media::{self, Media, MediaChange};
+use crate::migration::Transfer;
+use crate::notes;
+use crate::notifications::{Finished, Notifier};
+use crate::poll::{Category, Poller};
+use crate::pools::PoolsView;
+use crate::power::{self, PowerOverrides};
+use crate::preflight::{self, Check};
+use crate::probe::{self, Probes};
+use crate::publish::{self, Publish};
+use crate::quick::{self, QuickView};
+use crate::recall::PromptHistory;
+use crate::report::{self, ErrorReport};
+use crate::restart::Restarts;
+use crate::schedule::Schedule;
+use crate::search::{self, KeySearch};
+use crate::server::{self, ServerDetails};
+use crate::service::{self, Service, ServiceAction, ServiceControl};
+use crate::sidebar;
+use crate::space::{self, Verdict};
+use crate::ssh;
+use crate::store::{ImageStore, StoredImage};
+use crate::tags;
+use crate::templates::{self, InstanceMetadata, TemplatesView};
+use crate::theme::{Theme, Tone};
+use crate::top::Top;
+use crate::tour::{self, Tour};
+use crate::undo::{Snapshot, UndoStack, ViewState};
+use crate::uptime::Uptime;
+use crate::usage::Ledger;
+use crate::vm::{self, Knob, VmSettings};
+use crate::watch::Watch;
+use crate::workspace::{SortKey, Workspace};
+use crate::zones::{self, Row, ZoneChange, Zones};
+use anyhow::{bail, Result};
+use chrono::{TimeDelta, Timelike};
 use log::{debug, error, info, warn};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, oneshot, RwLock};
 use tokio::task::JoinHandle;
-use tokio::time::{Duration, Instant};
+use tokio::time::{timeout, Duration, Instant};
 use uuid::Uuid;
 
 // Type for background task results
 pub type TaskResult = (String, bool, Option<String>, String); // (op_id, success, error_msg, container_name)
 
+/// Operations kept in the sidebar; older ones are dropped
+const MAX_OPERATION_HISTORY: usize = 100;
+
+/// Time between refreshes of the list, and between checks of an operation's
+/// progress, before jitter
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+const OPERATION_INTERVAL: Duration = Duration::from_millis(500);
+const IMAGE_CHECK_INTERVAL: Duration = Duration::from_secs(600);
+
+/// How long a notice stays in the title bar
+const NOTICE_FOR: Duration = Duration::from_secs(4);
+
+// Result of a scheduled backup or snapshot
+pub type ScheduleResult = (String, String, Result<(), String>); // (op_id, description, outcome)
+
 // LXD Operation Tracker
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct LxdOperationTracker {
     pub ui_operation_id: String,    // Our internal UI operation ID
     pub lxd_operation_path: String, // LXD's operation path (e.g., "/1.0/operations/uuid")
@@ -25,9 +113,47 @@ pub struct LxdOperationTracker {
     pub container_name: String,
     pub action: String, // "start", "stop", "restart", "delete"
     pub started_at: Instant,
-    pub last_checked: Instant,
+    pub next_check: Instant,
     pub status_code: i32,      // LXD status code
     pub progress: Option<i32>, // Progress percentage if available
+    pub success_message: String,
+}
+
+impl LxdOperationTracker {
+    /// The container action that started this operation
+    pub fn confirm_action(&self) -> Option<ConfirmAction> {
+        let name = self.container_name.clone();
+        match self.action.as_str() {
+            "start" => Some(ConfirmAction::StartContainer(name)),
+            "stop" => Some(ConfirmAction::StopContainer(name)),
+            "restart" => Some(ConfirmAction::RestartContainer(name)),
+            "delete" => Some(ConfirmAction::DeleteContainer(name)),
+            _ => None,
+        }
+    }
+}
+
+/// Latest and slowest duration of one kind of work
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Timing {
+    pub last: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    pub fn record(&mut self, elapsed: Duration) {
+        self.last = elapsed;
+        self.max = self.max.max(elapsed);
+    }
+}
+
+/// Timings shown in the performance HUD
+#[derive(Debug, Default)]
+pub struct PerfStats {
+    pub draw: Timing,    // Rendering one frame
+    pub tick: Timing,    // Background polling before each frame
+    pub key: Timing,     // Handling one key press
+    pub refresh: Timing, // Listing containers
 }
 
 #[derive(Debug, Clone)]
@@ -35,6 +161,7 @@ pub enum WizardState {
     Name,
     SelectImage,
     SelectType,
+    Media, // Only for an empty VM
     Confirm,
 }
 
@@ -43,21 +170,53 @@ pub struct WizardData {
     pub name: String,
     pub image: String,
     pub is_vm: bool,
+    pub media: Option<Media>,        // ISO an empty VM boots from first
+    pub media_error: Option<String>, // Why the ISO typed was refused
+    pub image_query: String,         // Typed on the image step
     pub selected_image_index: usize,
 }
 
+impl WizardData {
+    /// A wizard pre-filled from the creation defaults, so only a name is left
+    pub fn from_defaults(defaults: &CreateDefaults) -> Self {
+        WizardData {
+            image: defaults.image.clone(),
+            is_vm: defaults.vm,
+            ..WizardData::default()
+        }
+    }
+
+    /// Whether the instance starts out with no image, to install into
+    pub fn is_empty(&self) -> bool {
+        self.image == images::EMPTY
+    }
+
+    /// The step after choosing the type: the ISO for an empty VM
+    pub fn after_type(&self) -> WizardState {
+        if self.is_vm && self.is_empty() {
+            WizardState::Media
+        } else {
+            WizardState::Confirm
+        }
+    }
+}
+
 impl Default for WizardData {
     fn default() -> Self {
         WizardData {
             name: String::new(),
             image: "ubuntu:24.04".to_string(),
             is_vm: false,
+            media: None,
+            media_error: None,
+            image_query: String::new(),
             selected_image_index: 0,
         }
     }
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum ConfirmAction {
     StartContainer(String),
     StopContainer(String),
@@ -65,27 +224,181 @@ pub enum ConfirmAction {
     DeleteContainer(String),
 }
 
+impl ConfirmAction {
+    pub fn container_name(&self) -> &str {
+        match self {
+            ConfirmAction::StartContainer(name)
+            | ConfirmAction::StopContainer(name)
+            | ConfirmAction::RestartContainer(name)
+            | ConfirmAction::DeleteContainer(name) => name,
+        }
+    }
+
+    /// Capitalised verb for messages, e.g. "Start"
+    pub fn verb(&self) -> &'static str {
+        match self {
+            ConfirmAction::StartContainer(_) => "Start",
+            ConfirmAction::StopContainer(_) => "Stop",
+            ConfirmAction::RestartContainer(_) => "Restart",
+            ConfirmAction::DeleteContainer(_) => "Delete",
+        }
+    }
+
+    pub fn permission(&self) -> Permission {
+        match self {
+            ConfirmAction::StartContainer(_) => Permission::Start,
+            ConfirmAction::StopContainer(_) => Permission::Stop,
+            ConfirmAction::RestartContainer(_) => Permission::Restart,
+            ConfirmAction::DeleteContainer(_) => Permission::Delete,
+        }
+    }
+}
+
+/// A mutating action that can be previewed as its API request before it runs
+#[derive(Debug, Clone)]
+pub enum PendingAction {
+    Container(ConfirmAction),
+    Clone {
+        source: String,
+        destination: String,
+        options: CloneOptions,
+    },
+    RefreshCopy {
+        source: String,
+        destination: String,
+    },
+    CreateContainer, // Uses the wizard data
+    CreateBackup(String),
+    DeleteBackup {
+        container: String,
+        backup: String,
+    },
+    CreateSnapshot {
+        container: String,
+        snapshot: Option<String>,
+    }, // None: server-chosen name
+    SetTags {
+        container: String,
+        tags: Vec<String>,
+    },
+    SetBootConfig {
+        container: String,
+        key: &'static str,
+        value: String, // Empty unsets the key
+    },
+    UpdateConfig {
+        container: String,
+        config: InstanceConfig,
+    },
+    ChangeMedia {
+        container: String,
+        change: MediaChange,
+        config: InstanceConfig, // With the change made
+    },
+    SetVmSetting {
+        container: String,
+        knob: Knob,
+        value: String,
+        config: InstanceConfig, // With the value set
+    },
+    ChangeIdmap {
+        container: String,
+        change: IdmapChange,
+        config: InstanceConfig, // With the change made
+    },
+    UpdateMetadata {
+        container: String,
+        metadata: InstanceMetadata,
+    },
+    SetTemplate {
+        container: String,
+        template: String,
+        content: String,
+    },
+    Rebuild {
+        container: String,
+        fingerprint: String, // Image to rebuild from
+    },
+    Recreate(Box<Conversion>), // Snapshots the source, then creates the other type
+    ChangeProfiles {
+        change: ProfileChange,
+        changes: Vec<(String, Vec<String>)>, // New profile list of each instance changed
+    },
+    ChangeZone(ZoneChange), // On the active server
+    SetImageAutoUpdate {
+        fingerprint: String,
+        description: String,
+        auto_update: bool,
+    },
+    RefreshImage {
+        fingerprint: String,
+        description: String,
+    },
+    Publish {
+        container: String,
+        publish: Publish,
+    },
+    SetNotes {
+        container: String,
+        notes: String, // Empty removes them
+    },
+}
+
+impl PendingAction {
+    pub fn permission(&self) -> Permission {
+        match self {
+            PendingAction::Container(action) => action.permission(),
+            PendingAction::Clone { .. } | PendingAction::RefreshCopy { .. } => Permission::Clone,
+            PendingAction::CreateContainer => Permission::Create,
+            PendingAction::CreateBackup(_)
+            | PendingAction::DeleteBackup { .. }
+            | PendingAction::CreateSnapshot { .. } => Permission::Backup,
+            PendingAction::SetTags { .. }
+            | PendingAction::SetNotes { .. }
+            | PendingAction::SetBootConfig { .. }
+            | PendingAction::UpdateConfig { .. }
+            | PendingAction::ChangeMedia { .. }
+            | PendingAction::SetVmSetting { .. }
+            | PendingAction::ChangeIdmap { .. }
+            | PendingAction::ChangeProfiles { .. }
+            | PendingAction::UpdateMetadata { .. }
+            | PendingAction::SetTemplate { .. }
+            | PendingAction::ChangeZone(_) => Permission::Edit,
+            PendingAction::Rebuild { .. } => Permission::Rebuild,
+            PendingAction::Recreate(_) => Permission::Create,
+            PendingAction::SetImageAutoUpdate { .. }
+            | PendingAction::RefreshImage { .. }
+            | PendingAction::Publish { .. } => Permission::Images,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub enum CommandMenu {
     Closed,
     Main,
     Container,
     System,
+    RemoteAuth,
 }
 
 #[derive(Debug, Clone)]
 pub enum StatusModalType {
     Info {
         message: String,
-        auto_close: bool,
+        auto_close: bool, // Closes by itself like a success dialog
+        started_at: Instant,
     },
     Progress {
         operation_id: String,
+        confirm_cancel: bool, // Waiting for y to cancel the operation
     },
     Error {
         title: String,
         details: String,
         suggestions: Vec<String>,
+        report: Box<ErrorReport>,
     },
     Success {
         message: String,
@@ -104,6 +417,7 @@ pub enum OperationStatus {
 }
 
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct UserOperation {
     pub id: String,
     pub description: String,
@@ -112,6 +426,55 @@ pub struct UserOperation {
     pub started_at: Option<Instant>,
     pub completed_at: Option<Instant>,
     pub retry_count: u32,
+    pub lxd_operation: Option<LxdOperation>, // As LXD last reported it
+    pub retry: Option<ConfirmAction>,        // Re-issues the request after a failure
+    pub finished_at: Option<chrono::DateTime<chrono::Local>>,
+    pub kind: Option<String>, // Operations of one kind take similar time, e.g. "backup web1"
+    pub duration: Option<Duration>,
+}
+
+impl UserOperation {
+    /// History entry for a finished operation
+    fn to_record(&self) -> Option<OperationRecord> {
+        let (outcome, error) = match &self.status {
+            OperationStatus::Success => (Outcome::Success, None),
+            OperationStatus::Failed(e) => (Outcome::Failed, Some(e.clone())),
+            OperationStatus::Cancelled => (Outcome::Cancelled, None),
+            _ => return None,
+        };
+        Some(OperationRecord {
+            description: self.description.clone(),
+            container: self.container.clone(),
+            outcome,
+            error,
+            finished_at: self.finished_at?,
+            lxd_operation: self.lxd_operation.clone(),
+            kind: self.kind.clone(),
+            duration_secs: self.duration.map(|d| d.as_secs()),
+        })
+    }
+
+    /// An operation from an earlier session
+    fn from_record(record: OperationRecord) -> Self {
+        UserOperation {
+            id: Uuid::new_v4().to_string(),
+            description: record.description,
+            container: record.container,
+            status: match record.outcome {
+                Outcome::Success => OperationStatus::Success,
+                Outcome::Failed => OperationStatus::Failed(record.error.unwrap_or_default()),
+                Outcome::Cancelled => OperationStatus::Cancelled,
+            },
+            started_at: None,
+            completed_at: None,
+            retry_count: 0,
+            lxd_operation: record.lxd_operation,
+            retry: None,
+            finished_at: Some(record.finished_at),
+            kind: record.kind,
+            duration: record.duration_secs.map(Duration::from_secs),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -122,6 +485,8 @@ pub enum InputMode {
     Confirmation {
         message: String,
         action: ConfirmAction,
+        typed_name: Option<String>, // Name the user must type to confirm
+        checks: Vec<Check>,         // What would block the action or be lost
     },
     Input {
         prompt: String,
@@ -129,77 +494,435 @@ pub enum InputMode {
         callback_action: InputCallback,
     },
     Wizard(WizardState),
+    Backups {
+        container: String, // qualified name
+        confirm_delete: bool,
+    },
+    Help {
+        query: String,
+        searching: bool, // Typing into the search field
+    },
+    Preview(PendingAction),
+    SpaceWarning {
+        action: PendingAction, // A create or clone
+        message: String,       // How full the pool would get
+    },
+    CloneOptions(CloneForm), // After the clone's name
+    Log,
+    Watch,     // The container in `App::watch`
+    Compare,   // The instances in `App::compare`
+    Top,       // Ranking in `App::top`
+    Dashboard, // Host at a glance, from `App::dashboard` and `App::top`
+    BootOrder, // Settings in `App::boot_order`
+    Audit {
+        confirm_rebuild: bool, // Asking to rebuild the selected instance
+    },
+    Zones {
+        confirm_delete: bool, // Asking to delete the selected zone or record
+    },
+    ImageStore, // Images in `App::image_store`
+    Pools {
+        confirm_scrub: bool, // Asking to scrub the selected pool
+    },
+    Capacity,   // Reservations in `App::capacity`
+    Templates,  // The container in `App::templates`
+    VmSettings, // The VM in `App::vm_settings`
+    Idmap,      // The container in `App::idmap`
+    Recreate,   // The plan in `App::recreate`
+    Profiles,   // The picked instances in `App::profile_batch`
+    Quick {
+        confirm: Option<usize>, // Asking to run this action of `App::quick`
+    },
+    KeySearch,  // The hits in `App::key_search`
+    Drift,      // The comparison in `App::drift`
+    Tour,       // The step in `App::tour`
+    Console,    // The container in `App::console`
+    Operations, // The operations sidebar has focus
+    OperationDetails {
+        id: String, // UI operation id
+        scroll: u16,
+    },
 }
 
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub enum InputType {
     ContainerName,
     ImageName,
+    Url,
+    Token,
+    Command,
+    Tags,
+    Number,
+    FileName,
+    DomainName,
+    ZoneEntries, // Of a DNS record
+    Media,       // ISO path or pool/volume
+    CpuSet,      // A count or ranges of CPUs
+    IdmapEntry,  // A line of raw.idmap
+    ConfigKey,   // A key, or a prefix ending in *
+    Path,        // A file on this machine
+}
+
+impl InputType {
+    /// Whether `c` may be typed into a field of this type
+    pub fn accepts(&self, c: char) -> bool {
+        match self {
+            InputType::ContainerName => c.is_alphanumeric() || c == '-' || c == '_',
+            InputType::ImageName => c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.' | '/'),
+            InputType::Url | InputType::Token => !c.is_whitespace(),
+            InputType::Command | InputType::ZoneEntries => !c.is_control(),
+            InputType::Tags => tags::is_tag_char(c),
+            InputType::Number => c.is_ascii_digit() || c == '-',
+            InputType::FileName => templates::is_file_name_char(c),
+            InputType::DomainName => c.is_alphanumeric() || c == '-' || c == '.',
+            InputType::Media => media::is_media_char(c),
+            InputType::CpuSet => c.is_ascii_digit() || c == '-' || c == ',',
+            InputType::IdmapEntry => c.is_ascii_alphanumeric() || c == ' ' || c == '-',
+            InputType::ConfigKey => search::is_pattern_char(c),
+            InputType::Path => !c.is_control(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub enum InputCallback {
     CloneContainer(String), // source name
+    RefreshCopy(String),    // source name
     CreateContainer,
+    AddRemoteName,
+    AddRemoteUrl(String), // remote name
+    AddRemoteToken,
+    Command,
+    SetTags(String),     // container name
+    AttachMedia(String), // VM name
+    RecreateName,        // Of the instance in `App::recreate`
+    RecreateImage,
+    SearchConfigKey,
+    DriftFile,
+    QuickCommand, // Run in the instances of `App::quick`
+    SetVmSetting {
+        container: String,
+        knob: Knob,
+    },
+    IdmapLine {
+        container: String,
+        index: Option<usize>, // The line replaced; None adds one
+    },
+    SetBootConfig {
+        container: String,
+        key: &'static str,
+    },
+    NewTemplate(String), // container name
+    NewZone,
+    NewZoneRecord(String), // zone name
+    EditZoneRecord {
+        zone: String,
+        record: LxdZoneRecord,
+    },
 }
 
+impl InputCallback {
+    /// Name the prompt's history is kept under; None for prompts not worth
+    /// recalling, or that take secrets
+    pub fn history(&self) -> Option<&'static str> {
+        match self {
+            InputCallback::CloneContainer(_)
+            | InputCallback::RefreshCopy(_)
+            | InputCallback::RecreateName => Some("destination"),
+            InputCallback::Command => Some("command"),
+            InputCallback::SetTags(_) => Some("tags"),
+            InputCallback::AttachMedia(_) => Some("media"),
+            InputCallback::AddRemoteUrl(_) => Some("remote-address"),
+            InputCallback::NewZoneRecord(_) => Some("zone-record"),
+            InputCallback::SearchConfigKey => Some("config-key"),
+            InputCallback::DriftFile => Some("inventory-file"),
+            InputCallback::QuickCommand => Some("quick-command"),
+            InputCallback::CreateContainer
+            | InputCallback::AddRemoteName
+            | InputCallback::AddRemoteToken
+            | InputCallback::SetBootConfig { .. }
+            | InputCallback::SetVmSetting { .. }
+            | InputCallback::IdmapLine { .. }
+            | InputCallback::RecreateImage
+            | InputCallback::NewTemplate(_)
+            | InputCallback::NewZone
+            | InputCallback::EditZoneRecord { .. } => None,
+        }
+    }
+}
+
+/// An HTTPS remote that is connected but not yet authenticated
+pub struct PendingRemote {
+    pub remote: RemoteConfig,
+    pub server_cert: Vec<u8>,
+    pub fingerprint: String,
+    pub client: LxcClient,
+}
+
+/// Result of a background OIDC device login
+pub type OidcLoginResult = Result<OidcTokens, AuthError>;
+
 pub struct App {
     pub containers: Arc<RwLock<Vec<Container>>>,
     pub selected: usize,
     pub lxc_client: LxcClient,
     pub input_mode: InputMode,
-    pub input_buffer: String,
+    pub input_buffer: TextField,
     pub wizard_data: WizardData,
     pub available_images: Vec<Image>,
+    pub images: ImageCatalog, // Recent, local and searched images for the wizard
+    pub prompt_history: Option<PromptHistory>, // What was typed at prompts, for Up/Down
     pub message: Option<String>,
     pub should_quit: bool,
     pub exec_container: Option<String>,
     pub operations: Vec<Operation>,
     pub user_operations: Vec<UserOperation>,
     pub last_refresh: Option<Instant>,
+    pub next_refresh: Option<Instant>, // Of the list, jittered
+    pub refresh_wanted: bool,          // An event asked for one the rate didn't allow yet
+    pub polls: Poller,                 // Rate limits and jitter of everything polled
     pub pending_action: Option<ConfirmAction>,
     pub command_feedback: Option<String>,
     pub active_operation_count: usize,
     pub show_operation_sidebar: bool,
+    pub operation_selected: usize, // In the sidebar, top to bottom
+    pub operation_filter: sidebar::Filter,
+    pub operation_grouping: sidebar::Grouping,
     pub last_lxd_check: Option<Instant>,
     pub lxd_status: bool,
+    pub heartbeat: Heartbeat, // Background health check of the active server
     pub background_tasks: HashMap<String, JoinHandle<()>>, // Track background operations (simplified)
+    #[allow(dead_code)]
     pub task_result_tx: mpsc::UnboundedSender<TaskResult>, // Channel to send results from background tasks
     pub task_result_rx: mpsc::UnboundedReceiver<TaskResult>, // Channel to receive results in main thread
     pub lxd_operations: HashMap<String, LxdOperationTracker>, // Track LXD operations
     pub menu_selected: usize,                                // Currently selected menu item
+    pub active_remote: Option<String>, // None when talking to the local socket
+    pub pending_remote: Option<PendingRemote>, // Remote being added
+    pub oidc_login: Option<(String, oneshot::Receiver<OidcLoginResult>)>, // (op_id, result)
+    pub all_remotes: bool,             // List containers from every configured remote
+    pub remote_clients: Vec<(String, LxcClient)>, // Clients used in all-remotes mode
+    pub unreachable_remotes: Vec<String>, // Remotes the last all-remotes refresh couldn't list
+    pub backups: Vec<Backup>,          // Backups of the instance being viewed
+    pub backup_selected: usize,
+    pub schedules: Vec<(ScheduleConfig, Schedule)>, // Scheduled backups from the config file
+    pub last_schedule_minute: Option<i64>,          // Minute the schedules were last checked
+    pub schedule_tx: mpsc::UnboundedSender<ScheduleResult>,
+    pub schedule_rx: mpsc::UnboundedReceiver<ScheduleResult>,
+    pub schedule_warning: Option<String>, // Latest scheduled job failure
+    pub config_warnings: Vec<String>,     // Config entries skipped or only partly usable
+    pub notice: Option<(String, Instant)>, // Title bar word on what a key did, like an undo
+    pub power_overrides: PowerOverrides,  // Skipped and held starts and stops
+    pub help_scroll: u16,                 // First visible line of the help screen
+    pub confirmations: Confirmations,     // Confirmation policy from the config file
+    pub preview_requests: bool,           // Show API requests before sending them
+    pub read_only: bool,                  // Mutating actions are disabled
+    pub permissions: PermissionsConfig,   // Changes this deployment allows
+    pub log_lines: Vec<String>,           // Log file shown in the log view
+    pub log_scroll: Option<u16>,          // First visible log line; None follows the end
+    pub show_debug: bool,                 // Show the debug overlay
+    pub show_perf: bool,                  // Show the performance HUD
+    pub lxd_version: Option<String>,      // Server version, for error reports
+    pub server_details: Option<ServerDetails>, // Shown in the system menu
+    pub minimum_version: String,          // Older servers get a warning
+    pub unavailable: Unavailable,         // Features the server turned out to lack
+    pub service_control: Option<ServiceControl>, // Service command awaiting the API
+    pub perf: PerfStats,
+    pub filter: Option<ContainerFilter>, // Only these containers are listed
+    pub group_by_tag: bool,              // List containers grouped by their first tag
+    pub sort: Option<SortKey>,           // Order of the list; None keeps the server's
+    pub workspaces: Vec<Workspace>,      // Saved views from the config file, keys 1-9
+    pub workspace: Option<usize>,        // Index of the active workspace, until the view changes
+    pub undo: UndoStack,                 // Earlier views of the list, for u and Ctrl+R
+    pub config_edit: Option<ConfigEdit>, // Instance config in the editor, or kept after an invalid edit
+    pub script: VecDeque<Action>,        // Commands from --script still to run
+    pub custom_actions: Vec<CustomAction>, // Extra container menu items from the config file
+    pub external_command: Option<ExternalCommand>, // Run by the event loop with the TUI suspended
+    pub watch: Option<Watch>,            // Container followed in the watch view
+    pub console: Option<ConsoleView>,    // Container followed in the console view
+    pub events: EventFeed,               // Lifecycle events from the connected servers
+    pub show_events: bool,               // Show the event feed panel
+    pub history: Option<History>,        // Where finished operations are kept
+    pub compare_marks: Vec<String>,      // Containers marked for comparison, qualified names
+    pub picked: Vec<String>,             // Instances picked for batch changes, qualified names
+    pub compare: Option<Comparison>,     // Shown in the compare view
+    pub top: Option<Top>,                // Usage samples for the ranking view
+    pub dashboard: Option<Dashboard>,    // Storage pool usage for the dashboard
+    pub dashboard_on_start: bool,        // Open on the dashboard rather than the list
+    pub boot_order: Option<BootOrder>,   // Autostart settings for the boot order view
+    pub audit: Option<Audit>,            // Instances on outdated base images
+    pub zones: Option<Zones>,            // DNS zones of the active server
+    pub image_store: Option<ImageStore>, // Images stored on the active server
+    pub expiring_images: Vec<StoredImage>, // Images on the active server LXD deletes soon
+    pub expiry_warning: TimeDelta,       // How soon counts as soon; zero warns of none
+    pub next_image_check: Option<Instant>, // When to look for expiring images again
+    pub capacity: Option<Capacity>,      // Reservations against the active server's resources
+    pub pools: Option<PoolsView>,        // Storage pools of the active server and their health
+    pub templates: Option<TemplatesView>, // Image metadata and templates of one container
+    pub vm_settings: Option<VmSettings>, // VM-only settings of one VM
+    pub idmap: Option<IdmapView>,        // ID mapping of one container
+    pub recreate: Option<Conversion>,    // Recreating one instance as the other type
+    pub profile_batch: Option<ProfileBatch>, // Profiles of the picked instances
+    pub quick_actions: Vec<QuickAction>, // The quick exec library
+    pub quick: Option<QuickView>,        // Quick exec actions and what they wrote
+    pub quick_timeout: Duration,         // Longest a quick exec command is waited on
+    pub key_search: Option<KeySearch>,   // Instances setting a config key
+    pub drift: Option<DriftView>,        // An exported inventory against the server now
+    pub tour: Option<Tour>,              // The first-run tour, while it runs
+    pub tour_config: TourConfig,         // Whether to offer the tour, and where it is marked seen
+    pub alerts: Alerts,                  // Containers breaching the configured thresholds
+    pub crashes: Crashes,                // Console logs of instances that stopped by themselves
+    pub restarts: Restarts,              // Crashed instances started again by their policy
+    restart_policies: bool,              // A listed instance has one, so events are followed
+    pub uptime: Option<Uptime>,          // When each instance was seen up and down
+    pub notifier: Notifier,              // Bell and command when an operation finishes
+    pub space: SpaceConfig,              // Free space guard for creates and clones
+    pub inventory: InventoryConfig,      // Where the inventory export is written
+    pub usage: Ledger,                   // What each instance used this session
+    pub usage_config: UsageConfig,       // How often usage is sampled, and where reports go
+    pub create_defaults: CreateDefaults, // Shape of new instances, pre-filled in the wizard
+    pub flashes: Flashes,                // Rows whose status just changed
+    pub probes: Probes,                  // Health checks of instances that define one
+    pub theme: Theme,                    // Status colours; high contrast from the config or `A`
+    pub accents: Accents,                // Colours of remotes and projects
+    pub reduced_motion: bool,            // Static progress and feedback that waits to be dismissed
+    pub image_column: bool,              // List the image each container was created from
+    pub dismiss_after: Option<Duration>, // How long success and information dialogs stay open
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(lxc_client: LxcClient) -> Self {
         // Create the channel for background task results
         let (task_result_tx, task_result_rx) = mpsc::unbounded_channel();
+        let (schedule_tx, schedule_rx) = mpsc::unbounded_channel();
 
         App {
             containers: Arc::new(RwLock::new(Vec::new())),
             selected: 0,
-            lxc_client: LxcClient::new(),
+            lxc_client,
             input_mode: InputMode::Normal,
-            input_buffer: String::new(),
+            input_buffer: TextField::default(),
             wizard_data: WizardData::default(),
             available_images: Vec::new(),
+            images: ImageCatalog::default(),
+            prompt_history: None,
             message: None,
             should_quit: false,
             exec_container: None,
             operations: Vec::new(),
             user_operations: Vec::new(),
             last_refresh: None,
+            next_refresh: None,
+            refresh_wanted: false,
+            polls: Poller::default(),
             pending_action: None,
             command_feedback: None,
             active_operation_count: 0,
             show_operation_sidebar: false,
+            operation_selected: 0,
+            operation_filter: sidebar::Filter::default(),
+            operation_grouping: sidebar::Grouping::default(),
             last_lxd_check: None,
             lxd_status: false,
+            heartbeat: Heartbeat::new(),
             background_tasks: HashMap::new(),
             task_result_tx,
             task_result_rx,
             lxd_operations: HashMap::new(),
             menu_selected: 0,
+            active_remote: None,
+            pending_remote: None,
+            oidc_login: None,
+            all_remotes: false,
+            remote_clients: Vec::new(),
+            unreachable_remotes: Vec::new(),
+            backups: Vec::new(),
+            backup_selected: 0,
+            schedules: Vec::new(),
+            last_schedule_minute: None,
+            schedule_tx,
+            schedule_rx,
+            schedule_warning: None,
+            config_warnings: Vec::new(),
+            notice: None,
+            power_overrides: PowerOverrides::default(),
+            help_scroll: 0,
+            confirmations: Confirmations::default(),
+            preview_requests: false,
+            read_only: false,
+            permissions: PermissionsConfig::default(),
+            log_lines: Vec::new(),
+            log_scroll: None,
+            show_debug: false,
+            show_perf: false,
+            lxd_version: None,
+            server_details: None,
+            minimum_version: ServerConfig::default().minimum_version,
+            unavailable: Unavailable::default(),
+            service_control: None,
+            perf: PerfStats::default(),
+            filter: None,
+            group_by_tag: false,
+            sort: None,
+            workspaces: Vec::new(),
+            workspace: None,
+            undo: UndoStack::default(),
+            config_edit: None,
+            script: VecDeque::new(),
+            custom_actions: Vec::new(),
+            external_command: None,
+            watch: None,
+            compare_marks: Vec::new(),
+            picked: Vec::new(),
+            compare: None,
+            top: None,
+            dashboard: None,
+            dashboard_on_start: false,
+            boot_order: None,
+            audit: None,
+            zones: None,
+            image_store: None,
+            expiring_images: Vec::new(),
+            expiry_warning: TimeDelta::days(ImagesConfig::default().expiry_warning_days.into()),
+            next_image_check: None,
+            capacity: None,
+            pools: None,
+            templates: None,
+            vm_settings: None,
+            idmap: None,
+            recreate: None,
+            profile_batch: None,
+            quick_actions: QuickConfig::default().actions,
+            quick: None,
+            quick_timeout: QuickConfig::default().timeout(),
+            key_search: None,
+            drift: None,
+            tour: None,
+            tour_config: TourConfig::default(),
+            alerts: Alerts::default(),
+            crashes: Crashes::new(),
+            restarts: Restarts::new(),
+            restart_policies: false,
+            uptime: None,
+            notifier: Notifier::default(),
+            space: SpaceConfig::default(),
+            inventory: InventoryConfig::default(),
+            usage: Ledger::new(&UsageConfig::default()),
+            usage_config: UsageConfig::default(),
+            create_defaults: CreateDefaults::default(),
+            flashes: Flashes::default(),
+            probes: Probes::new(ProbesConfig::default()),
+            theme: Theme::default(),
+            accents: Accents::default(),
+            reduced_motion: false,
+            image_column: false,
+            dismiss_after: DisplayConfig::default().dismiss_after(),
+            console: None,
+            events: EventFeed::new(),
+            show_events: false,
+            history: None,
         }
     }
 
@@ -208,9 +931,395 @@ impl App {
 
         // Load available images
         self.load_available_images();
+        self.load_config();
 
         // Try to ensure LXD is running and refresh containers
         self.ensure_lxd_and_refresh().await;
+        self.heartbeat
+            .start(self.lxc_client.clone(), self.polls.clone());
+
+        // In place of the "LXD service is running" notice, but not of an error
+        if self.dashboard_on_start
+            && !matches!(
+                self.input_mode,
+                InputMode::StatusModal(StatusModalType::Error { .. })
+            )
+        {
+            self.open_dashboard().await;
+        }
+        self.offer_tour();
+    }
+
+    /// Start the tour on first launch, unless an error is showing
+    fn offer_tour(&mut self) {
+        let error = matches!(
+            self.input_mode,
+            InputMode::StatusModal(StatusModalType::Error { .. })
+        );
+        if self.tour_config.offer && !error && !tour::seen(&self.tour_config.seen_path()) {
+            self.start_tour();
+        }
+    }
+
+    pub fn start_tour(&mut self) {
+        self.tour = Some(Tour::default());
+        self.input_mode = InputMode::Tour;
+    }
+
+    /// Go to the next step of the tour, ending it after the last
+    pub fn next_tour_step(&mut self) {
+        if !self.tour.as_mut().is_some_and(Tour::next) {
+            self.end_tour();
+        }
+    }
+
+    pub fn previous_tour_step(&mut self) {
+        if let Some(tour) = &mut self.tour {
+            tour.previous();
+        }
+    }
+
+    /// Close the tour, marking it seen so it isn't offered again
+    pub fn end_tour(&mut self) {
+        self.tour = None;
+        self.input_mode = InputMode::Normal;
+        let path = self.tour_config.seen_path();
+        if let Err(e) = tour::mark_seen(&path) {
+            warn!("Failed to mark the tour seen at {}: {}", path.display(), e);
+        }
+    }
+
+    /// Apply settings from the config file, skipping invalid schedules and
+    /// workspaces
+    pub fn load_config(&mut self) {
+        let config = match Config::load() {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Failed to load config: {}", e);
+                return;
+            }
+        };
+
+        self.confirmations = config.confirmations;
+        self.preview_requests = config.preview_requests;
+        self.read_only = config.read_only;
+        self.permissions = config.permissions.clone();
+        self.custom_actions = config.actions;
+        for warning in hooks::shadowed(&self.custom_actions) {
+            warn!("{}", warning);
+            self.config_warnings.push(warning);
+        }
+        self.quick_timeout = config.quick.timeout();
+        self.quick_actions = config.quick.actions;
+        for warning in quick::shadowed(&self.quick_actions) {
+            warn!("{}", warning);
+            self.config_warnings.push(warning);
+        }
+        self.history = History::new(&config.history);
+        self.images = ImageCatalog::new(&config.images);
+        self.expiry_warning = TimeDelta::days(config.images.expiry_warning_days.into());
+        self.prompt_history = PromptHistory::new(&config.prompt_history);
+        if let Some(history) = &mut self.prompt_history {
+            if let Err(e) = history.load() {
+                warn!("Failed to load prompt history: {}", e);
+            }
+        }
+        self.uptime = Uptime::new(&config.uptime);
+        if let Some(uptime) = &mut self.uptime {
+            if let Err(e) = uptime.load(chrono::Local::now()) {
+                warn!("Failed to load uptime history: {}", e);
+            }
+        }
+        self.alerts = Alerts::new(config.alerts);
+        self.probes = Probes::new(config.probes);
+        self.notifier = Notifier::new(config.notifications);
+        self.space = config.space;
+        self.inventory = config.inventory;
+        self.usage = Ledger::new(&config.usage);
+        self.usage_config = config.usage;
+        self.tour_config = config.tour;
+        self.polls.configure(&config.polling);
+        self.create_defaults = config.create;
+        self.theme = Theme::new(config.display.high_contrast);
+        self.accents = Accents::new(&config.accents);
+        self.reduced_motion = config.display.reduced_motion;
+        self.image_column = config.display.image_column;
+        self.dashboard_on_start = config.display.dashboard;
+        self.dismiss_after = config.display.dismiss_after();
+        self.minimum_version = config.server.minimum_version;
+        self.restore_history();
+
+        for job in config.schedules {
+            match Schedule::parse(&job.schedule) {
+                Ok(schedule) => self.schedules.push((job, schedule)),
+                Err(e) => {
+                    warn!("Ignoring schedule for {}: {}", job.instance, e);
+                    self.config_warnings
+                        .push(format!("schedule {}: {}", job.instance, e));
+                }
+            }
+        }
+
+        if !self.schedules.is_empty() {
+            info!("Loaded {} scheduled jobs", self.schedules.len());
+        }
+
+        for config in config.workspaces {
+            match Workspace::from_config(&config) {
+                Ok(workspace) => self.workspaces.push(workspace),
+                Err(e) => {
+                    warn!("Ignoring workspace {}: {}", config.name, e);
+                    self.config_warnings
+                        .push(format!("workspace {}: {}", config.name, e));
+                }
+            }
+        }
+    }
+
+    /// Put operations from earlier sessions in the sidebar, before any from
+    /// this one
+    fn restore_history(&mut self) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let records = match history.load(chrono::Local::now()) {
+            Ok(records) => records,
+            Err(e) => {
+                warn!("Failed to load operation history: {}", e);
+                return;
+            }
+        };
+
+        let skip = records.len().saturating_sub(MAX_OPERATION_HISTORY);
+        let restored: Vec<UserOperation> = records
+            .into_iter()
+            .skip(skip)
+            .map(UserOperation::from_record)
+            .collect();
+        info!("Restored {} operations from history", restored.len());
+        self.user_operations.splice(0..0, restored);
+        let excess = self
+            .user_operations
+            .len()
+            .saturating_sub(MAX_OPERATION_HISTORY);
+        self.user_operations.drain(..excess);
+    }
+
+    /// Append a finished operation to the history file
+    fn save_to_history(&self, operation_id: &str) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let record = self
+            .user_operations
+            .iter()
+            .find(|o| o.id == operation_id)
+            .and_then(UserOperation::to_record);
+        if let Some(record) = record {
+            if let Err(e) = history.append(&record) {
+                warn!("Failed to save operation history: {}", e);
+            }
+        }
+    }
+
+    /// Start any scheduled jobs due this minute, at most once per minute
+    pub fn run_due_schedules(&mut self) {
+        self.run_schedules_at(chrono::Local::now());
+    }
+
+    /// Start the scheduled jobs due in the minute containing `now`, unless
+    /// that minute was checked already. Starts and stops of skipped or held
+    /// instances are left out.
+    fn run_schedules_at(&mut self, now: chrono::DateTime<chrono::Local>) {
+        if self.schedules.is_empty() || self.read_only {
+            return;
+        }
+
+        let minute = now.timestamp() / 60;
+        if self.last_schedule_minute == Some(minute) {
+            return;
+        }
+        self.last_schedule_minute = Some(minute);
+
+        let time = now.naive_local();
+        let Some(minute_start) = time.with_second(0).and_then(|t| t.with_nanosecond(0)) else {
+            return;
+        };
+        let due: Vec<ScheduleConfig> = self
+            .schedules
+            .iter()
+            .filter(|(job, schedule)| {
+                schedule.matches(&time) && self.permissions.permits(job.action.permission())
+            })
+            .map(|(job, _)| job.clone())
+            .collect();
+
+        for job in due {
+            if job.action.is_power() && !self.power_overrides.allows(&job.instance, &minute_start) {
+                info!(
+                    "Skipped scheduled {} of '{}'",
+                    job.action.name(),
+                    job.instance
+                );
+                continue;
+            }
+            if job.action == ScheduledAction::Stop {
+                let listed = self.listed_name(&job.instance);
+                self.alerts.expect_stop(&listed);
+                self.crashes.expect_stop(&listed);
+            }
+
+            let description = format!("Scheduled {} of '{}'", job.action.name(), job.instance);
+            let operation_id =
+                self.register_operation(description.clone(), Some(job.instance.clone()));
+            self.start_operation(&operation_id);
+
+            let target = self.schedule_client(&job.instance);
+            let tx = self.schedule_tx.clone();
+            let op_id = operation_id.clone();
+            let handle = tokio::spawn(async move {
+                let outcome = match target {
+                    Ok((client, name)) => {
+                        // LXD refuses to start a running instance or stop a
+                        // stopped one; either is already done
+                        let wanted = match job.action {
+                            ScheduledAction::Start => Some("Running"),
+                            ScheduledAction::Stop => Some("Stopped"),
+                            _ => None,
+                        };
+                        let done = match wanted {
+                            Some(status) => client
+                                .get_state(&name)
+                                .await
+                                .is_ok_and(|state| state.status == status),
+                            None => false,
+                        };
+                        match job.action {
+                            _ if done => Ok(()),
+                            ScheduledAction::Backup => client.create_backup(&name).await,
+                            ScheduledAction::Snapshot => client.create_snapshot(&name, None).await,
+                            ScheduledAction::Start => client.start_container(&name).await,
+                            ScheduledAction::Stop => client.stop_container(&name).await,
+                        }
+                        .map_err(|e| e.to_string())
+                    }
+                    Err(e) => Err(e),
+                };
+                let _ = tx.send((op_id, description, outcome));
+            });
+            self.background_tasks.insert(operation_id, handle);
+        }
+    }
+
+    /// How schedules name `container`: qualified with its remote, except on
+    /// the local server
+    pub fn schedule_name(&self, container: &Container) -> String {
+        match container
+            .remote
+            .as_deref()
+            .or(self.active_remote.as_deref())
+        {
+            Some(remote) if remote != "local" => format!("{}:{}", remote, container.name),
+            _ => container.name.clone(),
+        }
+    }
+
+    /// The list's name for the instance a schedule names
+    fn listed_name(&self, instance: &str) -> String {
+        let Ok(containers) = self.containers.try_read() else {
+            return instance.to_string();
+        };
+        containers
+            .iter()
+            .find(|c| self.schedule_name(c) == instance)
+            .map_or_else(|| instance.to_string(), Container::qualified_name)
+    }
+
+    /// The selected container as schedules name it, when it has a start or
+    /// stop schedule
+    async fn selected_power_schedule(&mut self) -> Option<String> {
+        let container = self.get_selected_container().await?;
+        let instance = self.schedule_name(&container);
+        if power::scheduled(&self.schedules, &instance) {
+            return Some(instance);
+        }
+        self.show_info(
+            format!("'{}' has no start or stop schedule", container.name),
+            true,
+        );
+        None
+    }
+
+    /// Skip the selected container's next scheduled start or stop, or run it
+    /// after all when it is skipped
+    pub async fn skip_power_schedule(&mut self) {
+        let Some(instance) = self.selected_power_schedule().await else {
+            return;
+        };
+        let now = chrono::Local::now().naive_local();
+        let Some(run) = power::next_run(&self.schedules, &instance, &now) else {
+            return;
+        };
+        let when = run.at.format("%a %H:%M");
+        let message = if self.power_overrides.toggle_skip(&instance, &run) {
+            format!(
+                "Skipping the {} of '{}' at {}",
+                run.action.name(),
+                instance,
+                when
+            )
+        } else {
+            format!(
+                "The {} of '{}' at {} will run",
+                run.action.name(),
+                instance,
+                when
+            )
+        };
+        info!("{}", message);
+        self.show_info(message, true);
+    }
+
+    /// Hold the selected container's power schedule, or lift the hold
+    pub async fn hold_power_schedule(&mut self) {
+        let Some(instance) = self.selected_power_schedule().await else {
+            return;
+        };
+        let message = if self.power_overrides.toggle_hold(&instance) {
+            format!(
+                "Holding '{}': scheduled starts and stops are left out",
+                instance
+            )
+        } else {
+            format!("Released '{}': its schedule runs again", instance)
+        };
+        info!("{}", message);
+        self.show_info(message, true);
+    }
+
+    /// Client for a scheduled job's instance, connecting to its remote if needed
+    fn schedule_client(&self, target: &str) -> std::result::Result<(LxcClient, String), String> {
+        let Some((remote, name)) = target.split_once(':') else {
+            if self.active_remote.is_none() {
+                return Ok((self.lxc_client.clone(), target.to_string()));
+            }
+            return LxcClient::local()
+                .map(|client| (client, target.to_string()))
+                .map_err(|e| e.to_string());
+        };
+
+        if self.active_remote.as_deref() == Some(remote) {
+            return Ok((self.lxc_client.clone(), name.to_string()));
+        }
+        if let Some((_, client)) = self.remote_clients.iter().find(|(n, _)| n == remote) {
+            return Ok((client.clone(), name.to_string()));
+        }
+
+        let config = Config::load().map_err(|e| e.to_string())?;
+        let remote = config.remote(remote).map_err(|e| e.to_string())?;
+        LxcClient::for_remote(remote)
+            .map(|client| (client, name.to_string()))
+            .map_err(|e| e.to_string())
     }
 
     pub fn load_available_images(&mut self) {
@@ -261,6 +1370,7 @@ impl App {
                 self.lxd_status = started;
                 self.last_lxd_check = Some(Instant::now());
                 if started {
+                    self.load_server_details().await;
                     self.show_info("LXD service is running".to_string(), true);
                     let _ = self.refresh_containers().await;
                 } else {
@@ -268,7 +1378,7 @@ impl App {
                         "LXD service not running".to_string(),
                         "Could not start LXD service".to_string(),
                         vec![
-                            "Try running with sudo".to_string(),
+                            "Add your user to the lxd group, or restart LXTUI and accept the privileged helper".to_string(),
                             "Check systemctl status lxd".to_string(),
                         ],
                     );
@@ -283,6 +1393,7 @@ impl App {
                     e.to_string(),
                     vec![
                         "Check LXD installation".to_string(),
+                        "Start it from the system menu (Space, then s)".to_string(),
                         "Run 'sudo systemctl status lxd'".to_string(),
                     ],
                 );
@@ -290,21 +1401,158 @@ impl App {
         }
     }
 
+    /// Start LXD, or restart it when it answers, through its service
+    /// manager. The command runs with the TUI suspended so sudo can prompt.
+    pub fn control_service(&mut self) {
+        if self.active_remote.is_some() {
+            self.show_info(
+                "Only the local LXD service can be started from LXTUI".to_string(),
+                true,
+            );
+            return;
+        }
+        let Some(service) = Service::detect() else {
+            self.show_error(
+                "Cannot control the LXD service".to_string(),
+                "Neither the LXD snap nor systemctl was found".to_string(),
+                vec!["Start LXD the way it was installed".to_string()],
+            );
+            return;
+        };
+
+        let action = if self.lxd_status {
+            ServiceAction::Restart
+        } else {
+            ServiceAction::Start
+        };
+        let command = service.command(action, service::escalation());
+        info!("Running '{}'", command);
+        self.external_command = Some(ExternalCommand {
+            name: format!("LXD {}", action),
+            command: command.clone(),
+            pause: false,
+        });
+        self.service_control = Some(ServiceControl::new(service, action, command));
+    }
+
+    /// After the service command returned: wait for the API, or show why
+    /// the command failed
+    pub async fn service_command_finished(&mut self, succeeded: bool) {
+        let Some(control) = &mut self.service_control else {
+            return;
+        };
+        if succeeded {
+            control.finished = Some(Instant::now());
+            self.show_info("Waiting for LXD to answer...".to_string(), false);
+        } else {
+            let reason = format!("'{}' failed", control.command);
+            self.service_failed(reason).await;
+        }
+    }
+
+    /// Ask the API whether the service is up yet, giving up with the
+    /// journal after `service::START_TIMEOUT`
+    pub async fn poll_service(&mut self) {
+        let now = Instant::now();
+        let Some(control) = &mut self.service_control else {
+            return;
+        };
+        if !control.check_due(now) {
+            return;
+        }
+        control.last_check = Some(now);
+
+        if self.lxc_client.ensure_lxd_running().await.is_ok() {
+            if let Some(control) = self.service_control.take() {
+                info!("LXD answered after '{}'", control.command);
+            }
+            self.ensure_lxd_and_refresh().await;
+        } else if control.timed_out(now) {
+            let reason = format!(
+                "LXD didn't answer within {} seconds of '{}'",
+                service::START_TIMEOUT.as_secs(),
+                control.command
+            );
+            self.service_failed(reason).await;
+        }
+    }
+
+    async fn service_failed(&mut self, reason: String) {
+        let Some(control) = self.service_control.take() else {
+            return;
+        };
+        error!("{}", reason);
+        let journal = control.service.journal().await;
+        self.show_error(
+            format!("LXD failed to {}", control.action),
+            format!(
+                "{}\n\nJournal of {}:\n{}",
+                reason,
+                control.service.unit(),
+                journal
+            ),
+            vec![
+                format!("Run 'systemctl status {}'", control.service.unit()),
+                "Press c to copy the full report".to_string(),
+            ],
+        );
+    }
+
+    /// Ask the active server for its version and storage driver, and the
+    /// local snap for its channel, warning when the server is too old
+    pub async fn load_server_details(&mut self) {
+        let Ok(info) = self.lxc_client.server_info().await else {
+            self.server_details = None;
+            self.lxd_version = None;
+            return;
+        };
+        let mut details = ServerDetails::new(&info, &self.minimum_version);
+        if self.active_remote.is_none() {
+            details.channel = server::snap_channel(&details.server.to_lowercase()).await;
+        }
+        if let Some(minimum) = &details.outdated {
+            warn!(
+                "{} {} is older than the minimum version {}",
+                details.server, details.version, minimum
+            );
+        }
+
+        self.lxd_version = Some(details.version.clone()).filter(|v| !v.is_empty());
+        self.server_details = Some(details);
+    }
+
+    /// The tone `container`'s row flashes in after a status change, unless
+    /// motion is reduced
+    pub fn flash_tone(&self, container: &str) -> Option<Tone> {
+        if self.reduced_motion {
+            return None;
+        }
+        self.flashes.tone(container, Instant::now())
+    }
+
     pub async fn refresh_containers(&mut self) -> Result<()> {
+        let started = Instant::now();
+        let result = self.fetch_containers().await;
+        if self.next_image_check.is_none_or(|at| Instant::now() >= at) {
+            self.check_expiring_images().await;
+        }
+        self.perf.refresh.record(started.elapsed());
+        self.next_refresh = Some(Instant::now() + self.polls.jittered(REFRESH_INTERVAL));
+        self.refresh_wanted = false;
+        result
+    }
+
+    async fn fetch_containers(&mut self) -> Result<()> {
         debug!("Refreshing container list");
 
+        if self.all_remotes {
+            return self.refresh_all_remotes().await;
+        }
+
         match self.lxc_client.list_containers().await {
             Ok(containers) => {
                 let container_count = containers.len();
-                *self.containers.write().await = containers;
-
-                let containers_read = self.containers.read().await;
-                if self.selected >= containers_read.len() && !containers_read.is_empty() {
-                    self.selected = containers_read.len() - 1;
-                }
-                drop(containers_read);
-
-                self.last_refresh = Some(Instant::now());
+                self.store_containers(containers).await;
                 self.message = Some(format!("Refreshed - {} containers found", container_count));
                 info!("Container list refreshed - {} containers", container_count);
                 Ok(())
@@ -318,430 +1566,5538 @@ impl App {
         }
     }
 
-    pub async fn next(&mut self) {
-        let containers = self.containers.read().await;
-        if !containers.is_empty() {
-            self.selected = (self.selected + 1) % containers.len();
+    async fn store_containers(&mut self, containers: Vec<Container>) {
+        let count = self.visible(&containers).len();
+        self.alerts.check(&containers, Instant::now());
+        self.flashes.check(&containers, Instant::now());
+        if let Some(uptime) = &mut self.uptime {
+            if uptime.observe(&containers, chrono::Local::now()) {
+                if let Err(e) = uptime.save() {
+                    warn!("Failed to save uptime history: {}", e);
+                }
+            }
+        }
+        // Requested stops are only told from crashes by their events
+        let restarts = containers.iter().any(|c| c.restart);
+        if restarts != self.restart_policies {
+            self.restart_policies = restarts;
+            if !self.events_wanted() {
+                self.events.stop();
+            } else if !self.events.following() {
+                self.resubscribe_events();
+            }
+        }
+        // The event of a requested stop may have come in while listing
+        let delivered = self.events.drain();
+        self.take_in_events(&delivered);
+        for (name, status) in self.crashes.check(&containers, Instant::now()) {
+            let restart = containers
+                .iter()
+                .any(|c| c.restart && c.qualified_name() == name);
+            self.capture_console(name.clone(), status);
+            if restart {
+                self.restart_crashed(name);
+            }
+        }
+        *self.containers.write().await = containers;
+
+        if self.selected >= count && count > 0 {
+            self.selected = count - 1;
+        }
+        self.last_refresh = Some(Instant::now());
+    }
+
+    /// List every remote concurrently and merge the results, tagged by remote
+    async fn refresh_all_remotes(&mut self) -> Result<()> {
+        let listings =
+            futures::future::join_all(self.remote_clients.iter().map(
+                |(name, client)| async move { (name.clone(), client.list_containers().await) },
+            ))
+            .await;
+
+        let mut containers = Vec::new();
+        let mut unreachable = Vec::new();
+        for (remote, result) in listings {
+            match result {
+                Ok(list) => containers.extend(list.into_iter().map(|mut c| {
+                    c.remote = Some(remote.clone());
+                    c
+                })),
+                Err(e) => {
+                    warn!("Failed to list containers on remote {}: {:?}", remote, e);
+                    unreachable.push(remote);
+                }
+            }
+        }
+
+        let container_count = containers.len();
+        self.store_containers(containers).await;
+        self.message = Some(format!(
+            "Refreshed - {} containers on {} remotes",
+            container_count,
+            self.remote_clients.len() - unreachable.len()
+        ));
+        self.unreachable_remotes = unreachable;
+        info!(
+            "Container list refreshed across remotes - {} containers",
+            container_count
+        );
+        Ok(())
+    }
+
+    /// Connect to the local socket and every configured remote
+    fn connect_all_remotes(&mut self) -> Vec<String> {
+        let mut failures = Vec::new();
+        self.remote_clients.clear();
+
+        match LxcClient::local() {
+            Ok(client) => self.remote_clients.push(("local".to_string(), client)),
+            Err(e) => debug!("No local LXD socket: {:?}", e),
+        }
+
+        let remotes = match Config::load() {
+            Ok(config) => config.remotes,
+            Err(e) => {
+                failures.push(format!("config: {}", e));
+                Vec::new()
+            }
+        };
+        for remote in remotes {
+            match LxcClient::for_remote(&remote) {
+                Ok(client) => self.remote_clients.push((remote.name, client)),
+                Err(e) => {
+                    warn!("Failed to connect to remote {}: {:?}", remote.name, e);
+                    failures.push(format!("{}: {}", remote.name, e));
+                }
+            }
+        }
+
+        failures
+    }
+
+    pub async fn toggle_all_remotes(&mut self) {
+        self.selected = 0;
+
+        if self.all_remotes {
+            self.all_remotes = false;
+            self.remote_clients.clear();
+            self.unreachable_remotes.clear();
+            self.resubscribe_events();
+            let _ = self.refresh_containers().await;
+            return;
+        }
+
+        let failures = self.connect_all_remotes();
+        self.all_remotes = true;
+        self.resubscribe_events();
+        let _ = self.refresh_containers().await;
+
+        if !failures.is_empty() {
+            self.show_error(
+                "Some remotes are unavailable".to_string(),
+                failures.join("\n"),
+                vec!["Re-add the remote from the System menu".to_string()],
+            );
+        }
+    }
+
+    /// Resolve an optionally remote-qualified name ("remote:name") to the
+    /// client that owns it and the bare container name
+    pub fn client_for(&self, target: &str) -> (LxcClient, String) {
+        if let Some((remote, name)) = target.split_once(':') {
+            if let Some((_, client)) = self.remote_clients.iter().find(|(n, _)| n == remote) {
+                return (client.clone(), name.to_string());
+            }
+        }
+        (self.lxc_client.clone(), target.to_string())
+    }
+
+    /// Remote `target` lives on: the one it is qualified with, or else the
+    /// active one
+    pub fn remote_of<'a>(&'a self, target: &'a str) -> &'a str {
+        match target.split_once(':') {
+            Some((remote, _)) if self.remote_clients.iter().any(|(n, _)| n == remote) => remote,
+            _ => self.active_remote.as_deref().unwrap_or("local"),
+        }
+    }
+
+    /// The containers shown in the list, in order: those matching the filter
+    pub fn visible(&self, containers: &[Container]) -> Vec<Container> {
+        let mut visible: Vec<Container> = containers
+            .iter()
+            .filter(|c| self.filter.as_ref().is_none_or(|f| f.matches(c)))
+            .cloned()
+            .collect();
+        if let Some(sort) = self.sort {
+            sort.sort(&mut visible);
+        }
+        if self.group_by_tag {
+            // Untagged containers last; the sort is stable within a group
+            visible.sort_by_cached_key(|c| match c.tags.first() {
+                Some(tag) => (false, tag.to_lowercase()),
+                None => (true, String::new()),
+            });
+        }
+        visible
+    }
+
+    pub fn toggle_group_by_tag(&mut self) {
+        self.group_by_tag = !self.group_by_tag;
+        self.workspace = None;
+        self.selected = 0;
+    }
+
+    pub fn toggle_high_contrast(&mut self) {
+        self.theme = self.theme.toggled();
+    }
+
+    pub fn set_sort(&mut self, sort: Option<SortKey>) {
+        self.sort = sort;
+        self.workspace = None;
+        self.selected = 0;
+    }
+
+    /// Switch to the workspace on number key `number`; 0 shows every
+    /// container in the server's order
+    pub fn switch_workspace(&mut self, number: usize) {
+        if number == 0 {
+            self.filter = None;
+            self.sort = None;
+            self.group_by_tag = false;
+            self.workspace = None;
+            self.selected = 0;
+            return;
+        }
+
+        let Some(workspace) = self.workspaces.get(number - 1) else {
+            self.show_info(
+                format!(
+                    "No workspace {}. Save the current view with :workspace save NAME",
+                    number
+                ),
+                true,
+            );
+            return;
+        };
+        self.filter = workspace.filter.clone();
+        self.sort = workspace.sort;
+        self.group_by_tag = workspace.group_by_tag;
+        self.workspace = Some(number - 1);
+        self.selected = 0;
+    }
+
+    pub fn view_snapshot(&self) -> Snapshot {
+        Snapshot {
+            view: ViewState {
+                filter: self.filter.clone(),
+                sort: self.sort,
+                group_by_tag: self.group_by_tag,
+                workspace: self.workspace,
+                compare_marks: self.compare_marks.clone(),
+                picked: self.picked.clone(),
+                image_column: self.image_column,
+                show_sidebar: self.show_operation_sidebar,
+                show_events: self.show_events,
+            },
+            selected: self.selected,
+        }
+    }
+
+    /// Keep the view an action started from, if the action changed it
+    pub fn record_view_change(&mut self, before: Snapshot) {
+        if self.view_snapshot().view != before.view {
+            self.undo.record(before);
+        }
+    }
+
+    pub fn undo_view(&mut self) {
+        match self.undo.undo(self.view_snapshot()) {
+            Some(previous) => self.restore_view(previous, "Undone"),
+            None => self.notify("Nothing to undo".to_string()),
+        }
+    }
+
+    pub fn redo_view(&mut self) {
+        match self.undo.redo(self.view_snapshot()) {
+            Some(next) => self.restore_view(next, "Redone"),
+            None => self.notify("Nothing to redo".to_string()),
+        }
+    }
+
+    /// Go back to an earlier view, saying what changed
+    fn restore_view(&mut self, snapshot: Snapshot, verb: &str) {
+        let Snapshot { view, selected } = snapshot;
+        let changes = view.changes(&self.view_snapshot().view);
+        self.notify(if changes.is_empty() {
+            verb.to_string()
+        } else {
+            format!("{}: {}", verb, changes.join(", "))
+        });
+        if view.show_events != self.show_events {
+            self.toggle_events();
+        }
+        self.filter = view.filter;
+        self.sort = view.sort;
+        self.group_by_tag = view.group_by_tag;
+        self.workspace = view.workspace;
+        self.compare_marks = view.compare_marks;
+        self.picked = view.picked;
+        self.image_column = view.image_column;
+        self.show_operation_sidebar = view.show_sidebar;
+        self.selected = selected;
+    }
+
+    /// Say `text` in the title bar for a few seconds
+    pub fn notify(&mut self, text: String) {
+        self.notice = Some((text, Instant::now()));
+    }
+
+    /// The notice in the title bar, while it's still fresh; with reduced
+    /// motion it stays until the next key
+    pub fn notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, at)| self.reduced_motion || at.elapsed() < NOTICE_FOR)
+            .map(|(text, _)| text.as_str())
+    }
+
+    /// Clear the feedback a key press dismisses: the message in the list,
+    /// or with reduced motion, which leaves it until the next one replaces
+    /// it, the notice
+    pub fn key_pressed(&mut self) {
+        if self.reduced_motion {
+            self.notice = None;
+        } else if matches!(self.input_mode, InputMode::Normal) {
+            self.clear_message();
+        }
+    }
+
+    /// Switch to a workspace by name, ignoring case
+    pub fn open_workspace(&mut self, name: &str) {
+        match self
+            .workspaces
+            .iter()
+            .position(|w| w.name.eq_ignore_ascii_case(name))
+        {
+            Some(index) => self.switch_workspace(index + 1),
+            None => self.show_error(
+                format!("No workspace named '{}'", name),
+                format!(
+                    "Workspaces: {}",
+                    self.workspaces
+                        .iter()
+                        .map(|w| w.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                vec!["Save the current view with :workspace save NAME".to_string()],
+            ),
+        }
+    }
+
+    /// Save the current filter, sort and grouping as a workspace in the
+    /// config file, replacing one with the same name
+    pub fn save_workspace(&mut self, name: String) {
+        let workspace = Workspace {
+            name,
+            filter: self.filter.clone(),
+            sort: self.sort,
+            group_by_tag: self.group_by_tag,
+        };
+        let saved = Config::load().and_then(|mut config| {
+            config.upsert_workspace(workspace.to_config());
+            config.save()
+        });
+        if let Err(e) = saved {
+            self.show_error(
+                format!("Failed to save workspace '{}'", workspace.name),
+                e.to_string(),
+                vec![format!("Check {}", Config::path().display())],
+            );
+            return;
+        }
+
+        let index = match self
+            .workspaces
+            .iter()
+            .position(|w| w.name == workspace.name)
+        {
+            Some(index) => {
+                self.workspaces[index] = workspace;
+                index
+            }
+            None => {
+                self.workspaces.push(workspace);
+                self.workspaces.len() - 1
+            }
+        };
+        self.workspace = Some(index);
+        let key = if index < 9 {
+            format!(" (key {})", index + 1)
+        } else {
+            String::new()
+        };
+        self.show_success(format!(
+            "Saved workspace '{}'{}",
+            self.workspaces[index].name, key
+        ));
+    }
+
+    async fn visible_count(&self) -> usize {
+        self.visible(&self.containers.read().await).len()
+    }
+
+    pub async fn next(&mut self) {
+        let count = self.visible_count().await;
+        if count > 0 {
+            self.selected = (self.selected + 1) % count;
+        }
+    }
+
+    pub async fn previous(&mut self) {
+        let count = self.visible_count().await;
+        if count > 0 {
+            if self.selected > 0 {
+                self.selected -= 1;
+            } else {
+                self.selected = count - 1;
+            }
+        }
+    }
+
+    pub async fn get_selected_container(&self) -> Option<Container> {
+        let containers = self.containers.read().await;
+        self.visible(&containers).get(self.selected).cloned()
+    }
+
+    /// Show only containers matching `filter`, or all of them
+    pub fn set_filter(&mut self, filter: Option<ContainerFilter>) {
+        self.message = Some(match &filter {
+            Some(filter) => format!("Filter: {}", filter),
+            None => "Filter cleared".to_string(),
+        });
+        self.filter = filter;
+        self.workspace = None;
+        self.selected = 0;
+    }
+
+    /// Select a listed container by name, remote-qualified in all-remotes mode
+    pub async fn select_container(&mut self, name: &str) -> bool {
+        let containers = self.containers.read().await;
+        let position = self
+            .visible(&containers)
+            .iter()
+            .position(|c| c.qualified_name() == name || c.name == name);
+        drop(containers);
+
+        match position {
+            Some(index) => {
+                self.selected = index;
+                true
+            }
+            None => {
+                self.show_error(
+                    "Container not found".to_string(),
+                    format!("No listed container named '{}'", name),
+                    vec!["Check the name, or clear the filter with ':filter'".to_string()],
+                );
+                false
+            }
+        }
+    }
+
+    /// Turn down an action that would change something; an error, so a
+    /// script stops there
+    pub fn refuse_read_only(&mut self) {
+        self.show_error(
+            "Read-only mode".to_string(),
+            "LXTUI is running read-only, so actions that change instances or the server are disabled"
+                .to_string(),
+            vec!["Start LXTUI without --read-only and with read_only = false in the config file"
+                .to_string()],
+        );
+    }
+
+    /// Whether this deployment permits `permission`; when it doesn't, say
+    /// so with an error, which stops a script there
+    pub fn permits(&mut self, permission: Permission) -> bool {
+        if self.permissions.permits(permission) {
+            return true;
+        }
+        self.show_error(
+            "Not permitted".to_string(),
+            format!(
+                "'{}' is not permitted by the [permissions] section of the config file",
+                permission.name()
+            ),
+            vec!["Ask whoever manages this deployment's config file to allow it".to_string()],
+        );
+        false
+    }
+
+    /// Next script command, once the previous one has finished. An error
+    /// stops the script so later commands don't act on a failed state.
+    pub fn next_script_action(&mut self) -> Option<Action> {
+        if self.script.is_empty() {
+            return None;
+        }
+
+        match &self.input_mode {
+            InputMode::StatusModal(StatusModalType::Error { .. }) => {
+                warn!("Script stopped with {} commands left", self.script.len());
+                self.message = Some(format!(
+                    "Script stopped after an error; {} commands skipped",
+                    self.script.len()
+                ));
+                self.script.clear();
+                None
+            }
+            InputMode::Normal
+            | InputMode::StatusModal(StatusModalType::Info { .. })
+            | InputMode::StatusModal(StatusModalType::Success { .. })
+                if self.lxd_operations.is_empty() && self.background_tasks.is_empty() =>
+            {
+                self.script.pop_front()
+            }
+            _ => None,
+        }
+    }
+
+    /// Run a container action, tracking its LXD operation in the background
+    pub async fn execute_action(&mut self, action: ConfirmAction) {
+        // Immediately show progress modal BEFORE executing the action
+        let (operation_desc, container_name, action_str) = match &action {
+            ConfirmAction::StartContainer(name) => {
+                (format!("Start container '{}'", name), name.clone(), "start")
+            }
+            ConfirmAction::StopContainer(name) => {
+                (format!("Stop container '{}'", name), name.clone(), "stop")
+            }
+            ConfirmAction::RestartContainer(name) => (
+                format!("Restart container '{}'", name),
+                name.clone(),
+                "restart",
+            ),
+            ConfirmAction::DeleteContainer(name) => (
+                format!("Delete container '{}'", name),
+                name.clone(),
+                "delete",
+            ),
+        };
+
+        // Register UI operation and show progress modal immediately
+        let ui_operation_id = self.register_operation(operation_desc, Some(container_name.clone()));
+        self.show_progress(&ui_operation_id);
+
+        // Clear pending action since we're executing it
+        self.pending_action = None;
+
+        // Mark operation as started
+        self.start_operation(&ui_operation_id);
+
+        if action_str != "start" {
+            self.alerts.expect_stop(&container_name);
+            self.crashes.expect_stop(&container_name);
+        }
+
+        // Use the new non-blocking LXD operations, routed to the container's remote
+        let (client, name) = self.client_for(&container_name);
+        let lxd_operation_result = match action {
+            ConfirmAction::StartContainer(_) => client.start_container_async(&name).await,
+            ConfirmAction::StopContainer(_) => client.stop_container_async(&name).await,
+            ConfirmAction::RestartContainer(_) => client.restart_container_async(&name).await,
+            ConfirmAction::DeleteContainer(_) => client.delete_container_async(&name).await,
+        };
+
+        match lxd_operation_result {
+            Ok(lxd_operation_path) => {
+                let success_message = format!(
+                    "Container '{}' {} successfully",
+                    container_name,
+                    match action_str {
+                        "start" => "started",
+                        "stop" => "stopped",
+                        "restart" => "restarted",
+                        _ => "deleted",
+                    }
+                );
+                self.track_lxd_operation(
+                    &ui_operation_id,
+                    lxd_operation_path,
+                    container_name,
+                    action_str,
+                    success_message,
+                );
+            }
+            Err(e) => {
+                error!("Failed to start LXD operation: {:?}", e);
+                self.complete_operation(&ui_operation_id, false, Some(e.to_string()));
+                self.set_retry(&ui_operation_id, action.clone());
+                self.show_error(
+                    format!("Failed to {} '{}'", action_str, container_name),
+                    e.to_string(),
+                    vec!["Check if LXD is running".to_string()],
+                );
+            }
+        }
+    }
+
+    /// Follow an LXD operation from the main event loop, which finishes the
+    /// UI operation once LXD reports the outcome
+    fn track_lxd_operation(
+        &mut self,
+        ui_operation_id: &str,
+        lxd_operation_path: String,
+        container_name: String,
+        action: &str,
+        success_message: String,
+    ) {
+        info!("LXD operation started: {}", lxd_operation_path);
+        let description = self
+            .user_operations
+            .iter()
+            .find(|o| o.id == ui_operation_id)
+            .map(|o| o.description.clone())
+            .unwrap_or_default();
+
+        let tracker = LxdOperationTracker {
+            ui_operation_id: ui_operation_id.to_string(),
+            lxd_operation_path,
+            description,
+            container_name,
+            action: action.to_string(),
+            started_at: Instant::now(),
+            next_check: Instant::now() + self.polls.jittered(OPERATION_INTERVAL),
+            status_code: 103, // Running
+            progress: None,
+            success_message,
+        };
+        self.lxd_operations
+            .insert(ui_operation_id.to_string(), tracker);
+    }
+
+    /// Confirmation the configured policy requires for `action`
+    fn confirm_policy(&self, action: &ConfirmAction) -> ConfirmPolicy {
+        match action {
+            ConfirmAction::StartContainer(_) => self.confirmations.start,
+            ConfirmAction::StopContainer(_) => self.confirmations.stop,
+            ConfirmAction::RestartContainer(_) => self.confirmations.restart,
+            ConfirmAction::DeleteContainer(_) => self.confirmations.delete,
+        }
+    }
+
+    /// Ask for confirmation as the policy requires, or run the action directly.
+    /// In preview mode the request preview stands in for a simple confirmation.
+    pub async fn request_action(&mut self, message: String, action: ConfirmAction) {
+        match self.confirm_policy(&action) {
+            // Whatever the policy, a protected container gets the dialog
+            // offering to remove the protection rather than LXD's refusal
+            _ if self.is_protected(action.container_name()).await => {
+                let checks = self.preflight(&action).await;
+                self.show_confirm_dialog(message, action, checks)
+            }
+            ConfirmPolicy::None => self.run_or_preview(PendingAction::Container(action)).await,
+            ConfirmPolicy::Simple if self.preview_requests => {
+                self.pending_action = Some(action.clone());
+                self.input_mode = InputMode::Preview(PendingAction::Container(action));
+            }
+            _ => {
+                let checks = self.preflight(&action).await;
+                self.show_confirm_dialog(message, action, checks)
+            }
+        }
+    }
+
+    /// Pre-flight checks listed in the confirmation of `action`; only
+    /// deletes are checked
+    async fn preflight(&self, action: &ConfirmAction) -> Vec<Check> {
+        let ConfirmAction::DeleteContainer(target) = action else {
+            return Vec::new();
+        };
+        let (client, name) = self.client_for(target);
+        client.delete_checks(&name).await.unwrap_or_else(|e| {
+            vec![Check::Warning(format!(
+                "Couldn't check what the delete affects: {}",
+                e
+            ))]
+        })
+    }
+
+    /// Run or preview a create or clone once the `[space]` guard is happy
+    /// with how full its pool would get. A pool that can't be read doesn't
+    /// hold the action up.
+    pub async fn run_checking_space(&mut self, action: PendingAction) {
+        match self.space_verdict(&action).await {
+            Verdict::Fine => self.run_or_preview(action).await,
+            Verdict::Warn(message) => {
+                self.input_mode = InputMode::SpaceWarning { action, message };
+            }
+            Verdict::Block(message) => self.show_error(
+                "Not enough free space".to_string(),
+                message,
+                vec![
+                    "Free up space in the pool, or clone to another pool".to_string(),
+                    "Set guard = \"warn\" under [space] to be asked instead".to_string(),
+                ],
+            ),
+        }
+    }
+
+    async fn space_verdict(&self, action: &PendingAction) -> Verdict {
+        if self.space.guard == SpaceGuard::Off {
+            return Verdict::Fine;
+        }
+        let projection = match action {
+            PendingAction::CreateContainer => {
+                self.lxc_client
+                    .project_create(
+                        &self.wizard_data.image,
+                        self.create_defaults.pool.as_deref(),
+                    )
+                    .await
+            }
+            PendingAction::Clone {
+                source, options, ..
+            } => {
+                let (client, name) = self.client_for(source);
+                client.project_clone(&name, options.pool.as_deref()).await
+            }
+            _ => return Verdict::Fine,
+        };
+        match projection {
+            Ok(Some(projection)) => space::judge(&self.space, &projection),
+            Ok(None) => Verdict::Fine,
+            Err(e) => {
+                warn!("Couldn't check the free space for {:?}: {}", action, e);
+                Verdict::Fine
+            }
+        }
+    }
+
+    /// Whether `action` may run: not in read-only mode, and permitted. Views
+    /// reach their actions through here, so none can skip the checks.
+    fn may_run(&mut self, action: &PendingAction) -> bool {
+        if self.read_only {
+            self.refuse_read_only();
+            return false;
+        }
+        self.permits(action.permission())
+    }
+
+    /// Show the API request for `action` first when preview mode is on
+    pub async fn run_or_preview(&mut self, action: PendingAction) {
+        if !self.may_run(&action) {
+            return;
+        }
+        if self.preview_requests {
+            self.input_mode = InputMode::Preview(action);
+        } else {
+            self.run_action(action).await;
+        }
+    }
+
+    pub async fn run_action(&mut self, action: PendingAction) {
+        if !self.may_run(&action) {
+            return;
+        }
+        match action {
+            PendingAction::Container(action) => self.execute_action(action).await,
+            PendingAction::Clone {
+                source,
+                destination,
+                options,
+            } => self.clone_container(&source, &destination, &options).await,
+            PendingAction::RefreshCopy {
+                source,
+                destination,
+            } => self.refresh_copy(&source, &destination).await,
+            PendingAction::CreateContainer => self.create_container().await,
+            PendingAction::CreateBackup(container) => {
+                self.return_to_backups(&container);
+                self.create_backup(&container).await;
+            }
+            PendingAction::DeleteBackup { container, backup } => {
+                self.return_to_backups(&container);
+                self.delete_backup(&container, &backup).await;
+            }
+            PendingAction::CreateSnapshot {
+                container,
+                snapshot,
+            } => {
+                self.input_mode = InputMode::Normal;
+                self.create_snapshot(&container, snapshot.as_deref()).await;
+            }
+            PendingAction::SetTags { container, tags } => {
+                self.input_mode = InputMode::Normal;
+                self.set_tags(&container, &tags).await;
+            }
+            PendingAction::SetNotes { container, notes } => {
+                self.input_mode = InputMode::Normal;
+                self.set_notes(&container, &notes).await;
+            }
+            PendingAction::SetBootConfig {
+                container,
+                key,
+                value,
+            } => {
+                self.input_mode = InputMode::BootOrder;
+                self.set_boot_config(&container, key, &value).await;
+            }
+            PendingAction::UpdateConfig { container, config } => {
+                self.input_mode = InputMode::Normal;
+                self.update_instance_config(&container, &config).await;
+            }
+            PendingAction::ChangeMedia {
+                container,
+                change,
+                config,
+            } => {
+                self.input_mode = InputMode::Normal;
+                self.change_media(&container, &change, &config).await;
+            }
+            PendingAction::SetVmSetting {
+                container,
+                knob,
+                value,
+                config,
+            } => {
+                self.input_mode = InputMode::VmSettings;
+                self.set_vm_setting(&container, knob, &value, &config).await;
+            }
+            PendingAction::ChangeIdmap {
+                container,
+                change,
+                config,
+            } => {
+                self.input_mode = InputMode::Idmap;
+                self.change_idmap(&container, &change, &config).await;
+            }
+            PendingAction::UpdateMetadata {
+                container,
+                metadata,
+            } => {
+                self.input_mode = InputMode::Templates;
+                self.update_metadata(&container, &metadata).await;
+            }
+            PendingAction::SetTemplate {
+                container,
+                template,
+                content,
+            } => {
+                self.input_mode = InputMode::Templates;
+                self.set_template(&container, &template, &content).await;
+            }
+            PendingAction::Recreate(plan) => {
+                self.input_mode = InputMode::Normal;
+                self.recreate_instance(&plan).await;
+            }
+            PendingAction::ChangeProfiles { change, changes } => {
+                self.input_mode = InputMode::Profiles;
+                self.change_profiles(&change, changes).await;
+            }
+            PendingAction::Rebuild {
+                container,
+                fingerprint,
+            } => {
+                self.return_to_audit();
+                self.rebuild_instance(&container, &fingerprint).await;
+            }
+            PendingAction::ChangeZone(change) => {
+                self.return_to_zones();
+                self.change_zone(&change).await;
+            }
+            PendingAction::SetImageAutoUpdate {
+                fingerprint,
+                description,
+                auto_update,
+            } => {
+                self.return_to_image_store();
+                self.set_image_auto_update(&fingerprint, &description, auto_update)
+                    .await;
+            }
+            PendingAction::RefreshImage {
+                fingerprint,
+                description,
+            } => {
+                self.return_to_image_store();
+                self.refresh_image(&fingerprint, &description).await;
+            }
+            PendingAction::Publish { container, publish } => {
+                self.input_mode = InputMode::Normal;
+                self.publish_instance(&container, &publish).await;
+            }
+        }
+    }
+
+    /// Leave a preview, going back to the backups view for backup actions
+    pub fn cancel_preview(&mut self, action: &PendingAction) {
+        match action {
+            PendingAction::CreateBackup(container)
+            | PendingAction::DeleteBackup { container, .. } => self.return_to_backups(container),
+            PendingAction::SetBootConfig { .. } => self.input_mode = InputMode::BootOrder,
+            PendingAction::SetVmSetting { .. } => self.input_mode = InputMode::VmSettings,
+            PendingAction::ChangeIdmap { .. } => self.input_mode = InputMode::Idmap,
+            PendingAction::UpdateMetadata { .. } | PendingAction::SetTemplate { .. } => {
+                self.input_mode = InputMode::Templates
+            }
+            PendingAction::Rebuild { .. } => self.return_to_audit(),
+            PendingAction::Recreate(_) => self.input_mode = InputMode::Recreate,
+            PendingAction::ChangeProfiles { .. } => self.input_mode = InputMode::Profiles,
+            PendingAction::ChangeZone(_) => self.return_to_zones(),
+            PendingAction::SetImageAutoUpdate { .. } | PendingAction::RefreshImage { .. } => {
+                self.return_to_image_store()
+            }
+            _ => self.cancel_dialog(),
+        }
+    }
+
+    fn return_to_backups(&mut self, container: &str) {
+        self.input_mode = InputMode::Backups {
+            container: container.to_string(),
+            confirm_delete: false,
+        };
+    }
+
+    /// The remote an action targets and the exact request it will send
+    pub fn api_request(&self, action: &PendingAction) -> (String, ApiRequest) {
+        let target = match action {
+            PendingAction::Container(action) => action.container_name(),
+            PendingAction::Clone { source, .. } | PendingAction::RefreshCopy { source, .. } => {
+                source
+            }
+            PendingAction::CreateContainer
+            | PendingAction::ChangeZone(_)
+            | PendingAction::SetImageAutoUpdate { .. }
+            | PendingAction::RefreshImage { .. } => "",
+            PendingAction::CreateBackup(container)
+            | PendingAction::DeleteBackup { container, .. }
+            | PendingAction::CreateSnapshot { container, .. }
+            | PendingAction::SetTags { container, .. }
+            | PendingAction::SetNotes { container, .. }
+            | PendingAction::SetBootConfig { container, .. }
+            | PendingAction::UpdateConfig { container, .. }
+            | PendingAction::ChangeMedia { container, .. }
+            | PendingAction::SetVmSetting { container, .. }
+            | PendingAction::ChangeIdmap { container, .. }
+            | PendingAction::UpdateMetadata { container, .. }
+            | PendingAction::SetTemplate { container, .. }
+            | PendingAction::Rebuild { container, .. }
+            | PendingAction::Publish { container, .. } => container,
+            PendingAction::Recreate(plan) => &plan.source,
+            // The preview shows the first of the requests
+            PendingAction::ChangeProfiles { changes, .. } => {
+                changes.first().map_or("", |(name, _)| name.as_str())
+            }
+        };
+
+        let (remote, name) = match target.split_once(':') {
+            Some((remote, name)) => (remote.to_string(), name),
+            None => (
+                self.active_remote.as_deref().unwrap_or("local").to_string(),
+                target,
+            ),
+        };
+
+        let request = match action {
+            PendingAction::Container(ConfirmAction::StartContainer(_)) => {
+                ApiRequest::instance_state(name, "start")
+            }
+            PendingAction::Container(ConfirmAction::StopContainer(_)) => {
+                ApiRequest::instance_state(name, "stop")
+            }
+            PendingAction::Container(ConfirmAction::RestartContainer(_)) => {
+                ApiRequest::instance_state(name, "restart")
+            }
+            PendingAction::Container(ConfirmAction::DeleteContainer(_)) => {
+                ApiRequest::delete_instance(name)
+            }
+            PendingAction::Clone {
+                destination,
+                options,
+                ..
+            } => ApiRequest::clone_instance(name, destination, options),
+            PendingAction::RefreshCopy { destination, .. } => {
+                ApiRequest::copy_instance(name, destination, true)
+            }
+            PendingAction::CreateContainer => ApiRequest::create_instance(
+                &self.wizard_data.name,
+                &self.wizard_data.image,
+                self.wizard_data.is_vm,
+                self.wizard_data.media.as_ref(),
+                &self.create_defaults,
+            ),
+            PendingAction::CreateBackup(_) => ApiRequest::create_backup(name),
+            PendingAction::DeleteBackup { backup, .. } => ApiRequest::delete_backup(name, backup),
+            PendingAction::CreateSnapshot { snapshot, .. } => {
+                ApiRequest::create_snapshot(name, snapshot.as_deref())
+            }
+            PendingAction::SetTags { tags, .. } => {
+                ApiRequest::set_instance_config(name, tags::CONFIG_KEY, &tags::join(tags))
+            }
+            PendingAction::SetNotes { notes, .. } => {
+                ApiRequest::set_instance_config(name, notes::CONFIG_KEY, notes)
+            }
+            PendingAction::SetBootConfig { key, value, .. } => {
+                ApiRequest::set_instance_config(name, key, value)
+            }
+            PendingAction::UpdateConfig { config, .. }
+            | PendingAction::ChangeMedia { config, .. }
+            | PendingAction::SetVmSetting { config, .. }
+            | PendingAction::ChangeIdmap { config, .. } => {
+                ApiRequest::update_instance(name, config.to_json())
+            }
+            PendingAction::UpdateMetadata { metadata, .. } => {
+                ApiRequest::update_metadata(name, metadata.to_json())
+            }
+            PendingAction::SetTemplate {
+                template, content, ..
+            } => ApiRequest::set_template(name, template, content),
+            PendingAction::Rebuild { fingerprint, .. } => {
+                ApiRequest::rebuild_instance(name, fingerprint)
+            }
+            PendingAction::Recreate(plan) => ApiRequest::recreate_instance(plan),
+            PendingAction::ChangeProfiles { changes, .. } => {
+                let profiles = changes
+                    .first()
+                    .map(|(_, p)| p.as_slice())
+                    .unwrap_or_default();
+                ApiRequest::set_profiles(name, profiles)
+            }
+            PendingAction::ChangeZone(change) => change.request(),
+            PendingAction::SetImageAutoUpdate {
+                fingerprint,
+                auto_update,
+                ..
+            } => ApiRequest::set_image_auto_update(fingerprint, *auto_update),
+            PendingAction::RefreshImage { fingerprint, .. } => {
+                ApiRequest::refresh_image(fingerprint)
+            }
+            PendingAction::Publish { publish, .. } => ApiRequest::publish_instance(name, publish),
+        };
+
+        (remote, request)
+    }
+
+    pub fn toggle_preview_requests(&mut self) {
+        self.preview_requests = !self.preview_requests;
+        let state = if self.preview_requests { "on" } else { "off" };
+        self.show_info(format!("API preview mode {}", state), true);
+    }
+
+    pub fn show_confirm_dialog(
+        &mut self,
+        message: String,
+        action: ConfirmAction,
+        checks: Vec<Check>,
+    ) {
+        let typed_name = match &action {
+            ConfirmAction::StartContainer(name)
+            | ConfirmAction::StopContainer(name)
+            | ConfirmAction::RestartContainer(name)
+            | ConfirmAction::DeleteContainer(name)
+                if self.confirm_policy(&action) == ConfirmPolicy::Typed =>
+            {
+                Some(name.clone())
+            }
+            _ => None,
+        };
+
+        self.input_buffer.clear();
+        self.pending_action = Some(action.clone());
+        self.input_mode = InputMode::Confirmation {
+            message,
+            action,
+            typed_name,
+            checks,
+        };
+    }
+
+    pub fn show_status_modal(&mut self, modal_type: StatusModalType) {
+        self.input_mode = InputMode::StatusModal(modal_type);
+    }
+
+    /// Follow an operation in the progress modal
+    pub fn show_progress(&mut self, operation_id: &str) {
+        self.show_status_modal(StatusModalType::Progress {
+            operation_id: operation_id.to_string(),
+            confirm_cancel: false,
+        });
+    }
+
+    /// Ask before cancelling the operation in the progress modal, or stop
+    /// asking
+    pub fn confirm_cancel_progress(&mut self, confirm: bool) {
+        if let InputMode::StatusModal(StatusModalType::Progress { confirm_cancel, .. }) =
+            &mut self.input_mode
+        {
+            *confirm_cancel = confirm;
+        }
+    }
+
+    /// Close the progress modal and leave the operation running; the
+    /// sidebar and title bar keep following it
+    pub fn run_in_background(&mut self) {
+        if let InputMode::StatusModal(StatusModalType::Progress { operation_id, .. }) =
+            &self.input_mode
+        {
+            info!("Running {} in the background", operation_id);
+            self.input_mode = InputMode::Normal;
+        }
+    }
+
+    pub fn show_command_menu(&mut self, menu: CommandMenu) {
+        self.menu_selected = 0; // Reset selection when opening menu
+        self.input_mode = InputMode::CommandMenu(menu);
+    }
+
+    pub fn menu_next(&mut self, item_count: usize) {
+        if item_count > 0 {
+            self.menu_selected = (self.menu_selected + 1) % item_count;
+        }
+    }
+
+    pub fn menu_previous(&mut self, item_count: usize) {
+        if item_count > 0 {
+            if self.menu_selected > 0 {
+                self.menu_selected -= 1;
+            } else {
+                self.menu_selected = item_count - 1;
+            }
+        }
+    }
+
+    pub fn show_info(&mut self, message: String, auto_close: bool) {
+        self.show_status_modal(StatusModalType::Info {
+            message,
+            auto_close: auto_close && self.dismiss_timeout().is_some(),
+            started_at: Instant::now(),
+        });
+    }
+
+    /// How long dialogs that close by themselves stay open, or None if
+    /// they wait for a key
+    pub fn dismiss_timeout(&self) -> Option<Duration> {
+        self.dismiss_after.filter(|_| !self.reduced_motion)
+    }
+
+    /// Close a success dialog, or an information dialog that closes by
+    /// itself, once it has been open for the configured time. Called from
+    /// the main loop so it closes without waiting for a key.
+    pub fn dismiss_expired_modal(&mut self, now: Instant) {
+        let Some(timeout) = self.dismiss_timeout() else {
+            return;
+        };
+        let started_at = match &self.input_mode {
+            InputMode::StatusModal(StatusModalType::Info {
+                auto_close: true,
+                started_at,
+                ..
+            })
+            | InputMode::StatusModal(StatusModalType::Success { started_at, .. }) => *started_at,
+            _ => return,
+        };
+        if now.duration_since(started_at) >= timeout {
+            self.input_mode = InputMode::Normal;
+        }
+    }
+
+    pub fn show_error(&mut self, title: String, details: String, suggestions: Vec<String>) {
+        let request = lxd_api::take_failed_request();
+        let report = ErrorReport {
+            action: title.clone(),
+            container: request
+                .as_ref()
+                .and_then(|r| r.instance())
+                .map(str::to_string),
+            remote: request
+                .as_ref()
+                .map_or(self.active_remote.clone(), |r| r.remote.clone()),
+            request: request.map(|r| format!("{} {}", r.method, r.path)),
+            error: details.clone(),
+            lxd_version: self.lxd_version.clone(),
+            time: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        };
+
+        self.show_status_modal(StatusModalType::Error {
+            title,
+            details,
+            suggestions,
+            report: Box::new(report),
+        });
+    }
+
+    /// Copy the error report to the clipboard, saving it to a file if that fails
+    pub fn copy_error_report(&mut self, report: &ErrorReport) {
+        match report.copy_to_clipboard() {
+            Ok(tool) => self.show_info(format!("Error report copied with {}", tool), true),
+            Err(e) => {
+                warn!("Failed to copy error report: {}", e);
+                self.save_error_report(report);
+            }
+        }
+    }
+
+    /// Write the active server's inventory into the configured directory
+    pub async fn export_inventory(&mut self) {
+        let server = self
+            .active_remote
+            .clone()
+            .unwrap_or_else(|| "local".to_string());
+        let InventoryConfig { format, stable, .. } = self.inventory;
+        let dir = self.inventory.inventory_dir();
+        let now = chrono::Local::now();
+        let saved = match self.lxc_client.inventory(&server, stable).await {
+            Ok(mut inventory) => {
+                let stamp = (!stable).then(|| now.format("%Y%m%d-%H%M%S").to_string());
+                if !stable {
+                    inventory.exported_at = Some(now.to_rfc3339());
+                }
+                let counts = format!(
+                    "instances {}, profiles {}, networks {}, storage pools {}, images {}",
+                    inventory.instances.len(),
+                    inventory.profiles.len(),
+                    inventory.networks.len(),
+                    inventory.storage_pools.len(),
+                    inventory.images.len()
+                );
+                inventory
+                    .save(&dir, format, stamp.as_deref())
+                    .map(|path| (path, counts))
+                    .map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e.to_string()),
+        };
+        match saved {
+            Ok((path, counts)) => self.show_success(format!(
+                "Saved the inventory of {} to {} ({})",
+                server,
+                path.display(),
+                counts
+            )),
+            Err(e) => {
+                error!("Failed to export the inventory of {}: {}", server, e);
+                self.show_error(
+                    format!("Failed to export the inventory of {}", server),
+                    e,
+                    vec![format!(
+                        "Check that {} can be written, or set [inventory] dir",
+                        dir.display()
+                    )],
+                );
+            }
+        }
+    }
+
+    /// Read the active server's usage into the ledger when a sample is due
+    pub async fn poll_usage(&mut self) {
+        let now = Instant::now();
+        if !self.usage.due(now) || !self.polls.try_take(Category::Views, now) {
+            return;
+        }
+        self.usage.sampled(now);
+        if let Err(e) = self.sample_usage().await {
+            debug!("Failed to sample usage: {}", e);
+        }
+    }
+
+    async fn sample_usage(&mut self) -> Result<(), LxcError> {
+        let readings = self.lxc_client.usage_readings().await?;
+        let server = self.inventory_server();
+        self.usage.record(&server, &readings, chrono::Local::now());
+        Ok(())
+    }
+
+    /// Sample the active server once more, then write what each instance
+    /// used this session into the configured directory
+    pub async fn export_usage(&mut self) {
+        let dir = self.usage_config.usage_dir();
+        let now = chrono::Local::now();
+        let sampled = self.sample_usage().await;
+        if self.usage.is_empty() {
+            let reason = match sampled {
+                Ok(()) => "The server reported no instances".to_string(),
+                Err(e) => e.to_string(),
+            };
+            self.show_error(
+                "No usage to report".to_string(),
+                reason,
+                vec![
+                    "Usage is read from /1.0/metrics, which only covers running instances"
+                        .to_string(),
+                ],
+            );
+            return;
+        }
+        if let Err(e) = sampled {
+            warn!("Reporting usage without a last sample: {}", e);
+        }
+
+        match self
+            .usage
+            .save(&dir, &now.format("%Y%m%d-%H%M%S").to_string())
+        {
+            Ok(path) => self.show_success(format!(
+                "Saved the usage of {} instance{} to {}",
+                self.usage.len(),
+                if self.usage.len() == 1 { "" } else { "s" },
+                path.display()
+            )),
+            Err(e) => {
+                error!("Failed to save the usage report: {}", e);
+                self.show_error(
+                    "Failed to save the usage report".to_string(),
+                    e.to_string(),
+                    vec![format!(
+                        "Check that {} can be written, or set [usage] dir",
+                        dir.display()
+                    )],
+                );
+            }
+        }
+    }
+
+    /// The active server's name in inventory exports
+    fn inventory_server(&self) -> String {
+        self.active_remote
+            .clone()
+            .unwrap_or_else(|| "local".to_string())
+    }
+
+    /// Ask for an exported inventory to compare the server with, offering
+    /// the one open or the newest export of the server
+    pub fn start_drift_check(&mut self) {
+        let dir = self.inventory.inventory_dir();
+        let file = match &self.drift {
+            Some(view) => Some(view.file.clone()),
+            None => drift::latest_export(&dir, &self.inventory_server()),
+        };
+        let suggested = file.unwrap_or_else(|| dir.join(""));
+        self.input_mode = InputMode::Input {
+            prompt: "Inventory file to compare with:".to_string(),
+            input_type: InputType::Path,
+            callback_action: InputCallback::DriftFile,
+        };
+        self.input_buffer.set(&suggested.to_string_lossy());
+    }
+
+    pub async fn submit_drift_file(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            self.cancel_input();
+            return;
+        }
+        let file = match (text.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(text),
+        };
+        self.input_buffer.clear();
+        self.drift = Some(DriftView::new(file, self.inventory_server()));
+        self.input_mode = InputMode::Drift;
+        self.reload_drift().await;
+    }
+
+    /// Read the file again and compare it with the server as it is now
+    pub async fn reload_drift(&mut self) {
+        let Some(view) = &self.drift else {
+            return;
+        };
+        let compared = match drift::load(&view.file) {
+            Ok(then) => self
+                .lxc_client
+                .inventory(&view.server, true)
+                .await
+                .map(|now| (then, now))
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        let Some(view) = &mut self.drift else {
+            return;
+        };
+        match compared {
+            Ok((then, now)) => view.set_inventories(&then, &now),
+            Err(e) => {
+                error!("Failed to compare with {}: {}", view.file.display(), e);
+                view.error = Some(e);
+            }
+        }
+    }
+
+    /// Save the report next to the log; when that fails the error stays
+    /// open, so it can still be read or copied
+    pub fn save_error_report(&mut self, report: &ErrorReport) {
+        match report.save() {
+            Ok(path) => self.show_info(format!("Error report saved to {}", path.display()), false),
+            Err(e) => {
+                error!("Failed to save error report: {}", e);
+                self.notify(format!("Failed to save error report: {}", e));
+            }
+        }
+    }
+
+    pub fn show_success(&mut self, message: String) {
+        self.show_status_modal(StatusModalType::Success {
+            message,
+            started_at: Instant::now(),
+        });
+    }
+
+    pub async fn start_selected(&mut self) {
+        if let Some(container) = self.get_selected_container().await {
+            let name = container.qualified_name();
+            self.request_action(
+                format!("Start container '{}'?", name),
+                ConfirmAction::StartContainer(name),
+            )
+            .await;
+        }
+    }
+
+    // execute_pending_action has been removed - the logic is now in execute_action, called from
+    // Action::Confirm in action.rs to ensure immediate UI updates when the user confirms an action
+
+    pub async fn _unused_execute_pending_action(&mut self) {
+        if let Some(action) = self.pending_action.clone() {
+            self.pending_action = None;
+
+            // This method is kept for reference but not used
+            match action {
+                ConfirmAction::StartContainer(name) => {
+                    let operation_id = self.register_operation(
+                        format!("Start container '{}'", name),
+                        Some(name.clone()),
+                    );
+
+                    self.show_progress(&operation_id);
+                    self.start_operation(&operation_id);
+
+                    match self.lxc_client.start_container(&name).await {
+                        Ok(_) => {
+                            self.complete_operation(&operation_id, true, None);
+                            self.show_success(format!("Container '{}' started successfully", name));
+                            let _ = self.refresh_containers().await;
+                        }
+                        Err(e) => {
+                            error!("Failed to start container {}: {:?}", name, e);
+                            self.complete_operation(&operation_id, false, Some(e.to_string()));
+                            self.show_error(
+                                format!("Failed to start '{}'", name),
+                                e.to_string(),
+                                vec![
+                                    "Check if the container exists".to_string(),
+                                    "Verify LXD service is running".to_string(),
+                                    "Check container logs with 'lxc info'".to_string(),
+                                ],
+                            );
+                        }
+                    }
+                }
+                ConfirmAction::StopContainer(name) => {
+                    let operation_id = self.register_operation(
+                        format!("Stop container '{}'", name),
+                        Some(name.clone()),
+                    );
+
+                    self.show_progress(&operation_id);
+                    self.start_operation(&operation_id);
+
+                    match self.lxc_client.stop_container(&name).await {
+                        Ok(_) => {
+                            self.complete_operation(&operation_id, true, None);
+                            self.show_success(format!("Container '{}' stopped successfully", name));
+                            let _ = self.refresh_containers().await;
+                        }
+                        Err(e) => {
+                            error!("Failed to stop container {}: {:?}", name, e);
+                            self.complete_operation(&operation_id, false, Some(e.to_string()));
+                            self.show_error(
+                                format!("Failed to stop '{}'", name),
+                                e.to_string(),
+                                vec![
+                                    "Try force stopping with 'lxc stop -f'".to_string(),
+                                    "Check if processes are hung inside container".to_string(),
+                                ],
+                            );
+                        }
+                    }
+                }
+                ConfirmAction::RestartContainer(name) => {
+                    let operation_id = self.register_operation(
+                        format!("Restart container '{}'", name),
+                        Some(name.clone()),
+                    );
+
+                    self.show_progress(&operation_id);
+                    self.start_operation(&operation_id);
+
+                    match self.lxc_client.restart_container(&name).await {
+                        Ok(_) => {
+                            self.complete_operation(&operation_id, true, None);
+                            self.show_success(format!(
+                                "Container '{}' restarted successfully",
+                                name
+                            ));
+                            let _ = self.refresh_containers().await;
+                        }
+                        Err(e) => {
+                            error!("Failed to restart container {}: {:?}", name, e);
+                            self.complete_operation(&operation_id, false, Some(e.to_string()));
+                            self.show_error(
+                                format!("Failed to restart '{}'", name),
+                                e.to_string(),
+                                vec![
+                                    "Check container status first".to_string(),
+                                    "Try stopping then starting manually".to_string(),
+                                ],
+                            );
+                        }
+                    }
+                }
+                ConfirmAction::DeleteContainer(name) => {
+                    let operation_id = self.register_operation(
+                        format!("Delete container '{}'", name),
+                        Some(name.clone()),
+                    );
+
+                    self.show_progress(&operation_id);
+                    self.start_operation(&operation_id);
+
+                    match self.lxc_client.delete_container(&name).await {
+                        Ok(_) => {
+                            self.complete_operation(&operation_id, true, None);
+                            self.show_success(format!("Container '{}' deleted successfully", name));
+                            let _ = self.refresh_containers().await;
+                        }
+                        Err(e) => {
+                            error!("Failed to delete container {}: {:?}", name, e);
+                            self.complete_operation(&operation_id, false, Some(e.to_string()));
+                            self.show_error(
+                                format!("Failed to delete '{}'", name),
+                                e.to_string(),
+                                vec![
+                                    "Stop the container first if it's running".to_string(),
+                                    "Check for dependent snapshots".to_string(),
+                                ],
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn stop_selected(&mut self) {
+        if let Some(container) = self.get_selected_container().await {
+            let name = container.qualified_name();
+            self.request_action(
+                format!("Stop container '{}'?", name),
+                ConfirmAction::StopContainer(name),
+            )
+            .await;
+        }
+    }
+
+    pub async fn restart_selected(&mut self) {
+        if let Some(container) = self.get_selected_container().await {
+            let name = container.qualified_name();
+            self.request_action(
+                format!("Restart container '{}'?", name),
+                ConfirmAction::RestartContainer(name),
+            )
+            .await;
+        }
+    }
+
+    pub async fn delete_selected(&mut self) {
+        if let Some(container) = self.get_selected_container().await {
+            let name = container.qualified_name();
+            self.request_action(delete_message(&name), ConfirmAction::DeleteContainer(name))
+                .await;
+        }
+    }
+
+    /// Delete from the list's shortcut, which protected containers don't
+    /// take: their protection is removed from the actions menu first
+    pub async fn quick_delete(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        if container.protected {
+            self.show_error(
+                format!("'{}' is protected", container.qualified_name()),
+                format!(
+                    "{} is set, so LXD refuses to delete it",
+                    preflight::PROTECTION_KEY
+                ),
+                vec![
+                    "Choose Delete in the actions menu (Enter), then P to remove the protection"
+                        .to_string(),
+                ],
+            );
+        } else {
+            self.input_mode = InputMode::Normal;
+            self.delete_selected().await;
+        }
+    }
+
+    /// Whether the listed `container` is protected from deletion
+    async fn is_protected(&self, container: &str) -> bool {
+        self.containers
+            .read()
+            .await
+            .iter()
+            .any(|c| c.qualified_name() == container && c.protected)
+    }
+
+    /// Unset `security.protection.delete` of a container whose delete was
+    /// refused, then ask to delete it again. When a profile protects it,
+    /// the container's own config overrides that with "false".
+    pub async fn remove_protection(&mut self, container: &str) {
+        let (client, name) = self.client_for(container);
+        let unprotect = async {
+            let instance = client.get_instance(&name).await?;
+            if instance.config.contains_key(preflight::PROTECTION_KEY) {
+                client
+                    .set_config(&name, preflight::PROTECTION_KEY, "")
+                    .await?;
+            }
+            if preflight::instance_protected(&client.get_instance(&name).await?) {
+                client
+                    .set_config(&name, preflight::PROTECTION_KEY, "false")
+                    .await?;
+            }
+            Ok::<bool, LxcError>(preflight::instance_protected(
+                &client.get_instance(&name).await?,
+            ))
+        };
+        match unprotect.await {
+            Ok(false) => {}
+            Ok(true) => {
+                self.show_error(
+                    format!("'{}' is still protected", container),
+                    format!(
+                        "{} is still true in its expanded config",
+                        preflight::PROTECTION_KEY
+                    ),
+                    vec!["Check the profiles of the instance".to_string()],
+                );
+                return;
+            }
+            Err(e) => {
+                error!("Failed to remove the protection of {}: {:?}", container, e);
+                self.show_error(
+                    format!("Failed to remove the protection of '{}'", container),
+                    e.to_string(),
+                    vec!["Check that you may edit the instance's config".to_string()],
+                );
+                return;
+            }
+        }
+        info!("Removed the delete protection of {}", container);
+        let _ = self.refresh_containers().await;
+        let action = ConfirmAction::DeleteContainer(container.to_string());
+        let checks = self.preflight(&action).await;
+        self.show_confirm_dialog(delete_message(container), action, checks);
+    }
+
+    /// Quit to a shell in the selected container (local containers only)
+    pub async fn exec_selected(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+
+        if container.remote.is_some() || self.active_remote.is_some() {
+            self.show_error(
+                "Exec not available".to_string(),
+                format!(
+                    "'{}' is on a remote; shell access is only supported for local containers",
+                    container.qualified_name()
+                ),
+                vec!["Use 'lxc exec <remote>:<name>' from your shell".to_string()],
+            );
+        } else if container.status == "Running" {
+            self.exec_container = Some(container.name.clone());
+            self.should_quit = true;
+            info!("Exec requested for container: {}", container.name);
+        } else {
+            self.show_error(
+                "Container not running".to_string(),
+                format!(
+                    "Container '{}' must be running to exec into it",
+                    container.name
+                ),
+                vec!["Start the container first".to_string()],
+            );
+        }
+    }
+
+    /// SSH to the selected container's IPv4 address with the TUI suspended,
+    /// for VMs and images without the LXD agent
+    pub async fn ssh_selected(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+
+        match hooks::ssh_command(&container) {
+            Ok(command) => {
+                info!("SSH to {}: {}", container.qualified_name(), command);
+                self.external_command = Some(ExternalCommand {
+                    name: format!("SSH to '{}'", container.qualified_name()),
+                    command,
+                    pause: false,
+                });
+            }
+            Err(e) => self.show_error(
+                "SSH not available".to_string(),
+                e.to_string(),
+                vec![
+                    "Start the container and wait for it to get an address".to_string(),
+                    "Refresh the list to pick up a new address".to_string(),
+                ],
+            ),
+        }
+    }
+
+    /// Drop to a shell on this machine until it exits, for checks such as
+    /// `df` or `zpool status` without another terminal
+    pub fn open_host_shell(&mut self) {
+        let command = hooks::host_shell(std::env::var("SHELL").ok().as_deref());
+        info!("Host shell: {}", command);
+        self.external_command = Some(ExternalCommand {
+            name: "Host shell".to_string(),
+            command,
+            pause: false,
+        });
+    }
+
+    /// Copy a `code --remote` command and SSH config stanza for the selected
+    /// container, showing them as well for when there is no clipboard
+    pub async fn copy_editor_remote(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+
+        match hooks::editor_remote(&container) {
+            Ok(remote) => {
+                let text = remote.to_text();
+                let copied = match report::copy_to_clipboard(&text) {
+                    Ok(tool) => format!("Copied with {}:", tool),
+                    Err(e) => {
+                        warn!("Failed to copy the editor remote: {}", e);
+                        format!("Not copied ({}):", e)
+                    }
+                };
+                self.show_info(format!("{}\n\n{}", copied, text), false);
+            }
+            Err(e) => self.show_error(
+                "Editor remote not available".to_string(),
+                e.to_string(),
+                vec![
+                    "Start the container and wait for it to get an address".to_string(),
+                    "Refresh the list to pick up a new address".to_string(),
+                ],
+            ),
+        }
+    }
+
+    /// Hand a custom action's command for the selected container to the
+    /// event loop, which suspends the TUI to run it
+    pub async fn run_custom_action(&mut self, index: usize) {
+        let (Some(action), Some(container)) = (
+            self.custom_actions.get(index).cloned(),
+            self.get_selected_container().await,
+        ) else {
+            return;
+        };
+
+        match hooks::expand(&action.command, &container) {
+            Ok(command) => {
+                info!("Running custom action '{}': {}", action.name, command);
+                self.external_command = Some(ExternalCommand {
+                    name: action.name,
+                    command,
+                    pause: action.pause,
+                });
+            }
+            Err(e) => self.show_error(
+                format!("Cannot run '{}'", action.name),
+                e.to_string(),
+                vec!["Check the command under [[actions]] in the config file".to_string()],
+            ),
+        }
+    }
+
+    /// Whether `feature` can be used, explaining why not when the server
+    /// lacks it
+    pub fn feature_available(&mut self, feature: Feature) -> bool {
+        match self.unavailable.reason(feature) {
+            Some(reason) => {
+                let message = format!(
+                    "{} isn't available on this server: {}",
+                    feature.name(),
+                    reason
+                );
+                self.show_info(message, true);
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Disable `feature` for the session when `error` says the server lacks
+    /// it, returning false for any other error
+    fn note_unsupported(&mut self, feature: Feature, error: &LxcError) -> bool {
+        let LxcError::Unsupported(reason) = error else {
+            return false;
+        };
+        warn!("{} unavailable on this server: {}", feature.name(), reason);
+        self.unavailable.mark(feature, reason.clone());
+        true
+    }
+
+    pub async fn open_backups(&mut self) {
+        if !self.feature_available(Feature::Backups) {
+            return;
+        }
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        let name = container.qualified_name();
+
+        self.backup_selected = 0;
+        self.message = None;
+        if self.reload_backups(&name).await {
+            self.input_mode = InputMode::Backups {
+                container: name,
+                confirm_delete: false,
+            };
+        }
+    }
+
+    /// Fetch the backup list, returning false (with an error shown) on failure
+    pub async fn reload_backups(&mut self, container: &str) -> bool {
+        let (client, name) = self.client_for(container);
+        match client.list_backups(&name).await {
+            Ok(backups) => {
+                self.backups = backups;
+                if self.backup_selected >= self.backups.len() {
+                    self.backup_selected = self.backups.len().saturating_sub(1);
+                }
+                true
+            }
+            Err(e) if self.note_unsupported(Feature::Backups, &e) => {
+                self.feature_available(Feature::Backups);
+                false
+            }
+            Err(e) => {
+                error!("Failed to list backups for {}: {:?}", container, e);
+                self.show_error(
+                    format!("Failed to list backups of '{}'", container),
+                    e.to_string(),
+                    vec!["Check if LXD is running".to_string()],
+                );
+                false
+            }
+        }
+    }
+
+    pub fn backup_next(&mut self) {
+        if !self.backups.is_empty() {
+            self.backup_selected = (self.backup_selected + 1) % self.backups.len();
+        }
+    }
+
+    pub fn backup_previous(&mut self) {
+        if !self.backups.is_empty() {
+            self.backup_selected = if self.backup_selected == 0 {
+                self.backups.len() - 1
+            } else {
+                self.backup_selected - 1
+            };
+        }
+    }
+
+    pub async fn create_backup(&mut self, container: &str) {
+        let operation_id = self.register_operation(
+            format!("Back up '{}'", container),
+            Some(container.to_string()),
+        );
+        self.set_kind(&operation_id, format!("backup {}", container));
+
+        self.show_progress(&operation_id);
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(container);
+        match client.create_backup_async(&name).await {
+            Ok(lxd_operation_path) => self.track_lxd_operation(
+                &operation_id,
+                lxd_operation_path,
+                container.to_string(),
+                "backup",
+                format!("Created backup of '{}'", container),
+            ),
+            Err(e) => {
+                error!("Failed to back up {}: {:?}", container, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to back up '{}'", container),
+                    e.to_string(),
+                    vec!["Verify sufficient disk space on the storage pool".to_string()],
+                );
+            }
+        }
+    }
+
+    pub async fn create_snapshot(&mut self, container: &str, snapshot: Option<&str>) {
+        let operation_id = self.register_operation(
+            format!("Snapshot '{}'", container),
+            Some(container.to_string()),
+        );
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(container);
+        match client.create_snapshot(&name, snapshot).await {
+            Ok(_) => {
+                self.complete_operation(&operation_id, true, None);
+                self.message = Some(match snapshot {
+                    Some(snapshot) => format!("Created snapshot '{}' of '{}'", snapshot, container),
+                    None => format!("Created snapshot of '{}'", container),
+                });
+            }
+            Err(e) => {
+                error!("Failed to snapshot {}: {:?}", container, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to snapshot '{}'", container),
+                    e.to_string(),
+                    vec!["Snapshot names must be unique per instance".to_string()],
+                );
+            }
+        }
+    }
+
+    pub async fn delete_backup(&mut self, container: &str, backup: &str) {
+        let backup = backup.to_string();
+
+        let operation_id = self.register_operation(
+            format!("Delete backup '{}' of '{}'", backup, container),
+            Some(container.to_string()),
+        );
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(container);
+        match client.delete_backup(&name, &backup).await {
+            Ok(_) => {
+                self.complete_operation(&operation_id, true, None);
+                self.message = Some(format!("Deleted backup '{}'", backup));
+                self.reload_backups(container).await;
+            }
+            Err(e) => {
+                error!(
+                    "Failed to delete backup {} of {}: {:?}",
+                    backup, container, e
+                );
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to delete backup '{}'", backup),
+                    e.to_string(),
+                    vec!["Refresh the list; the backup may have expired".to_string()],
+                );
+            }
+        }
+    }
+
+    /// Save the selected backup as a tarball in the user's download directory
+    pub async fn download_selected_backup(&mut self, container: &str) {
+        let Some(backup) = self
+            .backups
+            .get(self.backup_selected)
+            .map(|b| b.name.clone())
+        else {
+            return;
+        };
+
+        let (client, name) = self.client_for(container);
+        let dest = dirs::download_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(format!("{}-{}.tar.gz", name, backup));
+
+        let operation_id = self.register_operation(
+            format!("Download backup '{}' of '{}'", backup, container),
+            Some(container.to_string()),
+        );
+        self.start_operation(&operation_id);
+
+        match client.download_backup(&name, &backup, &dest).await {
+            Ok(size) => {
+                self.complete_operation(&operation_id, true, None);
+                self.message = Some(format!(
+                    "Saved {} ({} MiB)",
+                    dest.display(),
+                    size / (1024 * 1024)
+                ));
+            }
+            Err(e) => {
+                error!(
+                    "Failed to download backup {} of {}: {:?}",
+                    backup, container, e
+                );
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to download backup '{}'", backup),
+                    e.to_string(),
+                    vec![format!("Check that {} is writable", dest.display())],
+                );
+            }
+        }
+    }
+
+    pub fn cancel_dialog(&mut self) {
+        self.pending_action = None;
+        self.input_mode = InputMode::Normal;
+        self.message = Some("Operation cancelled".to_string());
+    }
+
+    pub fn clear_message(&mut self) {
+        self.message = None;
+    }
+
+    pub async fn start_clone(&mut self) {
+        if let Some(container) = self.get_selected_container().await {
+            self.input_mode = InputMode::Input {
+                prompt: format!("Clone '{}' to:", container.qualified_name()),
+                input_type: InputType::ContainerName,
+                callback_action: InputCallback::CloneContainer(container.qualified_name()),
+            };
+            self.input_buffer.clear();
+        }
+    }
+
+    pub async fn start_refresh_copy(&mut self) {
+        if !self.feature_available(Feature::RefreshCopy) {
+            return;
+        }
+        if let Some(container) = self.get_selected_container().await {
+            self.input_mode = InputMode::Input {
+                prompt: format!("Refresh existing copy of '{}':", container.qualified_name()),
+                input_type: InputType::ContainerName,
+                callback_action: InputCallback::RefreshCopy(container.qualified_name()),
+            };
+            self.input_buffer.clear();
+        }
+    }
+
+    /// Prompt for the selected container's tags, starting from the current ones
+    pub async fn start_edit_tags(&mut self) {
+        if let Some(container) = self.get_selected_container().await {
+            self.input_mode = InputMode::Input {
+                prompt: format!(
+                    "Tags for '{}' (comma separated):",
+                    container.qualified_name()
+                ),
+                input_type: InputType::Tags,
+                callback_action: InputCallback::SetTags(container.qualified_name()),
+            };
+            self.input_buffer.set(&container.tags.join(", "));
+        }
+    }
+
+    pub async fn set_tags(&mut self, container: &str, tags: &[String]) {
+        let operation_id =
+            self.register_operation(format!("Tag '{}'", container), Some(container.to_string()));
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(container);
+        match client.set_tags(&name, tags).await {
+            Ok(_) => {
+                self.complete_operation(&operation_id, true, None);
+                self.show_success(if tags.is_empty() {
+                    format!("Removed the tags of '{}'", container)
+                } else {
+                    format!("Tagged '{}' with {}", container, tags.join(", "))
+                });
+                let _ = self.refresh_containers().await;
+            }
+            Err(e) => {
+                error!("Failed to tag {}: {:?}", container, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to tag '{}'", container),
+                    e.to_string(),
+                    vec!["Check that you may edit the instance's config".to_string()],
+                );
+            }
+        }
+        self.input_buffer.clear();
+    }
+
+    /// Open the selected instance's notes in the user's editor, as Markdown
+    pub async fn start_edit_notes(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        let name = container.qualified_name();
+        if let Some(other) = self.config_edit.take() {
+            other.discard();
+        }
+
+        // Read afresh, as the list may be a refresh behind
+        let (client, instance) = self.client_for(&name);
+        let edit = match client.get_instance(&instance).await {
+            Ok(instance) => {
+                let text = instance
+                    .config
+                    .get(notes::CONFIG_KEY)
+                    .map_or(String::new(), |notes| format!("{}\n", notes));
+                ConfigEdit::write(&name, EditKind::Notes, &text).map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        match edit {
+            Ok(mut edit) => {
+                edit.open = true;
+                self.external_command = Some(edit.command());
+                self.config_edit = Some(edit);
+            }
+            Err(e) => {
+                error!("Failed to open the notes of {}: {}", name, e);
+                self.show_error(
+                    format!("Failed to edit the notes of '{}'", name),
+                    e,
+                    vec!["Check that LXD is reachable".to_string()],
+                );
+            }
+        }
+    }
+
+    pub async fn set_notes(&mut self, container: &str, notes: &str) {
+        let operation_id = self.register_operation(
+            format!("Edit the notes of '{}'", container),
+            Some(container.to_string()),
+        );
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(container);
+        match client.set_config(&name, notes::CONFIG_KEY, notes).await {
+            Ok(_) => {
+                self.complete_operation(&operation_id, true, None);
+                self.show_success(if notes.is_empty() {
+                    format!("Removed the notes of '{}'", container)
+                } else {
+                    format!("Saved the notes of '{}'", container)
+                });
+                let _ = self.refresh_containers().await;
+            }
+            Err(e) => {
+                error!("Failed to set the notes of {}: {:?}", container, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to save the notes of '{}'", container),
+                    e.to_string(),
+                    vec!["Check that you may edit the instance's config".to_string()],
+                );
+            }
+        }
+    }
+
+    /// Open the selected instance's config in the user's editor. A file kept
+    /// from an edit that failed validation is reopened as it was left.
+    pub async fn start_edit_config(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        let name = container.qualified_name();
+
+        let edit = match self.config_edit.take() {
+            Some(edit) if edit.container == name && edit.kind == EditKind::Config => Ok(edit),
+            other => {
+                if let Some(other) = other {
+                    other.discard();
+                }
+                let (client, instance) = self.client_for(&name);
+                match client.get_instance(&instance).await {
+                    Ok(instance) => ConfigEdit::create(&name, &instance).map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+        };
+
+        match edit {
+            Ok(mut edit) => {
+                edit.open = true;
+                self.external_command = Some(edit.command());
+                self.config_edit = Some(edit);
+            }
+            Err(e) => {
+                error!("Failed to open the config of {}: {}", name, e);
+                self.show_error(
+                    format!("Failed to edit '{}'", name),
+                    e,
+                    vec!["Check that LXD is reachable".to_string()],
+                );
+            }
+        }
+    }
+
+    /// Open the image the selected instance would be published as in the
+    /// user's editor. LXD only publishes stopped instances. A file kept from
+    /// an edit that failed validation is reopened as it was left.
+    pub async fn start_publish(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        let name = container.qualified_name();
+        if container.status == "Running" {
+            self.show_error(
+                format!("Can't publish '{}' while it runs", name),
+                "LXD only makes images of stopped instances".to_string(),
+                vec![format!("Stop '{}' first, then publish it", name)],
+            );
+            return;
+        }
+
+        let edit = match self.config_edit.take() {
+            Some(edit) if edit.container == name && edit.kind == EditKind::Publish => Ok(edit),
+            other => {
+                if let Some(other) = other {
+                    other.discard();
+                }
+                let (client, instance) = self.client_for(&name);
+                match client.get_instance(&instance).await {
+                    Ok(instance) => Publish::to_yaml(&instance)
+                        .map_err(|e| e.to_string())
+                        .and_then(|text| {
+                            ConfigEdit::write(&name, EditKind::Publish, &text)
+                                .map_err(|e| e.to_string())
+                        }),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+        };
+
+        match edit {
+            Ok(mut edit) => {
+                edit.open = true;
+                self.external_command = Some(edit.command());
+                self.config_edit = Some(edit);
+            }
+            Err(e) => {
+                error!("Failed to start publishing {}: {}", name, e);
+                self.show_error(
+                    format!("Failed to publish '{}'", name),
+                    e,
+                    vec!["Check that LXD is reachable".to_string()],
+                );
+            }
+        }
+    }
+
+    async fn publish_instance(&mut self, container: &str, publish: &Publish) {
+        let image = publish.alias.as_deref().unwrap_or(container);
+        let operation_id = self.register_operation(
+            format!("Publish '{}' as '{}'", container, image),
+            Some(container.to_string()),
+        );
+        self.set_kind(&operation_id, format!("publish {}", container));
+
+        self.show_progress(&operation_id);
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(container);
+        match client.publish_instance_async(&name, publish).await {
+            Ok(lxd_operation_path) => self.track_lxd_operation(
+                &operation_id,
+                lxd_operation_path,
+                container.to_string(),
+                "publish",
+                format!("Published '{}' as image '{}'", container, image),
+            ),
+            Err(e) => {
+                error!("Failed to publish {}: {:?}", container, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to publish '{}'", container),
+                    e.to_string(),
+                    vec![
+                        "Check that the instance is stopped".to_string(),
+                        "Check that the alias isn't taken by another image".to_string(),
+                    ],
+                );
+            }
+        }
+    }
+
+    /// Validate the file the editor saved and send it. An invalid file is
+    /// kept so the next edit picks up where this one stopped.
+    pub async fn finish_config_edit(&mut self) {
+        let Some(mut edit) = self.config_edit.take() else {
+            return;
+        };
+        if !edit.open {
+            self.config_edit = Some(edit);
+            return;
+        }
+        edit.open = false;
+
+        let text = match edit.read() {
+            Ok(text) => text,
+            Err(e) => {
+                self.show_error(
+                    format!("Failed to read the config of '{}'", edit.container),
+                    e.to_string(),
+                    vec!["Save the file where the editor opened it".to_string()],
+                );
+                edit.discard();
+                return;
+            }
+        };
+        if text == edit.original {
+            match (self.templates.as_mut(), &edit.kind) {
+                (Some(view), EditKind::Metadata | EditKind::Template(_)) => {
+                    view.status = Some("No changes".to_string())
+                }
+                (_, EditKind::Publish) => self.show_info(
+                    format!(
+                        "Left '{}' unpublished, as the file wasn't changed",
+                        edit.container
+                    ),
+                    true,
+                ),
+                _ => self.show_info(format!("No changes to '{}'", edit.container), true),
+            }
+            edit.discard();
+            return;
+        }
+
+        match &edit.kind {
+            EditKind::Config => {}
+            EditKind::Notes => {
+                let action = PendingAction::SetNotes {
+                    container: edit.container.clone(),
+                    notes: notes::value(&text),
+                };
+                edit.discard();
+                self.run_or_preview(action).await;
+                return;
+            }
+            EditKind::Publish => {
+                match Publish::parse(&text, chrono::Local::now()) {
+                    Ok(publish) => {
+                        let container = edit.container.clone();
+                        edit.discard();
+                        self.run_or_preview(PendingAction::Publish { container, publish })
+                            .await;
+                    }
+                    Err(e) => {
+                        self.show_error(
+                            format!("Invalid image for '{}'", edit.container),
+                            e.to_string(),
+                            vec!["Publish again to fix it; your changes are kept".to_string()],
+                        );
+                        self.config_edit = Some(edit);
+                    }
+                }
+                return;
+            }
+            EditKind::Metadata => {
+                match InstanceMetadata::parse(&text) {
+                    Ok(metadata) => {
+                        let container = edit.container.clone();
+                        edit.discard();
+                        self.run_or_preview(PendingAction::UpdateMetadata {
+                            container,
+                            metadata,
+                        })
+                        .await;
+                    }
+                    Err(e) => {
+                        self.show_error(
+                            format!("Invalid metadata for '{}'", edit.container),
+                            e.to_string(),
+                            vec!["Edit the metadata again to fix it; your changes are kept"
+                                .to_string()],
+                        );
+                        self.config_edit = Some(edit);
+                    }
+                }
+                return;
+            }
+            EditKind::Template(template) => {
+                let action = PendingAction::SetTemplate {
+                    container: edit.container.clone(),
+                    template: template.clone(),
+                    content: text,
+                };
+                edit.discard();
+                self.run_or_preview(action).await;
+                return;
+            }
+        }
+
+        match InstanceConfig::parse(&text) {
+            Ok(config) => {
+                let container = edit.container.clone();
+                edit.discard();
+                self.run_or_preview(PendingAction::UpdateConfig { container, config })
+                    .await;
+            }
+            Err(e) => {
+                self.show_error(
+                    format!("Invalid config for '{}'", edit.container),
+                    e.to_string(),
+                    vec!["Edit the config again to fix it; your changes are kept".to_string()],
+                );
+                self.config_edit = Some(edit);
+            }
+        }
+    }
+
+    pub async fn update_instance_config(&mut self, container: &str, config: &InstanceConfig) {
+        let operation_id = self.register_operation(
+            format!("Edit config of '{}'", container),
+            Some(container.to_string()),
+        );
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(container);
+        match client.update_instance(&name, config).await {
+            Ok(_) => {
+                self.complete_operation(&operation_id, true, None);
+                self.show_success(format!("Updated the config of '{}'", container));
+                let _ = self.refresh_containers().await;
+            }
+            Err(e) => {
+                error!("Failed to update the config of {}: {:?}", container, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to update '{}'", container),
+                    e.to_string(),
+                    vec![
+                        "Some keys can only change while the instance is stopped".to_string(),
+                        "Profiles the config lists must exist".to_string(),
+                    ],
+                );
+            }
+        }
+    }
+
+    /// The selected instance when it is a VM; containers boot the host's
+    /// kernel, so have nothing to install from an ISO
+    async fn selected_vm(&mut self) -> Option<Container> {
+        let container = self.get_selected_container().await?;
+        if container.container_type != "virtual-machine" {
+            self.show_info(
+                format!(
+                    "'{}' is a container; only VMs boot from an ISO",
+                    container.qualified_name()
+                ),
+                true,
+            );
+            return None;
+        }
+        Some(container)
+    }
+
+    /// Prompt for an ISO to attach to the selected VM, starting from the
+    /// one attached
+    pub async fn start_attach_media(&mut self) {
+        let Some(container) = self.selected_vm().await else {
+            return;
+        };
+        let name = container.qualified_name();
+        let (client, instance) = self.client_for(&name);
+        let current = match client.get_instance(&instance).await {
+            Ok(instance) => media::attached(&InstanceConfig::from(&instance).devices),
+            Err(e) => {
+                warn!("Failed to read the devices of {}: {}", name, e);
+                None
+            }
+        };
+        self.input_mode = InputMode::Input {
+            prompt: format!("ISO to boot '{}' from (host path or pool/volume):", name),
+            input_type: InputType::Media,
+            callback_action: InputCallback::AttachMedia(name),
+        };
+        match current {
+            Some((media, _)) => self.input_buffer.set(&media.to_string()),
+            None => self.input_buffer.clear(),
+        }
+    }
+
+    pub async fn submit_media(&mut self, container: String, text: &str) {
+        self.input_buffer.clear();
+        match Media::parse(text) {
+            Some(media) => {
+                self.prepare_media_change(&container, MediaChange::Attach(media))
+                    .await
+            }
+            None => self.show_error(
+                format!("Invalid ISO for '{}'", container),
+                format!("'{}' is neither a path nor pool/volume", text),
+                vec![
+                    "Give the absolute path of the ISO on the server's host".to_string(),
+                    "Or the pool and name of a custom ISO volume, as default/debian-12".to_string(),
+                ],
+            ),
+        }
+    }
+
+    /// Turn the selected VM's booting from its ISO off or back on
+    pub async fn toggle_media_boot(&mut self) {
+        let Some(container) = self.selected_vm().await else {
+            return;
+        };
+        let name = container.qualified_name();
+        let (client, instance) = self.client_for(&name);
+        match client.get_instance(&instance).await {
+            Ok(instance) => match media::attached(&InstanceConfig::from(&instance).devices) {
+                Some((_, first)) => {
+                    self.prepare_media_change(&name, MediaChange::BootFirst(!first))
+                        .await
+                }
+                None => self.show_info(format!("No ISO is attached to '{}'", name), true),
+            },
+            Err(e) => self.show_error(
+                format!("Failed to read '{}'", name),
+                e.to_string(),
+                Vec::new(),
+            ),
+        }
+    }
+
+    pub async fn detach_media(&mut self) {
+        if let Some(container) = self.selected_vm().await {
+            self.prepare_media_change(&container.qualified_name(), MediaChange::Detach)
+                .await;
+        }
+    }
+
+    /// Make `change` to the instance's current config and send the whole
+    /// config back, as LXD only removes a device when it is left out
+    async fn prepare_media_change(&mut self, container: &str, change: MediaChange) {
+        let (client, name) = self.client_for(container);
+        let mut config = match client.get_instance(&name).await {
+            Ok(instance) => InstanceConfig::from(&instance),
+            Err(e) => {
+                self.show_error(
+                    format!("Failed to read '{}'", container),
+                    e.to_string(),
+                    Vec::new(),
+                );
+                return;
+            }
+        };
+        if !change.apply(&mut config.devices) {
+            self.show_info(format!("No ISO is attached to '{}'", container), true);
+            return;
+        }
+        self.run_or_preview(PendingAction::ChangeMedia {
+            container: container.to_string(),
+            change,
+            config,
+        })
+        .await;
+    }
+
+    async fn change_media(
+        &mut self,
+        container: &str,
+        change: &MediaChange,
+        config: &InstanceConfig,
+    ) {
+        let operation_id =
+            self.register_operation(change.describe(container), Some(container.to_string()));
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(container);
+        match client.update_instance(&name, config).await {
+            Ok(_) => {
+                self.complete_operation(&operation_id, true, None);
+                self.show_success(match change {
+                    MediaChange::Attach(media) => {
+                        format!(
+                            "Attached {} to '{}'; it boots from it next",
+                            media, container
+                        )
+                    }
+                    MediaChange::BootFirst(true) => {
+                        format!("'{}' boots from its ISO next", container)
+                    }
+                    MediaChange::BootFirst(false) => {
+                        format!("'{}' boots from its disk next", container)
+                    }
+                    MediaChange::Detach => format!("Detached the ISO of '{}'", container),
+                });
+                let _ = self.refresh_containers().await;
+            }
+            Err(e) => {
+                error!("Failed to change the ISO of {}: {:?}", container, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to change the ISO of '{}'", container),
+                    e.to_string(),
+                    vec![
+                        "The ISO must exist on the server, or the volume in the pool".to_string(),
+                        "Some VMs only take a new ISO while stopped".to_string(),
+                    ],
+                );
+            }
+        }
+    }
+
+    /// List the selected container's image templates and metadata
+    pub async fn open_templates(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        self.templates = Some(TemplatesView::new(container.qualified_name()));
+        self.input_mode = InputMode::Templates;
+        self.reload_templates().await;
+    }
+
+    pub async fn reload_templates(&mut self) {
+        let Some(view) = &self.templates else {
+            return;
+        };
+        let (client, name) = self.client_for(&view.container);
+        let result = match client.instance_metadata(&name).await {
+            Ok(metadata) => client
+                .list_templates(&name)
+                .await
+                .map(|files| (metadata, files)),
+            Err(e) => Err(e),
+        };
+        let Some(view) = &mut self.templates else {
+            return;
+        };
+        match result {
+            Ok((metadata, files)) => view.load(metadata, files),
+            Err(e) => {
+                error!(
+                    "Failed to load the templates of {}: {:?}",
+                    view.container, e
+                );
+                view.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// List the VM-only settings of the selected VM
+    pub async fn open_vm_settings(&mut self) {
+        let Some(container) = self.selected_vm().await else {
+            return;
+        };
+        self.vm_settings = Some(VmSettings::new(container.qualified_name()));
+        self.input_mode = InputMode::VmSettings;
+        self.reload_vm_settings().await;
+    }
+
+    pub async fn reload_vm_settings(&mut self) {
+        let Some(view) = &self.vm_settings else {
+            return;
+        };
+        let (client, name) = self.client_for(&view.container);
+        let result = client.vm_settings(&name).await;
+        let Some(view) = &mut self.vm_settings else {
+            return;
+        };
+        match result {
+            Ok((running, settings)) => view.set_settings(running, settings),
+            Err(e) => {
+                error!(
+                    "Failed to load the VM settings of {}: {:?}",
+                    view.container, e
+                );
+                view.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Switch the selected setting, or prompt for the CPUs
+    pub async fn change_vm_setting(&mut self) {
+        let Some(view) = &self.vm_settings else {
+            return;
+        };
+        let Some(setting) = view.selected_setting().cloned() else {
+            return;
+        };
+        let container = view.container.clone();
+        self.set_vm_settings_status(None);
+        if let Some(extension) = &setting.missing {
+            self.set_vm_settings_status(Some(format!(
+                "{} needs the server's {} API extension",
+                setting.knob.label(),
+                extension
+            )));
+        } else if setting.knob.is_toggle() {
+            self.prepare_vm_setting(&container, setting.knob, setting.toggled())
+                .await;
+        } else if setting.knob == Knob::Cpus {
+            self.input_mode = InputMode::Input {
+                prompt: format!("CPUs of '{}' (a count, or a set such as 0-3):", container),
+                input_type: InputType::CpuSet,
+                callback_action: InputCallback::SetVmSetting {
+                    container,
+                    knob: setting.knob,
+                },
+            };
+            self.input_buffer.set(if setting.inherited {
+                ""
+            } else {
+                &setting.value
+            });
+        } else {
+            self.set_vm_settings_status(Some(format!(
+                "LXD sets the {} itself",
+                setting.knob.label()
+            )));
+        }
+    }
+
+    fn set_vm_settings_status(&mut self, status: Option<String>) {
+        if let Some(view) = &mut self.vm_settings {
+            view.status = status;
+        }
+    }
+
+    /// Check typed CPUs before setting them
+    pub async fn submit_vm_setting(&mut self, container: String, knob: Knob, text: &str) {
+        self.input_buffer.clear();
+        self.input_mode = InputMode::VmSettings;
+        if !vm::valid_cpus(text) {
+            self.show_error(
+                format!("Invalid CPUs for '{}'", container),
+                format!("'{}' is neither a count nor a set of CPUs", text),
+                vec!["Use a count such as 4, or CPUs to pin to such as 0-3,8".to_string()],
+            );
+            return;
+        }
+        self.prepare_vm_setting(&container, knob, text).await;
+    }
+
+    /// Set the value in the VM's current config, which is sent back whole
+    /// so the TPM device can be removed
+    async fn prepare_vm_setting(&mut self, container: &str, knob: Knob, value: &str) {
+        let (client, name) = self.client_for(container);
+        let mut config = match client.get_instance(&name).await {
+            Ok(instance) => InstanceConfig::from(&instance),
+            Err(e) => {
+                self.show_error(
+                    format!("Failed to read '{}'", container),
+                    e.to_string(),
+                    Vec::new(),
+                );
+                return;
+            }
+        };
+        knob.apply(&mut config, value);
+        self.run_or_preview(PendingAction::SetVmSetting {
+            container: container.to_string(),
+            knob,
+            value: value.to_string(),
+            config,
+        })
+        .await;
+    }
+
+    async fn set_vm_setting(
+        &mut self,
+        container: &str,
+        knob: Knob,
+        value: &str,
+        config: &InstanceConfig,
+    ) {
+        let (client, name) = self.client_for(container);
+        match client.update_instance(&name, config).await {
+            Ok(()) => {
+                self.reload_vm_settings().await;
+                let running = self.vm_settings.as_ref().is_some_and(|view| view.running);
+                if running && !knob.is_live(value) {
+                    self.set_vm_settings_status(Some(format!(
+                        "{} of '{}' changes when it next starts",
+                        knob.label(),
+                        container
+                    )));
+                }
+            }
+            Err(e) => {
+                error!("Failed to set {} of {}: {:?}", knob.label(), container, e);
+                self.show_error(
+                    format!("Failed to set {} of '{}'", knob.label(), container),
+                    e.to_string(),
+                    vec![
+                        "Some settings can only change while the VM is stopped".to_string(),
+                        "Check that you may edit the instance's config".to_string(),
+                    ],
+                );
+            }
+        }
+    }
+
+    /// Show the selected container's ID mapping. VMs have their own
+    /// kernel, so map nothing.
+    pub async fn open_idmap(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        if container.container_type == "virtual-machine" {
+            self.show_info(
+                format!(
+                    "'{}' is a VM; only containers share the host's IDs",
+                    container.qualified_name()
+                ),
+                true,
+            );
+            return;
+        }
+        self.idmap = Some(IdmapView::new(container.qualified_name()));
+        self.input_mode = InputMode::Idmap;
+        self.reload_idmap().await;
+    }
+
+    pub async fn reload_idmap(&mut self) {
+        let Some(view) = &self.idmap else {
+            return;
+        };
+        let (client, name) = self.client_for(&view.container);
+        let result = client.get_instance(&name).await;
+        let Some(view) = &mut self.idmap else {
+            return;
+        };
+        match result {
+            Ok(instance) => view.load(&instance),
+            Err(e) => {
+                error!("Failed to load the ID map of {}: {:?}", view.container, e);
+                view.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Prompt for a `raw.idmap` line: a new one, or the selected one
+    pub fn start_idmap_line(&mut self, edit: bool) {
+        let Some(view) = &self.idmap else {
+            return;
+        };
+        let index = if edit {
+            let Some(index) = view.selected_line() else {
+                return;
+            };
+            Some(index)
+        } else {
+            None
+        };
+        let current = index.map(|i| view.lines[i].clone()).unwrap_or_default();
+        self.input_mode = InputMode::Input {
+            prompt: format!(
+                "Map IDs of '{}' (uid, gid or both, host, container):",
+                view.container
+            ),
+            input_type: InputType::IdmapEntry,
+            callback_action: InputCallback::IdmapLine {
+                container: view.container.clone(),
+                index,
+            },
+        };
+        self.input_buffer.set(&current);
+    }
+
+    /// Check the map with the typed line in place before setting it
+    pub async fn submit_idmap_line(&mut self, container: String, index: Option<usize>, text: &str) {
+        self.input_buffer.clear();
+        self.input_mode = InputMode::Idmap;
+        let Some(view) = &self.idmap else {
+            return;
+        };
+        let mut lines = view.lines.clone();
+        match index {
+            Some(i) if i < lines.len() => lines[i] = text.trim().to_string(),
+            _ => lines.push(text.trim().to_string()),
+        }
+        // A notice rather than an error modal, which would close the view
+        if let Err(e) = idmap::check(&lines) {
+            self.set_idmap_status(Some(format!("Not set: {}", e)));
+            return;
+        }
+        self.prepare_idmap_change(&container, IdmapChange::Lines(lines))
+            .await;
+    }
+
+    pub async fn delete_idmap_line(&mut self) {
+        let Some(view) = &self.idmap else {
+            return;
+        };
+        let Some(index) = view.selected_line() else {
+            return;
+        };
+        let container = view.container.clone();
+        let mut lines = view.lines.clone();
+        lines.remove(index);
+        self.prepare_idmap_change(&container, IdmapChange::Lines(lines))
+            .await;
+    }
+
+    pub async fn toggle_idmap_isolated(&mut self) {
+        let Some(view) = &self.idmap else {
+            return;
+        };
+        let (container, on) = (view.container.clone(), !view.isolated);
+        self.prepare_idmap_change(&container, IdmapChange::Isolated(on))
+            .await;
+    }
+
+    /// Switch the kernel's shifting of the selected disk on or off
+    pub async fn toggle_disk_shift(&mut self) {
+        let Some(view) = &self.idmap else {
+            return;
+        };
+        let Some(disk) = view.selected_disk() else {
+            return;
+        };
+        let change = IdmapChange::Shift {
+            device: disk.name.clone(),
+            on: !disk.shift,
+        };
+        let container = view.container.clone();
+        self.prepare_idmap_change(&container, change).await;
+    }
+
+    fn set_idmap_status(&mut self, status: Option<String>) {
+        if let Some(view) = &mut self.idmap {
+            view.status = status;
+        }
+    }
+
+    async fn prepare_idmap_change(&mut self, container: &str, change: IdmapChange) {
+        self.set_idmap_status(None);
+        let (client, name) = self.client_for(container);
+        let mut config = match client.get_instance(&name).await {
+            Ok(instance) => InstanceConfig::from(&instance),
+            Err(e) => {
+                self.show_error(
+                    format!("Failed to read '{}'", container),
+                    e.to_string(),
+                    Vec::new(),
+                );
+                return;
+            }
+        };
+        change.apply(&mut config);
+        self.run_or_preview(PendingAction::ChangeIdmap {
+            container: container.to_string(),
+            change,
+            config,
+        })
+        .await;
+    }
+
+    async fn change_idmap(
+        &mut self,
+        container: &str,
+        change: &IdmapChange,
+        config: &InstanceConfig,
+    ) {
+        let (client, name) = self.client_for(container);
+        match client.update_instance(&name, config).await {
+            Ok(()) => {
+                self.reload_idmap().await;
+                if self.idmap.as_ref().is_some_and(|view| view.running) {
+                    let what = match change {
+                        IdmapChange::Shift { device, .. } => {
+                            format!("the shifting of '{}'", device)
+                        }
+                        IdmapChange::Lines(_) | IdmapChange::Isolated(_) => {
+                            "the new ID map".to_string()
+                        }
+                    };
+                    self.set_idmap_status(Some(format!(
+                        "Restart '{}' to apply {}; its files are shifted as it starts",
+                        container, what
+                    )));
+                }
+            }
+            Err(e) => {
+                error!("Failed to change the ID map of {}: {:?}", container, e);
+                self.show_error(
+                    format!("Failed to change the ID map of '{}'", container),
+                    e.to_string(),
+                    vec![
+                        "Host IDs must be in the range the host gives LXD (/etc/subuid, /etc/subgid)"
+                            .to_string(),
+                        "Shifting needs idmapped mounts or shiftfs on the host".to_string(),
+                    ],
+                );
+            }
+        }
+    }
+
+    /// Open the metadata in the user's editor. A file kept from an edit
+    /// that failed validation is reopened as it was left.
+    pub fn start_edit_metadata(&mut self) {
+        let Some(view) = &self.templates else {
+            return;
+        };
+        let Some(metadata) = &view.metadata else {
+            return;
+        };
+        let edit = match self.config_edit.take() {
+            Some(edit) if edit.container == view.container && edit.kind == EditKind::Metadata => {
+                Ok(edit)
+            }
+            other => {
+                if let Some(other) = other {
+                    other.discard();
+                }
+                metadata
+                    .to_yaml()
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| {
+                        ConfigEdit::write(&view.container, EditKind::Metadata, &text)
+                            .map_err(|e| e.to_string())
+                    })
+            }
+        };
+        let container = view.container.clone();
+        self.open_in_editor(&container, edit);
+    }
+
+    /// Open the selected template in the user's editor; a file the
+    /// metadata names but the instance lacks starts out empty
+    pub async fn start_edit_template(&mut self) {
+        let Some(view) = &self.templates else {
+            return;
+        };
+        let Some(file) = view.selected_file() else {
+            return;
+        };
+        let (container, template, exists) =
+            (view.container.clone(), file.name.clone(), file.exists);
+        let content = if exists {
+            let (client, name) = self.client_for(&container);
+            client
+                .template(&name, &template)
+                .await
+                .map_err(|e| e.to_string())
+        } else {
+            Ok(String::new())
+        };
+        let edit = content.and_then(|text| {
+            ConfigEdit::write(&container, EditKind::Template(template), &text)
+                .map_err(|e| e.to_string())
+        });
+        self.open_in_editor(&container, edit);
+    }
+
+    /// Prompt for the file name of a new template
+    pub fn start_new_template(&mut self) {
+        let Some(view) = &self.templates else {
+            return;
+        };
+        self.input_mode = InputMode::Input {
+            prompt: format!("New template file for '{}':", view.container),
+            input_type: InputType::FileName,
+            callback_action: InputCallback::NewTemplate(view.container.clone()),
+        };
+        // Typing goes before the extension
+        self.input_buffer.set(".tpl");
+        self.input_buffer.apply(FieldEdit::Home);
+    }
+
+    /// Open an empty template in the user's editor
+    pub fn create_template(&mut self, container: &str, template: &str) {
+        self.input_buffer.clear();
+        self.input_mode = InputMode::Templates;
+        let edit = ConfigEdit::write(container, EditKind::Template(template.to_string()), "")
+            .map_err(|e| e.to_string());
+        self.open_in_editor(container, edit);
+    }
+
+    fn open_in_editor(&mut self, container: &str, edit: Result<ConfigEdit, String>) {
+        match edit {
+            Ok(mut edit) => {
+                edit.open = true;
+                self.external_command = Some(edit.command());
+                self.config_edit = Some(edit);
+            }
+            Err(e) => {
+                error!("Failed to open a template of {}: {}", container, e);
+                self.show_error(
+                    format!("Failed to edit the templates of '{}'", container),
+                    e,
+                    vec!["Check that LXD is reachable".to_string()],
+                );
+            }
+        }
+    }
+
+    async fn update_metadata(&mut self, container: &str, metadata: &InstanceMetadata) {
+        let (client, name) = self.client_for(container);
+        match client.update_metadata(&name, metadata).await {
+            Ok(()) => self.template_saved("Saved the metadata".to_string()).await,
+            Err(e) => {
+                error!("Failed to update the metadata of {}: {:?}", container, e);
+                self.show_error(
+                    format!("Failed to update the metadata of '{}'", container),
+                    e.to_string(),
+                    vec!["Every template the metadata names must exist".to_string()],
+                );
+            }
+        }
+    }
+
+    async fn set_template(&mut self, container: &str, template: &str, content: &str) {
+        let (client, name) = self.client_for(container);
+        match client.set_template(&name, template, content).await {
+            Ok(()) => self.template_saved(format!("Saved {}", template)).await,
+            Err(e) => {
+                error!("Failed to save {} of {}: {:?}", template, container, e);
+                self.show_error(
+                    format!("Failed to save {} of '{}'", template, container),
+                    e.to_string(),
+                    vec!["Check that you may edit the instance".to_string()],
+                );
+            }
+        }
+    }
+
+    async fn template_saved(&mut self, status: String) {
+        self.reload_templates().await;
+        if let Some(view) = &mut self.templates {
+            view.status = Some(status);
+        }
+    }
+
+    pub fn start_new_container_wizard(&mut self) {
+        self.wizard_data = WizardData::from_defaults(&self.create_defaults);
+        self.input_buffer.clear();
+        self.input_mode = InputMode::Wizard(WizardState::Name);
+    }
+
+    /// Fetch the local server's aliases for the wizard's image step
+    pub async fn load_image_aliases(&mut self) {
+        match self.lxc_client.image_aliases().await {
+            Ok(aliases) => {
+                self.images.local = aliases
+                    .iter()
+                    .map(|a| ImageChoice::new(&a.alias, &a.description, ImageSource::Local))
+                    .collect();
+            }
+            Err(e) => warn!("Failed to list image aliases: {}", e),
+        }
+    }
+
+    /// Ask how to copy `source` to `destination`, offering the storage
+    /// pools and projects of the source's server
+    pub async fn open_clone_options(&mut self, source: String, destination: String) {
+        let (client, _) = self.client_for(&source);
+        let (pools, projects) = client.clone_targets().await;
+        self.input_mode =
+            InputMode::CloneOptions(CloneForm::new(source, destination, pools, projects));
+    }
+
+    pub async fn clone_container(
+        &mut self,
+        source: &str,
+        destination: &str,
+        options: &CloneOptions,
+    ) {
+        let operation_id = self.register_operation(
+            format!("Clone '{}' to '{}'", source, destination),
+            Some(destination.to_string()),
+        );
+        self.set_kind(&operation_id, format!("clone {}", source));
+
+        self.show_progress(&operation_id);
+        self.start_operation(&operation_id);
+
+        // The copy is made on the source's remote
+        let (client, source_name) = self.client_for(source);
+        let copy = match source.split_once(':') {
+            Some((remote, _)) => format!("{}:{}", remote, destination),
+            None => destination.to_string(),
+        };
+        match client
+            .clone_container_async(&source_name, destination, options)
+            .await
+        {
+            Ok(lxd_operation_path) => {
+                self.track_lxd_operation(
+                    &operation_id,
+                    lxd_operation_path,
+                    copy,
+                    "clone",
+                    format!("Successfully cloned '{}' to '{}'", source, destination),
+                );
+                self.input_buffer.clear();
+            }
+            Err(e) => {
+                error!(
+                    "Failed to clone container {} to {}: {:?}",
+                    source, destination, e
+                );
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to clone '{}'", source),
+                    e.to_string(),
+                    vec![
+                        "Check if destination name is valid".to_string(),
+                        "Ensure destination doesn't already exist".to_string(),
+                        "Verify sufficient disk space".to_string(),
+                    ],
+                );
+                self.input_buffer.clear();
+            }
+        }
+    }
+
+    pub async fn refresh_copy(&mut self, source: &str, destination: &str) {
+        let operation_id = self.register_operation(
+            format!("Refresh '{}' from '{}'", destination, source),
+            Some(destination.to_string()),
+        );
+
+        self.show_progress(&operation_id);
+        self.start_operation(&operation_id);
+
+        let (client, source_name) = self.client_for(source);
+        match client.refresh_copy(&source_name, destination).await {
+            Ok(_) => {
+                self.complete_operation(&operation_id, true, None);
+                self.show_success(format!(
+                    "Successfully refreshed '{}' from '{}'",
+                    destination, source
+                ));
+                let _ = self.refresh_containers().await;
+                self.input_buffer.clear();
+            }
+            Err(e) if self.note_unsupported(Feature::RefreshCopy, &e) => {
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.feature_available(Feature::RefreshCopy);
+                self.input_buffer.clear();
+            }
+            Err(e) => {
+                error!(
+                    "Failed to refresh copy {} from {}: {:?}",
+                    destination, source, e
+                );
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to refresh '{}'", destination),
+                    e.to_string(),
+                    vec![
+                        "Ensure the copy exists on the same remote".to_string(),
+                        "Stop the copy before refreshing it".to_string(),
+                    ],
+                );
+                self.input_buffer.clear();
+            }
+        }
+    }
+
+    pub async fn create_container(&mut self) {
+        let name = self.wizard_data.name.clone();
+        let image = self.wizard_data.image.clone();
+        let is_vm = self.wizard_data.is_vm;
+        let media = self.wizard_data.media.clone().filter(|_| is_vm);
+        let kind = if is_vm { "VM" } else { "container" };
+
+        let description = match (self.wizard_data.is_empty(), &media) {
+            (true, Some(media)) => format!("Create empty {} '{}' booting {}", kind, name, media),
+            (true, None) => format!("Create empty {} '{}'", kind, name),
+            (false, _) => format!("Create {} '{}' from '{}'", kind, name, image),
+        };
+        let operation_id = self.register_operation(description, Some(name.clone()));
+        self.set_kind(&operation_id, format!("create {} {}", kind, image));
+
+        self.show_progress(&operation_id);
+        self.start_operation(&operation_id);
+        // Nothing to pick again from the recent images
+        if !self.wizard_data.is_empty() {
+            self.images.creating(&operation_id, &image);
+        }
+
+        match self
+            .lxc_client
+            .create_container_async(&name, &image, is_vm, media.as_ref(), &self.create_defaults)
+            .await
+        {
+            Ok(lxd_operation_path) => {
+                self.track_lxd_operation(
+                    &operation_id,
+                    lxd_operation_path,
+                    name.clone(),
+                    if is_vm {
+                        "create-vm"
+                    } else {
+                        "create-container"
+                    },
+                    format!("Successfully created {} '{}'", kind, name),
+                );
+                self.wizard_data = WizardData::default();
+                self.input_buffer.clear();
+            }
+            Err(e) => {
+                error!("Failed to create container {}: {:?}", name, e);
+                self.images.failed(&operation_id);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to create '{}'", name),
+                    e.to_string(),
+                    vec![
+                        "Check if image exists and is available".to_string(),
+                        "Verify network connectivity".to_string(),
+                        "Ensure sufficient resources".to_string(),
+                    ],
+                );
+                self.wizard_data = WizardData::default();
+                self.input_buffer.clear();
+            }
+        }
+    }
+
+    pub fn cancel_input(&mut self) {
+        self.input_mode = match self.input_mode {
+            InputMode::Input {
+                callback_action: InputCallback::SetBootConfig { .. },
+                ..
+            } => InputMode::BootOrder,
+            InputMode::Input {
+                callback_action: InputCallback::NewTemplate(_),
+                ..
+            } => InputMode::Templates,
+            InputMode::Input {
+                callback_action: InputCallback::SetVmSetting { .. },
+                ..
+            } => InputMode::VmSettings,
+            InputMode::Input {
+                callback_action: InputCallback::IdmapLine { .. },
+                ..
+            } => InputMode::Idmap,
+            InputMode::Input {
+                callback_action: InputCallback::RecreateName | InputCallback::RecreateImage,
+                ..
+            } => InputMode::Recreate,
+            InputMode::Input {
+                callback_action: InputCallback::SearchConfigKey,
+                ..
+            } if self.key_search.is_some() => InputMode::KeySearch,
+            InputMode::Input {
+                callback_action: InputCallback::DriftFile,
+                ..
+            } if self.drift.is_some() => InputMode::Drift,
+            InputMode::Input {
+                callback_action: InputCallback::QuickCommand,
+                ..
+            } if self.quick.is_some() => InputMode::Quick { confirm: None },
+            InputMode::Input {
+                callback_action:
+                    InputCallback::NewZone
+                    | InputCallback::NewZoneRecord(_)
+                    | InputCallback::EditZoneRecord { .. },
+                ..
+            } => InputMode::Zones {
+                confirm_delete: false,
+            },
+            _ => InputMode::Normal,
+        };
+        self.input_buffer.clear();
+        self.wizard_data = WizardData::default();
+        self.message = Some("Operation cancelled".to_string());
+    }
+
+    /// What the image step offers for the typed query, best first
+    pub fn image_choices(&self) -> Vec<ImageChoice> {
+        let popular: Vec<ImageChoice> = self
+            .available_images
+            .iter()
+            .map(|i| ImageChoice::new(&i.alias, &i.description, ImageSource::Popular))
+            .collect();
+        self.images
+            .suggestions(&self.wizard_data.image_query, &popular)
+    }
+
+    pub fn next_wizard_image(&mut self) {
+        if self.wizard_data.selected_image_index + 1 < self.image_choices().len() {
+            self.wizard_data.selected_image_index += 1;
+        }
+    }
+
+    pub fn previous_wizard_image(&mut self) {
+        self.wizard_data.selected_image_index =
+            self.wizard_data.selected_image_index.saturating_sub(1);
+    }
+
+    /// Type into the image step, searching the image server from the
+    /// first character
+    pub fn wizard_image_char(&mut self, c: char) {
+        self.wizard_image_paste(&c.to_string());
+    }
+
+    pub fn wizard_image_paste(&mut self, text: &str) {
+        self.wizard_data.image_query.push_str(text);
+        self.wizard_data.selected_image_index = 0;
+        self.images.start_search();
+    }
+
+    pub fn wizard_image_backspace(&mut self) {
+        self.wizard_data.image_query.pop();
+        self.wizard_data.selected_image_index = 0;
+    }
+
+    /// Take the typed ISO of an empty VM, if any, and move on to confirm
+    pub fn choose_wizard_media(&mut self) {
+        let text = self.input_buffer.as_str().trim().to_string();
+        if text.is_empty() {
+            self.wizard_data.media = None;
+        } else {
+            match Media::parse(&text) {
+                Some(media) => self.wizard_data.media = Some(media),
+                None => {
+                    self.wizard_data.media_error = Some(
+                        "An ISO is an absolute path on the server or a pool/volume".to_string(),
+                    );
+                    return;
+                }
+            }
+        }
+        self.wizard_data.media_error = None;
+        self.input_buffer.clear();
+        self.input_mode = InputMode::Wizard(WizardState::Confirm);
+    }
+
+    /// Move on from the type step, to the ISO for an empty VM
+    pub fn finish_wizard_type(&mut self) {
+        match self.wizard_data.after_type() {
+            WizardState::Media => self.edit_wizard_media(),
+            next => self.input_mode = InputMode::Wizard(next),
+        }
+    }
+
+    /// Go back from the confirm step to the one before it
+    pub fn wizard_back_from_confirm(&mut self) {
+        match self.wizard_data.after_type() {
+            WizardState::Media => self.edit_wizard_media(),
+            _ => self.input_mode = InputMode::Wizard(WizardState::SelectType),
+        }
+    }
+
+    fn edit_wizard_media(&mut self) {
+        let media = self.wizard_data.media.as_ref().map(Media::to_string);
+        self.input_buffer.set(&media.unwrap_or_default());
+        self.wizard_data.media_error = None;
+        self.input_mode = InputMode::Wizard(WizardState::Media);
+    }
+
+    /// Take the highlighted image and move on to the type step
+    pub fn choose_wizard_image(&mut self) {
+        if let Some(choice) = self
+            .image_choices()
+            .into_iter()
+            .nth(self.wizard_data.selected_image_index)
+        {
+            self.wizard_data.image = choice.alias;
+        }
+        self.input_mode = InputMode::Wizard(WizardState::SelectType);
+    }
+
+    pub fn start_add_remote(&mut self) {
+        self.pending_remote = None;
+        self.input_mode = InputMode::Input {
+            prompt: "Name for the new remote:".to_string(),
+            input_type: InputType::ContainerName,
+            callback_action: InputCallback::AddRemoteName,
+        };
+        self.input_buffer.clear();
+    }
+
+    pub fn prompt_remote_url(&mut self, name: String) {
+        self.input_mode = InputMode::Input {
+            prompt: format!("Address of '{}' (host[:port] or ssh://[user@]host):", name),
+            input_type: InputType::Url,
+            callback_action: InputCallback::AddRemoteUrl(name),
+        };
+        self.input_buffer.clear();
+    }
+
+    pub fn start_command_line(&mut self) {
+        self.input_mode = InputMode::Input {
+            prompt: ":".to_string(),
+            input_type: InputType::Command,
+            callback_action: InputCallback::Command,
+        };
+        self.input_buffer.clear();
+    }
+
+    /// Show the prompt's previous or next history entry in place of what
+    /// is typed
+    pub fn recall_input(&mut self, forward: bool) {
+        let InputMode::Input {
+            callback_action, ..
+        } = &self.input_mode
+        else {
+            return;
+        };
+        let (Some(prompt), Some(history)) = (callback_action.history(), &mut self.prompt_history)
+        else {
+            return;
+        };
+        let entry = if forward {
+            history.next(prompt)
+        } else {
+            history.previous(prompt, self.input_buffer.as_str())
+        };
+        if let Some(entry) = entry {
+            self.input_buffer.set(&entry);
+        }
+    }
+
+    /// Add what was submitted to the prompt's history
+    pub fn record_input(&mut self, callback: &InputCallback, text: &str) {
+        let Some(history) = &mut self.prompt_history else {
+            return;
+        };
+        match callback.history() {
+            Some(prompt) => {
+                if let Err(e) = history.record(prompt, text) {
+                    warn!("Failed to save prompt history: {}", e);
+                }
+            }
+            None => history.reset(),
+        }
+    }
+
+    pub fn prompt_trust_token(&mut self) {
+        self.input_mode = InputMode::Input {
+            prompt: "Paste the trust token:".to_string(),
+            input_type: InputType::Token,
+            callback_action: InputCallback::AddRemoteToken,
+        };
+        self.input_buffer.clear();
+    }
+
+    /// Connect to a new remote, pin its certificate and pick an auth method
+    pub async fn begin_add_remote(&mut self, name: String, url: String) {
+        self.input_buffer.clear();
+        if ssh::is_ssh_url(&url) {
+            self.add_ssh_remote(name, url).await;
+            return;
+        }
+        self.show_info(format!("Connecting to '{}'...", url), true);
+
+        match Self::connect_pending_remote(name, &url).await {
+            Ok(pending) => {
+                let trusted = pending
+                    .client
+                    .server_info()
+                    .await
+                    .map(|info| info.is_trusted())
+                    .unwrap_or(false);
+                self.pending_remote = Some(pending);
+
+                if trusted {
+                    self.finish_add_remote(AuthType::Tls, None).await;
+                } else {
+                    self.show_command_menu(CommandMenu::RemoteAuth);
+                }
+            }
+            Err(e) => {
+                error!("Failed to connect to remote {}: {:?}", url, e);
+                self.show_error(
+                    format!("Failed to connect to '{}'", url),
+                    e.to_string(),
+                    vec![
+                        "Check the address and that port 8443 is reachable".to_string(),
+                        "Ensure core.https_address is set on the server".to_string(),
+                    ],
+                );
+            }
+        }
+    }
+
+    /// Save a remote reached over SSH once its tunnel connects; ssh handles
+    /// authentication, so there is no certificate to pin or auth method to pick
+    async fn add_ssh_remote(&mut self, name: String, url: String) {
+        let remote = RemoteConfig {
+            name,
+            url: url.trim().to_string(),
+            auth_type: AuthType::Ssh,
+            socket_path: None,
+        };
+
+        self.show_info(format!("Opening SSH tunnel to '{}'...", remote.url), true);
+
+        // Starting the tunnel blocks until ssh connects
+        let tunnel_remote = remote.clone();
+        let connected: Result<LxcClient> = async {
+            let client = tokio::task::spawn_blocking(move || LxcClient::for_remote(&tunnel_remote))
+                .await??;
+            let mut config = Config::load()?;
+            config.upsert_remote(remote.clone());
+            config.save()?;
+            Ok(client)
+        }
+        .await;
+
+        match connected {
+            Ok(client) => {
+                info!("Added SSH remote {} ({})", remote.name, remote.url);
+                self.activate_remote(&remote, client).await;
+            }
+            Err(e) => {
+                error!("Failed to add SSH remote {}: {:?}", remote.url, e);
+                self.show_error(
+                    format!("Failed to connect to '{}'", remote.url),
+                    e.to_string(),
+                    vec![
+                        "Check that 'ssh' reaches the host without a password prompt".to_string(),
+                        format!(
+                            "Set socket_path for the remote if LXD isn't at {}",
+                            ssh::DEFAULT_REMOTE_SOCKET
+                        ),
+                    ],
+                );
+            }
+        }
+    }
+
+    async fn connect_pending_remote(name: String, url: &str) -> Result<PendingRemote> {
+        let url = auth::normalize_remote_url(url)?;
+
+        // The handshake is blocking, keep it off the async runtime
+        let fetch_url = url.clone();
+        let server_cert =
+            tokio::task::spawn_blocking(move || auth::fetch_server_certificate(&fetch_url))
+                .await??;
+        let fingerprint = auth::fingerprint(&server_cert)?;
+        let client_cert = auth::load_or_generate_client_cert()?;
+
+        let remote = RemoteConfig {
+            name,
+            url: url.to_string(),
+            auth_type: AuthType::Tls,
+            socket_path: None,
+        };
+        let client = LxcClient::from_api(LxdApiClient::https(
+            &remote,
+            &server_cert,
+            &client_cert,
+            None,
+        )?);
+
+        Ok(PendingRemote {
+            remote,
+            server_cert,
+            fingerprint,
+            client,
+        })
+    }
+
+    pub async fn add_remote_with_token(&mut self, token: String) {
+        self.input_mode = InputMode::Normal;
+        self.input_buffer.clear();
+        let Some(pending) = &self.pending_remote else {
+            return;
+        };
+
+        let result = async {
+            TrustToken::decode(&token)?.verify_server(&pending.fingerprint)?;
+            pending.client.add_trust_token(&token).await?;
+            if !pending.client.server_info().await?.is_trusted() {
+                bail!("The server accepted the token but still doesn't trust this client");
+            }
+            Ok(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => self.finish_add_remote(AuthType::Tls, None).await,
+            Err(e) => {
+                let name = pending.remote.name.clone();
+                self.pending_remote = None;
+                self.show_error(
+                    format!("Failed to add remote '{}'", name),
+                    e.to_string(),
+                    vec![
+                        "Generate a new token with 'lxc config trust add'".to_string(),
+                        "Tokens can only be used once".to_string(),
+                    ],
+                );
+            }
+        }
+    }
+
+    /// Use a client certificate that was added to the server's trust store manually
+    pub async fn add_remote_with_certificate(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let Some(pending) = &self.pending_remote else {
+            return;
+        };
+
+        match pending.client.server_info().await {
+            Ok(info) if info.is_trusted() => self.finish_add_remote(AuthType::Tls, None).await,
+            Ok(_) => {
+                let cert_path = Config::config_dir().join("client.crt");
+                self.show_error(
+                    format!("'{}' doesn't trust this client", pending.remote.name),
+                    "The client certificate is not in the server's trust store".to_string(),
+                    vec![
+                        format!(
+                            "On the server run: lxc config trust add-certificate {}",
+                            cert_path.display()
+                        ),
+                        "Or authenticate with a trust token instead".to_string(),
+                    ],
+                );
+                self.pending_remote = None;
+            }
+            Err(e) => {
+                self.show_error(
+                    format!("Failed to query '{}'", pending.remote.name),
+                    e.to_string(),
+                    vec!["Check the server is reachable".to_string()],
+                );
+                self.pending_remote = None;
+            }
+        }
+    }
+
+    /// Start an OIDC device-code login; completion is handled in the background
+    pub async fn add_remote_with_oidc(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let Some(pending) = &self.pending_remote else {
+            return;
+        };
+        let name = pending.remote.name.clone();
+
+        let provider = match pending.client.oidc_provider().await {
+            Ok(Some(provider)) => provider,
+            Ok(None) => {
+                self.pending_remote = None;
+                self.show_error(
+                    format!("'{}' doesn't offer OIDC login", name),
+                    "The server did not advertise an OIDC identity provider".to_string(),
+                    vec![
+                        "Configure oidc.issuer and oidc.client.id on the server".to_string(),
+                        "Or authenticate with a trust token instead".to_string(),
+                    ],
+                );
+                return;
+            }
+            Err(e) => {
+                self.pending_remote = None;
+                self.show_error(
+                    format!("Failed to query '{}'", name),
+                    e.to_string(),
+                    vec!["Check the server is reachable".to_string()],
+                );
+                return;
+            }
+        };
+
+        let http = reqwest::Client::new();
+        let device = match auth::start_device_login(&http, &provider).await {
+            Ok(device) => device,
+            Err(e) => {
+                self.pending_remote = None;
+                self.show_error(
+                    "OIDC login failed".to_string(),
+                    e.to_string(),
+                    vec!["Check the identity provider supports device login".to_string()],
+                );
+                return;
+            }
+        };
+
+        let operation_id = self.register_operation(format!("OIDC login for '{}'", name), None);
+        self.start_operation(&operation_id);
+
+        let (tx, rx) = oneshot::channel();
+        let login_device = device.clone();
+        tokio::spawn(async move {
+            let result = auth::poll_device_token(&http, &provider, &login_device).await;
+            let _ = tx.send(result);
+        });
+        self.oidc_login = Some((operation_id, rx));
+
+        let url = device
+            .verification_uri_complete
+            .unwrap_or(device.verification_uri);
+        self.show_info(
+            format!(
+                "To log in to '{}', open:\n\n{}\n\nand enter the code: {}\n\n\
+                Login continues in the background.",
+                name, url, device.user_code
+            ),
+            false,
+        );
+    }
+
+    async fn complete_oidc_login(&mut self, operation_id: String, result: OidcLoginResult) {
+        let tokens = match result {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.pending_remote = None;
+                self.show_error(
+                    "OIDC login failed".to_string(),
+                    e.to_string(),
+                    vec!["Try adding the remote again".to_string()],
+                );
+                return;
+            }
+        };
+
+        self.complete_operation(&operation_id, true, None);
+        self.finish_add_remote(AuthType::Oidc, Some(tokens)).await;
+    }
+
+    /// Persist the pending remote and its credentials, then switch to it
+    async fn finish_add_remote(&mut self, auth_type: AuthType, tokens: Option<OidcTokens>) {
+        let Some(pending) = self.pending_remote.take() else {
+            return;
+        };
+        let remote = RemoteConfig {
+            auth_type,
+            ..pending.remote
+        };
+
+        let saved = (|| -> Result<()> {
+            auth::save_server_cert(&remote.name, &pending.server_cert)?;
+            if let Some(tokens) = &tokens {
+                auth::save_oidc_tokens(&remote.name, tokens)?;
+            }
+            let mut config = Config::load()?;
+            config.upsert_remote(remote.clone());
+            config.save()?;
+            Ok(())
+        })();
+
+        if let Err(e) = saved {
+            error!("Failed to save remote {}: {:?}", remote.name, e);
+            self.show_error(
+                format!("Failed to save remote '{}'", remote.name),
+                e.to_string(),
+                vec![format!(
+                    "Check permissions on {}",
+                    Config::config_dir().display()
+                )],
+            );
+            return;
+        }
+
+        info!("Added remote {} ({:?})", remote.name, remote.auth_type);
+        self.switch_remote(&remote).await;
+    }
+
+    pub async fn switch_remote(&mut self, remote: &RemoteConfig) {
+        match LxcClient::for_remote(remote) {
+            Ok(client) => self.activate_remote(remote, client).await,
+            Err(e) => {
+                self.show_error(
+                    format!("Failed to connect to '{}'", remote.name),
+                    e.to_string(),
+                    vec!["Try adding the remote again".to_string()],
+                );
+            }
+        }
+    }
+
+    async fn activate_remote(&mut self, remote: &RemoteConfig, client: LxcClient) {
+        self.lxc_client = client;
+        self.active_remote = Some(remote.name.clone());
+        self.selected = 0;
+        self.expiring_images.clear();
+        self.next_image_check = None;
+        self.unavailable.clear();
+        self.resubscribe_events();
+        self.heartbeat
+            .start(self.lxc_client.clone(), self.polls.clone());
+        self.load_server_details().await;
+        let _ = self.refresh_containers().await;
+        self.show_success(format!("Connected to remote '{}'", remote.name));
+    }
+
+    pub fn cancel_add_remote(&mut self) {
+        self.pending_remote = None;
+        self.input_mode = InputMode::Normal;
+        self.message = Some("Operation cancelled".to_string());
+    }
+
+    /// Clamp scroll positions after the terminal is resized
+    pub fn handle_resize(&mut self, log_page: u16) {
+        debug!("Terminal resized; log view shows {} lines", log_page);
+        self.scroll_help(0);
+        if self.log_scroll.is_some() {
+            self.scroll_log(0, log_page);
+        }
+    }
+
+    /// Show or hide the performance HUD, starting from fresh timings
+    pub fn toggle_perf(&mut self) {
+        self.show_perf = !self.show_perf;
+        self.perf = PerfStats::default();
+    }
+
+    pub fn show_help(&mut self) {
+        self.help_scroll = 0;
+        self.input_mode = InputMode::Help {
+            query: String::new(),
+            searching: false,
+        };
+    }
+
+    /// Scroll the help screen by `delta` lines, clamped to its content
+    pub fn scroll_help(&mut self, delta: i32) {
+        let InputMode::Help { query, .. } = &self.input_mode else {
+            return;
+        };
+        let max = keymap::help_line_count(query).saturating_sub(1);
+        let scroll = (self.help_scroll as i32 + delta).clamp(0, max as i32);
+        self.help_scroll = scroll as u16;
+    }
+
+    /// Open the current log file in the log view, scrolled to the end
+    pub fn open_log(&mut self) {
+        let Some(path) = logging::log_path() else {
+            self.show_error(
+                "Log Unavailable".to_string(),
+                "File logging is not enabled".to_string(),
+                vec!["Check the [logging] section of the config file".to_string()],
+            );
+            return;
+        };
+
+        match std::fs::read_to_string(path) {
+            Ok(text) => {
+                self.log_lines = text.lines().map(str::to_string).collect();
+                self.log_scroll = None;
+                self.input_mode = InputMode::Log;
+            }
+            Err(e) => self.show_error(
+                "Log Unavailable".to_string(),
+                format!("Failed to read {}: {}", path.display(), e),
+                Vec::new(),
+            ),
+        }
+    }
+
+    /// Scroll the log view by `delta` lines, where `page` lines fit on screen
+    pub fn scroll_log(&mut self, delta: i32, page: u16) {
+        let max = self.log_lines.len().saturating_sub(page as usize);
+        let current = self.log_scroll.map_or(max, |scroll| scroll as usize);
+        let scroll = (current as i64 + delta as i64).clamp(0, max as i64) as u16;
+        // Reaching the bottom anchors the view to the end again
+        self.log_scroll = (scroll as usize != max).then_some(scroll);
+    }
+
+    /// Follow the selected container's state in the watch view
+    pub async fn open_watch(&mut self) {
+        if let Some(container) = self.get_selected_container().await {
+            let mut watch = Watch::new(container.qualified_name());
+            watch.image = container.image_label();
+            watch.healthcheck = container.healthcheck.clone();
+            watch.restart = container.restart;
+            watch.ports = container.ports.clone();
+            watch.notes = container.notes.clone();
+            let (client, name) = self.client_for(&watch.container);
+            match client.dns_names(&name).await {
+                Ok(names) => watch.dns_names = names,
+                Err(e) => warn!("Couldn't find the DNS names of {}: {}", watch.container, e),
+            }
+            self.watch = Some(watch);
+            self.input_mode = InputMode::Watch;
+            self.poll_watch().await;
+        }
+    }
+
+    /// Poll the watched container when the view is open and a poll is due
+    pub async fn poll_watch(&mut self) {
+        let Some(watch) = &self.watch else {
+            return;
+        };
+        let now = Instant::now();
+        if !matches!(self.input_mode, InputMode::Watch)
+            || !watch.due(now)
+            || !self.polls.try_take(Category::Views, now)
+        {
+            return;
+        }
+
+        let scrape = watch.scrape_due(now) && self.unavailable.reason(Feature::Metrics).is_none();
+        let (client, name) = self.client_for(&watch.container);
+        let result = client.get_state(&name).await.map_err(|e| e.to_string());
+        let metrics = if scrape {
+            let metrics = client.instance_metrics(&name).await;
+            if let Err(e) = &metrics {
+                self.note_unsupported(Feature::Metrics, e);
+            }
+            Some(metrics.map_err(|e| e.to_string()))
+        } else {
+            None
+        };
+        if let Some(watch) = &mut self.watch {
+            watch.record(result, now);
+            if let Some(metrics) = metrics {
+                watch.record_metrics(metrics, now);
+            }
+        }
+    }
+
+    /// Rank the running containers by usage. Samples are kept while the
+    /// view is closed, so reopening it shows the same window again.
+    pub async fn open_top(&mut self) {
+        self.top.get_or_insert_with(Top::new);
+        self.input_mode = InputMode::Top;
+        self.poll_top().await;
+    }
+
+    /// Sample every running container when the ranking view is open and a
+    /// poll is due
+    pub async fn poll_top(&mut self) {
+        let Some(top) = &self.top else {
+            return;
+        };
+        let now = Instant::now();
+        let viewed = matches!(self.input_mode, InputMode::Top | InputMode::Dashboard);
+        if !viewed || !top.due(now) || !self.polls.try_take(Category::Views, now) {
+            return;
+        }
+
+        let result = if self.all_remotes {
+            let polls = futures::future::join_all(self.remote_clients.iter().map(
+                |(remote, client)| async move { (remote.clone(), client.instance_states().await) },
+            ))
+            .await;
+            let mut states = Vec::new();
+            let mut failed = Vec::new();
+            for (remote, result) in polls {
+                match result {
+                    Ok(found) => states.extend(
+                        found
+                            .into_iter()
+                            .map(|(name, state)| (format!("{}:{}", remote, name), state)),
+                    ),
+                    Err(e) => failed.push(format!("{}: {}", remote, e)),
+                }
+            }
+            if failed.is_empty() {
+                Ok(states)
+            } else {
+                Err(failed.join("; "))
+            }
+        } else {
+            self.lxc_client
+                .instance_states()
+                .await
+                .map_err(|e| e.to_string())
+        };
+
+        if let Some(top) = &mut self.top {
+            top.record(result, now);
+        }
+    }
+
+    /// Sum up the listed servers; samples usage for the top consumers and
+    /// follows events while open
+    pub async fn open_dashboard(&mut self) {
+        self.dashboard.get_or_insert_with(Dashboard::new);
+        self.top.get_or_insert_with(Top::new);
+        self.input_mode = InputMode::Dashboard;
+        self.resubscribe_events();
+        self.poll_dashboard().await;
+        self.poll_top().await;
+    }
+
+    /// Drill down from the dashboard into the container list
+    pub fn close_dashboard(&mut self) {
+        self.input_mode = InputMode::Normal;
+        if !self.events_wanted() {
+            self.events.stop();
+        }
+    }
+
+    /// Read storage pool usage on the active server when the dashboard is
+    /// open and a read is due
+    pub async fn poll_dashboard(&mut self) {
+        let Some(dashboard) = &self.dashboard else {
+            return;
+        };
+        let now = Instant::now();
+        if !matches!(self.input_mode, InputMode::Dashboard)
+            || !dashboard.due(now)
+            || !self.polls.try_take(Category::Views, now)
+        {
+            return;
+        }
+
+        let result = self
+            .lxc_client
+            .storage_usage()
+            .await
+            .map_err(|e| e.to_string());
+        if let Some(dashboard) = &mut self.dashboard {
+            dashboard.record(result, now);
+        }
+    }
+
+    /// List when each container starts at boot, on the active server
+    pub async fn open_boot_order(&mut self) {
+        self.boot_order.get_or_insert_with(BootOrder::new);
+        self.input_mode = InputMode::BootOrder;
+        self.reload_boot_order().await;
+    }
+
+    pub async fn reload_boot_order(&mut self) {
+        let result = self.lxc_client.boot_order().await;
+        let Some(order) = &mut self.boot_order else {
+            return;
+        };
+        match result {
+            Ok(entries) => order.set_entries(entries),
+            Err(e) => {
+                error!("Failed to load the boot order: {:?}", e);
+                order.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Move the selected container to the next `boot.autostart` setting
+    pub async fn cycle_autostart(&mut self) {
+        let Some(entry) = self.boot_order.as_ref().and_then(BootOrder::selected_entry) else {
+            return;
+        };
+        let action = PendingAction::SetBootConfig {
+            container: entry.name.clone(),
+            key: boot::AUTOSTART_KEY,
+            value: entry.autostart.next_value().to_string(),
+        };
+        self.run_or_preview(action).await;
+    }
+
+    /// Prompt for the selected container's priority or delay, starting from
+    /// the current value
+    pub fn start_edit_boot_setting(&mut self, key: &'static str) {
+        let Some(entry) = self.boot_order.as_ref().and_then(BootOrder::selected_entry) else {
+            return;
+        };
+        let (prompt, current) = if key == boot::DELAY_KEY {
+            (
+                format!("Seconds to wait after starting '{}':", entry.name),
+                entry.delay.to_string(),
+            )
+        } else {
+            (
+                format!("Boot priority of '{}' (higher starts first):", entry.name),
+                entry.priority.to_string(),
+            )
+        };
+        self.input_mode = InputMode::Input {
+            prompt,
+            input_type: InputType::Number,
+            callback_action: InputCallback::SetBootConfig {
+                container: entry.name.clone(),
+                key,
+            },
+        };
+        self.input_buffer.set(&current);
+    }
+
+    /// Check a typed priority or delay before setting it
+    pub async fn submit_boot_setting(&mut self, container: String, key: &'static str, text: &str) {
+        self.input_buffer.clear();
+        self.input_mode = InputMode::BootOrder;
+        let valid = if key == boot::DELAY_KEY {
+            text.parse::<u64>().is_ok()
+        } else {
+            text.parse::<i64>().is_ok()
+        };
+        if !valid {
+            self.show_error(
+                format!("Invalid value for {}", key),
+                format!("'{}' is not a whole number", text),
+                vec![if key == boot::DELAY_KEY {
+                    "The delay is a number of seconds, 0 or more".to_string()
+                } else {
+                    "Use a whole number; 0 is the default".to_string()
+                }],
+            );
+            return;
+        }
+        self.run_or_preview(PendingAction::SetBootConfig {
+            container,
+            key,
+            value: text.to_string(),
+        })
+        .await;
+    }
+
+    async fn set_boot_config(&mut self, container: &str, key: &str, value: &str) {
+        let (client, name) = self.client_for(container);
+        match client.set_config(&name, key, value).await {
+            Ok(()) => self.reload_boot_order().await,
+            Err(e) => {
+                error!("Failed to set {} of {}: {:?}", key, container, e);
+                self.show_error(
+                    format!("Failed to set {} of '{}'", key, container),
+                    e.to_string(),
+                    vec!["Check that you may edit the instance's config".to_string()],
+                );
+            }
+        }
+    }
+
+    /// List the instances on the active server whose base image has a newer
+    /// version in its image store
+    pub async fn open_audit(&mut self) {
+        self.audit.get_or_insert_with(Audit::new);
+        self.return_to_audit();
+        self.reload_audit().await;
+    }
+
+    pub async fn reload_audit(&mut self) {
+        let result = self.lxc_client.audit().await;
+        let Some(audit) = &mut self.audit else {
+            return;
+        };
+        match result {
+            Ok(entries) => audit.set_entries(entries),
+            Err(e) => {
+                error!("Failed to audit base images: {:?}", e);
+                audit.error = Some(e.to_string());
+            }
+        }
+    }
+
+    fn return_to_audit(&mut self) {
+        self.input_mode = InputMode::Audit {
+            confirm_rebuild: false,
+        };
+    }
+
+    /// List the DNS zones of the active server with their records
+    pub async fn open_zones(&mut self) {
+        self.zones.get_or_insert_with(Zones::new);
+        self.return_to_zones();
+        self.reload_zones().await;
+    }
+
+    pub async fn reload_zones(&mut self) {
+        let result = self.lxc_client.network_zones().await;
+        let Some(view) = &mut self.zones else {
+            return;
+        };
+        match result {
+            Ok(zones) => view.set_zones(zones),
+            Err(e) => {
+                error!("Failed to load the network zones: {:?}", e);
+                view.error = Some(e.to_string());
+            }
+        }
+    }
+
+    fn return_to_zones(&mut self) {
+        self.input_mode = InputMode::Zones {
+            confirm_delete: false,
+        };
+    }
+
+    pub fn start_new_zone(&mut self) {
+        self.input_mode = InputMode::Input {
+            prompt: "Name of the new zone, e.g. lxd.example.net:".to_string(),
+            input_type: InputType::DomainName,
+            callback_action: InputCallback::NewZone,
+        };
+        self.input_buffer.clear();
+    }
+
+    /// Prompt for a record in the selected zone, typed as its name and entries
+    pub fn start_new_zone_record(&mut self) {
+        let Some(zone) = self.zones.as_ref().and_then(Zones::selected_zone) else {
+            return;
+        };
+        self.input_mode = InputMode::Input {
+            prompt: format!("New record in '{}', e.g. www A 10.0.0.5:", zone.name),
+            input_type: InputType::ZoneEntries,
+            callback_action: InputCallback::NewZoneRecord(zone.name.clone()),
+        };
+        self.input_buffer.clear();
+    }
+
+    /// Prompt for the entries of the selected record, starting from the current ones
+    pub fn start_edit_zone_record(&mut self) {
+        let Some((zone, record)) = self.zones.as_ref().and_then(Zones::selected_record) else {
+            return;
+        };
+        self.input_mode = InputMode::Input {
+            prompt: format!("Entries of '{}.{}':", record.name, zone.name),
+            input_type: InputType::ZoneEntries,
+            callback_action: InputCallback::EditZoneRecord {
+                zone: zone.name.clone(),
+                record: record.clone(),
+            },
+        };
+        self.input_buffer
+            .set(&zones::format_entries(&record.entries));
+    }
+
+    /// Check what was typed at a zones prompt before making the change
+    pub async fn submit_zone_input(&mut self, callback: InputCallback, text: &str) {
+        self.input_buffer.clear();
+        self.return_to_zones();
+        let change =
+            match callback {
+                InputCallback::NewZone if text.trim().is_empty() => return,
+                InputCallback::NewZone => Ok(ZoneChange::CreateZone(text.trim().to_string())),
+                InputCallback::NewZoneRecord(zone) => zones::parse_record(text)
+                    .map(|record| ZoneChange::CreateRecord { zone, record }),
+                InputCallback::EditZoneRecord { zone, mut record } => zones::parse_entries(text)
+                    .map(|entries| {
+                        record.entries = entries;
+                        ZoneChange::UpdateRecord { zone, record }
+                    }),
+                _ => return,
+            };
+        match change {
+            Ok(change) => self.run_or_preview(PendingAction::ChangeZone(change)).await,
+            Err(e) => self.show_error(
+                "Invalid record".to_string(),
+                e.to_string(),
+                vec![format!(
+                    "Use one of the types {}",
+                    zones::RECORD_TYPES.join(", ")
+                )],
+            ),
+        }
+    }
+
+    /// Ask before deleting the selected zone or record
+    pub fn ask_delete_zone_entry(&mut self) {
+        if self.zones.as_ref().and_then(Zones::selected_row).is_some() {
+            self.input_mode = InputMode::Zones {
+                confirm_delete: true,
+            };
+        }
+    }
+
+    pub async fn delete_selected_zone_entry(&mut self) {
+        self.return_to_zones();
+        let Some(view) = &self.zones else {
+            return;
+        };
+        let change = match view.selected_row() {
+            Some(Row::Zone(z)) => ZoneChange::DeleteZone(view.zones[z].name.clone()),
+            Some(Row::Record(z, r)) => ZoneChange::DeleteRecord {
+                zone: view.zones[z].name.clone(),
+                record: view.zones[z].records[r].name.clone(),
+            },
+            None => return,
+        };
+        self.run_or_preview(PendingAction::ChangeZone(change)).await;
+    }
+
+    async fn change_zone(&mut self, change: &ZoneChange) {
+        match self.lxc_client.change_network_zone(change.request()).await {
+            Ok(()) => {
+                if let Some(view) = &mut self.zones {
+                    view.status = Some(change.done());
+                }
+                self.reload_zones().await;
+            }
+            Err(e) => {
+                error!("{}: {:?}", change.failed(), e);
+                let hint = match change {
+                    ZoneChange::DeleteZone(_) => {
+                        "Remove the zone from the networks publishing into it first"
+                    }
+                    _ => "Check the zone exists and the record name isn't taken",
+                };
+                self.show_error(change.failed(), e.to_string(), vec![hint.to_string()]);
+            }
+        }
+    }
+
+    /// List the images stored on the active server, with whether LXD keeps
+    /// each up to date
+    pub async fn open_image_store(&mut self) {
+        self.image_store.get_or_insert_with(ImageStore::new);
+        self.return_to_image_store();
+        self.reload_image_store().await;
+    }
+
+    pub async fn reload_image_store(&mut self) {
+        let result = self.lxc_client.image_store().await;
+        if let Ok(images) = &result {
+            self.note_expiring_images(images);
+        }
+        let Some(view) = &mut self.image_store else {
+            return;
+        };
+        match result {
+            Ok(images) => view.set_images(images),
+            Err(e) => {
+                error!("Failed to list the image store: {:?}", e);
+                view.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Look for images on the active server that LXD deletes within the
+    /// warning period, at most every few minutes
+    async fn check_expiring_images(&mut self) {
+        self.next_image_check = Some(Instant::now() + IMAGE_CHECK_INTERVAL);
+        if self.all_remotes || self.expiry_warning.is_zero() {
+            self.expiring_images.clear();
+            return;
+        }
+        match self.lxc_client.image_store().await {
+            Ok(images) => self.note_expiring_images(&images),
+            Err(e) => debug!("Couldn't check for expiring images: {}", e),
+        }
+    }
+
+    fn note_expiring_images(&mut self, images: &[StoredImage]) {
+        self.expiring_images = publish::expiring(images, chrono::Local::now(), self.expiry_warning)
+            .into_iter()
+            .cloned()
+            .collect();
+    }
+
+    fn return_to_image_store(&mut self) {
+        self.input_mode = InputMode::ImageStore;
+    }
+
+    /// Ask for a config key to look up across the active server's
+    /// instances, starting from the last one searched
+    pub fn start_key_search(&mut self) {
+        let pattern = self
+            .key_search
+            .as_ref()
+            .map(|view| view.pattern.clone())
+            .unwrap_or_default();
+        self.input_mode = InputMode::Input {
+            prompt: "Config key (security.privileged, or security.* for all it starts):"
+                .to_string(),
+            input_type: InputType::ConfigKey,
+            callback_action: InputCallback::SearchConfigKey,
+        };
+        self.input_buffer.set(&pattern);
+    }
+
+    pub async fn submit_key_search(&mut self, text: &str) {
+        let pattern = text.trim();
+        if pattern.is_empty() {
+            self.cancel_input();
+            return;
+        }
+        self.input_buffer.clear();
+        self.key_search = Some(KeySearch::new(pattern.to_string()));
+        self.input_mode = InputMode::KeySearch;
+        self.reload_key_search().await;
+    }
+
+    pub async fn reload_key_search(&mut self) {
+        let result = self.lxc_client.instances().await;
+        let Some(view) = &mut self.key_search else {
+            return;
+        };
+        match result {
+            Ok(instances) => view.set_instances(&instances),
+            Err(e) => {
+                error!("Failed to read the instances' config: {:?}", e);
+                view.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Close the key search on the selected instance in the list
+    pub async fn show_key_search_hit(&mut self) {
+        let Some(hit) = self.key_search.as_ref().and_then(|v| v.selected_hit()) else {
+            return;
+        };
+        let name = hit.instance.clone();
+        self.input_mode = InputMode::Normal;
+        self.select_container(&name).await;
+    }
+
+    /// List the active server's storage pools, checking the health of
+    /// those the local host has the tools for
+    pub async fn open_pools(&mut self) {
+        let local = self.active_remote.is_none();
+        self.pools
+            .get_or_insert_with(|| PoolsView::new(local))
+            .local = local;
+        self.return_to_pools();
+        self.reload_pools().await;
+    }
+
+    pub async fn reload_pools(&mut self) {
+        let result = self.lxc_client.storage_pools().await;
+        let Some(view) = &mut self.pools else {
+            return;
+        };
+        match result {
+            Ok(pools) => view.set_pools(pools),
+            Err(e) => {
+                error!("Failed to list the storage pools: {:?}", e);
+                view.error = Some(e.to_string());
+                return;
+            }
+        }
+        if !view.local {
+            return;
+        }
+
+        // The checks can take seconds, so they run in the background
+        let sudo = service::escalation() == Some("sudo");
+        for pool in &view.pools {
+            let Some(tool) = pool.tool.clone() else {
+                continue;
+            };
+            let name = pool.name.clone();
+            let tx = view.sender();
+            view.checking.insert(name.clone());
+            tokio::spawn(async move {
+                let _ = tx.send((name, tool.check(sudo).await));
+            });
+        }
+    }
+
+    /// Take in the pool health checks that finished
+    pub fn poll_pools(&mut self) {
+        if let Some(view) = &mut self.pools {
+            view.drain();
+        }
+    }
+
+    fn return_to_pools(&mut self) {
+        self.input_mode = InputMode::Pools {
+            confirm_scrub: false,
+        };
+    }
+
+    /// Ask before scrubbing the selected pool. Only the local server's ZFS
+    /// and btrfs pools can be.
+    pub fn ask_scrub_pool(&mut self) {
+        let Some(view) = &mut self.pools else {
+            return;
+        };
+        let Some(pool) = view.selected_pool() else {
+            return;
+        };
+        if !view.local {
+            view.status = Some("Only pools of the local server can be scrubbed".to_string());
+        } else if pool.tool.is_none() {
+            view.status = Some(format!("{} pools have no scrub", pool.driver));
+        } else {
+            self.input_mode = InputMode::Pools {
+                confirm_scrub: true,
+            };
+        }
+    }
+
+    /// Start scrubbing the selected pool as root, with the TUI suspended so
+    /// sudo can ask for a password
+    pub fn scrub_pool(&mut self) {
+        self.return_to_pools();
+        let Some(view) = &mut self.pools else {
+            return;
+        };
+        let Some((name, tool)) = view
+            .selected_pool()
+            .and_then(|pool| Some((pool.name.clone(), pool.tool.clone()?)))
+        else {
+            return;
+        };
+        let command = tool.scrub_command(service::escalation());
+        info!("Running '{}'", command);
+        view.scrubbing = Some(name.clone());
+        self.external_command = Some(ExternalCommand {
+            name: format!("Scrub {}", name),
+            command,
+            pause: false,
+        });
+    }
+
+    /// After the scrub command returned: check the pools again, so the
+    /// scrub shows as running
+    pub async fn scrub_command_finished(&mut self, succeeded: bool) {
+        let Some(name) = self.pools.as_mut().and_then(|view| view.scrubbing.take()) else {
+            return;
+        };
+        self.reload_pools().await;
+        if let Some(view) = &mut self.pools {
+            view.status = Some(if succeeded {
+                format!("Started scrubbing '{}'", name)
+            } else {
+                format!("Failed to start scrubbing '{}'", name)
+            });
+        }
+    }
+
+    /// Add up the CPU and memory limits of the active server's instances
+    /// against what its host has
+    pub async fn open_capacity(&mut self) {
+        self.capacity.get_or_insert_with(Capacity::new);
+        self.input_mode = InputMode::Capacity;
+        self.reload_capacity().await;
+    }
+
+    pub async fn reload_capacity(&mut self) {
+        let result = self.lxc_client.capacity().await;
+        let Some(view) = &mut self.capacity else {
+            return;
+        };
+        match result {
+            Ok((host, reservations)) => view.set_reservations(host, reservations),
+            Err(e) => {
+                error!("Failed to read the host's capacity: {:?}", e);
+                view.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// The selected image when it was copied from a remote; the others have
+    /// nothing to update from, which the view's status says
+    fn selected_refreshable_image(&mut self) -> Option<(String, String, bool)> {
+        let view = self.image_store.as_mut()?;
+        let image = view.selected_image()?;
+        if !image.refreshable() {
+            view.status = Some(format!(
+                "'{}' wasn't copied from a remote, so there is nothing to update it from",
+                image.description
+            ));
+            return None;
+        }
+        Some((
+            image.fingerprint.clone(),
+            image.description.clone(),
+            image.auto_update,
+        ))
+    }
+
+    /// Turn LXD's automatic updates of the selected image on or off
+    pub async fn toggle_image_auto_update(&mut self) {
+        let Some((fingerprint, description, auto_update)) = self.selected_refreshable_image()
+        else {
+            return;
+        };
+        let action = PendingAction::SetImageAutoUpdate {
+            fingerprint,
+            description,
+            auto_update: !auto_update,
+        };
+        self.run_or_preview(action).await;
+    }
+
+    /// Pull the latest build of the selected image from its remote now
+    pub async fn refresh_selected_image(&mut self) {
+        let Some((fingerprint, description, _)) = self.selected_refreshable_image() else {
+            return;
+        };
+        let action = PendingAction::RefreshImage {
+            fingerprint,
+            description,
+        };
+        self.run_or_preview(action).await;
+    }
+
+    async fn set_image_auto_update(&mut self, fingerprint: &str, description: &str, on: bool) {
+        let request = ApiRequest::set_image_auto_update(fingerprint, on);
+        match self.lxc_client.update_image(request).await {
+            Ok(()) => {
+                if let Some(view) = &mut self.image_store {
+                    view.status = Some(format!(
+                        "Auto-update {} for '{}'",
+                        if on { "on" } else { "off" },
+                        description
+                    ));
+                }
+                self.reload_image_store().await;
+            }
+            Err(e) => {
+                error!("Failed to set auto_update of {}: {:?}", fingerprint, e);
+                self.show_error(
+                    format!("Failed to change auto-update of '{}'", description),
+                    e.to_string(),
+                    vec!["Check that the image is still in the store".to_string()],
+                );
+            }
+        }
+    }
+
+    async fn refresh_image(&mut self, fingerprint: &str, description: &str) {
+        let operation_id =
+            self.register_operation(format!("Refresh image '{}'", description), None);
+        self.set_kind(&operation_id, "refresh image".to_string());
+
+        self.show_progress(&operation_id);
+        self.start_operation(&operation_id);
+
+        match self.lxc_client.refresh_image_async(fingerprint).await {
+            Ok(lxd_operation_path) => self.track_lxd_operation(
+                &operation_id,
+                lxd_operation_path,
+                String::new(),
+                "image-refresh",
+                format!("Refreshed image '{}'", description),
+            ),
+            Err(e) => {
+                error!("Failed to refresh image {}: {:?}", fingerprint, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to refresh image '{}'", description),
+                    e.to_string(),
+                    vec!["Check that the image's remote can be reached".to_string()],
+                );
+            }
+        }
+    }
+
+    /// Ask before rebuilding the selected instance; LXD only rebuilds
+    /// stopped instances
+    pub fn ask_rebuild(&mut self) {
+        let Some(audit) = &mut self.audit else {
+            return;
+        };
+        let Some(entry) = audit.selected_entry() else {
+            return;
+        };
+        if entry.running {
+            audit.status = Some(format!("Stop '{}' before rebuilding it", entry.name));
+            return;
+        }
+        self.input_mode = InputMode::Audit {
+            confirm_rebuild: true,
+        };
+    }
+
+    /// Rebuild the selected instance from the latest version of its image
+    pub async fn rebuild_selected(&mut self) {
+        self.return_to_audit();
+        let Some(entry) = self.audit.as_ref().and_then(Audit::selected_entry) else {
+            return;
+        };
+        let Freshness::Outdated { fingerprint, .. } = &entry.freshness else {
+            return;
+        };
+        let action = PendingAction::Rebuild {
+            container: entry.name.clone(),
+            fingerprint: fingerprint.clone(),
+        };
+        self.run_or_preview(action).await;
+    }
+
+    async fn rebuild_instance(&mut self, container: &str, fingerprint: &str) {
+        let operation_id = self.register_operation(
+            format!("Rebuild '{}' from the latest image", container),
+            Some(container.to_string()),
+        );
+        self.set_kind(&operation_id, format!("rebuild {}", container));
+
+        self.show_progress(&operation_id);
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(container);
+        match client.rebuild_instance_async(&name, fingerprint).await {
+            Ok(lxd_operation_path) => self.track_lxd_operation(
+                &operation_id,
+                lxd_operation_path,
+                container.to_string(),
+                "rebuild",
+                format!(
+                    "Rebuilt '{}' from image {}",
+                    container,
+                    fingerprint.chars().take(12).collect::<String>()
+                ),
+            ),
+            Err(e) => {
+                error!("Failed to rebuild {}: {:?}", container, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to rebuild '{}'", container),
+                    e.to_string(),
+                    vec!["Stop the instance before rebuilding it".to_string()],
+                );
+            }
+        }
+    }
+
+    /// Work out how the selected instance would be recreated as the other
+    /// type, and show it
+    pub async fn open_recreate(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        let source = container.qualified_name();
+        let (client, name) = self.client_for(&source);
+        match client.get_instance(&name).await {
+            Ok(instance) => {
+                let snapshot = chrono::Local::now()
+                    .format("before-recreate-%Y%m%d-%H%M")
+                    .to_string();
+                self.recreate = Some(Conversion::plan(&instance, source, snapshot));
+                self.input_mode = InputMode::Recreate;
+            }
+            Err(e) => self.show_error(
+                format!("Failed to read '{}'", source),
+                e.to_string(),
+                Vec::new(),
+            ),
+        }
+    }
+
+    /// Prompt for the new instance's name, or for the image it is created
+    /// from
+    pub fn start_recreate_field(&mut self, image: bool) {
+        let Some(plan) = &self.recreate else {
+            return;
+        };
+        let (prompt, input_type, callback_action, current) = if image {
+            (
+                format!(
+                    "Image for the {} (ubuntu:noble, images:debian/12):",
+                    plan.kind()
+                ),
+                InputType::ImageName,
+                InputCallback::RecreateImage,
+                plan.image.clone(),
+            )
+        } else {
+            (
+                format!("Name of the {}:", plan.kind()),
+                InputType::ContainerName,
+                InputCallback::RecreateName,
+                plan.name.clone(),
+            )
+        };
+        self.input_mode = InputMode::Input {
+            prompt,
+            input_type,
+            callback_action,
+        };
+        self.input_buffer.set(&current);
+    }
+
+    pub fn submit_recreate_field(&mut self, image: bool, text: &str) {
+        self.input_buffer.clear();
+        self.input_mode = InputMode::Recreate;
+        if let Some(plan) = &mut self.recreate {
+            let field = if image {
+                &mut plan.image
+            } else {
+                &mut plan.name
+            };
+            *field = text.trim().to_string();
+            plan.status = None;
+        }
+    }
+
+    /// Recreate the instance as planned, once it has a name and an image
+    pub async fn confirm_recreate(&mut self) {
+        let Some(plan) = &mut self.recreate else {
+            return;
+        };
+        if plan.name.is_empty() || plan.image.is_empty() {
+            plan.status = Some(format!(
+                "Give the {} a name and an image first",
+                plan.kind()
+            ));
+            return;
+        }
+        let action = PendingAction::Recreate(Box::new(plan.clone()));
+        self.run_or_preview(action).await;
+    }
+
+    async fn recreate_instance(&mut self, plan: &Conversion) {
+        self.recreate = None;
+        let target = plan.target();
+        let operation_id = self.register_operation(
+            format!(
+                "Recreate the config of '{}' as {} '{}'",
+                plan.source,
+                plan.kind(),
+                target
+            ),
+            Some(target.clone()),
+        );
+        self.set_kind(&operation_id, format!("recreate {}", plan.source));
+        self.show_progress(&operation_id);
+        self.start_operation(&operation_id);
+
+        // The snapshot keeps the original's data at the point it was left
+        let (client, name) = self.client_for(&plan.source);
+        if let Err(e) = client.create_snapshot(&name, Some(&plan.snapshot)).await {
+            error!("Failed to snapshot {}: {:?}", plan.source, e);
+            self.complete_operation(&operation_id, false, Some(e.to_string()));
+            self.show_error(
+                format!("Failed to snapshot '{}'", plan.source),
+                e.to_string(),
+                vec!["Nothing was created; check the space left in its pool".to_string()],
+            );
+            return;
+        }
+        match client.recreate_instance_async(plan).await {
+            Ok(lxd_operation_path) => self.track_lxd_operation(
+                &operation_id,
+                lxd_operation_path,
+                target.clone(),
+                "recreate",
+                format!(
+                    "Created {} '{}', stopped; '{}' is as it was, with snapshot '{}'",
+                    plan.kind(),
+                    target,
+                    plan.source,
+                    plan.snapshot
+                ),
+            ),
+            Err(e) => {
+                error!("Failed to recreate {}: {:?}", plan.source, e);
+                self.complete_operation(&operation_id, false, Some(e.to_string()));
+                self.show_error(
+                    format!("Failed to create '{}'", target),
+                    e.to_string(),
+                    recreate_suggestions(),
+                );
+            }
         }
     }
 
-    pub async fn previous(&mut self) {
-        let containers = self.containers.read().await;
-        if !containers.is_empty() {
-            if self.selected > 0 {
-                self.selected -= 1;
-            } else {
-                self.selected = containers.len() - 1;
+    /// Mark the selected container for comparison, or unmark it. Marking a
+    /// third container drops the oldest mark.
+    pub async fn toggle_compare_mark(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        let name = container.qualified_name();
+        if let Some(index) = self.compare_marks.iter().position(|m| *m == name) {
+            self.compare_marks.remove(index);
+        } else {
+            self.compare_marks.push(name);
+            if self.compare_marks.len() > 2 {
+                self.compare_marks.remove(0);
             }
         }
     }
 
-    pub async fn get_selected_container(&self) -> Option<Container> {
-        let containers = self.containers.read().await;
-        containers.get(self.selected).cloned()
+    /// Pick the selected instance for batch changes, or drop it
+    pub async fn toggle_pick(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        let name = container.qualified_name();
+        match self.picked.iter().position(|p| *p == name) {
+            Some(index) => {
+                self.picked.remove(index);
+            }
+            None => self.picked.push(name),
+        }
     }
 
-    pub fn show_confirm_dialog(&mut self, message: String, action: ConfirmAction) {
-        self.pending_action = Some(action.clone());
-        self.input_mode = InputMode::Confirmation { message, action };
+    /// Add a profile to the picked instances or remove it; with none
+    /// picked, to the selected one
+    pub async fn open_profiles(&mut self) {
+        let names = if self.picked.is_empty() {
+            let Some(container) = self.get_selected_container().await else {
+                return;
+            };
+            vec![container.qualified_name()]
+        } else {
+            self.picked.clone()
+        };
+        self.profile_batch = Some(ProfileBatch::new(names));
+        self.input_mode = InputMode::Profiles;
+        self.reload_profile_batch().await;
     }
 
-    pub fn show_status_modal(&mut self, modal_type: StatusModalType) {
-        self.input_mode = InputMode::StatusModal(modal_type);
+    /// Read the server's profiles, and each picked instance's
+    pub async fn reload_profile_batch(&mut self) {
+        let Some(view) = &self.profile_batch else {
+            return;
+        };
+        let names: Vec<String> = view.targets.iter().map(|t| t.name.clone()).collect();
+        let profiles = self.lxc_client.profiles().await;
+        let mut targets = Vec::new();
+        for name in names {
+            let (client, bare) = self.client_for(&name);
+            let result = client.get_instance(&bare).await;
+            targets.push(result.map(|i| i.profiles).map_err(|e| e.to_string()));
+        }
+        let Some(view) = &mut self.profile_batch else {
+            return;
+        };
+        match profiles {
+            Ok(profiles) => view.set_profiles(profiles),
+            Err(e) => view.error = Some(e.to_string()),
+        }
+        for (target, profiles) in view.targets.iter_mut().zip(targets) {
+            target.profiles = profiles;
+        }
     }
 
-    pub fn show_command_menu(&mut self, menu: CommandMenu) {
-        self.menu_selected = 0; // Reset selection when opening menu
-        self.input_mode = InputMode::CommandMenu(menu);
+    /// Add the selected profile to every picked instance, or remove it
+    pub async fn batch_profile(&mut self, add: bool) {
+        let Some(view) = &mut self.profile_batch else {
+            return;
+        };
+        let Some(profile) = view.selected_profile().cloned() else {
+            return;
+        };
+        let change = if add {
+            ProfileChange::Add(profile.clone())
+        } else {
+            ProfileChange::Remove(profile.clone())
+        };
+        let changes = view.changes(&change);
+        if changes.is_empty() {
+            view.status = Some(if add {
+                format!("Every instance already has '{}'", profile)
+            } else {
+                format!("No instance has '{}'", profile)
+            });
+            return;
+        }
+        self.run_or_preview(PendingAction::ChangeProfiles { change, changes })
+            .await;
     }
 
-    pub fn menu_next(&mut self, item_count: usize) {
-        if item_count > 0 {
-            self.menu_selected = (self.menu_selected + 1) % item_count;
+    /// Patch each instance's profiles in turn under one operation
+    async fn change_profiles(
+        &mut self,
+        change: &ProfileChange,
+        changes: Vec<(String, Vec<String>)>,
+    ) {
+        let operation_id = self.register_operation(change.describe(changes.len()), None);
+        self.start_operation(&operation_id);
+
+        let mut results = Vec::new();
+        for (container, profiles) in changes {
+            let (client, name) = self.client_for(&container);
+            let result = match client.set_profiles(&name, &profiles).await {
+                Ok(()) => Ok(profiles),
+                Err(e) => {
+                    error!("Failed to set the profiles of {}: {:?}", container, e);
+                    Err(e.to_string())
+                }
+            };
+            results.push((container, result));
+        }
+
+        let Some(view) = &mut self.profile_batch else {
+            return;
+        };
+        view.finish(results);
+        let (changed, unchanged, failed) = view.tally();
+        let mut status = match change {
+            ProfileChange::Add(profile) => format!("Added '{}' to {}", profile, changed),
+            ProfileChange::Remove(profile) => format!("Removed '{}' from {}", profile, changed),
+        };
+        if unchanged > 0 {
+            status.push_str(&format!(", {} unchanged", unchanged));
         }
+        if failed > 0 {
+            status.push_str(&format!(", {} failed", failed));
+        }
+        view.status = Some(status.clone());
+        let error = (failed > 0).then_some(status);
+        self.complete_operation(&operation_id, failed == 0, error);
     }
 
-    pub fn menu_previous(&mut self, item_count: usize) {
-        if item_count > 0 {
-            if self.menu_selected > 0 {
-                self.menu_selected -= 1;
+    /// Open the quick exec library for the picked instances, or the selected
+    /// one when none are picked. An action still running is shown instead.
+    pub async fn open_quick(&mut self) {
+        if !self.quick.as_ref().is_some_and(QuickView::running) {
+            let names = if self.picked.is_empty() {
+                let Some(container) = self.get_selected_container().await else {
+                    return;
+                };
+                vec![container.qualified_name()]
             } else {
-                self.menu_selected = item_count - 1;
+                self.picked.clone()
+            };
+            self.quick = Some(QuickView::new(self.quick_actions.clone(), names));
+        }
+        self.input_mode = InputMode::Quick { confirm: None };
+    }
+
+    /// Run the quick action at `index`, asking first when it wants that
+    pub async fn ask_quick(&mut self, index: usize) {
+        let Some(view) = &self.quick else {
+            return;
+        };
+        match view.actions.get(index) {
+            Some(action) if action.confirm => {
+                self.input_mode = InputMode::Quick {
+                    confirm: Some(index),
+                }
             }
+            Some(_) => self.run_quick(index),
+            None => {}
         }
     }
 
-    pub fn show_info(&mut self, message: String, auto_close: bool) {
-        self.show_status_modal(StatusModalType::Info {
-            message,
-            auto_close,
-        });
+    /// Run the quick action at `index` in every instance of the view at once
+    pub fn run_quick(&mut self, index: usize) {
+        self.input_mode = InputMode::Quick { confirm: None };
+        let action = self.quick.as_ref().and_then(|view| view.actions.get(index));
+        if let Some(action) = action.cloned() {
+            self.start_quick(action);
+        }
     }
 
-    pub fn show_error(&mut self, title: String, details: String, suggestions: Vec<String>) {
-        self.show_status_modal(StatusModalType::Error {
-            title,
-            details,
-            suggestions,
-        });
+    /// Ask for a command to run in the view's instances, as a one-off action
+    pub fn ask_quick_command(&mut self) {
+        let Some(view) = &self.quick else {
+            return;
+        };
+        let instances = match &view.names[..] {
+            [name] => format!("'{}'", name),
+            names => format!("{} instances", names.len()),
+        };
+        self.input_mode = InputMode::Input {
+            prompt: format!("Command to run in {} (sh -c):", instances),
+            input_type: InputType::Command,
+            callback_action: InputCallback::QuickCommand,
+        };
+        self.input_buffer.clear();
     }
 
-    pub fn show_success(&mut self, message: String) {
-        self.show_status_modal(StatusModalType::Success {
-            message,
-            started_at: Instant::now(),
+    pub fn submit_quick_command(&mut self, text: &str) {
+        let command = text.trim();
+        if command.is_empty() {
+            self.cancel_input();
+            return;
+        }
+        self.input_buffer.clear();
+        self.input_mode = InputMode::Quick { confirm: None };
+        self.start_quick(QuickAction {
+            name: command.to_string(),
+            command: command.to_string(),
+            key: quick::COMMAND_KEY,
+            confirm: false,
         });
     }
 
-    pub async fn start_selected(&mut self) {
-        if let Some(container) = self.get_selected_container().await {
-            let name = container.name.clone();
-            self.show_confirm_dialog(
-                format!("Start container '{}'?", name),
-                ConfirmAction::StartContainer(name),
-            );
+    /// Run the action last run again, in the same instances
+    pub fn rerun_quick(&mut self) {
+        let action = self.quick.as_ref().and_then(|view| view.run.as_ref());
+        if let Some(action) = action.map(|run| run.action.clone()) {
+            self.start_quick(action);
         }
     }
 
-    // execute_pending_action has been removed - the logic is now in handle_confirmation in main.rs
-    // to ensure immediate UI updates when the user confirms an action
+    /// Start `action` in every instance of the view at once, unless an
+    /// action still runs there
+    fn start_quick(&mut self, action: QuickAction) {
+        let Some(view) = &self.quick else {
+            return;
+        };
+        if view.running() {
+            return;
+        }
+        let names = view.names.clone();
+        let description = match &names[..] {
+            [name] => format!("Run '{}' in '{}'", action.name, name),
+            names => format!("Run '{}' in {} instances", action.name, names.len()),
+        };
+        let container = (names.len() == 1).then(|| names[0].clone());
+        let operation_id = self.register_operation(description, container);
+        self.start_operation(&operation_id);
 
-    pub async fn _unused_execute_pending_action(&mut self) {
-        if let Some(action) = self.pending_action.clone() {
-            self.pending_action = None;
+        let argv = ["sh".to_string(), "-c".to_string(), action.command.clone()];
+        let Some(view) = &mut self.quick else {
+            return;
+        };
+        view.start(action, operation_id);
+        let tx = view.sender();
+        let limit = self.quick_timeout;
+        let mut tasks = Vec::new();
+        for name in names {
+            let (client, instance) = self.client_for(&name);
+            let (tx, argv) = (tx.clone(), argv.clone());
+            tasks.push(tokio::spawn(async move {
+                let result = match timeout(limit, client.exec_output(&instance, &argv)).await {
+                    Ok(result) => result.map_err(|e| quick::Outcome::Failed(e.to_string())),
+                    Err(_) => Err(quick::Outcome::TimedOut(limit.as_secs())),
+                };
+                let _ = tx.send((name, result));
+            }));
+        }
+        if let Some(view) = &mut self.quick {
+            for task in tasks {
+                view.track(task.abort_handle());
+            }
+        }
+    }
 
-            // This method is kept for reference but not used
-            match action {
-                ConfirmAction::StartContainer(name) => {
-                    let operation_id = self.register_operation(
-                        format!("Start container '{}'", name),
-                        Some(name.clone()),
-                    );
+    /// Stop waiting on the instances a quick action is still running in
+    pub fn stop_quick(&mut self) {
+        let Some(view) = &mut self.quick else {
+            return;
+        };
+        if !view.cancel() {
+            return;
+        }
+        if let Some(run) = &view.run {
+            info!("Stopped waiting on '{}'", run.action.name);
+            let operation_id = run.operation_id.clone();
+            self.cancel_operation(&operation_id);
+        }
+    }
 
-                    self.show_status_modal(StatusModalType::Progress {
-                        operation_id: operation_id.clone(),
-                    });
-                    self.start_operation(&operation_id);
+    /// Take in the instances a quick action finished in, completing its
+    /// operation once every one has
+    pub fn poll_quick(&mut self) {
+        let Some(view) = &mut self.quick else {
+            return;
+        };
+        if view.drain() {
+            self.finish_quick();
+        }
+    }
 
-                    match self.lxc_client.start_container(&name).await {
-                        Ok(_) => {
-                            self.complete_operation(&operation_id, true, None);
-                            self.show_success(format!("Container '{}' started successfully", name));
-                            let _ = self.refresh_containers().await;
-                        }
-                        Err(e) => {
-                            error!("Failed to start container {}: {:?}", name, e);
-                            self.complete_operation(&operation_id, false, Some(e.to_string()));
-                            self.show_error(
-                                format!("Failed to start '{}'", name),
-                                e.to_string(),
-                                vec![
-                                    "Check if the container exists".to_string(),
-                                    "Verify LXD service is running".to_string(),
-                                    "Check container logs with 'lxc info'".to_string(),
-                                ],
-                            );
-                        }
-                    }
-                }
-                ConfirmAction::StopContainer(name) => {
-                    let operation_id = self.register_operation(
-                        format!("Stop container '{}'", name),
-                        Some(name.clone()),
-                    );
+    /// Complete the operation of the quick action that just finished
+    fn finish_quick(&mut self) {
+        let Some(run) = self.quick.as_ref().and_then(|view| view.run.as_ref()) else {
+            return;
+        };
+        let (ok, failed) = run.tally();
+        let operation_id = run.operation_id.clone();
+        let error = (failed > 0)
+            .then(|| format!("Failed in {} of {} instances", failed, run.targets.len()));
+        info!(
+            "'{}' succeeded in {} instances, failed in {}",
+            run.action.name, ok, failed
+        );
+        self.complete_operation(&operation_id, failed == 0, error);
+    }
 
-                    self.show_status_modal(StatusModalType::Progress {
-                        operation_id: operation_id.clone(),
-                    });
-                    self.start_operation(&operation_id);
+    /// Compare the two marked containers, or the marked one with the
+    /// selected one
+    pub async fn open_compare(&mut self) {
+        let selected = self
+            .get_selected_container()
+            .await
+            .map(|c| c.qualified_name());
+        let (left, right) = match (self.compare_marks.as_slice(), selected) {
+            ([left, right], _) => (left.clone(), right.clone()),
+            ([left], Some(selected)) if *left != selected => (left.clone(), selected),
+            _ => {
+                self.show_info(
+                    "Mark a container with 'm', select another and press 'C' to compare them"
+                        .to_string(),
+                    false,
+                );
+                return;
+            }
+        };
 
-                    match self.lxc_client.stop_container(&name).await {
-                        Ok(_) => {
-                            self.complete_operation(&operation_id, true, None);
-                            self.show_success(format!("Container '{}' stopped successfully", name));
-                            let _ = self.refresh_containers().await;
-                        }
-                        Err(e) => {
-                            error!("Failed to stop container {}: {:?}", name, e);
-                            self.complete_operation(&operation_id, false, Some(e.to_string()));
-                            self.show_error(
-                                format!("Failed to stop '{}'", name),
-                                e.to_string(),
-                                vec![
-                                    "Try force stopping with 'lxc stop -f'".to_string(),
-                                    "Check if processes are hung inside container".to_string(),
-                                ],
-                            );
-                        }
-                    }
-                }
-                ConfirmAction::RestartContainer(name) => {
-                    let operation_id = self.register_operation(
-                        format!("Restart container '{}'", name),
-                        Some(name.clone()),
+        let mut instances = Vec::new();
+        for name in [&left, &right] {
+            let (client, bare) = self.client_for(name);
+            match client.get_instance(&bare).await {
+                Ok(instance) => instances.push(instance),
+                Err(e) => {
+                    error!("Failed to load {} for comparison: {:?}", name, e);
+                    self.show_error(
+                        format!("Failed to load '{}'", name),
+                        e.to_string(),
+                        vec!["Refresh the list; the container may have been deleted".to_string()],
                     );
+                    return;
+                }
+            }
+        }
 
-                    self.show_status_modal(StatusModalType::Progress {
-                        operation_id: operation_id.clone(),
-                    });
-                    self.start_operation(&operation_id);
+        self.compare = Some(Comparison::new(left, right, &instances[0], &instances[1]));
+        self.compare_marks.clear();
+        self.input_mode = InputMode::Compare;
+    }
 
-                    match self.lxc_client.restart_container(&name).await {
-                        Ok(_) => {
-                            self.complete_operation(&operation_id, true, None);
-                            self.show_success(format!(
-                                "Container '{}' restarted successfully",
-                                name
-                            ));
-                            let _ = self.refresh_containers().await;
-                        }
-                        Err(e) => {
-                            error!("Failed to restart container {}: {:?}", name, e);
-                            self.complete_operation(&operation_id, false, Some(e.to_string()));
-                            self.show_error(
-                                format!("Failed to restart '{}'", name),
-                                e.to_string(),
-                                vec![
-                                    "Check container status first".to_string(),
-                                    "Try stopping then starting manually".to_string(),
-                                ],
-                            );
-                        }
-                    }
-                }
-                ConfirmAction::DeleteContainer(name) => {
-                    let operation_id = self.register_operation(
-                        format!("Delete container '{}'", name),
-                        Some(name.clone()),
-                    );
+    /// Follow the selected container's console log
+    pub async fn open_console(&mut self) {
+        if !self.feature_available(Feature::ConsoleLog) {
+            return;
+        }
+        if let Some(container) = self.get_selected_container().await {
+            self.console = Some(ConsoleView::new(container.qualified_name()));
+            self.input_mode = InputMode::Console;
+            self.poll_console().await;
+        }
+    }
 
-                    self.show_status_modal(StatusModalType::Progress {
-                        operation_id: operation_id.clone(),
-                    });
-                    self.start_operation(&operation_id);
+    /// Read the console log of an instance that just crashed, before a
+    /// restart clears it
+    fn capture_console(&mut self, container: String, status: String) {
+        if self.unavailable.reason(Feature::ConsoleLog).is_some() {
+            return;
+        }
+        let (client, name) = self.client_for(&container);
+        let tx = self.crashes.sender();
+        let at = chrono::Local::now();
+        tokio::spawn(async move {
+            let log = client.console_log(&name).await.map_err(|e| e.to_string());
+            let _ = tx.send((container, Capture { status, at, log }));
+        });
+    }
 
-                    match self.lxc_client.delete_container(&name).await {
-                        Ok(_) => {
-                            self.complete_operation(&operation_id, true, None);
-                            self.show_success(format!("Container '{}' deleted successfully", name));
-                            let _ = self.refresh_containers().await;
-                        }
-                        Err(e) => {
-                            error!("Failed to delete container {}: {:?}", name, e);
-                            self.complete_operation(&operation_id, false, Some(e.to_string()));
-                            self.show_error(
-                                format!("Failed to delete '{}'", name),
-                                e.to_string(),
-                                vec![
-                                    "Stop the container first if it's running".to_string(),
-                                    "Check for dependent snapshots".to_string(),
-                                ],
-                            );
-                        }
-                    }
-                }
-            }
+    /// Take in the console logs captured from crashed instances
+    pub fn poll_crashes(&mut self) {
+        self.crashes.drain();
+    }
+
+    /// Start a crashed container again in the background, as its restart
+    /// policy asks, unless it keeps crashing or starting isn't allowed
+    fn restart_crashed(&mut self, container: String) {
+        if self.read_only || !self.permissions.permits(Permission::Start) {
+            warn!("Not restarting {}: starting is disabled", container);
+            return;
         }
+        if !self.restarts.restart(&container, Instant::now()) {
+            return;
+        }
+        info!("Restarting {} after it stopped by itself", container);
+        let operation_id = self.register_operation(
+            format!("Restart policy: start '{}'", container),
+            Some(container.clone()),
+        );
+        self.start_operation(&operation_id);
+
+        let (client, name) = self.client_for(&container);
+        let tx = self.restarts.sender();
+        let op_id = operation_id.clone();
+        let handle = tokio::spawn(async move {
+            let outcome = client
+                .start_container(&name)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send((op_id, container, outcome));
+        });
+        self.background_tasks.insert(operation_id, handle);
     }
 
-    pub async fn stop_selected(&mut self) {
-        if let Some(container) = self.get_selected_container().await {
-            let name = container.name.clone();
-            self.show_confirm_dialog(
-                format!("Stop container '{}'?", name),
-                ConfirmAction::StopContainer(name),
-            );
+    /// Record restarts that finished, without interrupting the user
+    pub fn poll_restarts(&mut self) {
+        for (op_id, container, outcome) in self.restarts.drain() {
+            match outcome {
+                Ok(()) => self.complete_operation(&op_id, true, None),
+                Err(e) => {
+                    warn!("Failed to restart {}: {}", container, e);
+                    self.complete_operation(&op_id, false, Some(e));
+                }
+            }
         }
     }
 
-    pub async fn restart_selected(&mut self) {
-        if let Some(container) = self.get_selected_container().await {
-            let name = container.name.clone();
-            self.show_confirm_dialog(
-                format!("Restart container '{}'?", name),
-                ConfirmAction::RestartContainer(name),
-            );
+    /// Show the console log captured when the selected container crashed
+    pub async fn open_crash_log(&mut self) {
+        let Some(container) = self.get_selected_container().await else {
+            return;
+        };
+        let name = container.qualified_name();
+        match self.crashes.get(&name) {
+            Some(capture) => {
+                self.console = Some(ConsoleView::captured(name, capture));
+                self.input_mode = InputMode::Console;
+            }
+            None => self.show_info(format!("{} hasn't crashed since LXTUI started", name), true),
         }
     }
 
-    pub async fn delete_selected(&mut self) {
-        if let Some(container) = self.get_selected_container().await {
-            let name = container.name.clone();
-            self.show_confirm_dialog(
-                format!("Delete container '{}'? This action cannot be undone!", name),
-                ConfirmAction::DeleteContainer(name),
-            );
+    /// Re-read the console log when the view is open and a read is due
+    pub async fn poll_console(&mut self) {
+        let Some(console) = &self.console else {
+            return;
+        };
+        let now = Instant::now();
+        if !matches!(self.input_mode, InputMode::Console)
+            || !console.due(now)
+            || self.unavailable.reason(Feature::ConsoleLog).is_some()
+        {
+            return;
+        }
+
+        let (client, name) = self.client_for(&console.container);
+        let result = client.console_log(&name).await;
+        if let Err(e) = &result {
+            self.note_unsupported(Feature::ConsoleLog, e);
+        }
+        let result = result.map_err(|e| e.to_string());
+        if let Some(console) = &mut self.console {
+            console.record(result, now);
         }
     }
 
-    pub fn cancel_dialog(&mut self) {
-        self.pending_action = None;
-        self.input_mode = InputMode::Normal;
-        self.message = Some("Operation cancelled".to_string());
+    /// Give the operations sidebar focus, showing it if hidden
+    pub fn focus_operations(&mut self) {
+        self.show_operation_sidebar = true;
+        self.operation_selected = self
+            .operation_selected
+            .min(self.listed_operations().len().saturating_sub(1));
+        self.input_mode = InputMode::Operations;
     }
 
-    pub fn clear_message(&mut self) {
-        self.message = None;
+    /// Indices of the operations the sidebar lists, top to bottom
+    pub fn listed_operations(&self) -> Vec<usize> {
+        sidebar::listed(
+            &self.user_operations,
+            self.operation_filter,
+            self.operation_grouping,
+        )
     }
 
-    pub async fn start_clone(&mut self) {
-        if let Some(container) = self.get_selected_container().await {
-            self.input_mode = InputMode::Input {
-                prompt: format!("Clone '{}' to:", container.name),
-                input_type: InputType::ContainerName,
-                callback_action: InputCallback::CloneContainer(container.name.clone()),
-            };
-            self.input_buffer.clear();
+    /// Move the sidebar selection, wrapping like the menus
+    pub fn select_operation(&mut self, forward: bool) {
+        let count = self.listed_operations().len();
+        if count == 0 {
+            return;
         }
+        self.operation_selected = if forward {
+            (self.operation_selected + 1) % count
+        } else {
+            (self.operation_selected + count - 1) % count
+        };
     }
 
-    pub fn start_new_container_wizard(&mut self) {
-        self.wizard_data = WizardData::default();
-        self.input_buffer.clear();
-        self.input_mode = InputMode::Wizard(WizardState::Name);
+    /// Operation selected in the sidebar
+    pub fn selected_operation(&self) -> Option<&UserOperation> {
+        let index = *self.listed_operations().get(self.operation_selected)?;
+        self.user_operations.get(index)
     }
 
-    pub async fn clone_container(&mut self, source: &str, destination: &str) {
-        let operation_id = self.register_operation(
-            format!("Clone '{}' to '{}'", source, destination),
-            Some(destination.to_string()),
-        );
+    /// Show the next filter or grouping in the sidebar, keeping the selected
+    /// operation selected while it is still listed
+    pub fn arrange_operations(&mut self, filter: sidebar::Filter, grouping: sidebar::Grouping) {
+        let selected = self.selected_operation().map(|op| op.id.clone());
+        self.operation_filter = filter;
+        self.operation_grouping = grouping;
+        let listed = self.listed_operations();
+        self.operation_selected = selected
+            .and_then(|id| {
+                listed
+                    .iter()
+                    .position(|&i| self.user_operations[i].id == id)
+            })
+            .unwrap_or(0);
+    }
 
-        self.show_status_modal(StatusModalType::Progress {
-            operation_id: operation_id.clone(),
-        });
-        self.start_operation(&operation_id);
+    /// Take the finished operations the filter shows out of the sidebar;
+    /// the history file keeps them
+    pub fn clear_finished_operations(&mut self) {
+        let filter = self.operation_filter;
+        self.user_operations
+            .retain(|op| !sidebar::clears(op, filter));
+        self.operation_selected = self
+            .operation_selected
+            .min(self.listed_operations().len().saturating_sub(1));
+    }
 
-        match self.lxc_client.clone_container(source, destination).await {
-            Ok(_) => {
-                self.complete_operation(&operation_id, true, None);
-                self.show_success(format!(
-                    "Successfully cloned '{}' to '{}'",
-                    source, destination
-                ));
-                let _ = self.refresh_containers().await;
-                self.input_buffer.clear();
-            }
-            Err(e) => {
-                error!(
-                    "Failed to clone container {} to {}: {:?}",
-                    source, destination, e
-                );
-                self.complete_operation(&operation_id, false, Some(e.to_string()));
-                self.show_error(
-                    format!("Failed to clone '{}'", source),
-                    e.to_string(),
-                    vec![
-                        "Check if destination name is valid".to_string(),
-                        "Ensure destination doesn't already exist".to_string(),
-                        "Verify sufficient disk space".to_string(),
-                    ],
-                );
-                self.input_buffer.clear();
-            }
+    pub fn toggle_events(&mut self) {
+        self.show_events = !self.show_events;
+        if self.show_events {
+            self.resubscribe_events();
+        } else if !self.events_wanted() {
+            self.events.stop();
         }
     }
 
-    pub async fn create_container(&mut self) {
-        let name = self.wizard_data.name.clone();
-        let image = self.wizard_data.image.clone();
-        let is_vm = self.wizard_data.is_vm;
+    /// Whether to follow events: while they're shown, or while a restart
+    /// policy needs them to tell requested stops from crashes
+    fn events_wanted(&self) -> bool {
+        self.show_events || self.restart_policies || matches!(self.input_mode, InputMode::Dashboard)
+    }
 
-        let operation_id = self.register_operation(
-            format!(
-                "Create {} '{}' from '{}'",
-                if is_vm { "VM" } else { "container" },
-                name,
-                image
-            ),
-            Some(name.clone()),
-        );
+    /// Follow the servers currently listed: every remote in all-remotes
+    /// mode, otherwise the active one
+    fn resubscribe_events(&mut self) {
+        if !self.events_wanted() {
+            return;
+        }
+        let clients = if self.all_remotes {
+            self.remote_clients
+                .iter()
+                .map(|(name, client)| (Some(name.clone()), client.clone()))
+                .collect()
+        } else {
+            vec![(None, self.lxc_client.clone())]
+        };
+        self.events.subscribe(clients, &self.polls);
+    }
 
-        self.show_status_modal(StatusModalType::Progress {
-            operation_id: operation_id.clone(),
-        });
-        self.start_operation(&operation_id);
+    /// Take in new events, refreshing the list when an instance changed
+    pub async fn poll_events(&mut self) {
+        let listed = matches!(self.input_mode, InputMode::Normal | InputMode::Dashboard);
+        let delivered = self.events.drain();
+        self.take_in_events(&delivered);
+        if delivered.iter().any(LifecycleEvent::affects_instances) && listed {
+            self.refresh_when_allowed().await;
+        }
+    }
 
-        match self.lxc_client.create_container(&name, &image, is_vm).await {
-            Ok(_) => {
-                self.complete_operation(&operation_id, true, None);
-                self.show_success(format!(
-                    "Successfully created {} '{}'",
-                    if is_vm { "VM" } else { "container" },
-                    name
-                ));
-                let _ = self.refresh_containers().await;
-                self.wizard_data = WizardData::default();
-                self.input_buffer.clear();
+    /// Record delivered events in the uptime history, and note the stops
+    /// someone asked for so they aren't taken for crashes
+    pub fn take_in_events(&mut self, delivered: &[LifecycleEvent]) {
+        for event in delivered {
+            self.crashes.take_in(event);
+        }
+        if let Some(uptime) = &mut self.uptime {
+            let mut changed = false;
+            for event in delivered {
+                changed |= uptime.record_event(event);
             }
-            Err(e) => {
-                error!("Failed to create container {}: {:?}", name, e);
-                self.complete_operation(&operation_id, false, Some(e.to_string()));
-                self.show_error(
-                    format!("Failed to create '{}'", name),
-                    e.to_string(),
-                    vec![
-                        "Check if image exists and is available".to_string(),
-                        "Verify network connectivity".to_string(),
-                        "Ensure sufficient resources".to_string(),
-                    ],
-                );
-                self.wizard_data = WizardData::default();
-                self.input_buffer.clear();
+            if changed {
+                if let Err(e) = uptime.save() {
+                    warn!("Failed to save uptime history: {}", e);
+                }
             }
         }
     }
 
-    pub fn cancel_input(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.input_buffer.clear();
-        self.wizard_data = WizardData::default();
-        self.message = Some("Operation cancelled".to_string());
-    }
-
-    pub fn next_wizard_image(&mut self) {
-        if self.wizard_data.selected_image_index < self.available_images.len() - 1 {
-            self.wizard_data.selected_image_index += 1;
-            self.wizard_data.image = self.available_images[self.wizard_data.selected_image_index]
-                .alias
-                .clone();
+    /// Refresh the list now if the rate allows it, and otherwise as soon as
+    /// it does
+    async fn refresh_when_allowed(&mut self) {
+        if self.polls.try_take(Category::Refresh, Instant::now()) {
+            let _ = self.refresh_containers().await;
+        } else {
+            self.refresh_wanted = true;
         }
     }
 
-    pub fn previous_wizard_image(&mut self) {
-        if self.wizard_data.selected_image_index > 0 {
-            self.wizard_data.selected_image_index -= 1;
-            self.wizard_data.image = self.available_images[self.wizard_data.selected_image_index]
-                .alias
-                .clone();
+    /// Take in finished health probes and start the ones that are due,
+    /// unless commands may not be run in instances
+    pub fn poll_probes(&mut self) {
+        self.probes.drain();
+        if self.read_only || !self.permissions.permits(Permission::Exec) {
+            return;
+        }
+        let due = match self.containers.try_read() {
+            Ok(containers) => self.probes.due(&containers, Instant::now()),
+            Err(_) => return,
+        };
+        for (name, command) in due {
+            let (client, instance) = self.client_for(&name);
+            let tx = self.probes.sender();
+            let limit = self.probes.timeout();
+            tokio::spawn(async move {
+                let probe = probe::run(client, &instance, &command, limit).await;
+                let _ = tx.send((name, probe));
+            });
         }
     }
 
-    pub fn show_help(&mut self) {
-        self.show_info(
-            "Keyboard Shortcuts:\n\
-            \n\
-            Navigation:\n\
-              ↑/↓ or j/k  - Select container\n\
-              Enter       - Container actions menu\n\
-            \n\
-            Quick Actions:\n\
-              s           - Start container\n\
-              S           - Stop container\n\
-              d           - Delete container\n\
-              n           - New container\n\
-              r/F5        - Refresh list\n\
-            \n\
-            System:\n\
-              Space       - System menu\n\
-              o/O         - Toggle operations sidebar\n\
-              ?/h         - This help\n\
-              q/Q         - Quit"
-                .to_string(),
-            false,
-        );
+    /// Take in the background health check, refreshing the list when the
+    /// server answers again
+    pub async fn poll_health(&mut self) {
+        let Some(beat) = self.heartbeat.drain() else {
+            return;
+        };
+        let came_back = beat.up && !self.lxd_status;
+        self.lxd_status = beat.up;
+        self.last_lxd_check = Some(beat.at);
+        if came_back && matches!(self.input_mode, InputMode::Normal) {
+            self.refresh_when_allowed().await;
+        }
     }
 
+    #[allow(dead_code)]
     pub fn close_modal(&mut self) {
         self.input_mode = InputMode::Normal;
     }
@@ -751,11 +7107,7 @@ impl App {
     }
 
     pub fn should_auto_refresh(&self) -> bool {
-        if let Some(last_refresh) = self.last_refresh {
-            last_refresh.elapsed() > Duration::from_secs(10)
-        } else {
-            true
-        }
+        self.refresh_wanted || self.next_refresh.is_none_or(|at| Instant::now() >= at)
     }
 
     pub fn register_operation(&mut self, description: String, container: Option<String>) -> String {
@@ -768,14 +7120,18 @@ impl App {
             started_at: None,
             completed_at: None,
             retry_count: 0,
+            lxd_operation: None,
+            retry: None,
+            finished_at: None,
+            kind: None,
+            duration: None,
         };
 
         self.user_operations.push(operation);
         self.command_feedback = Some(format!("⏳ Command registered: {}", description));
         self.active_operation_count += 1;
 
-        // Limit operation history to last 10 items
-        if self.user_operations.len() > 10 {
+        if self.user_operations.len() > MAX_OPERATION_HISTORY {
             self.user_operations.remove(0);
         }
 
@@ -827,6 +7183,8 @@ impl App {
                 OperationStatus::Failed(error_msg.clone().unwrap_or_default())
             };
             op.completed_at = Some(Instant::now());
+            op.finished_at = Some(chrono::Local::now());
+            op.duration = op.started_at.map(|started| started.elapsed());
 
             if self.active_operation_count > 0 {
                 self.active_operation_count -= 1;
@@ -838,6 +7196,13 @@ impl App {
                 String::new()
             };
 
+            self.notifier.notify(&Finished {
+                description: op.description.clone(),
+                container: op.container.clone(),
+                error: (!success).then(|| error_msg.clone().unwrap_or_default()),
+                duration: op.duration,
+            });
+
             if success {
                 self.command_feedback =
                     Some(format!("✅ Completed: {}{}", op.description, duration));
@@ -847,6 +7212,144 @@ impl App {
                     self.message = Some(format!("Error: {}", msg));
                 }
             }
+            self.save_to_history(operation_id);
+        }
+    }
+
+    fn set_kind(&mut self, operation_id: &str, kind: String) {
+        if let Some(op) = self
+            .user_operations
+            .iter_mut()
+            .find(|o| o.id == operation_id)
+        {
+            op.kind = Some(kind);
+        }
+    }
+
+    /// Time left for a running operation, from LXD's progress and from how
+    /// long earlier operations of the same kind took
+    pub fn operation_estimate(&self, operation: &UserOperation) -> Option<Estimate> {
+        let elapsed = operation.started_at?.elapsed();
+        estimate::estimate(
+            elapsed,
+            self.operation_progress(operation),
+            &self.past_durations(operation),
+        )
+    }
+
+    /// Data a running operation is moving between pools, members or
+    /// servers, as LXD last reported it
+    pub fn operation_transfer(&self, operation: &UserOperation) -> Option<Transfer> {
+        if !matches!(
+            operation.status,
+            OperationStatus::Running | OperationStatus::Retrying(_)
+        ) {
+            return None;
+        }
+        Transfer::parse(operation.lxd_operation.as_ref()?.metadata.as_ref()?)
+    }
+
+    /// Number of active operations and how far along they are together,
+    /// for the title bar
+    pub fn operations_progress(&self) -> (usize, Option<i32>) {
+        let percentages = self
+            .user_operations
+            .iter()
+            .filter(|o| {
+                matches!(
+                    o.status,
+                    OperationStatus::Running | OperationStatus::Retrying(_)
+                )
+            })
+            .map(|o| {
+                let elapsed = o.started_at?.elapsed();
+                estimate::percent_done(elapsed, self.operation_progress(o), &self.past_durations(o))
+            });
+        (self.active_operation_count, estimate::overall(percentages))
+    }
+
+    /// Progress LXD last reported for an operation
+    fn operation_progress(&self, operation: &UserOperation) -> Option<i32> {
+        self.lxd_operations
+            .get(&operation.id)
+            .and_then(|tracker| tracker.progress)
+    }
+
+    /// How long earlier successful operations of the same kind took
+    fn past_durations(&self, operation: &UserOperation) -> Vec<Duration> {
+        match &operation.kind {
+            Some(kind) => self
+                .user_operations
+                .iter()
+                .filter(|o| o.kind.as_ref() == Some(kind))
+                .filter(|o| matches!(o.status, OperationStatus::Success))
+                .filter_map(|o| o.duration)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn set_retry(&mut self, operation_id: &str, action: ConfirmAction) {
+        if let Some(op) = self
+            .user_operations
+            .iter_mut()
+            .find(|o| o.id == operation_id)
+        {
+            op.retry = Some(action);
+        }
+    }
+
+    /// Re-issue the request of the failed operation selected in the sidebar
+    pub async fn retry_selected_operation(&mut self) {
+        let Some(operation) = self.selected_operation() else {
+            return;
+        };
+        match (&operation.status, &operation.retry) {
+            (OperationStatus::Failed(_), Some(action)) => {
+                info!("Retrying: {}", operation.description);
+                let action = action.clone();
+                if self.permits(action.permission()) {
+                    self.run_or_preview(PendingAction::Container(action)).await;
+                }
+            }
+            (OperationStatus::Failed(_), None) => self.show_info(
+                "Only failed start, stop, restart and delete operations can be retried".to_string(),
+                true,
+            ),
+            _ => {}
+        }
+    }
+
+    /// Cancel the operation in the progress modal. LXD is asked to cancel
+    /// the operation it runs; if it refuses, the operation carries on.
+    pub async fn cancel_progress(&mut self, operation_id: &str) {
+        self.input_mode = InputMode::Normal;
+        let Some(tracker) = self.lxd_operations.remove(operation_id) else {
+            self.lxc_client.cancel_all_operations();
+            self.cancel_operation(operation_id);
+            return;
+        };
+
+        let (client, _) = self.client_for(&tracker.container_name);
+        match client
+            .cancel_lxd_operation(&tracker.lxd_operation_path)
+            .await
+        {
+            Ok(()) => self.cancel_operation(operation_id),
+            Err(e) => {
+                warn!("Failed to cancel {}: {}", tracker.lxd_operation_path, e);
+                let description = tracker.description.clone();
+                self.lxd_operations
+                    .insert(operation_id.to_string(), tracker);
+                self.show_error(
+                    format!("Couldn't cancel '{}'", description),
+                    e.to_string(),
+                    vec![
+                        "LXD can't cancel every operation; it carries on in the background"
+                            .to_string(),
+                    ],
+                );
+            }
         }
     }
 
@@ -858,43 +7361,49 @@ impl App {
         {
             op.status = OperationStatus::Cancelled;
             op.completed_at = Some(Instant::now());
+            op.finished_at = Some(chrono::Local::now());
 
             if self.active_operation_count > 0 {
                 self.active_operation_count -= 1;
             }
 
             self.command_feedback = Some(format!("🚫 Cancelled: {}", op.description));
+            self.save_to_history(operation_id);
         }
     }
 
     pub async fn maybe_auto_refresh(&mut self) {
-        if self.should_auto_refresh() && matches!(self.input_mode, InputMode::Normal) {
+        let listed = matches!(self.input_mode, InputMode::Normal | InputMode::Dashboard);
+        if listed
+            && self.should_auto_refresh()
+            && self.polls.try_take(Category::Refresh, Instant::now())
+        {
             let _ = self.refresh_containers().await;
         }
 
         // Clear command feedback after 3 seconds if no active operations
-        if self.active_operation_count == 0 {
-            if self.command_feedback.is_some() {
-                // Check if the last completed operation was more than 3 seconds ago
-                let should_clear = self
-                    .user_operations
-                    .iter()
-                    .filter(|op| {
-                        matches!(
-                            op.status,
-                            OperationStatus::Success
-                                | OperationStatus::Failed(_)
-                                | OperationStatus::Cancelled
-                        )
-                    })
-                    .next_back()
-                    .and_then(|op| op.completed_at)
-                    .map(|completed| completed.elapsed().as_secs() > 3)
-                    .unwrap_or(true);
+        if self.active_operation_count == 0
+            && self.command_feedback.is_some()
+            && !self.reduced_motion
+        {
+            // Check if the last completed operation was more than 3 seconds ago
+            let should_clear = self
+                .user_operations
+                .iter()
+                .rfind(|op| {
+                    matches!(
+                        op.status,
+                        OperationStatus::Success
+                            | OperationStatus::Failed(_)
+                            | OperationStatus::Cancelled
+                    )
+                })
+                .and_then(|op| op.completed_at)
+                .map(|completed| completed.elapsed().as_secs() > 3)
+                .unwrap_or(true);
 
-                if should_clear {
-                    self.command_feedback = None;
-                }
+            if should_clear {
+                self.command_feedback = None;
             }
         }
     }
@@ -904,30 +7413,37 @@ impl App {
         let mut operations_to_check = Vec::new();
 
         // First pass: collect operations that need checking
+        let now = Instant::now();
         for (ui_op_id, tracker) in &mut self.lxd_operations {
-            // Poll every 500ms
-            if tracker.last_checked.elapsed() > Duration::from_millis(500) {
-                tracker.last_checked = Instant::now();
-                operations_to_check.push((ui_op_id.clone(), tracker.lxd_operation_path.clone()));
+            if now >= tracker.next_check && self.polls.try_take(Category::Operations, now) {
+                tracker.next_check = now + self.polls.jittered(OPERATION_INTERVAL);
+                operations_to_check.push((
+                    ui_op_id.clone(),
+                    tracker.lxd_operation_path.clone(),
+                    tracker.container_name.clone(),
+                ));
             }
         }
 
         // Second pass: check operations without holding mutable borrow
-        for (ui_op_id, lxd_op_path) in operations_to_check {
-            // Get operation status from LXD
-            match self.lxc_client.get_lxd_operation(&lxd_op_path).await {
+        for (ui_op_id, lxd_op_path, container_name) in operations_to_check {
+            // Get operation status from LXD on whichever remote owns the container
+            let (client, _) = self.client_for(&container_name);
+            match client.get_lxd_operation(&lxd_op_path).await {
                 Ok(lxd_op) => {
+                    if let Some(op) = self.user_operations.iter_mut().find(|o| o.id == ui_op_id) {
+                        op.lxd_operation = Some(lxd_op.clone());
+                    }
+
                     // Update tracker status if it exists
                     if let Some(tracker) = self.lxd_operations.get_mut(&ui_op_id) {
                         tracker.status_code = lxd_op.status_code;
 
                         // Parse progress if available
-                        if let Some(metadata) = &lxd_op.metadata {
-                            if let Some(progress) =
-                                metadata.get("progress").and_then(|p| p.as_i64())
-                            {
-                                tracker.progress = Some(progress as i32);
-                            }
+                        if let Some(progress) =
+                            lxd_op.metadata.as_ref().and_then(estimate::progress)
+                        {
+                            tracker.progress = Some(progress);
                         }
                     }
 
@@ -941,28 +7457,24 @@ impl App {
                         200 => {
                             // Success!
                             info!("LXD operation {} completed successfully", ui_op_id);
-                            self.complete_operation(&ui_op_id, true, None);
-
-                            if let Some((container_name, action)) = tracker_info {
-                                self.show_success(format!(
-                                    "Container '{}' {} successfully",
-                                    container_name,
-                                    match action.as_str() {
-                                        "start" => "started",
-                                        "stop" => "stopped",
-                                        "restart" => "restarted",
-                                        "delete" => "deleted",
-                                        _ => "operation completed",
-                                    }
-                                ));
-                            }
-                            completed_ops.push(ui_op_id.clone());
+                            let Some(tracker) = self.lxd_operations.remove(&ui_op_id) else {
+                                continue;
+                            };
+                            self.finish_lxd_operation(tracker).await;
                             let _ = self.refresh_containers().await;
                         }
                         400 | 401 => {
                             // Failed or cancelled
                             error!("LXD operation {} failed: {}", ui_op_id, lxd_op.err);
+                            self.images.failed(&ui_op_id);
                             self.complete_operation(&ui_op_id, false, Some(lxd_op.err.clone()));
+                            if let Some(action) = self
+                                .lxd_operations
+                                .get(&ui_op_id)
+                                .and_then(LxdOperationTracker::confirm_action)
+                            {
+                                self.set_retry(&ui_op_id, action);
+                            }
 
                             if let Some((container_name, action)) = tracker_info {
                                 let (title, suggestions) = match action.as_str() {
@@ -996,6 +7508,44 @@ impl App {
                                             "Check for dependent snapshots".to_string(),
                                         ],
                                     ),
+                                    "create-container" | "create-vm" => (
+                                        format!("Failed to create '{}'", container_name),
+                                        vec![
+                                            "Check if image exists and is available".to_string(),
+                                            "Verify network connectivity".to_string(),
+                                            "Ensure sufficient resources".to_string(),
+                                        ],
+                                    ),
+                                    "clone" => (
+                                        format!("Failed to clone to '{}'", container_name),
+                                        vec![
+                                            "Check if destination name is valid".to_string(),
+                                            "Ensure destination doesn't already exist".to_string(),
+                                            "Verify sufficient disk space".to_string(),
+                                        ],
+                                    ),
+                                    "backup" => (
+                                        format!("Failed to back up '{}'", container_name),
+                                        vec!["Verify sufficient disk space on the storage pool"
+                                            .to_string()],
+                                    ),
+                                    "image-refresh" => (
+                                        "Failed to refresh the image".to_string(),
+                                        vec!["Check that the image's remote can be reached"
+                                            .to_string()],
+                                    ),
+                                    "recreate" => (
+                                        format!("Failed to create '{}'", container_name),
+                                        recreate_suggestions(),
+                                    ),
+                                    "rebuild" => (
+                                        format!("Failed to rebuild '{}'", container_name),
+                                        vec![
+                                            "Stop the instance before rebuilding it".to_string(),
+                                            "Check that the image is still in the store"
+                                                .to_string(),
+                                        ],
+                                    ),
                                     _ => (
                                         format!("Operation failed for '{}'", container_name),
                                         vec!["Check LXD logs for details".to_string()],
@@ -1033,9 +7583,128 @@ impl App {
         }
     }
 
+    /// Finish the UI operation of an LXD operation that succeeded
+    async fn finish_lxd_operation(&mut self, tracker: LxdOperationTracker) {
+        let id = tracker.ui_operation_id.clone();
+        match tracker.action.as_str() {
+            "create-container" | "create-vm" => {
+                self.images.created(&id);
+
+                // LXD creates instances stopped; start it under the same UI
+                // operation so the progress modal stays up
+                let (client, name) = self.client_for(&tracker.container_name);
+                match client.start_container_async(&name).await {
+                    Ok(lxd_operation_path) => self.track_lxd_operation(
+                        &id,
+                        lxd_operation_path,
+                        tracker.container_name,
+                        "start",
+                        tracker.success_message,
+                    ),
+                    Err(e) => {
+                        error!("Failed to start {} after creating it: {:?}", name, e);
+                        self.complete_operation(&id, false, Some(e.to_string()));
+                        self.show_error(
+                            format!("Failed to start '{}'", tracker.container_name),
+                            e.to_string(),
+                            vec!["The instance was created; try starting it again".to_string()],
+                        );
+                    }
+                }
+            }
+            "backup" => {
+                self.complete_operation(&id, true, None);
+                if self.showing_progress(&id) {
+                    self.return_to_backups(&tracker.container_name);
+                    self.reload_backups(&tracker.container_name).await;
+                }
+                self.message = Some(tracker.success_message);
+            }
+            "rebuild" => {
+                self.complete_operation(&id, true, None);
+                if self.showing_progress(&id) {
+                    self.return_to_audit();
+                    self.reload_audit().await;
+                }
+                if let Some(audit) = &mut self.audit {
+                    audit.status = Some(tracker.success_message);
+                }
+            }
+            "publish" => {
+                self.complete_operation(&id, true, None);
+                if self.showing_progress(&id) {
+                    self.show_success(tracker.success_message);
+                }
+                // The new image may expire soon
+                self.next_image_check = None;
+                if self.image_store.is_some() {
+                    self.reload_image_store().await;
+                }
+            }
+            "image-refresh" => {
+                self.complete_operation(&id, true, None);
+                if self.showing_progress(&id) {
+                    self.return_to_image_store();
+                }
+                if self.image_store.is_some() {
+                    self.reload_image_store().await;
+                }
+                if let Some(view) = &mut self.image_store {
+                    view.status = Some(tracker.success_message);
+                }
+            }
+            _ => {
+                self.complete_operation(&id, true, None);
+                // Operations run in the background finish quietly in the sidebar
+                if self.showing_progress(&id) {
+                    self.show_success(tracker.success_message);
+                }
+            }
+        }
+    }
+
+    fn showing_progress(&self, operation_id: &str) -> bool {
+        matches!(
+            &self.input_mode,
+            InputMode::StatusModal(StatusModalType::Progress { operation_id: id, .. }) if id == operation_id
+        )
+    }
+
     pub async fn poll_background_tasks(&mut self) {
         // Poll LXD operations first
         self.poll_lxd_operations().await;
+        self.images.poll_search();
+
+        // Check for a finished OIDC login
+        if let Some((_, rx)) = &mut self.oidc_login {
+            match rx.try_recv() {
+                Ok(result) => {
+                    if let Some((operation_id, _)) = self.oidc_login.take() {
+                        self.complete_oidc_login(operation_id, result).await;
+                    }
+                }
+                Err(oneshot::error::TryRecvError::Empty) => {}
+                Err(oneshot::error::TryRecvError::Closed) => self.oidc_login = None,
+            }
+        }
+
+        // Record finished scheduled jobs without interrupting the user
+        while let Ok((op_id, description, outcome)) = self.schedule_rx.try_recv() {
+            match outcome {
+                Ok(()) => {
+                    info!("{} completed", description);
+                    self.complete_operation(&op_id, true, None);
+                    self.schedule_warning = None;
+                    // A scheduled start or stop shows in the list
+                    self.refresh_wanted = true;
+                }
+                Err(e) => {
+                    warn!("{} failed: {}", description, e);
+                    self.complete_operation(&op_id, false, Some(e.clone()));
+                    self.schedule_warning = Some(format!("{} failed", description));
+                }
+            }
+        }
 
         // Clean up finished task handles
         let mut completed = Vec::new();
@@ -1138,3 +7807,24 @@ impl App {
         }
     }
 }
+
+/// Question asking to delete a container
+/// Why creating the other type of an instance may fail
+fn recreate_suggestions() -> Vec<String> {
+    vec![
+        "Check that the image is published for the new type; not every OS has VM images"
+            .to_string(),
+        "Choose a name no instance has".to_string(),
+        "The original and its new snapshot are untouched".to_string(),
+    ]
+}
+
+fn delete_message(container: &str) -> String {
+    format!(
+        "Delete container '{}'? This action cannot be undone!",
+        container
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,2890 @@
+//! End-to-end tests of container actions against the fake LXD server
+//!
+//! Each test drives `App` through the same methods the actions call and
+//! checks both what the UI shows and what the server ended up with.
+
+use super::{
+    App, ConfirmAction, InputMode, OperationStatus, PendingAction, StatusModalType, WizardState,
+};
+use crate::batch::Outcome;
+use crate::clone::CloneForm;
+use crate::command::{self, Source};
+use crate::config::{
+    CreateDefaults, ImagesConfig, InventoryConfig, InventoryFormat, Permission, PollingConfig,
+    PromptHistoryConfig, ScheduleConfig, ScheduledAction, SpaceGuard, TourConfig, UptimeConfig,
+    UsageConfig,
+};
+use crate::fake_lxd::FakeLxd;
+use crate::filter::ContainerFilter;
+use crate::images::{ImageCatalog, ImageSource};
+use crate::inventory::Inventory;
+use crate::lxc::LxcClient;
+use crate::lxd_api::LxdApiClient;
+use crate::migration::Stage;
+use crate::ports;
+use crate::power::Override;
+use crate::preflight::Check;
+use crate::probe::Health;
+use crate::quick;
+use crate::recall::PromptHistory;
+use crate::schedule::Schedule;
+use crate::service::{Service, ServiceAction, ServiceControl};
+use crate::theme::Tone;
+use crate::tour;
+use crate::uptime::Uptime;
+use crate::workspace::{SortKey, Workspace};
+use crate::{action, input};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Longest a test waits for tracked LXD operations to finish
+const OPERATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn app_for(lxd: &FakeLxd) -> App {
+    let mut app = App::new(lxd.client());
+    app.load_available_images();
+    app.ensure_lxd_and_refresh().await;
+    app
+}
+
+/// Poll until every tracked LXD operation has completed
+async fn finish_operations(app: &mut App) {
+    let started = Instant::now();
+    while !app.lxd_operations.is_empty() {
+        assert!(
+            started.elapsed() < OPERATION_TIMEOUT,
+            "operations still pending"
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        app.poll_background_tasks().await;
+    }
+}
+
+/// Handle a key press the way the event loop does
+async fn press(app: &mut App, code: KeyCode) {
+    app.key_pressed();
+    if let Some(action) = input::map_key(app, KeyEvent::new(code, KeyModifiers::NONE), 20) {
+        action::update(app, action).await;
+    }
+}
+
+async fn listed(app: &App) -> Vec<(String, String)> {
+    app.containers
+        .read()
+        .await
+        .iter()
+        .map(|c| (c.name.clone(), c.status.clone()))
+        .collect()
+}
+
+fn success_message(app: &App) -> Option<&str> {
+    match &app.input_mode {
+        InputMode::StatusModal(StatusModalType::Success { message, .. }) => Some(message),
+        _ => None,
+    }
+}
+
+fn error_title(app: &App) -> Option<&str> {
+    match &app.input_mode {
+        InputMode::StatusModal(StatusModalType::Error { title, .. }) => Some(title),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn lists_instances_with_addresses() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", Some("10.0.0.10"))
+        .with_instance("db1", "Stopped", Some("10.0.0.11"));
+    let app = app_for(&lxd).await;
+
+    assert!(app.lxd_status);
+    assert_eq!(app.lxd_version.as_deref(), Some("5.21.0"));
+    assert_eq!(
+        listed(&app).await,
+        vec![
+            ("db1".to_string(), "Stopped".to_string()),
+            ("web1".to_string(), "Running".to_string()),
+        ]
+    );
+
+    let containers = app.containers.read().await;
+    assert!(containers[0].ipv4.is_empty());
+    assert_eq!(containers[1].ipv4, vec!["10.0.0.10".to_string()]);
+}
+
+#[tokio::test]
+async fn lists_the_image_each_instance_came_from() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_config(
+            "web1",
+            "image.description",
+            "Ubuntu noble amd64 (20240101_07:42)",
+        )
+        .with_config("web1", "volatile.base_image", "8d2e6c1f0a9b4e7d3c5a")
+        .with_instance("db1", "Stopped", None)
+        .with_config("db1", "image.os", "Debian")
+        .with_config("db1", "image.release", "bookworm")
+        .with_instance("old1", "Stopped", None)
+        .with_config("old1", "volatile.base_image", "0f1e2d3c4b5a69788796");
+    let app = app_for(&lxd).await;
+
+    let containers = app.containers.read().await;
+    let labels: Vec<_> = containers.iter().map(|c| c.image_label()).collect();
+    assert_eq!(
+        labels,
+        [
+            Some("Debian bookworm".to_string()),
+            Some("0f1e2d3c4b5a".to_string()),
+            Some("Ubuntu noble amd64 (20240101_07:42)".to_string()),
+        ]
+    );
+    assert_eq!(
+        containers[2].base_image.as_deref(),
+        Some("8d2e6c1f0a9b4e7d3c5a")
+    );
+}
+
+#[tokio::test]
+async fn start_and_stop() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+
+    app.execute_action(ConfirmAction::StartContainer("web1".to_string()))
+        .await;
+    finish_operations(&mut app).await;
+    assert_eq!(lxd.status("web1").as_deref(), Some("Running"));
+    assert_eq!(
+        success_message(&app),
+        Some("Container 'web1' started successfully")
+    );
+    assert_eq!(listed(&app).await[0].1, "Running");
+    // The row flashes in the new status's colour
+    assert_eq!(app.flash_tone("web1"), Some(Tone::Good));
+
+    app.execute_action(ConfirmAction::StopContainer("web1".to_string()))
+        .await;
+    finish_operations(&mut app).await;
+    assert_eq!(lxd.status("web1").as_deref(), Some("Stopped"));
+    assert_eq!(listed(&app).await[0].1, "Stopped");
+    assert_eq!(app.flash_tone("web1"), Some(Tone::Bad));
+
+    app.reduced_motion = true;
+    assert_eq!(app.flash_tone("web1"), None);
+}
+
+#[tokio::test]
+async fn delete_after_confirmation() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("db1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+
+    // Dismiss the connection notice, then d on the first container and y
+    // at the confirmation
+    press(&mut app, KeyCode::Esc).await;
+    app.selected = 0;
+    press(&mut app, KeyCode::Char('d')).await;
+    assert!(matches!(app.input_mode, InputMode::Confirmation { .. }));
+    assert!(lxd.requests().iter().all(|r| !r.starts_with("DELETE")));
+
+    press(&mut app, KeyCode::Char('y')).await;
+    finish_operations(&mut app).await;
+    assert_eq!(lxd.status("db1"), None);
+    assert_eq!(
+        listed(&app).await,
+        vec![("web1".to_string(), "Running".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn delete_running_instance_fails() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+
+    app.execute_action(ConfirmAction::DeleteContainer("web1".to_string()))
+        .await;
+
+    assert!(app.lxd_operations.is_empty());
+    assert_eq!(error_title(&app), Some("Failed to delete 'web1'"));
+    assert_eq!(lxd.status("web1").as_deref(), Some("Running"));
+}
+
+#[tokio::test]
+async fn delete_confirmation_lists_preflight_checks() {
+    let lxd = FakeLxd::start()
+        .with_instance("db1", "Stopped", None)
+        .with_config("db1", "security.protection.delete", "true")
+        .with_snapshot("db1", "nightly")
+        .with_device(
+            "db1",
+            "data",
+            &[
+                ("type", "disk"),
+                ("pool", "fast"),
+                ("source", "pgdata"),
+                ("path", "/var/lib/postgresql"),
+            ],
+        );
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    // Delete from the actions menu
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('4')).await;
+    let InputMode::Confirmation { checks, .. } = &app.input_mode else {
+        panic!("no confirmation: {:?}", app.input_mode);
+    };
+    assert_eq!(
+        checks,
+        &[
+            Check::Protected,
+            Check::Warning("1 snapshot deleted with it: nightly".to_string()),
+            Check::Warning(
+                "Custom volume pgdata on fast (at /var/lib/postgresql) is kept, detached"
+                    .to_string()
+            ),
+        ]
+    );
+
+    // A protected instance can't be confirmed, only unprotected
+    press(&mut app, KeyCode::Char('y')).await;
+    assert!(matches!(app.input_mode, InputMode::Confirmation { .. }));
+    assert!(lxd.requests().iter().all(|r| !r.starts_with("DELETE")));
+}
+
+#[tokio::test]
+async fn protected_container_is_unprotected_before_delete() {
+    let lxd = FakeLxd::start()
+        .with_instance("db1", "Stopped", None)
+        .with_config("db1", "security.protection.delete", "true");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    assert!(app.containers.read().await[0].protected);
+
+    // The quick delete refuses and points to the actions menu
+    press(&mut app, KeyCode::Char('d')).await;
+    assert_eq!(error_title(&app), Some("'db1' is protected"));
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('4')).await;
+    press(&mut app, KeyCode::Char('p')).await;
+    assert_eq!(lxd.config("db1", "security.protection.delete"), None);
+    let InputMode::Confirmation { checks, .. } = &app.input_mode else {
+        panic!("no confirmation: {:?}", app.input_mode);
+    };
+    assert!(checks.is_empty());
+    assert!(!app.containers.read().await[0].protected);
+
+    press(&mut app, KeyCode::Char('y')).await;
+    finish_operations(&mut app).await;
+    assert_eq!(lxd.status("db1"), None);
+}
+
+#[tokio::test]
+async fn protection_from_a_profile_is_overridden_before_delete() {
+    let lxd = FakeLxd::start()
+        .with_profile_config("default", "security.protection.delete", "true")
+        .with_instance("db1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    assert!(app.containers.read().await[0].protected);
+
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('4')).await;
+    press(&mut app, KeyCode::Char('p')).await;
+    assert_eq!(
+        lxd.config("db1", "security.protection.delete").as_deref(),
+        Some("false")
+    );
+    let InputMode::Confirmation { checks, .. } = &app.input_mode else {
+        panic!("no confirmation: {:?}", app.input_mode);
+    };
+    assert!(checks.is_empty());
+    assert!(!app.containers.read().await[0].protected);
+}
+
+#[tokio::test]
+async fn failed_operation_shows_error() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    lxd.fail_operations("Failed to start device \"eth0\"");
+
+    app.execute_action(ConfirmAction::StartContainer("web1".to_string()))
+        .await;
+    finish_operations(&mut app).await;
+
+    assert_eq!(error_title(&app), Some("Failed to start 'web1'"));
+    assert_eq!(lxd.status("web1").as_deref(), Some("Stopped"));
+}
+
+#[tokio::test]
+async fn progress_runs_in_background() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    lxd.hold_operations();
+
+    app.execute_action(ConfirmAction::StartContainer("web1".to_string()))
+        .await;
+    press(&mut app, KeyCode::Esc).await;
+    assert!(matches!(app.input_mode, InputMode::Normal));
+    assert_eq!(app.operations_progress().0, 1);
+
+    // It finishes in the sidebar without a dialog
+    lxd.release_operations();
+    finish_operations(&mut app).await;
+    assert!(matches!(app.input_mode, InputMode::Normal));
+    assert!(matches!(
+        app.user_operations[0].status,
+        OperationStatus::Success
+    ));
+    assert_eq!(lxd.status("web1").as_deref(), Some("Running"));
+}
+
+#[tokio::test]
+async fn cancel_progress_after_confirming() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    lxd.hold_operations();
+
+    app.execute_action(ConfirmAction::StartContainer("web1".to_string()))
+        .await;
+    let path = app
+        .lxd_operations
+        .values()
+        .next()
+        .unwrap()
+        .lxd_operation_path
+        .clone();
+
+    // Anything but y keeps it running
+    press(&mut app, KeyCode::Char('c')).await;
+    press(&mut app, KeyCode::Char('n')).await;
+    assert!(matches!(
+        app.input_mode,
+        InputMode::StatusModal(StatusModalType::Progress {
+            confirm_cancel: false,
+            ..
+        })
+    ));
+
+    press(&mut app, KeyCode::Char('c')).await;
+    press(&mut app, KeyCode::Char('y')).await;
+    assert!(matches!(app.input_mode, InputMode::Normal));
+    assert!(app.lxd_operations.is_empty());
+    assert!(matches!(
+        app.user_operations[0].status,
+        OperationStatus::Cancelled
+    ));
+    assert_eq!(lxd.operation_status(&path).as_deref(), Some("Cancelled"));
+    assert_eq!(lxd.status("web1").as_deref(), Some("Stopped"));
+}
+
+#[tokio::test]
+async fn retry_failed_operation_from_sidebar() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    lxd.fail_operations("Failed to start device \"eth0\"");
+
+    app.execute_action(ConfirmAction::StartContainer("web1".to_string()))
+        .await;
+    finish_operations(&mut app).await;
+    assert!(error_title(&app).is_some());
+
+    // Close the error, focus the sidebar and retry the failed start
+    lxd.succeed_operations();
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Tab).await;
+    assert!(matches!(app.input_mode, InputMode::Operations));
+    press(&mut app, KeyCode::Char('r')).await;
+    finish_operations(&mut app).await;
+
+    assert_eq!(lxd.status("web1").as_deref(), Some("Running"));
+    assert_eq!(app.user_operations.len(), 2);
+    assert!(matches!(
+        app.user_operations[1].status,
+        OperationStatus::Success
+    ));
+}
+
+#[tokio::test]
+async fn sidebar_filters_groups_and_clears_operations() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    for (name, status) in [
+        ("web1", OperationStatus::Success),
+        ("web2", OperationStatus::Failed("no space".to_string())),
+        ("web1", OperationStatus::Running),
+        ("web2", OperationStatus::Success),
+    ] {
+        let id = app.register_operation(format!("Start '{}'", name), Some(name.to_string()));
+        app.user_operations
+            .iter_mut()
+            .find(|op| op.id == id)
+            .unwrap()
+            .status = status;
+    }
+
+    press(&mut app, KeyCode::Tab).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    assert!(matches!(
+        app.selected_operation().unwrap().status,
+        OperationStatus::Running
+    ));
+
+    // Only failures, then only what still runs; the selection follows
+    press(&mut app, KeyCode::Char('f')).await;
+    press(&mut app, KeyCode::Char('f')).await;
+    assert_eq!(app.listed_operations().len(), 1);
+    assert!(matches!(
+        app.selected_operation().unwrap().status,
+        OperationStatus::Failed(_)
+    ));
+    press(&mut app, KeyCode::Char('f')).await;
+
+    // Grouped by container, the selected operation stays selected
+    press(&mut app, KeyCode::Char('k')).await;
+    press(&mut app, KeyCode::Char('g')).await;
+    assert_eq!(app.listed_operations(), [2, 0, 3, 1]);
+    assert_eq!(app.operation_selected, 0);
+    assert!(matches!(
+        app.selected_operation().unwrap().status,
+        OperationStatus::Running
+    ));
+    press(&mut app, KeyCode::Char('f')).await;
+    assert_eq!(app.listed_operations(), [2]);
+
+    // Clearing leaves what is running, then with every operation listed
+    // takes the rest of the finished ones
+    press(&mut app, KeyCode::Char('c')).await;
+    assert_eq!(app.user_operations.len(), 4);
+    press(&mut app, KeyCode::Char('f')).await;
+    press(&mut app, KeyCode::Char('f')).await;
+    press(&mut app, KeyCode::Char('c')).await;
+    assert_eq!(app.user_operations.len(), 1);
+    assert!(matches!(
+        app.user_operations[0].status,
+        OperationStatus::Running
+    ));
+    assert_eq!(app.operation_selected, 0);
+}
+
+#[tokio::test]
+async fn tour_is_offered_once_and_reopened_from_help() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    let dir = std::env::temp_dir().join(format!("lxtui-tour-{}", uuid::Uuid::new_v4()));
+    app.tour_config = TourConfig {
+        offer: true,
+        path: Some(dir.join("tour-seen")),
+    };
+
+    app.offer_tour();
+    assert!(matches!(app.input_mode, InputMode::Tour));
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Right).await;
+    press(&mut app, KeyCode::Left).await;
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(app.tour.unwrap().current().title, "The container menu");
+
+    // Skipping marks it seen; nothing was opened for real
+    press(&mut app, KeyCode::Esc).await;
+    assert!(matches!(app.input_mode, InputMode::Normal));
+    assert!(app.tour.is_none());
+    assert!(dir.join("tour-seen").exists());
+    app.offer_tour();
+    assert!(matches!(app.input_mode, InputMode::Normal));
+
+    press(&mut app, KeyCode::Char('?')).await;
+    press(&mut app, KeyCode::Char('t')).await;
+    assert!(matches!(app.input_mode, InputMode::Tour));
+    for _ in 0..tour::STEPS.len() {
+        press(&mut app, KeyCode::Enter).await;
+    }
+    assert!(matches!(app.input_mode, InputMode::Normal));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn create_from_wizard() {
+    let lxd = FakeLxd::start();
+    let mut app = app_for(&lxd).await;
+
+    app.start_new_container_wizard();
+    app.wizard_data.name = "test1".to_string();
+    app.wizard_data.image = "ubuntu:24.04".to_string();
+    app.run_action(PendingAction::CreateContainer).await;
+    finish_operations(&mut app).await;
+
+    assert_eq!(
+        success_message(&app),
+        Some("Successfully created container 'test1'")
+    );
+    assert_eq!(lxd.status("test1").as_deref(), Some("Running"));
+    assert_eq!(
+        listed(&app).await,
+        vec![("test1".to_string(), "Running".to_string())]
+    );
+    assert!(lxd.requests().contains(&"POST /1.0/instances".to_string()));
+}
+
+#[tokio::test]
+async fn wizard_creates_from_the_defaults_given_only_a_name() {
+    let lxd = FakeLxd::start();
+    let mut app = app_for(&lxd).await;
+    app.create_defaults = CreateDefaults {
+        image: "debian/12".to_string(),
+        vm: false,
+        profiles: vec!["default".to_string(), "web".to_string()],
+        limits: [("cpu".to_string(), "4".to_string())].into(),
+        pool: Some("default".to_string()),
+        network: Some("lxdbr1".to_string()),
+    };
+
+    press(&mut app, KeyCode::Esc).await; // The "LXD service is running" notice
+    press(&mut app, KeyCode::Char('n')).await;
+    assert_eq!(app.wizard_data.image, "debian/12");
+    for c in "web2".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(
+        app.input_mode,
+        InputMode::Wizard(WizardState::Confirm)
+    ));
+    press(&mut app, KeyCode::Enter).await;
+    finish_operations(&mut app).await;
+
+    assert_eq!(
+        success_message(&app),
+        Some("Successfully created container 'web2'")
+    );
+    assert_eq!(lxd.source("web2").unwrap()["alias"], "debian/12");
+    assert_eq!(lxd.profiles("web2"), ["default", "web"]);
+    assert_eq!(lxd.config("web2", "limits.cpu").as_deref(), Some("4"));
+    assert_eq!(lxd.config("web2", "limits.memory"), None);
+    assert_eq!(
+        lxd.device("web2", "root").unwrap()["pool"],
+        "default".to_string()
+    );
+    assert_eq!(
+        lxd.device("web2", "eth0").unwrap()["network"],
+        "lxdbr1".to_string()
+    );
+}
+
+#[tokio::test]
+async fn wizard_completes_typed_images_and_remembers_them() {
+    let lxd = FakeLxd::start().with_image_alias("golden-web", "Published from web1");
+    let mut app = app_for(&lxd).await;
+    let path = std::env::temp_dir().join(format!("lxtui-recent-{}.json", uuid::Uuid::new_v4()));
+    app.images = ImageCatalog::new(&ImagesConfig {
+        recent: 5,
+        path: Some(path.clone()),
+        search: String::new(),
+        ..ImagesConfig::default()
+    });
+
+    press(&mut app, KeyCode::Esc).await; // The "LXD service is running" notice
+    press(&mut app, KeyCode::Char('n')).await;
+    for c in "test1".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Tab).await;
+    for c in "gold".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    // What was typed comes first, then the local alias it completes to
+    let choices = app.image_choices();
+    assert_eq!(choices[0].alias, "gold");
+    assert_eq!(choices[1].alias, "golden-web");
+
+    press(&mut app, KeyCode::Down).await;
+    press(&mut app, KeyCode::Tab).await;
+    press(&mut app, KeyCode::Tab).await;
+    press(&mut app, KeyCode::Enter).await;
+    finish_operations(&mut app).await;
+
+    assert_eq!(
+        success_message(&app),
+        Some("Successfully created container 'test1'")
+    );
+    assert_eq!(
+        lxd.source("test1"),
+        Some(serde_json::json!({"type": "image", "alias": "golden-web"}))
+    );
+
+    // The next wizard offers it first
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Char('n')).await;
+    app.wizard_data.name = "test2".to_string();
+    app.input_mode = InputMode::Wizard(WizardState::SelectImage);
+    let first = &app.image_choices()[0];
+    assert_eq!(
+        (first.alias.as_str(), first.source),
+        ("golden-web", ImageSource::Recent)
+    );
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("golden-web"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn wizard_creates_an_empty_vm_booting_an_iso() {
+    let lxd = FakeLxd::start();
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await; // The "LXD service is running" notice
+    press(&mut app, KeyCode::Char('n')).await;
+    for c in "win11".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Tab).await;
+    for c in "none".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    assert_eq!(app.image_choices()[0].source, ImageSource::Empty);
+    press(&mut app, KeyCode::Tab).await;
+    press(&mut app, KeyCode::Char('v')).await;
+    press(&mut app, KeyCode::Tab).await;
+    assert!(matches!(
+        app.input_mode,
+        InputMode::Wizard(WizardState::Media)
+    ));
+
+    // Neither a path nor a volume
+    for c in "win11.iso".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(
+        app.input_mode,
+        InputMode::Wizard(WizardState::Media)
+    ));
+    assert_eq!(
+        app.wizard_data.media_error.as_deref(),
+        Some("An ISO is an absolute path on the server or a pool/volume")
+    );
+    app.input_buffer.set("default/win11");
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(app.wizard_data.media_error, None);
+    assert!(matches!(
+        app.input_mode,
+        InputMode::Wizard(WizardState::Confirm)
+    ));
+    press(&mut app, KeyCode::Enter).await;
+    finish_operations(&mut app).await;
+
+    assert_eq!(
+        success_message(&app),
+        Some("Successfully created VM 'win11'")
+    );
+    assert_eq!(
+        lxd.source("win11"),
+        Some(serde_json::json!({"type": "none"}))
+    );
+    let iso = lxd.device("win11", "install").unwrap();
+    assert_eq!(
+        (iso["pool"].as_str(), iso["source"].as_str()),
+        ("default", "win11")
+    );
+    assert_eq!(iso["boot.priority"], "10");
+}
+
+#[tokio::test]
+async fn iso_is_attached_booted_and_detached_from_the_container_menu() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", Some("10.0.0.10"))
+        .with_vm("vm1")
+        .with_device(
+            "vm1",
+            "root",
+            &[("type", "disk"), ("path", "/"), ("pool", "default")],
+        );
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    // A container has nothing to boot from an ISO
+    assert!(app.select_container("web1").await);
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('a')).await;
+    assert!(matches!(app.input_mode, InputMode::StatusModal(_)));
+    press(&mut app, KeyCode::Esc).await;
+
+    assert!(app.select_container("vm1").await);
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('a')).await;
+    for c in "/var/lib/isos/debian-12.iso".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    finish_operations(&mut app).await;
+    assert_eq!(
+        success_message(&app),
+        Some("Attached /var/lib/isos/debian-12.iso to 'vm1'; it boots from it next")
+    );
+    let iso = lxd.device("vm1", "install").unwrap();
+    assert_eq!(iso["source"], "/var/lib/isos/debian-12.iso");
+    assert_eq!(iso["boot.priority"], "10");
+    assert!(lxd.device("vm1", "root").is_some());
+
+    // Installed: boot from the disk, then take the ISO out
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('v')).await;
+    finish_operations(&mut app).await;
+    assert_eq!(
+        success_message(&app),
+        Some("'vm1' boots from its disk next")
+    );
+    assert!(!lxd
+        .device("vm1", "install")
+        .unwrap()
+        .contains_key("boot.priority"));
+
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('D')).await;
+    finish_operations(&mut app).await;
+    assert_eq!(success_message(&app), Some("Detached the ISO of 'vm1'"));
+    assert_eq!(lxd.device("vm1", "install"), None);
+    assert!(lxd.device("vm1", "root").is_some());
+
+    // Nothing left to toggle
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('v')).await;
+    assert!(matches!(app.input_mode, InputMode::StatusModal(_)));
+}
+
+#[tokio::test]
+async fn vm_settings_toggle_config_keys_and_the_tpm() {
+    let lxd = FakeLxd::start().with_vm("vm1");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    assert!(app.select_container("vm1").await);
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('V')).await;
+    assert!(matches!(app.input_mode, InputMode::VmSettings));
+
+    // CPUs pinned to a set
+    press(&mut app, KeyCode::Enter).await;
+    for c in "0-3".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(lxd.config("vm1", "limits.cpu").as_deref(), Some("0-3"));
+    assert!(matches!(app.input_mode, InputMode::VmSettings));
+
+    // Secure boot is on by default
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(
+        lxd.config("vm1", "security.secureboot").as_deref(),
+        Some("false")
+    );
+
+    // The fake server lacks stateful migration
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(
+        app.vm_settings.as_ref().unwrap().status.as_deref(),
+        Some("Stateful migration needs the server's migration_stateful API extension")
+    );
+    assert_eq!(lxd.config("vm1", "migration.stateful"), None);
+
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(lxd.device("vm1", "tpm").unwrap()["type"].as_str(), "tpm");
+    let view = app.vm_settings.as_ref().unwrap();
+    assert!(view.settings[4].is_on());
+    assert_eq!(view.settings[0].value, "0-3");
+}
+
+#[tokio::test]
+async fn idmap_lines_are_checked_and_disks_shifted() {
+    let lxd = FakeLxd::start()
+        .with_instance("c1", "Stopped", None)
+        .with_device(
+            "c1",
+            "data",
+            &[("type", "disk"), ("source", "/srv/data"), ("path", "/data")],
+        );
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    assert!(app.select_container("c1").await);
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('g')).await;
+    assert!(matches!(app.input_mode, InputMode::Idmap));
+
+    for line in ["both 1000 1000", "uid 1000 2000"] {
+        press(&mut app, KeyCode::Char('a')).await;
+        for c in line.chars() {
+            press(&mut app, KeyCode::Char(c)).await;
+        }
+        press(&mut app, KeyCode::Enter).await;
+    }
+    assert_eq!(
+        lxd.config("c1", "raw.idmap").as_deref(),
+        Some("both 1000 1000")
+    );
+    assert_eq!(
+        app.idmap.as_ref().unwrap().status.as_deref(),
+        Some("Not set: Lines 1 and 2 both map host uid 1000")
+    );
+    assert!(matches!(app.input_mode, InputMode::Idmap));
+
+    // The disk follows the one line
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('s')).await;
+    assert_eq!(lxd.device("c1", "data").unwrap()["shift"].as_str(), "true");
+    assert_eq!(
+        lxd.config("c1", "raw.idmap").as_deref(),
+        Some("both 1000 1000")
+    );
+
+    press(&mut app, KeyCode::Char('k')).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    assert_eq!(lxd.config("c1", "raw.idmap"), None);
+    assert!(app.idmap.as_ref().unwrap().disks[0].shift);
+}
+
+#[tokio::test]
+async fn recreate_snapshots_a_container_and_creates_a_vm_like_it() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Stopped", None)
+        .with_config("web1", "image.os", "Debian")
+        .with_config("web1", "image.release", "bookworm")
+        .with_config("web1", "limits.memory", "2GiB")
+        .with_config("web1", "security.nesting", "true")
+        .with_device(
+            "web1",
+            "data",
+            &[
+                ("type", "disk"),
+                ("pool", "fast"),
+                ("source", "web-data"),
+                ("path", "/srv"),
+            ],
+        );
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    assert!(app.select_container("web1").await);
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('R')).await;
+    assert!(matches!(app.input_mode, InputMode::Recreate));
+    assert_eq!(app.recreate.as_ref().unwrap().volumes, ["fast/web-data"]);
+
+    press(&mut app, KeyCode::Enter).await;
+    finish_operations(&mut app).await;
+    assert!(success_message(&app)
+        .unwrap()
+        .starts_with("Created VM 'web1-vm', stopped"));
+    let snapshots = lxd.snapshots("web1");
+    assert!(snapshots.len() == 1 && snapshots[0].starts_with("before-recreate-"));
+    assert_eq!(
+        lxd.instance_type("web1-vm").as_deref(),
+        Some("virtual-machine")
+    );
+    assert_eq!(lxd.source("web1-vm").unwrap()["alias"], "debian/bookworm");
+    assert_eq!(
+        lxd.config("web1-vm", "limits.memory").as_deref(),
+        Some("2GiB")
+    );
+    assert_eq!(lxd.config("web1-vm", "security.nesting"), None);
+    assert_eq!(lxd.device("web1-vm", "data").unwrap()["source"], "web-data");
+    assert_eq!(lxd.status("web1-vm").as_deref(), Some("Stopped"));
+}
+
+#[tokio::test]
+async fn recreate_is_refused_without_create() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Stopped", None)
+        .with_config("web1", "image.os", "Debian")
+        .with_config("web1", "image.release", "bookworm");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    assert!(app.select_container("web1").await);
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('R')).await;
+    assert!(matches!(app.input_mode, InputMode::Recreate));
+
+    app.read_only = true;
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(error_title(&app), Some("Read-only mode"));
+    app.read_only = false;
+
+    app.permissions.deny = vec![Permission::Create];
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('R')).await;
+    assert_eq!(error_title(&app), Some("Not permitted"));
+
+    // Nor can a view get around the checks by running the action itself
+    press(&mut app, KeyCode::Esc).await;
+    app.run_action(PendingAction::CreateContainer).await;
+    assert_eq!(error_title(&app), Some("Not permitted"));
+
+    assert!(lxd.snapshots("web1").is_empty());
+    assert!(!lxd.requests().iter().any(|r| r.starts_with("POST")));
+}
+
+#[tokio::test]
+async fn batch_profile_changes_report_each_picked_instance() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Stopped", None)
+        .with_profile("monitoring");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    // With nothing picked the selected instance is changed
+    assert!(app.select_container("web1").await);
+    press(&mut app, KeyCode::Char('P')).await;
+    assert!(matches!(app.input_mode, InputMode::Profiles));
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('a')).await;
+    finish_operations(&mut app).await;
+    assert_eq!(lxd.profiles("web1"), ["default", "monitoring"]);
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('x')).await;
+    assert!(app.select_container("web2").await);
+    press(&mut app, KeyCode::Char('x')).await;
+    assert_eq!(app.picked, ["web1", "web2"]);
+    press(&mut app, KeyCode::Char('P')).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('a')).await;
+    finish_operations(&mut app).await;
+    assert_eq!(lxd.profiles("web2"), ["default", "monitoring"]);
+    let view = app.profile_batch.as_ref().unwrap();
+    assert_eq!(view.targets[0].outcome, Some(Outcome::Unchanged));
+    assert_eq!(view.targets[1].outcome, Some(Outcome::Changed));
+    assert_eq!(
+        view.status.as_deref(),
+        Some("Added 'monitoring' to 1, 1 unchanged")
+    );
+
+    // Adding it again changes nothing, and says so
+    press(&mut app, KeyCode::Char('a')).await;
+    assert_eq!(
+        app.profile_batch.as_ref().unwrap().status.as_deref(),
+        Some("Every instance already has 'monitoring'")
+    );
+
+    press(&mut app, KeyCode::Char('d')).await;
+    finish_operations(&mut app).await;
+    assert_eq!(lxd.profiles("web1"), ["default"]);
+    assert_eq!(lxd.profiles("web2"), ["default"]);
+}
+
+#[tokio::test]
+async fn key_search_lists_the_instances_setting_a_key() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Running", None)
+        .with_instance("db1", "Stopped", None)
+        .with_config("web2", "security.privileged", "true")
+        .with_config("db1", "security.privileged", "false")
+        .with_config("db1", "security.nesting", "true");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('K')).await;
+    for c in "security.privileged".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(app.input_mode, InputMode::KeySearch));
+    let view = app.key_search.as_ref().unwrap();
+    let found: Vec<(&str, &str)> = view
+        .hits
+        .iter()
+        .map(|h| (h.instance.as_str(), h.value.as_str()))
+        .collect();
+    assert_eq!(found, [("db1", "false"), ("web2", "true")]);
+    assert_eq!((view.matched(), view.instances), (2, 3));
+
+    // Searching again starts from the last key
+    press(&mut app, KeyCode::Char('/')).await;
+    assert_eq!(app.input_buffer.as_str(), "security.privileged");
+    for _ in "privileged".chars() {
+        press(&mut app, KeyCode::Backspace).await;
+    }
+    press(&mut app, KeyCode::Char('*')).await;
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(app.key_search.as_ref().unwrap().hits.len(), 3);
+
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(app.input_mode, InputMode::Normal));
+    assert_eq!(app.get_selected_container().await.unwrap().name, "web2");
+}
+
+#[tokio::test]
+async fn inventory_is_exported_from_the_system_menu() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("db1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    let dir = std::env::temp_dir().join(format!("lxtui-inventory-{}", uuid::Uuid::new_v4()));
+    app.inventory = InventoryConfig {
+        dir: Some(dir.clone()),
+        format: InventoryFormat::Json,
+        stable: true,
+    };
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char(' ')).await;
+    press(&mut app, KeyCode::Char('i')).await;
+    let path = dir.join("inventory-local.json");
+    assert_eq!(
+        success_message(&app),
+        Some(
+            format!(
+                "Saved the inventory of local to {} (instances 2, profiles 1, networks 0, storage pools 2, images 0)",
+                path.display()
+            )
+            .as_str()
+        )
+    );
+    let saved: Inventory = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved.instances.len(), 2);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn usage_report_is_exported_from_the_system_menu() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_metrics(
+            r#"lxd_cpu_seconds_total{cpu="0",mode="user",name="web1",project="default",type="container"} 42.5
+lxd_memory_MemTotal_bytes{name="web1",project="default",type="container"} 1073741824
+lxd_memory_MemAvailable_bytes{name="web1",project="default",type="container"} 536870912
+"#,
+        );
+    let mut app = app_for(&lxd).await;
+    let dir = std::env::temp_dir().join(format!("lxtui-usage-{}", uuid::Uuid::new_v4()));
+    app.usage_config = UsageConfig {
+        sample_seconds: 0,
+        dir: Some(dir.clone()),
+    };
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char(' ')).await;
+    press(&mut app, KeyCode::Char('u')).await;
+    let path = std::fs::read_dir(&dir)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    assert_eq!(
+        success_message(&app),
+        Some(format!("Saved the usage of 1 instance to {}", path.display()).as_str())
+    );
+    let csv = std::fs::read_to_string(&path).unwrap();
+    let row = csv.lines().nth(1).unwrap();
+    assert!(row.starts_with("local,web1,"), "{}", row);
+    assert!(
+        row.ends_with(",0,0.000,42.500,536870912,536870912,0.0000,0,0"),
+        "{}",
+        row
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn zfs_pools_are_scrubbed_from_the_pools_view() {
+    let lxd = FakeLxd::start().with_pool_warning("fast", "high", "Pool is degraded");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('T')).await;
+    let view = app.pools.as_ref().unwrap();
+    let names: Vec<&str> = view.pools.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["default", "fast"]);
+    assert!(view.pools[0].warnings.is_empty());
+    assert_eq!(view.pools[1].warnings[0].message, "Pool is degraded");
+    assert!(view.pools[1].troubled(None));
+
+    // Health checks finish in the background; dir pools have none
+    let started = Instant::now();
+    while !app.pools.as_ref().unwrap().checks.contains_key("fast") {
+        assert!(
+            started.elapsed() < Duration::from_secs(15),
+            "check never finished"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        app.poll_pools();
+    }
+    let view = app.pools.as_ref().unwrap();
+    assert!(view.checking.is_empty());
+    assert!(!view.checks.contains_key("default"));
+
+    // dir pools have nothing to scrub with
+    press(&mut app, KeyCode::Char('s')).await;
+    assert_eq!(
+        app.pools.as_ref().unwrap().status.as_deref(),
+        Some("dir pools have no scrub")
+    );
+    assert!(app.external_command.is_none());
+
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('s')).await;
+    assert!(matches!(
+        app.input_mode,
+        InputMode::Pools {
+            confirm_scrub: true
+        }
+    ));
+    press(&mut app, KeyCode::Char('y')).await;
+    let command = app.external_command.take().unwrap();
+    assert_eq!(command.name, "Scrub fast");
+    assert!(
+        command.command.ends_with("zpool scrub fast"),
+        "{}",
+        command.command
+    );
+
+    app.scrub_command_finished(true).await;
+    assert_eq!(
+        app.pools.as_ref().unwrap().status.as_deref(),
+        Some("Started scrubbing 'fast'")
+    );
+    assert!(matches!(
+        app.input_mode,
+        InputMode::Pools {
+            confirm_scrub: false
+        }
+    ));
+}
+
+#[tokio::test]
+async fn list_refreshes_stay_within_the_polling_rate() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    app.polls.configure(&PollingConfig {
+        burst: 1,
+        refresh_per_minute: 1,
+        ..PollingConfig::default()
+    });
+    let listed = |lxd: &FakeLxd| {
+        lxd.requests()
+            .iter()
+            .filter(|r| *r == "GET /1.0/instances")
+            .count()
+    };
+    let before = listed(&lxd);
+
+    // Due, and allowed once
+    app.next_refresh = None;
+    app.maybe_auto_refresh().await;
+    assert_eq!(listed(&lxd), before + 1);
+    let next = app.next_refresh.unwrap();
+    let interval = next - tokio::time::Instant::now();
+    assert!(interval > Duration::from_secs(7) && interval <= Duration::from_secs(12));
+
+    // An event asking for another waits for the rate, however often asked
+    app.refresh_wanted = true;
+    for _ in 0..5 {
+        app.maybe_auto_refresh().await;
+    }
+    assert_eq!(listed(&lxd), before + 1);
+    assert!(app.refresh_wanted);
+
+    // An explicit refresh isn't held back, and settles what was wanted
+    app.refresh_containers().await.unwrap();
+    assert_eq!(listed(&lxd), before + 2);
+    assert!(!app.refresh_wanted);
+}
+
+#[tokio::test]
+async fn drift_is_checked_against_the_newest_export() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("db1", "Stopped", None)
+        .with_config("web1", "limits.cpu", "2");
+    let mut app = app_for(&lxd).await;
+    let dir = std::env::temp_dir().join(format!("lxtui-drift-{}", uuid::Uuid::new_v4()));
+    app.inventory = InventoryConfig {
+        dir: Some(dir.clone()),
+        format: InventoryFormat::Yaml,
+        stable: false,
+    };
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Char(' ')).await;
+    press(&mut app, KeyCode::Char('i')).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    let client = lxd.client();
+    client.set_config("web1", "limits.cpu", "4").await.unwrap();
+    client.delete_container("db1").await.unwrap();
+    lxd.set_status("web1", "Stopped");
+
+    press(&mut app, KeyCode::Char(' ')).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    let offered = std::path::PathBuf::from(app.input_buffer.as_str());
+    assert_eq!(offered.parent(), Some(dir.as_path()));
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(app.input_mode, InputMode::Drift));
+    let view = app.drift.as_ref().unwrap();
+    assert_eq!(view.error, None);
+    let names: Vec<&str> = view.drifts.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["db1", "web1"]);
+    assert_eq!(view.tally(), (0, 1, 1));
+
+    // A file that isn't there says so in the view
+    press(&mut app, KeyCode::Char('o')).await;
+    app.input_buffer.set("~/no-such-inventory.yaml");
+    press(&mut app, KeyCode::Enter).await;
+    let view = app.drift.as_ref().unwrap();
+    assert!(view.error.as_ref().unwrap().starts_with("Inventory file:"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Run the queued script the way the event loop does, one command per tick
+async fn run_script(app: &mut App) {
+    let started = Instant::now();
+    while !app.script.is_empty() {
+        assert!(
+            started.elapsed() < OPERATION_TIMEOUT,
+            "script still running"
+        );
+        if let Some(action) = app.next_script_action() {
+            action::update(app, action).await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        app.poll_background_tasks().await;
+    }
+    finish_operations(app).await;
+}
+
+fn script(lines: &[&str]) -> VecDeque<action::Action> {
+    lines
+        .iter()
+        .map(|line| command::parse(line, Source::Script).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn script_runs_commands_in_order() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+
+    app.script = script(&["stop web1", "snapshot web1 pre-upgrade", "start web1"]);
+    run_script(&mut app).await;
+
+    let changes: Vec<String> = lxd
+        .requests()
+        .into_iter()
+        .filter(|r| r.starts_with("PUT") || r.starts_with("POST"))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            "PUT /1.0/instances/web1/state",
+            "POST /1.0/instances/web1/snapshots",
+            "PUT /1.0/instances/web1/state",
+        ]
+    );
+    assert_eq!(lxd.snapshots("web1"), vec!["pre-upgrade"]);
+    assert_eq!(lxd.status("web1").as_deref(), Some("Running"));
+}
+
+#[tokio::test]
+async fn script_stops_at_an_error() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    lxd.fail_operations("Failed to stop");
+
+    app.script = script(&["stop web1", "delete web1"]);
+    run_script(&mut app).await;
+
+    assert!(error_title(&app).is_some());
+    assert!(!lxd.requests().iter().any(|r| r.starts_with("DELETE")));
+    assert!(app
+        .message
+        .as_deref()
+        .unwrap()
+        .contains("1 commands skipped"));
+}
+
+#[tokio::test]
+async fn read_only_mode_refuses_changes() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Stopped", None)
+        .with_instance("web2", "Running", None);
+    let mut app = app_for(&lxd).await;
+    app.read_only = true;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('s')).await;
+    assert_eq!(error_title(&app), Some("Read-only mode"));
+    press(&mut app, KeyCode::Esc).await;
+
+    // Menus and the command line can't get around it either
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    assert_eq!(error_title(&app), Some("Read-only mode"));
+    app.script = script(&["select web2", "stop web2", "start web1"]);
+    run_script(&mut app).await;
+    assert_eq!(error_title(&app), Some("Read-only mode"));
+    assert!(app.script.is_empty());
+
+    assert!(!lxd
+        .requests()
+        .iter()
+        .any(|r| r.starts_with("PUT") || r.starts_with("DELETE")));
+    assert_eq!(
+        listed(&app).await,
+        [
+            ("web1".to_string(), "Stopped".to_string()),
+            ("web2".to_string(), "Running".to_string()),
+        ]
+    );
+
+    // Looking is still allowed
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Char('w')).await;
+    assert!(matches!(app.input_mode, InputMode::Watch));
+}
+
+#[tokio::test]
+async fn permissions_restrict_actions() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Stopped", None)
+        .with_instance("web2", "Running", None);
+    let mut app = app_for(&lxd).await;
+    app.permissions.deny = vec![Permission::Delete, Permission::Exec];
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('d')).await;
+    assert_eq!(error_title(&app), Some("Not permitted"));
+    press(&mut app, KeyCode::Esc).await;
+    action::update(&mut app, action::Action::ExecSelected).await;
+    assert_eq!(error_title(&app), Some("Not permitted"));
+    assert_eq!(app.exec_container, None);
+    press(&mut app, KeyCode::Esc).await;
+
+    // The host shell is permitted until denied
+    press(&mut app, KeyCode::Char('!')).await;
+    assert_eq!(app.external_command.take().unwrap().name, "Host shell");
+    app.permissions.deny.push(Permission::Shell);
+    press(&mut app, KeyCode::Char('!')).await;
+    assert_eq!(error_title(&app), Some("Not permitted"));
+    assert!(app.external_command.is_none());
+    press(&mut app, KeyCode::Esc).await;
+
+    // Everything else still runs, until a script reaches a denied command
+    app.script = script(&["stop web2", "delete web1", "start web1"]);
+    run_script(&mut app).await;
+    assert_eq!(error_title(&app), Some("Not permitted"));
+    assert!(app.script.is_empty());
+    assert_eq!(
+        listed(&app).await,
+        [
+            ("web1".to_string(), "Stopped".to_string()),
+            ("web2".to_string(), "Stopped".to_string()),
+        ]
+    );
+    press(&mut app, KeyCode::Esc).await;
+
+    // With an allow list, only what it names is permitted
+    app.permissions.deny.clear();
+    app.permissions.allow = vec![Permission::Start];
+    press(&mut app, KeyCode::Char('S')).await;
+    assert_eq!(error_title(&app), Some("Not permitted"));
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Char('s')).await;
+    assert_eq!(error_title(&app), None);
+    assert!(!lxd.requests().iter().any(|r| r.starts_with("DELETE")));
+}
+
+#[tokio::test]
+async fn probes_instances_with_a_health_check() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_config("web1", "user.lxtui.healthcheck", "curl -fs localhost")
+        .with_exec("web1", 22, "curl: (7) Failed to connect")
+        .with_instance("web2", "Running", None);
+    let mut app = app_for(&lxd).await;
+
+    let started = Instant::now();
+    while app.probes.get("web1").is_none() {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "probe never finished"
+        );
+        app.poll_probes();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let probe = app.probes.get("web1").unwrap();
+    assert_eq!(probe.health, Health::Unhealthy);
+    assert_eq!(
+        probe.output,
+        "curl: (7) Failed to connect\n(exit status 22)"
+    );
+    assert!(app.probes.get("web2").is_none());
+    assert_eq!(
+        lxd.requests()
+            .iter()
+            .filter(|r| r.ends_with("/exec"))
+            .collect::<Vec<_>>(),
+        ["POST /1.0/instances/web1/exec"]
+    );
+}
+
+#[tokio::test]
+async fn probes_need_exec() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_config("web1", "user.lxtui.healthcheck", "true");
+    let mut app = app_for(&lxd).await;
+
+    app.read_only = true;
+    app.poll_probes();
+    app.read_only = false;
+    app.permissions.deny = vec![Permission::Exec];
+    app.poll_probes();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!lxd.requests().iter().any(|r| r.ends_with("/exec")));
+}
+
+#[tokio::test]
+async fn dashboard_sums_up_the_host() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("db1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('D')).await;
+    assert!(matches!(app.input_mode, InputMode::Dashboard));
+    let dashboard = app.dashboard.as_ref().unwrap();
+    let pools: Vec<(&str, Option<f64>)> = dashboard
+        .pools
+        .iter()
+        .map(|pool| (pool.name.as_str(), pool.fraction()))
+        .collect();
+    assert_eq!(
+        pools,
+        [("default", Some(0.25)), ("fast", Some(29.0 / 32.0))]
+    );
+    assert!(app.top.is_some());
+
+    // Enter drills into the list, which the dashboard doesn't change
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(app.input_mode, InputMode::Normal));
+    assert_eq!(listed(&app).await.len(), 2);
+}
+
+#[tokio::test]
+async fn uptime_is_noted_on_refresh_and_kept() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("db1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    let dir = std::env::temp_dir().join(format!("lxtui-uptime-{}", uuid::Uuid::new_v4()));
+    let config = UptimeConfig {
+        path: Some(dir.join("uptime.json")),
+        ..UptimeConfig::default()
+    };
+    app.uptime = Uptime::new(&config);
+
+    app.refresh_containers().await.unwrap();
+    lxd.set_status("web1", "Stopped");
+    app.refresh_containers().await.unwrap();
+    lxd.set_status("web1", "Running");
+    app.refresh_containers().await.unwrap();
+
+    let uptime = app.uptime.as_ref().unwrap();
+    let ups = |name: &str| -> Vec<bool> { uptime.timelines[name].iter().map(|t| t.up).collect() };
+    assert_eq!(ups("web1"), [true, false, true]);
+    assert_eq!(ups("db1"), [false]);
+    let now = chrono::Local::now();
+    assert_eq!(uptime.availability("web1", now).unwrap().restarts, 1);
+
+    // Saved for the next session
+    let mut loaded = Uptime::new(&config).unwrap();
+    loaded.load(now).unwrap();
+    assert_eq!(loaded.timelines, uptime.timelines);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Run the schedules due at `at` and wait for the jobs they start
+async fn run_schedules(app: &mut App, at: chrono::NaiveDateTime) {
+    let at = at.and_local_timezone(chrono::Local).unwrap();
+    app.run_schedules_at(at);
+    let started = Instant::now();
+    while app
+        .user_operations
+        .iter()
+        .any(|op| matches!(op.status, OperationStatus::Running))
+    {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "scheduled jobs never finished"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        app.poll_background_tasks().await;
+    }
+}
+
+#[tokio::test]
+async fn power_schedules_run_unless_skipped_or_held() {
+    let lxd = FakeLxd::start()
+        .with_instance("dev1", "Running", None)
+        .with_instance("dev2", "Running", None)
+        .with_instance("dev3", "Running", None)
+        .with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    app.schedules = ["dev1", "dev2", "dev3"]
+        .iter()
+        .map(|name| {
+            let job = ScheduleConfig {
+                instance: name.to_string(),
+                action: ScheduledAction::Stop,
+                schedule: "* * * * *".to_string(),
+            };
+            (job, Schedule::parse("* * * * *").unwrap())
+        })
+        .collect();
+
+    app.select_container("dev2").await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('p')).await;
+    let Some(Override::Skip(skipped)) = app.power_overrides.get("dev2") else {
+        panic!("dev2's next stop not skipped");
+    };
+    press(&mut app, KeyCode::Esc).await;
+    app.select_container("dev3").await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('P')).await;
+    assert_eq!(app.power_overrides.get("dev3"), Some(Override::Hold));
+    press(&mut app, KeyCode::Esc).await;
+    app.select_container("web1").await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('p')).await;
+    assert!(matches!(
+        &app.input_mode,
+        InputMode::StatusModal(StatusModalType::Info { message, .. })
+            if message.contains("no start or stop schedule")
+    ));
+    press(&mut app, KeyCode::Esc).await;
+
+    run_schedules(&mut app, skipped).await;
+    assert_eq!(lxd.status("dev1").as_deref(), Some("Stopped"));
+    assert_eq!(lxd.status("dev2").as_deref(), Some("Running"));
+    assert_eq!(lxd.status("dev3").as_deref(), Some("Running"));
+    assert!(app.refresh_wanted);
+
+    // The skip is used up; the hold stays until lifted
+    run_schedules(&mut app, skipped + chrono::TimeDelta::minutes(1)).await;
+    assert_eq!(lxd.status("dev2").as_deref(), Some("Stopped"));
+    assert_eq!(lxd.status("dev3").as_deref(), Some("Running"));
+    assert!(app
+        .user_operations
+        .iter()
+        .any(|op| op.description == "Scheduled stop of 'dev2'"));
+}
+
+#[tokio::test]
+async fn power_schedules_leave_instances_already_in_that_state() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("dev1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    app.schedules = [
+        ("web1", ScheduledAction::Start),
+        ("dev1", ScheduledAction::Stop),
+    ]
+    .into_iter()
+    .map(|(name, action)| {
+        let job = ScheduleConfig {
+            instance: name.to_string(),
+            action,
+            schedule: "0 8 * * *".to_string(),
+        };
+        (job, Schedule::parse("0 8 * * *").unwrap())
+    })
+    .collect();
+
+    let eight = chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+        .unwrap()
+        .and_hms_opt(8, 0, 0)
+        .unwrap();
+    run_schedules(&mut app, eight).await;
+    assert!(app
+        .user_operations
+        .iter()
+        .all(|op| matches!(op.status, OperationStatus::Success)));
+    assert_eq!(app.schedule_warning, None);
+    assert!(!lxd.requests().iter().any(|r| r.starts_with("PUT")));
+}
+
+#[tokio::test]
+async fn restarts_crashed_instances_with_a_restart_policy() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_config("web1", "user.lxtui.restart", "always")
+        .with_instance("web2", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    lxd.set_status("web1", "Stopped");
+    lxd.set_status("web2", "Stopped");
+    app.refresh_containers().await.unwrap();
+    let started = Instant::now();
+    while app.active_operation_count > 0 {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "restart never finished"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        app.poll_restarts();
+    }
+
+    assert_eq!(lxd.status("web1").as_deref(), Some("Running"));
+    assert_eq!(lxd.status("web2").as_deref(), Some("Stopped"));
+    assert_eq!(app.restarts.get("web1").unwrap().count, 1);
+    assert!(app.restarts.get("web2").is_none());
+    // In the background, without a modal
+    assert!(matches!(app.input_mode, InputMode::Normal));
+}
+
+#[tokio::test]
+async fn instances_stopped_from_outside_are_not_restarted() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_config("web1", "user.lxtui.restart", "always");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    // The policy has events followed without the events panel open
+    assert!(!app.show_events);
+    let started = Instant::now();
+    while lxd.subscribers() == 0 {
+        assert!(started.elapsed() < OPERATION_TIMEOUT, "never subscribed");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // Another admin runs `lxc stop web1`
+    lxd.set_status("web1", "Stopped");
+    lxd.send_lifecycle("instance-shutdown", "web1", Some("admin"));
+    while app.events.events.is_empty() {
+        assert!(started.elapsed() < OPERATION_TIMEOUT, "event never arrived");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        app.poll_events().await;
+    }
+    app.refresh_containers().await.unwrap();
+    app.poll_restarts();
+
+    assert_eq!(app.active_operation_count, 0);
+    assert!(app.restarts.get("web1").is_none());
+    assert_eq!(lxd.status("web1").as_deref(), Some("Stopped"));
+}
+
+#[tokio::test]
+async fn captures_the_console_of_a_crashed_instance() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    lxd.write_console("web1", "Kernel panic - not syncing\n");
+
+    // Stopped from LXTUI: expected, nothing captured
+    app.select_container("web2").await;
+    press(&mut app, KeyCode::Char('S')).await;
+    press(&mut app, KeyCode::Char('y')).await;
+    finish_operations(&mut app).await;
+
+    lxd.set_status("web1", "Stopped");
+    app.refresh_containers().await.unwrap();
+    let started = Instant::now();
+    while app.crashes.get("web1").is_none() {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "console never captured"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        app.poll_crashes();
+    }
+    assert!(app.crashes.get("web2").is_none());
+
+    // Still readable after the instance runs again
+    lxd.set_status("web1", "Running");
+    app.refresh_containers().await.unwrap();
+    app.input_mode = InputMode::Normal;
+    app.select_container("web1").await;
+    press(&mut app, KeyCode::Char('F')).await;
+    assert!(matches!(app.input_mode, InputMode::Console));
+    let console = app.console.as_ref().unwrap();
+    assert_eq!(console.lines, ["Kernel panic - not syncing"]);
+    assert!(console.captured.is_some());
+}
+
+#[tokio::test]
+async fn filter_limits_the_selection() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Stopped", None)
+        .with_instance("db1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char(':')).await;
+    for c in "filter status=running".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(app.filter.as_ref().unwrap().to_string(), "status=running");
+
+    app.next().await;
+    assert_eq!(app.get_selected_container().await.unwrap().name, "web1");
+    app.next().await;
+    assert_eq!(app.get_selected_container().await.unwrap().name, "db1");
+
+    assert!(!app.select_container("web2").await);
+}
+
+#[tokio::test]
+async fn command_line_recalls_earlier_commands_across_sessions() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("db1", "Running", None);
+    let config = PromptHistoryConfig {
+        limit: 10,
+        path: Some(
+            std::env::temp_dir().join(format!("lxtui-prompts-{}.json", uuid::Uuid::new_v4())),
+        ),
+    };
+    let mut app = app_for(&lxd).await;
+    app.prompt_history = PromptHistory::new(&config);
+    press(&mut app, KeyCode::Esc).await;
+
+    for command in ["filter status=running", "sort name"] {
+        press(&mut app, KeyCode::Char(':')).await;
+        for c in command.chars() {
+            press(&mut app, KeyCode::Char(c)).await;
+        }
+        press(&mut app, KeyCode::Enter).await;
+    }
+
+    // A new session reads the history back
+    let mut app = app_for(&lxd).await;
+    app.prompt_history = PromptHistory::new(&config);
+    app.prompt_history.as_mut().unwrap().load().unwrap();
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char(':')).await;
+    press(&mut app, KeyCode::Char('f')).await;
+    press(&mut app, KeyCode::Up).await;
+    assert_eq!(app.input_buffer.as_str(), "sort name");
+    press(&mut app, KeyCode::Up).await;
+    press(&mut app, KeyCode::Up).await;
+    assert_eq!(app.input_buffer.as_str(), "filter status=running");
+    press(&mut app, KeyCode::Down).await;
+    press(&mut app, KeyCode::Down).await;
+    assert_eq!(app.input_buffer.as_str(), "f");
+
+    press(&mut app, KeyCode::Up).await;
+    press(&mut app, KeyCode::Up).await;
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(app.filter.as_ref().unwrap().to_string(), "status=running");
+
+    // Other prompts keep their own history
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('t')).await;
+    press(&mut app, KeyCode::Up).await;
+    assert!(app.input_buffer.is_empty());
+}
+
+#[tokio::test]
+async fn undo_restores_the_view_a_key_or_command_changed() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Stopped", None)
+        .with_instance("db1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    assert!(app.select_container("db1").await);
+    press(&mut app, KeyCode::Char('m')).await;
+    press(&mut app, KeyCode::Char(':')).await;
+    for c in "filter status=running".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('g')).await;
+
+    press(&mut app, KeyCode::Char('u')).await;
+    assert!(!app.group_by_tag);
+    assert_eq!(app.notice(), Some("Undone: grouping"));
+    press(&mut app, KeyCode::Char('u')).await;
+    assert!(app.filter.is_none());
+    assert_eq!(app.compare_marks, ["db1"]);
+    // The row selected before the filter is selected again
+    assert_eq!(app.get_selected_container().await.unwrap().name, "db1");
+    press(&mut app, KeyCode::Char('u')).await;
+    assert!(app.compare_marks.is_empty());
+    press(&mut app, KeyCode::Char('u')).await;
+    assert_eq!(app.notice(), Some("Nothing to undo"));
+
+    let redo = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+    for _ in 0..2 {
+        let action = input::map_key(&app, redo, 20).unwrap();
+        action::update(&mut app, action).await;
+    }
+    assert_eq!(app.filter.as_ref().unwrap().to_string(), "status=running");
+    assert_eq!(app.notice(), Some("Redone: filter"));
+
+    // A new change can't be followed by a redo of the grouping
+    press(&mut app, KeyCode::Char('o')).await;
+    let action = input::map_key(&app, redo, 20).unwrap();
+    action::update(&mut app, action).await;
+    assert!(!app.group_by_tag);
+    assert_eq!(app.notice(), Some("Nothing to redo"));
+}
+
+#[tokio::test]
+async fn undo_brings_back_cleared_picks() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('x')).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('x')).await;
+    assert_eq!(app.picked, ["web1", "web2"]);
+    press(&mut app, KeyCode::Char('X')).await;
+    assert!(app.picked.is_empty());
+
+    press(&mut app, KeyCode::Char('u')).await;
+    assert_eq!(app.picked, ["web1", "web2"]);
+    assert_eq!(app.notice(), Some("Undone: picks"));
+}
+
+#[tokio::test]
+async fn tag_filter_and_group() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    app.next().await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('t')).await;
+    for c in "prod, eu".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(success_message(&app), Some("Tagged 'web2' with prod, eu"));
+    assert_eq!(
+        lxd.config("web2", "user.lxtui.tags").as_deref(),
+        Some("prod,eu")
+    );
+    let containers = app.containers.read().await.clone();
+    assert_eq!(containers[1].tags, ["prod", "eu"]);
+
+    // Tagged containers come first when grouped
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Char('g')).await;
+    assert_eq!(app.get_selected_container().await.unwrap().name, "web2");
+
+    app.filter = Some(ContainerFilter::parse("tag=EU").unwrap());
+    assert_eq!(app.visible(&containers).len(), 1);
+}
+
+#[tokio::test]
+async fn reduced_motion_keeps_feedback_until_dismissed() {
+    let lxd = FakeLxd::start();
+    let mut app = app_for(&lxd).await;
+    app.reduced_motion = true;
+
+    app.show_info("Error report copied with xclip".to_string(), true);
+    assert!(matches!(
+        app.input_mode,
+        InputMode::StatusModal(StatusModalType::Info {
+            auto_close: false,
+            ..
+        })
+    ));
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(app.input_mode, InputMode::Normal));
+
+    // Notices don't expire, but go with the next key
+    press(&mut app, KeyCode::Char('u')).await;
+    let (_, at) = app.notice.as_mut().unwrap();
+    *at -= Duration::from_secs(60);
+    assert_eq!(app.notice(), Some("Nothing to undo"));
+    press(&mut app, KeyCode::Char('j')).await;
+    assert_eq!(app.notice(), None);
+}
+
+#[tokio::test]
+async fn success_and_info_dialogs_close_by_themselves() {
+    let lxd = FakeLxd::start();
+    let mut app = app_for(&lxd).await;
+    let now = tokio::time::Instant::now();
+
+    app.show_success("Successfully started 'web1'".to_string());
+    app.dismiss_expired_modal(now + Duration::from_secs(1));
+    assert!(success_message(&app).is_some());
+    app.dismiss_expired_modal(now + Duration::from_secs(3));
+    assert!(matches!(app.input_mode, InputMode::Normal));
+
+    app.show_info("Refreshing container list...".to_string(), true);
+    app.dismiss_expired_modal(now + Duration::from_secs(3));
+    assert!(matches!(app.input_mode, InputMode::Normal));
+
+    // Only information dialogs meant to close by themselves do
+    app.show_info("Error report saved".to_string(), false);
+    app.dismiss_expired_modal(now + Duration::from_secs(3));
+    assert!(!matches!(app.input_mode, InputMode::Normal));
+
+    // Turned off with dismiss_after_secs = 0
+    app.dismiss_after = None;
+    app.show_success("Successfully started 'web1'".to_string());
+    app.dismiss_expired_modal(now + Duration::from_secs(60));
+    assert!(success_message(&app).is_some());
+}
+
+#[tokio::test]
+async fn number_keys_switch_workspaces() {
+    let lxd = FakeLxd::start()
+        .with_instance("web2", "Stopped", None)
+        .with_instance("db1", "Running", None)
+        .with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    app.workspaces = vec![Workspace {
+        name: "web".to_string(),
+        filter: Some(ContainerFilter::parse("name=web").unwrap()),
+        sort: Some(SortKey::Name),
+        group_by_tag: false,
+    }];
+
+    press(&mut app, KeyCode::Char('1')).await;
+    assert_eq!(app.workspace, Some(0));
+    let containers = app.containers.read().await.clone();
+    let names: Vec<String> = app
+        .visible(&containers)
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(names, ["web1", "web2"]);
+
+    // Changing the view leaves the workspace
+    press(&mut app, KeyCode::Char('g')).await;
+    assert_eq!(app.workspace, None);
+    assert!(app.filter.is_some());
+
+    press(&mut app, KeyCode::Char('0')).await;
+    assert_eq!(app.visible(&containers).len(), 3);
+    assert_eq!(app.sort, None);
+    assert!(!app.group_by_tag);
+
+    // Keys without a workspace say so
+    press(&mut app, KeyCode::Char('2')).await;
+    assert!(matches!(
+        app.input_mode,
+        InputMode::StatusModal(StatusModalType::Info { .. })
+    ));
+}
+
+#[tokio::test]
+async fn unreachable_remotes_stay_flagged_until_they_answer() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    app.all_remotes = true;
+    app.remote_clients = vec![
+        ("home".to_string(), lxd.client()),
+        (
+            "away".to_string(),
+            LxcClient::from_api(LxdApiClient::unix("/nonexistent/lxd.socket".to_string())),
+        ),
+    ];
+
+    app.refresh_containers().await.unwrap();
+    assert_eq!(app.unreachable_remotes, ["away"]);
+    assert_eq!(app.containers.read().await.len(), 1);
+
+    app.remote_clients[1].1 = lxd.client();
+    app.refresh_containers().await.unwrap();
+    assert!(app.unreachable_remotes.is_empty());
+}
+
+#[tokio::test]
+async fn editor_remote_is_shown_for_the_selected_container() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", Some("10.0.0.10"));
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('o')).await;
+    let InputMode::StatusModal(StatusModalType::Info { message, .. }) = &app.input_mode else {
+        panic!("no editor remote: {:?}", app.input_mode);
+    };
+    assert!(message.contains("code --remote ssh-remote+10.0.0.10\n"));
+    assert!(message.contains("Host lxtui-web1\n    HostName 10.0.0.10\n"));
+}
+
+#[tokio::test]
+async fn edit_config_in_editor() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('i')).await;
+    let command = app.external_command.take().unwrap();
+    let path = app.config_edit.as_ref().unwrap().path.clone();
+    assert!(command.command.ends_with(&format!("'{}'", path.display())));
+    let original = std::fs::read_to_string(&path).unwrap();
+    assert!(original.contains("architecture: x86_64"));
+
+    // Invalid edits are kept for the next try
+    std::fs::write(&path, "architecture: [").unwrap();
+    app.finish_config_edit().await;
+    assert_eq!(error_title(&app), Some("Invalid config for 'web1'"));
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('i')).await;
+    assert_eq!(app.config_edit.as_ref().unwrap().path, path);
+
+    let edited = original.replace("config: {}", "config:\n  limits.cpu: 2");
+    std::fs::write(&path, edited).unwrap();
+    app.finish_config_edit().await;
+    assert_eq!(success_message(&app), Some("Updated the config of 'web1'"));
+    assert_eq!(lxd.config("web1", "limits.cpu").as_deref(), Some("2"));
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn publish_as_an_image_that_expires() {
+    let lxd = FakeLxd::start()
+        .with_instance("db1", "Running", None)
+        .with_instance("web1", "Stopped", None)
+        .with_config("web1", "image.os", "Debian")
+        .with_config("web1", "image.release", "12");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    assert!(app.expiring_images.is_empty());
+
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('I')).await;
+    assert_eq!(error_title(&app), Some("Can't publish 'db1' while it runs"));
+    press(&mut app, KeyCode::Esc).await;
+
+    app.next().await;
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('I')).await;
+    app.external_command.take().unwrap();
+    let path = app.config_edit.as_ref().unwrap().path.clone();
+    let original = std::fs::read_to_string(&path).unwrap();
+    assert!(original.contains("  os: Debian\n  release: '12'\n"));
+
+    let edited = original
+        .replace("alias: ''", "alias: golden-web")
+        .replace("expires: never", "expires: 3d");
+    std::fs::write(&path, edited).unwrap();
+    app.finish_config_edit().await;
+    finish_operations(&mut app).await;
+    assert_eq!(
+        success_message(&app),
+        Some("Published 'web1' as image 'golden-web'")
+    );
+    let image = lxd.images().pop().unwrap();
+    assert_eq!(image["aliases"][0]["name"], "golden-web");
+    assert_eq!(image["properties"]["release"], "12");
+    assert_eq!(image["public"], false);
+
+    // Expiring within the week, so the title bar counts it
+    app.refresh_containers().await.unwrap();
+    assert_eq!(app.expiring_images.len(), 1);
+    assert_eq!(app.expiring_images[0].aliases, ["golden-web"]);
+}
+
+#[tokio::test]
+async fn notes_are_edited_as_markdown() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_config("web1", "user.lxtui.notes", "# Restarting");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    assert_eq!(
+        app.containers.read().await[0].notes.as_deref(),
+        Some("# Restarting")
+    );
+
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('n')).await;
+    app.external_command.take().unwrap();
+    let path = app.config_edit.as_ref().unwrap().path.clone();
+    assert!(path.to_string_lossy().ends_with("-notes.md"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "# Restarting\n");
+
+    std::fs::write(&path, "# Restarting\n- drain `nginx` first\n\n").unwrap();
+    app.finish_config_edit().await;
+    assert_eq!(success_message(&app), Some("Saved the notes of 'web1'"));
+    assert_eq!(
+        lxd.config("web1", "user.lxtui.notes").as_deref(),
+        Some("# Restarting\n- drain `nginx` first")
+    );
+    press(&mut app, KeyCode::Esc).await;
+
+    // Emptying the file removes them
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('n')).await;
+    app.external_command.take().unwrap();
+    let path = app.config_edit.as_ref().unwrap().path.clone();
+    std::fs::write(&path, "\n").unwrap();
+    app.finish_config_edit().await;
+    assert_eq!(success_message(&app), Some("Removed the notes of 'web1'"));
+    assert_eq!(lxd.config("web1", "user.lxtui.notes"), None);
+    assert_eq!(app.containers.read().await[0].notes, None);
+}
+
+#[tokio::test]
+async fn quick_action_runs_in_every_picked_instance() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Running", None)
+        .with_instance("db1", "Stopped", None)
+        .with_exec("web1", 0, "/dev/sda1  10G  4G  6G  40% /\n")
+        .with_exec("web2", 1, "df: /srv: Input/output error\n");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    for name in ["web1", "web2", "db1"] {
+        assert!(app.select_container(name).await);
+        press(&mut app, KeyCode::Char('x')).await;
+    }
+    press(&mut app, KeyCode::Char('E')).await;
+    assert!(matches!(app.input_mode, InputMode::Quick { confirm: None }));
+
+    // Reboot asks first, and anything but y leaves them be
+    press(&mut app, KeyCode::Char('R')).await;
+    assert!(matches!(
+        app.input_mode,
+        InputMode::Quick { confirm: Some(3) }
+    ));
+    press(&mut app, KeyCode::Char('n')).await;
+    assert!(app.quick.as_ref().unwrap().run.is_none());
+
+    press(&mut app, KeyCode::Char('d')).await;
+    let started = Instant::now();
+    while app.quick.as_ref().unwrap().running() {
+        assert!(
+            started.elapsed() < OPERATION_TIMEOUT,
+            "quick action never finished"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        app.poll_quick();
+    }
+
+    let run = app.quick.as_ref().unwrap().run.as_ref().unwrap();
+    let outcomes: Vec<(&str, &quick::Outcome)> = run
+        .targets
+        .iter()
+        .map(|t| (t.name.as_str(), &t.outcome))
+        .collect();
+    assert_eq!(outcomes[0], ("web1", &quick::Outcome::Exited(0)));
+    assert_eq!(outcomes[1], ("web2", &quick::Outcome::Exited(1)));
+    assert!(matches!(outcomes[2], ("db1", quick::Outcome::Failed(_))));
+    assert_eq!(run.targets[0].output, "/dev/sda1  10G  4G  6G  40% /");
+    assert_eq!(
+        app.user_operations[0].description,
+        "Run 'Disk usage' in 3 instances"
+    );
+    assert!(matches!(
+        &app.user_operations[0].status,
+        OperationStatus::Failed(e) if e == "Failed in 2 of 3 instances"
+    ));
+
+    // Closed and opened again, the library is back for the next action
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Char('E')).await;
+    assert!(app.quick.as_ref().unwrap().run.is_none());
+}
+
+#[tokio::test]
+async fn one_off_command_runs_again_on_request() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_exec("web1", 0, "active\n");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('E')).await;
+    press(&mut app, KeyCode::Char('!')).await;
+    for c in "systemctl is-active nginx".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(app.input_mode, InputMode::Quick { confirm: None }));
+    assert_eq!(
+        app.user_operations[0].description,
+        "Run 'systemctl is-active nginx' in 'web1'"
+    );
+
+    for runs in 1..=2 {
+        let started = Instant::now();
+        while app.quick.as_ref().unwrap().running() {
+            assert!(
+                started.elapsed() < OPERATION_TIMEOUT,
+                "command never finished"
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            app.poll_quick();
+        }
+        let target = app.quick.as_ref().unwrap().selected_target().unwrap();
+        assert_eq!(target.outcome, quick::Outcome::Exited(0));
+        assert_eq!(target.output, "active");
+        assert_eq!(
+            lxd.requests()
+                .iter()
+                .filter(|r| r.ends_with("/exec"))
+                .count(),
+            runs
+        );
+        press(&mut app, KeyCode::Char('r')).await;
+    }
+}
+
+#[tokio::test]
+async fn quick_action_gives_up_on_commands_that_never_finish() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    app.quick_timeout = Duration::from_millis(100);
+    lxd.hold_operations();
+
+    press(&mut app, KeyCode::Char('E')).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    let started = Instant::now();
+    while app.quick.as_ref().unwrap().running() {
+        assert!(
+            started.elapsed() < OPERATION_TIMEOUT,
+            "quick action never timed out"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        app.poll_quick();
+    }
+    let target = app.quick.as_ref().unwrap().selected_target().unwrap();
+    assert!(matches!(target.outcome, quick::Outcome::TimedOut(_)));
+    assert!(matches!(
+        &app.user_operations[0].status,
+        OperationStatus::Failed(e) if e == "Failed in 1 of 1 instances"
+    ));
+}
+
+#[tokio::test]
+async fn quick_action_is_cancelled_with_esc() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    lxd.hold_operations();
+
+    for name in ["web1", "web2"] {
+        assert!(app.select_container(name).await);
+        press(&mut app, KeyCode::Char('x')).await;
+    }
+    press(&mut app, KeyCode::Char('E')).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    assert!(app.quick.as_ref().unwrap().running());
+
+    // Esc stops the wait but leaves the results up
+    press(&mut app, KeyCode::Esc).await;
+    assert!(matches!(app.input_mode, InputMode::Quick { confirm: None }));
+    let run = app.quick.as_ref().unwrap().run.as_ref().unwrap();
+    assert!(run
+        .targets
+        .iter()
+        .all(|t| t.outcome == quick::Outcome::Cancelled));
+    assert!(matches!(
+        app.user_operations[0].status,
+        OperationStatus::Cancelled
+    ));
+
+    // Finished, Esc closes the view again
+    press(&mut app, KeyCode::Esc).await;
+    assert!(matches!(app.input_mode, InputMode::Normal));
+}
+
+#[tokio::test]
+async fn compare_marked_containers() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    // One mark is not enough without another container selected
+    press(&mut app, KeyCode::Char('m')).await;
+    assert_eq!(app.compare_marks, ["web1"]);
+    press(&mut app, KeyCode::Char('C')).await;
+    assert!(matches!(
+        app.input_mode,
+        InputMode::StatusModal(StatusModalType::Info { .. })
+    ));
+    press(&mut app, KeyCode::Esc).await;
+
+    app.next().await;
+    press(&mut app, KeyCode::Char('C')).await;
+    assert!(matches!(app.input_mode, InputMode::Compare));
+    let compare = app.compare.as_ref().unwrap();
+    assert_eq!(
+        (compare.left.as_str(), compare.right.as_str()),
+        ("web1", "web2")
+    );
+    assert!(app.compare_marks.is_empty());
+    assert!(lxd
+        .requests()
+        .contains(&"GET /1.0/instances/web2".to_string()));
+}
+
+#[tokio::test]
+async fn console_view_follows_new_output() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    lxd.write_console("web1", "Booting\n");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('f')).await;
+    assert!(matches!(app.input_mode, InputMode::Console));
+    assert_eq!(app.console.as_ref().unwrap().lines, vec!["Booting"]);
+
+    lxd.write_console("web1", "Reached target multi-user\n");
+    tokio::time::sleep(crate::console::POLL_INTERVAL).await;
+    app.poll_console().await;
+    let console = app.console.as_ref().unwrap();
+    assert_eq!(console.lines.len(), 2);
+    assert!(console.error.is_none());
+}
+
+#[tokio::test]
+async fn missing_endpoint_disables_the_feature() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    let backup_requests = || {
+        lxd.requests()
+            .iter()
+            .filter(|r| r.starts_with("GET /1.0/instances/web1/backups"))
+            .count()
+    };
+
+    // The fake server has no backups endpoint, like an older LXD
+    for _ in 0..2 {
+        action::update(&mut app, action::Action::OpenBackups).await;
+        assert!(error_title(&app).is_none());
+        match &app.input_mode {
+            InputMode::StatusModal(StatusModalType::Info { message, .. }) => {
+                assert_eq!(message, "Backups isn't available on this server: not found")
+            }
+            mode => panic!("expected an explanation, got {:?}", mode),
+        }
+        press(&mut app, KeyCode::Esc).await;
+    }
+    assert_eq!(backup_requests(), 1);
+    assert_eq!(
+        app.unavailable.reason(crate::features::Feature::Backups),
+        Some("not found")
+    );
+}
+
+#[tokio::test]
+async fn service_command_waits_for_the_api() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let mut app = app_for(&lxd).await;
+    let mut control = ServiceControl::new(
+        Service::Snap,
+        ServiceAction::Restart,
+        "sudo snap restart lxd".to_string(),
+    );
+    control.finished = Some(tokio::time::Instant::now());
+    app.service_control = Some(control);
+
+    app.poll_service().await;
+    assert!(app.service_control.is_none());
+    assert!(app.lxd_status);
+}
+
+#[tokio::test]
+async fn clone_without_snapshots() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    app.lxc_client
+        .create_snapshot("web1", Some("before-upgrade"))
+        .await
+        .unwrap();
+    press(&mut app, KeyCode::Esc).await;
+
+    app.open_clone_options("web1".to_string(), "web2".to_string())
+        .await;
+    let InputMode::CloneOptions(form) = &app.input_mode else {
+        panic!("expected the clone form");
+    };
+    assert_eq!(form.pools, vec!["default", "fast"]);
+    press(&mut app, KeyCode::Char(' ')).await;
+    press(&mut app, KeyCode::Enter).await;
+    finish_operations(&mut app).await;
+
+    assert_eq!(lxd.snapshots("web1"), vec!["before-upgrade"]);
+    assert_eq!(lxd.status("web2").as_deref(), Some("Stopped"));
+    assert!(lxd.snapshots("web2").is_empty());
+}
+
+#[tokio::test]
+async fn clone_into_a_full_pool_is_checked_first() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Stopped", None)
+        .with_disk_usage("web1", 2 << 30);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    let clone_to_fast = |app: &mut App, destination: &str| {
+        app.input_mode = InputMode::CloneOptions(CloneForm::new(
+            "web1".to_string(),
+            destination.to_string(),
+            vec!["default".to_string(), "fast".to_string()],
+            Vec::new(),
+        ));
+        if let InputMode::CloneOptions(form) = &mut app.input_mode {
+            form.options.pool = Some("fast".to_string());
+        }
+    };
+
+    // The fast pool has 3 of its 32 GiB free
+    clone_to_fast(&mut app, "web2");
+    press(&mut app, KeyCode::Enter).await;
+    let InputMode::SpaceWarning { message, .. } = &app.input_mode else {
+        panic!("no warning: {:?}", app.input_mode);
+    };
+    assert!(
+        message.starts_with("Pool 'fast' would be 97% full"),
+        "{}",
+        message
+    );
+    press(&mut app, KeyCode::Char('n')).await;
+    assert!(matches!(app.input_mode, InputMode::Normal));
+    assert_eq!(lxd.status("web2"), None);
+
+    clone_to_fast(&mut app, "web2");
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('y')).await;
+    finish_operations(&mut app).await;
+    assert_eq!(lxd.status("web2").as_deref(), Some("Stopped"));
+
+    app.space.guard = SpaceGuard::Block;
+    clone_to_fast(&mut app, "web3");
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(error_title(&app), Some("Not enough free space"));
+    assert_eq!(lxd.status("web3"), None);
+}
+
+#[tokio::test]
+async fn clone_onto_a_pool_reports_its_transfer() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    let mut form = CloneForm::new(
+        "web1".to_string(),
+        "web2".to_string(),
+        vec!["default".to_string(), "fast".to_string()],
+        Vec::new(),
+    );
+    form.options.pool = Some("default".to_string());
+    app.input_mode = InputMode::CloneOptions(form);
+
+    lxd.hold_operations();
+    press(&mut app, KeyCode::Enter).await;
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    app.poll_background_tasks().await;
+    let operation = app.user_operations.last().unwrap();
+    let transfer = app.operation_transfer(operation).unwrap();
+    assert_eq!(transfer.stage, Stage::FinalSync);
+    assert_eq!(transfer.percent, Some(64));
+    assert_eq!(transfer.rate, Some(25_000_000));
+
+    lxd.release_operations();
+    finish_operations(&mut app).await;
+    let operation = app.user_operations.last().unwrap();
+    assert_eq!(app.operation_transfer(operation), None);
+}
+
+#[tokio::test]
+async fn boot_order_edits_autostart_settings() {
+    let lxd = FakeLxd::start()
+        .with_instance("db", "Stopped", None)
+        .with_instance("web", "Running", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('B')).await;
+    assert!(matches!(app.input_mode, InputMode::BootOrder));
+    let order = app.boot_order.as_ref().unwrap();
+    assert_eq!(order.selected_entry().unwrap().name, "web");
+
+    // Give db the highest priority so it starts first
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('a')).await;
+    press(&mut app, KeyCode::Char('p')).await;
+    press(&mut app, KeyCode::Backspace).await;
+    for c in "10".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(app.input_mode, InputMode::BootOrder));
+    assert_eq!(lxd.config("db", "boot.autostart").as_deref(), Some("true"));
+    assert_eq!(
+        lxd.config("db", "boot.autostart.priority").as_deref(),
+        Some("10")
+    );
+    let order = app.boot_order.as_ref().unwrap();
+    assert_eq!(order.entries[0].name, "db");
+    assert_eq!(order.selected_entry().unwrap().name, "db");
+
+    // A delay that isn't a number is refused before anything is sent
+    press(&mut app, KeyCode::Char('d')).await;
+    press(&mut app, KeyCode::Char('-')).await;
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(
+        error_title(&app),
+        Some("Invalid value for boot.autostart.delay")
+    );
+    assert_eq!(lxd.config("db", "boot.autostart.delay"), None);
+}
+
+#[tokio::test]
+async fn audit_rebuilds_instances_on_outdated_images() {
+    let noble = [("os", "Ubuntu"), ("release", "noble")];
+    let lxd = FakeLxd::start()
+        .with_image("old0noble", "2024-04-01T00:00:00Z", &noble)
+        .with_image("new1noble", "2024-06-01T00:00:00Z", &noble)
+        .with_instance("web1", "Stopped", None)
+        .with_instance("web2", "Running", None)
+        .with_instance("db1", "Stopped", None);
+    let lxd = ["web1", "web2", "db1"].iter().fold(lxd, |lxd, name| {
+        let base = if *name == "db1" {
+            "new1noble"
+        } else {
+            "old0noble"
+        };
+        lxd.with_config(name, "volatile.base_image", base)
+            .with_config(name, "image.os", "Ubuntu")
+            .with_config(name, "image.release", "noble")
+    });
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('I')).await;
+    let audit = app.audit.as_ref().unwrap();
+    let names: Vec<&str> = audit.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["web1", "web2"]);
+    assert_eq!(audit.current, 1);
+
+    // Running instances have to be stopped first
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('b')).await;
+    assert!(matches!(
+        app.input_mode,
+        InputMode::Audit {
+            confirm_rebuild: false
+        }
+    ));
+    assert_eq!(
+        app.audit.as_ref().unwrap().status.as_deref(),
+        Some("Stop 'web2' before rebuilding it")
+    );
+
+    // Anything but y keeps the old image
+    press(&mut app, KeyCode::Char('k')).await;
+    press(&mut app, KeyCode::Char('b')).await;
+    press(&mut app, KeyCode::Char('n')).await;
+    assert!(!lxd.requests().iter().any(|r| r.contains("rebuild")));
+
+    press(&mut app, KeyCode::Char('b')).await;
+    press(&mut app, KeyCode::Char('y')).await;
+    finish_operations(&mut app).await;
+    assert_eq!(
+        lxd.config("web1", "volatile.base_image").as_deref(),
+        Some("new1noble")
+    );
+    assert!(matches!(app.input_mode, InputMode::Audit { .. }));
+    let audit = app.audit.as_ref().unwrap();
+    assert_eq!(audit.selected_entry().unwrap().name, "web2");
+    assert_eq!(audit.current, 2);
+    assert_eq!(
+        audit.status.as_deref(),
+        Some("Rebuilt 'web1' from image new1noble")
+    );
+}
+
+#[tokio::test]
+async fn image_store_toggles_auto_update_and_refreshes_images() {
+    let lxd = FakeLxd::start()
+        .with_remote_image("old0debian", "debian/12", false, Some("new1debian"))
+        .with_image("0123456789abcdef", "2024-04-01T00:00:00Z", &[]);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('U')).await;
+    let view = app.image_store.as_ref().unwrap();
+    assert_eq!(view.images.len(), 2);
+    assert_eq!(
+        view.selected_image().unwrap().source.as_deref(),
+        Some("images:debian/12")
+    );
+
+    press(&mut app, KeyCode::Char('a')).await;
+    assert_eq!(lxd.images()[0]["auto_update"], true);
+    let view = app.image_store.as_ref().unwrap();
+    assert!(view.selected_image().unwrap().auto_update);
+    assert_eq!(
+        view.status.as_deref(),
+        Some("Auto-update on for 'debian/12'")
+    );
+
+    // The refresh reports how far the download has got
+    lxd.hold_operations();
+    press(&mut app, KeyCode::Char('u')).await;
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    app.poll_background_tasks().await;
+    assert_eq!(
+        app.lxd_operations.values().next().unwrap().progress,
+        Some(42)
+    );
+    lxd.release_operations();
+    finish_operations(&mut app).await;
+    assert!(matches!(app.input_mode, InputMode::ImageStore));
+    let view = app.image_store.as_ref().unwrap();
+    assert_eq!(view.selected_image().unwrap().fingerprint, "new1debian");
+    assert_eq!(view.status.as_deref(), Some("Refreshed image 'debian/12'"));
+
+    // A locally published image has nowhere to update from
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('u')).await;
+    assert_eq!(
+        app.image_store.as_ref().unwrap().status.as_deref(),
+        Some("'0123456789ab' wasn't copied from a remote, so there is nothing to update it from")
+    );
+    assert_eq!(
+        lxd.requests()
+            .iter()
+            .filter(|r| r.ends_with("/refresh"))
+            .count(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn network_zones_are_listed_and_edited() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", Some("10.0.0.10"))
+        .with_device("web1", "eth0", &[("type", "nic"), ("network", "lxdbr0")])
+        .with_network("lxdbr0", &[("dns.zone.forward", "lxd.example.net")])
+        .with_zone_record("lxd.example.net", "www", "10.0.0.10")
+        .with_zone_record_config("lxd.example.net", "www", "user.owner", "web-team");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+    let type_text = |text: &'static str| text.chars().map(KeyCode::Char);
+
+    press(&mut app, KeyCode::Char('Z')).await;
+    let view = app.zones.as_ref().unwrap();
+    assert_eq!(view.zones[0].name, "lxd.example.net");
+    assert_eq!(view.zones[0].networks, ["lxdbr0"]);
+
+    // Add a record to the selected zone
+    press(&mut app, KeyCode::Char('n')).await;
+    for key in type_text("api A 10.0.0.20, AAAA fd42::20") {
+        press(&mut app, key).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert!(matches!(app.input_mode, InputMode::Zones { .. }));
+    let api = lxd.zone_record("lxd.example.net", "api").unwrap();
+    assert_eq!(api["entries"][1]["value"], "fd42::20");
+    assert_eq!(
+        app.zones.as_ref().unwrap().status.as_deref(),
+        Some("Created record 'api.lxd.example.net'")
+    );
+
+    // Edit www's entries, which the prompt starts from
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('e')).await;
+    assert_eq!(app.input_buffer.as_str(), "A 10.0.0.10");
+    for key in type_text(", TXT hello") {
+        press(&mut app, key).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    let www = lxd.zone_record("lxd.example.net", "www").unwrap();
+    assert_eq!(www["entries"][1]["type"], "TXT");
+    assert_eq!(www["config"]["user.owner"], "web-team");
+
+    // A mistyped record is refused before anything is sent
+    press(&mut app, KeyCode::Char('n')).await;
+    for key in type_text("mail MX") {
+        press(&mut app, key).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(error_title(&app), Some("Invalid record"));
+    assert!(lxd.zone_record("lxd.example.net", "mail").is_none());
+    press(&mut app, KeyCode::Esc).await;
+
+    // Deleting asks first; anything but y keeps the record
+    press(&mut app, KeyCode::Char('Z')).await;
+    press(&mut app, KeyCode::Char('k')).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    press(&mut app, KeyCode::Char('n')).await;
+    assert!(lxd.zone_record("lxd.example.net", "api").is_some());
+    press(&mut app, KeyCode::Char('d')).await;
+    press(&mut app, KeyCode::Char('y')).await;
+    assert!(lxd.zone_record("lxd.example.net", "api").is_none());
+    assert!(lxd.zone_record("lxd.example.net", "www").is_some());
+
+    press(&mut app, KeyCode::Char('a')).await;
+    for key in type_text("0.0.10.in-addr.arpa") {
+        press(&mut app, key).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    assert_eq!(lxd.zones(), ["0.0.10.in-addr.arpa", "lxd.example.net"]);
+    assert_eq!(app.zones.as_ref().unwrap().zones.len(), 2);
+
+    // The watch view shows the names web1 resolves as
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Char('w')).await;
+    assert_eq!(
+        app.watch.as_ref().unwrap().dns_names,
+        ["web1.lxd", "web1.lxd.example.net"]
+    );
+}
+
+#[tokio::test]
+async fn edit_templates_and_metadata() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Enter).await;
+    press(&mut app, KeyCode::Char('m')).await;
+    assert!(matches!(app.input_mode, InputMode::Templates));
+    let view = app.templates.as_ref().unwrap();
+    assert_eq!(view.files.len(), 1);
+    assert_eq!(view.files[0].targets[0].0, "/etc/hostname");
+
+    // The template opens with its content and is saved back as is
+    press(&mut app, KeyCode::Enter).await;
+    app.external_command.take().unwrap();
+    let path = app.config_edit.as_ref().unwrap().path.clone();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "{{ instance.name }}\n"
+    );
+    std::fs::write(&path, "{{ instance.name }}.example.com\n").unwrap();
+    app.finish_config_edit().await;
+    assert!(matches!(app.input_mode, InputMode::Templates));
+    assert_eq!(
+        lxd.template("web1", "hostname.tpl").as_deref(),
+        Some("{{ instance.name }}.example.com\n")
+    );
+    assert_eq!(
+        app.templates.as_ref().unwrap().status.as_deref(),
+        Some("Saved hostname.tpl")
+    );
+
+    // A new motd template rendered on start
+    press(&mut app, KeyCode::Char('m')).await;
+    app.external_command.take().unwrap();
+    let path = app.config_edit.as_ref().unwrap().path.clone();
+    let edited = std::fs::read_to_string(&path).unwrap().replace(
+        "templates:\n",
+        "templates:\n  /etc/motd:\n    when: [start]\n    template: motd.tpl\n",
+    );
+    std::fs::write(&path, edited).unwrap();
+    app.finish_config_edit().await;
+    let metadata = lxd.metadata("web1").unwrap();
+    assert_eq!(metadata["templates"]["/etc/motd"]["template"], "motd.tpl");
+
+    let view = app.templates.as_ref().unwrap();
+    let motd = view.files.iter().find(|f| f.name == "motd.tpl").unwrap();
+    assert!(!motd.exists);
+}
+
+#[tokio::test]
+async fn published_ports_are_listed_and_watched() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", Some("10.0.0.10"))
+        .with_instance("db1", "Running", Some("10.0.0.11"))
+        .with_device(
+            "web1",
+            "http",
+            &[
+                ("type", "proxy"),
+                ("listen", "tcp:0.0.0.0:8080"),
+                ("connect", "tcp:127.0.0.1:80"),
+            ],
+        )
+        .with_network("lxdbr0", &[])
+        .with_forward("lxdbr0", "192.0.2.1", "443", ("10.0.0.10", "8443"));
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    let ports = |name: &str| {
+        let containers = app.containers.try_read().unwrap();
+        let container = containers.iter().find(|c| c.name == name).unwrap();
+        ports::summary(&container.ports)
+    };
+    assert_eq!(ports("web1"), "8080→80, 192.0.2.1:443→8443");
+    assert_eq!(ports("db1"), "");
+
+    assert!(app.select_container("web1").await);
+    press(&mut app, KeyCode::Char('w')).await;
+    let watch = app.watch.as_ref().unwrap();
+    assert_eq!(watch.ports.len(), 2);
+}
+
+#[tokio::test]
+async fn capacity_sums_limits_against_the_host() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", Some("10.0.0.10"))
+        .with_config("web1", "limits.cpu", "2")
+        .with_config("web1", "limits.memory", "25%")
+        .with_instance("db1", "Stopped", None)
+        .with_config("db1", "limits.memory", "14GiB");
+    let mut app = app_for(&lxd).await;
+    press(&mut app, KeyCode::Esc).await;
+
+    press(&mut app, KeyCode::Char('H')).await;
+    assert!(matches!(app.input_mode, InputMode::Capacity));
+    let view = app.capacity.as_ref().unwrap();
+    assert_eq!(view.host.cpus, 8);
+    let (running, all) = (view.running(), view.all());
+    assert_eq!((running.cpus, running.memory), (2, 4 << 30));
+    assert!(!running.overcommits(view.host));
+    // Starting db1 as well would need more memory than the host has
+    assert_eq!(all.memory, 18 << 30);
+    assert_eq!(all.unlimited, 1);
+    assert!(all.overcommits(view.host));
+
+    press(&mut app, KeyCode::Char('r')).await;
+    assert_eq!(app.capacity.as_ref().unwrap().reservations[0].name, "db1");
+}
@@ -0,0 +1,172 @@
+//! Base-image audit
+//!
+//! Lists instances whose `volatile.base_image` is older than the newest
+//! stored image with the same os, release, architecture and variant.
+
+use crate::lxc;
+use crate::lxd_api::LxdImage;
+use chrono::{DateTime, FixedOffset};
+use std::collections::HashMap;
+
+pub const BASE_IMAGE_KEY: &str = "volatile.base_image";
+
+/// Image properties that stay the same across versions of an image. An
+/// instance needs at least the os and release to be matched.
+const IDENTITY: [&str; 4] = ["os", "release", "architecture", "variant"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Freshness {
+    Current,
+    Outdated {
+        fingerprint: String, // Of the latest image
+        created_at: String,
+    },
+    Unknown, // No image in the store is the same os and release
+}
+
+/// One instance's base image and how it compares with the latest
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub name: String,
+    pub running: bool,
+    pub image: String,      // Description, or the start of the fingerprint
+    pub base_image: String, // Fingerprint it was created from
+    pub freshness: Freshness,
+}
+
+impl AuditEntry {
+    /// None for instances without a recorded base image
+    pub fn from_config(
+        name: &str,
+        running: bool,
+        config: &HashMap<String, String>,
+        images: &[LxdImage],
+    ) -> Option<Self> {
+        let base_image = config
+            .get(BASE_IMAGE_KEY)
+            .map(|fingerprint| fingerprint.trim())
+            .filter(|fingerprint| !fingerprint.is_empty())?;
+        let freshness = match latest(config, images) {
+            None => Freshness::Unknown,
+            Some(latest) if latest.fingerprint == base_image || !newer(latest, config) => {
+                Freshness::Current
+            }
+            Some(latest) => Freshness::Outdated {
+                fingerprint: latest.fingerprint.clone(),
+                created_at: latest.created_at.clone(),
+            },
+        };
+        Some(AuditEntry {
+            name: name.to_string(),
+            running,
+            image: lxc::image_description(config)
+                .unwrap_or_else(|| base_image.chars().take(12).collect()),
+            base_image: base_image.to_string(),
+            freshness,
+        })
+    }
+}
+
+/// The newest image in the store with the instance's image properties
+fn latest<'a>(config: &HashMap<String, String>, images: &'a [LxdImage]) -> Option<&'a LxdImage> {
+    let value = |property: &str| {
+        config
+            .get(&format!("image.{}", property))
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+    };
+    value("os").and(value("release"))?;
+    let wanted: Vec<(&str, &str)> = IDENTITY
+        .iter()
+        .filter_map(|property| Some((*property, value(property)?)))
+        .collect();
+
+    images
+        .iter()
+        .filter(|image| {
+            wanted.iter().all(|(property, value)| {
+                image
+                    .properties
+                    .get(*property)
+                    .is_some_and(|v| v.trim().eq_ignore_ascii_case(value))
+            })
+        })
+        .max_by_key(|image| created(image))
+}
+
+/// Whether `latest` is a later build than the instance's image. Serials
+/// settle it when both have one, as a re-uploaded old build is newer by
+/// date only.
+fn newer(latest: &LxdImage, config: &HashMap<String, String>) -> bool {
+    match (latest.properties.get("serial"), config.get("image.serial")) {
+        (Some(latest), Some(current)) => latest.trim() > current.trim(),
+        _ => true,
+    }
+}
+
+fn created(image: &LxdImage) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(&image.created_at).ok()
+}
+
+/// The audit view: instances on outdated images, by name
+#[derive(Debug)]
+pub struct Audit {
+    pub entries: Vec<AuditEntry>,
+    pub current: usize, // Instances on the latest image
+    pub unknown: usize, // Instances whose image isn't in the store
+    pub selected: usize,
+    pub error: Option<String>,  // Why the last load failed
+    pub status: Option<String>, // Outcome of the last rebuild
+}
+
+impl Audit {
+    pub fn new() -> Self {
+        Audit {
+            entries: Vec::new(),
+            current: 0,
+            unknown: 0,
+            selected: 0,
+            error: None,
+            status: None,
+        }
+    }
+
+    /// Take in a fresh audit of every instance, keeping the selected one
+    pub fn set_entries(&mut self, entries: Vec<AuditEntry>) {
+        let selected = self.selected_entry().map(|e| e.name.clone());
+        self.current = 0;
+        self.unknown = 0;
+        self.entries.clear();
+        for entry in entries {
+            match entry.freshness {
+                Freshness::Current => self.current += 1,
+                Freshness::Unknown => self.unknown += 1,
+                Freshness::Outdated { .. } => self.entries.push(entry),
+            }
+        }
+        self.entries.sort_by(|a, b| a.name.cmp(&b.name));
+        self.error = None;
+        self.selected = selected
+            .and_then(|name| self.entries.iter().position(|e| e.name == name))
+            .unwrap_or(0)
+            .min(self.entries.len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.entries.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.entries.len()
+        } else {
+            (self.selected + self.entries.len() - 1) % self.entries.len()
+        };
+    }
+
+    pub fn selected_entry(&self) -> Option<&AuditEntry> {
+        self.entries.get(self.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests;
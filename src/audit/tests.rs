@@ -0,0 +1,179 @@
+//! Tests of finding instances on outdated base images
+
+use super::{Audit, AuditEntry, Freshness};
+use crate::lxd_api::LxdImage;
+use std::collections::HashMap;
+
+fn pairs(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn image(fingerprint: &str, created_at: &str, properties: &[(&str, &str)]) -> LxdImage {
+    LxdImage {
+        fingerprint: fingerprint.to_string(),
+        properties: pairs(properties),
+        created_at: created_at.to_string(),
+        ..LxdImage::default()
+    }
+}
+
+fn store() -> Vec<LxdImage> {
+    let noble = [
+        ("os", "Ubuntu"),
+        ("release", "noble"),
+        ("architecture", "amd64"),
+    ];
+    vec![
+        image("aaa111", "2024-04-01T00:00:00Z", &noble),
+        image("bbb222", "2024-06-01T00:00:00Z", &noble),
+        image(
+            "ccc333",
+            "2024-09-01T00:00:00Z",
+            &[
+                ("os", "Ubuntu"),
+                ("release", "noble"),
+                ("architecture", "arm64"),
+            ],
+        ),
+        image(
+            "ddd444",
+            "2024-07-01T00:00:00Z",
+            &[("os", "Debian"), ("release", "bookworm")],
+        ),
+    ]
+}
+
+fn audit(name: &str, config: &[(&str, &str)]) -> Option<AuditEntry> {
+    AuditEntry::from_config(name, false, &pairs(config), &store())
+}
+
+#[test]
+fn finds_the_latest_image_with_the_same_properties() {
+    let web = audit(
+        "web1",
+        &[
+            ("volatile.base_image", "aaa111"),
+            ("image.os", "ubuntu"),
+            ("image.release", "noble"),
+            ("image.architecture", "amd64"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(web.image, "ubuntu noble");
+    assert_eq!(
+        web.freshness,
+        Freshness::Outdated {
+            fingerprint: "bbb222".to_string(),
+            created_at: "2024-06-01T00:00:00Z".to_string(),
+        }
+    );
+
+    let db = audit(
+        "db1",
+        &[
+            ("volatile.base_image", "ddd444"),
+            ("image.os", "Debian"),
+            ("image.release", "bookworm"),
+            ("image.description", "Debian bookworm amd64"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(db.image, "Debian bookworm amd64");
+    assert_eq!(db.freshness, Freshness::Current);
+}
+
+#[test]
+fn unknown_without_a_matching_image() {
+    // Not in the store
+    let alpine = audit(
+        "cache",
+        &[
+            ("volatile.base_image", "eee555"),
+            ("image.os", "Alpine"),
+            ("image.release", "3.20"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(alpine.freshness, Freshness::Unknown);
+    assert_eq!(alpine.image, "Alpine 3.20");
+
+    // No release to match on
+    let bare = audit(
+        "bare",
+        &[("volatile.base_image", "aaa111"), ("image.os", "Ubuntu")],
+    )
+    .unwrap();
+    assert_eq!(bare.freshness, Freshness::Unknown);
+
+    // No base image at all, as for instances copied from elsewhere
+    assert_eq!(audit("empty", &[("image.os", "Ubuntu")]), None);
+}
+
+#[test]
+fn serials_decide_when_both_have_one() {
+    let images = vec![image(
+        "fff666",
+        "2024-10-01T00:00:00Z",
+        &[
+            ("os", "Ubuntu"),
+            ("release", "noble"),
+            ("serial", "20240401"),
+        ],
+    )];
+    let entry = |serial: &str| {
+        AuditEntry::from_config(
+            "web1",
+            true,
+            &pairs(&[
+                ("volatile.base_image", "aaa111"),
+                ("image.os", "Ubuntu"),
+                ("image.release", "noble"),
+                ("image.serial", serial),
+            ]),
+            &images,
+        )
+        .unwrap()
+        .freshness
+    };
+    assert_eq!(entry("20240401"), Freshness::Current);
+    assert!(matches!(entry("20240301"), Freshness::Outdated { .. }));
+}
+
+#[test]
+fn lists_only_outdated_instances() {
+    let outdated = |name: &str| AuditEntry {
+        name: name.to_string(),
+        running: false,
+        image: "Ubuntu noble".to_string(),
+        base_image: "aaa111".to_string(),
+        freshness: Freshness::Outdated {
+            fingerprint: "bbb222".to_string(),
+            created_at: String::new(),
+        },
+    };
+    let with = |name: &str, freshness: Freshness| AuditEntry {
+        freshness,
+        ..outdated(name)
+    };
+
+    let mut view = Audit::new();
+    view.set_entries(vec![
+        outdated("web2"),
+        with("db1", Freshness::Current),
+        outdated("web1"),
+        with("cache", Freshness::Unknown),
+    ]);
+    let names: Vec<&str> = view.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["web1", "web2"]);
+    assert_eq!((view.current, view.unknown), (1, 1));
+
+    // The selection follows the instance across reloads
+    view.select(true);
+    view.set_entries(vec![outdated("web2"), outdated("app")]);
+    assert_eq!(view.selected_entry().unwrap().name, "web2");
+    view.set_entries(vec![with("web2", Freshness::Current)]);
+    assert_eq!(view.selected_entry(), None);
+}
@@ -0,0 +1,491 @@
+//! Remote authentication
+//!
+//! Client certificate management, trust token exchange and OIDC device-code
+//! login for HTTPS remotes. Credentials are persisted under the config
+//! directory using the same layout as the `lxc` client, except that the
+//! client key and OIDC tokens go in the OS keyring when there is one.
+
+use crate::config::{write_private, Config};
+use crate::secrets;
+use openssl::asn1::Asn1Time;
+use openssl::bn::{BigNum, MsbOption};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+use openssl::x509::extension::ExtendedKeyUsage;
+use openssl::x509::{X509NameBuilder, X509};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use url::Url;
+
+/// Default port of the LXD HTTPS API
+const DEFAULT_PORT: u16 = 8443;
+
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("TLS error: {0}")]
+    TlsError(String),
+    #[error("HTTP error: {0}")]
+    HttpError(#[from] reqwest::Error),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Invalid remote URL: {0}")]
+    InvalidUrl(String),
+    #[error("Invalid trust token: {0}")]
+    InvalidToken(String),
+    #[error("Server fingerprint mismatch: token expects {expected}, server presented {actual}")]
+    FingerprintMismatch { expected: String, actual: String },
+    #[error("OIDC error: {0}")]
+    OidcError(String),
+}
+
+impl From<openssl::error::ErrorStack> for AuthError {
+    fn from(err: openssl::error::ErrorStack) -> Self {
+        AuthError::TlsError(err.to_string())
+    }
+}
+
+/// Parse user input into an HTTPS URL, defaulting the scheme and LXD port
+pub fn normalize_remote_url(input: &str) -> Result<Url, AuthError> {
+    let input = input.trim().trim_end_matches('/');
+    let with_scheme = if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("https://{}", input)
+    };
+
+    let mut url = Url::parse(&with_scheme).map_err(|e| AuthError::InvalidUrl(e.to_string()))?;
+    if url.scheme() != "https" {
+        return Err(AuthError::InvalidUrl(format!(
+            "unsupported scheme '{}', expected https",
+            url.scheme()
+        )));
+    }
+    if url.host_str().is_none() {
+        return Err(AuthError::InvalidUrl("missing host".to_string()));
+    }
+    if url.port().is_none() {
+        let _ = url.set_port(Some(DEFAULT_PORT));
+    }
+    Ok(url)
+}
+
+// ============== Certificates ==============
+
+#[derive(Debug, Clone)]
+pub struct ClientCertificate {
+    pub cert_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+}
+
+fn client_cert_path() -> PathBuf {
+    Config::config_dir().join("client.crt")
+}
+
+/// Keyring account of the client key
+const CLIENT_KEY_ACCOUNT: &str = "client.key";
+
+fn client_key_path() -> PathBuf {
+    Config::config_dir().join("client.key")
+}
+
+pub fn server_cert_path(remote: &str) -> PathBuf {
+    Config::config_dir()
+        .join("servercerts")
+        .join(format!("{}.crt", remote))
+}
+
+/// Load the client certificate, generating a new one on first use
+pub fn load_or_generate_client_cert() -> Result<ClientCertificate, AuthError> {
+    if let (Ok(cert_pem), Ok(key_pem)) = (
+        fs::read(client_cert_path()),
+        secrets::load(CLIENT_KEY_ACCOUNT, &client_key_path()),
+    ) {
+        return Ok(ClientCertificate { cert_pem, key_pem });
+    }
+
+    let cert = generate_client_cert()?;
+    write_private(&client_cert_path(), &cert.cert_pem)?;
+    secrets::save(CLIENT_KEY_ACCOUNT, &client_key_path(), &cert.key_pem)?;
+    Ok(cert)
+}
+
+fn generate_client_cert() -> Result<ClientCertificate, AuthError> {
+    // Same key type and subject layout as the lxc client
+    let group = EcGroup::from_curve_name(Nid::SECP384R1)?;
+    let key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_nid(Nid::ORGANIZATIONNAME, "linuxcontainers.org")?;
+    name.append_entry_by_nid(Nid::COMMONNAME, "lxtui")?;
+    let name = name.build();
+
+    let serial = {
+        let mut bn = BigNum::new()?;
+        bn.rand(128, MsbOption::MAYBE_ZERO, false)?;
+        bn.to_asn1_integer()?
+    };
+
+    let mut builder = X509::builder()?;
+    builder.set_version(2)?;
+    builder.set_serial_number(&serial)?;
+    builder.set_subject_name(&name)?;
+    builder.set_issuer_name(&name)?;
+    builder.set_pubkey(&key)?;
+    let not_before = Asn1Time::days_from_now(0)?;
+    let not_after = Asn1Time::days_from_now(3650)?;
+    builder.set_not_before(&not_before)?;
+    builder.set_not_after(&not_after)?;
+    builder.append_extension(ExtendedKeyUsage::new().client_auth().build()?)?;
+    builder.sign(&key, MessageDigest::sha384())?;
+
+    Ok(ClientCertificate {
+        cert_pem: builder.build().to_pem()?,
+        key_pem: key.private_key_to_pem_pkcs8()?,
+    })
+}
+
+/// Connect to the remote and return the certificate it presents (PEM).
+///
+/// This is blocking and does no verification; the caller is expected to
+/// pin the result after checking its fingerprint.
+pub fn fetch_server_certificate(url: &Url) -> Result<Vec<u8>, AuthError> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| AuthError::InvalidUrl("missing host".to_string()))?;
+    let port = url.port().unwrap_or(DEFAULT_PORT);
+
+    let mut builder = SslConnector::builder(SslMethod::tls_client())?;
+    builder.set_verify(SslVerifyMode::NONE);
+    let connector = builder.build();
+
+    let stream = TcpStream::connect((host, port))?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(10)))?;
+
+    let tls = connector
+        .configure()?
+        .verify_hostname(false)
+        .connect(host, stream)
+        .map_err(|e| AuthError::TlsError(e.to_string()))?;
+
+    let cert = tls
+        .ssl()
+        .peer_certificate()
+        .ok_or_else(|| AuthError::TlsError("server presented no certificate".to_string()))?;
+    Ok(cert.to_pem()?)
+}
+
+/// SHA-256 fingerprint of a PEM certificate, as shown by `lxc remote add`
+pub fn fingerprint(cert_pem: &[u8]) -> Result<String, AuthError> {
+    let cert = X509::from_pem(cert_pem)?;
+    let digest = cert.digest(MessageDigest::sha256())?;
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+pub fn load_server_cert(remote: &str) -> Result<Vec<u8>, AuthError> {
+    Ok(fs::read(server_cert_path(remote))?)
+}
+
+pub fn save_server_cert(remote: &str, cert_pem: &[u8]) -> Result<(), AuthError> {
+    write_private(&server_cert_path(remote), cert_pem)?;
+    Ok(())
+}
+
+// ============== Trust tokens ==============
+
+/// Decoded contents of a token from `lxc config trust add`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrustToken {
+    pub fingerprint: String,
+    pub secret: String,
+}
+
+impl TrustToken {
+    pub fn decode(token: &str) -> Result<Self, AuthError> {
+        let raw = openssl::base64::decode_block(token.trim())
+            .map_err(|_| AuthError::InvalidToken("not valid base64".to_string()))?;
+        let token: Self =
+            serde_json::from_slice(&raw).map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+        if token.secret.is_empty() {
+            return Err(AuthError::InvalidToken("missing secret".to_string()));
+        }
+        Ok(token)
+    }
+
+    /// Ensure the token was issued by the server we're talking to
+    pub fn verify_server(&self, server_fingerprint: &str) -> Result<(), AuthError> {
+        if self.fingerprint.eq_ignore_ascii_case(server_fingerprint) {
+            Ok(())
+        } else {
+            Err(AuthError::FingerprintMismatch {
+                expected: self.fingerprint.clone(),
+                actual: server_fingerprint.to_string(),
+            })
+        }
+    }
+}
+
+// ============== OIDC ==============
+
+/// Identity provider advertised by the server in its `X-LXD-OIDC-*` headers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcProvider {
+    pub issuer: String,
+    pub client_id: String,
+    #[serde(default)]
+    pub audience: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcTokens {
+    pub provider: OidcProvider,
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Expiry as seconds since the Unix epoch
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+impl OidcTokens {
+    /// Whether the access token expires within the next 30 seconds
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|expires_at| unix_now() + 30 >= expires_at)
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub interval: u64,
+    pub expires_in: u64,
+    pub token_endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscoveryDocument {
+    device_authorization_endpoint: Option<String>,
+    token_endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorizationResponse {
+    device_code: String,
+    user_code: String,
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    #[serde(default)]
+    interval: Option<u64>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    #[serde(default)]
+    access_token: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn oidc_tokens_path(remote: &str) -> PathBuf {
+    Config::config_dir()
+        .join("oidctokens")
+        .join(format!("{}.json", remote))
+}
+
+/// Keyring account of a remote's OIDC tokens
+fn oidc_account(remote: &str) -> String {
+    format!("oidc/{}", remote)
+}
+
+pub fn load_oidc_tokens(remote: &str) -> Result<OidcTokens, AuthError> {
+    let raw = secrets::load(&oidc_account(remote), &oidc_tokens_path(remote))?;
+    Ok(serde_json::from_slice(&raw)?)
+}
+
+pub fn save_oidc_tokens(remote: &str, tokens: &OidcTokens) -> Result<(), AuthError> {
+    let raw = serde_json::to_vec_pretty(tokens)?;
+    secrets::save(&oidc_account(remote), &oidc_tokens_path(remote), &raw)?;
+    Ok(())
+}
+
+async fn discover(
+    http: &reqwest::Client,
+    provider: &OidcProvider,
+) -> Result<DiscoveryDocument, AuthError> {
+    let url = format!(
+        "{}/.well-known/openid-configuration",
+        provider.issuer.trim_end_matches('/')
+    );
+    Ok(http
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Start a device-code login; the user must visit the returned URL
+pub async fn start_device_login(
+    http: &reqwest::Client,
+    provider: &OidcProvider,
+) -> Result<DeviceAuthorization, AuthError> {
+    let discovery = discover(http, provider).await?;
+    let endpoint = discovery.device_authorization_endpoint.ok_or_else(|| {
+        AuthError::OidcError("identity provider does not support device login".to_string())
+    })?;
+
+    let mut form = vec![
+        ("client_id", provider.client_id.clone()),
+        ("scope", "openid offline_access".to_string()),
+    ];
+    if let Some(audience) = &provider.audience {
+        form.push(("audience", audience.clone()));
+    }
+
+    let response: DeviceAuthorizationResponse = http
+        .post(endpoint)
+        .form(&form)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(DeviceAuthorization {
+        device_code: response.device_code,
+        user_code: response.user_code,
+        verification_uri: response.verification_uri,
+        verification_uri_complete: response.verification_uri_complete,
+        interval: response.interval.unwrap_or(5),
+        expires_in: response.expires_in.unwrap_or(600),
+        token_endpoint: discovery.token_endpoint,
+    })
+}
+
+/// Poll the token endpoint until the user completes the device login
+pub async fn poll_device_token(
+    http: &reqwest::Client,
+    provider: &OidcProvider,
+    device: &DeviceAuthorization,
+) -> Result<OidcTokens, AuthError> {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(device.expires_in);
+    let mut interval = Duration::from_secs(device.interval.max(1));
+
+    loop {
+        if tokio::time::Instant::now() > deadline {
+            return Err(AuthError::OidcError("device login expired".to_string()));
+        }
+        tokio::time::sleep(interval).await;
+
+        let form = [
+            ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+            ("device_code", device.device_code.as_str()),
+            ("client_id", provider.client_id.as_str()),
+        ];
+        let response: TokenResponse = http
+            .post(&device.token_endpoint)
+            .form(&form)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        match response.error.as_deref() {
+            None => return tokens_from_response(provider, response, None),
+            Some("authorization_pending") => {}
+            Some("slow_down") => interval += Duration::from_secs(5),
+            Some(error) => {
+                return Err(AuthError::OidcError(
+                    response
+                        .error_description
+                        .unwrap_or_else(|| error.to_string()),
+                ))
+            }
+        }
+    }
+}
+
+/// Exchange a refresh token for a new access token
+pub async fn refresh_tokens(
+    http: &reqwest::Client,
+    tokens: &OidcTokens,
+) -> Result<OidcTokens, AuthError> {
+    let refresh_token = tokens
+        .refresh_token
+        .as_deref()
+        .ok_or_else(|| AuthError::OidcError("session expired, log in again".to_string()))?;
+    let discovery = discover(http, &tokens.provider).await?;
+
+    let form = [
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", tokens.provider.client_id.as_str()),
+    ];
+    let response: TokenResponse = http
+        .post(&discovery.token_endpoint)
+        .form(&form)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(error) = response.error {
+        return Err(AuthError::OidcError(
+            response.error_description.unwrap_or(error),
+        ));
+    }
+    tokens_from_response(&tokens.provider, response, Some(refresh_token))
+}
+
+fn tokens_from_response(
+    provider: &OidcProvider,
+    response: TokenResponse,
+    previous_refresh_token: Option<&str>,
+) -> Result<OidcTokens, AuthError> {
+    let access_token = response
+        .access_token
+        .ok_or_else(|| AuthError::OidcError("no access token in response".to_string()))?;
+
+    Ok(OidcTokens {
+        provider: provider.clone(),
+        access_token,
+        // Providers may omit the refresh token when it doesn't rotate
+        refresh_token: response
+            .refresh_token
+            .or_else(|| previous_refresh_token.map(str::to_string)),
+        expires_at: response.expires_in.map(|secs| unix_now() + secs),
+    })
+}
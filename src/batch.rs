@@ -0,0 +1,165 @@
+//! Batch profile changes
+//!
+//! Adds a profile to, or takes it off, every picked instance, patching each
+//! on its own so one failure doesn't stop the rest.
+
+/// Adding or removing one profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileChange {
+    Add(String),
+    Remove(String),
+}
+
+impl ProfileChange {
+    pub fn profile(&self) -> &str {
+        match self {
+            ProfileChange::Add(profile) | ProfileChange::Remove(profile) => profile,
+        }
+    }
+
+    /// The profile list with the change made; None when it changes nothing
+    pub fn apply(&self, profiles: &[String]) -> Option<Vec<String>> {
+        let has = profiles.iter().any(|p| p == self.profile());
+        match self {
+            ProfileChange::Add(profile) if !has => {
+                let mut profiles = profiles.to_vec();
+                profiles.push(profile.clone());
+                Some(profiles)
+            }
+            ProfileChange::Remove(profile) if has => {
+                Some(profiles.iter().filter(|p| *p != profile).cloned().collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// What it does to `count` instances, for operations
+    pub fn describe(&self, count: usize) -> String {
+        match self {
+            ProfileChange::Add(profile) => {
+                format!("Add profile '{}' to {} instances", profile, count)
+            }
+            ProfileChange::Remove(profile) => {
+                format!("Remove profile '{}' from {} instances", profile, count)
+            }
+        }
+    }
+}
+
+/// How the change went for one instance
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Changed,
+    Unchanged, // It already had the profile, or never had it
+    Failed(String),
+}
+
+/// A picked instance and its profiles
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    pub name: String,                          // qualified name
+    pub profiles: Result<Vec<String>, String>, // Or why they couldn't be read
+    pub outcome: Option<Outcome>,              // Of the last change
+}
+
+/// The profile view: the active server's profiles, and the picked
+/// instances they are added to or removed from
+#[derive(Debug)]
+pub struct ProfileBatch {
+    pub profiles: Vec<String>,
+    pub targets: Vec<Target>,
+    pub selected: usize,        // Profile
+    pub status: Option<String>, // What the last change did
+    pub error: Option<String>,  // Why the profiles couldn't be listed
+}
+
+impl ProfileBatch {
+    pub fn new(names: Vec<String>) -> Self {
+        ProfileBatch {
+            profiles: Vec::new(),
+            targets: names
+                .into_iter()
+                .map(|name| Target {
+                    name,
+                    profiles: Ok(Vec::new()),
+                    outcome: None,
+                })
+                .collect(),
+            selected: 0,
+            status: None,
+            error: None,
+        }
+    }
+
+    pub fn set_profiles(&mut self, profiles: Vec<String>) {
+        self.profiles = profiles;
+        self.error = None;
+        self.selected = self.selected.min(self.profiles.len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.profiles.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.profiles.len()
+        } else {
+            (self.selected + self.profiles.len() - 1) % self.profiles.len()
+        };
+    }
+
+    pub fn selected_profile(&self) -> Option<&String> {
+        self.profiles.get(self.selected)
+    }
+
+    /// The new profile list of each instance `change` changes
+    pub fn changes(&self, change: &ProfileChange) -> Vec<(String, Vec<String>)> {
+        self.targets
+            .iter()
+            .filter_map(|target| {
+                let profiles = target.profiles.as_ref().ok()?;
+                Some((target.name.clone(), change.apply(profiles)?))
+            })
+            .collect()
+    }
+
+    /// Record how the change went for each instance: those in `results`
+    /// were patched, the rest needed nothing
+    pub fn finish(&mut self, results: Vec<(String, Result<Vec<String>, String>)>) {
+        for target in &mut self.targets {
+            if target.profiles.is_err() {
+                target.outcome = None;
+                continue;
+            }
+            target.outcome = Some(Outcome::Unchanged);
+            let Some((_, result)) = results.iter().find(|(name, _)| *name == target.name) else {
+                continue;
+            };
+            target.outcome = Some(match result {
+                Ok(profiles) => {
+                    target.profiles = Ok(profiles.clone());
+                    Outcome::Changed
+                }
+                Err(e) => Outcome::Failed(e.clone()),
+            });
+        }
+    }
+
+    /// Count of instances changed, left alone, and failed
+    pub fn tally(&self) -> (usize, usize, usize) {
+        let count = |wanted: fn(&Outcome) -> bool| {
+            self.targets
+                .iter()
+                .filter(|t| t.outcome.as_ref().is_some_and(wanted))
+                .count()
+        };
+        (
+            count(|o| *o == Outcome::Changed),
+            count(|o| *o == Outcome::Unchanged),
+            count(|o| matches!(o, Outcome::Failed(_))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,55 @@
+//! Tests of batch profile changes
+
+use super::{Outcome, ProfileBatch, ProfileChange};
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+#[test]
+fn adds_last_and_removes_only_what_is_there() {
+    let add = ProfileChange::Add("monitoring".to_string());
+    assert_eq!(
+        add.apply(&names(&["default", "web"])),
+        Some(names(&["default", "web", "monitoring"]))
+    );
+    assert_eq!(add.apply(&names(&["monitoring", "default"])), None);
+
+    let remove = ProfileChange::Remove("web".to_string());
+    assert_eq!(
+        remove.apply(&names(&["default", "web"])),
+        Some(names(&["default"]))
+    );
+    assert_eq!(remove.apply(&names(&["default"])), None);
+    assert_eq!(remove.describe(3), "Remove profile 'web' from 3 instances");
+}
+
+#[test]
+fn reports_each_instance() {
+    let mut batch = ProfileBatch::new(names(&["web1", "web2", "db1", "gone"]));
+    batch.targets[0].profiles = Ok(names(&["default"]));
+    batch.targets[1].profiles = Ok(names(&["default", "monitoring"]));
+    batch.targets[2].profiles = Ok(names(&["default"]));
+    batch.targets[3].profiles = Err("not found".to_string());
+
+    let change = ProfileChange::Add("monitoring".to_string());
+    let changes = batch.changes(&change);
+    let changed: Vec<&str> = changes.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(changed, ["web1", "db1"]);
+
+    batch.finish(vec![
+        (changes[0].0.clone(), Ok(changes[0].1.clone())),
+        ("db1".to_string(), Err("Profile not found".to_string())),
+    ]);
+    assert_eq!(
+        batch.targets[0].profiles,
+        Ok(names(&["default", "monitoring"]))
+    );
+    assert_eq!(batch.targets[1].outcome, Some(Outcome::Unchanged));
+    assert_eq!(
+        batch.targets[2].outcome,
+        Some(Outcome::Failed("Profile not found".to_string()))
+    );
+    assert_eq!(batch.targets[3].outcome, None);
+    assert_eq!(batch.tally(), (1, 1, 1));
+}
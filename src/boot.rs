@@ -0,0 +1,188 @@
+//! Boot order
+//!
+//! Lists and edits `boot.autostart`, its priority and its delay for every
+//! instance on the server, in the order LXD starts them.
+
+use std::collections::HashMap;
+
+pub const AUTOSTART_KEY: &str = "boot.autostart";
+pub const PRIORITY_KEY: &str = "boot.autostart.priority";
+pub const DELAY_KEY: &str = "boot.autostart.delay";
+
+/// Whether LXD starts an instance at boot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Autostart {
+    Always,
+    Never,
+    LastState, // `boot.autostart` unset: start if it was running
+}
+
+impl Autostart {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("true" | "1" | "yes" | "on") => Autostart::Always,
+            Some("false" | "0" | "no" | "off") => Autostart::Never,
+            _ => Autostart::LastState,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Autostart::Always => "always",
+            Autostart::Never => "never",
+            Autostart::LastState => "if running",
+        }
+    }
+
+    /// The next setting, as a `boot.autostart` value; empty unsets the key
+    pub fn next_value(&self) -> &'static str {
+        match self {
+            Autostart::LastState => "true",
+            Autostart::Always => "false",
+            Autostart::Never => "",
+        }
+    }
+}
+
+/// One instance's boot settings, as expanded from its profiles
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootEntry {
+    pub name: String,
+    pub running: bool,
+    pub autostart: Autostart,
+    pub priority: i64,
+    pub delay: u64, // Seconds waited after starting it
+}
+
+impl BootEntry {
+    pub fn from_config(name: &str, running: bool, config: &HashMap<String, String>) -> Self {
+        let number = |key: &str| config.get(key).and_then(|v| v.trim().parse().ok());
+        BootEntry {
+            name: name.to_string(),
+            running,
+            autostart: Autostart::parse(config.get(AUTOSTART_KEY).map(String::as_str)),
+            priority: number(PRIORITY_KEY).unwrap_or(0),
+            delay: number(DELAY_KEY).map_or(0, |delay: i64| delay.max(0) as u64),
+        }
+    }
+
+    /// Whether LXD would start it if the host booted now
+    pub fn starts(&self) -> bool {
+        match self.autostart {
+            Autostart::Always => true,
+            Autostart::Never => false,
+            Autostart::LastState => self.running,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootSort {
+    StartOrder,
+    Name,
+    Delay,
+}
+
+impl BootSort {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BootSort::StartOrder => "start order",
+            BootSort::Name => "name",
+            BootSort::Delay => "delay",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            BootSort::StartOrder => BootSort::Name,
+            BootSort::Name => BootSort::Delay,
+            BootSort::Delay => BootSort::StartOrder,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BootOrder {
+    pub entries: Vec<BootEntry>, // In `sort` order
+    pub sort: BootSort,
+    pub selected: usize,
+    pub error: Option<String>, // Why the last load failed
+}
+
+impl BootOrder {
+    pub fn new() -> Self {
+        BootOrder {
+            entries: Vec::new(),
+            sort: BootSort::StartOrder,
+            selected: 0,
+            error: None,
+        }
+    }
+
+    /// Take in freshly loaded settings, keeping the selected instance
+    pub fn set_entries(&mut self, entries: Vec<BootEntry>) {
+        let selected = self.selected_entry().map(|e| e.name.clone());
+        self.entries = entries;
+        self.error = None;
+        self.sort_entries(selected);
+    }
+
+    pub fn sort_by(&mut self, sort: BootSort) {
+        let selected = self.selected_entry().map(|e| e.name.clone());
+        self.sort = sort;
+        self.sort_entries(selected);
+    }
+
+    fn sort_entries(&mut self, selected: Option<String>) {
+        let by_start = |a: &BootEntry, b: &BootEntry| {
+            b.starts()
+                .cmp(&a.starts())
+                .then(b.priority.cmp(&a.priority))
+                .then_with(|| a.name.cmp(&b.name))
+        };
+        match self.sort {
+            BootSort::StartOrder => self.entries.sort_by(by_start),
+            BootSort::Name => self.entries.sort_by(|a, b| a.name.cmp(&b.name)),
+            BootSort::Delay => self
+                .entries
+                .sort_by(|a, b| b.delay.cmp(&a.delay).then_with(|| by_start(a, b))),
+        }
+        self.selected = selected
+            .and_then(|name| self.entries.iter().position(|e| e.name == name))
+            .unwrap_or(0)
+            .min(self.entries.len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.entries.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.entries.len()
+        } else {
+            (self.selected + self.entries.len() - 1) % self.entries.len()
+        };
+    }
+
+    pub fn selected_entry(&self) -> Option<&BootEntry> {
+        self.entries.get(self.selected)
+    }
+
+    /// Where `name` comes in the boot sequence, from 1, and the seconds
+    /// after boot LXD starts it, counting the delays of the ones before.
+    /// None if it won't start.
+    pub fn start_position(&self, name: &str) -> Option<(usize, u64)> {
+        let mut starting: Vec<&BootEntry> = self.entries.iter().filter(|e| e.starts()).collect();
+        starting.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        let index = starting.iter().position(|e| e.name == name)?;
+        let after = starting[..index].iter().map(|e| e.delay).sum();
+        Some((index + 1, after))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,99 @@
+//! Tests of reading and ordering boot settings
+
+use super::{Autostart, BootEntry, BootOrder, BootSort};
+use std::collections::HashMap;
+
+fn entry(name: &str, running: bool, config: &[(&str, &str)]) -> BootEntry {
+    let config: HashMap<String, String> = config
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    BootEntry::from_config(name, running, &config)
+}
+
+fn order() -> BootOrder {
+    let mut order = BootOrder::new();
+    order.set_entries(vec![
+        entry("app", false, &[("boot.autostart", "true")]),
+        entry(
+            "db",
+            false,
+            &[
+                ("boot.autostart", "true"),
+                ("boot.autostart.priority", "10"),
+                ("boot.autostart.delay", "30"),
+            ],
+        ),
+        entry("scratch", false, &[("boot.autostart", "false")]),
+        entry("cache", true, &[("boot.autostart.priority", "5")]),
+        entry("idle", false, &[]),
+    ]);
+    order
+}
+
+fn names(order: &BootOrder) -> Vec<&str> {
+    order.entries.iter().map(|e| e.name.as_str()).collect()
+}
+
+#[test]
+fn reads_the_boot_keys() {
+    let db = entry(
+        "db",
+        false,
+        &[
+            ("boot.autostart", "true"),
+            ("boot.autostart.priority", "10"),
+            ("boot.autostart.delay", "30"),
+        ],
+    );
+    assert_eq!(db.autostart, Autostart::Always);
+    assert_eq!((db.priority, db.delay), (10, 30));
+
+    let unset = entry("idle", false, &[("boot.autostart.delay", "soon")]);
+    assert_eq!(unset.autostart, Autostart::LastState);
+    assert_eq!((unset.priority, unset.delay), (0, 0));
+}
+
+#[test]
+fn unset_autostart_follows_the_last_state() {
+    assert!(entry("cache", true, &[]).starts());
+    assert!(!entry("idle", false, &[]).starts());
+    assert!(!entry("off", true, &[("boot.autostart", "false")]).starts());
+}
+
+#[test]
+fn start_order_puts_higher_priorities_first() {
+    let order = order();
+    assert_eq!(names(&order), ["db", "cache", "app", "idle", "scratch"]);
+}
+
+#[test]
+fn start_position_adds_up_the_delays() {
+    let order = order();
+    assert_eq!(order.start_position("db"), Some((1, 0)));
+    assert_eq!(order.start_position("cache"), Some((2, 30)));
+    assert_eq!(order.start_position("app"), Some((3, 30)));
+    assert_eq!(order.start_position("scratch"), None);
+}
+
+#[test]
+fn sorting_keeps_the_selection() {
+    let mut order = order();
+    order.select(true);
+    assert_eq!(order.selected_entry().unwrap().name, "cache");
+
+    order.sort_by(BootSort::Name);
+    assert_eq!(names(&order), ["app", "cache", "db", "idle", "scratch"]);
+    assert_eq!(order.selected_entry().unwrap().name, "cache");
+
+    order.sort_by(order.sort.next());
+    assert_eq!(order.sort, BootSort::Delay);
+    assert_eq!(names(&order)[0], "db");
+}
+
+#[test]
+fn autostart_cycles_through_its_settings() {
+    assert_eq!(Autostart::LastState.next_value(), "true");
+    assert_eq!(Autostart::Always.next_value(), "false");
+    assert_eq!(Autostart::Never.next_value(), "");
+}
@@ -0,0 +1,175 @@
+//! Resource reservations
+//!
+//! Adds up `limits.cpu` and `limits.memory` over a server's instances and
+//! sets them against the host's CPU threads and memory.
+
+use crate::alerts;
+use std::collections::HashMap;
+
+/// What LXD gives a VM without limits
+const VM_DEFAULT_CPUS: u64 = 1;
+const VM_DEFAULT_MEMORY: u64 = 1 << 30;
+
+/// CPU threads and bytes of memory of the host
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Host {
+    pub cpus: u64,
+    pub memory: u64,
+}
+
+/// What one instance has reserved; None where it has no limit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reservation {
+    pub name: String,
+    pub virtual_machine: bool,
+    pub running: bool,
+    pub cpus: Option<u64>,
+    pub memory: Option<u64>, // Bytes
+}
+
+impl Reservation {
+    pub fn from_config(
+        name: &str,
+        instance_type: &str,
+        running: bool,
+        config: &HashMap<String, String>,
+        host: Host,
+    ) -> Self {
+        let virtual_machine = instance_type == "virtual-machine";
+        let default = |value| virtual_machine.then_some(value);
+        Reservation {
+            name: name.to_string(),
+            virtual_machine,
+            running,
+            cpus: config
+                .get("limits.cpu")
+                .and_then(|limit| cpu_count(limit))
+                .or(default(VM_DEFAULT_CPUS)),
+            memory: config
+                .get("limits.memory")
+                .and_then(|limit| memory_bytes(limit, host.memory))
+                .or(default(VM_DEFAULT_MEMORY)),
+        }
+    }
+}
+
+/// CPUs a `limits.cpu` value gives: a count, or the size of a set of
+/// ranges and single CPUs
+pub fn cpu_count(limit: &str) -> Option<u64> {
+    let limit = limit.trim();
+    if limit.is_empty() {
+        return None;
+    }
+    if !limit.contains(['-', ',']) {
+        return limit.parse().ok();
+    }
+    let mut count = 0;
+    for part in limit.split(',') {
+        count += match part.trim().split_once('-') {
+            Some((first, last)) => {
+                let (first, last): (u64, u64) = (first.parse().ok()?, last.parse().ok()?);
+                last.checked_sub(first)? + 1
+            }
+            None => {
+                part.trim().parse::<u64>().ok()?;
+                1
+            }
+        };
+    }
+    Some(count)
+}
+
+/// Bytes a `limits.memory` value gives, a percentage being of `host`
+pub fn memory_bytes(limit: &str, host: u64) -> Option<u64> {
+    match limit.trim().strip_suffix('%') {
+        Some(percent) => {
+            let percent: f64 = percent.trim().parse().ok()?;
+            Some((host as f64 * percent / 100.0) as u64)
+        }
+        None => alerts::parse_size(limit).and_then(|bytes| u64::try_from(bytes).ok()),
+    }
+}
+
+/// Reservations added up over some of the instances
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Totals {
+    pub cpus: u64,
+    pub memory: u64,
+    pub unlimited: usize, // Instances without a CPU or memory limit
+}
+
+impl Totals {
+    pub fn of<'a>(reservations: impl Iterator<Item = &'a Reservation>) -> Self {
+        let mut totals = Totals::default();
+        for reservation in reservations {
+            totals.cpus += reservation.cpus.unwrap_or(0);
+            totals.memory += reservation.memory.unwrap_or(0);
+            if reservation.cpus.is_none() || reservation.memory.is_none() {
+                totals.unlimited += 1;
+            }
+        }
+        totals
+    }
+
+    /// Whether these reservations add up to more than the host has
+    pub fn overcommits(&self, host: Host) -> bool {
+        self.cpus > host.cpus || self.memory > host.memory
+    }
+}
+
+/// The capacity view: the host and its instances' reservations, largest
+/// memory first
+#[derive(Debug)]
+pub struct Capacity {
+    pub host: Host,
+    pub reservations: Vec<Reservation>,
+    pub selected: usize,
+    pub error: Option<String>, // Why the last load failed
+}
+
+impl Capacity {
+    pub fn new() -> Self {
+        Capacity {
+            host: Host::default(),
+            reservations: Vec::new(),
+            selected: 0,
+            error: None,
+        }
+    }
+
+    /// Take in fresh figures, keeping the selected instance
+    pub fn set_reservations(&mut self, host: Host, mut reservations: Vec<Reservation>) {
+        let selected = self.reservations.get(self.selected).map(|r| r.name.clone());
+        reservations.sort_by(|a, b| b.memory.cmp(&a.memory).then(a.name.cmp(&b.name)));
+        self.host = host;
+        self.reservations = reservations;
+        self.error = None;
+        self.selected = selected
+            .and_then(|name| self.reservations.iter().position(|r| r.name == name))
+            .unwrap_or(0)
+            .min(self.reservations.len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.reservations.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.reservations.len()
+        } else {
+            (self.selected + self.reservations.len() - 1) % self.reservations.len()
+        };
+    }
+
+    /// Reserved by every instance, as they would be were all started
+    pub fn all(&self) -> Totals {
+        Totals::of(self.reservations.iter())
+    }
+
+    pub fn running(&self) -> Totals {
+        Totals::of(self.reservations.iter().filter(|r| r.running))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,74 @@
+//! Tests of resource reservations
+
+use super::{cpu_count, memory_bytes, Capacity, Host, Reservation, Totals};
+use std::collections::HashMap;
+
+const GIB: u64 = 1 << 30;
+
+fn reservation(name: &str, kind: &str, running: bool, limits: &[(&str, &str)]) -> Reservation {
+    let config: HashMap<String, String> = limits
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let host = Host {
+        cpus: 8,
+        memory: 16 * GIB,
+    };
+    Reservation::from_config(name, kind, running, &config, host)
+}
+
+#[test]
+fn reads_cpu_counts_sets_and_memory_sizes_or_shares() {
+    assert_eq!(cpu_count("4"), Some(4));
+    assert_eq!(cpu_count("0-3,8"), Some(5));
+    assert_eq!(cpu_count("3-1"), None);
+    assert_eq!(cpu_count(""), None);
+
+    assert_eq!(memory_bytes("8GiB", 0), Some(8 * GIB));
+    assert_eq!(memory_bytes("25%", 16 * GIB), Some(4 * GIB));
+    assert_eq!(memory_bytes("lots", 16 * GIB), None);
+}
+
+#[test]
+fn adds_up_limits_and_flags_an_overcommitted_host() {
+    let mut capacity = Capacity::new();
+    capacity.set_reservations(
+        Host {
+            cpus: 8,
+            memory: 16 * GIB,
+        },
+        vec![
+            reservation("web1", "container", true, &[("limits.memory", "2GiB")]),
+            reservation(
+                "vm1",
+                "virtual-machine",
+                true,
+                &[("limits.cpu", "4"), ("limits.memory", "8GiB")],
+            ),
+            reservation("vm2", "virtual-machine", false, &[("limits.cpu", "0-5")]),
+            reservation("db1", "container", false, &[("limits.memory", "50%")]),
+        ],
+    );
+
+    // Largest memory first, then by name; an unlimited VM gets LXD's defaults
+    let names: Vec<&str> = capacity
+        .reservations
+        .iter()
+        .map(|r| r.name.as_str())
+        .collect();
+    assert_eq!(names, ["db1", "vm1", "web1", "vm2"]);
+    assert_eq!(capacity.reservations[3].memory, Some(GIB));
+
+    assert_eq!(
+        capacity.running(),
+        Totals {
+            cpus: 4,
+            memory: 10 * GIB,
+            unlimited: 1,
+        }
+    );
+    assert!(!capacity.running().overcommits(capacity.host));
+    let all = capacity.all();
+    assert_eq!((all.cpus, all.memory, all.unlimited), (10, 19 * GIB, 2));
+    assert!(all.overcommits(capacity.host));
+}
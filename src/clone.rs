@@ -0,0 +1,114 @@
+//! Clone options
+//!
+//! After the new name, the clone form offers copying without snapshots, an
+//! ephemeral copy, and a storage pool or project for the copy. Each maps to
+//! part of the copy request: `source.instance_only`, `ephemeral`, a root
+//! disk device on the pool, and the `project` the copy is created in.
+
+/// How a copy differs from its source
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CloneOptions {
+    pub instance_only: bool,     // Leave the snapshots behind
+    pub ephemeral: bool,         // Deleted when it stops
+    pub pool: Option<String>,    // Root disk pool; None keeps the source's
+    pub project: Option<String>, // None copies within the current project
+}
+
+/// Rows of the clone form, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    InstanceOnly,
+    Ephemeral,
+    Pool,
+    Project,
+}
+
+pub const FIELDS: [Field; 4] = [
+    Field::InstanceOnly,
+    Field::Ephemeral,
+    Field::Pool,
+    Field::Project,
+];
+
+/// The clone form between the name prompt and the copy request
+#[derive(Debug, Clone)]
+pub struct CloneForm {
+    pub source: String,
+    pub destination: String,
+    pub options: CloneOptions,
+    pub pools: Vec<String>,    // Storage pools on the source's server
+    pub projects: Vec<String>, // Projects on the source's server
+    pub field: usize,          // Index into FIELDS
+}
+
+impl CloneForm {
+    pub fn new(
+        source: String,
+        destination: String,
+        pools: Vec<String>,
+        projects: Vec<String>,
+    ) -> Self {
+        CloneForm {
+            source,
+            destination,
+            options: CloneOptions::default(),
+            pools,
+            projects,
+            field: 0,
+        }
+    }
+
+    pub fn field(&self) -> Field {
+        FIELDS[self.field]
+    }
+
+    /// Move to the next or previous row, wrapping like the menus
+    pub fn move_field(&mut self, forward: bool) {
+        let count = FIELDS.len();
+        self.field = if forward {
+            (self.field + 1) % count
+        } else {
+            (self.field + count - 1) % count
+        };
+    }
+
+    /// Flip the selected checkbox, or step the selected picker forward
+    pub fn toggle(&mut self) {
+        match self.field() {
+            Field::InstanceOnly => self.options.instance_only = !self.options.instance_only,
+            Field::Ephemeral => self.options.ephemeral = !self.options.ephemeral,
+            Field::Pool | Field::Project => self.cycle(true),
+        }
+    }
+
+    /// Step the selected picker through "same as the source" and the
+    /// server's pools or projects
+    pub fn cycle(&mut self, forward: bool) {
+        match self.field() {
+            Field::Pool => self.options.pool = step(&self.options.pool, &self.pools, forward),
+            Field::Project => {
+                self.options.project = step(&self.options.project, &self.projects, forward)
+            }
+            Field::InstanceOnly | Field::Ephemeral => {}
+        }
+    }
+}
+
+/// The choice after `current`, where None comes before the first choice
+fn step(current: &Option<String>, choices: &[String], forward: bool) -> Option<String> {
+    // Position 0 is None, choice i is position i + 1
+    let count = choices.len() + 1;
+    let position = current
+        .as_ref()
+        .and_then(|c| choices.iter().position(|choice| choice == c))
+        .map_or(0, |i| i + 1);
+    let next = if forward {
+        (position + 1) % count
+    } else {
+        (position + count - 1) % count
+    };
+    next.checked_sub(1).map(|i| choices[i].clone())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,47 @@
+//! Tests of the clone form
+
+use super::{CloneForm, CloneOptions, Field};
+
+fn form() -> CloneForm {
+    CloneForm::new(
+        "web1".to_string(),
+        "web2".to_string(),
+        vec!["default".to_string(), "fast".to_string()],
+        vec!["default".to_string()],
+    )
+}
+
+#[test]
+fn toggles_checkboxes() {
+    let mut form = form();
+    form.toggle();
+    form.move_field(true);
+    form.toggle();
+    assert_eq!(
+        form.options,
+        CloneOptions {
+            instance_only: true,
+            ephemeral: true,
+            pool: None,
+            project: None,
+        }
+    );
+}
+
+#[test]
+fn pickers_cycle_through_the_server_choices() {
+    let mut form = form();
+    form.move_field(false);
+    assert_eq!(form.field(), Field::Project);
+    form.move_field(false);
+    assert_eq!(form.field(), Field::Pool);
+
+    form.cycle(true);
+    assert_eq!(form.options.pool.as_deref(), Some("default"));
+    form.cycle(true);
+    assert_eq!(form.options.pool.as_deref(), Some("fast"));
+    form.cycle(true);
+    assert_eq!(form.options.pool, None);
+    form.cycle(false);
+    assert_eq!(form.options.pool.as_deref(), Some("fast"));
+}
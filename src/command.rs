@@ -0,0 +1,129 @@
+//! Command line and script commands
+//!
+//! Commands typed after `:` or read from a `--script` file are parsed into
+//! the same `Action`s keys produce, so they behave exactly like the menus.
+
+use crate::action::Action;
+use crate::app::{ConfirmAction, PendingAction};
+use crate::filter::{ContainerFilter, FilterError};
+use crate::workspace::{SortKey, WorkspaceError};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CommandError {
+    #[error("Unknown command '{0}'")]
+    Unknown(String),
+    #[error("Usage: {0}")]
+    Usage(&'static str),
+    #[error(transparent)]
+    Filter(#[from] FilterError),
+    #[error(transparent)]
+    Workspace(#[from] WorkspaceError),
+    #[error("Failed to read script {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("{path}:{line}: {source}")]
+    Script {
+        path: String,
+        line: usize,
+        source: Box<CommandError>,
+    },
+}
+
+/// Where a command came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Typed interactively; container actions ask for confirmation as usual
+    CommandLine,
+    /// Read from a script, which runs unattended
+    Script,
+}
+
+/// Parse one command into the action that carries it out
+pub fn parse(line: &str, source: Source) -> Result<Action, CommandError> {
+    let mut words = line.split_whitespace();
+    let Some(command) = words.next() else {
+        return Err(CommandError::Usage("COMMAND [ARGS...]"));
+    };
+    let args: Vec<&str> = words.collect();
+
+    let container = |usage: &'static str, make: fn(String) -> ConfirmAction| match args[..] {
+        [name] => {
+            let action = make(name.to_string());
+            Ok(match source {
+                Source::CommandLine => Action::Request(action),
+                Source::Script => Action::Run(PendingAction::Container(action)),
+            })
+        }
+        _ => Err(CommandError::Usage(usage)),
+    };
+
+    match command {
+        "start" => container("start NAME", ConfirmAction::StartContainer),
+        "stop" => container("stop NAME", ConfirmAction::StopContainer),
+        "restart" => container("restart NAME", ConfirmAction::RestartContainer),
+        "delete" => container("delete NAME", ConfirmAction::DeleteContainer),
+        "snapshot" => match args[..] {
+            [container, ref snapshot @ ..] if snapshot.len() <= 1 => {
+                Ok(Action::Run(PendingAction::CreateSnapshot {
+                    container: container.to_string(),
+                    snapshot: snapshot.first().map(|s| s.to_string()),
+                }))
+            }
+            _ => Err(CommandError::Usage("snapshot NAME [SNAPSHOT]")),
+        },
+        "filter" if args.is_empty() => Ok(Action::SetFilter(None)),
+        "filter" => Ok(Action::SetFilter(Some(ContainerFilter::parse(
+            &args.join(" "),
+        )?))),
+        "sort" => match args[..] {
+            [] => Ok(Action::SetSort(None)),
+            [key] => Ok(Action::SetSort(Some(SortKey::parse(key)?))),
+            _ => Err(CommandError::Usage("sort [name|status|type|remote]")),
+        },
+        "workspace" => match args[..] {
+            ["save", ref name @ ..] if !name.is_empty() => {
+                Ok(Action::SaveWorkspace(name.join(" ")))
+            }
+            [] | ["save"] => Err(CommandError::Usage("workspace NAME | workspace save NAME")),
+            ref name => Ok(Action::OpenWorkspace(name.join(" "))),
+        },
+        "select" => match args[..] {
+            [name] => Ok(Action::SelectContainer(name.to_string())),
+            _ => Err(CommandError::Usage("select NAME")),
+        },
+        "refresh" if args.is_empty() => Ok(Action::Refresh),
+        "quit" | "q" if args.is_empty() => Ok(Action::Quit),
+        "refresh" | "quit" | "q" => Err(CommandError::Usage("refresh | quit")),
+        _ => Err(CommandError::Unknown(command.to_string())),
+    }
+}
+
+/// Parse a whole script, skipping blank lines and `#` comments, so a typo
+/// is reported before anything runs
+pub fn load_script(path: &Path) -> Result<Vec<Action>, CommandError> {
+    let display = path.display().to_string();
+    let text = std::fs::read_to_string(path).map_err(|source| CommandError::Read {
+        path: display.clone(),
+        source,
+    })?;
+
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            parse(line, Source::Script).map_err(|e| CommandError::Script {
+                path: display.clone(),
+                line: line_number,
+                source: Box::new(e),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests;
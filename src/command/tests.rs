@@ -0,0 +1,138 @@
+//! Tests of command and filter parsing
+
+use super::{load_script, parse, CommandError, Source};
+use crate::action::Action;
+use crate::app::{ConfirmAction, PendingAction};
+use crate::filter::ContainerFilter;
+use crate::lxc::{Container, ContainerState};
+use crate::workspace::SortKey;
+
+pub fn container(name: &str, status: &str, kind: &str) -> Container {
+    Container {
+        name: name.to_string(),
+        status: status.to_string(),
+        state: ContainerState {
+            status: status.to_string(),
+            status_code: 0,
+        },
+        ipv4: Vec::new(),
+        ipv6: Vec::new(),
+        container_type: kind.to_string(),
+        remote: None,
+        memory_usage: None,
+        memory_limit: None,
+        tags: Vec::new(),
+        ssh_user: None,
+        image: None,
+        base_image: None,
+        healthcheck: None,
+        protected: false,
+        restart: false,
+        ports: Vec::new(),
+        notes: None,
+    }
+}
+
+#[test]
+fn container_commands_confirm_only_when_typed() {
+    assert!(matches!(
+        parse("start web-01", Source::CommandLine),
+        Ok(Action::Request(ConfirmAction::StartContainer(name))) if name == "web-01"
+    ));
+    assert!(matches!(
+        parse("delete web-01", Source::Script),
+        Ok(Action::Run(PendingAction::Container(ConfirmAction::DeleteContainer(name))))
+            if name == "web-01"
+    ));
+}
+
+#[test]
+fn snapshot_name_is_optional() {
+    assert!(matches!(
+        parse("snapshot db-1 pre-upgrade", Source::Script),
+        Ok(Action::Run(PendingAction::CreateSnapshot { container, snapshot }))
+            if container == "db-1" && snapshot.as_deref() == Some("pre-upgrade")
+    ));
+    assert!(matches!(
+        parse("snapshot db-1", Source::Script),
+        Ok(Action::Run(PendingAction::CreateSnapshot {
+            snapshot: None,
+            ..
+        }))
+    ));
+    assert!(matches!(
+        parse("snapshot db-1 a b", Source::Script),
+        Err(CommandError::Usage(_))
+    ));
+}
+
+#[test]
+fn bad_commands_are_rejected() {
+    assert!(matches!(
+        parse("launch web1", Source::CommandLine),
+        Err(CommandError::Unknown(_))
+    ));
+    assert!(matches!(
+        parse("start", Source::CommandLine),
+        Err(CommandError::Usage(_))
+    ));
+    assert!(matches!(
+        parse("filter colour=red", Source::CommandLine),
+        Err(CommandError::Filter(_))
+    ));
+    assert!(matches!(
+        parse("filter", Source::CommandLine),
+        Ok(Action::SetFilter(None))
+    ));
+}
+
+#[test]
+fn sort_and_workspace_commands() {
+    assert!(matches!(
+        parse("sort Status", Source::CommandLine),
+        Ok(Action::SetSort(Some(SortKey::Status)))
+    ));
+    assert!(matches!(
+        parse("sort age", Source::CommandLine),
+        Err(CommandError::Workspace(_))
+    ));
+    assert!(matches!(
+        parse("workspace save prod web", Source::CommandLine),
+        Ok(Action::SaveWorkspace(name)) if name == "prod web"
+    ));
+    assert!(matches!(
+        parse("workspace stopped VMs", Source::CommandLine),
+        Ok(Action::OpenWorkspace(name)) if name == "stopped VMs"
+    ));
+    assert!(matches!(
+        parse("workspace save", Source::CommandLine),
+        Err(CommandError::Usage(_))
+    ));
+}
+
+#[test]
+fn filters_match_every_term() {
+    let filter = ContainerFilter::parse("status=Running type=vm").unwrap();
+    assert!(filter.matches(&container("vm1", "Running", "virtual-machine")));
+    assert!(!filter.matches(&container("web1", "Running", "container")));
+    assert!(!filter.matches(&container("vm2", "Stopped", "virtual-machine")));
+
+    let filter = ContainerFilter::parse("name=WEB").unwrap();
+    assert!(filter.matches(&container("web1", "Stopped", "container")));
+    assert!(ContainerFilter::parse("name").is_err());
+}
+
+#[test]
+fn script_errors_name_the_line() {
+    let path = std::env::temp_dir().join(format!("lxtui-script-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&path, "# upgrade\nstop web1\n\nsnapshot\n").unwrap();
+    let result = load_script(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let error = result.unwrap_err().to_string();
+    assert!(
+        error.ends_with(":4: Usage: snapshot NAME [SNAPSHOT]"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,157 @@
+//! Side-by-side comparison of two instances
+//!
+//! Lines up the profiles, limits, configuration and devices of two instances
+//! so a setting that differs, or that only one of them has, stands out.
+//! Expanded values are compared, so settings inherited from profiles count.
+
+use crate::lxd_api::LxdContainer;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Section {
+    General,
+    Profiles,
+    Limits,
+    Config,
+    Devices,
+}
+
+impl Section {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Section::General => "General",
+            Section::Profiles => "Profiles",
+            Section::Limits => "Limits",
+            Section::Config => "Config",
+            Section::Devices => "Devices",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareRow {
+    pub section: Section,
+    pub key: String,
+    pub left: Option<String>, // None when the instance lacks the setting
+    pub right: Option<String>,
+}
+
+impl CompareRow {
+    pub fn differs(&self) -> bool {
+        self.left != self.right
+    }
+}
+
+#[derive(Debug)]
+pub struct Comparison {
+    pub left: String, // qualified names
+    pub right: String,
+    pub rows: Vec<CompareRow>,
+    pub only_differences: bool,
+    pub scroll: u16,
+}
+
+impl Comparison {
+    pub fn new(left: String, right: String, a: &LxdContainer, b: &LxdContainer) -> Self {
+        let (a, b) = (settings(a), settings(b));
+        let keys: BTreeSet<&(Section, String)> = a.keys().chain(b.keys()).collect();
+        let rows = keys
+            .into_iter()
+            .map(|key| CompareRow {
+                section: key.0,
+                key: key.1.clone(),
+                left: a.get(key).cloned(),
+                right: b.get(key).cloned(),
+            })
+            .collect();
+
+        Comparison {
+            left,
+            right,
+            rows,
+            only_differences: false,
+            scroll: 0,
+        }
+    }
+
+    /// Rows the view shows, honouring the differences-only toggle
+    pub fn visible_rows(&self) -> Vec<&CompareRow> {
+        self.rows
+            .iter()
+            .filter(|row| !self.only_differences || row.differs())
+            .collect()
+    }
+
+    /// Scroll by `delta` rows, keeping the last row in reach
+    pub fn scroll_by(&mut self, delta: i32) {
+        let max = self.visible_rows().len().saturating_sub(1) as i32;
+        self.scroll = (self.scroll as i32 + delta).clamp(0, max) as u16;
+    }
+
+    pub fn toggle_differences(&mut self) {
+        self.only_differences = !self.only_differences;
+        self.scroll = 0;
+    }
+
+    pub fn difference_count(&self) -> usize {
+        self.rows.iter().filter(|row| row.differs()).count()
+    }
+}
+
+/// Every compared setting of an instance. Volatile keys hold per-instance
+/// values such as MAC addresses and UUIDs, so they are left out.
+fn settings(instance: &LxdContainer) -> BTreeMap<(Section, String), String> {
+    let mut settings = BTreeMap::new();
+    let mut set = |section, key: &str, value: String| {
+        settings.insert((section, key.to_string()), value);
+    };
+
+    set(Section::General, "type", instance.container_type.clone());
+    set(
+        Section::General,
+        "architecture",
+        instance.architecture.clone(),
+    );
+    set(
+        Section::General,
+        "ephemeral",
+        instance.ephemeral.to_string(),
+    );
+    // Profile order decides which one wins, so it is compared as a whole
+    set(Section::Profiles, "profiles", instance.profiles.join(", "));
+
+    let config = instance
+        .expanded_config
+        .as_ref()
+        .unwrap_or(&instance.config);
+    for (key, value) in config {
+        if key.starts_with("volatile.") {
+            continue;
+        }
+        let section = if key.starts_with("limits.") {
+            Section::Limits
+        } else {
+            Section::Config
+        };
+        set(section, key, value.clone());
+    }
+
+    let devices = instance
+        .expanded_devices
+        .as_ref()
+        .unwrap_or(&instance.devices);
+    for (device, options) in devices {
+        for (key, value) in options {
+            set(
+                Section::Devices,
+                &format!("{}.{}", device, key),
+                value.clone(),
+            );
+        }
+    }
+
+    settings
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,96 @@
+//! Tests of comparing two instances
+
+use super::{Comparison, Section};
+use crate::lxd_api::LxdContainer;
+use serde_json::json;
+
+/// An instance with the given expanded config and a root disk and NIC
+pub fn instance(name: &str, profiles: &[&str], config: serde_json::Value) -> LxdContainer {
+    serde_json::from_value(json!({
+        "architecture": "x86_64",
+        "config": {},
+        "created_at": "2024-01-01T00:00:00Z",
+        "devices": {},
+        "ephemeral": false,
+        "expanded_config": config,
+        "expanded_devices": {
+            "root": {"type": "disk", "path": "/", "pool": "default"},
+            "eth0": {"type": "nic", "network": "lxdbr0", "name": "eth0"},
+        },
+        "last_used_at": "2024-01-01T00:00:00Z",
+        "name": name,
+        "profiles": profiles,
+        "stateful": false,
+        "status": "Running",
+        "status_code": 103,
+        "type": "container",
+    }))
+    .unwrap()
+}
+
+fn comparison() -> Comparison {
+    let staging = instance(
+        "staging",
+        &["default"],
+        json!({
+            "limits.memory": "2GiB",
+            "image.os": "Ubuntu",
+            "volatile.eth0.hwaddr": "00:16:3e:00:00:01",
+        }),
+    );
+    let prod = instance(
+        "prod",
+        &["default", "prod"],
+        json!({
+            "limits.memory": "8GiB",
+            "limits.cpu": "4",
+            "image.os": "Ubuntu",
+            "volatile.eth0.hwaddr": "00:16:3e:00:00:02",
+        }),
+    );
+    Comparison::new("staging".to_string(), "prod".to_string(), &staging, &prod)
+}
+
+#[test]
+fn lines_up_settings_by_section() {
+    let comparison = comparison();
+    let row = |key: &str| comparison.rows.iter().find(|r| r.key == key).unwrap();
+
+    assert_eq!(row("limits.memory").section, Section::Limits);
+    assert_eq!(row("limits.memory").left.as_deref(), Some("2GiB"));
+    assert_eq!(row("limits.memory").right.as_deref(), Some("8GiB"));
+    assert_eq!(row("limits.cpu").left, None);
+    assert_eq!(row("image.os").section, Section::Config);
+    assert!(!row("image.os").differs());
+    assert_eq!(row("root.pool").section, Section::Devices);
+    assert_eq!(row("profiles").right.as_deref(), Some("default, prod"));
+
+    // Sections come in order
+    let sections: Vec<Section> = comparison.rows.iter().map(|r| r.section).collect();
+    let mut sorted = sections.clone();
+    sorted.sort();
+    assert_eq!(sections, sorted);
+}
+
+#[test]
+fn volatile_keys_are_left_out() {
+    let comparison = comparison();
+    assert!(comparison
+        .rows
+        .iter()
+        .all(|r| !r.key.starts_with("volatile.")));
+}
+
+#[test]
+fn differences_only() {
+    let mut comparison = comparison();
+    assert_eq!(comparison.difference_count(), 3);
+
+    comparison.only_differences = true;
+    let keys: Vec<&str> = comparison
+        .visible_rows()
+        .iter()
+        .map(|r| r.key.as_str())
+        .collect();
+    assert_eq!(keys, ["profiles", "limits.cpu", "limits.memory"]);
+}
@@ -0,0 +1,459 @@
+//! Shell completions and the man page
+//!
+//! Both are generated from the clap definition of the command line, so they
+//! follow new options and subcommands without being edited by hand.
+//! `lxtui completions <shell>` and `lxtui man` print them for packaging or
+//! for sourcing from a shell's startup file.
+
+use clap::{Arg, Command, ValueEnum, ValueHint};
+use std::fmt::Write;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// The completion script for `shell`
+pub fn script(shell: Shell, mut cmd: Command) -> String {
+    cmd.build();
+    match shell {
+        Shell::Bash => bash(&cmd),
+        Shell::Zsh => zsh(&cmd),
+        Shell::Fish => fish(&cmd),
+    }
+}
+
+/// What can be completed for an option's value or a positional argument
+enum Values {
+    Any,
+    Files,
+    Choices(Vec<String>),
+}
+
+fn values(arg: &Arg) -> Values {
+    let choices: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    if !choices.is_empty() {
+        return Values::Choices(choices);
+    }
+    match arg.get_value_hint() {
+        ValueHint::AnyPath | ValueHint::FilePath | ValueHint::DirPath => Values::Files,
+        _ => Values::Any,
+    }
+}
+
+/// What can be completed for the first argument of a subcommand: its
+/// first positional, or the subcommands it takes, as `help` does
+fn first_argument(cmd: &Command) -> Values {
+    if let Some(arg) = positionals(cmd).next() {
+        return values(arg);
+    }
+    let names: Vec<String> = subcommands(cmd)
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    if names.is_empty() {
+        Values::Any
+    } else {
+        Values::Choices(names)
+    }
+}
+
+fn options(cmd: &Command) -> impl Iterator<Item = &Arg> {
+    cmd.get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+}
+
+fn positionals(cmd: &Command) -> impl Iterator<Item = &Arg> {
+    cmd.get_positionals().filter(|arg| !arg.is_hide_set())
+}
+
+fn subcommands(cmd: &Command) -> impl Iterator<Item = &Command> {
+    cmd.get_subcommands().filter(|sub| !sub.is_hide_set())
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+}
+
+/// `-h` and `--help`, as typed
+fn flags(arg: &Arg) -> Vec<String> {
+    let short = arg.get_short().map(|c| format!("-{}", c));
+    let long = arg.get_long().map(|l| format!("--{}", l));
+    short.into_iter().chain(long).collect()
+}
+
+/// The first line of an argument's help, without its full stop
+fn summary(help: Option<String>) -> String {
+    let help = help.unwrap_or_default();
+    let line = help.lines().next().unwrap_or_default();
+    line.strip_suffix('.').unwrap_or(line).to_string()
+}
+
+fn arg_summary(arg: &Arg) -> String {
+    summary(arg.get_help().map(ToString::to_string))
+}
+
+fn command_summary(cmd: &Command) -> String {
+    summary(cmd.get_about().map(ToString::to_string))
+}
+
+/// Name shown for an argument's value, e.g. FILE
+fn value_name(arg: &Arg) -> String {
+    arg.get_value_names()
+        .and_then(|names| names.first())
+        .map(ToString::to_string)
+        .unwrap_or_else(|| arg.get_id().as_str().to_uppercase())
+}
+
+fn bash(cmd: &Command) -> String {
+    let name = cmd.get_name();
+    let function = format!("_{}", name.replace('-', "_"));
+    let words = |cmd: &Command, extra: Vec<String>| {
+        let mut words: Vec<String> = options(cmd).flat_map(flags).collect();
+        words.extend(extra);
+        words.join(" ")
+    };
+    let reply = |values: Values, words: String| match values {
+        Values::Files => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+        Values::Choices(choices) => format!(
+            "COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+            [words, choices.join(" ")].join(" ").trim()
+        ),
+        Values::Any if words.is_empty() => "COMPREPLY=()".to_string(),
+        Values::Any => format!("COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))", words),
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}() {{", function);
+    out.push_str("    local cur prev command i\n");
+    out.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n\n");
+
+    // The value of an option that takes one
+    out.push_str("    case \"$prev\" in\n");
+    for arg in options(cmd).filter(|arg| takes_value(arg)) {
+        let _ = writeln!(
+            out,
+            "        {}) {}; return ;;",
+            flags(arg).join("|"),
+            reply(values(arg), String::new())
+        );
+    }
+    out.push_str("    esac\n\n");
+
+    let names: Vec<&str> = subcommands(cmd).map(|sub| sub.get_name()).collect();
+    out.push_str("    command=\"\"\n");
+    out.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+    out.push_str("        case \"${COMP_WORDS[i]}\" in\n");
+    let _ = writeln!(
+        out,
+        "            {}) command=\"${{COMP_WORDS[i]}}\"; break ;;",
+        names.join("|")
+    );
+    out.push_str("        esac\n");
+    out.push_str("    done\n\n");
+
+    out.push_str("    case \"$command\" in\n");
+    let top = words(cmd, names.iter().map(|n| n.to_string()).collect());
+    let _ = writeln!(out, "        \"\") {} ;;", reply(Values::Any, top));
+    for sub in subcommands(cmd) {
+        let _ = writeln!(
+            out,
+            "        {}) {} ;;",
+            sub.get_name(),
+            reply(first_argument(sub), words(sub, Vec::new()))
+        );
+    }
+    out.push_str("    esac\n");
+    out.push_str("}\n\n");
+    let _ = writeln!(out, "complete -F {} {}", function, name);
+    out
+}
+
+/// Quoted for zsh, inside single quotes
+fn zsh_quote(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+fn zsh_action(values: Values) -> String {
+    match values {
+        Values::Any => " ".to_string(),
+        Values::Files => "_files".to_string(),
+        Values::Choices(choices) => format!("({})", choices.join(" ")),
+    }
+}
+
+/// `_arguments` specs for a command's options and positional arguments
+fn zsh_specs(cmd: &Command) -> Vec<String> {
+    let mut specs = Vec::new();
+    for arg in options(cmd) {
+        let flags = flags(arg);
+        let help = zsh_quote(&arg_summary(arg));
+        let value = if takes_value(arg) {
+            format!(":{}:{}", value_name(arg), zsh_action(values(arg)))
+        } else {
+            String::new()
+        };
+        specs.push(match flags.as_slice() {
+            [flag] => format!("'{}[{}]{}'", flag, help, value),
+            _ => format!(
+                "'({})'{{{}}}'[{}]{}'",
+                flags.join(" "),
+                flags.join(","),
+                help,
+                value
+            ),
+        });
+    }
+    for arg in positionals(cmd) {
+        let name = arg.get_id().as_str();
+        let many = arg.get_num_args().is_some_and(|n| n.max_values() > 1);
+        specs.push(if many {
+            format!("'*::{}:_normal'", name)
+        } else if arg.is_required_set() {
+            format!("':{}:{}'", name, zsh_action(values(arg)))
+        } else {
+            format!("'::{}:{}'", name, zsh_action(values(arg)))
+        });
+    }
+    specs
+}
+
+fn zsh(cmd: &Command) -> String {
+    let name = cmd.get_name();
+    let function = format!("_{}", name.replace('-', "_"));
+    let mut out = String::new();
+    let _ = writeln!(out, "#compdef {}\n", name);
+    let _ = writeln!(out, "{}() {{", function);
+    out.push_str("    local curcontext=\"$curcontext\" state line\n");
+    out.push_str("    _arguments -s -C \\\n");
+    for spec in zsh_specs(cmd) {
+        let _ = writeln!(out, "        {} \\", spec);
+    }
+    out.push_str("        '1: :->command' \\\n");
+    out.push_str("        '*:: :->args'\n\n");
+
+    out.push_str("    case $state in\n");
+    out.push_str("        command)\n");
+    out.push_str("            local -a commands\n");
+    out.push_str("            commands=(\n");
+    for sub in subcommands(cmd) {
+        let _ = writeln!(
+            out,
+            "                '{}:{}'",
+            sub.get_name(),
+            zsh_quote(&command_summary(sub))
+        );
+    }
+    out.push_str("            )\n");
+    out.push_str("            _describe -t commands 'command' commands\n");
+    out.push_str("            ;;\n");
+    out.push_str("        args)\n");
+    out.push_str("            case $line[1] in\n");
+    for sub in subcommands(cmd) {
+        let mut specs = zsh_specs(sub);
+        if positionals(sub).next().is_none() {
+            if let Values::Choices(names) = first_argument(sub) {
+                specs.push(format!("':command:({})'", names.join(" ")));
+            }
+        }
+        let _ = writeln!(
+            out,
+            "                {}) _arguments -s {} ;;",
+            sub.get_name(),
+            specs.join(" ")
+        );
+    }
+    out.push_str("            esac\n");
+    out.push_str("            ;;\n");
+    out.push_str("    esac\n");
+    out.push_str("}\n\n");
+    let _ = writeln!(out, "{} \"$@\"", function);
+    out
+}
+
+/// Quoted for fish, in single quotes
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn fish_option(out: &mut String, name: &str, condition: &str, arg: &Arg) {
+    let mut line = format!("complete -c {} -n {}", name, fish_quote(condition));
+    if let Some(short) = arg.get_short() {
+        let _ = write!(line, " -s {}", short);
+    }
+    if let Some(long) = arg.get_long() {
+        let _ = write!(line, " -l {}", long);
+    }
+    let _ = write!(line, " -d {}", fish_quote(&arg_summary(arg)));
+    if takes_value(arg) {
+        match values(arg) {
+            Values::Any => line.push_str(" -r"),
+            Values::Files => line.push_str(" -r -F"),
+            Values::Choices(choices) => {
+                let _ = write!(line, " -r -a {}", fish_quote(&choices.join(" ")));
+            }
+        }
+    }
+    out.push_str(&line);
+    out.push('\n');
+}
+
+fn fish(cmd: &Command) -> String {
+    let name = cmd.get_name();
+    let mut out = String::new();
+    // Nothing here is a file unless it says so
+    let _ = writeln!(out, "complete -c {} -f", name);
+
+    let top = "__fish_use_subcommand";
+    for arg in options(cmd) {
+        fish_option(&mut out, name, top, arg);
+    }
+    for sub in subcommands(cmd) {
+        let _ = writeln!(
+            out,
+            "complete -c {} -n {} -a {} -d {}",
+            name,
+            fish_quote(top),
+            sub.get_name(),
+            fish_quote(&command_summary(sub))
+        );
+    }
+
+    for sub in subcommands(cmd) {
+        let condition = format!("__fish_seen_subcommand_from {}", sub.get_name());
+        for arg in options(sub) {
+            fish_option(&mut out, name, &condition, arg);
+        }
+        match first_argument(sub) {
+            Values::Choices(choices) => {
+                let _ = writeln!(
+                    out,
+                    "complete -c {} -n {} -a {}",
+                    name,
+                    fish_quote(&condition),
+                    fish_quote(&choices.join(" "))
+                );
+            }
+            Values::Files => {
+                let _ = writeln!(out, "complete -c {} -n {} -F", name, fish_quote(&condition));
+            }
+            Values::Any => {}
+        }
+    }
+    out
+}
+
+/// Escaped for roff
+fn roff(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    escaped
+        .lines()
+        .map(|line| {
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// An argument as shown in a usage line, e.g. `[SNAPSHOT]`
+fn usage(arg: &Arg) -> String {
+    let name = format!("\\fI{}\\fR", roff(&value_name(arg)));
+    let many = arg.get_num_args().is_some_and(|n| n.max_values() > 1);
+    match (arg.is_last_set(), many, arg.is_required_set()) {
+        (true, _, _) => format!("[\\-\\- {}...]", name),
+        (false, true, _) => format!("[{}...]", name),
+        (false, false, true) => name,
+        (false, false, false) => format!("[{}]", name),
+    }
+}
+
+/// The man page, in roff
+pub fn man_page(mut cmd: Command) -> String {
+    cmd.build();
+    let name = cmd.get_name().to_string();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        ".TH {} 1 \"\" \"{} {}\" \"User Commands\"",
+        name.to_uppercase(),
+        name,
+        cmd.get_version().unwrap_or_default()
+    );
+
+    out.push_str(".SH NAME\n");
+    let _ = writeln!(out, "{} \\- {}", name, roff(&command_summary(&cmd)));
+
+    out.push_str(".SH SYNOPSIS\n");
+    let _ = writeln!(out, "\\fB{}\\fR [\\fIOPTIONS\\fR]", roff(&name));
+    out.push_str(".br\n");
+    let _ = writeln!(
+        out,
+        "\\fB{}\\fR [\\fIOPTIONS\\fR] \\fICOMMAND\\fR [\\fIARGS\\fR]",
+        roff(&name)
+    );
+
+    if let Some(about) = cmd.get_long_about().or(cmd.get_about()) {
+        out.push_str(".SH DESCRIPTION\n");
+        let _ = writeln!(out, "{}", roff(&about.to_string()));
+    }
+
+    out.push_str(".SH OPTIONS\n");
+    for arg in options(&cmd) {
+        out.push_str(".TP\n");
+        let flags: Vec<String> = flags(arg)
+            .iter()
+            .map(|flag| format!("\\fB{}\\fR", roff(flag)))
+            .collect();
+        let value = if takes_value(arg) {
+            format!(" \\fI{}\\fR", roff(&value_name(arg)))
+        } else {
+            String::new()
+        };
+        let _ = writeln!(out, "{}{}", flags.join(", "), value);
+        let help = arg.get_long_help().or(arg.get_help());
+        let _ = writeln!(
+            out,
+            "{}",
+            roff(&help.map(ToString::to_string).unwrap_or_default())
+        );
+    }
+
+    out.push_str(".SH COMMANDS\n");
+    for sub in subcommands(&cmd) {
+        out.push_str(".TP\n");
+        let mut line = vec![format!("\\fB{}\\fR", roff(sub.get_name()))];
+        line.extend(positionals(sub).map(usage));
+        let _ = writeln!(out, "{}", line.join(" "));
+        let about = sub.get_long_about().or(sub.get_about());
+        let _ = writeln!(
+            out,
+            "{}",
+            roff(&about.map(ToString::to_string).unwrap_or_default())
+        );
+    }
+
+    out.push_str(".SH FILES\n");
+    out.push_str(".TP\n");
+    let _ = writeln!(out, "\\fI~/.config/{}/config.toml\\fR", roff(&name));
+    out.push_str("Remotes, workspaces, schedules, custom actions and settings\n");
+    out.push_str(".TP\n");
+    let _ = writeln!(out, "\\fI~/.local/state/{0}/{0}.log\\fR", roff(&name));
+    out.push_str("The log, unless the config file moves it\n");
+    out
+}
+
+#[cfg(test)]
+mod tests;
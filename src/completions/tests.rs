@@ -0,0 +1,52 @@
+//! Tests of the generated completion scripts and man page
+
+use super::{man_page, script, Shell};
+use crate::Cli;
+use clap::{CommandFactory, Parser};
+
+#[test]
+fn parses_the_shell() {
+    use crate::subcommand::Action;
+
+    let cli = Cli::try_parse_from(["lxtui", "completions", "zsh"]).unwrap();
+    assert_eq!(cli.action, Some(Action::Completions { shell: Shell::Zsh }));
+    assert!(Cli::try_parse_from(["lxtui", "completions", "tcsh"]).is_err());
+}
+
+#[test]
+fn scripts_cover_options_and_subcommands() {
+    let bash = script(Shell::Bash, Cli::command());
+    assert!(bash.contains("--script) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;"));
+    assert!(bash.contains("completions) COMPREPLY=($(compgen -W \"-h --help bash zsh fish\""));
+    assert!(bash.ends_with("complete -F _lxtui lxtui\n"));
+
+    let zsh = script(Shell::Zsh, Cli::command());
+    assert!(zsh.starts_with("#compdef lxtui\n"));
+    assert!(zsh.contains(
+        "'--script[Run the commands in FILE, one per line, as soon as LXTUI starts]:FILE:_files'"
+    ));
+    assert!(zsh.contains("'start:Start an instance and wait until it runs'"));
+    assert!(zsh.contains("':shell:(bash zsh fish)'"));
+
+    let fish = script(Shell::Fish, Cli::command());
+    assert!(fish.contains("complete -c lxtui -n '__fish_use_subcommand' -l read-only"));
+    assert!(fish.contains(
+        "complete -c lxtui -n '__fish_seen_subcommand_from completions' -a 'bash zsh fish'"
+    ));
+
+    // The helper mode is internal
+    for script in [bash, zsh, fish] {
+        assert!(!script.contains("socket-proxy"));
+    }
+}
+
+#[test]
+fn man_page_lists_options_and_commands() {
+    let page = man_page(Cli::command());
+    assert!(page.starts_with(".TH LXTUI 1 "));
+    assert!(page.contains(".SH NAME\nlxtui \\- "));
+    assert!(page.contains("\\fB\\-\\-remote\\fR \\fIREMOTE\\fR\n"));
+    assert!(page.contains("\\fBsnapshot\\fR \\fINAME\\fR [\\fISNAPSHOT\\fR]\n"));
+    assert!(page.contains("\\fBexec\\fR \\fINAME\\fR [\\-\\- \\fICOMMAND\\fR...]\n"));
+    assert!(!page.contains("socket\\-proxy"));
+}
@@ -0,0 +1,902 @@
+//! Configuration file support
+//!
+//! Loads and saves the LXTUI configuration stored at
+//! `$XDG_CONFIG_HOME/lxtui/config.toml`, and provides helpers for the
+//! credential files kept alongside it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Invalid config file: {0}")]
+    ParseError(#[from] toml::de::Error),
+    #[error("Failed to serialize config: {0}")]
+    SerializeError(#[from] toml::ser::Error),
+    #[error("Remote not found: {0}")]
+    RemoteNotFound(String),
+}
+
+/// How LXTUI authenticates against an HTTPS remote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthType {
+    /// TLS client certificate trusted by the server
+    #[default]
+    Tls,
+    /// OpenID Connect bearer tokens obtained through the device-code flow
+    Oidc,
+    /// The remote's Unix socket forwarded over SSH, authenticated by ssh itself
+    Ssh,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteConfig {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub auth_type: AuthType,
+    /// LXD socket on the remote host, for SSH remotes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket_path: Option<String>,
+}
+
+/// What a scheduled job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduledAction {
+    Backup,
+    Snapshot,
+    Start,
+    Stop,
+}
+
+impl ScheduledAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            ScheduledAction::Backup => "backup",
+            ScheduledAction::Snapshot => "snapshot",
+            ScheduledAction::Start => "start",
+            ScheduledAction::Stop => "stop",
+        }
+    }
+
+    /// Whether the job powers its instance on or off
+    pub fn is_power(self) -> bool {
+        matches!(self, ScheduledAction::Start | ScheduledAction::Stop)
+    }
+
+    /// What a deployment must permit for the job to run
+    pub fn permission(self) -> Permission {
+        match self {
+            ScheduledAction::Backup | ScheduledAction::Snapshot => Permission::Backup,
+            ScheduledAction::Start => Permission::Start,
+            ScheduledAction::Stop => Permission::Stop,
+        }
+    }
+}
+
+/// A backup, snapshot, start or stop run on a cron-like schedule while
+/// LXTUI runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    /// Instance name, optionally qualified with a remote ("remote:name")
+    pub instance: String,
+    pub action: ScheduledAction,
+    /// Five-field cron expression or a shortcut such as `@daily`
+    pub schedule: String,
+}
+
+/// A named filter, sort order and grouping, switched to with a number key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceConfig {
+    pub name: String,
+    /// Filter terms as typed after `:filter`, empty for every container
+    #[serde(default)]
+    pub filter: String,
+    /// `name`, `status`, `type` or `remote`; the server's order without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    #[serde(default)]
+    pub group_by_tag: bool,
+}
+
+/// A container menu entry that runs an external command, e.g. `ssh root@{ip}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomAction {
+    /// Label shown in the container menu
+    pub name: String,
+    /// Run with `sh -c` after substituting `{name}`, `{remote}` and `{ip}`
+    pub command: String,
+    /// Shortcut in the container menu; built-in keys take precedence and
+    /// are warned about
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<char>,
+    /// Wait for Enter before returning to LXTUI, to read the output
+    #[serde(default)]
+    pub pause: bool,
+}
+
+/// A command in the quick exec library, run in instances through LXD
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickAction {
+    /// Label shown in the library
+    pub name: String,
+    /// Run with `sh -c` in each instance
+    pub command: String,
+    /// Runs it from the library; j, k, q and ! are taken by the view
+    pub key: char,
+    /// Ask before running it, for commands such as reboot
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+impl QuickAction {
+    fn new(name: &str, command: &str, key: char, confirm: bool) -> Self {
+        QuickAction {
+            name: name.to_string(),
+            command: command.to_string(),
+            key,
+            confirm,
+        }
+    }
+}
+
+/// The quick exec library; listing any actions replaces the built-in ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuickConfig {
+    pub actions: Vec<QuickAction>,
+    /// A command still running after this many seconds counts as failed
+    pub timeout_secs: u64,
+}
+
+impl QuickConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.max(1))
+    }
+}
+
+impl Default for QuickConfig {
+    fn default() -> Self {
+        QuickConfig {
+            actions: vec![
+                QuickAction::new(
+                    "Upgrade packages",
+                    "apt-get update && DEBIAN_FRONTEND=noninteractive apt-get -y upgrade",
+                    'u',
+                    false,
+                ),
+                QuickAction::new("Journal", "journalctl -e -n 200 --no-pager", 'J', false),
+                QuickAction::new("Disk usage", "df -h", 'd', false),
+                QuickAction::new("Reboot", "reboot", 'R', true),
+            ],
+            timeout_secs: 600,
+        }
+    }
+}
+
+/// How much confirmation an action needs before it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmPolicy {
+    /// Run immediately without asking
+    None,
+    /// A single Enter or y
+    #[default]
+    Simple,
+    /// Type the container name, for protecting production containers
+    Typed,
+}
+
+/// Confirmation policy for each container action
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Confirmations {
+    pub start: ConfirmPolicy,
+    pub stop: ConfirmPolicy,
+    pub restart: ConfirmPolicy,
+    pub delete: ConfirmPolicy,
+}
+
+/// Minimum level of log records written to the log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for log::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => log::LevelFilter::Off,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+/// Log file location, level and rotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Log file; defaults to `lxtui.log` in the user's state directory
+    pub path: Option<PathBuf>,
+    pub level: LogLevel,
+    /// Rotate the file once it grows past this many kilobytes
+    pub max_size_kb: u64,
+    /// Number of rotated files to keep (`lxtui.log.1`, `lxtui.log.2`, ...)
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            path: None,
+            level: LogLevel::default(),
+            max_size_kb: 1024,
+            max_files: 3,
+        }
+    }
+}
+
+impl LoggingConfig {
+    pub fn log_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            dirs::state_dir()
+                .map(|dir| dir.join("lxtui"))
+                .unwrap_or_else(Config::config_dir)
+                .join("lxtui.log")
+        })
+    }
+}
+
+/// Where finished operations are kept between sessions, and for how long
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// History file; defaults to `operations.jsonl` in the user's state directory
+    pub path: Option<PathBuf>,
+    /// Forget operations older than this many days; 0 keeps no history
+    pub retention_days: u32,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            path: None,
+            retention_days: 7,
+        }
+    }
+}
+
+impl HistoryConfig {
+    pub fn history_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            dirs::state_dir()
+                .map(|dir| dir.join("lxtui"))
+                .unwrap_or_else(Config::config_dir)
+                .join("operations.jsonl")
+        })
+    }
+}
+
+/// History of what was typed at prompts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptHistoryConfig {
+    /// Entries kept for each prompt; 0 keeps no history
+    pub limit: usize,
+    /// History file; defaults to `prompt-history.json` in the user's state directory
+    pub path: Option<PathBuf>,
+}
+
+impl Default for PromptHistoryConfig {
+    fn default() -> Self {
+        PromptHistoryConfig {
+            limit: 100,
+            path: None,
+        }
+    }
+}
+
+impl PromptHistoryConfig {
+    pub fn history_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            dirs::state_dir()
+                .map(|dir| dir.join("lxtui"))
+                .unwrap_or_else(Config::config_dir)
+                .join("prompt-history.json")
+        })
+    }
+}
+
+/// Image choices in the new container wizard, and warnings of images
+/// about to expire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImagesConfig {
+    /// How many recently used images to offer first; 0 keeps none
+    pub recent: usize,
+    /// Recent images file; defaults to `recent-images.json` in the user's state directory
+    pub path: Option<PathBuf>,
+    /// Image server whose aliases are searched as you type: `images`,
+    /// `ubuntu` or `ubuntu-daily`; empty turns the search off
+    pub search: String,
+    /// Warn of images LXD deletes within this many days; 0 turns the
+    /// warning off
+    pub expiry_warning_days: u32,
+}
+
+impl Default for ImagesConfig {
+    fn default() -> Self {
+        ImagesConfig {
+            recent: 10,
+            path: None,
+            search: "images".to_string(),
+            expiry_warning_days: 7,
+        }
+    }
+}
+
+impl ImagesConfig {
+    pub fn recent_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            dirs::state_dir()
+                .map(|dir| dir.join("lxtui"))
+                .unwrap_or_else(Config::config_dir)
+                .join("recent-images.json")
+        })
+    }
+}
+
+/// Expectations of the servers LXTUI connects to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Warn in the system menu when a server is older than this version
+    pub minimum_version: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            minimum_version: "5.0".to_string(),
+        }
+    }
+}
+
+/// Thresholds that raise an alert on a container
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertsConfig {
+    /// Alert when memory use reaches this percentage of `limits.memory`
+    pub memory_percent: Option<u8>,
+    /// Alert when a running container stops without LXTUI stopping it
+    pub unexpected_stop: bool,
+    /// Also raise a desktop notification through `notify-send`
+    pub notify: bool,
+}
+
+/// Health probes of instances with a `user.lxtui.healthcheck` command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProbesConfig {
+    /// Seconds between probes of each instance
+    pub interval_secs: u64,
+    /// A probe still running after this many seconds counts as failed
+    pub timeout_secs: u64,
+}
+
+impl Default for ProbesConfig {
+    fn default() -> Self {
+        ProbesConfig {
+            interval_secs: 30,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Which finished operations notify
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyOn {
+    /// Every operation, whether it succeeded or failed
+    #[default]
+    Always,
+    /// Only operations that failed
+    Failure,
+}
+
+/// Bell and command run when an operation finishes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Ring the terminal bell, which tmux and most terminals can flag
+    pub bell: bool,
+    /// Run this with `sh -c`, with the operation in `LXTUI_*` variables
+    pub command: Option<String>,
+    pub on: NotifyOn,
+    /// Leave out operations that finished quicker than this many seconds
+    pub min_duration_secs: u64,
+}
+
+/// Settings new instances get, pre-filled in the wizard so that only a name
+/// has to be typed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CreateDefaults {
+    pub image: String,
+    /// Create virtual machines rather than containers
+    pub vm: bool,
+    /// Profiles applied in order; empty leaves LXD's `default` profile
+    pub profiles: Vec<String>,
+    /// `limits.*` keys without the prefix, e.g. `cpu = "2"`
+    pub limits: BTreeMap<String, String>,
+    /// Storage pool for the root disk, instead of the profiles' one
+    pub pool: Option<String>,
+    /// Network eth0 is attached to, instead of the profiles' one
+    pub network: Option<String>,
+}
+
+impl Default for CreateDefaults {
+    fn default() -> Self {
+        CreateDefaults {
+            image: "ubuntu:24.04".to_string(),
+            vm: false,
+            profiles: Vec::new(),
+            limits: BTreeMap::from([
+                ("cpu".to_string(), "2".to_string()),
+                ("memory".to_string(), "2GB".to_string()),
+            ]),
+            pool: None,
+            network: None,
+        }
+    }
+}
+
+/// What happens when a create or clone would fill its storage pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpaceGuard {
+    /// Don't check
+    Off,
+    /// Ask before going ahead
+    #[default]
+    Warn,
+    /// Refuse
+    Block,
+}
+
+/// Free space check before instances are created or cloned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpaceConfig {
+    pub guard: SpaceGuard,
+    /// Share of the pool, in percent, the new instance may fill it up to
+    pub max_percent: u8,
+}
+
+impl Default for SpaceConfig {
+    fn default() -> Self {
+        SpaceConfig {
+            guard: SpaceGuard::Warn,
+            max_percent: 90,
+        }
+    }
+}
+
+/// Where credentials are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretStore {
+    /// The OS keyring when there is one, files otherwise
+    #[default]
+    Auto,
+    /// Only the OS keyring; storing fails without one
+    Keyring,
+    /// Private files under the config directory
+    File,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    pub store: SecretStore,
+}
+
+/// A kind of change that a deployment can allow or forbid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    Service, // Start or restart the local LXD service
+    Create,
+    Start,
+    Stop,
+    Restart,
+    Delete,
+    Clone, // Copies and refreshes of copies
+    Edit,  // Tags, config, boot settings, metadata and templates
+    Exec,  // Shells, through LXD or SSH, and quick exec commands
+    Custom,
+    Backup, // Backups and snapshots
+    Rebuild,
+    Images,  // Auto-update settings and refreshes of stored images
+    Shell,   // A shell on the host, with the TUI suspended
+    Storage, // Scrubs of the local storage pools
+}
+
+impl Permission {
+    /// Name as written in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Permission::Service => "service",
+            Permission::Create => "create",
+            Permission::Start => "start",
+            Permission::Stop => "stop",
+            Permission::Restart => "restart",
+            Permission::Delete => "delete",
+            Permission::Clone => "clone",
+            Permission::Edit => "edit",
+            Permission::Exec => "exec",
+            Permission::Custom => "custom",
+            Permission::Backup => "backup",
+            Permission::Rebuild => "rebuild",
+            Permission::Images => "images",
+            Permission::Shell => "shell",
+            Permission::Storage => "storage",
+        }
+    }
+}
+
+/// Changes this deployment permits. With an allow list only those are
+/// permitted; anything on the deny list never is.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PermissionsConfig {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<Permission>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<Permission>,
+}
+
+impl PermissionsConfig {
+    pub fn permits(&self, permission: Permission) -> bool {
+        (self.allow.is_empty() || self.allow.contains(&permission))
+            && !self.deny.contains(&permission)
+    }
+}
+
+/// How the interface is drawn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// Mark states with shapes and words and draw them in a high-contrast
+    /// palette instead of red, green and yellow
+    pub high_contrast: bool,
+    /// No spinner, and feedback stays on screen until it is dismissed
+    /// rather than closing or clearing by itself
+    pub reduced_motion: bool,
+    /// Close success and information dialogs after this many seconds; 0
+    /// keeps them open until a key is pressed
+    pub dismiss_after_secs: u64,
+    /// Show the image each instance was created from in the container list
+    pub image_column: bool,
+    /// Open on the host dashboard rather than the container list
+    pub dashboard: bool,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        DisplayConfig {
+            high_contrast: false,
+            reduced_motion: false,
+            dismiss_after_secs: 2,
+            image_column: false,
+            dashboard: false,
+        }
+    }
+}
+
+impl DisplayConfig {
+    /// How long success and information dialogs stay open, if they close
+    pub fn dismiss_after(&self) -> Option<Duration> {
+        match self.dismiss_after_secs {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+}
+
+/// The guided tour offered on first launch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TourConfig {
+    /// Offer the tour when it hasn't been taken or skipped yet
+    pub offer: bool,
+    /// File marking the tour seen; defaults to `tour-seen` in the user's state directory
+    pub path: Option<PathBuf>,
+}
+
+impl Default for TourConfig {
+    fn default() -> Self {
+        TourConfig {
+            offer: true,
+            path: None,
+        }
+    }
+}
+
+impl TourConfig {
+    pub fn seen_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            dirs::state_dir()
+                .map(|dir| dir.join("lxtui"))
+                .unwrap_or_else(Config::config_dir)
+                .join("tour-seen")
+        })
+    }
+}
+
+/// Where the uptime of each instance is kept, and for how long
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UptimeConfig {
+    /// Uptime file; defaults to `uptime.json` in the user's state directory
+    pub path: Option<PathBuf>,
+    /// Forget state changes older than this many days; 0 keeps no uptime
+    pub retention_days: u32,
+}
+
+impl Default for UptimeConfig {
+    fn default() -> Self {
+        UptimeConfig {
+            path: None,
+            retention_days: 30,
+        }
+    }
+}
+
+impl UptimeConfig {
+    pub fn uptime_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            dirs::state_dir()
+                .map(|dir| dir.join("lxtui"))
+                .unwrap_or_else(Config::config_dir)
+                .join("uptime.json")
+        })
+    }
+}
+
+/// Format of the inventory export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InventoryFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl InventoryFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            InventoryFormat::Yaml => "yaml",
+            InventoryFormat::Json => "json",
+        }
+    }
+}
+
+/// Where the inventory export is written, and how
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InventoryConfig {
+    /// Directory the inventory is written to; defaults to `inventory` in the user's state directory
+    pub dir: Option<PathBuf>,
+    pub format: InventoryFormat,
+    /// Leave out status, volatile keys and the time, and overwrite one file
+    /// a server, for keeping the exports in git
+    pub stable: bool,
+}
+
+impl InventoryConfig {
+    pub fn inventory_dir(&self) -> PathBuf {
+        self.dir.clone().unwrap_or_else(|| {
+            dirs::state_dir()
+                .map(|dir| dir.join("lxtui"))
+                .unwrap_or_else(Config::config_dir)
+                .join("inventory")
+        })
+    }
+}
+
+/// The ledger of what each instance uses, for usage reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageConfig {
+    /// Seconds between reads of the metrics endpoint; 0 only reads it for a report
+    pub sample_seconds: u64,
+    /// Directory reports are written to; defaults to `usage` in the user's state directory
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        UsageConfig {
+            sample_seconds: 60,
+            dir: None,
+        }
+    }
+}
+
+impl UsageConfig {
+    pub fn usage_dir(&self) -> PathBuf {
+        self.dir.clone().unwrap_or_else(|| {
+            dirs::state_dir()
+                .map(|dir| dir.join("lxtui"))
+                .unwrap_or_else(Config::config_dir)
+                .join("usage")
+        })
+    }
+}
+
+/// How often lxtui may poll the server: a rate for each kind of poll, and
+/// how far intervals are moved at random
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PollingConfig {
+    /// Share of an interval, in percent, it is made longer or shorter by
+    pub jitter_percent: u8,
+    /// Polls of a kind saved up while it is quiet, to spend at once
+    pub burst: u32,
+    /// Most polls a minute of each kind; 0 leaves the kind unlimited
+    pub operations_per_minute: u32,
+    pub health_per_minute: u32,
+    pub events_per_minute: u32,
+    pub refresh_per_minute: u32,
+    pub views_per_minute: u32,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        PollingConfig {
+            jitter_percent: 20,
+            burst: 3,
+            operations_per_minute: 240,
+            health_per_minute: 15,
+            events_per_minute: 12,
+            refresh_per_minute: 12,
+            views_per_minute: 120,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Show each mutating API request and wait for an explicit send
+    pub preview_requests: bool,
+    /// Disable every action that changes an instance or the server, as
+    /// `--read-only` does
+    pub read_only: bool,
+    pub confirmations: Confirmations,
+    pub logging: LoggingConfig,
+    pub history: HistoryConfig,
+    pub images: ImagesConfig,
+    pub prompt_history: PromptHistoryConfig,
+    pub create: CreateDefaults,
+    pub alerts: AlertsConfig,
+    pub probes: ProbesConfig,
+    pub notifications: NotificationsConfig,
+    pub space: SpaceConfig,
+    pub display: DisplayConfig,
+    pub tour: TourConfig,
+    pub uptime: UptimeConfig,
+    pub inventory: InventoryConfig,
+    pub usage: UsageConfig,
+    pub polling: PollingConfig,
+    /// Colours of remotes and projects, by `remote` or `remote:project`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub accents: BTreeMap<String, String>,
+    pub server: ServerConfig,
+    pub secrets: SecretsConfig,
+    pub permissions: PermissionsConfig,
+    pub quick: QuickConfig,
+    pub remotes: Vec<RemoteConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<ScheduleConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<CustomAction>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub workspaces: Vec<WorkspaceConfig>,
+}
+
+impl Config {
+    /// Directory holding the config file and stored credentials
+    pub fn config_dir() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("lxtui")
+    }
+
+    pub fn path() -> PathBuf {
+        Self::config_dir().join("config.toml")
+    }
+
+    /// Load the config file, falling back to defaults when it doesn't exist
+    pub fn load() -> Result<Self, ConfigError> {
+        match fs::read_to_string(Self::path()) {
+            Ok(text) => Ok(toml::from_str(&text)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let text = toml::to_string_pretty(self)?;
+        write_private(&Self::path(), text.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn remote(&self, name: &str) -> Result<&RemoteConfig, ConfigError> {
+        self.remotes
+            .iter()
+            .find(|r| r.name == name)
+            .ok_or_else(|| ConfigError::RemoteNotFound(name.to_string()))
+    }
+
+    /// Add a remote, replacing any existing remote with the same name
+    pub fn upsert_remote(&mut self, remote: RemoteConfig) {
+        if let Some(existing) = self.remotes.iter_mut().find(|r| r.name == remote.name) {
+            *existing = remote;
+        } else {
+            self.remotes.push(remote);
+        }
+    }
+
+    /// Add a workspace, replacing any existing workspace with the same name
+    pub fn upsert_workspace(&mut self, workspace: WorkspaceConfig) {
+        if let Some(existing) = self
+            .workspaces
+            .iter_mut()
+            .find(|w| w.name == workspace.name)
+        {
+            *existing = workspace;
+        } else {
+            self.workspaces.push(workspace);
+        }
+    }
+}
+
+/// Write a file readable only by the current user, creating parent directories
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(contents)
+}
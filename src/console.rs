@@ -0,0 +1,147 @@
+//! Console log follow view
+//!
+//! Re-reads a container's console log once a second, like `tail -f`. The
+//! view stays at the end until scrolled up, stops polling while paused, and
+//! can search the scrollback. It also shows the log captured when an
+//! instance crashed, which is never re-read.
+
+use crate::crash::Capture;
+use chrono::{DateTime, Local};
+use tokio::time::{Duration, Instant};
+
+/// How often the console log is re-read
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub struct ConsoleView {
+    pub container: String, // qualified name
+    pub lines: Vec<String>,
+    pub scroll: Option<usize>, // First visible line; None follows the end
+    pub paused: bool,
+    pub query: String,
+    pub searching: bool, // Typing into the search field
+    pub no_match: bool,  // The last search found nothing
+    pub error: Option<String>,
+    pub last_poll: Option<Instant>,
+    pub captured: Option<(String, DateTime<Local>)>, // Status and time of the crash shown
+}
+
+impl ConsoleView {
+    pub fn new(container: String) -> Self {
+        ConsoleView {
+            container,
+            lines: Vec::new(),
+            scroll: None,
+            paused: false,
+            query: String::new(),
+            searching: false,
+            no_match: false,
+            error: None,
+            last_poll: None,
+            captured: None,
+        }
+    }
+
+    /// The log captured when `container` crashed
+    pub fn captured(container: String, capture: &Capture) -> Self {
+        let mut view = ConsoleView::new(container);
+        view.record(capture.log.clone(), Instant::now());
+        view.captured = Some((capture.status.clone(), capture.at));
+        view
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        !self.paused
+            && self.captured.is_none()
+            && self
+                .last_poll
+                .is_none_or(|last| now.duration_since(last) >= POLL_INTERVAL)
+    }
+
+    /// Take in the log as read at `now`
+    pub fn record(&mut self, result: Result<String, String>, now: Instant) {
+        self.last_poll = Some(now);
+        match result {
+            Ok(text) => {
+                self.lines = text.lines().map(clean_line).collect();
+                self.error = None;
+                if let Some(scroll) = &mut self.scroll {
+                    // The log is a ring buffer and may have shrunk
+                    *scroll = (*scroll).min(self.lines.len().saturating_sub(1));
+                }
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// First line shown when `page` lines fit on screen
+    pub fn top(&self, page: usize) -> usize {
+        let max = self.lines.len().saturating_sub(page);
+        self.scroll.map_or(max, |scroll| scroll.min(max))
+    }
+
+    /// Scroll by `delta` lines; reaching the bottom follows the end again
+    pub fn scroll_by(&mut self, delta: i32, page: usize) {
+        let max = self.lines.len().saturating_sub(page);
+        let top = (self.top(page) as i64 + delta as i64).clamp(0, max as i64) as usize;
+        self.scroll = (top != max).then_some(top);
+    }
+
+    /// Scroll to the next line after (or before) the top one that matches
+    /// the query, wrapping around. Returns whether there was a match.
+    pub fn find(&mut self, forward: bool, page: usize) -> bool {
+        if self.query.is_empty() {
+            return false;
+        }
+
+        let query = self.query.to_lowercase();
+        let count = self.lines.len();
+        let top = self.top(page);
+        let found = (1..=count)
+            .map(|step| {
+                if forward {
+                    (top + step) % count
+                } else {
+                    (top + count - step % count) % count
+                }
+            })
+            .find(|&i| self.lines[i].to_lowercase().contains(&query));
+
+        if let Some(line) = found {
+            self.scroll = Some(line);
+            self.scroll_by(0, page);
+        }
+        self.no_match = found.is_none();
+        !self.no_match
+    }
+}
+
+/// Drop carriage returns and terminal escape sequences, which would corrupt
+/// the TUI, and expand tabs
+fn clean_line(line: &str) -> String {
+    let mut clean = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => {
+                // CSI sequences end with a letter; others are a single character
+                if chars.next_if_eq(&'[').is_some() {
+                    for c in chars.by_ref() {
+                        if c.is_ascii_alphabetic() || c == '~' {
+                            break;
+                        }
+                    }
+                } else {
+                    chars.next();
+                }
+            }
+            '\t' => clean.push_str("    "),
+            c if c.is_control() => {}
+            c => clean.push(c),
+        }
+    }
+    clean
+}
+
+#[cfg(test)]
+mod tests;
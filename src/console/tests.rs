@@ -0,0 +1,59 @@
+//! Tests of the console view's scrolling, search and output cleaning
+
+use super::{clean_line, ConsoleView};
+use tokio::time::Instant;
+
+const PAGE: usize = 3;
+
+fn view(lines: &[&str]) -> ConsoleView {
+    let mut view = ConsoleView::new("web1".to_string());
+    view.record(Ok(lines.join("\n")), Instant::now());
+    view
+}
+
+#[test]
+fn follows_the_end_until_scrolled() {
+    let mut view = view(&["a", "b", "c", "d", "e"]);
+    assert_eq!(view.top(PAGE), 2);
+
+    view.scroll_by(-1, PAGE);
+    assert_eq!(view.scroll, Some(1));
+    view.record(Ok("a\nb\nc\nd\ne\nf".to_string()), Instant::now());
+    assert_eq!(view.top(PAGE), 1);
+
+    // Scrolling back to the bottom follows new output again
+    view.scroll_by(10, PAGE);
+    assert_eq!(view.scroll, None);
+    assert_eq!(view.top(PAGE), 3);
+}
+
+#[test]
+fn search_wraps_in_both_directions() {
+    let mut view = view(&["boot", "Error one", "ok", "ok", "error two", "ok", "ok"]);
+    view.query = "ERROR".to_string();
+
+    assert!(view.find(true, PAGE));
+    assert_eq!(view.top(PAGE), 1);
+    assert!(view.find(true, PAGE));
+    assert_eq!(view.top(PAGE), 4);
+    assert!(view.find(false, PAGE));
+    assert_eq!(view.top(PAGE), 1);
+
+    view.query = "missing".to_string();
+    assert!(!view.find(true, PAGE));
+}
+
+#[test]
+fn paused_views_are_not_polled() {
+    let mut view = view(&["a"]);
+    view.paused = true;
+    assert!(!view.due(Instant::now() + super::POLL_INTERVAL));
+}
+
+#[test]
+fn escape_sequences_are_dropped() {
+    assert_eq!(
+        clean_line("\u{1b}[32m  OK  \u{1b}[0m] Started\tcron\r"),
+        "  OK  ] Started    cron"
+    );
+}
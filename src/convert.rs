@@ -0,0 +1,181 @@
+//! Recreating an instance as the other type
+//!
+//! Works out which profiles, config, devices and volumes carry over between a
+//! container and a VM, then snapshots the original and creates the other type
+//! from the same image. Files on the root disk are not copied.
+
+use crate::lxd_api::LxdContainer;
+use crate::media;
+use std::collections::{BTreeMap, HashMap};
+
+/// Config keys only containers take; a key ending in `.` covers those it
+/// starts
+const CONTAINER_KEYS: &[&str] = &[
+    "security.privileged",
+    "security.nesting",
+    "security.idmap.",
+    "security.syscalls.",
+    "raw.idmap",
+    "raw.lxc",
+    "raw.seccomp",
+    "linux.kernel_modules",
+    "linux.sysctl.",
+    "limits.kernel.",
+    "limits.processes",
+    "limits.memory.swap",
+    "limits.cpu.allowance",
+    "limits.cpu.priority",
+    "limits.hugepages.",
+    "nvidia.",
+];
+
+/// Config keys only VMs take
+const VM_KEYS: &[&str] = &[
+    "security.secureboot",
+    "security.csm",
+    "security.agent.",
+    "security.sev",
+    "limits.memory.hugepages",
+    "migration.stateful",
+    "raw.qemu",
+    "agent.",
+];
+
+/// Keys LXD sets itself, from the image and as the instance runs
+const GENERATED_KEYS: &[&str] = &["volatile.", "image."];
+
+fn matches(key: &str, patterns: &[&str]) -> bool {
+    patterns
+        .iter()
+        .any(|p| key == *p || (p.ends_with('.') && key.starts_with(p)))
+}
+
+/// An image for the other type of the same OS, from the `image.*` keys:
+/// `ubuntu:noble` for Ubuntu, `images:debian/bookworm` for the rest
+pub fn suggested_image(config: &HashMap<String, String>) -> Option<String> {
+    let os = config.get("image.os")?.to_lowercase();
+    let release = config.get("image.release")?;
+    if os == "ubuntu" {
+        return Some(format!("ubuntu:{}", release));
+    }
+    Some(match config.get("image.variant") {
+        Some(variant) if variant != "default" => format!("images:{}/{}/{}", os, release, variant),
+        _ => format!("images:{}/{}", os, release),
+    })
+}
+
+/// What a recreate does, and the recreate view showing it
+#[derive(Debug, Clone)]
+pub struct Conversion {
+    pub source: String, // qualified name
+    pub to_vm: bool,
+    pub name: String,     // of the new instance, on the source's server
+    pub image: String,    // empty until one is given
+    pub snapshot: String, // taken of the source first
+    pub profiles: Vec<String>,
+    pub config: BTreeMap<String, String>,
+    pub devices: BTreeMap<String, BTreeMap<String, String>>,
+    pub dropped: Vec<(String, &'static str)>, // Left behind, and why
+    pub volumes: Vec<String>,                 // Custom volumes mounted, as pool/volume
+    pub running: bool,
+    pub status: Option<String>, // Why the last recreate wasn't started
+}
+
+impl Conversion {
+    /// Work out how to recreate `instance`, known as `source`, as the other
+    /// type
+    pub fn plan(instance: &LxdContainer, source: String, snapshot: String) -> Self {
+        let to_vm = instance.container_type != "virtual-machine";
+        let mut dropped = Vec::new();
+
+        let mut config = BTreeMap::new();
+        for (key, value) in &instance.config {
+            if matches(key, GENERATED_KEYS) {
+                continue;
+            }
+            let other = if to_vm { CONTAINER_KEYS } else { VM_KEYS };
+            if matches(key, other) {
+                dropped.push((
+                    key.clone(),
+                    if to_vm { "containers only" } else { "VMs only" },
+                ));
+            } else {
+                config.insert(key.clone(), value.clone());
+            }
+        }
+
+        let mut devices = BTreeMap::new();
+        let mut volumes = Vec::new();
+        for (name, device) in &instance.devices {
+            let mut device: BTreeMap<String, String> = device.clone().into_iter().collect();
+            if let Some(why) = left_behind(name, &device, to_vm) {
+                dropped.push((format!("device {}", name), why));
+                continue;
+            }
+            if to_vm && device.remove("shift").is_some() {
+                dropped.push((format!("shift on {}", name), "VMs map no IDs"));
+            }
+            let path = device.get("path").map(String::as_str);
+            if let (Some(pool), Some(volume)) = (device.get("pool"), device.get("source")) {
+                if path != Some("/") {
+                    volumes.push(format!("{}/{}", pool, volume));
+                }
+            }
+            devices.insert(name.clone(), device);
+        }
+        dropped.sort();
+        volumes.sort();
+
+        let name = format!("{}-{}", instance.name, if to_vm { "vm" } else { "ct" });
+        Conversion {
+            source,
+            to_vm,
+            name,
+            image: suggested_image(&instance.config).unwrap_or_default(),
+            snapshot,
+            profiles: instance.profiles.clone(),
+            config,
+            devices,
+            dropped,
+            volumes,
+            running: instance.status == "Running",
+            status: None,
+        }
+    }
+
+    /// The new instance's name, qualified with the source's remote
+    pub fn target(&self) -> String {
+        match self.source.split_once(':') {
+            Some((remote, _)) => format!("{}:{}", remote, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// The type it becomes, for messages
+    pub fn kind(&self) -> &'static str {
+        if self.to_vm {
+            "VM"
+        } else {
+            "container"
+        }
+    }
+}
+
+/// Why a device can't go to the other type, if it can't
+fn left_behind(name: &str, device: &BTreeMap<String, String>, to_vm: bool) -> Option<&'static str> {
+    let kind = device.get("type").map(String::as_str).unwrap_or_default();
+    let iso = name == media::DEVICE
+        || kind == "disk" && device.get("source").is_some_and(|s| s.ends_with(".iso"));
+    match kind {
+        "unix-char" | "unix-block" | "unix-hotplug" if to_vm => Some("containers only"),
+        "pci" if !to_vm => Some("VMs only"),
+        "proxy" if to_vm && device.get("nat").map(String::as_str) != Some("true") => {
+            Some("VMs only take NAT proxies")
+        }
+        "disk" if iso && !to_vm => Some("only VMs boot ISOs"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests;
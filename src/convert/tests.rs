@@ -0,0 +1,104 @@
+//! Tests of recreating an instance as the other type
+
+use super::{suggested_image, Conversion};
+use crate::lxd_api::LxdContainer;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+fn instance(kind: &str, config: Value, devices: Value) -> LxdContainer {
+    serde_json::from_value(json!({
+        "architecture": "x86_64",
+        "config": config,
+        "created_at": "2024-01-01T00:00:00Z",
+        "devices": devices,
+        "ephemeral": false,
+        "last_used_at": "2024-01-01T00:00:00Z",
+        "name": "web1",
+        "profiles": ["default", "web"],
+        "stateful": false,
+        "status": "Stopped",
+        "status_code": 102,
+        "type": kind,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn suggests_the_same_os_for_the_other_type() {
+    let config = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    };
+    let ubuntu = config(&[("image.os", "Ubuntu"), ("image.release", "noble")]);
+    assert_eq!(suggested_image(&ubuntu).as_deref(), Some("ubuntu:noble"));
+    let debian = config(&[
+        ("image.os", "Debian"),
+        ("image.release", "bookworm"),
+        ("image.variant", "cloud"),
+    ]);
+    assert_eq!(
+        suggested_image(&debian).as_deref(),
+        Some("images:debian/bookworm/cloud")
+    );
+    assert_eq!(suggested_image(&config(&[("image.os", "Alpine")])), None);
+}
+
+#[test]
+fn carries_over_what_the_other_type_takes() {
+    let container = instance(
+        "container",
+        json!({
+            "limits.memory": "2GiB",
+            "security.nesting": "true",
+            "raw.idmap": "both 1000 1000",
+            "user.lxtui.tags": "web",
+            "volatile.base_image": "abc",
+            "image.os": "Debian",
+            "image.release": "bookworm",
+        }),
+        json!({
+            "root": {"type": "disk", "path": "/", "pool": "default", "size": "20GiB"},
+            "data": {"type": "disk", "path": "/srv", "pool": "fast", "source": "web-data"},
+            "home": {"type": "disk", "path": "/home", "source": "/srv/home", "shift": "true"},
+            "fuse": {"type": "unix-char", "path": "/dev/fuse"},
+            "http": {"type": "proxy", "listen": "tcp:0.0.0.0:80", "connect": "tcp:127.0.0.1:80"},
+        }),
+    );
+    let plan = Conversion::plan(&container, "web1".to_string(), "recreate".to_string());
+    assert!(plan.to_vm);
+    assert_eq!(plan.name, "web1-vm");
+    assert_eq!(plan.image, "images:debian/bookworm");
+    assert_eq!(plan.profiles, ["default", "web"]);
+    let keys: Vec<&str> = plan.config.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["limits.memory", "user.lxtui.tags"]);
+    let devices: Vec<&str> = plan.devices.keys().map(String::as_str).collect();
+    assert_eq!(devices, ["data", "home", "root"]);
+    assert!(!plan.devices["home"].contains_key("shift"));
+    assert_eq!(plan.volumes, ["fast/web-data"]);
+    let dropped: Vec<&str> = plan.dropped.iter().map(|(what, _)| what.as_str()).collect();
+    assert_eq!(
+        dropped,
+        [
+            "device fuse",
+            "device http",
+            "raw.idmap",
+            "security.nesting",
+            "shift on home"
+        ]
+    );
+
+    // And back: the ISO and secure boot stay with the VM
+    let vm = instance(
+        "virtual-machine",
+        json!({"security.secureboot": "false", "limits.cpu": "2"}),
+        json!({"install": {"type": "disk", "source": "/isos/debian.iso", "boot.priority": "10"}}),
+    );
+    let plan = Conversion::plan(&vm, "web1".to_string(), "recreate".to_string());
+    assert_eq!((plan.kind(), plan.name.as_str()), ("container", "web1-ct"));
+    assert_eq!(plan.image, "");
+    assert_eq!(plan.config.keys().collect::<Vec<_>>(), ["limits.cpu"]);
+    assert!(plan.devices.is_empty());
+    assert_eq!(plan.dropped.len(), 2);
+}
@@ -0,0 +1,123 @@
+//! Console captures of crashed instances
+//!
+//! Reads the console log of an instance that stopped without anyone asking,
+//! before a restart clears it.
+
+use crate::alerts::EXPECTED_STOP_WINDOW;
+use crate::events::LifecycleEvent;
+use crate::lxc::Container;
+use chrono::{DateTime, Local};
+use log::warn;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// Lifecycle events of an instance stopping; with a requestor, someone
+/// asked for it
+const STOP_EVENTS: [&str; 3] = [
+    "instance-stopped",
+    "instance-shutdown",
+    "instance-restarted",
+];
+
+/// The console log of an instance as it was just after it crashed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub status: String, // "Stopped" or "Error"
+    pub at: DateTime<Local>,
+    pub log: Result<String, String>, // The log, or why it couldn't be read
+}
+
+#[derive(Debug)]
+pub struct Crashes {
+    statuses: HashMap<String, String>, // As of the previous check
+    expected_stops: HashMap<String, Instant>,
+    captures: HashMap<String, Capture>, // Latest crash of each, by qualified name
+    tx: mpsc::UnboundedSender<(String, Capture)>,
+    rx: mpsc::UnboundedReceiver<(String, Capture)>,
+}
+
+impl Crashes {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Crashes {
+            statuses: HashMap::new(),
+            expected_stops: HashMap::new(),
+            captures: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Note that LXTUI is about to stop, restart or delete `container`, so
+    /// seeing it stopped is no crash
+    pub fn expect_stop(&mut self, container: &str) {
+        self.expected_stops
+            .insert(container.to_string(), Instant::now());
+    }
+
+    /// Take in a lifecycle event. A stop requested through the API, by
+    /// `lxc stop` or another admin, names who asked; one LXD sends when an
+    /// instance stopped by itself doesn't.
+    pub fn take_in(&mut self, event: &LifecycleEvent) {
+        if STOP_EVENTS.contains(&event.action.as_str()) && event.requestor.is_some() {
+            self.expect_stop(&event.qualified_entity());
+        }
+    }
+
+    /// Check a refreshed container list. Returns the instances that crashed
+    /// since the previous one, with their new status; their console logs
+    /// are due for capture.
+    pub fn check(&mut self, containers: &[Container], now: Instant) -> Vec<(String, String)> {
+        self.expected_stops
+            .retain(|_, at| now.duration_since(*at) < EXPECTED_STOP_WINDOW);
+
+        let mut crashed = Vec::new();
+        let mut statuses = HashMap::new();
+        for container in containers {
+            let name = container.qualified_name();
+            let was_running = self.statuses.get(&name).map(String::as_str) == Some("Running");
+            if was_running
+                && matches!(container.status.as_str(), "Stopped" | "Error")
+                && !self.expected_stops.contains_key(&name)
+            {
+                warn!("{} is {} unexpectedly", name, container.status);
+                crashed.push((name.clone(), container.status.clone()));
+            }
+            statuses.insert(name, container.status.clone());
+        }
+        self.statuses = statuses;
+        crashed
+    }
+
+    /// Where captures are delivered once read
+    pub fn sender(&self) -> mpsc::UnboundedSender<(String, Capture)> {
+        self.tx.clone()
+    }
+
+    /// Take in the captures read since the last call
+    pub fn drain(&mut self) {
+        while let Ok((name, capture)) = self.rx.try_recv() {
+            self.captures.insert(name, capture);
+        }
+    }
+
+    /// The latest crash of an instance, by qualified name
+    pub fn get(&self, container: &str) -> Option<&Capture> {
+        self.captures.get(container)
+    }
+
+    /// Every instance with a capture, most recent crash first
+    pub fn all(&self) -> Vec<(&str, &Capture)> {
+        let mut all: Vec<(&str, &Capture)> = self
+            .captures
+            .iter()
+            .map(|(name, capture)| (name.as_str(), capture))
+            .collect();
+        all.sort_by(|a, b| b.1.at.cmp(&a.1.at).then_with(|| a.0.cmp(b.0)));
+        all
+    }
+}
+
+#[cfg(test)]
+mod tests;
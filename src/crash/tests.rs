@@ -0,0 +1,91 @@
+//! Tests of spotting crashed instances
+
+use super::{Capture, Crashes};
+use crate::command::tests::container;
+use crate::events::LifecycleEvent;
+use chrono::{Local, TimeZone};
+use tokio::time::Instant;
+
+#[test]
+fn spots_instances_that_stop_by_themselves() {
+    let mut crashes = Crashes::new();
+    let now = Instant::now();
+
+    // The first list only records statuses
+    let first = [
+        container("web1", "Running", "container"),
+        container("db1", "Running", "container"),
+        container("vm1", "Stopped", "virtual-machine"),
+    ];
+    assert!(crashes.check(&first, now).is_empty());
+
+    crashes.expect_stop("db1");
+    let second = [
+        container("web1", "Error", "container"),
+        container("db1", "Stopped", "container"),
+        container("vm1", "Running", "virtual-machine"),
+    ];
+    assert_eq!(
+        crashes.check(&second, now),
+        [("web1".to_string(), "Error".to_string())]
+    );
+
+    // Still stopped is not a new crash
+    assert!(crashes.check(&second, now).is_empty());
+}
+
+#[test]
+fn stops_someone_asked_for_are_no_crash() {
+    let mut crashes = Crashes::new();
+    let now = Instant::now();
+    let event = |action: &str, entity: &str, requestor: Option<&str>| LifecycleEvent {
+        at: Local::now(),
+        remote: None,
+        action: action.to_string(),
+        entity: entity.to_string(),
+        requestor: requestor.map(str::to_string),
+    };
+    let running = [
+        container("web1", "Running", "container"),
+        container("db1", "Running", "container"),
+    ];
+    assert!(crashes.check(&running, now).is_empty());
+
+    // `lxc stop web1` names who asked; db1 stopping by itself doesn't
+    crashes.take_in(&event("instance-shutdown", "web1", Some("admin (unix)")));
+    crashes.take_in(&event("instance-stopped", "db1", None));
+    crashes.take_in(&event("instance-started", "db1", Some("admin (unix)")));
+    let stopped = [
+        container("web1", "Stopped", "container"),
+        container("db1", "Stopped", "container"),
+    ];
+    assert_eq!(
+        crashes.check(&stopped, now),
+        [("db1".to_string(), "Stopped".to_string())]
+    );
+}
+
+#[test]
+fn keeps_the_latest_capture_of_each_instance() {
+    let mut crashes = Crashes::new();
+    let capture = |hour, log: &str| Capture {
+        status: "Stopped".to_string(),
+        at: Local.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap(),
+        log: Ok(log.to_string()),
+    };
+    let captures = crashes.sender();
+    captures
+        .send(("web1".to_string(), capture(9, "first")))
+        .unwrap();
+    captures
+        .send(("db1".to_string(), capture(10, "db")))
+        .unwrap();
+    captures
+        .send(("web1".to_string(), capture(11, "second")))
+        .unwrap();
+    crashes.drain();
+
+    assert_eq!(crashes.get("web1").unwrap().log.as_deref(), Ok("second"));
+    let names: Vec<&str> = crashes.all().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["web1", "db1"]);
+}
@@ -0,0 +1,150 @@
+//! Host at a glance
+//!
+//! An optional start screen summing up the listed servers: how many
+//! instances run, the latest lifecycle events, active warnings, the
+//! heaviest running instances and how full each storage pool of the active
+//! server is. Enter drills into the container list.
+
+use crate::app::App;
+use crate::lxc::Container;
+use tokio::time::{Duration, Instant};
+
+/// How often storage pool usage is read
+pub const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Rows shown in the events and top consumers sections
+pub const RECENT_EVENTS: usize = 5;
+pub const TOP_CONSUMERS: usize = 5;
+
+/// Space used in one storage pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolUsage {
+    pub name: String,
+    pub driver: String,
+    pub used: u64,  // Bytes
+    pub total: u64, // Bytes; 0 when the pool's size couldn't be read
+}
+
+impl PoolUsage {
+    /// Share of the pool in use, None when its size is unknown
+    pub fn fraction(&self) -> Option<f64> {
+        (self.total > 0).then(|| self.used as f64 / self.total as f64)
+    }
+}
+
+/// Instances by state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub running: usize,
+    pub stopped: usize,
+    pub other: usize, // Frozen, starting, in error...
+}
+
+impl Counts {
+    pub fn of(containers: &[Container]) -> Self {
+        let mut counts = Counts::default();
+        for container in containers {
+            match container.status.as_str() {
+                "Running" => counts.running += 1,
+                "Stopped" => counts.stopped += 1,
+                _ => counts.other += 1,
+            }
+        }
+        counts
+    }
+}
+
+#[derive(Debug)]
+pub struct Dashboard {
+    pub pools: Vec<PoolUsage>,
+    pub error: Option<String>, // Why the last read of the pools failed
+    pub last_poll: Option<Instant>,
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        Dashboard {
+            pools: Vec::new(),
+            error: None,
+            last_poll: None,
+        }
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        self.last_poll
+            .is_none_or(|last| now.duration_since(last) >= POLL_INTERVAL)
+    }
+
+    /// Take in the pool usage read at `now`; a failure keeps the previous
+    /// figures on screen
+    pub fn record(&mut self, result: Result<Vec<PoolUsage>, String>, now: Instant) {
+        self.last_poll = Some(now);
+        match result {
+            Ok(pools) => {
+                self.pools = pools;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+/// Everything that needs attention: the server, schedules, the event feed,
+/// usage alerts, instances still down after a crash and failing health
+/// checks
+pub fn warnings(app: &App) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if !app.lxd_status {
+        warnings.push("LXD is not running".to_string());
+    }
+    if let Some(details) = &app.server_details {
+        if let Some(minimum) = &details.outdated {
+            warnings.push(format!(
+                "{} {} is older than {}",
+                details.server, details.version, minimum
+            ));
+        }
+    }
+    if let Some(warning) = &app.schedule_warning {
+        warnings.push(format!("Schedule: {}", warning));
+    }
+    for warning in &app.config_warnings {
+        warnings.push(format!("Config: {}", warning));
+    }
+    if let Some(error) = &app.events.error {
+        warnings.push(format!("Events: {}", error));
+    }
+    for (name, alert) in app.alerts.active() {
+        warnings.push(format!("{}: {}", name, alert.message));
+    }
+    let running: Vec<String> = app
+        .containers
+        .try_read()
+        .map(|containers| {
+            containers
+                .iter()
+                .filter(|c| c.status == "Running")
+                .map(Container::qualified_name)
+                .collect()
+        })
+        .unwrap_or_default();
+    for (name, capture) in app.crashes.all() {
+        if running.iter().any(|r| r == name) {
+            continue;
+        }
+        warnings.push(format!(
+            "{}: {} at {} (F for its console)",
+            name,
+            capture.status.to_lowercase(),
+            capture.at.format("%H:%M:%S")
+        ));
+    }
+    for (name, probe) in app.probes.unhealthy() {
+        let reason = probe.output.lines().last().unwrap_or("no output");
+        warnings.push(format!("{}: unhealthy, {}", name, reason));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests;
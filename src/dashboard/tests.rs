@@ -0,0 +1,53 @@
+//! Tests of the dashboard's counts and pool usage
+
+use super::{Counts, Dashboard, PoolUsage, POLL_INTERVAL};
+use crate::command::tests::container;
+use tokio::time::Instant;
+
+fn pool(name: &str, used: u64, total: u64) -> PoolUsage {
+    PoolUsage {
+        name: name.to_string(),
+        driver: "zfs".to_string(),
+        used,
+        total,
+    }
+}
+
+#[test]
+fn counts_instances_by_state() {
+    let containers = [
+        container("web1", "Running", "container"),
+        container("web2", "Running", "container"),
+        container("db1", "Stopped", "container"),
+        container("vm1", "Frozen", "virtual-machine"),
+    ];
+    assert_eq!(
+        Counts::of(&containers),
+        Counts {
+            running: 2,
+            stopped: 1,
+            other: 1,
+        }
+    );
+}
+
+#[test]
+fn unknown_pool_size_has_no_fraction() {
+    assert_eq!(pool("default", 1 << 30, 4 << 30).fraction(), Some(0.25));
+    assert_eq!(pool("broken", 0, 0).fraction(), None);
+}
+
+#[test]
+fn failed_read_keeps_the_previous_pools() {
+    let start = Instant::now();
+    let mut dashboard = Dashboard::new();
+    assert!(dashboard.due(start));
+
+    dashboard.record(Ok(vec![pool("default", 1, 2)]), start);
+    assert!(!dashboard.due(start));
+    assert!(dashboard.due(start + POLL_INTERVAL));
+
+    dashboard.record(Err("connection refused".to_string()), start + POLL_INTERVAL);
+    assert_eq!(dashboard.pools, [pool("default", 1, 2)]);
+    assert_eq!(dashboard.error.as_deref(), Some("connection refused"));
+}
@@ -0,0 +1,282 @@
+//! Inventory drift
+//!
+//! Compares an exported inventory with the server now, as dotted paths such
+//! as `config.limits.cpu`, ignoring running state and `volatile.*` keys.
+
+use crate::config::InventoryFormat;
+use crate::inventory::{self, Inventory, InventoryError};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// What the comparison covers, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Section {
+    Instances,
+    Profiles,
+    Networks,
+    StoragePools,
+    Images,
+}
+
+impl Section {
+    pub fn name(self) -> &'static str {
+        match self {
+            Section::Instances => "Instances",
+            Section::Profiles => "Profiles",
+            Section::Networks => "Networks",
+            Section::StoragePools => "Storage pools",
+            Section::Images => "Images",
+        }
+    }
+}
+
+/// How one setting differs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    pub path: String,
+    pub then: Option<String>, // None when it was added since
+    pub now: Option<String>,  // None when it was removed since
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added,
+    Removed,
+    Changed(Vec<Difference>),
+}
+
+/// One entry that is not as it was exported
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    pub section: Section,
+    pub name: String,
+    pub change: Change,
+}
+
+/// Whether a flattened path is one that changes by itself
+fn ignored(path: &str) -> bool {
+    path == "status" || path.starts_with("config.volatile.")
+}
+
+/// `value` as dotted paths to text: objects by key, lists joined by commas
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten(&join(key), value, out);
+            }
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| match item {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                })
+                .collect();
+            out.insert(prefix.to_string(), items.join(", "));
+        }
+        Value::String(text) => {
+            out.insert(prefix.to_string(), text.clone());
+        }
+        Value::Null => {}
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+fn settings(entry: &impl Serialize) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    if let Ok(value) = serde_json::to_value(entry) {
+        flatten("", &value, &mut out);
+    }
+    out.retain(|path, _| !ignored(path));
+    out
+}
+
+/// The settings that differ between two versions of an entry
+pub fn differences(then: &impl Serialize, now: &impl Serialize) -> Vec<Difference> {
+    let (then, now) = (settings(then), settings(now));
+    let mut paths: Vec<&String> = then.keys().chain(now.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    paths
+        .into_iter()
+        .filter(|path| then.get(*path) != now.get(*path))
+        .map(|path| Difference {
+            path: path.clone(),
+            then: then.get(path).cloned(),
+            now: now.get(path).cloned(),
+        })
+        .collect()
+}
+
+fn compare<T: Serialize>(
+    section: Section,
+    then: &BTreeMap<String, T>,
+    now: &BTreeMap<String, T>,
+    drifts: &mut Vec<Drift>,
+) {
+    let mut names: Vec<&String> = then.keys().chain(now.keys()).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        let change = match (then.get(name), now.get(name)) {
+            (None, Some(_)) => Change::Added,
+            (Some(_), None) => Change::Removed,
+            (Some(then), Some(now)) => match differences(then, now) {
+                differences if differences.is_empty() => continue,
+                differences => Change::Changed(differences),
+            },
+            (None, None) => continue,
+        };
+        drifts.push(Drift {
+            section,
+            name: name.clone(),
+            change,
+        });
+    }
+}
+
+/// Everything in `now` that isn't as it was in `then`
+pub fn diff(then: &Inventory, now: &Inventory) -> Vec<Drift> {
+    let mut drifts = Vec::new();
+    compare(
+        Section::Instances,
+        &then.instances,
+        &now.instances,
+        &mut drifts,
+    );
+    compare(
+        Section::Profiles,
+        &then.profiles,
+        &now.profiles,
+        &mut drifts,
+    );
+    compare(
+        Section::Networks,
+        &then.networks,
+        &now.networks,
+        &mut drifts,
+    );
+    compare(
+        Section::StoragePools,
+        &then.storage_pools,
+        &now.storage_pools,
+        &mut drifts,
+    );
+    compare(Section::Images, &then.images, &now.images, &mut drifts);
+    drifts
+}
+
+/// Read an exported inventory, as JSON when the file says so and YAML
+/// otherwise
+pub fn load(path: &Path) -> Result<Inventory, InventoryError> {
+    let text = std::fs::read_to_string(path)?;
+    let json = path.extension().and_then(|e| e.to_str()) == Some(InventoryFormat::Json.extension());
+    Ok(if json {
+        serde_json::from_str(&text)?
+    } else {
+        serde_yaml::from_str(&text)?
+    })
+}
+
+/// Whether `name` is a file the inventory export wrote for `server`:
+/// `inventory-SERVER.yaml`, or with the time before the extension
+fn is_export_of(name: &str, server: &str) -> bool {
+    let Some(stem) = [InventoryFormat::Yaml, InventoryFormat::Json]
+        .iter()
+        .find_map(|format| name.strip_suffix(&format!(".{}", format.extension())))
+    else {
+        return false;
+    };
+    let own = inventory::file_name(server, InventoryFormat::Yaml, None);
+    let Some(rest) = stem.strip_prefix(own.trim_end_matches(".yaml")) else {
+        return false;
+    };
+    rest.is_empty()
+        || rest.strip_prefix('-').is_some_and(|stamp| {
+            stamp.len() == "20240101-120000".len()
+                && stamp.chars().all(|c| c.is_ascii_digit() || c == '-')
+        })
+}
+
+/// The newest export of `server` in `dir`
+pub fn latest_export(dir: &Path, server: &str) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| is_export_of(&entry.file_name().to_string_lossy(), server))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max()
+        .map(|(_, path)| path)
+}
+
+/// The drift view: an export against the server now
+#[derive(Debug)]
+pub struct DriftView {
+    pub file: PathBuf,
+    pub exported_from: String, // The server the file was exported from
+    pub exported_at: Option<String>, // Stable exports don't say
+    pub server: String,        // The server compared with
+    pub drifts: Vec<Drift>,
+    pub selected: usize,
+    pub error: Option<String>, // Why the file or the server couldn't be read
+}
+
+impl DriftView {
+    pub fn new(file: PathBuf, server: String) -> Self {
+        DriftView {
+            file,
+            exported_from: String::new(),
+            exported_at: None,
+            server,
+            drifts: Vec::new(),
+            selected: 0,
+            error: None,
+        }
+    }
+
+    pub fn set_inventories(&mut self, then: &Inventory, now: &Inventory) {
+        self.exported_from = then.server.clone();
+        self.exported_at = then.exported_at.clone();
+        self.drifts = diff(then, now);
+        self.error = None;
+        self.selected = self.selected.min(self.drifts.len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.drifts.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.drifts.len()
+        } else {
+            (self.selected + self.drifts.len() - 1) % self.drifts.len()
+        };
+    }
+
+    /// Count of entries added, removed and changed
+    pub fn tally(&self) -> (usize, usize, usize) {
+        let count =
+            |wanted: fn(&Change) -> bool| self.drifts.iter().filter(|d| wanted(&d.change)).count();
+        (
+            count(|c| *c == Change::Added),
+            count(|c| *c == Change::Removed),
+            count(|c| matches!(c, Change::Changed(_))),
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,124 @@
+//! Tests of inventory drift
+
+use super::{diff, is_export_of, latest_export, load, Change, Difference, DriftView, Section};
+use crate::config::InventoryFormat;
+use crate::inventory::{Instance, Inventory, Profile};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+pub fn instance(config: &[(&str, &str)], status: &str) -> Instance {
+    Instance {
+        kind: "container".to_string(),
+        architecture: "x86_64".to_string(),
+        description: String::new(),
+        profiles: vec!["default".to_string()],
+        ephemeral: false,
+        status: Some(status.to_string()),
+        config: config
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        devices: BTreeMap::new(),
+    }
+}
+
+pub fn exported() -> Inventory {
+    let mut inventory = Inventory {
+        server: "prod".to_string(),
+        ..Inventory::default()
+    };
+    inventory.instances.insert(
+        "web1".to_string(),
+        instance(&[("limits.cpu", "2"), ("volatile.uuid", "a")], "Running"),
+    );
+    inventory
+        .instances
+        .insert("db1".to_string(), instance(&[], "Running"));
+    inventory.profiles.insert(
+        "default".to_string(),
+        Profile {
+            description: String::new(),
+            config: BTreeMap::new(),
+            devices: BTreeMap::new(),
+        },
+    );
+    inventory
+}
+
+#[test]
+fn finds_what_was_added_removed_and_changed() {
+    let then = exported();
+    let mut now = exported();
+    now.instances.remove("db1");
+    now.instances
+        .insert("web2".to_string(), instance(&[], "Stopped"));
+    let web1 = now.instances.get_mut("web1").unwrap();
+    web1.config
+        .insert("limits.cpu".to_string(), "4".to_string());
+    web1.config
+        .insert("limits.memory".to_string(), "2GiB".to_string());
+    web1.profiles.push("monitoring".to_string());
+    // Changes by itself, so not drift
+    web1.status = None;
+    web1.config
+        .insert("volatile.uuid".to_string(), "b".to_string());
+
+    let drifts = diff(&then, &now);
+    let summary: Vec<(Section, &str)> = drifts
+        .iter()
+        .map(|d| (d.section, d.name.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (Section::Instances, "db1"),
+            (Section::Instances, "web1"),
+            (Section::Instances, "web2"),
+        ]
+    );
+    assert_eq!(drifts[0].change, Change::Removed);
+    assert_eq!(drifts[2].change, Change::Added);
+    let Change::Changed(differences) = &drifts[1].change else {
+        panic!("web1 should have changed");
+    };
+    let difference = |path: &str, then: Option<&str>, now: Option<&str>| Difference {
+        path: path.to_string(),
+        then: then.map(str::to_string),
+        now: now.map(str::to_string),
+    };
+    assert_eq!(
+        differences,
+        &[
+            difference("config.limits.cpu", Some("2"), Some("4")),
+            difference("config.limits.memory", None, Some("2GiB")),
+            difference("profiles", Some("default"), Some("default, monitoring")),
+        ]
+    );
+
+    let mut view = DriftView::new("inventory-prod.yaml".into(), "prod".to_string());
+    view.set_inventories(&then, &now);
+    assert_eq!(view.tally(), (1, 1, 1));
+    assert!(diff(&then, &then).is_empty());
+}
+
+#[test]
+fn reads_exports_back_and_finds_the_newest() {
+    assert!(is_export_of("inventory-prod.yaml", "prod"));
+    assert!(is_export_of("inventory-prod-20240101-120000.json", "prod"));
+    assert!(!is_export_of("inventory-prod-eu.yaml", "prod"));
+    assert!(!is_export_of("inventory-prod.txt", "prod"));
+
+    let dir = std::env::temp_dir().join(format!("lxtui-drift-{}", Uuid::new_v4()));
+    assert_eq!(latest_export(&dir, "prod"), None);
+    let inventory = exported();
+    let path = inventory
+        .save(&dir, InventoryFormat::Json, Some("20240101-120000"))
+        .unwrap();
+    assert_eq!(latest_export(&dir, "prod"), Some(path.clone()));
+    assert_eq!(latest_export(&dir, "local"), None);
+    assert_eq!(load(&path).unwrap(), inventory);
+
+    std::fs::write(dir.join("broken.yaml"), "instances: [").unwrap();
+    assert!(load(&dir.join("broken.yaml")).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,253 @@
+//! Instance config editing
+//!
+//! The editable part of an instance goes to a YAML file for the user's
+//! editor, as `lxc config edit` does. What comes back is validated before
+//! it replaces the instance's config with a PUT.
+
+use crate::hooks::ExternalCommand;
+use crate::lxd_api::LxdContainer;
+use crate::reboot;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+use uuid::Uuid;
+
+const HEADER: &str = "\
+### This is a YAML representation of the instance's configuration.
+### Lines starting with '#' are ignored.
+###
+### Saving with invalid YAML or values shows the error and keeps your edits;
+### saving the file unchanged leaves the instance alone.
+";
+
+#[derive(Debug, Error)]
+pub enum EditError {
+    #[error("Invalid YAML: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("'{0}' must be a single value, not a list or mapping")]
+    NotScalar(String),
+    #[error("Device '{0}' has no type")]
+    UntypedDevice(String),
+    #[error("'{key}' must be {expected}, not '{value}'")]
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: String,
+    },
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// The fields of an instance a PUT replaces
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceConfig {
+    pub architecture: String,
+    pub config: BTreeMap<String, String>,
+    pub devices: BTreeMap<String, BTreeMap<String, String>>,
+    pub ephemeral: bool,
+    pub profiles: Vec<String>,
+    pub stateful: bool,
+    pub description: String,
+}
+
+/// The text as typed, with values that YAML reads as numbers or booleans
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Edited {
+    architecture: String,
+    #[serde(default)]
+    config: BTreeMap<String, Value>,
+    #[serde(default)]
+    devices: BTreeMap<String, BTreeMap<String, Value>>,
+    #[serde(default)]
+    ephemeral: bool,
+    #[serde(default)]
+    profiles: Vec<String>,
+    #[serde(default)]
+    stateful: bool,
+    #[serde(default)]
+    description: String,
+}
+
+impl From<&LxdContainer> for InstanceConfig {
+    fn from(instance: &LxdContainer) -> Self {
+        InstanceConfig {
+            architecture: instance.architecture.clone(),
+            config: instance.config.clone().into_iter().collect(),
+            devices: instance
+                .devices
+                .iter()
+                .map(|(name, options)| (name.clone(), options.clone().into_iter().collect()))
+                .collect(),
+            ephemeral: instance.ephemeral,
+            profiles: instance.profiles.clone(),
+            stateful: instance.stateful,
+            description: instance.description.clone(),
+        }
+    }
+}
+
+impl InstanceConfig {
+    /// The file handed to the editor, with `notes` as comments after the
+    /// header
+    pub fn to_yaml(&self, notes: &[String]) -> Result<String, EditError> {
+        let mut text = HEADER.to_string();
+        if !notes.is_empty() {
+            text.push_str("###\n");
+        }
+        for note in notes {
+            text.push_str(&format!("### {}\n", note));
+        }
+        Ok(text + &serde_yaml::to_string(self)?)
+    }
+
+    /// Validate an edited file. Unquoted numbers and booleans in config and
+    /// device values are taken as the strings LXD stores them as.
+    pub fn parse(text: &str) -> Result<Self, EditError> {
+        let edited: Edited = serde_yaml::from_str(text)?;
+
+        let config = edited
+            .config
+            .into_iter()
+            .map(|(key, value)| {
+                let value = scalar(&key, value)?;
+                match reboot::check(&key, &value) {
+                    Some(expected) => Err(EditError::InvalidValue {
+                        key,
+                        value,
+                        expected,
+                    }),
+                    None => Ok((key, value)),
+                }
+            })
+            .collect::<Result<_, EditError>>()?;
+
+        let mut devices = BTreeMap::new();
+        for (device, options) in edited.devices {
+            if !options.contains_key("type") {
+                return Err(EditError::UntypedDevice(device));
+            }
+            let options = options
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = scalar(&format!("{}.{}", device, key), value)?;
+                    Ok((key, value))
+                })
+                .collect::<Result<_, EditError>>()?;
+            devices.insert(device, options);
+        }
+
+        Ok(InstanceConfig {
+            architecture: edited.architecture,
+            config,
+            devices,
+            ephemeral: edited.ephemeral,
+            profiles: edited.profiles,
+            stateful: edited.stateful,
+            description: edited.description,
+        })
+    }
+
+    /// The body of the PUT request
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+/// What an edited file replaces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditKind {
+    Config,
+    Metadata,         // The image metadata
+    Template(String), // A template file, by name
+    Publish,          // The image the instance is published as
+    Notes,            // The instance's notes, as Markdown
+}
+
+/// A config file handed to the editor, or kept after failing validation
+#[derive(Debug, Clone)]
+pub struct ConfigEdit {
+    pub container: String, // qualified name
+    pub kind: EditKind,
+    pub path: PathBuf,
+    pub original: String, // As written, to tell whether anything changed
+    pub open: bool,       // In the editor now
+}
+
+impl ConfigEdit {
+    /// Write `instance`'s config to a new temporary file, with what a host
+    /// reboot and an evacuation do to it explained at the top
+    pub fn create(container: &str, instance: &LxdContainer) -> Result<Self, EditError> {
+        let text = InstanceConfig::from(instance).to_yaml(&reboot::notes(instance))?;
+        Self::write(container, EditKind::Config, &text)
+    }
+
+    /// Write `text` to a new temporary file named so editors recognise it
+    pub fn write(container: &str, kind: EditKind, text: &str) -> Result<Self, EditError> {
+        let suffix = match &kind {
+            EditKind::Config | EditKind::Metadata | EditKind::Publish => ".yaml".to_string(),
+            EditKind::Template(name) => format!("-{}", name),
+            EditKind::Notes => "-notes.md".to_string(),
+        };
+        let path = std::env::temp_dir().join(format!(
+            "lxtui-{}-{}{}",
+            container.replace(':', "-"),
+            Uuid::new_v4(),
+            suffix
+        ));
+        fs::write(&path, text)?;
+        Ok(ConfigEdit {
+            container: container.to_string(),
+            kind,
+            path,
+            original: text.to_string(),
+            open: false,
+        })
+    }
+
+    /// The command opening the file in `$VISUAL` or `$EDITOR`, else vi
+    pub fn command(&self) -> ExternalCommand {
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok()
+            .filter(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| "vi".to_string());
+        let name = match &self.kind {
+            EditKind::Config => format!("Editing '{}'", self.container),
+            EditKind::Metadata => format!("Editing the metadata of '{}'", self.container),
+            EditKind::Template(template) => format!("Editing {} of '{}'", template, self.container),
+            EditKind::Publish => format!("Publishing '{}'", self.container),
+            EditKind::Notes => format!("Editing the notes of '{}'", self.container),
+        };
+        ExternalCommand {
+            name,
+            command: format!("{} '{}'", editor, self.path.display()),
+            pause: false,
+        }
+    }
+
+    pub fn read(&self) -> io::Result<String> {
+        fs::read_to_string(&self.path)
+    }
+
+    pub fn discard(self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+pub fn scalar(key: &str, value: Value) -> Result<String, EditError> {
+    match value {
+        Value::String(s) => Ok(s),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Null => Ok(String::new()),
+        _ => Err(EditError::NotScalar(key.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,82 @@
+//! Tests of the instance config edit file
+
+use super::{ConfigEdit, EditError, InstanceConfig};
+use crate::compare::tests::instance;
+use serde_json::json;
+
+#[test]
+fn round_trips_through_yaml() {
+    let mut instance = instance("web1", &["default"], json!({}));
+    instance.config = [("limits.cpu".to_string(), "2".to_string())].into();
+    instance.devices = [(
+        "root".to_string(),
+        [
+            ("type".to_string(), "disk".to_string()),
+            ("path".to_string(), "/".to_string()),
+        ]
+        .into(),
+    )]
+    .into();
+    let config = InstanceConfig::from(&instance);
+
+    let yaml = config.to_yaml(&[]).unwrap();
+    assert!(yaml.starts_with("### "));
+    assert!(yaml.contains("limits.cpu: '2'"));
+    assert_eq!(InstanceConfig::parse(&yaml).unwrap(), config);
+}
+
+#[test]
+fn unquoted_scalars_become_strings() {
+    let config = InstanceConfig::parse(
+        "architecture: x86_64\n\
+         config:\n  limits.cpu: 4\n  security.nesting: true\n\
+         devices:\n  eth0:\n    type: nic\n    mtu: 1500\n",
+    )
+    .unwrap();
+    assert_eq!(config.config["limits.cpu"], "4");
+    assert_eq!(config.config["security.nesting"], "true");
+    assert_eq!(config.devices["eth0"]["mtu"], "1500");
+    assert_eq!(config.profiles, Vec::<String>::new());
+}
+
+#[test]
+fn rejects_invalid_edits() {
+    assert!(matches!(
+        InstanceConfig::parse("architecture: [x86_64"),
+        Err(EditError::Yaml(_))
+    ));
+    assert!(matches!(
+        InstanceConfig::parse("architecture: x86_64\nconfig:\n  limits.cpu: [1, 2]\n"),
+        Err(EditError::NotScalar(key)) if key == "limits.cpu"
+    ));
+    assert!(matches!(
+        InstanceConfig::parse("architecture: x86_64\ndevices:\n  eth0:\n    mtu: 1500\n"),
+        Err(EditError::UntypedDevice(device)) if device == "eth0"
+    ));
+    assert!(matches!(
+        InstanceConfig::parse("architecture: x86_64\nconfig:\n  cluster.evacuate: move\n"),
+        Err(EditError::InvalidValue { key, .. }) if key == "cluster.evacuate"
+    ));
+    // A typo in a field name is not silently dropped
+    assert!(matches!(
+        InstanceConfig::parse("architecture: x86_64\nprofile: [default]\n"),
+        Err(EditError::Yaml(_))
+    ));
+}
+
+#[test]
+fn explains_reboot_and_evacuation_at_the_top() {
+    let mut instance = instance("web1", &["default"], json!({"boot.autostart": "true"}));
+    instance.config = [("boot.autostart".to_string(), "true".to_string())].into();
+    let edit = ConfigEdit::create("web1", &instance).unwrap();
+
+    let text = edit.read().unwrap();
+    assert!(text.contains("### On host reboot:\n###   starts again, as boot.autostart is true"));
+    assert!(text.contains("### On evacuation of its cluster member:\n"));
+    // Comments only: the file reads back as the instance's config
+    assert_eq!(
+        InstanceConfig::parse(&text).unwrap(),
+        InstanceConfig::from(&instance)
+    );
+    edit.discard();
+}
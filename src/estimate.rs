@@ -0,0 +1,116 @@
+//! Time remaining for long operations
+//!
+//! Creates, clones and backups can take minutes. While LXD reports a
+//! percentage the remaining time is extrapolated from it; otherwise it is
+//! judged against how long the same kind of operation took before.
+
+use serde_json::Value;
+use std::time::Duration;
+
+/// Progress below this is too early to extrapolate from
+const MIN_PROGRESS: i32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Estimate {
+    Remaining(Duration),
+    LongerThanUsual, // Past every earlier run of the same kind
+}
+
+impl Estimate {
+    pub fn describe(&self) -> String {
+        match self {
+            Estimate::Remaining(left) => format!("About {} left", format_duration(*left)),
+            Estimate::LongerThanUsual => "Taking longer than usual".to_string(),
+        }
+    }
+}
+
+/// Estimate the time left from the reported progress, falling back to the
+/// median duration of earlier successful runs
+pub fn estimate(elapsed: Duration, progress: Option<i32>, past: &[Duration]) -> Option<Estimate> {
+    if let Some(progress) = progress.filter(|p| (MIN_PROGRESS..100).contains(p)) {
+        let total = elapsed.as_secs_f64() * 100.0 / f64::from(progress);
+        let left = Duration::from_secs_f64(total) - elapsed;
+        return Some(Estimate::Remaining(left));
+    }
+
+    let mut past = past.to_vec();
+    if past.is_empty() {
+        return None;
+    }
+    past.sort();
+    let median = past[past.len() / 2];
+    let slowest = past[past.len() - 1];
+
+    Some(if elapsed < median {
+        Estimate::Remaining(median - elapsed)
+    } else if elapsed < slowest {
+        // Slower than usual, but earlier runs have taken this long
+        Estimate::Remaining(slowest - elapsed)
+    } else {
+        Estimate::LongerThanUsual
+    })
+}
+
+/// How far along an operation is, in percent: the reported progress, or
+/// else its elapsed time against the median of earlier runs, held below 100
+/// until it finishes
+pub fn percent_done(elapsed: Duration, progress: Option<i32>, past: &[Duration]) -> Option<i32> {
+    if let Some(progress) = progress {
+        return Some(progress.clamp(0, 100));
+    }
+
+    let mut past = past.to_vec();
+    if past.is_empty() {
+        return None;
+    }
+    past.sort();
+    let median = past[past.len() / 2].as_secs_f64().max(1.0);
+    Some((elapsed.as_secs_f64() * 100.0 / median).min(99.0) as i32)
+}
+
+/// Overall progress of several operations: the mean of the ones whose
+/// percentage is known
+pub fn overall(percentages: impl Iterator<Item = Option<i32>>) -> Option<i32> {
+    let known: Vec<i32> = percentages.flatten().collect();
+    if known.is_empty() {
+        return None;
+    }
+    Some(known.iter().sum::<i32>() / known.len() as i32)
+}
+
+/// Percentage reported in an operation's metadata: a numeric "progress"
+/// field, or a status string such as "rootfs: 45% (12.3MB/s)"
+pub fn progress(metadata: &Value) -> Option<i32> {
+    if let Some(progress) = metadata.get("progress").and_then(Value::as_i64) {
+        return i32::try_from(progress).ok();
+    }
+
+    metadata
+        .as_object()?
+        .values()
+        .filter_map(Value::as_str)
+        .find_map(percentage)
+}
+
+fn percentage(text: &str) -> Option<i32> {
+    let end = text.find('%')?;
+    let digits = text[..end]
+        .rsplit(|c: char| !c.is_ascii_digit())
+        .next()
+        .filter(|d| !d.is_empty())?;
+    digits.parse().ok().filter(|p| *p <= 100)
+}
+
+/// "45s", "3m 05s" or "1h 02m"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests;
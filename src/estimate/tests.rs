@@ -0,0 +1,87 @@
+//! Tests of time remaining estimates
+
+use super::{estimate, format_duration, overall, percent_done, progress, Estimate};
+use serde_json::json;
+use std::time::Duration;
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn extrapolates_from_progress() {
+    assert_eq!(
+        estimate(secs(30), Some(25), &[]),
+        Some(Estimate::Remaining(secs(90)))
+    );
+    // Reported progress wins over earlier runs
+    assert_eq!(
+        estimate(secs(30), Some(50), &[secs(600)]),
+        Some(Estimate::Remaining(secs(30)))
+    );
+}
+
+#[test]
+fn early_progress_falls_back_to_history() {
+    assert_eq!(estimate(secs(2), Some(1), &[]), None);
+    assert_eq!(
+        estimate(secs(2), Some(1), &[secs(10), secs(60), secs(20)]),
+        Some(Estimate::Remaining(secs(18)))
+    );
+}
+
+#[test]
+fn compares_against_earlier_runs() {
+    let past = [secs(40), secs(50), secs(120)];
+    assert_eq!(
+        estimate(secs(10), None, &past),
+        Some(Estimate::Remaining(secs(40)))
+    );
+    assert_eq!(
+        estimate(secs(60), None, &past),
+        Some(Estimate::Remaining(secs(60)))
+    );
+    assert_eq!(
+        estimate(secs(130), None, &past),
+        Some(Estimate::LongerThanUsual)
+    );
+}
+
+#[test]
+fn reads_progress_from_metadata() {
+    assert_eq!(progress(&json!({"progress": 42})), Some(42));
+    assert_eq!(
+        progress(&json!({"download_progress": "rootfs: 45% (12.3MB/s)"})),
+        Some(45)
+    );
+    assert_eq!(
+        progress(&json!({"fs_progress": "web1: 1.20GB (50.00MB/s)"})),
+        None
+    );
+    assert_eq!(progress(&json!(null)), None);
+}
+
+#[test]
+fn formats_durations() {
+    assert_eq!(format_duration(secs(45)), "45s");
+    assert_eq!(format_duration(secs(185)), "3m 05s");
+    assert_eq!(format_duration(secs(3720)), "1h 02m");
+}
+
+#[test]
+fn percent_done_falls_back_to_earlier_runs() {
+    assert_eq!(percent_done(secs(30), Some(46), &[]), Some(46));
+    assert_eq!(
+        percent_done(secs(15), None, &[secs(10), secs(60), secs(30)]),
+        Some(50)
+    );
+    // Held below 100 while it is still running
+    assert_eq!(percent_done(secs(90), None, &[secs(30)]), Some(99));
+    assert_eq!(percent_done(secs(90), None, &[]), None);
+}
+
+#[test]
+fn overall_progress_averages_the_known_percentages() {
+    assert_eq!(overall([Some(40), None, Some(60)].into_iter()), Some(50));
+    assert_eq!(overall([None, None].into_iter()), None);
+}
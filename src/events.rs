@@ -0,0 +1,225 @@
+//! Lifecycle event feed
+//!
+//! Subscribes to the events API of each connected server and keeps the most
+//! recent lifecycle events, so instances started, stopped, created or deleted
+//! by other admins or by automation show up as they happen.
+
+use crate::lxc::LxcClient;
+use crate::lxd_api::LxdEvent;
+use crate::poll::{Category, Poller};
+use chrono::{DateTime, Local};
+use futures::StreamExt;
+use log::{info, warn};
+use std::collections::VecDeque;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+
+/// Events kept in the panel; older ones are dropped
+const MAX_EVENTS: usize = 50;
+
+/// Wait before reconnecting after the websocket closes or fails, before
+/// jitter
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifecycleEvent {
+    pub at: DateTime<Local>,
+    pub remote: Option<String>, // Set in all-remotes mode
+    pub action: String,         // e.g. "instance-started"
+    pub entity: String,         // e.g. "web1" or "web1/snap0"
+    pub requestor: Option<String>,
+}
+
+impl LifecycleEvent {
+    /// Read a lifecycle event; other event types give None
+    pub fn from_lxd(event: &LxdEvent, remote: Option<String>) -> Option<Self> {
+        if event.event_type != "lifecycle" {
+            return None;
+        }
+
+        let metadata = &event.metadata;
+        let action = metadata.get("action")?.as_str()?.to_string();
+        let source = metadata
+            .get("source")
+            .and_then(|s| s.as_str())
+            .unwrap_or_default();
+
+        let requestor = metadata.get("requestor").and_then(|r| {
+            let username = r.get("username")?.as_str().filter(|u| !u.is_empty())?;
+            Some(match r.get("protocol").and_then(|p| p.as_str()) {
+                Some(protocol) if !protocol.is_empty() => format!("{} ({})", username, protocol),
+                _ => username.to_string(),
+            })
+        });
+
+        let at = DateTime::parse_from_rfc3339(&event.timestamp)
+            .map(|at| at.with_timezone(&Local))
+            .unwrap_or_else(|_| Local::now());
+
+        Some(LifecycleEvent {
+            at,
+            remote,
+            action,
+            entity: entity(source),
+            requestor,
+        })
+    }
+
+    /// Whether the event changes the container list
+    pub fn affects_instances(&self) -> bool {
+        self.action.starts_with("instance-")
+    }
+
+    /// The entity as the list names it, with the remote in all-remotes mode
+    pub fn qualified_entity(&self) -> String {
+        match &self.remote {
+            Some(remote) => format!("{}:{}", remote, self.entity),
+            None => self.entity.clone(),
+        }
+    }
+}
+
+/// Names from an API path: "/1.0/instances/web1/snapshots/snap0" is
+/// "web1/snap0"
+fn entity(source: &str) -> String {
+    let path = source.split('?').next().unwrap_or_default();
+    path.trim_start_matches("/1.0/")
+        .split('/')
+        .skip(1)
+        .step_by(2)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+enum FeedMessage {
+    Event(LifecycleEvent),
+    Connected,
+    Failed(Option<String>, String),
+}
+
+/// Subscriptions to one or more servers and the events they delivered
+pub struct EventFeed {
+    pub events: VecDeque<LifecycleEvent>, // Oldest first
+    pub error: Option<String>,            // Latest connection failure
+    tx: mpsc::UnboundedSender<FeedMessage>,
+    rx: mpsc::UnboundedReceiver<FeedMessage>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl EventFeed {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        EventFeed {
+            events: VecDeque::new(),
+            error: None,
+            tx,
+            rx,
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Follow these servers instead of any previous ones. Each subscription
+    /// reconnects on its own, as often as `polls` allows, until `stop` is
+    /// called.
+    pub fn subscribe(&mut self, clients: Vec<(Option<String>, LxcClient)>, polls: &Poller) {
+        self.stop();
+        self.error = None;
+        for (remote, client) in clients {
+            let tx = self.tx.clone();
+            let polls = polls.clone();
+            self.tasks
+                .push(tokio::spawn(follow(client, remote, polls, tx)));
+        }
+    }
+
+    /// Whether any server is being followed
+    pub fn following(&self) -> bool {
+        !self.tasks.is_empty()
+    }
+
+    pub fn stop(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+    }
+
+    /// Take in what the subscriptions delivered. Returns the events new
+    /// since the last drain.
+    pub fn drain(&mut self) -> Vec<LifecycleEvent> {
+        let mut delivered = Vec::new();
+        while let Ok(message) = self.rx.try_recv() {
+            match message {
+                FeedMessage::Event(event) => {
+                    if self.events.len() == MAX_EVENTS {
+                        self.events.pop_front();
+                    }
+                    self.events.push_back(event.clone());
+                    delivered.push(event);
+                }
+                FeedMessage::Connected => self.error = None,
+                FeedMessage::Failed(remote, e) => {
+                    self.error = Some(match remote {
+                        Some(remote) => format!("{}: {}", remote, e),
+                        None => e,
+                    });
+                }
+            }
+        }
+        delivered
+    }
+}
+
+impl Drop for EventFeed {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Read events from one server, reconnecting whenever the socket closes
+async fn follow(
+    client: LxcClient,
+    remote: Option<String>,
+    polls: Poller,
+    tx: mpsc::UnboundedSender<FeedMessage>,
+) {
+    loop {
+        polls.take(Category::Events).await;
+        match client.lifecycle_events().await {
+            Ok(mut stream) => {
+                info!("Subscribed to lifecycle events ({:?})", remote);
+                let _ = tx.send(FeedMessage::Connected);
+
+                while let Some(event) = stream.next().await {
+                    let message = match event {
+                        Ok(event) => match LifecycleEvent::from_lxd(&event, remote.clone()) {
+                            Some(event) => FeedMessage::Event(event),
+                            None => continue,
+                        },
+                        Err(e) => FeedMessage::Failed(remote.clone(), e.to_string()),
+                    };
+                    if tx.send(message).is_err() {
+                        return;
+                    }
+                }
+                let closed = FeedMessage::Failed(remote.clone(), "Disconnected".to_string());
+                if tx.send(closed).is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                warn!("Failed to subscribe to events ({:?}): {}", remote, e);
+                if tx
+                    .send(FeedMessage::Failed(remote.clone(), e.to_string()))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+        sleep(polls.jittered(RECONNECT_DELAY)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests;
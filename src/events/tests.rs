@@ -0,0 +1,66 @@
+//! Tests of reading lifecycle events
+
+use super::{entity, LifecycleEvent};
+use crate::lxd_api::LxdEvent;
+use serde_json::json;
+
+fn event(value: serde_json::Value) -> LxdEvent {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn reads_instance_lifecycle_event() {
+    let lxd = event(json!({
+        "type": "lifecycle",
+        "timestamp": "2026-01-02T03:04:05.123456789Z",
+        "metadata": {
+            "action": "instance-started",
+            "source": "/1.0/instances/web1?project=default",
+            "requestor": {"username": "alice", "protocol": "tls", "address": "10.0.0.5:41234"},
+        },
+        "project": "default",
+    }));
+
+    let event = LifecycleEvent::from_lxd(&lxd, Some("prod".to_string())).unwrap();
+    assert_eq!(event.action, "instance-started");
+    assert_eq!(event.entity, "web1");
+    assert_eq!(event.requestor.as_deref(), Some("alice (tls)"));
+    assert_eq!(event.remote.as_deref(), Some("prod"));
+    assert_eq!(event.at.timestamp(), 1767323045);
+    assert!(event.affects_instances());
+}
+
+#[test]
+fn requestor_is_optional() {
+    let lxd = event(json!({
+        "type": "lifecycle",
+        "timestamp": "2026-01-02T03:04:05Z",
+        "metadata": {"action": "network-created", "source": "/1.0/networks/lxdbr1"},
+    }));
+
+    let event = LifecycleEvent::from_lxd(&lxd, None).unwrap();
+    assert_eq!(event.entity, "lxdbr1");
+    assert_eq!(event.requestor, None);
+    assert!(!event.affects_instances());
+}
+
+#[test]
+fn ignores_other_event_types() {
+    let lxd = event(json!({
+        "type": "logging",
+        "timestamp": "2026-01-02T03:04:05Z",
+        "metadata": {"message": "Started container", "level": "info"},
+    }));
+
+    assert_eq!(LifecycleEvent::from_lxd(&lxd, None), None);
+}
+
+#[test]
+fn entity_keeps_nested_names() {
+    assert_eq!(entity("/1.0/instances/web1/snapshots/snap0"), "web1/snap0");
+    assert_eq!(
+        entity("/1.0/instances/web1/backups/b1?project=x"),
+        "web1/b1"
+    );
+    assert_eq!(entity(""), "");
+}
@@ -0,0 +1,1379 @@
+//! Fake LXD server for tests
+//!
+//! Serves the parts of the LXD REST API that LXTUI uses, events included,
+//! over a Unix socket in a temporary directory. Operations complete as soon
+//! as they are created, so tests only need to poll once to see the result.
+
+use futures::SinkExt;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::net::UnixListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Message, Role};
+use tokio_tungstenite::WebSocketStream;
+use uuid::Uuid;
+
+use crate::lxc::LxcClient;
+use crate::lxd_api::LxdApiClient;
+
+#[derive(Debug, Clone)]
+struct Instance {
+    status: String,
+    instance_type: String,
+    ipv4: Option<String>,
+    snapshots: Vec<String>,
+    console: String,
+    config: BTreeMap<String, String>,
+    devices: BTreeMap<String, BTreeMap<String, String>>,
+    profiles: Vec<String>,
+    disk_usage: u64, // Bytes used by the root disk
+    metadata: Value,
+    templates: BTreeMap<String, String>, // File name to content
+    exec: (i64, String),                 // Exit status and output of every command
+}
+
+type Body = Full<Bytes>;
+
+/// Storage pools every server has: name, driver, bytes used and in total
+const STORAGE_POOLS: [(&str, &str, u64, u64); 2] = [
+    ("default", "dir", 10 << 30, 40 << 30),
+    ("fast", "zfs", 29 << 30, 32 << 30),
+];
+type Change = Box<dyn FnOnce(&mut State) + Send>;
+
+#[derive(Default)]
+struct State {
+    instances: BTreeMap<String, Instance>,
+    image_aliases: Vec<(String, String)>, // Name, description
+    profiles: Vec<String>,                // Besides "default"
+    profile_config: BTreeMap<String, BTreeMap<String, String>>, // Config set in profiles
+    images: Vec<Value>,                   // Images in the store, as LXD lists them
+    image_updates: BTreeMap<String, String>, // Fingerprint a refresh replaces an image with
+    sources: BTreeMap<String, Value>,     // Source each instance was created from
+    networks: BTreeMap<String, BTreeMap<String, String>>, // Managed bridges and their config
+    forwards: BTreeMap<String, Vec<Value>>, // Network forwards by network
+    zones: BTreeMap<String, BTreeMap<String, Value>>, // Network zones and their records by name
+    operations: HashMap<String, Value>,
+    logs: BTreeMap<String, String>,  // Recorded exec output by path
+    metrics: String,                 // Served at /1.0/metrics
+    warnings: Vec<Value>,            // Served at /1.0/warnings
+    requests: Vec<String>,           // "PUT /1.0/instances/web1/state"
+    operation_error: Option<String>, // Error new operations fail with
+    hold_operations: bool,           // Leave new operations running
+    held: HashMap<String, Change>,   // Changes running operations make once released
+    connections: usize,              // Connections accepted so far
+    subscribers: Vec<mpsc::UnboundedSender<String>>, // Events websockets
+}
+
+pub struct FakeLxd {
+    dir: PathBuf,
+    socket: PathBuf,
+    state: Arc<Mutex<State>>,
+    server: JoinHandle<()>,
+}
+
+impl FakeLxd {
+    /// Start a server with no instances. Must be called inside a Tokio runtime.
+    pub fn start() -> Self {
+        let dir = std::env::temp_dir().join(format!("lxtui-fake-lxd-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("unix.socket");
+        let state = Arc::new(Mutex::new(State::default()));
+
+        // Each connection is served on its own task and kept alive, like
+        // LXD, so clients can reuse it
+        let listener = UnixListener::bind(&socket).unwrap();
+        let server_state = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                server_state.lock().unwrap().connections += 1;
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| {
+                        let state = state.clone();
+                        async move { Ok::<_, Infallible>(handle(&state, request).await) }
+                    });
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .with_upgrades()
+                        .await;
+                });
+            }
+        });
+
+        FakeLxd {
+            dir,
+            socket,
+            state,
+            server,
+        }
+    }
+
+    /// Add an instance; `ipv4` is reported on eth0 while it runs
+    pub fn with_instance(self, name: &str, status: &str, ipv4: Option<&str>) -> Self {
+        self.state.lock().unwrap().instances.insert(
+            name.to_string(),
+            Instance {
+                status: status.to_string(),
+                instance_type: "container".to_string(),
+                ipv4: ipv4.map(str::to_string),
+                snapshots: Vec::new(),
+                console: String::new(),
+                config: BTreeMap::new(),
+                devices: BTreeMap::new(),
+                profiles: vec!["default".to_string()],
+                disk_usage: 0,
+                metadata: default_metadata(),
+                templates: BTreeMap::from([(
+                    "hostname.tpl".to_string(),
+                    "{{ instance.name }}\n".to_string(),
+                )]),
+                exec: (0, String::new()),
+            },
+        );
+        self
+    }
+
+    /// Add a stopped virtual machine
+    pub fn with_vm(self, name: &str) -> Self {
+        let fake = self.with_instance(name, "Stopped", None);
+        if let Some(instance) = fake.state.lock().unwrap().instances.get_mut(name) {
+            instance.instance_type = "virtual-machine".to_string();
+        }
+        fake
+    }
+
+    /// Set a config key of an instance
+    pub fn with_config(self, name: &str, key: &str, value: &str) -> Self {
+        if let Some(instance) = self.state.lock().unwrap().instances.get_mut(name) {
+            instance.config.insert(key.to_string(), value.to_string());
+        }
+        self
+    }
+
+    /// Add a device to an instance
+    pub fn with_device(self, name: &str, device: &str, options: &[(&str, &str)]) -> Self {
+        if let Some(instance) = self.state.lock().unwrap().instances.get_mut(name) {
+            let options = options
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            instance.devices.insert(device.to_string(), options);
+        }
+        self
+    }
+
+    /// Set the bytes an instance's root disk uses
+    pub fn with_disk_usage(self, name: &str, bytes: u64) -> Self {
+        if let Some(instance) = self.state.lock().unwrap().instances.get_mut(name) {
+            instance.disk_usage = bytes;
+        }
+        self
+    }
+
+    /// Give an instance a snapshot
+    /// Add a profile besides "default"
+    pub fn with_profile(self, name: &str) -> Self {
+        self.state.lock().unwrap().profiles.push(name.to_string());
+        self
+    }
+
+    /// Set `key` in a profile's config, adding the profile if it isn't one
+    pub fn with_profile_config(self, profile: &str, key: &str, value: &str) -> Self {
+        {
+            let mut state = self.state.lock().unwrap();
+            if profile != "default" && !state.profiles.iter().any(|p| p == profile) {
+                state.profiles.push(profile.to_string());
+            }
+            state
+                .profile_config
+                .entry(profile.to_string())
+                .or_default()
+                .insert(key.to_string(), value.to_string());
+        }
+        self
+    }
+
+    pub fn with_snapshot(self, name: &str, snapshot: &str) -> Self {
+        if let Some(instance) = self.state.lock().unwrap().instances.get_mut(name) {
+            instance.snapshots.push(snapshot.to_string());
+        }
+        self
+    }
+
+    /// Make every command run in an instance exit with `status` after
+    /// writing `output`
+    pub fn with_exec(self, name: &str, status: i64, output: &str) -> Self {
+        if let Some(instance) = self.state.lock().unwrap().instances.get_mut(name) {
+            instance.exec = (status, output.to_string());
+        }
+        self
+    }
+
+    /// Serve `text` as the metrics of every instance
+    pub fn with_metrics(self, text: &str) -> Self {
+        self.state.lock().unwrap().metrics = text.to_string();
+        self
+    }
+
+    /// Raise an unresolved warning against storage pool `pool`
+    pub fn with_pool_warning(self, pool: &str, severity: &str, message: &str) -> Self {
+        self.state.lock().unwrap().warnings.push(json!({
+            "status": "new",
+            "severity": severity,
+            "count": 1,
+            "last_seen_at": "2024-06-01T08:30:00Z",
+            "last_message": message,
+            "entity_url": format!("/1.0/storage-pools/{}", pool),
+        }));
+        self
+    }
+
+    /// Add an image alias to the image store
+    pub fn with_image_alias(self, name: &str, description: &str) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .image_aliases
+            .push((name.to_string(), description.to_string()));
+        self
+    }
+
+    /// Add an image to the image store
+    pub fn with_image(
+        self,
+        fingerprint: &str,
+        created_at: &str,
+        properties: &[(&str, &str)],
+    ) -> Self {
+        let properties: BTreeMap<&str, &str> = properties.iter().copied().collect();
+        self.state.lock().unwrap().images.push(json!({
+            "fingerprint": fingerprint,
+            "created_at": created_at,
+            "properties": properties,
+        }));
+        self
+    }
+
+    /// Add an image copied from `alias` on the images: server, which a
+    /// refresh replaces with the build `newer` when there is one
+    pub fn with_remote_image(
+        self,
+        fingerprint: &str,
+        alias: &str,
+        auto_update: bool,
+        newer: Option<&str>,
+    ) -> Self {
+        let mut state = self.state.lock().unwrap();
+        state.images.push(json!({
+            "fingerprint": fingerprint,
+            "created_at": "2024-01-01T00:00:00Z",
+            "properties": {"description": alias},
+            "aliases": [{"name": alias}],
+            "size": 120 << 20,
+            "cached": true,
+            "auto_update": auto_update,
+            "update_source": {
+                "alias": alias,
+                "server": "https://images.linuxcontainers.org",
+                "protocol": "simplestreams"
+            },
+        }));
+        if let Some(newer) = newer {
+            state
+                .image_updates
+                .insert(fingerprint.to_string(), newer.to_string());
+        }
+        drop(state);
+        self
+    }
+
+    /// Add a managed bridge with `config`
+    pub fn with_network(self, name: &str, config: &[(&str, &str)]) -> Self {
+        let config = config
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        self.state
+            .lock()
+            .unwrap()
+            .networks
+            .insert(name.to_string(), config);
+        self
+    }
+
+    /// Forward `listen_port` of `listen_address` on `network` to
+    /// `target_port` of `target_address`
+    pub fn with_forward(
+        self,
+        network: &str,
+        listen_address: &str,
+        listen_port: &str,
+        target: (&str, &str),
+    ) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .forwards
+            .entry(network.to_string())
+            .or_default()
+            .push(json!({
+                "listen_address": listen_address,
+                "config": {},
+                "ports": [{
+                    "protocol": "tcp",
+                    "listen_port": listen_port,
+                    "target_address": target.0,
+                    "target_port": target.1,
+                }],
+            }));
+        self
+    }
+
+    /// Add a network zone with `record` pointing at `address`
+    pub fn with_zone_record(self, zone: &str, record: &str, address: &str) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .zones
+            .entry(zone.to_string())
+            .or_default()
+            .insert(
+                record.to_string(),
+                json!({
+                    "name": record,
+                    "description": "",
+                    "entries": [{"type": "A", "value": address}],
+                    "config": {},
+                }),
+            );
+        self
+    }
+
+    /// Set a config key on a zone record
+    pub fn with_zone_record_config(self, zone: &str, record: &str, key: &str, value: &str) -> Self {
+        if let Some(record) = self
+            .state
+            .lock()
+            .unwrap()
+            .zones
+            .get_mut(zone)
+            .and_then(|records| records.get_mut(record))
+        {
+            record["config"][key] = json!(value);
+        }
+        self
+    }
+
+    pub fn client(&self) -> LxcClient {
+        LxcClient::from_api(LxdApiClient::unix(self.socket_path()))
+    }
+
+    pub fn socket_path(&self) -> String {
+        self.socket.display().to_string()
+    }
+
+    /// Status of an instance, or None once it has been deleted
+    pub fn status(&self, name: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.instances.get(name).map(|i| i.status.clone())
+    }
+
+    /// "container" or "virtual-machine"
+    pub fn instance_type(&self, name: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.instances.get(name).map(|i| i.instance_type.clone())
+    }
+
+    /// The `source` of the request that created an instance
+    pub fn source(&self, name: &str) -> Option<Value> {
+        self.state.lock().unwrap().sources.get(name).cloned()
+    }
+
+    /// Change an instance's status behind LXTUI's back, as a crash or
+    /// another client would
+    pub fn set_status(&self, name: &str, status: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(instance) = state.instances.get_mut(name) {
+            instance.status = status.to_string();
+        }
+    }
+
+    /// Send a lifecycle event about an instance to every events websocket;
+    /// LXD names a requestor when someone asked for the change
+    pub fn send_lifecycle(&self, action: &str, name: &str, requestor: Option<&str>) {
+        let event = json!({
+            "type": "lifecycle",
+            "timestamp": chrono::Local::now().to_rfc3339(),
+            "metadata": {
+                "action": action,
+                "source": format!("/1.0/instances/{}", name),
+                "requestor": requestor.map(|username| json!({
+                    "username": username,
+                    "protocol": "unix",
+                })),
+            },
+        })
+        .to_string();
+        self.state
+            .lock()
+            .unwrap()
+            .subscribers
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    /// Events websockets opened so far and still open
+    pub fn subscribers(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.subscribers.retain(|tx| !tx.is_closed());
+        state.subscribers.len()
+    }
+
+    /// Append `text` to an instance's console log
+    pub fn write_console(&self, name: &str, text: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(instance) = state.instances.get_mut(name) {
+            instance.console.push_str(text);
+        }
+    }
+
+    /// A config key of an instance
+    pub fn config(&self, name: &str, key: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.instances.get(name)?.config.get(key).cloned()
+    }
+
+    /// Options of a device of an instance
+    pub fn device(&self, name: &str, device: &str) -> Option<BTreeMap<String, String>> {
+        let state = self.state.lock().unwrap();
+        state.instances.get(name)?.devices.get(device).cloned()
+    }
+
+    /// Images in the store as LXD lists them
+    pub fn images(&self) -> Vec<Value> {
+        self.state.lock().unwrap().images.clone()
+    }
+
+    /// Profiles an instance was created with
+    pub fn profiles(&self, name: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .instances
+            .get(name)
+            .map(|instance| instance.profiles.clone())
+            .unwrap_or_default()
+    }
+
+    /// Content of a template file of an instance
+    pub fn template(&self, name: &str, template: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.instances.get(name)?.templates.get(template).cloned()
+    }
+
+    /// Image metadata of an instance
+    pub fn metadata(&self, name: &str) -> Option<Value> {
+        let state = self.state.lock().unwrap();
+        state.instances.get(name).map(|i| i.metadata.clone())
+    }
+
+    /// Snapshot names of an instance, oldest first
+    pub fn snapshots(&self, name: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .instances
+            .get(name)
+            .map(|i| i.snapshots.clone())
+            .unwrap_or_default()
+    }
+
+    /// A record of a network zone, as LXD lists it
+    pub fn zone_record(&self, zone: &str, record: &str) -> Option<Value> {
+        let state = self.state.lock().unwrap();
+        state.zones.get(zone)?.get(record).cloned()
+    }
+
+    /// Names of the network zones
+    pub fn zones(&self) -> Vec<String> {
+        self.state.lock().unwrap().zones.keys().cloned().collect()
+    }
+
+    /// Exec output files the server still keeps
+    pub fn exec_logs(&self) -> Vec<String> {
+        self.state.lock().unwrap().logs.keys().cloned().collect()
+    }
+
+    /// Requests received so far, as "METHOD /path"
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Connections accepted so far
+    pub fn connections(&self) -> usize {
+        self.state.lock().unwrap().connections
+    }
+
+    /// Make operations created from now on fail with `error`
+    pub fn fail_operations(&self, error: &str) {
+        self.state.lock().unwrap().operation_error = Some(error.to_string());
+    }
+
+    /// Let operations created from now on succeed again
+    pub fn succeed_operations(&self) {
+        self.state.lock().unwrap().operation_error = None;
+    }
+
+    /// Leave operations created from now on running until released or
+    /// cancelled
+    pub fn hold_operations(&self) {
+        self.state.lock().unwrap().hold_operations = true;
+    }
+
+    /// Finish the running operations and stop holding new ones
+    pub fn release_operations(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.hold_operations = false;
+        for (id, change) in std::mem::take(&mut state.held) {
+            change(&mut state);
+            if let Some(operation) = state.operations.get_mut(&id) {
+                operation["status"] = json!("Success");
+                operation["status_code"] = json!(200);
+            }
+        }
+    }
+
+    /// Status LXD reports for an operation, e.g. "Cancelled"
+    pub fn operation_status(&self, path: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        let id = path.rsplit('/').next()?;
+        state.operations.get(id)?["status"]
+            .as_str()
+            .map(str::to_string)
+    }
+}
+
+impl Drop for FakeLxd {
+    fn drop(&mut self) {
+        self.server.abort();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+async fn handle(state: &Mutex<State>, request: Request<Incoming>) -> Response<Body> {
+    if request.uri().path() == "/1.0/events" {
+        return subscribe(state, request);
+    }
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().unwrap_or_default().to_string();
+    let raw = match request.into_body().collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(_) => Bytes::new(),
+    };
+    let body: Value = serde_json::from_slice(&raw).unwrap_or(Value::Null);
+
+    let mut state = state.lock().unwrap();
+    state.requests.push(format!("{} {}", method, path));
+
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    match (&method, segments.as_slice()) {
+        (&Method::GET, [""]) => sync(json!(["/1.0"])),
+        (&Method::GET, ["1.0"]) => sync(json!({
+            "auth": "trusted",
+            "api_version": "1.0",
+            "api_extensions": ["virtual-machines", "tpm_device_type"],
+            "environment": {
+                "server": "lxd",
+                "server_version": "5.21.0",
+                "storage": "dir",
+                "storage_version": "1"
+            }
+        })),
+        (&Method::GET, ["1.0", "instances"]) => {
+            let instances: Vec<Value> = state
+                .instances
+                .iter()
+                .map(|(name, instance)| instance_json(name, instance, &state.profile_config))
+                .collect();
+            sync(json!(instances))
+        }
+        (&Method::POST, ["1.0", "instances"]) => create_instance(&mut state, &body),
+        (&Method::GET, ["1.0", "images", "aliases"]) => {
+            let aliases: Vec<Value> = state
+                .image_aliases
+                .iter()
+                .map(|(name, description)| {
+                    json!({"name": name, "description": description, "target": "0123abcd"})
+                })
+                .collect();
+            sync(json!(aliases))
+        }
+        (&Method::GET, ["1.0", "images"]) => sync(json!(state.images)),
+        (&Method::POST, ["1.0", "images"]) => {
+            // Publishing an instance; LXD refuses running ones
+            let name = body["source"]["name"].as_str().unwrap_or_default();
+            match state.instances.get(name) {
+                None => return not_found(),
+                Some(instance) if instance.status == "Running" => {
+                    return error(StatusCode::BAD_REQUEST, "The instance is running");
+                }
+                Some(_) => {}
+            }
+            let mut image = json!({
+                "fingerprint": format!("{:0>12}", state.images.len()),
+                "created_at": "2024-06-01T00:00:00Z",
+                "expires_at": "0001-01-01T00:00:00Z",
+            });
+            for key in ["properties", "aliases", "public", "expires_at"] {
+                if let Some(value) = body.get(key) {
+                    image[key] = value.clone();
+                }
+            }
+            operation_with(&mut state, "Creating image", json!({}), move |state| {
+                state.images.push(image)
+            })
+        }
+        (&Method::PATCH, ["1.0", "images", fingerprint]) => {
+            let Some(image) = state
+                .images
+                .iter_mut()
+                .find(|image| image["fingerprint"] == *fingerprint)
+            else {
+                return not_found();
+            };
+            for (key, value) in body.as_object().into_iter().flatten() {
+                image[key] = value.clone();
+            }
+            sync(json!({}))
+        }
+        (&Method::POST, ["1.0", "images", fingerprint, "refresh"]) => {
+            if !state
+                .images
+                .iter()
+                .any(|image| image["fingerprint"] == *fingerprint)
+            {
+                return not_found();
+            }
+            // A newer build takes the old one's place, aliases and all
+            let fingerprint = fingerprint.to_string();
+            operation_with(
+                &mut state,
+                "Refreshing image",
+                json!({"download_progress": "rootfs: 42% (12.00MB/s)"}),
+                move |state| {
+                    let Some(newer) = state.image_updates.remove(&fingerprint) else {
+                        return;
+                    };
+                    if let Some(image) = state
+                        .images
+                        .iter_mut()
+                        .find(|image| image["fingerprint"] == fingerprint)
+                    {
+                        image["fingerprint"] = json!(newer);
+                        image["created_at"] = json!("2024-06-01T00:00:00Z");
+                    }
+                },
+            )
+        }
+        (&Method::GET, ["1.0", "storage-pools"]) if query.contains("recursion") => {
+            let pools: Vec<Value> = STORAGE_POOLS
+                .iter()
+                .map(|(name, driver, _, _)| json!({"name": name, "driver": driver, "status": "Created"}))
+                .collect();
+            sync(json!(pools))
+        }
+        (&Method::GET, ["1.0", "storage-pools"]) => {
+            let urls: Vec<String> = STORAGE_POOLS
+                .iter()
+                .map(|(name, ..)| format!("/1.0/storage-pools/{}", name))
+                .collect();
+            sync(json!(urls))
+        }
+        (&Method::GET, ["1.0", "profiles"]) if query.contains("recursion") => {
+            let profiles: Vec<Value> = std::iter::once(default_profile())
+                .chain(state.profiles.iter().map(|name| {
+                    let config = state.profile_config.get(name).cloned().unwrap_or_default();
+                    json!({"name": name, "config": config, "devices": {}})
+                }))
+                .collect();
+            sync(json!(profiles))
+        }
+        (&Method::GET, ["1.0", "profiles"]) => {
+            let urls: Vec<String> = std::iter::once("default")
+                .chain(state.profiles.iter().map(String::as_str))
+                .map(|name| format!("/1.0/profiles/{}", name))
+                .collect();
+            sync(json!(urls))
+        }
+        (&Method::GET, ["1.0", "profiles", "default"]) => sync(default_profile()),
+        (&Method::GET, ["1.0", "networks"]) => {
+            let networks: Vec<Value> = state
+                .networks
+                .iter()
+                .map(|(name, config)| {
+                    json!({"name": name, "type": "bridge", "managed": true, "config": config})
+                })
+                .collect();
+            sync(json!(networks))
+        }
+        (&Method::GET, ["1.0", "networks", name, "forwards"])
+            if state.networks.contains_key(*name) =>
+        {
+            sync(json!(state
+                .forwards
+                .get(*name)
+                .cloned()
+                .unwrap_or_default()))
+        }
+        (&Method::GET, ["1.0", "networks", name]) => match state.networks.get(*name) {
+            Some(config) => sync(json!({
+                "name": name,
+                "type": "bridge",
+                "managed": true,
+                "config": config,
+            })),
+            None => not_found(),
+        },
+        (&Method::GET, ["1.0", "network-zones"]) => {
+            let zones: Vec<Value> = state
+                .zones
+                .keys()
+                .map(|zone| {
+                    // Networks publish into the zones named in dns.zone.forward
+                    let used_by: Vec<String> = state
+                        .networks
+                        .iter()
+                        .filter(|(_, config)| {
+                            config
+                                .get("dns.zone.forward")
+                                .is_some_and(|zones| zones.split(',').any(|z| z.trim() == zone))
+                        })
+                        .map(|(network, _)| format!("/1.0/networks/{}", network))
+                        .collect();
+                    json!({"name": zone, "description": "", "used_by": used_by})
+                })
+                .collect();
+            sync(json!(zones))
+        }
+        (&Method::POST, ["1.0", "network-zones"]) => match body["name"].as_str() {
+            Some(zone) if state.zones.contains_key(zone) => {
+                error(StatusCode::CONFLICT, "Network zone already exists")
+            }
+            Some(zone) => {
+                state.zones.insert(zone.to_string(), BTreeMap::new());
+                sync(json!({}))
+            }
+            None => error(StatusCode::BAD_REQUEST, "No name provided"),
+        },
+        (&Method::DELETE, ["1.0", "network-zones", zone]) => match state.zones.remove(*zone) {
+            Some(_) => sync(json!({})),
+            None => not_found(),
+        },
+        (&Method::GET, ["1.0", "network-zones", zone, "records"]) => match state.zones.get(*zone) {
+            Some(records) => sync(json!(records.values().collect::<Vec<_>>())),
+            None => not_found(),
+        },
+        (&Method::POST, ["1.0", "network-zones", zone, "records"]) => {
+            let Some(records) = state.zones.get_mut(*zone) else {
+                return not_found();
+            };
+            match body["name"].as_str() {
+                Some(record) if records.contains_key(record) => {
+                    error(StatusCode::CONFLICT, "Network zone record already exists")
+                }
+                Some(record) => {
+                    records.insert(record.to_string(), body.clone());
+                    sync(json!({}))
+                }
+                None => error(StatusCode::BAD_REQUEST, "No name provided"),
+            }
+        }
+        (&Method::PUT, ["1.0", "network-zones", zone, "records", record]) => {
+            match state.zones.get_mut(*zone).and_then(|r| r.get_mut(*record)) {
+                Some(existing) => {
+                    // LXD replaces the whole record, dropping what's left out
+                    for field in ["description", "entries", "config"] {
+                        existing[field] = body[field].clone();
+                    }
+                    sync(json!({}))
+                }
+                None => not_found(),
+            }
+        }
+        (&Method::DELETE, ["1.0", "network-zones", zone, "records", record]) => {
+            match state.zones.get_mut(*zone).and_then(|r| r.remove(*record)) {
+                Some(_) => sync(json!({})),
+                None => not_found(),
+            }
+        }
+        (&Method::GET, ["1.0", "storage-pools", name, "resources"]) => {
+            match STORAGE_POOLS.iter().find(|(pool, ..)| pool == name) {
+                Some((_, _, used, total)) => sync(json!({"space": {"used": used, "total": total}})),
+                None => not_found(),
+            }
+        }
+        (&Method::GET, ["1.0", "resources"]) => sync(json!({
+            "cpu": {"total": 8},
+            "memory": {"total": 16u64 << 30},
+        })),
+        (&Method::GET, ["1.0", "projects"]) => sync(json!(["/1.0/projects/default"])),
+        (&Method::GET, ["1.0", "instances", name]) => match state.instances.get(*name) {
+            Some(instance) => sync(instance_json(name, instance, &state.profile_config)),
+            None => not_found(),
+        },
+        (&Method::PATCH, ["1.0", "instances", name]) => {
+            let known =
+                |profile: &str| profile == "default" || state.profiles.iter().any(|p| p == profile);
+            let profiles: Option<Vec<String>> =
+                serde_json::from_value(body["profiles"].clone()).ok();
+            if let Some(missing) = profiles.iter().flatten().find(|p| !known(p)) {
+                let message = format!("Profile \"{}\" not found", missing);
+                return error(StatusCode::NOT_FOUND, &message);
+            }
+            let Some(instance) = state.instances.get_mut(*name) else {
+                return not_found();
+            };
+            if let Some(profiles) = profiles {
+                instance.profiles = profiles;
+            }
+            // Empty values remove keys, as in LXD
+            for (key, value) in body["config"].as_object().into_iter().flatten() {
+                match value.as_str() {
+                    Some("") | None => instance.config.remove(key),
+                    Some(value) => instance.config.insert(key.clone(), value.to_string()),
+                };
+            }
+            sync(json!({}))
+        }
+        (&Method::PUT, ["1.0", "instances", name]) => {
+            if !state.instances.contains_key(*name) {
+                return not_found();
+            }
+            let config: BTreeMap<String, String> =
+                serde_json::from_value(body["config"].clone()).unwrap_or_default();
+            let devices: BTreeMap<String, BTreeMap<String, String>> =
+                serde_json::from_value(body["devices"].clone()).unwrap_or_default();
+            let name = name.to_string();
+            operation(&mut state, "Updating instance", move |state| {
+                if let Some(instance) = state.instances.get_mut(&name) {
+                    instance.config = config;
+                    instance.devices = devices;
+                }
+            })
+        }
+        (&Method::DELETE, ["1.0", "instances", name]) => {
+            let protected = |i: &Instance| {
+                i.config
+                    .get("security.protection.delete")
+                    .map(String::as_str)
+                    == Some("true")
+            };
+            match state.instances.get(*name) {
+                None => not_found(),
+                Some(i) if i.status == "Running" => {
+                    error(StatusCode::BAD_REQUEST, "Instance is running")
+                }
+                Some(i) if protected(i) => error(
+                    StatusCode::BAD_REQUEST,
+                    "Instance is protected from being deleted",
+                ),
+                Some(_) => {
+                    let name = name.to_string();
+                    operation(&mut state, "Deleting instance", move |state| {
+                        state.instances.remove(&name);
+                    })
+                }
+            }
+        }
+        (&Method::GET, ["1.0", "instances", name, "state"]) => match state.instances.get(*name) {
+            Some(instance) => sync(state_json(instance)),
+            None => not_found(),
+        },
+        (&Method::PUT, ["1.0", "instances", name, "state"]) => {
+            let Some(instance) = state.instances.get(*name) else {
+                return not_found();
+            };
+            let status = match body["action"].as_str() {
+                Some("start") if instance.status == "Running" => {
+                    return error(StatusCode::BAD_REQUEST, "The instance is already running")
+                }
+                Some("stop") if instance.status == "Stopped" => {
+                    return error(StatusCode::BAD_REQUEST, "The instance is already stopped")
+                }
+                Some("start") | Some("restart") => "Running",
+                Some("stop") => "Stopped",
+                _ => return error(StatusCode::BAD_REQUEST, "Unknown state action"),
+            };
+            let name = name.to_string();
+            operation(&mut state, "Changing instance state", move |state| {
+                if let Some(instance) = state.instances.get_mut(&name) {
+                    instance.status = status.to_string();
+                }
+            })
+        }
+        (&Method::GET, ["1.0", "instances", name, "console"]) => match state.instances.get(*name) {
+            Some(instance) if instance.instance_type == "container" => {
+                Response::new(Body::from(instance.console.clone()))
+            }
+            Some(_) => error(
+                StatusCode::BAD_REQUEST,
+                "Console log is only available for containers",
+            ),
+            None => not_found(),
+        },
+        (&Method::GET, ["1.0", "instances", name, "snapshots"]) => match state.instances.get(*name)
+        {
+            Some(instance) => sync(json!(instance
+                .snapshots
+                .iter()
+                .map(|s| format!("/1.0/instances/{}/snapshots/{}", name, s))
+                .collect::<Vec<_>>())),
+            None => not_found(),
+        },
+        (&Method::POST, ["1.0", "instances", name, "snapshots"]) => {
+            let Some(instance) = state.instances.get(*name) else {
+                return not_found();
+            };
+            // LXD's default snapshots.pattern
+            let snapshot = body["name"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("snap{}", instance.snapshots.len()));
+            let name = name.to_string();
+            operation(&mut state, "Snapshotting instance", move |state| {
+                if let Some(instance) = state.instances.get_mut(&name) {
+                    instance.snapshots.push(snapshot);
+                }
+            })
+        }
+        (&Method::POST, ["1.0", "instances", name, "rebuild"]) => {
+            match state.instances.get(*name).map(|i| i.status.as_str()) {
+                None => return not_found(),
+                Some("Running") => {
+                    return error(
+                        StatusCode::BAD_REQUEST,
+                        "Instance must be stopped to be rebuilt",
+                    )
+                }
+                Some(_) => {}
+            }
+            let fingerprint = body["source"]["fingerprint"].as_str().unwrap_or_default();
+            let Some(image) = state
+                .images
+                .iter()
+                .find(|image| image["fingerprint"] == fingerprint)
+                .cloned()
+            else {
+                return error(StatusCode::NOT_FOUND, "Image not found");
+            };
+            // The new root disk comes with the image's volatile and image keys
+            let name = name.to_string();
+            operation(&mut state, "Rebuilding instance", move |state| {
+                if let Some(instance) = state.instances.get_mut(&name) {
+                    instance.config.insert(
+                        "volatile.base_image".to_string(),
+                        image["fingerprint"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                    );
+                    for (key, value) in image["properties"].as_object().into_iter().flatten() {
+                        if let Some(value) = value.as_str() {
+                            instance
+                                .config
+                                .insert(format!("image.{}", key), value.to_string());
+                        }
+                    }
+                }
+            })
+        }
+        (&Method::POST, ["1.0", "instances", name, "exec"]) => {
+            let (status, output) = match state.instances.get(*name) {
+                None => return not_found(),
+                Some(instance) if instance.status != "Running" => {
+                    return error(StatusCode::BAD_REQUEST, "Instance is not running")
+                }
+                Some(instance) => instance.exec.clone(),
+            };
+            if body["record-output"] != json!(true) {
+                return error(StatusCode::BAD_REQUEST, "Only recorded output is supported");
+            }
+            let log = format!(
+                "/1.0/instances/{}/logs/exec-output/exec_{}",
+                name,
+                Uuid::new_v4()
+            );
+            let (stdout, stderr) = (format!("{}.stdout", log), format!("{}.stderr", log));
+            state.logs.insert(stdout.clone(), output);
+            state.logs.insert(stderr.clone(), String::new());
+            let metadata = json!({"return": status, "output": {"1": stdout, "2": stderr}});
+            operation_with(&mut state, "Executing command", metadata, |_| {})
+        }
+        (&Method::GET, ["1.0", "metrics"]) => Response::new(Body::from(state.metrics.clone())),
+        (&Method::GET, ["1.0", "warnings"]) => sync(json!(state.warnings)),
+        (&Method::GET, ["1.0", "instances", _, "logs", "exec-output", _]) => {
+            match state.logs.get(&path) {
+                Some(text) => Response::new(Body::from(text.clone())),
+                None => not_found(),
+            }
+        }
+        (&Method::DELETE, ["1.0", "instances", _, "logs", "exec-output", _]) => {
+            match state.logs.remove(&path) {
+                Some(_) => sync(json!({})),
+                None => not_found(),
+            }
+        }
+        (&Method::GET, ["1.0", "instances", name, "metadata"]) => {
+            match state.instances.get(*name) {
+                Some(instance) => sync(instance.metadata.clone()),
+                None => not_found(),
+            }
+        }
+        (&Method::PUT, ["1.0", "instances", name, "metadata"]) => {
+            match state.instances.get_mut(*name) {
+                Some(instance) => {
+                    instance.metadata = body;
+                    sync(json!({}))
+                }
+                None => not_found(),
+            }
+        }
+        (&Method::GET, ["1.0", "instances", name, "metadata", "templates"]) => {
+            let Some(instance) = state.instances.get(*name) else {
+                return not_found();
+            };
+            match query.strip_prefix("path=") {
+                None => sync(json!(instance.templates.keys().collect::<Vec<_>>())),
+                Some(file) => match instance.templates.get(file) {
+                    Some(content) => Response::new(Body::from(content.clone())),
+                    None => error(StatusCode::NOT_FOUND, "Template not found"),
+                },
+            }
+        }
+        (&Method::POST, ["1.0", "instances", name, "metadata", "templates"]) => {
+            let Some(instance) = state.instances.get_mut(*name) else {
+                return not_found();
+            };
+            match query.strip_prefix("path=") {
+                Some(file) => {
+                    let content = String::from_utf8_lossy(&raw).into_owned();
+                    instance.templates.insert(file.to_string(), content);
+                    sync(json!({}))
+                }
+                None => error(StatusCode::BAD_REQUEST, "Missing path argument"),
+            }
+        }
+        (&Method::GET, ["1.0", "operations", id]) => match state.operations.get(*id) {
+            Some(operation) => sync(operation.clone()),
+            None => not_found(),
+        },
+        (&Method::DELETE, ["1.0", "operations", id]) => {
+            let id = id.to_string();
+            if state.held.remove(&id).is_none() {
+                return error(
+                    StatusCode::FORBIDDEN,
+                    "Only running operations can be cancelled",
+                );
+            }
+            let operation = state.operations.get_mut(&id).unwrap();
+            operation["status"] = json!("Cancelled");
+            operation["status_code"] = json!(401);
+            operation["err"] = json!("Operation cancelled");
+            sync(json!({}))
+        }
+        // LXD's router answers unknown endpoints with a bare "not found"
+        _ => error(StatusCode::NOT_FOUND, "not found"),
+    }
+}
+
+fn create_instance(state: &mut State, body: &Value) -> Response<Body> {
+    let Some(name) = body["name"].as_str().map(str::to_string) else {
+        return error(StatusCode::BAD_REQUEST, "No name provided");
+    };
+    if state.instances.contains_key(&name) {
+        return error(StatusCode::CONFLICT, "Instance already exists");
+    }
+
+    // A copy takes the source's snapshots unless asked not to
+    let source = &body["source"];
+    let snapshots = match source["type"].as_str() {
+        Some("copy") if !source["instance_only"].as_bool().unwrap_or(false) => source["source"]
+            .as_str()
+            .and_then(|path| path.rsplit('/').next())
+            .and_then(|source| state.instances.get(source))
+            .map(|source| source.snapshots.clone())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+
+    let instance_type = body["type"].as_str().unwrap_or("container").to_string();
+    let config = serde_json::from_value(body["config"].clone()).unwrap_or_default();
+    let devices = serde_json::from_value(body["devices"].clone()).unwrap_or_default();
+    let profiles = serde_json::from_value(body["profiles"].clone())
+        .unwrap_or_else(|_| vec!["default".to_string()]);
+    // Moving a copy to another pool streams it across, as a migration does
+    let metadata = match (
+        source["type"].as_str(),
+        body["devices"]["root"]["pool"].as_str(),
+    ) {
+        (Some("copy"), Some(_)) => json!({"fs_progress": format!("{}: 64% (25.00MB/s)", name)}),
+        _ => Value::Null,
+    };
+    state.sources.insert(name.clone(), source.clone());
+    operation_with(state, "Creating instance", metadata, |state| {
+        state.instances.insert(
+            name,
+            Instance {
+                status: "Stopped".to_string(),
+                instance_type,
+                ipv4: None,
+                snapshots,
+                console: String::new(),
+                config,
+                devices,
+                profiles,
+                disk_usage: 0,
+                metadata: default_metadata(),
+                templates: BTreeMap::new(),
+                exec: (0, String::new()),
+            },
+        );
+    })
+}
+
+/// Create an operation that has already finished, applying `change` unless
+/// operations are set to fail
+fn operation(
+    state: &mut State,
+    description: &str,
+    change: impl FnOnce(&mut State) + Send + 'static,
+) -> Response<Body> {
+    operation_with(state, description, Value::Null, change)
+}
+
+/// Create an operation as `operation` does, reporting `metadata`
+fn operation_with(
+    state: &mut State,
+    description: &str,
+    metadata: Value,
+    change: impl FnOnce(&mut State) + Send + 'static,
+) -> Response<Body> {
+    let id = Uuid::new_v4().to_string();
+    let (status, status_code, err) = match state.operation_error.clone() {
+        Some(err) => ("Failure", 400, err),
+        None if state.hold_operations => {
+            state.held.insert(id.clone(), Box::new(change));
+            ("Running", 103, String::new())
+        }
+        None => {
+            change(state);
+            ("Success", 200, String::new())
+        }
+    };
+
+    let operation = json!({
+        "id": id,
+        "class": "task",
+        "description": description,
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+        "status": status,
+        "status_code": status_code,
+        "may_cancel": status_code == 103,
+        "err": err,
+        "metadata": metadata,
+    });
+    state.operations.insert(id.clone(), operation.clone());
+
+    respond(
+        StatusCode::ACCEPTED,
+        json!({
+            "type": "async",
+            "status": "Operation created",
+            "status_code": 100,
+            "operation": format!("/1.0/operations/{}", id),
+            "metadata": operation,
+        }),
+    )
+}
+
+/// Metadata rendering /etc/hostname from hostname.tpl, as images have
+fn default_metadata() -> Value {
+    json!({
+        "architecture": "x86_64",
+        "creation_date": 1704067200,
+        "expiry_date": 0,
+        "properties": {"os": "Ubuntu", "release": "noble"},
+        "templates": {
+            "/etc/hostname": {
+                "when": ["create", "copy"],
+                "create_only": false,
+                "template": "hostname.tpl",
+                "properties": {}
+            }
+        }
+    })
+}
+
+fn default_profile() -> Value {
+    json!({
+        "name": "default",
+        "description": "Default LXD profile",
+        "devices": {
+            "root": {"type": "disk", "path": "/", "pool": "default"},
+            "eth0": {"type": "nic", "network": "lxdbr0", "name": "eth0"},
+        },
+    })
+}
+
+fn instance_json(
+    name: &str,
+    instance: &Instance,
+    profile_config: &BTreeMap<String, BTreeMap<String, String>>,
+) -> Value {
+    // The profiles' config in order, then the instance's own, as LXD expands it
+    let mut expanded = BTreeMap::new();
+    for profile in &instance.profiles {
+        expanded.extend(profile_config.get(profile).into_iter().flatten());
+    }
+    expanded.extend(&instance.config);
+    json!({
+        "architecture": "x86_64",
+        "config": instance.config,
+        "created_at": "2024-01-01T00:00:00Z",
+        "devices": instance.devices,
+        "ephemeral": false,
+        "expanded_config": expanded,
+        "expanded_devices": null,
+        "last_used_at": "2024-01-01T00:00:00Z",
+        "name": name,
+        "profiles": instance.profiles,
+        "stateful": false,
+        "status": instance.status,
+        "status_code": status_code(&instance.status),
+        "type": instance.instance_type,
+        "state": null,
+    })
+}
+
+fn state_json(instance: &Instance) -> Value {
+    let running = instance.status == "Running";
+    let network = match (&instance.ipv4, running) {
+        (Some(address), true) => json!({
+            "eth0": {
+                "addresses": [{
+                    "address": address,
+                    "family": "inet",
+                    "netmask": "24",
+                    "scope": "global",
+                }],
+                "counters": {},
+                "hwaddr": "00:16:3e:00:00:01",
+                "mtu": 1500,
+                "state": "up",
+                "type": "broadcast",
+            }
+        }),
+        _ => Value::Null,
+    };
+
+    json!({
+        "status": instance.status,
+        "status_code": status_code(&instance.status),
+        "network": network,
+        "pid": if running { 1234 } else { 0 },
+        "processes": if running { 10 } else { 0 },
+        "cpu": null,
+        "memory": null,
+        "disk": {"root": {"usage": instance.disk_usage}},
+    })
+}
+
+fn status_code(status: &str) -> i32 {
+    match status {
+        "Running" => 103,
+        _ => 102,
+    }
+}
+
+fn sync(metadata: Value) -> Response<Body> {
+    respond(
+        StatusCode::OK,
+        json!({
+            "type": "sync",
+            "status": "Success",
+            "status_code": 200,
+            "metadata": metadata,
+        }),
+    )
+}
+
+fn not_found() -> Response<Body> {
+    error(StatusCode::NOT_FOUND, "Not Found")
+}
+
+fn error(code: StatusCode, message: &str) -> Response<Body> {
+    respond(
+        code,
+        json!({
+            "type": "error",
+            "status": "",
+            "status_code": 0,
+            "error": message,
+            "error_code": code.as_u16(),
+            "metadata": null,
+        }),
+    )
+}
+
+/// Switch the connection to a websocket that sends the events passed to
+/// `FakeLxd::send_lifecycle` from now on
+fn subscribe(state: &Mutex<State>, request: Request<Incoming>) -> Response<Body> {
+    let Some(key) = request.headers().get("Sec-WebSocket-Key") else {
+        return error(StatusCode::BAD_REQUEST, "not a websocket request");
+    };
+    let accept = derive_accept_key(key.as_bytes());
+
+    let mut state = state.lock().unwrap();
+    state.requests.push(format!("GET {}", request.uri()));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    state.subscribers.push(tx);
+    tokio::spawn(async move {
+        let Ok(upgraded) = hyper::upgrade::on(request).await else {
+            return;
+        };
+        let mut socket =
+            WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None).await;
+        while let Some(event) = rx.recv().await {
+            if socket.send(Message::Text(event)).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header("Upgrade", "websocket")
+        .header("Connection", "Upgrade")
+        .header("Sec-WebSocket-Accept", accept)
+        .body(Body::default())
+        .unwrap()
+}
+
+fn respond(code: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(code)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
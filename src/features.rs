@@ -0,0 +1,60 @@
+//! Server features found missing during the session
+//!
+//! An older LXD, or a project that restricts them, answers some endpoints
+//! with "not found" or "not supported". The first such answer disables the
+//! feature until LXTUI connects to another server, so its menu entry is
+//! greyed out with the reason rather than failing again on every attempt.
+
+use std::collections::HashMap;
+
+/// Feature backed by an endpoint not every server has
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    Backups,
+    RefreshCopy,
+    ConsoleLog,
+    Metrics,
+}
+
+impl Feature {
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::Backups => "Backups",
+            Feature::RefreshCopy => "Refresh copy",
+            Feature::ConsoleLog => "Console log",
+            Feature::Metrics => "Metrics",
+        }
+    }
+
+    /// Feature behind a container menu entry, by the entry's label
+    pub fn of_menu_item(label: &str) -> Option<Feature> {
+        match label {
+            "Backups" => Some(Feature::Backups),
+            "Refresh Copy" => Some(Feature::RefreshCopy),
+            "Console Log" => Some(Feature::ConsoleLog),
+            _ => None,
+        }
+    }
+}
+
+/// Features the connected server turned out not to offer, with its answer
+#[derive(Debug, Default)]
+pub struct Unavailable {
+    reasons: HashMap<Feature, String>,
+}
+
+impl Unavailable {
+    /// Record the server's answer, returning false if already known
+    pub fn mark(&mut self, feature: Feature, reason: String) -> bool {
+        self.reasons.insert(feature, reason).is_none()
+    }
+
+    pub fn reason(&self, feature: Feature) -> Option<&str> {
+        self.reasons.get(&feature).map(String::as_str)
+    }
+
+    /// Forget everything, for a newly connected server
+    pub fn clear(&mut self) {
+        self.reasons.clear();
+    }
+}
@@ -0,0 +1,143 @@
+//! Text fields
+//!
+//! A `TextField` is the text typed at a prompt with a cursor in it. Keys
+//! are mapped to `FieldEdit`s in `input.rs`, so the same editing works in
+//! every prompt: moving by character or word, Home/End, deleting either
+//! side of the cursor and pasting.
+
+/// A change to a text field
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldEdit {
+    Insert(char),
+    Paste(String), // Already filtered to the characters the field accepts
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    WordLeft,
+    WordRight,
+    Home,
+    End,
+    DeleteWord,    // The word before the cursor
+    DeleteToStart, // Everything before the cursor
+    DeleteToEnd,   // Everything after the cursor
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextField {
+    text: String,
+    cursor: usize, // Byte offset, always on a character boundary
+}
+
+impl TextField {
+    /// A field holding `text`, with the cursor at the end
+    pub fn new(text: &str) -> Self {
+        TextField {
+            text: text.to_string(),
+            cursor: text.len(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// The text before and after the cursor
+    pub fn split(&self) -> (&str, &str) {
+        self.text.split_at(self.cursor)
+    }
+
+    /// Replace the text, moving the cursor to the end
+    pub fn set(&mut self, text: &str) {
+        *self = TextField::new(text);
+    }
+
+    pub fn clear(&mut self) {
+        *self = TextField::default();
+    }
+
+    /// The text, leaving the field empty
+    pub fn take(&mut self) -> String {
+        std::mem::take(self).text
+    }
+
+    pub fn apply(&mut self, edit: FieldEdit) {
+        match edit {
+            FieldEdit::Insert(c) => {
+                self.text.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+            }
+            FieldEdit::Paste(text) => {
+                self.text.insert_str(self.cursor, &text);
+                self.cursor += text.len();
+            }
+            FieldEdit::Backspace => {
+                let start = self.previous();
+                self.text.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            FieldEdit::Delete => {
+                let end = self.next();
+                self.text.replace_range(self.cursor..end, "");
+            }
+            FieldEdit::Left => self.cursor = self.previous(),
+            FieldEdit::Right => self.cursor = self.next(),
+            FieldEdit::WordLeft => self.cursor = self.word_start(),
+            FieldEdit::WordRight => self.cursor = self.word_end(),
+            FieldEdit::Home => self.cursor = 0,
+            FieldEdit::End => self.cursor = self.text.len(),
+            FieldEdit::DeleteWord => {
+                let start = self.word_start();
+                self.text.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            FieldEdit::DeleteToStart => {
+                self.text.replace_range(..self.cursor, "");
+                self.cursor = 0;
+            }
+            FieldEdit::DeleteToEnd => self.text.truncate(self.cursor),
+        }
+    }
+
+    /// Where the character before the cursor starts
+    fn previous(&self) -> usize {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    /// Where the character after the cursor ends
+    fn next(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    /// The start of the word before the cursor, skipping separators
+    fn word_start(&self) -> usize {
+        let before = self.text[..self.cursor].trim_end_matches(|c: char| !is_word_char(c));
+        before.trim_end_matches(is_word_char).len()
+    }
+
+    /// The end of the word after the cursor, skipping separators
+    fn word_end(&self) -> usize {
+        let after = &self.text[self.cursor..];
+        let word = after.trim_start_matches(|c: char| !is_word_char(c));
+        let rest = word.trim_start_matches(is_word_char);
+        self.text.len() - rest.len()
+    }
+}
+
+/// Words are runs of letters and digits, so `ubuntu:24.04` is three
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,79 @@
+//! Tests of editing text fields
+
+use super::{FieldEdit, TextField};
+
+fn edited(text: &str, edits: Vec<FieldEdit>) -> (String, String) {
+    let mut field = TextField::new(text);
+    for edit in edits {
+        field.apply(edit);
+    }
+    let (before, after) = field.split();
+    (before.to_string(), after.to_string())
+}
+
+fn parts(before: &str, after: &str) -> (String, String) {
+    (before.to_string(), after.to_string())
+}
+
+#[test]
+fn inserts_and_deletes_at_the_cursor() {
+    use FieldEdit::*;
+    assert_eq!(
+        edited("web2", vec![Left, Insert('-'), Home, Insert('x')]),
+        parts("x", "web-2")
+    );
+    assert_eq!(
+        edited("web2", vec![Home, Right, Delete, Backspace]),
+        parts("", "b2")
+    );
+    // Nothing to delete at either end
+    assert_eq!(edited("", vec![Backspace, Delete, Left]), parts("", ""));
+    assert_eq!(edited("ab", vec![Delete, Right]), parts("ab", ""));
+}
+
+#[test]
+fn moves_and_deletes_by_word() {
+    use FieldEdit::*;
+    assert_eq!(
+        edited("images:debian/12", vec![WordLeft]),
+        parts("images:debian/", "12")
+    );
+    assert_eq!(
+        edited("images:debian/12", vec![WordLeft, WordLeft, DeleteWord]),
+        parts("", "debian/12")
+    );
+    assert_eq!(
+        edited("images:debian/12", vec![Home, WordRight]),
+        parts("images", ":debian/12")
+    );
+    assert_eq!(edited("start web1 ", vec![DeleteWord]), parts("start ", ""));
+}
+
+#[test]
+fn deletes_either_side_and_pastes() {
+    use FieldEdit::*;
+    assert_eq!(
+        edited("filter status", vec![WordLeft, DeleteToStart]),
+        parts("", "status")
+    );
+    assert_eq!(
+        edited("filter status", vec![WordLeft, DeleteToEnd]),
+        parts("filter ", "")
+    );
+    assert_eq!(
+        edited("db", vec![Home, Paste("prod-".to_string()), End]),
+        parts("prod-db", "")
+    );
+}
+
+#[test]
+fn keeps_the_cursor_on_character_boundaries() {
+    use FieldEdit::*;
+    assert_eq!(edited("añb", vec![Left, Left, Delete]), parts("a", "b"));
+    assert_eq!(edited("añ", vec![Backspace]), parts("a", ""));
+
+    let mut field = TextField::new("tag");
+    assert_eq!(field.take(), "tag");
+    assert!(field.is_empty());
+    assert_eq!(field.split(), ("", ""));
+}
@@ -0,0 +1,108 @@
+//! Container list filters
+//!
+//! A filter is a list of `field=value` terms, all of which a container must
+//! match to be shown, e.g. `status=Running name=web tag=prod`.
+
+use crate::lxc::Container;
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum FilterError {
+    #[error("Expected field=value, got '{0}'")]
+    Syntax(String),
+    #[error("Unknown filter field '{0}' (use name, status, type, remote or tag)")]
+    UnknownField(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,   // Substring of the name
+    Status, // Running, Stopped, ...
+    Type,   // container or vm
+    Remote,
+    Tag, // One of the container's tags
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    field: Field,
+    value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerFilter {
+    terms: Vec<Term>,
+}
+
+impl ContainerFilter {
+    /// Parse space-separated `field=value` terms. Matching ignores case.
+    pub fn parse(text: &str) -> Result<Self, FilterError> {
+        let terms = text
+            .split_whitespace()
+            .map(|term| {
+                let (field, value) = term
+                    .split_once('=')
+                    .filter(|(_, value)| !value.is_empty())
+                    .ok_or_else(|| FilterError::Syntax(term.to_string()))?;
+                let field = match field.to_lowercase().as_str() {
+                    "name" => Field::Name,
+                    "status" | "state" => Field::Status,
+                    "type" => Field::Type,
+                    "remote" => Field::Remote,
+                    "tag" => Field::Tag,
+                    _ => return Err(FilterError::UnknownField(field.to_string())),
+                };
+                Ok(Term {
+                    field,
+                    value: value.to_lowercase(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ContainerFilter { terms })
+    }
+
+    pub fn matches(&self, container: &Container) -> bool {
+        self.terms.iter().all(|term| match term.field {
+            Field::Name => container.name.to_lowercase().contains(&term.value),
+            Field::Status => container.status.to_lowercase() == term.value,
+            Field::Type => match term.value.as_str() {
+                "vm" => container.container_type == "virtual-machine",
+                value => container.container_type == value,
+            },
+            Field::Remote => {
+                container
+                    .remote
+                    .as_deref()
+                    .unwrap_or("local")
+                    .to_lowercase()
+                    == term.value
+            }
+            Field::Tag => container
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase() == term.value),
+        })
+    }
+}
+
+impl fmt::Display for ContainerFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let terms: Vec<String> = self
+            .terms
+            .iter()
+            .map(|term| {
+                let field = match term.field {
+                    Field::Name => "name",
+                    Field::Status => "status",
+                    Field::Type => "type",
+                    Field::Remote => "remote",
+                    Field::Tag => "tag",
+                };
+                format!("{}={}", field, term.value)
+            })
+            .collect();
+        write!(f, "{}", terms.join(" "))
+    }
+}
@@ -0,0 +1,54 @@
+//! Status-change flashes
+//!
+//! Compares each refreshed container list with the previous one and
+//! remembers the containers whose status changed, so their rows can be
+//! highlighted in the colour of the new status for a moment: green when
+//! one starts, red when one stops.
+
+use crate::lxc::Container;
+use crate::theme::Tone;
+use std::collections::HashMap;
+use tokio::time::{Duration, Instant};
+
+/// How long a row stays highlighted after its status changes
+const FLASH_DURATION: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Default)]
+pub struct Flashes {
+    statuses: HashMap<String, String>, // As of the previous check
+    active: HashMap<String, (Tone, Instant)>, // New status and when, by name
+}
+
+impl Flashes {
+    /// Check a refreshed container list. The first list flashes nothing,
+    /// and neither do containers appearing or disappearing.
+    pub fn check(&mut self, containers: &[Container], now: Instant) {
+        self.active
+            .retain(|_, (_, at)| now.duration_since(*at) < FLASH_DURATION);
+
+        let mut statuses = HashMap::new();
+        for container in containers {
+            let name = container.qualified_name();
+            if let Some(previous) = self.statuses.get(&name) {
+                if *previous != container.status {
+                    self.active
+                        .insert(name.clone(), (Tone::of_status(&container.status), now));
+                }
+            }
+            statuses.insert(name, container.status.clone());
+        }
+        self.active.retain(|name, _| statuses.contains_key(name));
+        self.statuses = statuses;
+    }
+
+    /// The tone `container`'s row flashes in, while it does
+    pub fn tone(&self, container: &str, now: Instant) -> Option<Tone> {
+        self.active
+            .get(container)
+            .filter(|(_, at)| now.duration_since(*at) < FLASH_DURATION)
+            .map(|(tone, _)| *tone)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,55 @@
+//! Tests of flashing rows whose status changed
+
+use super::{Flashes, FLASH_DURATION};
+use crate::command::tests::container;
+use crate::theme::Tone;
+use tokio::time::{Duration, Instant};
+
+#[test]
+fn flashes_in_the_new_status_for_a_moment() {
+    let mut flashes = Flashes::default();
+    let now = Instant::now();
+
+    flashes.check(
+        &[
+            container("web1", "Stopped", "container"),
+            container("db1", "Running", "container"),
+        ],
+        now,
+    );
+    assert_eq!(flashes.tone("web1", now), None);
+
+    flashes.check(
+        &[
+            container("web1", "Running", "container"),
+            container("db1", "Stopped", "container"),
+        ],
+        now,
+    );
+    assert_eq!(flashes.tone("web1", now), Some(Tone::Good));
+    assert_eq!(flashes.tone("db1", now), Some(Tone::Bad));
+
+    // Gone once the moment has passed
+    let later = now + FLASH_DURATION;
+    assert_eq!(
+        flashes.tone("web1", later - Duration::from_millis(1)),
+        Some(Tone::Good)
+    );
+    assert_eq!(flashes.tone("web1", later), None);
+}
+
+#[test]
+fn new_and_removed_containers_do_not_flash() {
+    let mut flashes = Flashes::default();
+    let now = Instant::now();
+
+    flashes.check(&[container("web1", "Running", "container")], now);
+    flashes.check(&[container("web1", "Stopped", "container")], now);
+    flashes.check(&[container("db1", "Running", "container")], now);
+    assert_eq!(flashes.tone("db1", now), None);
+    assert_eq!(flashes.tone("web1", now), None);
+
+    // Coming back isn't a change either
+    flashes.check(&[container("web1", "Stopped", "container")], now);
+    assert_eq!(flashes.tone("web1", now), None);
+}
@@ -0,0 +1,110 @@
+//! Background LXD health check
+//!
+//! A task asks the active server for `GET /1.0` every few seconds, as the
+//! polling scheduler allows, and
+//! reports whether it answered and how long it took, so the title bar shows
+//! the server going away or coming back without a manual refresh.
+
+use crate::lxc::LxcClient;
+use crate::poll::{Category, Poller};
+use log::{info, warn};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration, Instant};
+
+/// Time between checks, before jitter
+pub const INTERVAL: Duration = Duration::from_secs(5);
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Beat {
+    pub up: bool,
+    pub latency: Duration, // Until the answer or the failure
+    pub at: Instant,
+}
+
+/// Checks of one server, running until dropped or restarted
+pub struct Heartbeat {
+    tx: mpsc::UnboundedSender<Beat>,
+    rx: mpsc::UnboundedReceiver<Beat>,
+    task: Option<JoinHandle<()>>,
+    pub last: Option<Beat>,
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Heartbeat {
+            tx,
+            rx,
+            task: None,
+            last: None,
+        }
+    }
+
+    /// Check `client` from now on instead of any previous server
+    pub fn start(&mut self, client: LxcClient, polls: Poller) {
+        self.stop();
+        self.last = None;
+        let tx = self.tx.clone();
+        self.task = Some(tokio::spawn(beat(client, polls, tx)));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+
+    /// Take in finished checks, returning the newest if there was one
+    pub fn drain(&mut self) -> Option<Beat> {
+        let mut newest = None;
+        while let Ok(beat) = self.rx.try_recv() {
+            newest = Some(beat);
+        }
+        if newest.is_some() {
+            self.last = newest;
+        }
+        newest
+    }
+
+    /// Latency of the last answered check
+    pub fn latency(&self) -> Option<Duration> {
+        self.last.filter(|beat| beat.up).map(|beat| beat.latency)
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+async fn beat(client: LxcClient, polls: Poller, tx: mpsc::UnboundedSender<Beat>) {
+    let mut was_up = None;
+    loop {
+        polls.take(Category::Health).await;
+        let started = Instant::now();
+        let up = client.server_info().await.is_ok();
+        let beat = Beat {
+            up,
+            latency: started.elapsed(),
+            at: Instant::now(),
+        };
+
+        match (was_up, up) {
+            (Some(false), true) => info!("LXD answered again"),
+            (Some(true) | None, false) => warn!("LXD health check failed"),
+            _ => {}
+        }
+        was_up = Some(up);
+
+        if tx.send(beat).is_err() {
+            return;
+        }
+        sleep(polls.jittered(INTERVAL)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,41 @@
+//! Tests of the health check against the fake LXD server
+
+use super::{Beat, Heartbeat};
+use crate::fake_lxd::FakeLxd;
+use crate::lxc::LxcClient;
+use crate::lxd_api::LxdApiClient;
+use crate::poll::Poller;
+use tokio::time::{sleep, Duration};
+
+/// Wait for the first check, which runs as soon as the heartbeat starts
+async fn first_beat(heartbeat: &mut Heartbeat) -> Beat {
+    for _ in 0..50 {
+        if let Some(beat) = heartbeat.drain() {
+            return beat;
+        }
+        sleep(Duration::from_millis(20)).await;
+    }
+    panic!("no health check reported");
+}
+
+#[tokio::test]
+async fn reports_a_server_that_answers() {
+    let lxd = FakeLxd::start();
+    let mut heartbeat = Heartbeat::new();
+    heartbeat.start(lxd.client(), Poller::default());
+
+    let beat = first_beat(&mut heartbeat).await;
+    assert!(beat.up);
+    assert_eq!(heartbeat.latency(), Some(beat.latency));
+    assert!(lxd.requests().contains(&"GET /1.0".to_string()));
+}
+
+#[tokio::test]
+async fn reports_a_missing_server() {
+    let client = LxcClient::from_api(LxdApiClient::unix("/nonexistent/lxd.socket".to_string()));
+    let mut heartbeat = Heartbeat::new();
+    heartbeat.start(client, Poller::default());
+
+    assert!(!first_beat(&mut heartbeat).await.up);
+    assert_eq!(heartbeat.latency(), None);
+}
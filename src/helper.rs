@@ -0,0 +1,186 @@
+//! Privileged socket helper
+//!
+//! When the local LXD socket is only accessible to root, LXTUI can start a
+//! copy of itself through sudo or pkexec that proxies the socket to one owned
+//! by the invoking user. Only the helper runs privileged; the TUI does not.
+
+use crate::lxd_api::{self, LxdApiError};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::io::AsyncReadExt;
+use tokio::net::{UnixListener, UnixStream};
+use uuid::Uuid;
+
+/// Long enough to type a password at the sudo prompt
+const START_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Error)]
+pub enum HelperError {
+    #[error("Neither sudo nor pkexec is installed")]
+    NoEscalationTool,
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("The privileged helper exited before it was ready")]
+    Exited,
+    #[error("Timed out waiting for the privileged helper")]
+    Timeout,
+    #[error(transparent)]
+    Lxd(#[from] LxdApiError),
+}
+
+/// A running helper; dropping it closes its stdin, which stops it
+pub struct PrivilegedHelper {
+    child: Child,
+    dir: PathBuf,
+    socket: PathBuf,
+}
+
+impl PrivilegedHelper {
+    /// Start the helper and wait for its socket. sudo and pkexec prompt on
+    /// the terminal, so call this before the TUI takes it over.
+    pub fn start() -> Result<Self, HelperError> {
+        let escalate = escalation_tool().ok_or(HelperError::NoEscalationTool)?;
+
+        // A private directory keeps other users away from the socket before
+        // the helper hands it over to us
+        let dir = dirs::runtime_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(format!("lxtui-helper-{}", Uuid::new_v4()));
+        std::fs::create_dir(&dir)?;
+        std::fs::set_permissions(&dir, std::os::unix::fs::PermissionsExt::from_mode(0o700))?;
+        let socket = dir.join("lxd.socket");
+
+        let child = Command::new(escalate)
+            .arg(std::env::current_exe()?)
+            .arg("--socket-proxy")
+            .arg(&socket)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let mut helper = PrivilegedHelper { child, dir, socket };
+
+        let started = Instant::now();
+        while !helper.socket.exists() {
+            if helper.child.try_wait()?.is_some() {
+                return Err(HelperError::Exited);
+            }
+            if started.elapsed() > START_TIMEOUT {
+                return Err(HelperError::Timeout);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        Ok(helper)
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket
+    }
+}
+
+impl Drop for PrivilegedHelper {
+    fn drop(&mut self) {
+        drop(self.child.stdin.take());
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Offer to start the helper when the local socket needs root, returning it
+/// once running. The local client then connects through its socket.
+pub fn offer() -> Option<PrivilegedHelper> {
+    let socket = lxd_api::socket_permission_denied()?;
+    if !io::stdin().is_terminal() {
+        return None;
+    }
+
+    println!("The LXD socket at {} requires root access.", socket);
+    print!("Start a privileged helper so LXTUI itself runs unprivileged? [Y/n] ");
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    let _ = io::stdin().lock().read_line(&mut answer);
+    if answer.trim().to_lowercase().starts_with('n') {
+        return None;
+    }
+
+    match PrivilegedHelper::start() {
+        Ok(helper) => {
+            log::info!("Privileged helper proxying {}", socket);
+            lxd_api::set_local_socket(helper.socket_path().display().to_string());
+            Some(helper)
+        }
+        Err(e) => {
+            eprintln!("Failed to start the privileged helper: {}", e);
+            None
+        }
+    }
+}
+
+/// Helper mode: forward connections on `listen` to the LXD socket until
+/// stdin closes
+pub async fn run_proxy(listen: &Path) -> Result<(), HelperError> {
+    let target = lxd_api::find_socket()?;
+
+    // Bind under a temporary name so the parent only sees the socket once
+    // it has been handed over
+    let staging = listen.with_extension("staging");
+    let listener = UnixListener::bind(&staging)?;
+    hand_over(&staging)?;
+    std::fs::rename(&staging, listen)?;
+
+    let mut stdin = tokio::io::stdin();
+    let mut buf = [0u8; 64];
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (mut client, _) = accepted?;
+                let target = target.clone();
+                tokio::spawn(async move {
+                    if let Ok(mut server) = UnixStream::connect(&target).await {
+                        let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+                    }
+                });
+            }
+            // The parent closes stdin when it exits
+            read = stdin.read(&mut buf) => {
+                if matches!(read, Ok(0) | Err(_)) {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = std::fs::remove_file(listen);
+    Ok(())
+}
+
+/// Give the socket to the user who ran sudo or pkexec, readable only by them
+fn hand_over(path: &Path) -> io::Result<()> {
+    let uid = ["SUDO_UID", "PKEXEC_UID"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok()?.parse().ok());
+    let gid = std::env::var("SUDO_GID")
+        .ok()
+        .and_then(|gid| gid.parse().ok());
+
+    std::fs::set_permissions(path, std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    std::os::unix::fs::chown(path, uid, gid)
+}
+
+/// sudo, or pkexec where sudo isn't installed
+pub fn escalation_tool() -> Option<&'static str> {
+    ["sudo", "pkexec"]
+        .into_iter()
+        .find(|program| in_path(program))
+}
+
+pub fn in_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
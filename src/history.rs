@@ -0,0 +1,120 @@
+//! Operation history
+//!
+//! Finished operations are appended to a JSON Lines file so the operations
+//! sidebar still shows what was done after LXTUI restarts. Entries older
+//! than the retention period are dropped when the history is loaded.
+
+use crate::config::{self, HistoryConfig};
+use crate::lxd_api::LxdOperation;
+use chrono::{DateTime, Local, TimeDelta};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Failed to serialize operation: {0}")]
+    SerializeError(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Success,
+    Failed,
+    Cancelled,
+}
+
+/// One finished operation, as stored in the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationRecord {
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub outcome: Outcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub finished_at: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lxd_operation: Option<LxdOperation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+}
+
+pub struct History {
+    path: PathBuf,
+    retention: TimeDelta,
+}
+
+impl History {
+    /// The configured history, or None when it is turned off
+    pub fn new(config: &HistoryConfig) -> Option<Self> {
+        (config.retention_days > 0).then(|| History {
+            path: config.history_path(),
+            retention: TimeDelta::days(config.retention_days.into()),
+        })
+    }
+
+    /// Records still within the retention period, oldest first. Expired and
+    /// unreadable lines are dropped from the file.
+    pub fn load(&self, now: DateTime<Local>) -> Result<Vec<OperationRecord>, HistoryError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let cutoff = now - self.retention;
+        let mut records = Vec::new();
+        let mut pruned = false;
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str::<OperationRecord>(line) {
+                Ok(record) if record.finished_at >= cutoff => records.push(record),
+                Ok(_) => pruned = true,
+                Err(e) => {
+                    warn!("Skipping unreadable history entry: {}", e);
+                    pruned = true;
+                }
+            }
+        }
+
+        if pruned {
+            let mut text = String::new();
+            for record in &records {
+                text.push_str(&serde_json::to_string(record)?);
+                text.push('\n');
+            }
+            config::write_private(&self.path, text.as_bytes())?;
+        }
+        Ok(records)
+    }
+
+    pub fn append(&self, record: &OperationRecord) -> Result<(), HistoryError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        options.open(&self.path)?.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
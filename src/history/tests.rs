@@ -0,0 +1,77 @@
+//! Tests of the operation history file
+
+use super::{History, OperationRecord, Outcome};
+use crate::config::HistoryConfig;
+use chrono::{Local, TimeDelta, TimeZone};
+use std::fs;
+use uuid::Uuid;
+
+fn history(retention_days: u32) -> (History, std::path::PathBuf) {
+    let dir = std::env::temp_dir().join(format!("lxtui-history-{}", Uuid::new_v4()));
+    let config = HistoryConfig {
+        path: Some(dir.join("operations.jsonl")),
+        retention_days,
+    };
+    (History::new(&config).unwrap(), dir)
+}
+
+fn record(description: &str, days_ago: i64) -> OperationRecord {
+    let now = Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+    OperationRecord {
+        description: description.to_string(),
+        container: Some("web1".to_string()),
+        outcome: Outcome::Failed,
+        error: Some("Instance is busy".to_string()),
+        finished_at: now - TimeDelta::days(days_ago),
+        lxd_operation: None,
+        kind: None,
+        duration_secs: None,
+    }
+}
+
+#[test]
+fn zero_retention_turns_history_off() {
+    let config = HistoryConfig {
+        path: None,
+        retention_days: 0,
+    };
+    assert!(History::new(&config).is_none());
+}
+
+#[test]
+fn appended_records_load_back() {
+    let (history, dir) = history(7);
+    let now = Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+    assert!(history.load(now).unwrap().is_empty());
+
+    history
+        .append(&record("Start container 'web1'", 1))
+        .unwrap();
+    history.append(&record("Stop container 'web1'", 0)).unwrap();
+
+    let loaded = history.load(now).unwrap();
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded[0].description, "Start container 'web1'");
+    assert_eq!(loaded[1].outcome, Outcome::Failed);
+    assert_eq!(loaded[1].error.as_deref(), Some("Instance is busy"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn expired_and_unreadable_entries_are_dropped() {
+    let (history, dir) = history(7);
+    let now = Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+    history.append(&record("Old", 8)).unwrap();
+    history.append(&record("Recent", 2)).unwrap();
+    let path = dir.join("operations.jsonl");
+    let mut text = fs::read_to_string(&path).unwrap();
+    text.push_str("not json\n");
+    fs::write(&path, text).unwrap();
+
+    let loaded = history.load(now).unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].description, "Recent");
+    // The file was rewritten without them
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+    fs::remove_dir_all(dir).unwrap();
+}
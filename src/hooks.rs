@@ -0,0 +1,184 @@
+//! User-defined custom actions
+//!
+//! `[[actions]]` entries in the config file add container menu items that run
+//! an external command with the selected container's details substituted.
+//! The TUI is suspended while the command has the terminal, as it is for
+//! the built-in SSH action and the host shell.
+
+use crate::config::CustomAction;
+use crate::keymap;
+use crate::lxc::Container;
+use std::io::{self, BufRead, Write};
+use std::process::{Command, ExitStatus};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum HookError {
+    #[error("Unknown placeholder '{{{0}}}' (use {{name}}, {{remote}} or {{ip}})")]
+    UnknownPlaceholder(String),
+    #[error("Unclosed '{{' in command")]
+    Unclosed,
+    #[error("'{0}' has no IPv4 address")]
+    NoAddress(String),
+}
+
+/// A custom action's command, ready to run once the TUI is suspended
+#[derive(Debug, Clone)]
+pub struct ExternalCommand {
+    pub name: String,
+    pub command: String,
+    pub pause: bool,
+}
+
+/// A warning for each custom action on a key the container menu already
+/// answers, which then only the arrows and Enter reach
+pub fn shadowed(actions: &[CustomAction]) -> Vec<String> {
+    let taken: Vec<char> = keymap::CONTAINER_MENU
+        .iter()
+        .flat_map(|binding| binding.keys.split('/'))
+        .filter_map(|key| {
+            let mut chars = key.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        })
+        .chain(['j', 'k'])
+        .collect();
+    actions
+        .iter()
+        .filter_map(|action| {
+            let key = action.key.filter(|key| taken.contains(key))?;
+            Some(format!(
+                "action {}: '{}' is taken by the container menu, use Enter",
+                action.name, key
+            ))
+        })
+        .collect()
+}
+
+/// Config key naming the user the SSH action logs in as
+pub const SSH_USER_KEY: &str = "user.lxtui.ssh-user";
+
+/// `ssh [user@]ip` for the container's first IPv4 address. Without
+/// `user.lxtui.ssh-user` ssh picks the user, e.g. from `~/.ssh/config`.
+pub fn ssh_command(container: &Container) -> Result<String, HookError> {
+    let ip = container
+        .ipv4
+        .first()
+        .ok_or_else(|| HookError::NoAddress(container.name.clone()))?;
+    Ok(match &container.ssh_user {
+        Some(user) => format!("ssh {}@{}", user, ip),
+        None => format!("ssh {}", ip),
+    })
+}
+
+/// The user's login shell from `$SHELL`, or `/bin/sh` without one
+pub fn host_shell(shell: Option<&str>) -> String {
+    shell
+        .map(str::trim)
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or("/bin/sh")
+        .to_string()
+}
+
+/// How to open a container in VS Code over SSH: the `code` command, and an
+/// SSH config stanza that names the host for the editor's remote explorer
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorRemote {
+    pub command: String,
+    pub ssh_config: String,
+}
+
+impl EditorRemote {
+    pub fn to_text(&self) -> String {
+        format!("{}\n\n{}", self.command, self.ssh_config)
+    }
+}
+
+/// The editor remote for the container's first IPv4 address, logging in as
+/// `user.lxtui.ssh-user` as the SSH action does
+pub fn editor_remote(container: &Container) -> Result<EditorRemote, HookError> {
+    let ip = container
+        .ipv4
+        .first()
+        .ok_or_else(|| HookError::NoAddress(container.name.clone()))?;
+    let target = match &container.ssh_user {
+        Some(user) => format!("{}@{}", user, ip),
+        None => ip.clone(),
+    };
+    let host = format!("lxtui-{}", container.qualified_name().replace(':', "-"));
+    let mut ssh_config = format!("Host {}\n    HostName {}\n", host, ip);
+    if let Some(user) = &container.ssh_user {
+        ssh_config.push_str(&format!("    User {}\n", user));
+    }
+    Ok(EditorRemote {
+        command: format!("code --remote ssh-remote+{}", target),
+        ssh_config,
+    })
+}
+
+/// Substitute the container's details into `template`. `{{` and `}}` are
+/// literal braces. Names and addresses never need shell quoting: LXD only
+/// allows letters, digits and dashes in instance names.
+pub fn expand(template: &str, container: &Container) -> Result<String, HookError> {
+    let mut command = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        command.push_str(&rest[..start]);
+        let brace = &rest[start..];
+
+        if brace.starts_with("{{") || brace.starts_with("}}") {
+            command.push_str(&brace[..1]);
+            rest = &brace[2..];
+            continue;
+        }
+        if let Some(after) = brace.strip_prefix('}') {
+            command.push('}');
+            rest = after;
+            continue;
+        }
+
+        let end = brace.find('}').ok_or(HookError::Unclosed)?;
+        let value = match &brace[1..end] {
+            "name" => container.name.clone(),
+            "remote" => container
+                .remote
+                .clone()
+                .unwrap_or_else(|| "local".to_string()),
+            "ip" => container
+                .ipv4
+                .first()
+                .cloned()
+                .ok_or_else(|| HookError::NoAddress(container.name.clone()))?,
+            other => return Err(HookError::UnknownPlaceholder(other.to_string())),
+        };
+        command.push_str(&value);
+        rest = &brace[end + 1..];
+    }
+    command.push_str(rest);
+
+    Ok(command)
+}
+
+/// Run the command on the terminal, which the caller has handed back to the
+/// shell, and wait for Enter afterwards if asked to or if it failed
+pub fn run(external: &ExternalCommand) -> io::Result<ExitStatus> {
+    println!("$ {}", external.command);
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(&external.command)
+        .status()?;
+
+    if external.pause || !status.success() {
+        print!(
+            "\n{} ({}). Press Enter to return to LXTUI.",
+            external.name, status
+        );
+        io::stdout().flush()?;
+        io::stdin().lock().read_line(&mut String::new())?;
+    }
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests;
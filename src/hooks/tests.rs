@@ -0,0 +1,109 @@
+//! Tests of custom action command expansion, the SSH command and the host shell
+
+use super::{editor_remote, expand, host_shell, shadowed, ssh_command, HookError};
+use crate::command::tests::container;
+use crate::config::CustomAction;
+use crate::lxc::Container;
+
+fn web1(ipv4: &[&str]) -> Container {
+    Container {
+        ipv4: ipv4.iter().map(|ip| ip.to_string()).collect(),
+        ..container("web1", "Running", "container")
+    }
+}
+
+#[test]
+fn substitutes_placeholders() {
+    let container = web1(&["10.0.0.10", "10.0.0.11"]);
+    assert_eq!(
+        expand("ssh root@{ip}", &container).unwrap(),
+        "ssh root@10.0.0.10"
+    );
+    assert_eq!(
+        expand("ansible-playbook -l {name} site.yml", &container).unwrap(),
+        "ansible-playbook -l web1 site.yml"
+    );
+    assert_eq!(
+        expand("lxc info {remote}:{name} | awk '{{print $1}}'", &container).unwrap(),
+        "lxc info local:web1 | awk '{print $1}'"
+    );
+}
+
+#[test]
+fn rejects_bad_templates() {
+    assert!(matches!(
+        expand("ssh {user}@{ip}", &web1(&["10.0.0.10"])),
+        Err(HookError::UnknownPlaceholder(p)) if p == "user"
+    ));
+    assert!(matches!(
+        expand("ping {ip", &web1(&["10.0.0.10"])),
+        Err(HookError::Unclosed)
+    ));
+    assert!(matches!(
+        expand("ssh root@{ip}", &web1(&[])),
+        Err(HookError::NoAddress(_))
+    ));
+}
+
+#[test]
+fn ssh_uses_the_configured_user() {
+    let mut container = web1(&["10.0.0.10"]);
+    assert_eq!(ssh_command(&container).unwrap(), "ssh 10.0.0.10");
+
+    container.ssh_user = Some("ubuntu".to_string());
+    assert_eq!(ssh_command(&container).unwrap(), "ssh ubuntu@10.0.0.10");
+
+    assert!(matches!(
+        ssh_command(&web1(&[])),
+        Err(HookError::NoAddress(_))
+    ));
+}
+
+#[test]
+fn host_shell_falls_back_to_sh() {
+    assert_eq!(host_shell(Some("/usr/bin/zsh")), "/usr/bin/zsh");
+    assert_eq!(host_shell(Some(" ")), "/bin/sh");
+    assert_eq!(host_shell(None), "/bin/sh");
+}
+
+#[test]
+fn editor_remote_names_the_host_for_ssh() {
+    let mut container = web1(&["10.0.0.10"]);
+    container.ssh_user = Some("ubuntu".to_string());
+    container.remote = Some("lab".to_string());
+    let remote = editor_remote(&container).unwrap();
+    assert_eq!(remote.command, "code --remote ssh-remote+ubuntu@10.0.0.10");
+    assert_eq!(
+        remote.ssh_config,
+        "Host lxtui-lab-web1\n    HostName 10.0.0.10\n    User ubuntu\n"
+    );
+
+    assert!(matches!(
+        editor_remote(&web1(&[])),
+        Err(HookError::NoAddress(_))
+    ));
+}
+
+#[test]
+fn keys_the_container_menu_takes_are_warned_about() {
+    let action = |name: &str, key: char| CustomAction {
+        name: name.to_string(),
+        command: "true".to_string(),
+        key: Some(key),
+        pause: false,
+    };
+    let actions = [
+        action("Notify", 'n'),
+        action("Jump", 'j'),
+        action("Provision", 'x'),
+        action("Third", '3'),
+    ];
+    assert_eq!(
+        shadowed(&actions),
+        [
+            "action Notify: 'n' is taken by the container menu, use Enter",
+            "action Jump: 'j' is taken by the container menu, use Enter",
+            "action Third: '3' is taken by the container menu, use Enter",
+        ]
+    );
+}
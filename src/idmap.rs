@@ -0,0 +1,327 @@
+//! ID mapping
+//!
+//! Checks `raw.idmap` entries (`both 1000 1000`, `uid 50-59 500-509`) for
+//! mismatched ranges and IDs mapped twice before setting them, since a bad
+//! map only shows when the container next fails to start.
+
+use crate::edit::InstanceConfig;
+use crate::lxd_api::LxdContainer;
+use std::fmt;
+use thiserror::Error;
+
+pub const RAW_KEY: &str = "raw.idmap";
+pub const ISOLATED_KEY: &str = "security.idmap.isolated";
+const PRIVILEGED_KEY: &str = "security.privileged";
+
+/// The map the running container uses, and the one it starts with next
+const CURRENT_KEY: &str = "volatile.idmap.current";
+const NEXT_KEY: &str = "volatile.idmap.next";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum IdmapError {
+    #[error("Line {0}: give uid, gid or both, then the host IDs and the container IDs")]
+    Syntax(usize),
+    #[error("Line {line}: '{value}' is not an ID or a range like 1000-1009")]
+    BadRange { line: usize, value: String },
+    #[error("Line {0}: the host and container ranges differ in size")]
+    SizeMismatch(usize),
+    #[error("Lines {first} and {second} both map {side} {kind} {id}")]
+    Overlap {
+        first: usize,
+        second: usize,
+        side: &'static str, // "host" or "container"
+        kind: &'static str,
+        id: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Uid,
+    Gid,
+    Both,
+}
+
+impl Kind {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "uid" => Some(Kind::Uid),
+            "gid" => Some(Kind::Gid),
+            "both" => Some(Kind::Both),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Uid => "uid",
+            Kind::Gid => "gid",
+            Kind::Both => "both",
+        }
+    }
+
+    fn covers(self, kind: &str) -> bool {
+        self == Kind::Both || self.name() == kind
+    }
+}
+
+/// IDs `first` to `last` inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub first: u32,
+    pub last: u32,
+}
+
+impl Range {
+    fn parse(text: &str) -> Option<Self> {
+        let (first, last) = match text.split_once('-') {
+            Some((first, last)) => (first.parse().ok()?, last.parse().ok()?),
+            None => {
+                let id = text.parse().ok()?;
+                (id, id)
+            }
+        };
+        (first <= last).then_some(Range { first, last })
+    }
+
+    fn size(self) -> u32 {
+        self.last - self.first
+    }
+
+    /// The lowest ID in both ranges
+    fn overlap(self, other: Range) -> Option<u32> {
+        let first = self.first.max(other.first);
+        (first <= self.last.min(other.last)).then_some(first)
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.first == self.last {
+            write!(f, "{}", self.first)
+        } else {
+            write!(f, "{}-{}", self.first, self.last)
+        }
+    }
+}
+
+/// One line of `raw.idmap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub kind: Kind,
+    pub host: Range,
+    pub container: Range,
+}
+
+impl Entry {
+    /// Read the entry on `line` (from 1, for errors)
+    pub fn parse(text: &str, line: usize) -> Result<Self, IdmapError> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let [kind, host, container] = words[..] else {
+            return Err(IdmapError::Syntax(line));
+        };
+        let kind = Kind::parse(kind).ok_or(IdmapError::Syntax(line))?;
+        let range = |value: &str| {
+            Range::parse(value).ok_or_else(|| IdmapError::BadRange {
+                line,
+                value: value.to_string(),
+            })
+        };
+        let (host, container) = (range(host)?, range(container)?);
+        if host.size() != container.size() {
+            return Err(IdmapError::SizeMismatch(line));
+        }
+        Ok(Entry {
+            kind,
+            host,
+            container,
+        })
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.kind.name(), self.host, self.container)
+    }
+}
+
+/// Check a whole map, given as its lines; blank lines are skipped as LXD
+/// skips them
+pub fn check(lines: &[String]) -> Result<Vec<Entry>, IdmapError> {
+    let mut entries: Vec<(usize, Entry)> = Vec::new();
+    for (i, text) in lines.iter().enumerate() {
+        if text.trim().is_empty() {
+            continue;
+        }
+        let entry = Entry::parse(text, i + 1)?;
+        for &(line, other) in &entries {
+            for kind in ["uid", "gid"] {
+                if !(entry.kind.covers(kind) && other.kind.covers(kind)) {
+                    continue;
+                }
+                let sides = [
+                    ("host", other.host.overlap(entry.host)),
+                    ("container", other.container.overlap(entry.container)),
+                ];
+                if let Some((side, Some(id))) = sides.into_iter().find(|(_, id)| id.is_some()) {
+                    return Err(IdmapError::Overlap {
+                        first: line,
+                        second: i + 1,
+                        side,
+                        kind,
+                        id,
+                    });
+                }
+            }
+        }
+        entries.push((i + 1, entry));
+    }
+    Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+}
+
+fn is_true(value: Option<&String>) -> bool {
+    value.is_some_and(|v| matches!(v.trim(), "true" | "1" | "yes" | "on"))
+}
+
+/// A disk device mounting a host directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disk {
+    pub name: String,
+    pub source: String,
+    pub shift: bool,
+}
+
+/// A change to a container's ID mapping
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdmapChange {
+    Lines(Vec<String>), // The whole `raw.idmap`; none removes it
+    Isolated(bool),
+    Shift { device: String, on: bool },
+}
+
+impl IdmapChange {
+    pub fn apply(&self, config: &mut InstanceConfig) {
+        match self {
+            IdmapChange::Lines(lines) if lines.is_empty() => {
+                config.config.remove(RAW_KEY);
+            }
+            IdmapChange::Lines(lines) => {
+                config.config.insert(RAW_KEY.to_string(), lines.join("\n"));
+            }
+            IdmapChange::Isolated(on) => {
+                config
+                    .config
+                    .insert(ISOLATED_KEY.to_string(), on.to_string());
+            }
+            IdmapChange::Shift { device, on } => {
+                if let Some(device) = config.devices.get_mut(device) {
+                    if *on {
+                        device.insert("shift".to_string(), "true".to_string());
+                    } else {
+                        device.remove("shift");
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The ID mapping view: the container's `raw.idmap` lines, then its
+/// disks mounting host directories
+#[derive(Debug)]
+pub struct IdmapView {
+    pub container: String, // qualified name
+    pub lines: Vec<String>,
+    pub disks: Vec<Disk>,
+    pub isolated: bool,
+    pub running: bool,
+    pub privileged: bool,     // `raw.idmap` means nothing then
+    pub restart_needed: bool, // The next start applies a different map
+    pub selected: usize,
+    pub status: Option<String>, // What the last change did, or why it wasn't made
+    pub error: Option<String>,  // Why the last load failed
+}
+
+impl IdmapView {
+    pub fn new(container: String) -> Self {
+        IdmapView {
+            container,
+            lines: Vec::new(),
+            disks: Vec::new(),
+            isolated: false,
+            running: false,
+            privileged: false,
+            restart_needed: false,
+            selected: 0,
+            status: None,
+            error: None,
+        }
+    }
+
+    /// Take in the container's own config and devices
+    pub fn load(&mut self, instance: &LxdContainer) {
+        let config = &instance.config;
+        self.lines = config
+            .get(RAW_KEY)
+            .map(|map| {
+                map.lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut disks: Vec<Disk> = instance
+            .devices
+            .iter()
+            .filter(|(_, d)| {
+                d.get("type").map(String::as_str) == Some("disk")
+                    && d.contains_key("source")
+                    && !d.contains_key("pool")
+            })
+            .map(|(name, d)| Disk {
+                name: name.clone(),
+                source: d["source"].clone(),
+                shift: is_true(d.get("shift")),
+            })
+            .collect();
+        disks.sort_by(|a, b| a.name.cmp(&b.name));
+        self.disks = disks;
+        self.isolated = is_true(config.get(ISOLATED_KEY));
+        self.privileged = is_true(config.get(PRIVILEGED_KEY));
+        self.running = instance.status == "Running";
+        self.restart_needed = self.running
+            && config.get(NEXT_KEY).is_some()
+            && config.get(NEXT_KEY) != config.get(CURRENT_KEY);
+        self.error = None;
+        self.selected = self.selected.min(self.rows().saturating_sub(1));
+    }
+
+    /// Lines, then disks
+    pub fn rows(&self) -> usize {
+        self.lines.len() + self.disks.len()
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        let rows = self.rows();
+        if rows == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % rows
+        } else {
+            (self.selected + rows - 1) % rows
+        };
+    }
+
+    /// Index of the selected `raw.idmap` line
+    pub fn selected_line(&self) -> Option<usize> {
+        (self.selected < self.lines.len()).then_some(self.selected)
+    }
+
+    pub fn selected_disk(&self) -> Option<&Disk> {
+        self.disks.get(self.selected.checked_sub(self.lines.len())?)
+    }
+}
+
+#[cfg(test)]
+mod tests;
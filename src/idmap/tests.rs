@@ -0,0 +1,98 @@
+//! Tests of ID mapping
+
+use super::{check, Entry, IdmapChange, IdmapError, Kind, Range, RAW_KEY};
+use crate::edit::InstanceConfig;
+use std::collections::BTreeMap;
+
+fn lines(map: &[&str]) -> Vec<String> {
+    map.iter().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn reads_entries_and_ranges() {
+    let entry = Entry::parse("uid 50-59 500-509", 1).unwrap();
+    assert_eq!(entry.kind, Kind::Uid);
+    assert_eq!(
+        entry.host,
+        Range {
+            first: 50,
+            last: 59
+        }
+    );
+    assert_eq!(entry.to_string(), "uid 50-59 500-509");
+    assert_eq!(
+        Entry::parse("  both   1000 1000 ", 1).unwrap().to_string(),
+        "both 1000 1000"
+    );
+
+    assert_eq!(
+        Entry::parse("user 1000 1000", 2),
+        Err(IdmapError::Syntax(2))
+    );
+    assert_eq!(Entry::parse("both 1000", 2), Err(IdmapError::Syntax(2)));
+    assert_eq!(
+        Entry::parse("gid 60-50 1000", 3),
+        Err(IdmapError::BadRange {
+            line: 3,
+            value: "60-50".to_string()
+        })
+    );
+    assert_eq!(
+        Entry::parse("gid 50-60 1000-1005", 4),
+        Err(IdmapError::SizeMismatch(4))
+    );
+}
+
+#[test]
+fn refuses_an_id_mapped_twice() {
+    assert_eq!(
+        check(&lines(&["uid 1000 1000", "", "gid 1000 1000"])).map(|e| e.len()),
+        Ok(2)
+    );
+
+    // both covers uid and gid
+    let error = check(&lines(&["both 1000-1010 1000-1010", "gid 1005 2000"])).unwrap_err();
+    assert_eq!(error.to_string(), "Lines 1 and 2 both map host gid 1005");
+    let error = check(&lines(&["uid 1000 0", "uid 2000 0"])).unwrap_err();
+    assert_eq!(
+        error,
+        IdmapError::Overlap {
+            first: 1,
+            second: 2,
+            side: "container",
+            kind: "uid",
+            id: 0
+        }
+    );
+}
+
+#[test]
+fn changes_the_map_isolation_and_shifting() {
+    let mut config = InstanceConfig {
+        architecture: "x86_64".to_string(),
+        config: BTreeMap::new(),
+        devices: BTreeMap::from([(
+            "data".to_string(),
+            BTreeMap::from([
+                ("type".to_string(), "disk".to_string()),
+                ("source".to_string(), "/srv/data".to_string()),
+            ]),
+        )]),
+        ephemeral: false,
+        profiles: Vec::new(),
+        stateful: false,
+        description: String::new(),
+    };
+
+    IdmapChange::Lines(lines(&["both 1000 1000", "uid 50 500"])).apply(&mut config);
+    assert_eq!(config.config[RAW_KEY], "both 1000 1000\nuid 50 500");
+    IdmapChange::Lines(Vec::new()).apply(&mut config);
+    assert!(!config.config.contains_key(RAW_KEY));
+
+    IdmapChange::Shift {
+        device: "data".to_string(),
+        on: true,
+    }
+    .apply(&mut config);
+    assert_eq!(config.devices["data"]["shift"], "true");
+}
@@ -0,0 +1,414 @@
+//! Image choices for the new container wizard
+//!
+//! Offers recently used images, the server's aliases and popular images,
+//! and completes what is typed against them and a public image server.
+
+use crate::config::ImagesConfig;
+use log::warn;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+/// Image "name" of an instance created empty, to install into from an ISO
+pub const EMPTY: &str = "none";
+
+/// Most suggestions listed for a query
+const MAX_SUGGESTIONS: usize = 50;
+
+/// Image servers LXD knows by name: remote, server URL and simplestreams
+/// index listing their images
+const IMAGE_SERVERS: &[(&str, &str, &str)] = &[
+    (
+        "images",
+        "https://images.linuxcontainers.org",
+        "streams/v1/images.json",
+    ),
+    (
+        "ubuntu",
+        "https://cloud-images.ubuntu.com/releases",
+        "streams/v1/com.ubuntu.cloud:released:download.json",
+    ),
+    (
+        "ubuntu-daily",
+        "https://cloud-images.ubuntu.com/daily",
+        "streams/v1/com.ubuntu.cloud:daily:download.json",
+    ),
+];
+
+#[derive(Debug, Error)]
+pub enum ImagesError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Invalid JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("HTTP error: {0}")]
+    HttpError(#[from] reqwest::Error),
+    #[error("Unknown image server '{0}'")]
+    UnknownServer(String),
+}
+
+/// Where a suggestion came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageSource {
+    Typed, // The query itself, used as is
+    Recent,
+    Local,
+    Popular,
+    Remote,
+    Empty, // No image at all
+}
+
+impl ImageSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImageSource::Typed => "as typed",
+            ImageSource::Recent => "recent",
+            ImageSource::Local => "local",
+            ImageSource::Popular => "",
+            ImageSource::Remote => "remote",
+            ImageSource::Empty => "empty",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageChoice {
+    pub alias: String,
+    pub description: String,
+    pub source: ImageSource,
+}
+
+impl ImageChoice {
+    pub fn new(alias: &str, description: &str, source: ImageSource) -> Self {
+        ImageChoice {
+            alias: alias.to_string(),
+            description: description.to_string(),
+            source,
+        }
+    }
+}
+
+/// The images created from most recently, newest first
+#[derive(Debug)]
+pub struct RecentImages {
+    path: PathBuf,
+    limit: usize,
+    pub aliases: Vec<String>,
+}
+
+impl RecentImages {
+    /// The configured list, or None when it is turned off
+    pub fn new(config: &ImagesConfig) -> Option<Self> {
+        (config.recent > 0).then(|| RecentImages {
+            path: config.recent_path(),
+            limit: config.recent,
+            aliases: Vec::new(),
+        })
+    }
+
+    pub fn load(&mut self) -> Result<(), ImagesError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut aliases: Vec<String> = serde_json::from_str(&text)?;
+        aliases.truncate(self.limit);
+        self.aliases = aliases;
+        Ok(())
+    }
+
+    /// Move `alias` to the front and save the list
+    pub fn record(&mut self, alias: &str) -> Result<(), ImagesError> {
+        self.aliases.retain(|a| a != alias);
+        self.aliases.insert(0, alias.to_string());
+        self.aliases.truncate(self.limit);
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.aliases)?)?;
+        Ok(())
+    }
+}
+
+type SearchResult = Result<Vec<ImageChoice>, ImagesError>;
+
+/// Everything the image step suggests from, apart from the popular list
+#[derive(Debug, Default)]
+pub struct ImageCatalog {
+    pub recent: Option<RecentImages>,
+    pub local: Vec<ImageChoice>,  // Aliases on the local server
+    pub remote: Vec<ImageChoice>, // Aliases on the search server
+    pub search_server: String,    // Remote searched as the user types; empty for none
+    pub search_error: Option<String>,
+    search: Option<oneshot::Receiver<SearchResult>>,
+    searched: bool,                    // Whether a search was started this session
+    creating: HashMap<String, String>, // Image of each create operation
+}
+
+impl ImageCatalog {
+    pub fn new(config: &ImagesConfig) -> Self {
+        let mut recent = RecentImages::new(config);
+        if let Some(recent) = &mut recent {
+            if let Err(e) = recent.load() {
+                warn!("Failed to load recent images: {}", e);
+            }
+        }
+        ImageCatalog {
+            recent,
+            search_server: config.search.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Choices matching `query`, best first. An empty query lists recent,
+    /// local and popular images; a query is also matched against the
+    /// search server's images and offered as is when nothing matches
+    /// exactly.
+    pub fn suggestions(&self, query: &str, popular: &[ImageChoice]) -> Vec<ImageChoice> {
+        let recent = self.recent.iter().flat_map(|recent| {
+            recent.aliases.iter().map(|alias| {
+                let known = popular.iter().chain(&self.local).chain(&self.remote);
+                let description = known
+                    .filter(|c| c.alias == *alias)
+                    .map(|c| c.description.as_str())
+                    .next()
+                    .unwrap_or_default();
+                ImageChoice::new(alias, description, ImageSource::Recent)
+            })
+        });
+        let remote = self.remote.iter().filter(|_| !query.is_empty()).cloned();
+        let empty = ImageChoice::new(EMPTY, "No image, install from an ISO", ImageSource::Empty);
+        let candidates = recent
+            .chain(self.local.iter().cloned())
+            .chain(popular.iter().cloned())
+            .chain(std::iter::once(empty))
+            .chain(remote);
+
+        let query = query.trim();
+        let mut choices = suggestions(query, candidates);
+        if !query.is_empty() && !choices.iter().any(|c| c.alias == query) {
+            choices.insert(0, ImageChoice::new(query, "", ImageSource::Typed));
+        }
+        choices
+    }
+
+    /// Fetch the search server's images in the background, once
+    pub fn start_search(&mut self) {
+        if self.searched || self.search_server.is_empty() {
+            return;
+        }
+        self.searched = true;
+
+        let (tx, rx) = oneshot::channel();
+        let server = self.search_server.clone();
+        tokio::spawn(async move {
+            let _ = tx.send(search(&server).await);
+        });
+        self.search = Some(rx);
+    }
+
+    pub fn searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Take in the search results once they arrive
+    pub fn poll_search(&mut self) {
+        let Some(rx) = &mut self.search else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(images)) => {
+                self.remote = images;
+                self.search = None;
+            }
+            Ok(Err(e)) => {
+                warn!("Image search on {} failed: {}", self.search_server, e);
+                self.search_error = Some(e.to_string());
+                self.search = None;
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+            Err(oneshot::error::TryRecvError::Closed) => self.search = None,
+        }
+    }
+
+    /// Remember the image of a create operation until it finishes
+    pub fn creating(&mut self, operation_id: &str, alias: &str) {
+        self.creating
+            .insert(operation_id.to_string(), alias.to_string());
+    }
+
+    /// Record the image of a create operation that succeeded
+    pub fn created(&mut self, operation_id: &str) {
+        let (Some(alias), Some(recent)) = (self.creating.remove(operation_id), &mut self.recent)
+        else {
+            return;
+        };
+        if let Err(e) = recent.record(&alias) {
+            warn!("Failed to save recent images: {}", e);
+        }
+    }
+
+    /// Forget the image of a create operation that failed
+    pub fn failed(&mut self, operation_id: &str) {
+        self.creating.remove(operation_id);
+    }
+}
+
+/// Candidates containing `query`, ignoring case: exact matches first, then
+/// prefix matches, then the rest, each in candidate order. Later duplicates
+/// of an alias are dropped.
+pub fn suggestions(query: &str, candidates: impl Iterator<Item = ImageChoice>) -> Vec<ImageChoice> {
+    let query = query.to_lowercase();
+    let mut seen = HashSet::new();
+    let mut ranked: Vec<(u8, ImageChoice)> = candidates
+        .filter(|c| seen.insert(c.alias.clone()))
+        .filter_map(|c| {
+            let alias = c.alias.to_lowercase();
+            let rank = if alias == query {
+                0
+            } else if alias.starts_with(&query) {
+                1
+            } else if alias.contains(&query) || c.description.to_lowercase().contains(&query) {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, c))
+        })
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, c)| c)
+        .collect()
+}
+
+/// The `source` of a create request. Aliases on a known image server, like
+/// `images:alpine/3.20`, are pulled from that server; others name an image
+/// on the server itself, apart from `none`, which creates it empty.
+pub fn image_source(image: &str) -> Value {
+    if image == EMPTY {
+        return json!({"type": "none"});
+    }
+    let server = image.split_once(':').and_then(|(remote, alias)| {
+        IMAGE_SERVERS
+            .iter()
+            .find(|(name, _, _)| *name == remote)
+            .map(|(_, url, _)| (*url, alias))
+    });
+    match server {
+        Some((url, alias)) => json!({
+            "type": "image",
+            "alias": alias,
+            "server": url,
+            "protocol": "simplestreams",
+            "mode": "pull"
+        }),
+        None => json!({
+            "type": "image",
+            "alias": image
+        }),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Index {
+    #[serde(default)]
+    products: HashMap<String, Product>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Product {
+    #[serde(default)]
+    aliases: String, // Comma separated
+    #[serde(default)]
+    arch: String,
+    #[serde(default)]
+    os: String,
+    #[serde(default)]
+    release_title: String,
+    #[serde(default)]
+    variant: String,
+}
+
+/// The aliases of a simplestreams index for `arch`, as `remote:alias`,
+/// sorted by alias
+pub fn parse_index(remote: &str, json: &str, arch: &str) -> Result<Vec<ImageChoice>, ImagesError> {
+    let index: Index = serde_json::from_str(json)?;
+    let mut choices: Vec<ImageChoice> = index
+        .products
+        .values()
+        .filter(|product| product.arch == arch)
+        .flat_map(|product| {
+            let mut description = format!("{} {}", product.os, product.release_title);
+            if !product.variant.is_empty() && product.variant != "default" {
+                description.push_str(&format!(" ({})", product.variant));
+            }
+            product
+                .aliases
+                .split(',')
+                .map(str::trim)
+                .filter(|alias| !alias.is_empty())
+                .map(move |alias| {
+                    ImageChoice::new(
+                        &format!("{}:{}", remote, alias),
+                        description.trim(),
+                        ImageSource::Remote,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    choices.sort_by(|a, b| a.alias.cmp(&b.alias));
+    choices.dedup_by(|a, b| a.alias == b.alias);
+    Ok(choices)
+}
+
+/// A character allowed in a typed image alias, like `images:alpine/3.20`
+pub fn is_alias_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | ':')
+}
+
+/// The architecture name image servers use for this host
+fn host_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "powerpc64" => "ppc64el",
+        arch => arch,
+    }
+}
+
+async fn search(remote: &str) -> SearchResult {
+    let (_, url, index) = IMAGE_SERVERS
+        .iter()
+        .find(|(name, _, _)| *name == remote)
+        .ok_or_else(|| ImagesError::UnknownServer(remote.to_string()))?;
+    let json = reqwest::get(format!("{}/{}", url, index))
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    parse_index(remote, &json, host_arch())
+}
+
+/// How `image_source` would be asked for an image copied from `server`:
+/// `images:alpine/3.20` for a known server, else the URL and the alias
+pub fn remote_alias(server: &str, alias: &str) -> String {
+    let server = server.trim_end_matches('/');
+    match IMAGE_SERVERS.iter().find(|(_, url, _)| *url == server) {
+        Some((name, _, _)) => format!("{}:{}", name, alias),
+        None => format!("{} {}", server, alias),
+    }
+}
+
+#[cfg(test)]
+mod tests;
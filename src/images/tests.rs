@@ -0,0 +1,165 @@
+//! Tests of image suggestions, recent images and the image server index
+
+use super::{image_source, parse_index, ImageCatalog, ImageChoice, ImageSource, RecentImages};
+use crate::config::ImagesConfig;
+use serde_json::json;
+use std::fs;
+use uuid::Uuid;
+
+fn popular() -> Vec<ImageChoice> {
+    vec![
+        ImageChoice::new("ubuntu:24.04", "Ubuntu 24.04 LTS", ImageSource::Popular),
+        ImageChoice::new("debian:12", "Debian 12 (Bookworm)", ImageSource::Popular),
+    ]
+}
+
+fn aliases(choices: &[ImageChoice]) -> Vec<&str> {
+    choices.iter().map(|c| c.alias.as_str()).collect()
+}
+
+fn config() -> ImagesConfig {
+    ImagesConfig {
+        recent: 3,
+        path: Some(std::env::temp_dir().join(format!("lxtui-recent-{}.json", Uuid::new_v4()))),
+        search: String::new(),
+        ..ImagesConfig::default()
+    }
+}
+
+#[test]
+fn recent_images_come_first_without_duplicates() {
+    let mut catalog = ImageCatalog::new(&config());
+    catalog.recent.as_mut().unwrap().aliases = vec!["debian:12".to_string()];
+    catalog.local = vec![ImageChoice::new("golden", "", ImageSource::Local)];
+
+    let choices = catalog.suggestions("", &popular());
+    assert_eq!(
+        aliases(&choices),
+        ["debian:12", "golden", "ubuntu:24.04", "none"]
+    );
+    assert_eq!(choices[0].source, ImageSource::Recent);
+    // Described from the list it also appears in
+    assert_eq!(choices[0].description, "Debian 12 (Bookworm)");
+}
+
+#[test]
+fn typing_ranks_prefix_matches_first_and_searches_the_server() {
+    let catalog = ImageCatalog {
+        remote: vec![
+            ImageChoice::new("images:debian/12", "Debian bookworm", ImageSource::Remote),
+            ImageChoice::new("images:alpine/3.20", "Alpine 3.20", ImageSource::Remote),
+        ],
+        ..Default::default()
+    };
+
+    let choices = catalog.suggestions("deb", &popular());
+    assert_eq!(aliases(&choices), ["deb", "debian:12", "images:debian/12"]);
+    assert_eq!(choices[0].source, ImageSource::Typed);
+
+    // An exact match isn't offered twice
+    let choices = catalog.suggestions("debian:12", &popular());
+    assert_eq!(aliases(&choices), ["debian:12"]);
+
+    // The server's images only show up once something is typed
+    assert!(catalog
+        .suggestions("", &popular())
+        .iter()
+        .all(|c| c.source != ImageSource::Remote));
+}
+
+#[test]
+fn records_recent_images_newest_first() {
+    let config = config();
+    let mut recent = RecentImages::new(&config).unwrap();
+    for alias in ["a", "b", "c", "a", "d"] {
+        recent.record(alias).unwrap();
+    }
+    assert_eq!(recent.aliases, ["d", "a", "c"]);
+
+    let mut reloaded = RecentImages::new(&config).unwrap();
+    reloaded.load().unwrap();
+    assert_eq!(reloaded.aliases, ["d", "a", "c"]);
+    fs::remove_file(config.recent_path()).unwrap();
+
+    // Turned off
+    let off = ImagesConfig {
+        recent: 0,
+        ..config
+    };
+    assert!(RecentImages::new(&off).is_none());
+}
+
+#[test]
+fn created_images_are_recorded_only_on_success() {
+    let config = config();
+    let mut catalog = ImageCatalog::new(&config);
+    catalog.creating("op1", "ubuntu:24.04");
+    catalog.creating("op2", "debian:12");
+    catalog.failed("op2");
+    catalog.created("op1");
+    catalog.created("op2");
+
+    assert_eq!(catalog.recent.unwrap().aliases, ["ubuntu:24.04"]);
+    fs::remove_file(config.recent_path()).unwrap();
+}
+
+#[test]
+fn server_aliases_are_pulled_from_the_server() {
+    assert_eq!(
+        image_source("images:alpine/3.20"),
+        json!({
+            "type": "image",
+            "alias": "alpine/3.20",
+            "server": "https://images.linuxcontainers.org",
+            "protocol": "simplestreams",
+            "mode": "pull"
+        })
+    );
+    assert_eq!(
+        image_source("golden"),
+        json!({"type": "image", "alias": "golden"})
+    );
+    assert_eq!(image_source("none"), json!({"type": "none"}));
+}
+
+#[test]
+fn parses_the_index_for_one_architecture() {
+    let index = json!({
+        "products": {
+            "debian:bookworm:amd64:default": {
+                "aliases": "debian/bookworm/default,debian/bookworm,debian/12",
+                "arch": "amd64",
+                "os": "Debian",
+                "release_title": "bookworm",
+                "variant": "default"
+            },
+            "debian:bookworm:amd64:cloud": {
+                "aliases": "debian/bookworm/cloud,debian/12/cloud",
+                "arch": "amd64",
+                "os": "Debian",
+                "release_title": "bookworm",
+                "variant": "cloud"
+            },
+            "debian:bookworm:arm64:default": {
+                "aliases": "debian/bookworm/default,debian/bookworm,debian/12",
+                "arch": "arm64",
+                "os": "Debian",
+                "release_title": "bookworm",
+                "variant": "default"
+            }
+        }
+    });
+
+    let choices = parse_index("images", &index.to_string(), "amd64").unwrap();
+    assert_eq!(
+        aliases(&choices),
+        [
+            "images:debian/12",
+            "images:debian/12/cloud",
+            "images:debian/bookworm",
+            "images:debian/bookworm/cloud",
+            "images:debian/bookworm/default",
+        ]
+    );
+    assert_eq!(choices[1].description, "Debian bookworm (cloud)");
+}
@@ -0,0 +1,817 @@
+//! Key mapping
+//!
+//! Turns key presses into `Action`s for the current input mode. Nothing
+//! here changes the app; `action::update` applies what is returned.
+
+use crate::action::Action;
+use crate::app::{App, CommandMenu, ConfirmAction, InputMode, StatusModalType, WizardState};
+use crate::config::CustomAction;
+use crate::field::FieldEdit;
+use crate::images;
+use crate::keymap;
+use crate::media;
+use crate::preflight::Check;
+use crate::quick::{self, QuickView};
+use crate::top::RankBy;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Lines scrolled by PageUp/PageDown on the help screen
+const HELP_PAGE: i32 = 10;
+
+/// The action for `key`, if it does anything in the current mode. `log_page`
+/// is the number of lines the log view shows.
+pub fn map_key(app: &App, key: KeyEvent, log_page: u16) -> Option<Action> {
+    // The overlays toggle in every mode without affecting it
+    match key.code {
+        KeyCode::F(12) => return Some(Action::ToggleDebug),
+        KeyCode::F(11) => return Some(Action::TogglePerf),
+        _ => {}
+    }
+
+    match &app.input_mode {
+        InputMode::Normal => normal_mode(key),
+        InputMode::CommandMenu(menu) => command_menu(key, menu, app),
+        InputMode::StatusModal(modal_type) => status_modal(key, modal_type),
+        InputMode::Confirmation {
+            action: ConfirmAction::DeleteContainer(name),
+            checks,
+            ..
+        } if checks.contains(&Check::Protected) => match key.code {
+            KeyCode::Char('p') | KeyCode::Char('P') => Some(Action::RemoveProtection(name.clone())),
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => Some(Action::CancelDialog),
+            _ => None,
+        },
+        InputMode::Confirmation {
+            action,
+            typed_name: Some(name),
+            ..
+        } => match key.code {
+            KeyCode::Enter if app.input_buffer.as_str() == name => {
+                Some(Action::Confirm(action.clone()))
+            }
+            KeyCode::Esc => Some(Action::CancelDialog),
+            _ => field_edit(key, |_| true).map(Action::EditInput),
+        },
+        InputMode::Confirmation { action, .. } => match key.code {
+            KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => {
+                Some(Action::Confirm(action.clone()))
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => Some(Action::CancelDialog),
+            _ => None,
+        },
+        InputMode::Input {
+            input_type,
+            callback_action,
+            ..
+        } => match key.code {
+            KeyCode::Enter if !app.input_buffer.is_empty() => {
+                Some(Action::SubmitInput(callback_action.clone()))
+            }
+            KeyCode::Esc => Some(Action::CancelInput),
+            KeyCode::Up => Some(Action::RecallInput { forward: false }),
+            KeyCode::Down => Some(Action::RecallInput { forward: true }),
+            _ => field_edit(key, |c| input_type.accepts(c)).map(Action::EditInput),
+        },
+        InputMode::Wizard(state) => wizard(key, state, app.input_buffer.is_empty()),
+        InputMode::Backups {
+            container,
+            confirm_delete,
+        } => backups(key, container, *confirm_delete, app.backups.is_empty()),
+        InputMode::Help { query, searching } => help(key, *searching, query.is_empty()),
+        InputMode::SpaceWarning { action, .. } => match key.code {
+            KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => {
+                Some(Action::Run(action.clone()))
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => {
+                Some(Action::CancelPreview(action.clone()))
+            }
+            _ => None,
+        },
+        InputMode::Preview(action) => match key.code {
+            KeyCode::Enter | KeyCode::Char('s') => Some(Action::SendPreview(action.clone())),
+            KeyCode::Esc | KeyCode::Char('n') => Some(Action::CancelPreview(action.clone())),
+            _ => None,
+        },
+        InputMode::CloneOptions(_) => match key.code {
+            KeyCode::Char('j') | KeyCode::Down | KeyCode::Tab => Some(Action::CloneFieldNext),
+            KeyCode::Char('k') | KeyCode::Up | KeyCode::BackTab => Some(Action::CloneFieldPrevious),
+            KeyCode::Char(' ') => Some(Action::CloneToggle),
+            KeyCode::Char('l') | KeyCode::Right => Some(Action::CloneCycle(true)),
+            KeyCode::Char('h') | KeyCode::Left => Some(Action::CloneCycle(false)),
+            KeyCode::Enter => Some(Action::SubmitClone),
+            KeyCode::Esc => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Log => log_view(key, log_page),
+        InputMode::Console => match &app.console {
+            Some(console) => console_view(key, console.searching, log_page),
+            None => Some(Action::CloseView),
+        },
+        InputMode::Tour => match key.code {
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                Some(Action::TourNext)
+            }
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Backspace => Some(Action::TourPrevious),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::EndTour),
+            _ => None,
+        },
+        InputMode::Operations => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::OperationNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::OperationPrevious),
+            KeyCode::Enter => Some(Action::ShowOperationDetails),
+            KeyCode::Char('r') => Some(Action::RetryOperation),
+            KeyCode::Char('f') => Some(Action::FilterOperations),
+            KeyCode::Char('g') => Some(Action::GroupOperations),
+            KeyCode::Char('c') => Some(Action::ClearFinishedOperations),
+            KeyCode::Char('o') | KeyCode::Char('O') => Some(Action::ToggleSidebar),
+            KeyCode::Esc | KeyCode::Tab | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::OperationDetails { .. } => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::ScrollOperationDetails(1)),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::ScrollOperationDetails(-1)),
+            KeyCode::PageDown => Some(Action::ScrollOperationDetails(10)),
+            KeyCode::PageUp => Some(Action::ScrollOperationDetails(-10)),
+            KeyCode::Char('r') => Some(Action::RetryOperation),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => Some(Action::FocusOperations),
+            _ => None,
+        },
+        InputMode::Watch => match key.code {
+            KeyCode::Char('c') => Some(Action::ClearWatchEvents),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Top => match key.code {
+            KeyCode::Char('c') => Some(Action::RankTopBy(RankBy::Cpu)),
+            KeyCode::Char('m') => Some(Action::RankTopBy(RankBy::Memory)),
+            KeyCode::Char('n') => Some(Action::RankTopBy(RankBy::Network)),
+            KeyCode::Char('+') | KeyCode::Char('=') => Some(Action::ResizeTopWindow(1)),
+            KeyCode::Char('-') => Some(Action::ResizeTopWindow(-1)),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Dashboard => match key.code {
+            KeyCode::Enter | KeyCode::Esc => Some(Action::CloseDashboard),
+            KeyCode::Char('q') | KeyCode::Char('Q') => Some(Action::Quit),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Action::Quit)
+            }
+            _ => None,
+        },
+        InputMode::BootOrder => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::BootOrderNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::BootOrderPrevious),
+            KeyCode::Char('a') => Some(Action::CycleAutostart),
+            KeyCode::Char('p') => Some(Action::EditBootPriority),
+            KeyCode::Char('d') => Some(Action::EditBootDelay),
+            KeyCode::Char('s') => Some(Action::CycleBootSort),
+            KeyCode::Char('r') => Some(Action::ReloadBootOrder),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Audit { confirm_rebuild } => audit(key, *confirm_rebuild),
+        InputMode::Zones { confirm_delete } => zones(key, *confirm_delete),
+        InputMode::ImageStore => image_store(key),
+        InputMode::Pools { confirm_scrub } => pools(key, *confirm_scrub),
+        InputMode::Capacity => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::CapacityNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::CapacityPrevious),
+            KeyCode::Char('r') => Some(Action::ReloadCapacity),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Templates => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::TemplateNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::TemplatePrevious),
+            KeyCode::Enter | KeyCode::Char('e') => Some(Action::EditTemplate),
+            KeyCode::Char('m') => Some(Action::EditMetadata),
+            KeyCode::Char('n') => Some(Action::NewTemplate),
+            KeyCode::Char('r') => Some(Action::ReloadTemplates),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::VmSettings => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::VmSettingNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::VmSettingPrevious),
+            KeyCode::Enter | KeyCode::Char(' ') => Some(Action::ChangeVmSetting),
+            KeyCode::Char('r') => Some(Action::ReloadVmSettings),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Idmap => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::IdmapNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::IdmapPrevious),
+            KeyCode::Char('a') => Some(Action::AddIdmapLine),
+            KeyCode::Enter | KeyCode::Char('e') => Some(Action::EditIdmapLine),
+            KeyCode::Char('d') => Some(Action::DeleteIdmapLine),
+            KeyCode::Char('i') => Some(Action::ToggleIdmapIsolated),
+            KeyCode::Char('s') => Some(Action::ToggleDiskShift),
+            KeyCode::Char('r') => Some(Action::ReloadIdmap),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Profiles => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::ProfileNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::ProfilePrevious),
+            KeyCode::Char('a') | KeyCode::Enter => Some(Action::AddProfile),
+            KeyCode::Char('d') => Some(Action::RemoveProfile),
+            KeyCode::Char('r') => Some(Action::ReloadProfiles),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Quick { confirm } => quick(key, *confirm, app.quick.as_ref()?),
+        InputMode::KeySearch => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::KeySearchNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::KeySearchPrevious),
+            KeyCode::Enter => Some(Action::ShowKeySearchHit),
+            KeyCode::Char('/') => Some(Action::SearchConfigKey),
+            KeyCode::Char('r') => Some(Action::ReloadKeySearch),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Drift => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::DriftNext),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::DriftPrevious),
+            KeyCode::Char('o') => Some(Action::CheckDrift),
+            KeyCode::Char('r') => Some(Action::ReloadDrift),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Recreate => match key.code {
+            KeyCode::Char('n') => Some(Action::RenameRecreate),
+            KeyCode::Char('i') => Some(Action::ChangeRecreateImage),
+            KeyCode::Enter => Some(Action::ConfirmRecreate),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+        InputMode::Compare => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::ScrollCompare(1)),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::ScrollCompare(-1)),
+            KeyCode::PageDown => Some(Action::ScrollCompare(10)),
+            KeyCode::PageUp => Some(Action::ScrollCompare(-10)),
+            KeyCode::Char('d') => Some(Action::ToggleCompareDifferences),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::CloseView),
+            _ => None,
+        },
+    }
+}
+
+fn normal_mode(key: KeyEvent) -> Option<Action> {
+    let action = match key.code {
+        // Show container operations menu when Enter is pressed on a container
+        KeyCode::Enter => Action::OpenMenu(CommandMenu::Container),
+        KeyCode::Char(' ') => Action::OpenMenu(CommandMenu::System),
+        KeyCode::Char('?') | KeyCode::Char('h') => Action::ShowHelp,
+        KeyCode::Char('L') => Action::OpenLog,
+        KeyCode::Char('w') => Action::WatchSelected,
+        KeyCode::Char('f') => Action::FollowConsole,
+        KeyCode::Char('F') => Action::ShowCrashLog,
+        KeyCode::Char('t') => Action::OpenTop,
+        KeyCode::Char('D') => Action::OpenDashboard,
+        KeyCode::Char('B') => Action::OpenBootOrder,
+        KeyCode::Char('I') => Action::OpenAudit,
+        KeyCode::Char('Z') => Action::OpenZones,
+        KeyCode::Char('U') => Action::OpenImageStore,
+        KeyCode::Char('H') => Action::OpenCapacity,
+        KeyCode::Char('T') => Action::OpenPools,
+        KeyCode::Char('m') => Action::ToggleCompareMark,
+        KeyCode::Char('C') => Action::CompareMarked,
+        KeyCode::Char('x') => Action::TogglePick,
+        KeyCode::Char('X') => Action::ClearPicks,
+        KeyCode::Char('P') => Action::OpenProfiles,
+        KeyCode::Char('E') => Action::OpenQuick,
+        KeyCode::Char('K') => Action::SearchConfigKey,
+        KeyCode::Char(':') => Action::OpenCommandLine,
+        KeyCode::Char('!') => Action::HostShell,
+        KeyCode::Char('q') | KeyCode::Char('Q') => Action::Quit,
+        KeyCode::Char('j') | KeyCode::Down => Action::SelectNext,
+        KeyCode::Char('k') | KeyCode::Up => Action::SelectPrevious,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+        KeyCode::Char('O') | KeyCode::Char('o') => Action::ToggleSidebar,
+        KeyCode::Char('v') => Action::ToggleEvents,
+        KeyCode::Char('g') => Action::ToggleGroupByTag,
+        KeyCode::Char('A') => Action::ToggleHighContrast,
+        KeyCode::Char(c @ '0'..='9') => Action::SwitchWorkspace(c as usize - '0' as usize),
+        KeyCode::Tab => Action::FocusOperations,
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Redo,
+        KeyCode::Char('r') | KeyCode::Char('R') => Action::Refresh,
+        KeyCode::Char('u') => Action::Undo,
+        // Quick container actions (direct shortcuts)
+        KeyCode::Char('s') => Action::StartSelected,
+        KeyCode::Char('S') => Action::StopSelected,
+        KeyCode::Char('d') => Action::QuickDelete,
+        KeyCode::Char('n') => Action::NewContainer,
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn command_menu(key: KeyEvent, menu: &CommandMenu, app: &App) -> Option<Action> {
+    let selected = app.menu_selected;
+    match (menu, key.code) {
+        (CommandMenu::RemoteAuth, KeyCode::Esc) => Some(Action::CancelAddRemote),
+        (_, KeyCode::Esc) => Some(Action::CloseView),
+        (CommandMenu::Container, _) => container_menu(key, selected, &app.custom_actions),
+        (CommandMenu::System, _) => system_menu(key, selected),
+        (CommandMenu::RemoteAuth, _) => remote_auth_menu(key, selected),
+        // Main menu no longer used, close if somehow reached
+        (CommandMenu::Main | CommandMenu::Closed, _) => Some(Action::CloseView),
+    }
+}
+
+/// Custom actions from the config file follow the built-in items
+fn container_menu(key: KeyEvent, selected: usize, custom: &[CustomAction]) -> Option<Action> {
+    const BUILT_IN: usize = keymap::CONTAINER_MENU.len() - 1; // Number of menu items (excluding Esc)
+    let menu_items = BUILT_IN + custom.len();
+
+    let item = match key.code {
+        KeyCode::Down | KeyCode::Char('j') => return Some(Action::MenuNext(menu_items)),
+        KeyCode::Up | KeyCode::Char('k') => return Some(Action::MenuPrevious(menu_items)),
+        KeyCode::Enter => selected,
+        // Hotkeys (still work as shortcuts)
+        KeyCode::Char('s') | KeyCode::Char('1') => 1,
+        KeyCode::Char('S') | KeyCode::Char('2') => 2,
+        KeyCode::Char('r') | KeyCode::Char('3') => 3,
+        KeyCode::Char('d') | KeyCode::Char('4') => 4,
+        KeyCode::Char('c') | KeyCode::Char('5') => 5,
+        KeyCode::Char('u') | KeyCode::Char('6') => 6,
+        KeyCode::Char('b') | KeyCode::Char('7') => 7,
+        KeyCode::Char('e') | KeyCode::Char('E') => 8,
+        KeyCode::Char('h') => 9,
+        KeyCode::Char('w') => 10,
+        KeyCode::Char('f') => 11,
+        KeyCode::Char('t') => 12,
+        KeyCode::Char('i') => 13,
+        KeyCode::Char('m') => 14,
+        KeyCode::Char('o') => 15,
+        KeyCode::Char('a') => 16,
+        KeyCode::Char('v') => 17,
+        KeyCode::Char('D') => 18,
+        KeyCode::Char('V') => 19,
+        KeyCode::Char('g') => 20,
+        KeyCode::Char('R') => 21,
+        KeyCode::Char('p') => 22,
+        KeyCode::Char('P') => 23,
+        KeyCode::Char('I') => 24,
+        KeyCode::Char('n') => 25,
+        KeyCode::Char(c) => BUILT_IN + custom.iter().position(|a| a.key == Some(c))?,
+        _ => return None,
+    };
+    container_menu_action(item, custom.len())
+}
+
+/// Action of the container menu entry at `item`, counting from 0; the
+/// `custom` configured actions follow the built-in entries
+pub fn container_menu_action(item: usize, custom: usize) -> Option<Action> {
+    const BUILT_IN: usize = keymap::CONTAINER_MENU.len() - 1;
+    let action = match item {
+        0 => Action::ToggleSelected,
+        1 => Action::StartSelected,
+        2 => Action::StopSelected,
+        3 => Action::RestartSelected,
+        4 => Action::DeleteSelected,
+        5 => Action::CloneSelected,
+        6 => Action::RefreshCopySelected,
+        7 => Action::OpenBackups,
+        8 => Action::ExecSelected,
+        9 => Action::SshSelected,
+        10 => Action::WatchSelected,
+        11 => Action::FollowConsole,
+        12 => Action::EditTags,
+        13 => Action::EditConfig,
+        14 => Action::OpenTemplates,
+        15 => Action::CopyEditorRemote,
+        16 => Action::AttachMedia,
+        17 => Action::ToggleMediaBoot,
+        18 => Action::DetachMedia,
+        19 => Action::OpenVmSettings,
+        20 => Action::OpenIdmap,
+        21 => Action::OpenRecreate,
+        22 => Action::SkipPowerSchedule,
+        23 => Action::HoldPowerSchedule,
+        24 => Action::PublishSelected,
+        25 => Action::EditNotes,
+        item if item < BUILT_IN + custom => Action::RunCustom(item - BUILT_IN),
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn system_menu(key: KeyEvent, selected: usize) -> Option<Action> {
+    const MENU_ITEMS: usize = keymap::SYSTEM_MENU.len() - 1; // Number of menu items (excluding Esc)
+
+    let item = match key.code {
+        KeyCode::Down | KeyCode::Char('j') => return Some(Action::MenuNext(MENU_ITEMS)),
+        KeyCode::Up | KeyCode::Char('k') => return Some(Action::MenuPrevious(MENU_ITEMS)),
+        KeyCode::Enter => selected,
+        // Direct hotkeys still work
+        KeyCode::Char('r') | KeyCode::Char('1') => 0,
+        KeyCode::Char('l') | KeyCode::Char('2') => 1,
+        KeyCode::Char('n') | KeyCode::Char('3') => 2,
+        KeyCode::Char('o') | KeyCode::Char('4') => 3,
+        KeyCode::Char('a') | KeyCode::Char('5') => 4,
+        KeyCode::Char('m') | KeyCode::Char('6') => 5,
+        KeyCode::Char('p') | KeyCode::Char('7') => 6,
+        KeyCode::Char('h') | KeyCode::Char('?') | KeyCode::Char('8') => 7,
+        KeyCode::Char('q') | KeyCode::Char('9') => 8,
+        KeyCode::Char('s') | KeyCode::Char('0') => 9,
+        KeyCode::Char('i') => 10,
+        KeyCode::Char('d') => 11,
+        KeyCode::Char('u') => 12,
+        _ => return None,
+    };
+    system_menu_action(item)
+}
+
+/// Action of the system menu entry at `item`, counting from 0
+pub fn system_menu_action(item: usize) -> Option<Action> {
+    let action = match item {
+        0 => Action::Refresh,
+        1 => Action::ReloadLxd,
+        2 => Action::NewContainer,
+        3 => Action::ToggleSidebar,
+        4 => Action::AddRemote,
+        5 => Action::ToggleAllRemotes,
+        6 => Action::TogglePreviewRequests,
+        7 => Action::ShowHelp,
+        8 => Action::Quit,
+        9 => Action::ControlService,
+        10 => Action::ExportInventory,
+        11 => Action::CheckDrift,
+        12 => Action::ExportUsage,
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn remote_auth_menu(key: KeyEvent, selected: usize) -> Option<Action> {
+    const MENU_ITEMS: usize = keymap::REMOTE_AUTH_MENU.len() - 1;
+
+    let item = match key.code {
+        KeyCode::Down | KeyCode::Char('j') => return Some(Action::MenuNext(MENU_ITEMS)),
+        KeyCode::Up | KeyCode::Char('k') => return Some(Action::MenuPrevious(MENU_ITEMS)),
+        KeyCode::Enter => selected,
+        KeyCode::Char('t') | KeyCode::Char('1') => 0,
+        KeyCode::Char('o') | KeyCode::Char('2') => 1,
+        KeyCode::Char('c') | KeyCode::Char('3') => 2,
+        _ => return None,
+    };
+
+    match item {
+        0 => Some(Action::AuthWithToken),
+        1 => Some(Action::AuthWithOidc),
+        2 => Some(Action::AuthWithCertificate),
+        _ => None,
+    }
+}
+
+fn status_modal(key: KeyEvent, modal_type: &StatusModalType) -> Option<Action> {
+    match modal_type {
+        StatusModalType::Progress {
+            operation_id,
+            confirm_cancel: true,
+        } => Some(match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Action::CancelProgress(operation_id.clone()),
+            _ => Action::KeepProgress,
+        }),
+        StatusModalType::Progress { .. } => match key.code {
+            KeyCode::Esc | KeyCode::Char('b') => Some(Action::RunInBackground),
+            KeyCode::Char('c') => Some(Action::AskCancelProgress),
+            _ => None,
+        },
+        StatusModalType::Error { report, .. } => match key.code {
+            KeyCode::Char('c') => Some(Action::CopyErrorReport(report.clone())),
+            KeyCode::Char('w') => Some(Action::SaveErrorReport(report.clone())),
+            _ => Some(Action::CloseView),
+        },
+        // Close on any key for Info and Success modals
+        _ => Some(Action::CloseView),
+    }
+}
+
+fn wizard(key: KeyEvent, state: &WizardState, name_empty: bool) -> Option<Action> {
+    let action = match (state, key.code) {
+        (_, KeyCode::Esc) => Action::CancelInput,
+
+        (WizardState::Name, KeyCode::Tab) if !name_empty => Action::WizardNameDone,
+        (WizardState::Name, KeyCode::Enter) if !name_empty => Action::WizardUseDefaults,
+        (WizardState::Name, _) => {
+            return field_edit(key, is_wizard_name_char).map(Action::EditInput)
+        }
+
+        (WizardState::SelectImage, KeyCode::Up) => Action::WizardPreviousImage,
+        (WizardState::SelectImage, KeyCode::Down) => Action::WizardNextImage,
+        (WizardState::SelectImage, KeyCode::Tab | KeyCode::Enter) => Action::WizardImageDone,
+        (WizardState::SelectImage, KeyCode::BackTab) => Action::WizardBackToName,
+        (WizardState::SelectImage, KeyCode::Backspace) => Action::WizardImageBackspace,
+        (WizardState::SelectImage, KeyCode::Char(c)) if images::is_alias_char(c) => {
+            Action::WizardImageChar(c)
+        }
+
+        (WizardState::SelectType, KeyCode::Char('c') | KeyCode::Char('C')) => {
+            Action::WizardSetVm(false)
+        }
+        (WizardState::SelectType, KeyCode::Char('v') | KeyCode::Char('V')) => {
+            Action::WizardSetVm(true)
+        }
+        (WizardState::SelectType, KeyCode::Tab) => Action::WizardTypeDone,
+        (WizardState::SelectType, KeyCode::BackTab) => Action::WizardGoTo(WizardState::SelectImage),
+
+        (WizardState::Media, KeyCode::Tab | KeyCode::Enter) => Action::WizardMediaDone,
+        (WizardState::Media, KeyCode::BackTab) => Action::WizardBackToType,
+        (WizardState::Media, _) => {
+            return field_edit(key, media::is_media_char).map(Action::EditInput)
+        }
+
+        (WizardState::Confirm, KeyCode::Enter) => Action::CreateContainer,
+        (WizardState::Confirm, KeyCode::BackTab) => Action::WizardBackFromConfirm,
+
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// The action for text pasted into the terminal: what the field being typed
+/// into accepts of it. Fields hold a single line, so line breaks and tabs
+/// in the text become spaces, for the fields that take them.
+pub fn map_paste(app: &App, text: &str) -> Option<Action> {
+    let line = text
+        .trim()
+        .replace("\r\n", " ")
+        .replace(['\n', '\r', '\t'], " ");
+    let field = |accepts: &dyn Fn(char) -> bool| {
+        let text: String = line.chars().filter(|&c| accepts(c)).collect();
+        (!text.is_empty()).then_some(text)
+    };
+    let paste = |text| Action::EditInput(FieldEdit::Paste(text));
+
+    match &app.input_mode {
+        InputMode::Confirmation {
+            typed_name: Some(_),
+            ..
+        } => field(&|c| !c.is_control()).map(paste),
+        InputMode::Input { input_type, .. } => field(&|c| input_type.accepts(c)).map(paste),
+        InputMode::Wizard(WizardState::Name) => field(&is_wizard_name_char).map(paste),
+        InputMode::Wizard(WizardState::Media) => field(&media::is_media_char).map(paste),
+        InputMode::Wizard(WizardState::SelectImage) => {
+            field(&images::is_alias_char).map(Action::WizardImagePaste)
+        }
+        InputMode::Help {
+            searching: true, ..
+        } => field(&|c| !c.is_control()).map(Action::HelpSearchPaste),
+        InputMode::Console if app.console.as_ref().is_some_and(|c| c.searching) => {
+            field(&|c| !c.is_control()).map(Action::ConsoleSearchPaste)
+        }
+        _ => None,
+    }
+}
+
+fn is_wizard_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-'
+}
+
+/// The edit a key makes to a text field, typing only the characters
+/// `accepts` allows
+fn field_edit(key: KeyEvent, accepts: impl Fn(char) -> bool) -> Option<FieldEdit> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let word = ctrl || key.modifiers.contains(KeyModifiers::ALT);
+    let edit = match key.code {
+        KeyCode::Backspace if word => FieldEdit::DeleteWord,
+        KeyCode::Backspace => FieldEdit::Backspace,
+        KeyCode::Delete => FieldEdit::Delete,
+        KeyCode::Left if word => FieldEdit::WordLeft,
+        KeyCode::Left => FieldEdit::Left,
+        KeyCode::Right if word => FieldEdit::WordRight,
+        KeyCode::Right => FieldEdit::Right,
+        KeyCode::Home => FieldEdit::Home,
+        KeyCode::End => FieldEdit::End,
+        KeyCode::Char('a') if ctrl => FieldEdit::Home,
+        KeyCode::Char('e') if ctrl => FieldEdit::End,
+        KeyCode::Char('w') if ctrl => FieldEdit::DeleteWord,
+        KeyCode::Char('u') if ctrl => FieldEdit::DeleteToStart,
+        KeyCode::Char('k') if ctrl => FieldEdit::DeleteToEnd,
+        KeyCode::Char(c) if key.modifiers.difference(KeyModifiers::SHIFT).is_empty() => {
+            if !accepts(c) {
+                return None;
+            }
+            FieldEdit::Insert(c)
+        }
+        _ => return None,
+    };
+    Some(edit)
+}
+
+fn audit(key: KeyEvent, confirm_rebuild: bool) -> Option<Action> {
+    if confirm_rebuild {
+        // Any key other than y keeps the instance as it is
+        return Some(match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Action::RebuildSelected,
+            _ => Action::CancelRebuild,
+        });
+    }
+
+    let action = match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Action::AuditNext,
+        KeyCode::Char('k') | KeyCode::Up => Action::AuditPrevious,
+        KeyCode::Char('b') => Action::AskRebuild,
+        KeyCode::Char('r') => Action::ReloadAudit,
+        KeyCode::Esc | KeyCode::Char('q') => Action::CloseView,
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// The library picks an action by its key; once one runs, the keys move
+/// through the instances and their output
+fn quick(key: KeyEvent, confirm: Option<usize>, view: &QuickView) -> Option<Action> {
+    if let Some(index) = confirm {
+        // Any key other than y leaves the instances alone
+        return Some(match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Action::RunQuick(index),
+            _ => Action::CancelQuick,
+        });
+    }
+
+    let action = match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Action::QuickNext,
+        KeyCode::Char('k') | KeyCode::Up => Action::QuickPrevious,
+        KeyCode::Esc if view.running() => Action::StopQuick,
+        KeyCode::Esc | KeyCode::Char('q') => Action::CloseView,
+        KeyCode::PageDown if view.run.is_some() => Action::ScrollQuick(10),
+        KeyCode::PageUp if view.run.is_some() => Action::ScrollQuick(-10),
+        KeyCode::Enter if view.run.is_some() => Action::ToggleQuickOutput,
+        KeyCode::Char('r') if view.run.is_some() => Action::RerunQuick,
+        _ if view.run.is_some() => return None,
+        KeyCode::Enter => Action::AskQuick(view.selected),
+        KeyCode::Char(quick::COMMAND_KEY) => Action::AskQuickCommand,
+        KeyCode::Char(c) => Action::AskQuick(view.action_for(c)?),
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn zones(key: KeyEvent, confirm_delete: bool) -> Option<Action> {
+    if confirm_delete {
+        return Some(match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Action::DeleteZoneEntry,
+            _ => Action::CancelZoneDelete,
+        });
+    }
+
+    let action = match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Action::ZoneNext,
+        KeyCode::Char('k') | KeyCode::Up => Action::ZonePrevious,
+        KeyCode::Char('a') => Action::NewZone,
+        KeyCode::Char('n') => Action::NewZoneRecord,
+        KeyCode::Enter | KeyCode::Char('e') => Action::EditZoneRecord,
+        KeyCode::Char('d') => Action::AskDeleteZoneEntry,
+        KeyCode::Char('r') => Action::ReloadZones,
+        KeyCode::Esc | KeyCode::Char('q') => Action::CloseView,
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn image_store(key: KeyEvent) -> Option<Action> {
+    let action = match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Action::ImageNext,
+        KeyCode::Char('k') | KeyCode::Up => Action::ImagePrevious,
+        KeyCode::Char('a') => Action::ToggleImageAutoUpdate,
+        KeyCode::Char('u') => Action::RefreshImage,
+        KeyCode::Char('r') => Action::ReloadImageStore,
+        KeyCode::Esc | KeyCode::Char('q') => Action::CloseView,
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn pools(key: KeyEvent, confirm_scrub: bool) -> Option<Action> {
+    if confirm_scrub {
+        return Some(match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Action::ScrubPool,
+            _ => Action::CancelScrubPool,
+        });
+    }
+
+    let action = match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Action::PoolNext,
+        KeyCode::Char('k') | KeyCode::Up => Action::PoolPrevious,
+        KeyCode::Char('s') => Action::AskScrubPool,
+        KeyCode::Char('r') => Action::ReloadPools,
+        KeyCode::Esc | KeyCode::Char('q') => Action::CloseView,
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn backups(
+    key: KeyEvent,
+    container: &str,
+    confirm_delete: bool,
+    no_backups: bool,
+) -> Option<Action> {
+    if confirm_delete {
+        // Any key other than y cancels the pending delete
+        return Some(match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Action::DeleteBackup(container.to_string()),
+            _ => Action::CancelDeleteBackup,
+        });
+    }
+
+    let action = match key.code {
+        KeyCode::Down | KeyCode::Char('j') => Action::BackupNext,
+        KeyCode::Up | KeyCode::Char('k') => Action::BackupPrevious,
+        KeyCode::Char('n') => Action::CreateBackup(container.to_string()),
+        KeyCode::Char('d') if !no_backups => Action::AskDeleteBackup(container.to_string()),
+        KeyCode::Char('w') => Action::DownloadBackup(container.to_string()),
+        KeyCode::Char('r') => Action::ReloadBackups(container.to_string()),
+        KeyCode::Esc | KeyCode::Char('q') => Action::CloseView,
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn help(key: KeyEvent, searching: bool, query_empty: bool) -> Option<Action> {
+    if searching {
+        return match key.code {
+            KeyCode::Enter => Some(Action::EndHelpSearch),
+            KeyCode::Esc => Some(Action::CancelHelpSearch),
+            KeyCode::Backspace => Some(Action::HelpSearchBackspace),
+            KeyCode::Char(c) => Some(Action::HelpSearchChar(c)),
+            _ => None,
+        };
+    }
+
+    let action = match key.code {
+        KeyCode::Char('/') => Action::StartHelpSearch,
+        KeyCode::Char('t') => Action::StartTour,
+        KeyCode::Esc if !query_empty => Action::ClearHelpQuery,
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => Action::CloseView,
+        KeyCode::Down | KeyCode::Char('j') => Action::ScrollHelp(1),
+        KeyCode::Up | KeyCode::Char('k') => Action::ScrollHelp(-1),
+        KeyCode::PageDown | KeyCode::Char(' ') => Action::ScrollHelp(HELP_PAGE),
+        KeyCode::PageUp => Action::ScrollHelp(-HELP_PAGE),
+        KeyCode::Char('g') | KeyCode::Home => Action::HelpTop,
+        KeyCode::Char('G') | KeyCode::End => Action::HelpBottom,
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn log_view(key: KeyEvent, page: u16) -> Option<Action> {
+    let step = page.max(1) as i32;
+    let scroll = |delta| Action::ScrollLog { delta, page };
+
+    let action = match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => Action::CloseView,
+        KeyCode::Down | KeyCode::Char('j') => scroll(1),
+        KeyCode::Up | KeyCode::Char('k') => scroll(-1),
+        KeyCode::PageDown | KeyCode::Char(' ') => scroll(step),
+        KeyCode::PageUp => scroll(-step),
+        KeyCode::Char('g') | KeyCode::Home => Action::LogTop,
+        KeyCode::Char('G') | KeyCode::End => Action::LogBottom,
+        KeyCode::Char('r') => Action::OpenLog,
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn console_view(key: KeyEvent, searching: bool, page: u16) -> Option<Action> {
+    if searching {
+        return match key.code {
+            KeyCode::Enter => Some(Action::EndConsoleSearch { page }),
+            KeyCode::Esc => Some(Action::CancelConsoleSearch),
+            KeyCode::Backspace => Some(Action::ConsoleSearchBackspace),
+            KeyCode::Char(c) => Some(Action::ConsoleSearchChar(c)),
+            _ => None,
+        };
+    }
+
+    let step = page.max(1) as i32;
+    let scroll = |delta| Action::ScrollConsole { delta, page };
+
+    let action = match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => Action::CloseView,
+        KeyCode::Down | KeyCode::Char('j') => scroll(1),
+        KeyCode::Up | KeyCode::Char('k') => scroll(-1),
+        KeyCode::PageDown => scroll(step),
+        KeyCode::PageUp => scroll(-step),
+        KeyCode::Char('g') | KeyCode::Home => Action::ConsoleTop,
+        KeyCode::Char('G') | KeyCode::End => Action::ConsoleBottom,
+        KeyCode::Char(' ') | KeyCode::Char('p') => Action::ToggleConsolePause,
+        KeyCode::Char('/') => Action::StartConsoleSearch,
+        KeyCode::Char('n') => Action::ConsoleFind {
+            forward: true,
+            page,
+        },
+        KeyCode::Char('N') => Action::ConsoleFind {
+            forward: false,
+            page,
+        },
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[cfg(test)]
+mod tests;
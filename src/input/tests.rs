@@ -0,0 +1,278 @@
+//! Tests of the key mapping
+//!
+//! These check which `Action` a key produces in each mode; what the action
+//! then does is covered by the app tests.
+
+use super::{map_key, map_paste};
+use crate::action::Action;
+use crate::app::{
+    App, CommandMenu, ConfirmAction, InputCallback, InputMode, InputType, WizardState,
+};
+use crate::config::CustomAction;
+use crate::field::FieldEdit;
+use crate::lxc::LxcClient;
+use crate::lxd_api::LxdApiClient;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+const LOG_PAGE: u16 = 20;
+
+fn app() -> App {
+    App::new(LxcClient::from_api(LxdApiClient::unix(
+        "/nonexistent/lxd.socket".to_string(),
+    )))
+}
+
+fn press(app: &App, code: KeyCode) -> Option<Action> {
+    map_key(app, KeyEvent::new(code, KeyModifiers::NONE), LOG_PAGE)
+}
+
+#[test]
+fn normal_mode_shortcuts() {
+    let app = app();
+    assert!(matches!(
+        press(&app, KeyCode::Char('s')),
+        Some(Action::StartSelected)
+    ));
+    assert!(matches!(
+        press(&app, KeyCode::Char(' ')),
+        Some(Action::OpenMenu(CommandMenu::System))
+    ));
+    assert!(matches!(
+        map_key(
+            &app,
+            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            LOG_PAGE
+        ),
+        Some(Action::Quit)
+    ));
+    assert!(matches!(
+        press(&app, KeyCode::Char('x')),
+        Some(Action::TogglePick)
+    ));
+    assert!(press(&app, KeyCode::Char('y')).is_none());
+}
+
+#[test]
+fn overlays_toggle_in_every_mode() {
+    let mut app = app();
+    app.start_new_container_wizard();
+    assert!(matches!(
+        press(&app, KeyCode::F(12)),
+        Some(Action::ToggleDebug)
+    ));
+    assert!(matches!(
+        press(&app, KeyCode::F(11)),
+        Some(Action::TogglePerf)
+    ));
+}
+
+#[test]
+fn menus_act_on_the_highlighted_item() {
+    let mut app = app();
+    app.show_command_menu(CommandMenu::Container);
+    app.menu_selected = 4;
+    assert!(matches!(
+        press(&app, KeyCode::Enter),
+        Some(Action::DeleteSelected)
+    ));
+    assert!(matches!(
+        press(&app, KeyCode::Char('b')),
+        Some(Action::OpenBackups)
+    ));
+
+    app.show_command_menu(CommandMenu::System);
+    app.menu_selected = 4;
+    assert!(matches!(
+        press(&app, KeyCode::Enter),
+        Some(Action::AddRemote)
+    ));
+    assert!(matches!(press(&app, KeyCode::Esc), Some(Action::CloseView)));
+}
+
+#[test]
+fn typed_confirmation_needs_the_name() {
+    let mut app = app();
+    app.input_mode = InputMode::Confirmation {
+        message: "Delete container 'db1'?".to_string(),
+        action: ConfirmAction::DeleteContainer("db1".to_string()),
+        typed_name: Some("db1".to_string()),
+        checks: Vec::new(),
+    };
+
+    app.input_buffer.set("db");
+    assert!(press(&app, KeyCode::Enter).is_none());
+    assert!(matches!(
+        press(&app, KeyCode::Char('y')),
+        Some(Action::EditInput(FieldEdit::Insert('y')))
+    ));
+
+    app.input_buffer.set("db1");
+    assert!(matches!(
+        press(&app, KeyCode::Enter),
+        Some(Action::Confirm(ConfirmAction::DeleteContainer(name))) if name == "db1"
+    ));
+}
+
+#[test]
+fn prompts_edit_at_the_cursor() {
+    let mut app = app();
+    app.input_mode = InputMode::Input {
+        prompt: "Name for the copy of 'web1':".to_string(),
+        input_type: InputType::ContainerName,
+        callback_action: InputCallback::CloneContainer("web1".to_string()),
+    };
+    let ctrl = |code| KeyEvent::new(code, KeyModifiers::CONTROL);
+    let edit = |key| match map_key(&app, key, LOG_PAGE) {
+        Some(Action::EditInput(edit)) => Some(edit),
+        _ => None,
+    };
+
+    assert_eq!(edit(KeyCode::Left.into()), Some(FieldEdit::Left));
+    assert_eq!(edit(ctrl(KeyCode::Left)), Some(FieldEdit::WordLeft));
+    assert_eq!(edit(KeyCode::Home.into()), Some(FieldEdit::Home));
+    assert_eq!(edit(ctrl(KeyCode::Char('e'))), Some(FieldEdit::End));
+    assert_eq!(edit(ctrl(KeyCode::Char('w'))), Some(FieldEdit::DeleteWord));
+    assert_eq!(
+        edit(KeyEvent::new(KeyCode::Backspace, KeyModifiers::ALT)),
+        Some(FieldEdit::DeleteWord)
+    );
+    assert_eq!(edit(KeyCode::Delete.into()), Some(FieldEdit::Delete));
+    assert_eq!(
+        edit(KeyCode::Char('w').into()),
+        Some(FieldEdit::Insert('w'))
+    );
+    // Characters the field doesn't take are ignored
+    assert_eq!(edit(KeyCode::Char(' ').into()), None);
+
+    // Pasting keeps only those it does
+    assert!(matches!(
+        map_paste(&app, "web 2\n"),
+        Some(Action::EditInput(FieldEdit::Paste(text))) if text == "web2"
+    ));
+    assert!(map_paste(&app, " \n").is_none());
+}
+
+#[test]
+fn pastes_a_single_line_into_every_field() {
+    let mut app = app();
+    assert!(map_paste(&app, "web1").is_none());
+
+    app.input_mode = InputMode::Input {
+        prompt: "Command:".to_string(),
+        input_type: InputType::Command,
+        callback_action: InputCallback::Command,
+    };
+    assert!(matches!(
+        map_paste(&app, "start web1\r\nstart\tweb2\n"),
+        Some(Action::EditInput(FieldEdit::Paste(text))) if text == "start web1 start web2"
+    ));
+
+    app.start_new_container_wizard();
+    app.input_mode = InputMode::Wizard(WizardState::SelectImage);
+    assert!(matches!(
+        map_paste(&app, " images:debian/12 \n"),
+        Some(Action::WizardImagePaste(text)) if text == "images:debian/12"
+    ));
+
+    app.input_mode = InputMode::Help {
+        query: String::new(),
+        searching: false,
+    };
+    assert!(map_paste(&app, "snapshot").is_none());
+    app.input_mode = InputMode::Help {
+        query: String::new(),
+        searching: true,
+    };
+    assert!(matches!(
+        map_paste(&app, "snapshot"),
+        Some(Action::HelpSearchPaste(text)) if text == "snapshot"
+    ));
+}
+
+#[test]
+fn wizard_name_step() {
+    let mut app = app();
+    app.start_new_container_wizard();
+    assert!(press(&app, KeyCode::Tab).is_none());
+    assert!(press(&app, KeyCode::Char('_')).is_none());
+
+    app.input_buffer.set("web1");
+    assert!(matches!(
+        press(&app, KeyCode::Tab),
+        Some(Action::WizardNameDone)
+    ));
+
+    app.input_mode = InputMode::Wizard(WizardState::SelectType);
+    assert!(matches!(
+        press(&app, KeyCode::Char('v')),
+        Some(Action::WizardSetVm(true))
+    ));
+}
+
+#[test]
+fn log_view_pages_by_its_height() {
+    let mut app = app();
+    app.input_mode = InputMode::Log;
+    assert!(matches!(
+        press(&app, KeyCode::PageDown),
+        Some(Action::ScrollLog {
+            delta: 20,
+            page: LOG_PAGE
+        })
+    ));
+    assert!(matches!(
+        press(&app, KeyCode::Char('G')),
+        Some(Action::LogBottom)
+    ));
+}
+
+#[test]
+fn custom_actions_follow_the_built_in_items() {
+    let mut app = app();
+    app.custom_actions = vec![CustomAction {
+        name: "SSH".to_string(),
+        command: "ssh root@{ip}".to_string(),
+        key: Some('x'),
+        pause: false,
+    }];
+    app.show_command_menu(CommandMenu::Container);
+
+    assert!(matches!(
+        press(&app, KeyCode::Char('x')),
+        Some(Action::RunCustom(0))
+    ));
+    assert!(matches!(
+        press(&app, KeyCode::Down),
+        Some(Action::MenuNext(27))
+    ));
+    app.menu_selected = 26;
+    assert!(matches!(
+        press(&app, KeyCode::Enter),
+        Some(Action::RunCustom(0))
+    ));
+}
+
+#[test]
+fn progress_modal_backgrounds_and_asks_before_cancelling() {
+    let mut app = app();
+    app.show_progress("op1");
+    assert!(matches!(
+        press(&app, KeyCode::Esc),
+        Some(Action::RunInBackground)
+    ));
+    assert!(matches!(
+        press(&app, KeyCode::Char('c')),
+        Some(Action::AskCancelProgress)
+    ));
+    assert!(press(&app, KeyCode::Char('y')).is_none());
+
+    app.confirm_cancel_progress(true);
+    assert!(matches!(
+        press(&app, KeyCode::Char('y')),
+        Some(Action::CancelProgress(id)) if id == "op1"
+    ));
+    assert!(matches!(
+        press(&app, KeyCode::Esc),
+        Some(Action::KeepProgress)
+    ));
+}
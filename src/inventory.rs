@@ -0,0 +1,265 @@
+//! Host inventory
+//!
+//! A server's instances, profiles, networks, pools and images as one sorted
+//! YAML or JSON document; a stable export leaves out what changes by itself.
+
+use crate::config::InventoryFormat;
+use crate::lxd_api::{LxdContainer, LxdImage, LxdNetwork, LxdProfile, LxdStoragePool};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const VOLATILE: &str = "volatile.";
+
+#[derive(Debug, Error)]
+pub enum InventoryError {
+    #[error("Inventory YAML: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("Inventory JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Inventory file: {0}")]
+    Io(#[from] io::Error),
+}
+
+type Devices = BTreeMap<String, BTreeMap<String, String>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instance {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub architecture: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub profiles: Vec<String>,
+    #[serde(default)]
+    pub ephemeral: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>, // Left out of stable exports
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+    #[serde(default)]
+    pub devices: Devices,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+    #[serde(default)]
+    pub devices: Devices,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Network {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoragePool {
+    pub driver: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Image {
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub auto_update: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>, // server and alias it was copied from
+}
+
+/// What the server is asked for
+#[derive(Debug, Default)]
+pub struct Sources {
+    pub instances: Vec<LxdContainer>,
+    pub profiles: Vec<LxdProfile>,
+    pub networks: Vec<LxdNetwork>,
+    pub pools: Vec<LxdStoragePool>,
+    pub images: Vec<LxdImage>,
+}
+
+/// A server's setup, keyed by name; images by fingerprint
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    pub server: String, // remote name, or "local"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<String>,
+    #[serde(default)]
+    pub instances: BTreeMap<String, Instance>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(default)]
+    pub networks: BTreeMap<String, Network>,
+    #[serde(default)]
+    pub storage_pools: BTreeMap<String, StoragePool>,
+    #[serde(default)]
+    pub images: BTreeMap<String, Image>,
+}
+
+fn sorted(map: &HashMap<String, String>) -> BTreeMap<String, String> {
+    map.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+}
+
+fn sorted_devices(devices: &HashMap<String, HashMap<String, String>>) -> Devices {
+    devices
+        .iter()
+        .map(|(name, device)| (name.clone(), sorted(device)))
+        .collect()
+}
+
+impl Inventory {
+    /// The inventory of `server`; a stable one leaves out what changes by
+    /// itself
+    pub fn new(server: &str, sources: &Sources, stable: bool) -> Self {
+        let instances = sources
+            .instances
+            .iter()
+            .map(|instance| {
+                let mut config = sorted(&instance.config);
+                if stable {
+                    config.retain(|key, _| !key.starts_with(VOLATILE));
+                }
+                let entry = Instance {
+                    kind: instance.container_type.clone(),
+                    architecture: instance.architecture.clone(),
+                    description: instance.description.clone(),
+                    profiles: instance.profiles.clone(),
+                    ephemeral: instance.ephemeral,
+                    status: (!stable).then(|| instance.status.clone()),
+                    config,
+                    devices: sorted_devices(&instance.devices),
+                };
+                (instance.name.clone(), entry)
+            })
+            .collect();
+        let profiles = sources
+            .profiles
+            .iter()
+            .map(|profile| {
+                let entry = Profile {
+                    description: profile.description.clone(),
+                    config: sorted(&profile.config),
+                    devices: sorted_devices(&profile.devices),
+                };
+                (profile.name.clone(), entry)
+            })
+            .collect();
+        // Unmanaged networks are the host's own interfaces, not LXD's setup
+        let networks = sources
+            .networks
+            .iter()
+            .filter(|network| network.managed)
+            .map(|network| {
+                let entry = Network {
+                    kind: network.network_type.clone(),
+                    description: network.description.clone(),
+                    config: sorted(&network.config),
+                };
+                (network.name.clone(), entry)
+            })
+            .collect();
+        let storage_pools = sources
+            .pools
+            .iter()
+            .map(|pool| {
+                let entry = StoragePool {
+                    driver: pool.driver.clone(),
+                    description: pool.description.clone(),
+                    config: sorted(&pool.config),
+                };
+                (pool.name.clone(), entry)
+            })
+            .collect();
+        let images = sources
+            .images
+            .iter()
+            .map(|image| {
+                let mut aliases: Vec<String> =
+                    image.aliases.iter().map(|a| a.name.clone()).collect();
+                aliases.sort();
+                let entry = Image {
+                    aliases,
+                    properties: sorted(&image.properties),
+                    size: image.size,
+                    auto_update: image.auto_update,
+                    source: image
+                        .update_source
+                        .as_ref()
+                        .map(|source| format!("{} {}", source.server, source.alias)),
+                };
+                (image.fingerprint.clone(), entry)
+            })
+            .collect();
+
+        Inventory {
+            server: server.to_string(),
+            exported_at: None,
+            instances,
+            profiles,
+            networks,
+            storage_pools,
+            images,
+        }
+    }
+
+    pub fn render(&self, format: InventoryFormat) -> Result<String, InventoryError> {
+        Ok(match format {
+            InventoryFormat::Yaml => serde_yaml::to_string(self)?,
+            InventoryFormat::Json => serde_json::to_string_pretty(self)? + "\n",
+        })
+    }
+
+    /// Write the inventory into `dir`, returning the file's path: one file
+    /// a server when stable, otherwise one for each export
+    pub fn save(
+        &self,
+        dir: &Path,
+        format: InventoryFormat,
+        stamp: Option<&str>,
+    ) -> Result<PathBuf, InventoryError> {
+        let path = dir.join(file_name(&self.server, format, stamp));
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&path, self.render(format)?)?;
+        Ok(path)
+    }
+}
+
+/// `inventory-SERVER.yaml`, with the time of the export before the
+/// extension when there is one
+pub fn file_name(server: &str, format: InventoryFormat, stamp: Option<&str>) -> String {
+    let server: String = server
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match stamp {
+        Some(stamp) => format!("inventory-{}-{}.{}", server, stamp, format.extension()),
+        None => format!("inventory-{}.{}", server, format.extension()),
+    }
+}
+
+#[cfg(test)]
+mod tests;
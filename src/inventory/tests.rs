@@ -0,0 +1,122 @@
+//! Tests of the inventory export
+
+use super::{file_name, Inventory, Sources};
+use crate::compare::tests::instance;
+use crate::config::InventoryFormat;
+use crate::lxd_api::{LxdImage, LxdImageAlias, LxdNetwork, LxdProfile, LxdStoragePool};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+fn sources() -> Sources {
+    let mut web1 = instance("web1", &["default"], serde_json::json!({}));
+    web1.config = HashMap::from([
+        ("limits.cpu".to_string(), "2".to_string()),
+        (
+            "volatile.eth0.hwaddr".to_string(),
+            "00:16:3e:00:00:01".to_string(),
+        ),
+    ]);
+    let network = |name: &str, managed| LxdNetwork {
+        name: name.to_string(),
+        network_type: "bridge".to_string(),
+        managed,
+        description: String::new(),
+        config: HashMap::from([("ipv4.address".to_string(), "10.0.0.1/24".to_string())]),
+    };
+    Sources {
+        instances: vec![web1, instance("db1", &["default"], serde_json::json!({}))],
+        profiles: vec![LxdProfile {
+            name: "default".to_string(),
+            description: "Default LXD profile".to_string(),
+            config: HashMap::new(),
+            devices: HashMap::from([(
+                "root".to_string(),
+                HashMap::from([
+                    ("type".to_string(), "disk".to_string()),
+                    ("path".to_string(), "/".to_string()),
+                    ("pool".to_string(), "default".to_string()),
+                ]),
+            )]),
+        }],
+        networks: vec![network("lxdbr0", true), network("eth0", false)],
+        pools: vec![LxdStoragePool {
+            name: "default".to_string(),
+            driver: "zfs".to_string(),
+            description: String::new(),
+            config: HashMap::from([("source".to_string(), "tank/lxd".to_string())]),
+            status: "Created".to_string(),
+        }],
+        images: vec![LxdImage {
+            fingerprint: "abc123".to_string(),
+            aliases: vec![
+                LxdImageAlias {
+                    name: "noble".to_string(),
+                    description: String::new(),
+                    target: "abc123".to_string(),
+                },
+                LxdImageAlias {
+                    name: "base".to_string(),
+                    description: String::new(),
+                    target: "abc123".to_string(),
+                },
+            ],
+            size: 1024,
+            ..LxdImage::default()
+        }],
+    }
+}
+
+#[test]
+fn records_the_servers_setup_in_order() {
+    let inventory = Inventory::new("local", &sources(), false);
+    let names: Vec<&String> = inventory.instances.keys().collect();
+    assert_eq!(names, ["db1", "web1"]);
+    let web1 = &inventory.instances["web1"];
+    assert_eq!(web1.status.as_deref(), Some("Running"));
+    assert!(web1.config.contains_key("volatile.eth0.hwaddr"));
+    assert_eq!(
+        inventory.profiles["default"].devices["root"]["pool"],
+        "default"
+    );
+    assert_eq!(inventory.networks.keys().collect::<Vec<_>>(), ["lxdbr0"]);
+    assert_eq!(inventory.storage_pools["default"].driver, "zfs");
+    assert_eq!(inventory.images["abc123"].aliases, ["base", "noble"]);
+}
+
+#[test]
+fn a_stable_export_leaves_out_what_changes_by_itself() {
+    let inventory = Inventory::new("prod", &sources(), true);
+    let web1 = &inventory.instances["web1"];
+    assert_eq!(web1.status, None);
+    assert_eq!(web1.config.keys().collect::<Vec<_>>(), ["limits.cpu"]);
+
+    // The same server gives the same text, which reads back as it was
+    let yaml = inventory.render(InventoryFormat::Yaml).unwrap();
+    let again = Inventory::new("prod", &sources(), true);
+    assert_eq!(again.render(InventoryFormat::Yaml).unwrap(), yaml);
+    assert!(!yaml.contains("status"));
+    assert_eq!(serde_yaml::from_str::<Inventory>(&yaml).unwrap(), inventory);
+
+    let json = inventory.render(InventoryFormat::Json).unwrap();
+    assert_eq!(serde_json::from_str::<Inventory>(&json).unwrap(), inventory);
+}
+
+#[test]
+fn saves_one_file_a_server_or_one_an_export() {
+    assert_eq!(
+        file_name("prod", InventoryFormat::Yaml, None),
+        "inventory-prod.yaml"
+    );
+    assert_eq!(
+        file_name("my.remote", InventoryFormat::Json, Some("20240101-120000")),
+        "inventory-my_remote-20240101-120000.json"
+    );
+
+    let dir = std::env::temp_dir().join(format!("lxtui-inventory-{}", Uuid::new_v4()));
+    let inventory = Inventory::new("local", &sources(), true);
+    let path = inventory.save(&dir, InventoryFormat::Yaml, None).unwrap();
+    assert_eq!(path, dir.join("inventory-local.yaml"));
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(saved, inventory.render(InventoryFormat::Yaml).unwrap());
+    std::fs::remove_dir_all(dir).unwrap();
+}
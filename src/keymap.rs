@@ -0,0 +1,740 @@
+//! Key bindings
+//!
+//! A single table of every key binding, grouped by the view it applies to.
+//! The command menus and the help screen are rendered from these tables so
+//! they stay in step with the key mapping in `input.rs`.
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyBinding {
+    pub keys: &'static str,
+    pub action: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyGroup {
+    pub title: &'static str,
+    pub bindings: &'static [KeyBinding],
+}
+
+const fn bind(keys: &'static str, action: &'static str, description: &'static str) -> KeyBinding {
+    KeyBinding {
+        keys,
+        action,
+        description,
+    }
+}
+
+pub const CONTAINER_LIST: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select container"),
+    bind("Enter", "Actions", "Open the container actions menu"),
+    bind("Space", "System", "Open the system menu"),
+    bind("s", "Start", "Start the selected container"),
+    bind("S", "Stop", "Stop the selected container"),
+    bind("d", "Delete", "Delete the container unless protected"),
+    bind("n", "New", "Create a new container"),
+    bind("r/R", "Refresh", "Reload the container list"),
+    bind("o/O", "Operations", "Toggle the operations sidebar"),
+    bind(
+        "Tab",
+        "Focus Operations",
+        "Select operations in the sidebar",
+    ),
+    bind("v", "Events", "Toggle the lifecycle event feed"),
+    bind("g", "Group", "Toggle grouping the list by tag"),
+    bind("1-9", "Workspace", "Switch to a saved workspace"),
+    bind("0", "All", "Leave the workspace and list everything"),
+    bind("u/Ctrl+R", "Undo/Redo", "Undo or redo the last view change"),
+    bind(
+        "A",
+        "High Contrast",
+        "Toggle shapes and a high-contrast palette",
+    ),
+    bind("?/h", "Help", "Show this help"),
+    bind("w", "Watch", "Follow the selected container live"),
+    bind(
+        "f",
+        "Console Log",
+        "Follow the selected container's console",
+    ),
+    bind(
+        "F",
+        "Crash Log",
+        "Console log captured when it last crashed",
+    ),
+    bind("t", "Top", "Rank running containers by usage"),
+    bind("D", "Dashboard", "Sum up the host at a glance"),
+    bind("B", "Boot Order", "Edit when containers start at boot"),
+    bind("I", "Image Audit", "List instances on outdated images"),
+    bind("Z", "DNS Zones", "List and edit the network zones"),
+    bind("U", "Image Store", "Auto-update and refresh stored images"),
+    bind(
+        "H",
+        "Capacity",
+        "Sum up limits against the host's resources",
+    ),
+    bind(
+        "T",
+        "Storage Pools",
+        "Check pool health and scrub ZFS or btrfs",
+    ),
+    bind("m", "Mark", "Mark the selected container for comparison"),
+    bind("C", "Compare", "Compare the marked container with another"),
+    bind("x", "Pick", "Pick the selected instance for batch changes"),
+    bind("X", "Clear Picks", "Drop every picked instance"),
+    bind(
+        "P",
+        "Profiles",
+        "Add or remove a profile on the picked instances",
+    ),
+    bind(
+        "E",
+        "Quick Exec",
+        "Run a library command in the picked instances",
+    ),
+    bind("K", "Key Search", "List the instances setting a config key"),
+    bind("L", "Log", "View the log file"),
+    bind(":", "Command", "Type a command, e.g. start web1"),
+    bind("!", "Host Shell", "Drop to $SHELL here; exit to return"),
+    bind("F11", "Performance", "Toggle the timing HUD (any screen)"),
+    bind("F12", "Debug", "Toggle the debug overlay (any screen)"),
+    bind("q/Q Ctrl+C", "Quit", "Exit LXTUI"),
+];
+
+/// Container actions menu, in menu order; Esc is always last
+pub const CONTAINER_MENU: &[KeyBinding] = &[
+    bind("Enter", "Smart Action", "Start if stopped, Stop if running"),
+    bind("1/s", "Start Container", "Start the selected container"),
+    bind("2/S", "Stop Container", "Stop the selected container"),
+    bind("3/r", "Restart Container", "Restart the selected container"),
+    bind("4/d", "Delete Container", "Delete the selected container"),
+    bind("5/c", "Clone Container", "Create a copy of the container"),
+    bind(
+        "6/u",
+        "Refresh Copy",
+        "Re-sync an existing copy from this one",
+    ),
+    bind("7/b", "Backups", "List, download and delete backups"),
+    bind("e/E", "Exec Shell", "Open shell in running container"),
+    bind("h", "SSH", "ssh to the IPv4 address as user.lxtui.ssh-user"),
+    bind("w", "Watch", "Follow state, usage and events live"),
+    bind("f", "Console Log", "Follow the console output"),
+    bind("t", "Tags", "Set the container's tags"),
+    bind("i", "Edit Config", "Edit the instance's YAML in $EDITOR"),
+    bind("m", "Templates", "Edit image metadata and templates"),
+    bind(
+        "o",
+        "Open in Editor",
+        "Copy a code --remote command and SSH config",
+    ),
+    bind("a", "Attach ISO", "Attach an ISO to a VM and boot from it"),
+    bind("v", "ISO Boot", "Toggle booting the VM from its ISO"),
+    bind("D", "Detach ISO", "Remove the VM's ISO once installed"),
+    bind(
+        "V",
+        "VM Settings",
+        "CPUs, hugepages, secure boot, migration, TPM",
+    ),
+    bind("g", "ID Mapping", "raw.idmap, isolation and disk shifting"),
+    bind(
+        "R",
+        "Recreate",
+        "Config only: a container's as a VM, or a VM's as a container",
+    ),
+    bind(
+        "p",
+        "Skip Schedule",
+        "Skip the next scheduled start or stop",
+    ),
+    bind("P", "Hold Schedule", "Leave out scheduled starts and stops"),
+    bind(
+        "I",
+        "Publish Image",
+        "Make an image of the stopped instance",
+    ),
+    bind("n", "Notes", "Edit the container's notes in $EDITOR"),
+    bind("Esc", "Cancel", "Return to container list"),
+];
+
+/// System menu, in menu order; Esc is always last
+pub const SYSTEM_MENU: &[KeyBinding] = &[
+    bind("1/r", "Refresh List", "Reload container list"),
+    bind("2/l", "Check LXD Service", "Ensure LXD service is running"),
+    bind("3/n", "New Container", "Create a new container"),
+    bind("4/o", "Toggle Operations", "Show/hide operations sidebar"),
+    bind("5/a", "Add Remote", "Connect to an LXD server over HTTPS"),
+    bind(
+        "6/m",
+        "All Remotes",
+        "Toggle listing containers from every remote",
+    ),
+    bind(
+        "7/p",
+        "API Preview",
+        "Toggle showing requests before they are sent",
+    ),
+    bind("8/h", "Help", "Show keyboard shortcuts"),
+    bind("9/q", "Quit", "Exit LXTUI"),
+    bind(
+        "0/s",
+        "Start LXD",
+        "Start or restart the service (systemctl or snap)",
+    ),
+    bind(
+        "i",
+        "Export Inventory",
+        "Save the server's instances, profiles, networks, pools and images",
+    ),
+    bind(
+        "d",
+        "Check Drift",
+        "Compare the server with an exported inventory",
+    ),
+    bind(
+        "u",
+        "Usage Report",
+        "Save what each instance used this session as CSV",
+    ),
+    bind("Esc", "Cancel", "Return to container list"),
+];
+
+/// Remote authentication menu, in menu order; Esc is always last
+pub const REMOTE_AUTH_MENU: &[KeyBinding] = &[
+    bind(
+        "1/t",
+        "Trust Token",
+        "Paste a token from 'lxc config trust add'",
+    ),
+    bind(
+        "2/o",
+        "OIDC Login",
+        "Sign in through the server's identity provider",
+    ),
+    bind(
+        "3/c",
+        "Client Certificate",
+        "Certificate already trusted by the server",
+    ),
+    bind("Esc", "Cancel", "Abort adding the remote"),
+];
+
+pub const BACKUPS_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select backup"),
+    bind("n", "New", "Create a new backup"),
+    bind(
+        "w",
+        "Download",
+        "Save the backup to your downloads directory",
+    ),
+    bind("d", "Delete", "Delete the backup (confirm with y)"),
+    bind("r", "Reload", "Reload the backup list"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const CONFIRMATION: &[KeyBinding] = &[
+    bind("Enter/y", "Confirm", "Run the action"),
+    bind("Esc/n", "Cancel", "Dismiss the dialog"),
+    bind(
+        "p",
+        "Remove Protection",
+        "Unset security.protection.delete, then ask again",
+    ),
+    bind(
+        "name, Enter",
+        "Typed Confirm",
+        "Type the container name for actions set to typed",
+    ),
+];
+
+pub const PROGRESS: &[KeyBinding] = &[
+    bind(
+        "b/Esc",
+        "Background",
+        "Close the dialog; the operation carries on in the sidebar",
+    ),
+    bind(
+        "c, y",
+        "Cancel",
+        "Cancel the operation, after confirming with y",
+    ),
+];
+
+pub const WIZARD: &[KeyBinding] = &[
+    bind("Tab", "Next", "Go to the next step"),
+    bind("Shift+Tab", "Previous", "Go back a step"),
+    bind("↑/↓", "Select", "Choose an image"),
+    bind(
+        "Type",
+        "Search",
+        "Filter images by alias, including the image server's",
+    ),
+    bind("c/v", "Type", "Pick container or virtual machine"),
+    bind(
+        "none",
+        "Empty",
+        "Type as the image for no image; a VM then takes an ISO",
+    ),
+    bind(
+        "Enter",
+        "Confirm",
+        "After the name, skip to the last step with the defaults; there, create",
+    ),
+    bind("Esc", "Cancel", "Abandon the wizard"),
+];
+
+pub const TEXT_FIELDS: &[KeyBinding] = &[
+    bind(
+        "←/→",
+        "Move",
+        "Move the cursor; with Ctrl or Alt, by a word",
+    ),
+    bind(
+        "Home/End",
+        "Start/End",
+        "Jump to the start or end (also Ctrl+A/E)",
+    ),
+    bind("Ctrl+W", "Delete word", "Delete the word before the cursor"),
+    bind(
+        "Ctrl+U/K",
+        "Delete line",
+        "Delete everything before or after the cursor",
+    ),
+    bind(
+        "↑/↓",
+        "History",
+        "In prompts, step through what was typed there before",
+    ),
+];
+
+pub const PREVIEW: &[KeyBinding] = &[
+    bind("Enter/s", "Send", "Send the request as shown"),
+    bind("Esc/n", "Cancel", "Discard the request"),
+];
+
+pub const HELP_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Scroll", "Scroll one line"),
+    bind("PgUp/PgDn", "Page", "Scroll one page"),
+    bind("g/G", "Top/Bottom", "Jump to the start or end"),
+    bind("/", "Search", "Filter bindings; Enter keeps the filter"),
+    bind("t", "Tour", "Take the guided tour again"),
+    bind("Esc/q", "Close", "Clear the filter, then close help"),
+];
+
+pub const TOUR: &[KeyBinding] = &[
+    bind("Enter/→", "Next", "Go to the next step"),
+    bind("←", "Back", "Go back a step"),
+    bind("Esc/q", "End", "End the tour; t in help shows it again"),
+];
+
+pub const LOG_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Scroll", "Scroll one line"),
+    bind("PgUp/PgDn", "Page", "Scroll one page"),
+    bind("g/G", "Top/Bottom", "Jump to the start or end"),
+    bind("r", "Reload", "Re-read the log file"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+/// Commands typed after `:` or listed in a `--script` file
+pub const COMMANDS: &[KeyBinding] = &[
+    bind("start NAME", "Start", "Start a container"),
+    bind("stop NAME", "Stop", "Stop a container"),
+    bind("restart NAME", "Restart", "Restart a container"),
+    bind("delete NAME", "Delete", "Delete a container"),
+    bind(
+        "snapshot NAME",
+        "Snapshot",
+        "Snapshot a container; a 2nd word names it",
+    ),
+    bind(
+        "filter F=V ...",
+        "Filter",
+        "Filter by name/status/type/remote/tag",
+    ),
+    bind("filter", "Clear Filter", "Show every container again"),
+    bind("select NAME", "Select", "Select a container"),
+    bind("refresh", "Refresh", "Reload the container list"),
+    bind("quit", "Quit", "Exit LXTUI"),
+];
+
+pub const OPERATIONS_SIDEBAR: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select an operation, newest first"),
+    bind("Enter", "Details", "Show the full error and LXD metadata"),
+    bind("r", "Retry", "Re-issue a failed start/stop/restart/delete"),
+    bind(
+        "f",
+        "Filter",
+        "Show all operations, only active ones, or only failures",
+    ),
+    bind("g", "Group", "Group by container, by status, or not at all"),
+    bind(
+        "c",
+        "Clear",
+        "Clear the finished operations the filter shows",
+    ),
+    bind("o/O", "Hide", "Hide the sidebar"),
+    bind("Esc/Tab", "Back", "Return focus to the container list"),
+];
+
+pub const WATCH_VIEW: &[KeyBinding] = &[
+    bind("c", "Clear", "Clear the event list"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const TOP_VIEW: &[KeyBinding] = &[
+    bind("c", "CPU", "Rank by CPU use"),
+    bind("m", "Memory", "Rank by memory use"),
+    bind("n", "Network", "Rank by bytes received and sent"),
+    bind("+/-", "Window", "Average over more or fewer samples"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const DASHBOARD: &[KeyBinding] = &[
+    bind("Enter/Esc", "List", "Drill into the container list"),
+    bind("q/Q Ctrl+C", "Quit", "Exit LXTUI"),
+];
+
+pub const BOOT_ORDER_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select container"),
+    bind("a", "Autostart", "Cycle always, never and if running"),
+    bind("p", "Priority", "Set the priority; higher starts first"),
+    bind("d", "Delay", "Set the seconds waited after starting it"),
+    bind("s", "Sort", "Sort by start order, name or delay"),
+    bind("r", "Reload", "Reload the settings from LXD"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const AUDIT_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select instance"),
+    bind(
+        "b",
+        "Rebuild",
+        "Rebuild it from the latest image (stopped only)",
+    ),
+    bind("y", "Confirm", "Confirm the rebuild; any other key cancels"),
+    bind("r", "Reload", "Check the images again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const ZONES_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select zone or record"),
+    bind("a", "New Zone", "Create a zone, e.g. lxd.example.net"),
+    bind("n", "New Record", "Add a record to the selected zone"),
+    bind(
+        "Enter/e",
+        "Edit Record",
+        "Change the selected record's entries",
+    ),
+    bind("d", "Delete", "Delete the selected zone or record"),
+    bind("y", "Confirm", "Confirm the delete; any other key cancels"),
+    bind("r", "Reload", "Load the zones again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const IMAGE_STORE_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select image"),
+    bind(
+        "a",
+        "Auto-update",
+        "Turn LXD's automatic updates of the image on or off",
+    ),
+    bind("u", "Update Now", "Pull the latest build from its remote"),
+    bind("r", "Reload", "List the images again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const POOLS_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select pool"),
+    bind("s", "Scrub", "Scrub the ZFS or btrfs pool as root"),
+    bind("y", "Confirm", "Confirm the scrub; any other key cancels"),
+    bind("r", "Reload", "Read the pools and check them again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const CAPACITY_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select instance"),
+    bind("r", "Reload", "Read the limits and resources again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const TEMPLATES_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select template file"),
+    bind("Enter/e", "Edit", "Edit the template in $EDITOR"),
+    bind("m", "Metadata", "Edit metadata.yaml in $EDITOR"),
+    bind("n", "New", "Add a template file"),
+    bind("r", "Reload", "Reload the templates from LXD"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const VM_SETTINGS_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select setting"),
+    bind(
+        "Enter/Space",
+        "Change",
+        "Switch it on or off, or set the CPUs",
+    ),
+    bind("r", "Reload", "Read the settings again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const IDMAP_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select a line or disk"),
+    bind("a", "Add", "Add a raw.idmap line"),
+    bind("Enter/e", "Edit", "Change the selected line"),
+    bind("d", "Delete", "Remove the selected line"),
+    bind("i", "Isolated", "Toggle a range no other container shares"),
+    bind("s", "Shift", "Toggle shifting the selected disk"),
+    bind("r", "Reload", "Read the map again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const PROFILES_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select profile"),
+    bind("a/Enter", "Add", "Add it to every picked instance"),
+    bind("d", "Remove", "Remove it from every picked instance"),
+    bind("r", "Reload", "Read the profiles again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const QUICK_VIEW: &[KeyBinding] = &[
+    bind(
+        "j/k ↑/↓",
+        "Navigate",
+        "Select an action, or an instance once run",
+    ),
+    bind(
+        "key/Enter",
+        "Run",
+        "Run the action in every instance at once",
+    ),
+    bind("y", "Confirm", "Run an action that asks first"),
+    bind("!", "Command", "Type a command to run once"),
+    bind(
+        "PgUp/PgDn",
+        "Scroll",
+        "Scroll the selected instance's output",
+    ),
+    bind(
+        "Enter",
+        "Expand",
+        "Show the output across the view, once run",
+    ),
+    bind("r", "Run Again", "Run the command again, once run"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const KEY_SEARCH_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select instance"),
+    bind("Enter", "Show", "Select the instance in the list"),
+    bind("/", "Search", "Look up another key"),
+    bind("r", "Reload", "Read the instances again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const DRIFT_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Navigate", "Select entry"),
+    bind("o", "Open", "Compare with another file"),
+    bind("r", "Reload", "Compare again"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const RECREATE_VIEW: &[KeyBinding] = &[
+    bind("n", "Name", "Name the new instance"),
+    bind("i", "Image", "Choose the image it is created from"),
+    bind(
+        "Enter",
+        "Recreate",
+        "Snapshot the original and create the new one, files left behind",
+    ),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const COMPARE_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Scroll", "Scroll one row"),
+    bind("PgUp/PgDn", "Page", "Scroll ten rows"),
+    bind("d", "Differences", "Show only the settings that differ"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const CONSOLE_VIEW: &[KeyBinding] = &[
+    bind("j/k ↑/↓", "Scroll", "Scroll one line"),
+    bind("PgUp/PgDn", "Page", "Scroll one page"),
+    bind("g/G", "Top/Bottom", "Jump to the start, or follow the end"),
+    bind("Space/p", "Pause", "Stop or resume reading new output"),
+    bind("/", "Search", "Search the scrollback"),
+    bind("n/N", "Next/Previous", "Jump to the next or previous match"),
+    bind("Esc/q", "Close", "Return to container list"),
+];
+
+pub const GROUPS: &[KeyGroup] = &[
+    KeyGroup {
+        title: "Container List",
+        bindings: CONTAINER_LIST,
+    },
+    KeyGroup {
+        title: "Commands (:)",
+        bindings: COMMANDS,
+    },
+    KeyGroup {
+        title: "Container Actions Menu (Enter)",
+        bindings: CONTAINER_MENU,
+    },
+    KeyGroup {
+        title: "System Menu (Space)",
+        bindings: SYSTEM_MENU,
+    },
+    KeyGroup {
+        title: "Remote Authentication Menu",
+        bindings: REMOTE_AUTH_MENU,
+    },
+    KeyGroup {
+        title: "Operations Sidebar (Tab)",
+        bindings: OPERATIONS_SIDEBAR,
+    },
+    KeyGroup {
+        title: "Backups View",
+        bindings: BACKUPS_VIEW,
+    },
+    KeyGroup {
+        title: "Confirmation Dialogs",
+        bindings: CONFIRMATION,
+    },
+    KeyGroup {
+        title: "Operation Progress",
+        bindings: PROGRESS,
+    },
+    KeyGroup {
+        title: "API Preview",
+        bindings: PREVIEW,
+    },
+    KeyGroup {
+        title: "New Container Wizard",
+        bindings: WIZARD,
+    },
+    KeyGroup {
+        title: "Text Fields",
+        bindings: TEXT_FIELDS,
+    },
+    KeyGroup {
+        title: "Watch View",
+        bindings: WATCH_VIEW,
+    },
+    KeyGroup {
+        title: "Top View",
+        bindings: TOP_VIEW,
+    },
+    KeyGroup {
+        title: "Dashboard (D)",
+        bindings: DASHBOARD,
+    },
+    KeyGroup {
+        title: "Boot Order View",
+        bindings: BOOT_ORDER_VIEW,
+    },
+    KeyGroup {
+        title: "Image Audit View",
+        bindings: AUDIT_VIEW,
+    },
+    KeyGroup {
+        title: "DNS Zones View",
+        bindings: ZONES_VIEW,
+    },
+    KeyGroup {
+        title: "Image Store View",
+        bindings: IMAGE_STORE_VIEW,
+    },
+    KeyGroup {
+        title: "Storage Pools View",
+        bindings: POOLS_VIEW,
+    },
+    KeyGroup {
+        title: "Capacity View",
+        bindings: CAPACITY_VIEW,
+    },
+    KeyGroup {
+        title: "Templates View",
+        bindings: TEMPLATES_VIEW,
+    },
+    KeyGroup {
+        title: "VM Settings View",
+        bindings: VM_SETTINGS_VIEW,
+    },
+    KeyGroup {
+        title: "ID Mapping View",
+        bindings: IDMAP_VIEW,
+    },
+    KeyGroup {
+        title: "Profiles View",
+        bindings: PROFILES_VIEW,
+    },
+    KeyGroup {
+        title: "Quick Exec View",
+        bindings: QUICK_VIEW,
+    },
+    KeyGroup {
+        title: "Key Search View",
+        bindings: KEY_SEARCH_VIEW,
+    },
+    KeyGroup {
+        title: "Drift View",
+        bindings: DRIFT_VIEW,
+    },
+    KeyGroup {
+        title: "Recreate View",
+        bindings: RECREATE_VIEW,
+    },
+    KeyGroup {
+        title: "Compare View",
+        bindings: COMPARE_VIEW,
+    },
+    KeyGroup {
+        title: "Console View",
+        bindings: CONSOLE_VIEW,
+    },
+    KeyGroup {
+        title: "Log View",
+        bindings: LOG_VIEW,
+    },
+    KeyGroup {
+        title: "Help",
+        bindings: HELP_VIEW,
+    },
+    KeyGroup {
+        title: "Tour (t in help)",
+        bindings: TOUR,
+    },
+];
+
+/// Groups and bindings matching `query` (case-insensitive); a group whose
+/// title matches keeps all of its bindings
+pub fn search(query: &str) -> Vec<(&'static KeyGroup, Vec<&'static KeyBinding>)> {
+    let query = query.to_lowercase();
+
+    GROUPS
+        .iter()
+        .filter_map(|group| {
+            let bindings: Vec<&KeyBinding> = if group.title.to_lowercase().contains(&query) {
+                group.bindings.iter().collect()
+            } else {
+                group
+                    .bindings
+                    .iter()
+                    .filter(|b| {
+                        b.keys.to_lowercase().contains(&query)
+                            || b.action.to_lowercase().contains(&query)
+                            || b.description.to_lowercase().contains(&query)
+                    })
+                    .collect()
+            };
+            (!bindings.is_empty()).then_some((group, bindings))
+        })
+        .collect()
+}
+
+/// Number of lines the help screen needs for `query`: a title and a blank
+/// separator per group plus one line per binding
+pub fn help_line_count(query: &str) -> usize {
+    search(query)
+        .iter()
+        .map(|(_, bindings)| bindings.len() + 2)
+        .sum()
+}
@@ -0,0 +1,171 @@
+//! File logging
+//!
+//! Log records go to a file rather than stderr, which would draw over the
+//! TUI. The file is rotated by size according to the `[logging]` section of
+//! the config file.
+
+use crate::config::LoggingConfig;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LoggingError {
+    #[error("Failed to open log file {0}: {1}")]
+    Open(PathBuf, io::Error),
+    #[error("Logger already initialized")]
+    AlreadyInitialized(#[from] log::SetLoggerError),
+}
+
+/// Path of the active log file, set by `init`
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Number of log lines kept in memory for the debug overlay
+const RECENT_LINES: usize = 200;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+struct LogFile {
+    file: File,
+    size: u64,
+}
+
+struct FileLogger {
+    level: LevelFilter,
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: Mutex<LogFile>,
+}
+
+/// Install the file logger. `RUST_LOG` set to a level name overrides the
+/// configured level.
+pub fn init(config: &LoggingConfig) -> Result<(), LoggingError> {
+    let level = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| config.level.into());
+
+    let path = config.log_path();
+    let file = open(&path).map_err(|e| LoggingError::Open(path.clone(), e))?;
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+    let logger = FileLogger {
+        level,
+        path: path.clone(),
+        max_bytes: config.max_size_kb.saturating_mul(1024),
+        max_files: config.max_files,
+        file: Mutex::new(LogFile { file, size }),
+    };
+
+    log::set_boxed_logger(Box::new(logger))?;
+    log::set_max_level(level);
+    let _ = LOG_PATH.set(path);
+    Ok(())
+}
+
+/// The file log records are being written to, if logging was initialized
+pub fn log_path() -> Option<&'static Path> {
+    LOG_PATH.get().map(PathBuf::as_path)
+}
+
+/// The most recent log lines, oldest first
+pub fn recent_lines() -> Vec<String> {
+    RECENT
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Open the log for appending, readable only by the current user
+fn open(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// `lxtui.log` -> `lxtui.log.N`
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+impl FileLogger {
+    /// Shift `lxtui.log.N` to `.N+1`, dropping the oldest, and start a new file
+    fn rotate(&self, current: &mut LogFile) -> io::Result<()> {
+        if self.max_files > 0 {
+            let _ = fs::remove_file(rotated_path(&self.path, self.max_files));
+            for n in (1..self.max_files).rev() {
+                let _ = fs::rename(rotated_path(&self.path, n), rotated_path(&self.path, n + 1));
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+
+        current.file = open(&self.path)?;
+        current.size = 0;
+        Ok(())
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "{} {:<5} {}: {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+
+        if let Ok(mut recent) = RECENT.lock() {
+            if recent.len() == RECENT_LINES {
+                recent.pop_front();
+            }
+            recent.push_back(line.trim_end().to_string());
+        }
+
+        let Ok(mut current) = self.file.lock() else {
+            return;
+        };
+        if self.max_bytes > 0
+            && current.size > 0
+            && current.size + line.len() as u64 > self.max_bytes
+        {
+            // Keep writing to the old file if rotation fails
+            let _ = self.rotate(&mut current);
+        }
+        if current.file.write_all(line.as_bytes()).is_ok() {
+            current.size += line.len() as u64;
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut current) = self.file.lock() {
+            let _ = current.file.flush();
+        }
+    }
+}
@@ -3,11 +3,42 @@
 //! This module provides the interface to LXC/LXD operations, handling
 //! container management, state monitoring, and async operations.
 
+use crate::alerts;
+use crate::audit::AuditEntry;
+use crate::auth::OidcProvider;
+use crate::boot::BootEntry;
+use crate::capacity::{Host, Reservation};
+use crate::clone::CloneOptions;
+use crate::config::{CreateDefaults, RemoteConfig};
+use crate::convert::Conversion;
+use crate::dashboard::PoolUsage;
+use crate::edit::InstanceConfig;
+use crate::hooks;
+use crate::inventory::{Inventory, Sources};
 use crate::lxd_api::{
-    ContainerState as ApiContainerState, LxdApiClient, LxdApiError, LxdContainer, LxdOperation,
+    ApiRequest, ExecOutput, LxdApiClient, LxdApiError, LxdBackup, LxdContainer, LxdNetworkForward,
+    LxdOperation, ServerInfo,
 };
+use crate::media::Media;
+use crate::metrics::{self, InstanceMetrics, MetricsError};
+use crate::notes;
+use crate::pools::{self, Pool};
+use crate::ports::{self, Port};
+use crate::preflight::{self, Check};
+use crate::probe;
+use crate::publish::Publish;
+use crate::restart;
+use crate::space::{self, Projection};
+use crate::store::StoredImage;
+use crate::tags;
+use crate::templates::InstanceMetadata;
+use crate::usage::{self, Reading};
+use crate::vm::{self, Setting};
+use crate::zones::{self, Zone};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
@@ -22,9 +53,12 @@ pub struct Image {
 }
 
 #[derive(Debug, Error)]
+#[allow(dead_code)]
 pub enum LxcError {
     #[error("API error: {0}")]
     ApiError(String),
+    #[error("Not supported by this server: {0}")]
+    Unsupported(String),
     #[error("Timeout waiting for operation: {0}")]
     Timeout(String),
     #[error("Container not found: {0}")]
@@ -39,6 +73,8 @@ pub enum LxcError {
     JsonError(#[from] serde_json::Error),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("Metrics error: {0}")]
+    MetricsError(#[from] MetricsError),
 }
 
 impl From<LxdApiError> for LxcError {
@@ -46,6 +82,7 @@ impl From<LxdApiError> for LxcError {
         match err {
             LxdApiError::Timeout(msg) => LxcError::Timeout(msg),
             LxdApiError::ApiError(msg) => LxcError::ApiError(msg),
+            LxdApiError::Unsupported(msg) => LxcError::Unsupported(msg),
             LxdApiError::OperationFailed(msg) => LxcError::ApiError(msg),
             _ => LxcError::ApiError(err.to_string()),
         }
@@ -83,6 +120,109 @@ pub struct Container {
     pub ipv6: Vec<String>,
     #[serde(rename = "type")]
     pub container_type: String,
+    /// Remote the container lives on when listing several remotes at once
+    #[serde(default)]
+    pub remote: Option<String>,
+    /// Bytes of memory in use, when running
+    #[serde(default)]
+    pub memory_usage: Option<i64>,
+    /// Bytes allowed by `limits.memory`, when set to a size
+    #[serde(default)]
+    pub memory_limit: Option<i64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// User to log in as over SSH, from `user.lxtui.ssh-user`
+    #[serde(default)]
+    pub ssh_user: Option<String>,
+    /// Description of the image the instance was created from
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Fingerprint of that image, from `volatile.base_image`
+    #[serde(default)]
+    pub base_image: Option<String>,
+    /// Command probing the instance's health, from `user.lxtui.healthcheck`
+    #[serde(default)]
+    pub healthcheck: Option<String>,
+    /// Whether `security.protection.delete` stops LXD deleting it
+    #[serde(default)]
+    pub protected: bool,
+    /// Started again when it crashes, from `user.lxtui.restart=always`
+    #[serde(default)]
+    pub restart: bool,
+    /// Host ports its proxy devices and network forwards publish
+    #[serde(default)]
+    pub ports: Vec<Port>,
+    /// Free-form notes, from `user.lxtui.notes`
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+impl Container {
+    /// The image the instance was created from: its description, or the
+    /// start of its fingerprint when the image left no description
+    pub fn image_label(&self) -> Option<String> {
+        self.image.clone().or_else(|| {
+            self.base_image
+                .as_ref()
+                .map(|fingerprint| fingerprint.chars().take(12).collect())
+        })
+    }
+
+    /// Name qualified with its remote ("remote:name"), as accepted by `lxc`
+    pub fn qualified_name(&self) -> String {
+        match &self.remote {
+            Some(remote) => format!("{}:{}", remote, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub name: String,
+    pub created_at: String,
+    pub expires_at: Option<String>, // None when the backup never expires
+}
+
+impl From<LxdBackup> for Backup {
+    fn from(backup: LxdBackup) -> Self {
+        // Older servers report names as "instance/backup"
+        let name = backup
+            .name
+            .rsplit('/')
+            .next()
+            .unwrap_or(&backup.name)
+            .to_string();
+        let expires_at = if backup.expires_at.starts_with("0001-") {
+            None
+        } else {
+            Some(format_timestamp(&backup.expires_at))
+        };
+
+        Backup {
+            name,
+            created_at: format_timestamp(&backup.created_at),
+            expires_at,
+        }
+    }
+}
+
+/// Shorten an RFC 3339 timestamp to "YYYY-MM-DD HH:MM:SS"
+fn format_timestamp(timestamp: &str) -> String {
+    timestamp.get(..19).unwrap_or(timestamp).replace('T', " ")
+}
+
+/// What an instance's `image.*` keys say about the image it was created
+/// from: `image.description`, or the OS and release
+pub(crate) fn image_description(config: &HashMap<String, String>) -> Option<String> {
+    let key = |key: &str| config.get(key).map(|v| v.trim()).filter(|v| !v.is_empty());
+    key("image.description").map(str::to_string).or_else(|| {
+        let os = key("image.os")?;
+        Some(match key("image.release") {
+            Some(release) => format!("{} {}", os, release),
+            None => os.to_string(),
+        })
+    })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,7 +233,7 @@ pub struct ContainerState {
 
 #[derive(Clone)]
 pub struct LxcClient {
-    api_client: Arc<Mutex<LxdApiClient>>,
+    api_client: Arc<LxdApiClient>, // Shared; requests run concurrently over pooled connections
     operations: Arc<RwLock<Vec<Operation>>>,
     cancellation_token: CancellationToken,
     operation_lock: Arc<Mutex<()>>,
@@ -111,8 +251,22 @@ impl LxcClient {
             })
         });
 
+        Self::from_api(api_client)
+    }
+
+    /// Create a client for the local socket, failing instead of panicking when absent
+    pub fn local() -> Result<Self, LxcError> {
+        Ok(Self::from_api(LxdApiClient::new()?))
+    }
+
+    /// Create a client for a configured HTTPS or SSH remote
+    pub fn for_remote(remote: &RemoteConfig) -> Result<Self, LxcError> {
+        Ok(Self::from_api(LxdApiClient::for_remote(remote)?))
+    }
+
+    pub fn from_api(api_client: LxdApiClient) -> Self {
         Self {
-            api_client: Arc::new(Mutex::new(api_client)),
+            api_client: Arc::new(api_client),
             operations: Arc::new(RwLock::new(Vec::new())),
             cancellation_token: CancellationToken::new(),
             operation_lock: Arc::new(Mutex::new(())),
@@ -123,6 +277,7 @@ impl LxcClient {
         self.operations.read().await.clone()
     }
 
+    #[allow(dead_code)]
     pub async fn add_operation(&self, operation: Operation) -> String {
         let mut ops = self.operations.write().await;
         let id = operation.id.clone();
@@ -133,6 +288,7 @@ impl LxcClient {
         id
     }
 
+    #[allow(dead_code)]
     pub async fn update_operation_status(&self, id: &str, status: OperationStatus) {
         let mut ops = self.operations.write().await;
         if let Some(op) = ops.iter_mut().find(|o| o.id == id) {
@@ -145,7 +301,7 @@ impl LxcClient {
     }
 
     pub async fn ensure_lxd_running(&self) -> Result<bool, LxcError> {
-        let client = self.api_client.lock().await;
+        let client = &self.api_client;
 
         // Check if LXD is accessible via API
         if client.check_lxd_running().await {
@@ -157,10 +313,26 @@ impl LxcClient {
         Err(LxcError::ServiceUnavailable)
     }
 
+    pub async fn server_info(&self) -> Result<ServerInfo, LxcError> {
+        let client = &self.api_client;
+        Ok(client.server_info().await?)
+    }
+
+    pub async fn add_trust_token(&self, token: &str) -> Result<(), LxcError> {
+        let client = &self.api_client;
+        Ok(client.add_trust_token(token).await?)
+    }
+
+    pub async fn oidc_provider(&self) -> Result<Option<OidcProvider>, LxcError> {
+        let client = &self.api_client;
+        Ok(client.oidc_provider().await?)
+    }
+
     pub async fn list_containers(&self) -> Result<Vec<Container>, LxcError> {
-        let client = self.api_client.lock().await;
+        let client = &self.api_client;
 
         let api_containers = client.list_containers().await?;
+        let forwards = self.network_forwards().await;
 
         let mut containers = Vec::new();
         for api_container in api_containers {
@@ -170,7 +342,7 @@ impl LxcClient {
             let mut ipv4_addresses = Vec::new();
             if let Some(state) = &state {
                 if let Some(network) = &state.network {
-                    for (_name, interface) in network {
+                    for interface in network.values() {
                         for addr in &interface.addresses {
                             if addr.family == "inet" && addr.address != "127.0.0.1" {
                                 ipv4_addresses.push(addr.address.clone());
@@ -180,6 +352,55 @@ impl LxcClient {
                 }
             }
 
+            let memory_usage = state
+                .as_ref()
+                .and_then(|state| state.memory.as_ref())
+                .map(|memory| memory.usage);
+            let expanded = api_container
+                .expanded_config
+                .as_ref()
+                .unwrap_or(&api_container.config);
+            let memory_limit = expanded
+                .get("limits.memory")
+                .and_then(|limit| alerts::parse_size(limit));
+            // Often set for a whole group of instances through a profile
+            let healthcheck = expanded
+                .get(probe::CONFIG_KEY)
+                .map(|command| command.trim().to_string())
+                .filter(|command| !command.is_empty());
+            let protected = preflight::protected(expanded);
+            let restart = expanded
+                .get(restart::CONFIG_KEY)
+                .is_some_and(|value| restart::always(value));
+            let tags = api_container
+                .config
+                .get(tags::CONFIG_KEY)
+                .map(|value| tags::parse(value))
+                .unwrap_or_default();
+            let ssh_user = api_container
+                .config
+                .get(hooks::SSH_USER_KEY)
+                .map(|user| user.trim().to_string())
+                .filter(|user| !user.is_empty());
+            let notes = api_container
+                .config
+                .get(notes::CONFIG_KEY)
+                .filter(|notes| !notes.trim().is_empty())
+                .cloned();
+            let image = image_description(&api_container.config);
+            let base_image = api_container
+                .config
+                .get("volatile.base_image")
+                .filter(|fingerprint| !fingerprint.is_empty())
+                .cloned();
+            let mut ports = ports::proxied(
+                api_container
+                    .expanded_devices
+                    .as_ref()
+                    .unwrap_or(&api_container.devices),
+            );
+            ports.extend(ports::forwarded(&forwards, &ipv4_addresses));
+
             containers.push(Container {
                 name: api_container.name,
                 status: api_container.status.clone(),
@@ -190,25 +411,60 @@ impl LxcClient {
                 ipv4: ipv4_addresses,
                 ipv6: Vec::new(),
                 container_type: api_container.container_type,
+                remote: None,
+                memory_usage,
+                memory_limit,
+                tags,
+                ssh_user,
+                image,
+                base_image,
+                healthcheck,
+                protected,
+                restart,
+                ports,
+                notes,
             });
         }
 
         Ok(containers)
     }
 
+    /// Forwards of every managed network; none when the server is too old
+    /// to have them
+    async fn network_forwards(&self) -> Vec<LxdNetworkForward> {
+        let client = &self.api_client;
+        let Ok(networks) = client.list_networks().await else {
+            return Vec::new();
+        };
+        futures::future::join_all(
+            networks
+                .iter()
+                .filter(|network| network.managed)
+                .map(|network| client.list_network_forwards(&network.name)),
+        )
+        .await
+        .into_iter()
+        .filter_map(Result::ok)
+        .flatten()
+        .collect()
+    }
+
+    #[allow(dead_code)]
     pub async fn start_container(&self, name: &str) -> Result<(), LxcError> {
         let _lock = self.operation_lock.lock().await;
 
         // Check if container exists and is not already running
-        let client = self.api_client.lock().await;
-        let state = client.get_container_state(name).await?;
+        {
+            let client = &self.api_client;
+            let state = client.get_container_state(name).await?;
 
-        if state.status == "Running" {
-            return Ok(());
-        }
+            if state.status == "Running" {
+                return Ok(());
+            }
 
-        // Start the container
-        client.start_container(name).await?;
+            // Start the container
+            client.start_container(name).await?;
+        }
 
         // Wait for it to be running
         self.wait_for_state(name, "Running", Duration::from_secs(30))
@@ -217,17 +473,20 @@ impl LxcClient {
         Ok(())
     }
 
+    #[allow(dead_code)]
     pub async fn stop_container(&self, name: &str) -> Result<(), LxcError> {
         let _lock = self.operation_lock.lock().await;
 
-        let client = self.api_client.lock().await;
-        let state = client.get_container_state(name).await?;
+        {
+            let client = &self.api_client;
+            let state = client.get_container_state(name).await?;
 
-        if state.status == "Stopped" {
-            return Ok(());
-        }
+            if state.status == "Stopped" {
+                return Ok(());
+            }
 
-        client.stop_container(name).await?;
+            client.stop_container(name).await?;
+        }
 
         // Wait for it to be stopped
         self.wait_for_state(name, "Stopped", Duration::from_secs(30))
@@ -236,11 +495,14 @@ impl LxcClient {
         Ok(())
     }
 
+    #[allow(dead_code)]
     pub async fn restart_container(&self, name: &str) -> Result<(), LxcError> {
         let _lock = self.operation_lock.lock().await;
 
-        let client = self.api_client.lock().await;
-        client.restart_container(name).await?;
+        {
+            let client = &self.api_client;
+            client.restart_container(name).await?;
+        }
 
         // Wait for it to be running again
         self.wait_for_state(name, "Running", Duration::from_secs(60))
@@ -249,42 +511,345 @@ impl LxcClient {
         Ok(())
     }
 
+    #[allow(dead_code)]
     pub async fn delete_container(&self, name: &str) -> Result<(), LxcError> {
         let _lock = self.operation_lock.lock().await;
 
-        let client = self.api_client.lock().await;
+        let client = &self.api_client;
         client.delete_container(name).await?;
 
         Ok(())
     }
 
-    pub async fn create_container(
+    pub async fn refresh_copy(&self, source: &str, destination: &str) -> Result<(), LxcError> {
+        let _lock = self.operation_lock.lock().await;
+
+        let client = &self.api_client;
+        client.refresh_copy(source, destination).await?;
+
+        Ok(())
+    }
+
+    pub async fn list_backups(&self, name: &str) -> Result<Vec<Backup>, LxcError> {
+        let client = &self.api_client;
+        let mut backups: Vec<Backup> = client
+            .list_backups(name)
+            .await?
+            .into_iter()
+            .map(Backup::from)
+            .collect();
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(backups)
+    }
+
+    pub async fn create_backup(&self, name: &str) -> Result<(), LxcError> {
+        let _lock = self.operation_lock.lock().await;
+
+        let client = &self.api_client;
+        client.create_backup(name).await?;
+
+        Ok(())
+    }
+
+    /// Replace an instance's config with an edited one
+    pub async fn update_instance(
         &self,
         name: &str,
-        image: &str,
-        is_vm: bool,
+        config: &InstanceConfig,
     ) -> Result<(), LxcError> {
         let _lock = self.operation_lock.lock().await;
 
-        let client = self.api_client.lock().await;
-        client.create_container(name, image, is_vm).await?;
+        let client = &self.api_client;
+        Ok(client.update_instance(name, config.to_json()).await?)
+    }
 
-        // Container should be started automatically by the API
-        self.wait_for_state(name, "Running", Duration::from_secs(120))
-            .await?;
+    /// Replace the tags of an instance
+    pub async fn set_tags(&self, name: &str, tags: &[String]) -> Result<(), LxcError> {
+        let client = &self.api_client;
+        Ok(client
+            .set_instance_config(name, tags::CONFIG_KEY, &tags::join(tags))
+            .await?)
+    }
+
+    /// The boot settings of every instance on the server
+    pub async fn boot_order(&self) -> Result<Vec<BootEntry>, LxcError> {
+        let instances = self.api_client.list_containers().await?;
+        Ok(instances
+            .iter()
+            .map(|instance| {
+                BootEntry::from_config(
+                    &instance.name,
+                    instance.status == "Running",
+                    instance
+                        .expanded_config
+                        .as_ref()
+                        .unwrap_or(&instance.config),
+                )
+            })
+            .collect())
+    }
+
+    /// Space used in each storage pool on the server. A pool whose
+    /// resources can't be read is listed with a total of 0.
+    pub async fn storage_usage(&self) -> Result<Vec<PoolUsage>, LxcError> {
+        let client = &self.api_client;
+        let pools = client.get_storage_pools().await?;
+        let resources = futures::future::join_all(
+            pools
+                .iter()
+                .map(|pool| client.get_storage_pool_resources(&pool.name)),
+        )
+        .await;
+        Ok(pools
+            .into_iter()
+            .zip(resources)
+            .map(|(pool, resources)| {
+                let space = resources.map(|r| r.space).unwrap_or_default();
+                PoolUsage {
+                    name: pool.name,
+                    driver: pool.driver,
+                    used: space.used,
+                    total: space.total,
+                }
+            })
+            .collect())
+    }
+
+    /// The server's storage pools, with how full each is and the warnings
+    /// LXD raised against them
+    pub async fn storage_pools(&self) -> Result<Vec<Pool>, LxcError> {
+        let client = &self.api_client;
+        let pools = client.get_storage_pools().await?;
+        let resources = futures::future::join_all(
+            pools
+                .iter()
+                .map(|pool| client.get_storage_pool_resources(&pool.name)),
+        )
+        .await;
+        // Servers older than the warnings API have none to show
+        let warnings = client.get_warnings().await.unwrap_or_default();
+        let lxd_dir = pools::lxd_dir();
+        Ok(pools
+            .into_iter()
+            .zip(resources)
+            .map(|(pool, resources)| {
+                let space = resources.map(|r| r.space).unwrap_or_default();
+                Pool::new(pool, space, &warnings, &lxd_dir)
+            })
+            .collect())
+    }
+
+    /// Everything the inventory export records: instances, profiles,
+    /// networks, storage pools and images
+    pub async fn inventory(&self, server: &str, stable: bool) -> Result<Inventory, LxcError> {
+        let client = &self.api_client;
+        let sources = Sources {
+            instances: client.list_containers().await?,
+            profiles: client.get_profiles().await?,
+            networks: client.list_networks().await?,
+            pools: client.get_storage_pools().await?,
+            images: client.list_images().await?,
+        };
+        Ok(Inventory::new(server, &sources, stable))
+    }
+
+    /// Every instance on the server, with its expanded config
+    pub async fn instances(&self) -> Result<Vec<LxdContainer>, LxcError> {
+        Ok(self.api_client.list_containers().await?)
+    }
+
+    /// The base image of every instance on the server, against the latest
+    /// images in its store
+    pub async fn audit(&self) -> Result<Vec<AuditEntry>, LxcError> {
+        let instances = self.api_client.list_containers().await?;
+        let images = self.api_client.list_images().await?;
+        Ok(instances
+            .iter()
+            .filter_map(|instance| {
+                AuditEntry::from_config(
+                    &instance.name,
+                    instance.status == "Running",
+                    &instance.config,
+                    &images,
+                )
+            })
+            .collect())
+    }
+
+    /// The host's CPUs and memory, and what each instance has reserved
+    pub async fn capacity(&self) -> Result<(Host, Vec<Reservation>), LxcError> {
+        let resources = self.api_client.get_resources().await?;
+        let host = Host {
+            cpus: resources.cpu.total,
+            memory: resources.memory.total,
+        };
+        let instances = self.api_client.list_containers().await?;
+        Ok((
+            host,
+            instances
+                .iter()
+                .map(|instance| {
+                    Reservation::from_config(
+                        &instance.name,
+                        &instance.container_type,
+                        instance.status == "Running",
+                        instance
+                            .expanded_config
+                            .as_ref()
+                            .unwrap_or(&instance.config),
+                        host,
+                    )
+                })
+                .collect(),
+        ))
+    }
+
+    /// Whether a VM is running, and its VM-only settings against the
+    /// extensions the server advertises
+    pub async fn vm_settings(&self, name: &str) -> Result<(bool, Vec<Setting>), LxcError> {
+        let info = self.api_client.server_info().await?;
+        let instance = self.api_client.get_container(name).await?;
+        Ok((
+            instance.status == "Running",
+            vm::settings(
+                &instance.config,
+                instance
+                    .expanded_config
+                    .as_ref()
+                    .unwrap_or(&instance.config),
+                instance
+                    .expanded_devices
+                    .as_ref()
+                    .unwrap_or(&instance.devices),
+                &info.api_extensions,
+            ),
+        ))
+    }
+
+    /// Every image in the server's image store
+    pub async fn image_store(&self) -> Result<Vec<StoredImage>, LxcError> {
+        let images = self.api_client.list_images().await?;
+        Ok(images.iter().map(StoredImage::new).collect())
+    }
+
+    /// Send a change to an image's settings
+    pub async fn update_image(&self, request: ApiRequest) -> Result<(), LxcError> {
+        Ok(self.api_client.update_image(request).await?)
+    }
+
+    pub async fn refresh_image_async(&self, fingerprint: &str) -> Result<String, LxcError> {
+        let client = &self.api_client;
+        client
+            .refresh_image_async(fingerprint)
+            .await
+            .map_err(|e| LxcError::ApiError(e.to_string()))
+    }
+
+    pub async fn publish_instance_async(
+        &self,
+        name: &str,
+        publish: &Publish,
+    ) -> Result<String, LxcError> {
+        let client = &self.api_client;
+        client
+            .publish_instance_async(name, publish)
+            .await
+            .map_err(|e| LxcError::ApiError(e.to_string()))
+    }
+
+    pub async fn instance_metadata(&self, name: &str) -> Result<InstanceMetadata, LxcError> {
+        Ok(self.api_client.instance_metadata(name).await?)
+    }
+
+    pub async fn update_metadata(
+        &self,
+        name: &str,
+        metadata: &InstanceMetadata,
+    ) -> Result<(), LxcError> {
+        Ok(self.api_client.update_metadata(name, metadata).await?)
+    }
+
+    /// Image aliases on the server
+    pub async fn image_aliases(&self) -> Result<Vec<Image>, LxcError> {
+        let aliases = self.api_client.list_image_aliases().await?;
+        Ok(aliases
+            .into_iter()
+            .map(|alias| Image {
+                alias: alias.name,
+                description: alias.description,
+            })
+            .collect())
+    }
+
+    pub async fn list_templates(&self, name: &str) -> Result<Vec<String>, LxcError> {
+        Ok(self.api_client.list_templates(name).await?)
+    }
+
+    pub async fn template(&self, name: &str, template: &str) -> Result<String, LxcError> {
+        Ok(self.api_client.template(name, template).await?)
+    }
+
+    pub async fn set_template(
+        &self,
+        name: &str,
+        template: &str,
+        content: &str,
+    ) -> Result<(), LxcError> {
+        Ok(self
+            .api_client
+            .set_template(name, template, content)
+            .await?)
+    }
+
+    /// Set one config key of an instance; an empty value unsets it
+    pub async fn set_config(&self, name: &str, key: &str, value: &str) -> Result<(), LxcError> {
+        Ok(self
+            .api_client
+            .set_instance_config(name, key, value)
+            .await?)
+    }
+
+    /// Names of the server's profiles
+    pub async fn profiles(&self) -> Result<Vec<String>, LxcError> {
+        Ok(self.api_client.list_profiles().await?)
+    }
+
+    pub async fn set_profiles(&self, name: &str, profiles: &[String]) -> Result<(), LxcError> {
+        Ok(self.api_client.set_profiles(name, profiles).await?)
+    }
+
+    pub async fn create_snapshot(
+        &self,
+        name: &str,
+        snapshot: Option<&str>,
+    ) -> Result<(), LxcError> {
+        let _lock = self.operation_lock.lock().await;
+
+        let client = &self.api_client;
+        client.create_snapshot(name, snapshot).await?;
 
         Ok(())
     }
 
-    pub async fn clone_container(&self, source: &str, destination: &str) -> Result<(), LxcError> {
+    pub async fn delete_backup(&self, name: &str, backup: &str) -> Result<(), LxcError> {
         let _lock = self.operation_lock.lock().await;
 
-        let client = self.api_client.lock().await;
-        client.clone_container(source, destination).await?;
+        let client = &self.api_client;
+        client.delete_backup(name, backup).await?;
 
         Ok(())
     }
 
+    pub async fn download_backup(
+        &self,
+        name: &str,
+        backup: &str,
+        dest: &Path,
+    ) -> Result<u64, LxcError> {
+        let client = &self.api_client;
+        Ok(client.download_backup(name, backup, dest).await?)
+    }
+
     async fn wait_for_state(
         &self,
         name: &str,
@@ -302,7 +867,7 @@ impl LxcClient {
                 )));
             }
 
-            let client = self.api_client.lock().await;
+            let client = &self.api_client;
             match client.get_container_state(name).await {
                 Ok(state) => {
                     if state.status == expected_state {
@@ -324,9 +889,206 @@ impl LxcClient {
         }
     }
 
+    /// Full runtime state: processes, CPU, memory, disk and network counters
+    pub async fn get_state(&self, name: &str) -> Result<crate::lxd_api::ContainerState, LxcError> {
+        let client = &self.api_client;
+        Ok(client.get_container_state(name).await?)
+    }
+
+    /// Console log of a container; LXD keeps none for virtual machines
+    pub async fn console_log(&self, name: &str) -> Result<String, LxcError> {
+        let client = &self.api_client;
+        Ok(client.console_log(name).await?)
+    }
+
+    /// Run a command in an instance without a terminal and collect its output
+    pub async fn exec_output(
+        &self,
+        name: &str,
+        command: &[String],
+    ) -> Result<ExecOutput, LxcError> {
+        let client = &self.api_client;
+        Ok(client.exec_output(name, command).await?)
+    }
+
+    /// Metrics of one instance from the server's metrics endpoint; None
+    /// when it reports none, as for a stopped instance
+    pub async fn instance_metrics(&self, name: &str) -> Result<Option<InstanceMetrics>, LxcError> {
+        let client = &self.api_client;
+        let text = client.metrics().await?;
+        Ok(InstanceMetrics::of(&metrics::parse(&text)?, name))
+    }
+
+    /// What each instance on the server has used, from its metrics endpoint
+    pub async fn usage_readings(&self) -> Result<BTreeMap<String, Reading>, LxcError> {
+        let client = &self.api_client;
+        let text = client.metrics().await?;
+        Ok(usage::readings(&metrics::parse(&text)?))
+    }
+
+    /// Subscribe to the server's lifecycle events
+    pub async fn lifecycle_events(&self) -> Result<crate::lxd_api::EventStream, LxcError> {
+        let client = &self.api_client;
+        Ok(client.events("lifecycle").await?)
+    }
+
+    /// Runtime state of every instance, by name
+    pub async fn instance_states(
+        &self,
+    ) -> Result<Vec<(String, crate::lxd_api::ContainerState)>, LxcError> {
+        let client = &self.api_client;
+        let instances = client.list_instance_states().await?;
+        Ok(instances
+            .into_iter()
+            .filter_map(|instance| Some((instance.name, instance.state?)))
+            .collect())
+    }
+
+    /// The pool a new instance from `image` lands in, `pool` or else the
+    /// default profile's, with the size of the image when it is in the
+    /// server's image store. None when there is no root disk to go by.
+    pub async fn project_create(
+        &self,
+        image: &str,
+        pool: Option<&str>,
+    ) -> Result<Option<Projection>, LxcError> {
+        let client = &self.api_client;
+        let pool = match pool {
+            Some(pool) => Some(pool.to_string()),
+            None => space::root_pool(&client.get_profile("default").await?.devices),
+        };
+        let Some(pool) = pool else {
+            return Ok(None);
+        };
+        let adding = match tokio::try_join!(client.list_image_aliases(), client.list_images()) {
+            Ok((aliases, images)) => {
+                let fingerprint = aliases
+                    .iter()
+                    .find(|alias| alias.name == image)
+                    .map_or(image, |alias| alias.target.as_str());
+                images
+                    .iter()
+                    .find(|i| i.fingerprint == fingerprint)
+                    .map_or(0, |i| i.size)
+            }
+            Err(_) => 0,
+        };
+        self.projection(&pool, adding).await
+    }
+
+    /// The pool a copy of `source` lands in, `pool` or else that of the
+    /// source's root disk, with the space the source's root disk takes
+    pub async fn project_clone(
+        &self,
+        source: &str,
+        pool: Option<&str>,
+    ) -> Result<Option<Projection>, LxcError> {
+        let client = &self.api_client;
+        let (instance, state) = tokio::try_join!(
+            client.get_container(source),
+            client.get_container_state(source)
+        )?;
+        let pool = match pool {
+            Some(pool) => Some(pool.to_string()),
+            None => match instance
+                .expanded_devices
+                .as_ref()
+                .and_then(space::root_pool)
+            {
+                Some(pool) => Some(pool),
+                None => space::root_pool(&client.get_profile("default").await?.devices),
+            },
+        };
+        let Some(pool) = pool else {
+            return Ok(None);
+        };
+        let adding = state
+            .disk
+            .as_ref()
+            .and_then(|disk| disk.get("root"))
+            .map_or(0, |root| root.usage.max(0) as u64);
+        self.projection(&pool, adding).await
+    }
+
+    async fn projection(&self, pool: &str, adding: u64) -> Result<Option<Projection>, LxcError> {
+        Ok(self
+            .storage_usage()
+            .await?
+            .into_iter()
+            .find(|usage| usage.name == pool)
+            .map(|pool| Projection { pool, adding }))
+    }
+
+    /// What deleting an instance would fail on or lose
+    pub async fn delete_checks(&self, name: &str) -> Result<Vec<Check>, LxcError> {
+        let client = &self.api_client;
+        let (instance, snapshots) =
+            tokio::try_join!(client.get_container(name), client.list_snapshots(name))?;
+        Ok(preflight::delete_checks(&instance, &snapshots))
+    }
+
+    /// The server's network zones, each with its records
+    pub async fn network_zones(&self) -> Result<Vec<Zone>, LxcError> {
+        let client = &self.api_client;
+        let zones = client.list_network_zones().await?;
+        let records = futures::future::try_join_all(
+            zones
+                .iter()
+                .map(|zone| client.list_zone_records(&zone.name)),
+        )
+        .await?;
+        Ok(zones
+            .into_iter()
+            .zip(records)
+            .map(|(zone, records)| Zone::new(zone, records))
+            .collect())
+    }
+
+    /// Create or delete a zone, or one of its records
+    pub async fn change_network_zone(&self, request: ApiRequest) -> Result<(), LxcError> {
+        Ok(self.api_client.change_network_zone(request).await?)
+    }
+
+    /// Names an instance resolves as on the managed networks its NICs are on
+    pub async fn dns_names(&self, name: &str) -> Result<Vec<String>, LxcError> {
+        let client = &self.api_client;
+        let instance = client.get_container(name).await?;
+        let mut devices: Vec<_> = instance
+            .expanded_devices
+            .as_ref()
+            .unwrap_or(&instance.devices)
+            .iter()
+            .collect();
+        devices.sort_by(|a, b| a.0.cmp(b.0));
+        let mut networks: Vec<&str> = Vec::new();
+        for (_, device) in devices {
+            if device.get("type").map(String::as_str) != Some("nic") {
+                continue;
+            }
+            let network = device.get("network").or_else(|| device.get("parent"));
+            if let Some(network) = network.filter(|n| !networks.contains(&n.as_str())) {
+                networks.push(network);
+            }
+        }
+        // An unmanaged parent interface isn't an LXD network
+        let networks: Vec<_> =
+            futures::future::join_all(networks.iter().map(|network| client.get_network(network)))
+                .await
+                .into_iter()
+                .filter_map(Result::ok)
+                .collect();
+        Ok(zones::dns_names(&instance.name, &networks))
+    }
+
+    /// Configuration, devices and profiles of an instance
+    pub async fn get_instance(&self, name: &str) -> Result<LxdContainer, LxcError> {
+        let client = &self.api_client;
+        Ok(client.get_container(name).await?)
+    }
+
     #[allow(dead_code)]
     pub async fn get_container_info(&self, name: &str) -> Result<String, LxcError> {
-        let client = self.api_client.lock().await;
+        let client = &self.api_client;
         let container = client.get_container(name).await?;
         Ok(serde_json::to_string_pretty(&container)?)
     }
@@ -347,7 +1109,7 @@ impl LxcClient {
 
     // Non-blocking operation methods
     pub async fn start_container_async(&self, name: &str) -> Result<String, LxcError> {
-        let client = self.api_client.lock().await;
+        let client = &self.api_client;
         client
             .start_container_async(name)
             .await
@@ -355,7 +1117,7 @@ impl LxcClient {
     }
 
     pub async fn stop_container_async(&self, name: &str) -> Result<String, LxcError> {
-        let client = self.api_client.lock().await;
+        let client = &self.api_client;
         client
             .stop_container_async(name)
             .await
@@ -363,7 +1125,7 @@ impl LxcClient {
     }
 
     pub async fn restart_container_async(&self, name: &str) -> Result<String, LxcError> {
-        let client = self.api_client.lock().await;
+        let client = &self.api_client;
         client
             .restart_container_async(name)
             .await
@@ -371,15 +1133,86 @@ impl LxcClient {
     }
 
     pub async fn delete_container_async(&self, name: &str) -> Result<String, LxcError> {
-        let client = self.api_client.lock().await;
+        let client = &self.api_client;
         client
             .delete_container_async(name)
             .await
             .map_err(|e| LxcError::ApiError(e.to_string()))
     }
 
+    pub async fn create_container_async(
+        &self,
+        name: &str,
+        image: &str,
+        is_vm: bool,
+        media: Option<&Media>,
+        defaults: &CreateDefaults,
+    ) -> Result<String, LxcError> {
+        let client = &self.api_client;
+        client
+            .create_container_async(name, image, is_vm, media, defaults)
+            .await
+            .map_err(|e| LxcError::ApiError(e.to_string()))
+    }
+
+    pub async fn rebuild_instance_async(
+        &self,
+        name: &str,
+        fingerprint: &str,
+    ) -> Result<String, LxcError> {
+        let client = &self.api_client;
+        client
+            .rebuild_instance_async(name, fingerprint)
+            .await
+            .map_err(|e| LxcError::ApiError(e.to_string()))
+    }
+
+    pub async fn recreate_instance_async(
+        &self,
+        conversion: &Conversion,
+    ) -> Result<String, LxcError> {
+        let client = &self.api_client;
+        client
+            .recreate_instance_async(conversion)
+            .await
+            .map_err(|e| LxcError::ApiError(e.to_string()))
+    }
+
+    pub async fn clone_container_async(
+        &self,
+        source: &str,
+        destination: &str,
+        options: &CloneOptions,
+    ) -> Result<String, LxcError> {
+        let client = &self.api_client;
+        client
+            .clone_container_async(source, destination, options)
+            .await
+            .map_err(|e| LxcError::ApiError(e.to_string()))
+    }
+
+    /// Storage pools and projects a copy can be placed in; empty when the
+    /// server won't list them
+    pub async fn clone_targets(&self) -> (Vec<String>, Vec<String>) {
+        let client = &self.api_client;
+        let (pools, projects) = tokio::join!(client.list_storage_pools(), client.list_projects());
+        (pools.unwrap_or_default(), projects.unwrap_or_default())
+    }
+
+    pub async fn create_backup_async(&self, name: &str) -> Result<String, LxcError> {
+        let client = &self.api_client;
+        client
+            .create_backup_async(name)
+            .await
+            .map_err(|e| LxcError::ApiError(e.to_string()))
+    }
+
+    pub async fn cancel_lxd_operation(&self, operation_path: &str) -> Result<(), LxcError> {
+        Ok(self.api_client.cancel_operation(operation_path).await?)
+    }
+
     pub async fn get_lxd_operation(&self, operation_path: &str) -> Result<LxdOperation, LxcError> {
-        let client = self.api_client.lock().await;
+        let client = &self.api_client;
         client
             .get_operation(operation_path)
             .await
@@ -1,35 +1,76 @@
 //! LXD REST API client
 //!
 //! Low-level API client for communicating with the LXD daemon
-//! over the Unix socket using the REST API.
-
+//! over the Unix socket or an HTTPS remote using the REST API.
+
+use crate::auth::{self, AuthError, ClientCertificate, OidcProvider, OidcTokens};
+use crate::clone::CloneOptions;
+use crate::config::{AuthType, CreateDefaults, RemoteConfig};
+use crate::convert::Conversion;
+use crate::images;
+use crate::media::{self, Media};
+use crate::publish::Publish;
+use crate::ssh::{self, SshError, SshTunnel};
+use crate::templates::InstanceMetadata;
 use anyhow::Result;
-use hyper::{Body, Client, Method, Request};
-use hyperlocal::{UnixClientExt, UnixConnector, Uri};
+use futures::stream::{BoxStream, StreamExt};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::{Method, Request};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use hyperlocal::{UnixConnector, Uri};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::path::Path;
 use std::time::Duration;
 use thiserror::Error;
-use tokio::time::{sleep, timeout};
+use tokio::io::AsyncWriteExt;
+use tokio::net::UnixStream;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest, Message};
+use url::Url;
 
 #[derive(Debug, Error)]
 pub enum LxdApiError {
     #[error("HTTP error: {0}")]
     HttpError(#[from] hyper::Error),
+    #[error("HTTP error: {0}")]
+    ClientError(#[from] hyper_util::client::legacy::Error),
     #[error("HTTP builder error: {0}")]
     HttpBuilderError(#[from] hyper::http::Error),
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
     #[error("API error: {0}")]
     ApiError(String),
+    #[error("Not supported by this server: {0}")]
+    Unsupported(String),
     #[error("Operation failed: {0}")]
     OperationFailed(String),
     #[error("Timeout: {0}")]
     Timeout(String),
     #[error("Socket not found: {0}")]
     SocketNotFound(String),
+    #[error("HTTPS error: {0}")]
+    HttpsError(#[from] reqwest::Error),
+    #[error("SSH error: {0}")]
+    SshError(#[from] SshError),
+    #[error("Authentication error: {0}")]
+    AuthError(#[from] AuthError),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("TLS error: {0}")]
+    TlsError(#[from] native_tls::Error),
+    #[error("WebSocket error: {0}")]
+    WebSocketError(#[from] Box<tungstenite::Error>),
+}
+
+impl From<tungstenite::Error> for LxdApiError {
+    fn from(e: tungstenite::Error) -> Self {
+        LxdApiError::WebSocketError(Box::new(e))
+    }
 }
 
 // API Response structures
@@ -88,6 +129,573 @@ pub struct LxdContainer {
     #[serde(rename = "type")]
     pub container_type: String,
     pub state: Option<ContainerState>,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A storage pool, as listed with recursion
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LxdStoragePool {
+    pub name: String,
+    #[serde(default)]
+    pub driver: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub config: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String, // Created, Pending, Unavailable or Errored
+}
+
+/// Something LXD noticed going wrong, from `/1.0/warnings`
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LxdWarning {
+    #[serde(default)]
+    pub status: String, // new, acknowledged or resolved
+    #[serde(default)]
+    pub severity: String,
+    #[serde(default)]
+    pub count: u64,
+    #[serde(default)]
+    pub last_seen_at: String,
+    #[serde(default)]
+    pub last_message: String,
+    #[serde(default)]
+    pub entity_url: String, // What it is about, like /1.0/storage-pools/tank
+}
+
+/// Space in a storage pool, in bytes, from its resources
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LxdStorageSpace {
+    #[serde(default)]
+    pub used: u64,
+    #[serde(default)]
+    pub total: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LxdProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub config: HashMap<String, String>,
+    #[serde(default)]
+    pub devices: HashMap<String, HashMap<String, String>>,
+}
+
+/// A network: one LXD manages, or one of the host's interfaces
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LxdNetwork {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub network_type: String,
+    #[serde(default)]
+    pub managed: bool,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub config: HashMap<String, String>,
+}
+
+/// A network forward: traffic to one of a network's addresses sent on to
+/// instances
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LxdNetworkForward {
+    pub listen_address: String,
+    #[serde(default)]
+    pub config: HashMap<String, String>, // target_address takes the unlisted ports
+    #[serde(default)]
+    pub ports: Vec<LxdForwardPort>,
+}
+
+/// Ports of a forward sent to one address
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LxdForwardPort {
+    pub protocol: String,
+    pub listen_port: String, // "80", "80,443" or "8000-8010"
+    pub target_address: String,
+    #[serde(default)]
+    pub target_port: String, // Empty to keep the listen port
+}
+
+/// A DNS zone LXD serves for its networks
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LxdNetworkZone {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub used_by: Vec<String>, // Networks publishing into the zone
+}
+
+/// Records added to a zone by hand, next to the ones LXD generates
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct LxdZoneRecord {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub entries: Vec<LxdZoneEntry>,
+    #[serde(default)]
+    pub config: HashMap<String, String>, // Kept as is through edits
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct LxdZoneEntry {
+    #[serde(rename = "type")]
+    pub record_type: String, // A, AAAA, CNAME, TXT, ...
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LxdStoragePoolResources {
+    #[serde(default)]
+    pub space: LxdStorageSpace,
+}
+
+/// The host's hardware; only its CPU threads and memory are read
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LxdResources {
+    #[serde(default)]
+    pub cpu: LxdTotal,
+    #[serde(default)]
+    pub memory: LxdTotal,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LxdTotal {
+    #[serde(default)]
+    pub total: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LxdBackup {
+    pub name: String,
+    pub created_at: String,
+    pub expires_at: String,
+    #[serde(default)]
+    pub instance_only: bool,
+    #[serde(default)]
+    pub optimized_storage: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LxdImageAlias {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub target: String, // Fingerprint of the image
+}
+
+/// An image in the server's image store
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LxdImage {
+    pub fingerprint: String,
+    #[serde(default)]
+    pub properties: HashMap<String, String>, // os, release, architecture, variant, serial...
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub size: u64, // Bytes
+    #[serde(default)]
+    pub aliases: Vec<LxdImageAlias>,
+    #[serde(default)]
+    pub auto_update: bool,
+    #[serde(default)]
+    pub cached: bool, // Copied in to create an instance rather than by hand
+    #[serde(default)]
+    pub update_source: Option<LxdImageSource>, // Set on images copied from a remote
+    #[serde(default)]
+    pub expires_at: String, // Year 1 when it never expires
+}
+
+/// Where an image copied from a remote came from, and is refreshed from
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LxdImageSource {
+    pub alias: String,
+    pub server: String,
+    #[serde(default)]
+    pub protocol: String,
+}
+
+/// What a command run with `exec_output` returned
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecOutput {
+    pub status: i32, // Exit status
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// A mutating API call, built separately so it can be previewed before sending
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    pub method: Method,
+    pub path: String,
+    pub body: Option<serde_json::Value>,
+}
+
+impl ApiRequest {
+    /// Change an instance's power state ("start", "stop", "restart")
+    pub fn instance_state(name: &str, action: &str) -> Self {
+        Self {
+            method: Method::PUT,
+            path: format!("/1.0/instances/{}/state", name),
+            body: Some(json!({
+                "action": action,
+                "timeout": 30
+            })),
+        }
+    }
+
+    pub fn delete_instance(name: &str) -> Self {
+        Self {
+            method: Method::DELETE,
+            path: format!("/1.0/instances/{}", name),
+            body: None,
+        }
+    }
+
+    /// Create `name` from `image` shaped by the configured creation
+    /// defaults: their limits, profiles, root pool and network. A VM can
+    /// boot from installation `media` first.
+    pub fn create_instance(
+        name: &str,
+        image: &str,
+        is_vm: bool,
+        media: Option<&Media>,
+        defaults: &CreateDefaults,
+    ) -> Self {
+        let container_type = if is_vm {
+            "virtual-machine"
+        } else {
+            "container"
+        };
+        let limits: serde_json::Map<String, Value> = defaults
+            .limits
+            .iter()
+            .map(|(key, value)| (format!("limits.{}", key), json!(value)))
+            .collect();
+
+        let mut body = json!({
+            "name": name,
+            "source": images::image_source(image),
+            "type": container_type,
+            "config": limits
+        });
+        if !defaults.profiles.is_empty() {
+            body["profiles"] = json!(defaults.profiles);
+        }
+        // Devices named as the default profile's override them
+        let mut devices = serde_json::Map::new();
+        if let Some(pool) = &defaults.pool {
+            devices.insert(
+                "root".to_string(),
+                json!({"type": "disk", "path": "/", "pool": pool}),
+            );
+        }
+        if let Some(network) = &defaults.network {
+            devices.insert(
+                "eth0".to_string(),
+                json!({"type": "nic", "name": "eth0", "network": network}),
+            );
+        }
+        if let Some(media) = media.filter(|_| is_vm) {
+            devices.insert(media::DEVICE.to_string(), media.device());
+        }
+        if !devices.is_empty() {
+            body["devices"] = Value::Object(devices);
+        }
+
+        Self {
+            method: Method::POST,
+            path: "/1.0/instances".to_string(),
+            body: Some(body),
+        }
+    }
+
+    /// Copy `source` to `destination`, or re-sync an existing copy when `refresh` is set
+    pub fn copy_instance(source: &str, destination: &str, refresh: bool) -> Self {
+        Self {
+            method: Method::POST,
+            path: "/1.0/instances".to_string(),
+            body: Some(json!({
+                "name": destination,
+                "source": {
+                    "type": "copy",
+                    "source": format!("/1.0/instances/{}", source),
+                    "refresh": refresh
+                }
+            })),
+        }
+    }
+
+    /// Copy `source` to a new instance `destination` as `options` describe
+    pub fn clone_instance(source: &str, destination: &str, options: &CloneOptions) -> Self {
+        let mut body = json!({
+            "name": destination,
+            "ephemeral": options.ephemeral,
+            "source": {
+                "type": "copy",
+                "source": format!("/1.0/instances/{}", source),
+                "instance_only": options.instance_only
+            }
+        });
+        if let Some(pool) = &options.pool {
+            body["devices"] = json!({
+                "root": { "type": "disk", "path": "/", "pool": pool }
+            });
+        }
+        let path = match &options.project {
+            // LXTUI works in the default project, where the source is
+            Some(project) => {
+                body["source"]["project"] = json!("default");
+                format!("/1.0/instances?project={}", project)
+            }
+            None => "/1.0/instances".to_string(),
+        };
+
+        Self {
+            method: Method::POST,
+            path,
+            body: Some(body),
+        }
+    }
+
+    /// Create the other type of instance `conversion` plans, from its
+    /// image with the config and devices that carry over
+    pub fn recreate_instance(conversion: &Conversion) -> Self {
+        let container_type = if conversion.to_vm {
+            "virtual-machine"
+        } else {
+            "container"
+        };
+        Self {
+            method: Method::POST,
+            path: "/1.0/instances".to_string(),
+            body: Some(json!({
+                "name": conversion.name,
+                "source": images::image_source(&conversion.image),
+                "type": container_type,
+                "profiles": conversion.profiles,
+                "config": conversion.config,
+                "devices": conversion.devices
+            })),
+        }
+    }
+
+    pub fn create_backup(instance: &str) -> Self {
+        Self {
+            method: Method::POST,
+            path: format!("/1.0/instances/{}/backups", instance),
+            body: Some(json!({
+                "instance_only": false,
+                "optimized_storage": false
+            })),
+        }
+    }
+
+    pub fn delete_backup(instance: &str, backup: &str) -> Self {
+        Self {
+            method: Method::DELETE,
+            path: format!("/1.0/instances/{}/backups/{}", instance, backup),
+            body: None,
+        }
+    }
+
+    /// Replace the architecture, config, devices, profiles and flags of an instance
+    pub fn update_instance(instance: &str, config: serde_json::Value) -> Self {
+        Self {
+            method: Method::PUT,
+            path: format!("/1.0/instances/{}", instance),
+            body: Some(config),
+        }
+    }
+
+    /// Set one config key of an instance, leaving the others alone; an
+    /// empty value removes the key
+    pub fn set_instance_config(instance: &str, key: &str, value: &str) -> Self {
+        Self {
+            method: Method::PATCH,
+            path: format!("/1.0/instances/{}", instance),
+            body: Some(json!({
+                "config": { key: value }
+            })),
+        }
+    }
+
+    /// Replace an instance's profile list, leaving its own config and
+    /// devices alone
+    pub fn set_profiles(instance: &str, profiles: &[String]) -> Self {
+        Self {
+            method: Method::PATCH,
+            path: format!("/1.0/instances/{}", instance),
+            body: Some(json!({ "profiles": profiles })),
+        }
+    }
+
+    /// Without a name, LXD names the snapshot by the instance's `snapshots.pattern`
+    pub fn create_snapshot(instance: &str, name: Option<&str>) -> Self {
+        let mut body = json!({ "stateful": false });
+        if let Some(name) = name {
+            body["name"] = json!(name);
+        }
+        Self {
+            method: Method::POST,
+            path: format!("/1.0/instances/{}/snapshots", instance),
+            body: Some(body),
+        }
+    }
+
+    /// Replace the root disk of a stopped instance with a fresh copy of
+    /// the image `fingerprint`, keeping its config and devices
+    pub fn rebuild_instance(instance: &str, fingerprint: &str) -> Self {
+        Self {
+            method: Method::POST,
+            path: format!("/1.0/instances/{}/rebuild", instance),
+            body: Some(json!({
+                "source": {
+                    "type": "image",
+                    "fingerprint": fingerprint
+                }
+            })),
+        }
+    }
+
+    /// Have LXD keep an image copied from a remote up to date, or not
+    pub fn set_image_auto_update(fingerprint: &str, auto_update: bool) -> Self {
+        Self {
+            method: Method::PATCH,
+            path: format!("/1.0/images/{}", fingerprint),
+            body: Some(json!({ "auto_update": auto_update })),
+        }
+    }
+
+    /// Check the image's source for a newer build now, replacing the image
+    /// with it when there is one
+    pub fn refresh_image(fingerprint: &str) -> Self {
+        Self {
+            method: Method::POST,
+            path: format!("/1.0/images/{}/refresh", fingerprint),
+            body: None,
+        }
+    }
+
+    /// Make an image of a stopped instance, with an alias, properties and
+    /// expiry as `publish` sets them
+    pub fn publish_instance(instance: &str, publish: &Publish) -> Self {
+        let mut body = json!({
+            "source": {
+                "type": "instance",
+                "name": instance
+            },
+            "public": publish.public,
+            "properties": publish.properties
+        });
+        if let Some(alias) = &publish.alias {
+            body["aliases"] = json!([{ "name": alias }]);
+        }
+        if let Some(expires_at) = &publish.expires_at {
+            body["expires_at"] = json!(expires_at.to_rfc3339());
+        }
+        Self {
+            method: Method::POST,
+            path: "/1.0/images".to_string(),
+            body: Some(body),
+        }
+    }
+
+    /// Run `command` in an instance without a terminal, keeping its output
+    /// in log files on the server
+    pub fn exec(instance: &str, command: &[String]) -> Self {
+        Self {
+            method: Method::POST,
+            path: format!("/1.0/instances/{}/exec", instance),
+            body: Some(json!({
+                "command": command,
+                "environment": {},
+                "interactive": false,
+                "wait-for-websocket": false,
+                "record-output": true
+            })),
+        }
+    }
+
+    /// Replace the image metadata an instance would be published with
+    pub fn update_metadata(instance: &str, metadata: serde_json::Value) -> Self {
+        Self {
+            method: Method::PUT,
+            path: format!("/1.0/instances/{}/metadata", instance),
+            body: Some(metadata),
+        }
+    }
+
+    /// Create or replace a template file. The content is sent as the raw
+    /// request body; it is shown here as a JSON string.
+    pub fn set_template(instance: &str, template: &str, content: &str) -> Self {
+        Self {
+            method: Method::POST,
+            path: template_path(instance, template),
+            body: Some(json!(content)),
+        }
+    }
+
+    pub fn create_network_zone(zone: &str) -> Self {
+        Self {
+            method: Method::POST,
+            path: "/1.0/network-zones".to_string(),
+            body: Some(json!({ "name": zone })),
+        }
+    }
+
+    pub fn delete_network_zone(zone: &str) -> Self {
+        Self {
+            method: Method::DELETE,
+            path: format!("/1.0/network-zones/{}", zone),
+            body: None,
+        }
+    }
+
+    pub fn create_zone_record(zone: &str, record: &LxdZoneRecord) -> Self {
+        Self {
+            method: Method::POST,
+            path: format!("/1.0/network-zones/{}/records", zone),
+            body: Some(json!(record)),
+        }
+    }
+
+    /// Replace an existing record, which LXD does wholesale, so its config
+    /// goes back as read
+    pub fn update_zone_record(zone: &str, record: &LxdZoneRecord) -> Self {
+        Self {
+            method: Method::PUT,
+            path: format!("/1.0/network-zones/{}/records/{}", zone, record.name),
+            body: Some(json!({
+                "description": record.description,
+                "entries": record.entries,
+                "config": record.config
+            })),
+        }
+    }
+
+    pub fn delete_zone_record(zone: &str, record: &str) -> Self {
+        Self {
+            method: Method::DELETE,
+            path: format!("/1.0/network-zones/{}/records/{}", zone, record),
+            body: None,
+        }
+    }
+}
+
+fn template_path(instance: &str, template: &str) -> String {
+    let template: String = url::form_urlencoded::byte_serialize(template.as_bytes()).collect();
+    format!(
+        "/1.0/instances/{}/metadata/templates?path={}",
+        instance, template
+    )
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -99,6 +707,8 @@ pub struct ContainerState {
     pub processes: i64,
     pub cpu: Option<CpuUsage>,
     pub memory: Option<MemoryUsage>,
+    #[serde(default)]
+    pub disk: Option<HashMap<String, DiskUsage>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -133,72 +743,506 @@ pub struct MemoryUsage {
     pub swap_usage_peak: i64,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DiskUsage {
+    pub usage: i64,
+}
+
+/// Subset of `GET /1.0` used to check how the server sees us
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ServerInfo {
+    pub auth: String,
+    #[serde(default)]
+    pub auth_methods: Vec<String>,
+    #[serde(default)]
+    pub api_version: String,
+    #[serde(default)]
+    pub api_extensions: Vec<String>,
+    #[serde(default)]
+    pub environment: ServerEnvironment,
+}
+
+/// Subset of the `environment` section of `GET /1.0`
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ServerEnvironment {
+    #[serde(default)]
+    pub server: String, // "lxd" or "incus"
+    #[serde(default)]
+    pub server_version: String,
+    #[serde(default)]
+    pub storage: String, // Drivers in use, e.g. "zfs | dir"
+    #[serde(default)]
+    pub storage_version: String,
+}
+
+/// A message from `GET /1.0/events`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LxdEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub timestamp: String,
+    pub metadata: serde_json::Value,
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+/// Stream of events from the events websocket
+pub type EventStream = BoxStream<'static, Result<LxdEvent, LxdApiError>>;
+
+/// A request that failed, kept so the error modal can include it in a report
+#[derive(Debug, Clone)]
+pub struct FailedRequest {
+    pub remote: Option<String>,
+    pub method: Method,
+    pub path: String,
+}
+
+impl FailedRequest {
+    /// Instance named in the request path, if any
+    pub fn instance(&self) -> Option<&str> {
+        let rest = self.path.strip_prefix("/1.0/instances/")?;
+        rest.split(['/', '?'])
+            .next()
+            .filter(|name| !name.is_empty())
+    }
+}
+
+static LAST_FAILED_REQUEST: std::sync::Mutex<Option<FailedRequest>> = std::sync::Mutex::new(None);
+
+/// The most recent failed request from any client, clearing it
+pub fn take_failed_request() -> Option<FailedRequest> {
+    LAST_FAILED_REQUEST.lock().ok()?.take()
+}
+
+impl ServerInfo {
+    pub fn is_trusted(&self) -> bool {
+        self.auth == "trusted"
+    }
+}
+
+/// How long an idle connection is kept open for the next request
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+enum Transport {
+    Unix {
+        client: Client<UnixConnector, Full<Bytes>>,
+        socket_path: String,
+    },
+    Https {
+        client: reqwest::Client,
+        tls: native_tls::TlsConnector, // Same trust and identity, for websockets
+        base_url: Url,
+        oidc: Option<Mutex<OidcTokens>>,
+    },
+}
+
 pub struct LxdApiClient {
-    client: Client<UnixConnector>,
-    socket_path: String,
+    transport: Transport,
+    remote_name: Option<String>,
+    #[allow(dead_code)]
+    tunnel: Option<SshTunnel>, // Held to keep an SSH remote's forwarded socket open
+}
+
+/// Standard locations of the local LXD socket
+const SOCKET_PATHS: &[&str] = &[
+    "/var/lib/lxd/unix.socket",
+    "/var/snap/lxd/common/lxd/unix.socket",
+];
+
+/// Socket used for the local server instead of the standard locations, set
+/// when a privileged helper proxies the real socket
+static LOCAL_SOCKET_OVERRIDE: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+pub fn set_local_socket(path: String) {
+    let _ = LOCAL_SOCKET_OVERRIDE.set(path);
+}
+
+/// Path of the local LXD socket
+pub fn find_socket() -> Result<String, LxdApiError> {
+    if let Some(path) = LOCAL_SOCKET_OVERRIDE.get() {
+        return Ok(path.clone());
+    }
+
+    SOCKET_PATHS
+        .iter()
+        .find(|path| Path::new(path).exists())
+        .map(|path| path.to_string())
+        .ok_or_else(|| {
+            LxdApiError::SocketNotFound("LXD socket not found at standard locations".to_string())
+        })
+}
+
+/// The local socket, if it exists but this user isn't allowed to connect
+pub fn socket_permission_denied() -> Option<String> {
+    let path = find_socket().ok()?;
+    match std::os::unix::net::UnixStream::connect(&path) {
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Some(path),
+        _ => None,
+    }
+}
+
+/// Decode the text messages of an events websocket; pings are answered by
+/// the websocket itself and other frames carry no events
+fn event_stream<S>(socket: tokio_tungstenite::WebSocketStream<S>) -> EventStream
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    socket
+        .filter_map(|message| async move {
+            match message {
+                Ok(Message::Text(text)) => {
+                    Some(serde_json::from_str(&text).map_err(LxdApiError::from))
+                }
+                Ok(_) => None,
+                Err(e) => Some(Err(e.into())),
+            }
+        })
+        .boxed()
 }
 
 impl LxdApiClient {
     pub fn new() -> Result<Self, LxdApiError> {
-        // Try standard locations for LXD socket
-        let socket_paths = vec![
-            "/var/lib/lxd/unix.socket",
-            "/var/snap/lxd/common/lxd/unix.socket",
-        ];
-
-        let socket_path = socket_paths
-            .into_iter()
-            .find(|path| Path::new(path).exists())
-            .ok_or_else(|| {
-                LxdApiError::SocketNotFound(
-                    "LXD socket not found at standard locations".to_string(),
-                )
-            })?;
+        Ok(Self::unix(find_socket()?))
+    }
+
+    /// Client for the LXD API behind a Unix socket. Connections are kept
+    /// open and reused by later requests.
+    pub fn unix(socket_path: String) -> Self {
+        let client = Client::builder(TokioExecutor::new())
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .build(UnixConnector);
+        Self {
+            transport: Transport::Unix {
+                client,
+                socket_path,
+            },
+            remote_name: None,
+            tunnel: None,
+        }
+    }
+
+    /// Connect to a configured HTTPS remote using its stored credentials
+    pub fn for_remote(remote: &RemoteConfig) -> Result<Self, LxdApiError> {
+        if remote.auth_type == AuthType::Ssh {
+            return Self::ssh(remote);
+        }
+
+        let server_cert = auth::load_server_cert(&remote.name)?;
+        let client_cert = auth::load_or_generate_client_cert()?;
+        let oidc = match remote.auth_type {
+            AuthType::Oidc => Some(auth::load_oidc_tokens(&remote.name)?),
+            AuthType::Tls | AuthType::Ssh => None,
+        };
+
+        Self::https(remote, &server_cert, &client_cert, oidc)
+    }
 
-        let client = Client::unix();
+    /// Reach a remote's Unix socket through an SSH tunnel. Blocks until the
+    /// tunnel is up.
+    pub fn ssh(remote: &RemoteConfig) -> Result<Self, LxdApiError> {
+        let remote_socket = remote
+            .socket_path
+            .as_deref()
+            .unwrap_or(ssh::DEFAULT_REMOTE_SOCKET);
+        let tunnel = SshTunnel::open(&remote.url, remote_socket)?;
+
+        let mut client = Self::unix(tunnel.local_socket().display().to_string());
+        client.remote_name = Some(remote.name.clone());
+        client.tunnel = Some(tunnel);
+        Ok(client)
+    }
+
+    /// Build an HTTPS client pinned to `server_cert`
+    pub fn https(
+        remote: &RemoteConfig,
+        server_cert: &[u8],
+        client_cert: &ClientCertificate,
+        oidc: Option<OidcTokens>,
+    ) -> Result<Self, LxdApiError> {
+        let base_url = auth::normalize_remote_url(&remote.url).map_err(LxdApiError::AuthError)?;
+        let identity =
+            reqwest::Identity::from_pkcs8_pem(&client_cert.cert_pem, &client_cert.key_pem)?;
+
+        // LXD servers use self-signed certificates, so trust exactly the one
+        // we pinned when the remote was added
+        let client = reqwest::Client::builder()
+            .identity(identity)
+            .add_root_certificate(reqwest::Certificate::from_pem(server_cert)?)
+            .tls_built_in_root_certs(false)
+            .danger_accept_invalid_hostnames(true)
+            .timeout(Duration::from_secs(30))
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .build()?;
+        let tls = native_tls::TlsConnector::builder()
+            .identity(native_tls::Identity::from_pkcs8(
+                &client_cert.cert_pem,
+                &client_cert.key_pem,
+            )?)
+            .add_root_certificate(native_tls::Certificate::from_pem(server_cert)?)
+            .disable_built_in_roots(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()?;
 
         Ok(Self {
-            client,
-            socket_path: socket_path.to_string(),
+            transport: Transport::Https {
+                client,
+                tls,
+                base_url,
+                oidc: oidc.map(Mutex::new),
+            },
+            remote_name: Some(remote.name.clone()),
+            tunnel: None,
         })
     }
 
-    async fn request<T, B>(
+    /// Current OIDC access token, refreshed and persisted when expired
+    async fn bearer_token(&self, oidc: &Mutex<OidcTokens>) -> Result<String, LxdApiError> {
+        let mut tokens = oidc.lock().await;
+        if tokens.is_expired() {
+            let refreshed = auth::refresh_tokens(&reqwest::Client::new(), &tokens).await?;
+            if let Some(remote) = &self.remote_name {
+                auth::save_oidc_tokens(remote, &refreshed)?;
+            }
+            *tokens = refreshed;
+        }
+        Ok(tokens.access_token.clone())
+    }
+
+    /// Send a request over whichever transport this client uses and return the body
+    async fn send<B>(
         &self,
         method: Method,
         path: &str,
         body: Option<B>,
-    ) -> Result<T, LxdApiError>
+    ) -> Result<String, LxdApiError>
     where
-        T: for<'de> Deserialize<'de>,
         B: Serialize,
     {
-        let uri: hyper::Uri = Uri::new(&self.socket_path, path).into();
+        let body = match body {
+            Some(body) => Some((serde_json::to_vec(&body)?, "application/json")),
+            None => None,
+        };
+        let result = self.send_over_transport(method.clone(), path, body).await;
+        self.note_failure(&method, path, result)
+    }
 
-        let mut request = Request::builder().method(method).uri(uri);
+    /// Send a file as the raw request body and return the response body
+    async fn send_octets(
+        &self,
+        method: Method,
+        path: &str,
+        content: Vec<u8>,
+    ) -> Result<String, LxdApiError> {
+        let body = Some((content, "application/octet-stream"));
+        let result = self.send_over_transport(method.clone(), path, body).await;
+        self.note_failure(&method, path, result)
+    }
 
-        let req = if let Some(body) = body {
-            let json_body = serde_json::to_string(&body)?;
-            request
-                .header("Content-Type", "application/json")
-                .body(Body::from(json_body))?
-        } else {
-            request.body(Body::empty())?
+    /// Record a failed request for error reports, passing the result through
+    fn note_failure<T>(
+        &self,
+        method: &Method,
+        path: &str,
+        result: Result<T, LxdApiError>,
+    ) -> Result<T, LxdApiError> {
+        if result.is_err() {
+            if let Ok(mut last) = LAST_FAILED_REQUEST.lock() {
+                *last = Some(FailedRequest {
+                    remote: self.remote_name.clone(),
+                    method: method.clone(),
+                    path: path.to_string(),
+                });
+            }
+        }
+        result
+    }
+
+    /// `body` is the encoded body and its content type
+    async fn send_over_transport(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<(Vec<u8>, &'static str)>,
+    ) -> Result<String, LxdApiError> {
+        match &self.transport {
+            Transport::Unix {
+                client,
+                socket_path,
+            } => {
+                let uri: hyper::Uri = Uri::new(socket_path, path).into();
+
+                let request = Request::builder().method(method).uri(uri);
+
+                let req = if let Some((body, content_type)) = body {
+                    request
+                        .header("Content-Type", content_type)
+                        .body(Full::from(body))?
+                } else {
+                    request.body(Full::default())?
+                };
+
+                let response = client.request(req).await?;
+                let body = response.into_body().collect().await?.to_bytes();
+                Ok(String::from_utf8_lossy(&body).into_owned())
+            }
+            Transport::Https { .. } => {
+                let mut request = self.https_request(method, path).await?;
+                if let Some((body, content_type)) = body {
+                    request = request.header("Content-Type", content_type).body(body);
+                }
+
+                Ok(request.send().await?.text().await?)
+            }
+        }
+    }
+
+    /// Start an authenticated request against the HTTPS remote
+    async fn https_request(
+        &self,
+        method: Method,
+        path: &str,
+    ) -> Result<reqwest::RequestBuilder, LxdApiError> {
+        let Transport::Https {
+            client,
+            base_url,
+            oidc,
+            ..
+        } = &self.transport
+        else {
+            return Err(LxdApiError::ApiError("Not an HTTPS remote".to_string()));
+        };
+
+        let url = base_url
+            .join(path)
+            .map_err(|e| LxdApiError::ApiError(e.to_string()))?;
+        let mut request = client.request(method, url);
+
+        if let Some(oidc) = oidc {
+            let token = self.bearer_token(oidc).await?;
+            request = request.bearer_auth(token).header("X-LXD-OIDC", "true");
+        }
+        Ok(request)
+    }
+
+    /// Subscribe to the events websocket for a comma-separated list of event
+    /// types, e.g. "lifecycle". The stream ends when the connection closes.
+    pub async fn events(&self, types: &str) -> Result<EventStream, LxdApiError> {
+        let path = format!("/1.0/events?type={}", types);
+        let result = match &self.transport {
+            Transport::Unix { socket_path, .. } => {
+                let stream = UnixStream::connect(socket_path).await?;
+                let url = format!("ws://lxd{}", path);
+                tokio_tungstenite::client_async(url, stream)
+                    .await
+                    .map(|(socket, _)| event_stream(socket))
+                    .map_err(LxdApiError::from)
+            }
+            Transport::Https {
+                tls,
+                base_url,
+                oidc,
+                ..
+            } => {
+                let mut url = base_url
+                    .join(&path)
+                    .map_err(|e| LxdApiError::ApiError(e.to_string()))?;
+                let _ = url.set_scheme("wss");
+
+                let mut request = url.as_str().into_client_request()?;
+                if let Some(oidc) = oidc {
+                    let token = self.bearer_token(oidc).await?;
+                    let headers = request.headers_mut();
+                    headers.insert(
+                        "Authorization",
+                        format!("Bearer {}", token)
+                            .parse()
+                            .map_err(|_| LxdApiError::ApiError("Invalid token".to_string()))?,
+                    );
+                    headers.insert("X-LXD-OIDC", "true".parse().expect("static header"));
+                }
+
+                let connector = tokio_tungstenite::Connector::NativeTls(tls.clone());
+                tokio_tungstenite::connect_async_tls_with_config(
+                    request,
+                    None,
+                    false,
+                    Some(connector),
+                )
+                .await
+                .map(|(socket, _)| event_stream(socket))
+                .map_err(LxdApiError::from)
+            }
         };
+        self.note_failure(&Method::GET, &path, result)
+    }
+
+    /// Stream a binary GET response into `dest`, returning the bytes written
+    async fn download(&self, path: &str, dest: &Path) -> Result<u64, LxdApiError> {
+        let mut file = tokio::fs::File::create(dest).await?;
+        let mut written = 0u64;
+
+        match &self.transport {
+            Transport::Unix {
+                client,
+                socket_path,
+            } => {
+                let uri: hyper::Uri = Uri::new(socket_path, path).into();
+                let response = client.get(uri).await?;
+                if !response.status().is_success() {
+                    let body = response.into_body().collect().await?.to_bytes();
+                    return self.note_failure(&Method::GET, path, Err(error_from_body(&body)));
+                }
+
+                let mut body = response.into_body();
+                while let Some(frame) = body.frame().await {
+                    if let Ok(chunk) = frame?.into_data() {
+                        file.write_all(&chunk).await?;
+                        written += chunk.len() as u64;
+                    }
+                }
+            }
+            Transport::Https { .. } => {
+                let mut response = self.https_request(Method::GET, path).await?.send().await?;
+                if !response.status().is_success() {
+                    let body = response.bytes().await?;
+                    return self.note_failure(&Method::GET, path, Err(error_from_body(&body)));
+                }
 
-        let response = self.client.request(req).await?;
-        let body = hyper::body::to_bytes(response.into_body()).await?;
-        let text = String::from_utf8_lossy(&body);
+                while let Some(chunk) = response.chunk().await? {
+                    file.write_all(&chunk).await?;
+                    written += chunk.len() as u64;
+                }
+            }
+        }
+
+        file.flush().await?;
+        Ok(written)
+    }
+
+    async fn request<T, B>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<B>,
+    ) -> Result<T, LxdApiError>
+    where
+        T: for<'de> Deserialize<'de>,
+        B: Serialize,
+    {
+        let text = self.send(method.clone(), path, body).await?;
 
         // Parse the response
         let lxd_response: LxdResponse<T> = serde_json::from_str(&text)?;
 
-        if lxd_response.status_code >= 400 {
-            return Err(LxdApiError::ApiError(
-                lxd_response
-                    .error
-                    .unwrap_or_else(|| "Unknown error".to_string()),
-            ));
+        // Error responses carry the HTTP code in error_code and a zero status_code
+        let code = lxd_response
+            .status_code
+            .max(lxd_response.error_code.unwrap_or(0));
+        if code >= 400 {
+            let error = lxd_response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string());
+            return self.note_failure(&method, path, Err(api_error(code, error)));
         }
 
         lxd_response
@@ -212,6 +1256,12 @@ impl LxdApiClient {
             .await
     }
 
+    /// Every instance with its runtime state, in one request
+    pub async fn list_instance_states(&self) -> Result<Vec<LxdContainer>, LxdApiError> {
+        self.request(Method::GET, "/1.0/instances?recursion=2", None::<()>)
+            .await
+    }
+
     pub async fn get_container(&self, name: &str) -> Result<LxdContainer, LxdApiError> {
         let path = format!("/1.0/instances/{}", name);
         self.request(Method::GET, &path, None::<()>).await
@@ -222,24 +1272,36 @@ impl LxdApiClient {
         self.request(Method::GET, &path, None::<()>).await
     }
 
-    pub async fn start_container(&self, name: &str) -> Result<(), LxdApiError> {
-        let path = format!("/1.0/instances/{}/state", name);
-        let body = json!({
-            "action": "start",
-            "timeout": 30
-        });
+    /// Console output of a container, as `lxc console --show-log` prints it
+    pub async fn console_log(&self, name: &str) -> Result<String, LxdApiError> {
+        let path = format!("/1.0/instances/{}/console", name);
+        let text = self.send(Method::GET, &path, None::<()>).await?;
 
-        let response: LxdResponse<serde_json::Value> =
-            self.request_raw(Method::PUT, &path, Some(body)).await?;
+        // The log is plain text; failures come back as a JSON response
+        if let Ok(response) = serde_json::from_str::<LxdResponse<serde_json::Value>>(&text) {
+            self.note_failure(&Method::GET, &path, check_response(&response))?;
+        }
+        Ok(text)
+    }
 
-        // If it's an async operation, wait for it
-        if let Some(operation_path) = response.operation {
-            self.wait_for_operation(&operation_path).await?;
+    /// Metrics of every instance in OpenMetrics text format
+    pub async fn metrics(&self) -> Result<String, LxdApiError> {
+        let path = "/1.0/metrics";
+        let text = self.send(Method::GET, path, None::<()>).await?;
+
+        // Failures come back as a JSON response
+        if let Ok(response) = serde_json::from_str::<LxdResponse<serde_json::Value>>(&text) {
+            self.note_failure(&Method::GET, path, check_response(&response))?;
         }
+        Ok(text)
+    }
 
-        Ok(())
+    pub async fn start_container(&self, name: &str) -> Result<(), LxdApiError> {
+        self.send_and_wait(ApiRequest::instance_state(name, "start"))
+            .await
     }
 
+    #[allow(dead_code)]
     pub async fn stop_container(&self, name: &str) -> Result<(), LxdApiError> {
         let path = format!("/1.0/instances/{}/state", name);
         let body = json!({
@@ -258,6 +1320,7 @@ impl LxdApiClient {
         Ok(())
     }
 
+    #[allow(dead_code)]
     pub async fn restart_container(&self, name: &str) -> Result<(), LxdApiError> {
         let path = format!("/1.0/instances/{}/state", name);
         let body = json!({
@@ -265,91 +1328,27 @@ impl LxdApiClient {
             "timeout": 30
         });
 
-        let response: LxdResponse<serde_json::Value> =
-            self.request_raw(Method::PUT, &path, Some(body)).await?;
-
-        if let Some(operation_path) = response.operation {
-            self.wait_for_operation(&operation_path).await?;
-        }
-
-        Ok(())
-    }
-
-    pub async fn delete_container(&self, name: &str) -> Result<(), LxdApiError> {
-        // First stop if running
-        let state = self.get_container_state(name).await?;
-        if state.status == "Running" {
-            self.stop_container(name).await?;
-        }
-
-        let path = format!("/1.0/instances/{}", name);
-        let response: LxdResponse<serde_json::Value> =
-            self.request_raw(Method::DELETE, &path, None::<()>).await?;
-
-        if let Some(operation_path) = response.operation {
-            self.wait_for_operation(&operation_path).await?;
-        }
-
-        Ok(())
-    }
-
-    pub async fn create_container(
-        &self,
-        name: &str,
-        image: &str,
-        is_vm: bool,
-    ) -> Result<(), LxdApiError> {
-        let container_type = if is_vm {
-            "virtual-machine"
-        } else {
-            "container"
-        };
-
-        let body = json!({
-            "name": name,
-            "source": {
-                "type": "image",
-                "alias": image
-            },
-            "type": container_type,
-            "config": {
-                "limits.cpu": "2",
-                "limits.memory": "2GB"
-            }
-        });
-
-        let response: LxdResponse<serde_json::Value> = self
-            .request_raw(Method::POST, "/1.0/instances", Some(body))
-            .await?;
-
+        let response: LxdResponse<serde_json::Value> =
+            self.request_raw(Method::PUT, &path, Some(body)).await?;
+
         if let Some(operation_path) = response.operation {
             self.wait_for_operation(&operation_path).await?;
         }
 
-        // Auto-start after creation
-        self.start_container(name).await?;
-
         Ok(())
     }
 
-    pub async fn clone_container(
-        &self,
-        source: &str,
-        destination: &str,
-    ) -> Result<(), LxdApiError> {
-        let source_path = format!("/1.0/instances/{}", source);
-
-        let body = json!({
-            "name": destination,
-            "source": {
-                "type": "copy",
-                "source": source_path
-            }
-        });
+    #[allow(dead_code)]
+    pub async fn delete_container(&self, name: &str) -> Result<(), LxdApiError> {
+        // First stop if running
+        let state = self.get_container_state(name).await?;
+        if state.status == "Running" {
+            self.stop_container(name).await?;
+        }
 
-        let response: LxdResponse<serde_json::Value> = self
-            .request_raw(Method::POST, "/1.0/instances", Some(body))
-            .await?;
+        let path = format!("/1.0/instances/{}", name);
+        let response: LxdResponse<serde_json::Value> =
+            self.request_raw(Method::DELETE, &path, None::<()>).await?;
 
         if let Some(operation_path) = response.operation {
             self.wait_for_operation(&operation_path).await?;
@@ -358,6 +1357,21 @@ impl LxdApiClient {
         Ok(())
     }
 
+    /// Re-sync an existing copy from its source, transferring only what changed
+    pub async fn refresh_copy(&self, source: &str, destination: &str) -> Result<(), LxdApiError> {
+        self.copy_instance(source, destination, true).await
+    }
+
+    async fn copy_instance(
+        &self,
+        source: &str,
+        destination: &str,
+        refresh: bool,
+    ) -> Result<(), LxdApiError> {
+        self.send_and_wait(ApiRequest::copy_instance(source, destination, refresh))
+            .await
+    }
+
     async fn request_raw<B>(
         &self,
         method: Method,
@@ -367,27 +1381,78 @@ impl LxdApiClient {
     where
         B: Serialize,
     {
-        let uri: hyper::Uri = Uri::new(&self.socket_path, path).into();
+        let text = self.send(method, path, body).await?;
 
-        let mut request = Request::builder().method(method).uri(uri);
+        serde_json::from_str(&text).map_err(LxdApiError::from)
+    }
 
-        let req = if let Some(body) = body {
-            let json_body = serde_json::to_string(&body)?;
-            request
-                .header("Content-Type", "application/json")
-                .body(Body::from(json_body))?
-        } else {
-            request.body(Body::empty())?
-        };
+    /// Send a prepared request, failing on LXD error responses
+    async fn send_request(
+        &self,
+        request: ApiRequest,
+    ) -> Result<LxdResponse<serde_json::Value>, LxdApiError> {
+        let response = self
+            .request_raw(request.method.clone(), &request.path, request.body)
+            .await?;
+        self.note_failure(&request.method, &request.path, check_response(&response))?;
+        Ok(response)
+    }
 
-        let response = self.client.request(req).await?;
-        let body = hyper::body::to_bytes(response.into_body()).await?;
-        let text = String::from_utf8_lossy(&body);
+    /// Send a prepared request and wait for any operation it starts
+    async fn send_and_wait(&self, request: ApiRequest) -> Result<(), LxdApiError> {
+        let (method, path) = (request.method.clone(), request.path.clone());
+        if let Some(operation_path) = self.send_request(request).await?.operation {
+            // Report the request that started a failed operation, not the poll
+            let result = self.wait_for_operation(&operation_path).await;
+            self.note_failure(&method, &path, result)?;
+        }
+        Ok(())
+    }
 
-        serde_json::from_str(&text).map_err(LxdApiError::from)
+    /// Run a command in an instance without a terminal and wait for it,
+    /// returning its exit status and what it wrote. LXD keeps the output in
+    /// log files, removed here once read.
+    pub async fn exec_output(
+        &self,
+        instance: &str,
+        command: &[String],
+    ) -> Result<ExecOutput, LxdApiError> {
+        let request = ApiRequest::exec(instance, command);
+        let (method, path) = (request.method.clone(), request.path.clone());
+        let operation_path = self.send_async(request).await?;
+        let result = self.wait_for_operation(&operation_path).await;
+        let metadata = self
+            .note_failure(&method, &path, result)?
+            .metadata
+            .unwrap_or_default();
+
+        let mut output = ExecOutput {
+            status: metadata["return"].as_i64().unwrap_or(-1) as i32,
+            stdout: String::new(),
+            stderr: String::new(),
+        };
+        for (fd, text) in [("1", &mut output.stdout), ("2", &mut output.stderr)] {
+            let Some(log) = metadata["output"][fd].as_str() else {
+                continue;
+            };
+            *text = self.send(Method::GET, log, None::<()>).await?;
+            // A log left behind only takes space; servers before the
+            // exec-output API can't remove it
+            let _ = self.send(Method::DELETE, log, None::<()>).await;
+        }
+        Ok(output)
+    }
+
+    /// Send a prepared request and return the operation it starts without waiting
+    async fn send_async(&self, request: ApiRequest) -> Result<String, LxdApiError> {
+        self.send_request(request)
+            .await?
+            .operation
+            .ok_or_else(|| LxdApiError::ApiError("No operation returned".to_string()))
     }
 
-    async fn wait_for_operation(&self, operation_path: &str) -> Result<(), LxdApiError> {
+    /// Wait for an operation to finish, returning it as it ended
+    async fn wait_for_operation(&self, operation_path: &str) -> Result<LxdOperation, LxdApiError> {
         let max_wait = Duration::from_secs(180);
         let poll_interval = Duration::from_millis(500);
 
@@ -408,7 +1473,7 @@ impl LxdApiClient {
 
             match operation.status_code {
                 // Success
-                200 => return Ok(()),
+                200 => return Ok(operation),
                 // Cancelled
                 401 => {
                     return Err(LxdApiError::OperationFailed(
@@ -436,6 +1501,122 @@ impl LxdApiClient {
         }
     }
 
+    pub async fn list_backups(&self, instance: &str) -> Result<Vec<LxdBackup>, LxdApiError> {
+        let path = format!("/1.0/instances/{}/backups?recursion=1", instance);
+        self.request(Method::GET, &path, None::<()>).await
+    }
+
+    /// Create a backup with a server-chosen name and wait for it to finish
+    pub async fn create_backup(&self, instance: &str) -> Result<(), LxdApiError> {
+        self.send_and_wait(ApiRequest::create_backup(instance))
+            .await
+    }
+
+    /// Create a snapshot, named by the instance's `snapshots.pattern` unless
+    /// `name` is given, and wait for it
+    pub async fn create_snapshot(
+        &self,
+        instance: &str,
+        name: Option<&str>,
+    ) -> Result<(), LxdApiError> {
+        self.send_and_wait(ApiRequest::create_snapshot(instance, name))
+            .await
+    }
+
+    pub async fn update_instance(
+        &self,
+        instance: &str,
+        config: serde_json::Value,
+    ) -> Result<(), LxdApiError> {
+        self.send_and_wait(ApiRequest::update_instance(instance, config))
+            .await
+    }
+
+    pub async fn set_instance_config(
+        &self,
+        instance: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), LxdApiError> {
+        self.send_and_wait(ApiRequest::set_instance_config(instance, key, value))
+            .await
+    }
+
+    pub async fn set_profiles(
+        &self,
+        instance: &str,
+        profiles: &[String],
+    ) -> Result<(), LxdApiError> {
+        self.send_and_wait(ApiRequest::set_profiles(instance, profiles))
+            .await
+    }
+
+    pub async fn instance_metadata(&self, instance: &str) -> Result<InstanceMetadata, LxdApiError> {
+        let path = format!("/1.0/instances/{}/metadata", instance);
+        self.request(Method::GET, &path, None::<()>).await
+    }
+
+    pub async fn update_metadata(
+        &self,
+        instance: &str,
+        metadata: &InstanceMetadata,
+    ) -> Result<(), LxdApiError> {
+        self.send_and_wait(ApiRequest::update_metadata(instance, metadata.to_json()))
+            .await
+    }
+
+    /// File names under the instance's `templates/`
+    pub async fn list_templates(&self, instance: &str) -> Result<Vec<String>, LxdApiError> {
+        let path = format!("/1.0/instances/{}/metadata/templates", instance);
+        self.request(Method::GET, &path, None::<()>).await
+    }
+
+    /// Content of a template file
+    pub async fn template(&self, instance: &str, template: &str) -> Result<String, LxdApiError> {
+        let path = template_path(instance, template);
+        let text = self.send(Method::GET, &path, None::<()>).await?;
+
+        // The file comes back as is; failures come back as a JSON response
+        if let Ok(response) = serde_json::from_str::<LxdResponse<serde_json::Value>>(&text) {
+            self.note_failure(&Method::GET, &path, check_response(&response))?;
+        }
+        Ok(text)
+    }
+
+    pub async fn set_template(
+        &self,
+        instance: &str,
+        template: &str,
+        content: &str,
+    ) -> Result<(), LxdApiError> {
+        let request = ApiRequest::set_template(instance, template, content);
+        let text = self
+            .send_octets(
+                request.method.clone(),
+                &request.path,
+                content.as_bytes().to_vec(),
+            )
+            .await?;
+        let response: LxdResponse<serde_json::Value> = serde_json::from_str(&text)?;
+        self.note_failure(&request.method, &request.path, check_response(&response))
+    }
+
+    pub async fn delete_backup(&self, instance: &str, backup: &str) -> Result<(), LxdApiError> {
+        self.send_and_wait(ApiRequest::delete_backup(instance, backup))
+            .await
+    }
+
+    /// Download a backup tarball to `dest`, returning its size in bytes
+    pub async fn download_backup(
+        &self,
+        instance: &str,
+        backup: &str,
+        dest: &Path,
+    ) -> Result<u64, LxdApiError> {
+        let path = format!("/1.0/instances/{}/backups/{}/export", instance, backup);
+        self.download(&path, dest).await
+    }
+
     pub async fn check_lxd_running(&self) -> bool {
         // Try to get API version as a health check
         self.request::<Vec<String>, ()>(Method::GET, "/", None)
@@ -443,63 +1624,276 @@ impl LxdApiClient {
             .is_ok()
     }
 
-    // ============== Non-blocking Operation Methods ==============
-    // These methods return operation IDs/paths immediately without waiting
+    pub async fn server_info(&self) -> Result<ServerInfo, LxdApiError> {
+        self.request(Method::GET, "/1.0", None::<()>).await
+    }
 
-    pub async fn start_container_async(&self, name: &str) -> Result<String, LxdApiError> {
-        let path = format!("/1.0/instances/{}/state", name);
+    /// Add our client certificate to the server's trust store using a token
+    pub async fn add_trust_token(&self, token: &str) -> Result<(), LxdApiError> {
         let body = json!({
-            "action": "start",
-            "timeout": 30
+            "type": "client",
+            "trust_token": token
         });
 
-        let response: LxdResponse<serde_json::Value> =
-            self.request_raw(Method::PUT, &path, Some(body)).await?;
-
-        response
-            .operation
-            .ok_or_else(|| LxdApiError::ApiError("No operation returned".to_string()))
-    }
+        let response = self
+            .request_raw(Method::POST, "/1.0/certificates", Some(body))
+            .await?;
+        if response.error_code.unwrap_or(0) < 400 {
+            return Ok(());
+        }
 
-    pub async fn stop_container_async(&self, name: &str) -> Result<String, LxdApiError> {
-        let path = format!("/1.0/instances/{}/state", name);
+        // Servers older than LXD 5.21 expect the token in the password field
         let body = json!({
-            "action": "stop",
-            "timeout": 30
+            "type": "client",
+            "password": token
         });
+        let response = self
+            .request_raw(Method::POST, "/1.0/certificates", Some(body))
+            .await?;
+        match response.error_code {
+            Some(code) if code >= 400 => Err(LxdApiError::ApiError(
+                response
+                    .error
+                    .unwrap_or_else(|| "Failed to add trust token".to_string()),
+            )),
+            _ => Ok(()),
+        }
+    }
 
-        let response: LxdResponse<serde_json::Value> =
-            self.request_raw(Method::PUT, &path, Some(body)).await?;
+    /// Ask an HTTPS remote which OIDC provider it accepts, if any
+    pub async fn oidc_provider(&self) -> Result<Option<OidcProvider>, LxdApiError> {
+        let Transport::Https {
+            client, base_url, ..
+        } = &self.transport
+        else {
+            return Ok(None);
+        };
 
-        response
-            .operation
-            .ok_or_else(|| LxdApiError::ApiError("No operation returned".to_string()))
+        let url = base_url
+            .join("/1.0/instances")
+            .map_err(|e| LxdApiError::ApiError(e.to_string()))?;
+        let response = client.get(url).header("X-LXD-OIDC", "true").send().await?;
+
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        Ok(
+            match (header("X-LXD-OIDC-issuer"), header("X-LXD-OIDC-clientid")) {
+                (Some(issuer), Some(client_id)) => Some(OidcProvider {
+                    issuer,
+                    client_id,
+                    audience: header("X-LXD-OIDC-audience"),
+                }),
+                _ => None,
+            },
+        )
     }
 
-    pub async fn restart_container_async(&self, name: &str) -> Result<String, LxdApiError> {
-        let path = format!("/1.0/instances/{}/state", name);
-        let body = json!({
-            "action": "restart",
-            "timeout": 30
-        });
+    // ============== Non-blocking Operation Methods ==============
+    // These methods return operation IDs/paths immediately without waiting
 
-        let response: LxdResponse<serde_json::Value> =
-            self.request_raw(Method::PUT, &path, Some(body)).await?;
+    pub async fn start_container_async(&self, name: &str) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::instance_state(name, "start"))
+            .await
+    }
 
-        response
-            .operation
-            .ok_or_else(|| LxdApiError::ApiError("No operation returned".to_string()))
+    pub async fn stop_container_async(&self, name: &str) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::instance_state(name, "stop"))
+            .await
+    }
+
+    pub async fn restart_container_async(&self, name: &str) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::instance_state(name, "restart"))
+            .await
     }
 
     pub async fn delete_container_async(&self, name: &str) -> Result<String, LxdApiError> {
-        let path = format!("/1.0/instances/{}", name);
+        self.send_async(ApiRequest::delete_instance(name)).await
+    }
 
-        let response: LxdResponse<serde_json::Value> =
-            self.request_raw(Method::DELETE, &path, None::<()>).await?;
+    pub async fn create_container_async(
+        &self,
+        name: &str,
+        image: &str,
+        is_vm: bool,
+        media: Option<&Media>,
+        defaults: &CreateDefaults,
+    ) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::create_instance(
+            name, image, is_vm, media, defaults,
+        ))
+        .await
+    }
 
-        response
-            .operation
-            .ok_or_else(|| LxdApiError::ApiError("No operation returned".to_string()))
+    pub async fn clone_container_async(
+        &self,
+        source: &str,
+        destination: &str,
+        options: &CloneOptions,
+    ) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::clone_instance(source, destination, options))
+            .await
+    }
+
+    pub async fn recreate_instance_async(
+        &self,
+        conversion: &Conversion,
+    ) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::recreate_instance(conversion))
+            .await
+    }
+
+    /// Names of the server's storage pools
+    pub async fn list_storage_pools(&self) -> Result<Vec<String>, LxdApiError> {
+        self.list_names("/1.0/storage-pools").await
+    }
+
+    /// The server's storage pools with their drivers
+    pub async fn get_storage_pools(&self) -> Result<Vec<LxdStoragePool>, LxdApiError> {
+        self.request(Method::GET, "/1.0/storage-pools?recursion=1", None::<()>)
+            .await
+    }
+
+    /// Warnings LXD has raised, about the server and what is on it
+    pub async fn get_warnings(&self) -> Result<Vec<LxdWarning>, LxdApiError> {
+        self.request(Method::GET, "/1.0/warnings?recursion=1", None::<()>)
+            .await
+    }
+
+    /// Space used and available in a storage pool
+    pub async fn get_storage_pool_resources(
+        &self,
+        pool: &str,
+    ) -> Result<LxdStoragePoolResources, LxdApiError> {
+        let path = format!("/1.0/storage-pools/{}/resources", pool);
+        self.request(Method::GET, &path, None::<()>).await
+    }
+
+    /// CPUs, memory and other hardware of the host
+    pub async fn get_resources(&self) -> Result<LxdResources, LxdApiError> {
+        self.request(Method::GET, "/1.0/resources", None::<()>)
+            .await
+    }
+
+    /// Aliases of the images stored on the server
+    pub async fn list_image_aliases(&self) -> Result<Vec<LxdImageAlias>, LxdApiError> {
+        self.request(Method::GET, "/1.0/images/aliases?recursion=1", None::<()>)
+            .await
+    }
+
+    /// Images in the server's image store
+    pub async fn list_images(&self) -> Result<Vec<LxdImage>, LxdApiError> {
+        self.request(Method::GET, "/1.0/images?recursion=1", None::<()>)
+            .await
+    }
+
+    /// Change an image's settings; this doesn't start an operation
+    pub async fn update_image(&self, request: ApiRequest) -> Result<(), LxdApiError> {
+        self.send_request(request).await.map(|_| ())
+    }
+
+    pub async fn refresh_image_async(&self, fingerprint: &str) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::refresh_image(fingerprint))
+            .await
+    }
+
+    pub async fn publish_instance_async(
+        &self,
+        instance: &str,
+        publish: &Publish,
+    ) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::publish_instance(instance, publish))
+            .await
+    }
+
+    pub async fn rebuild_instance_async(
+        &self,
+        instance: &str,
+        fingerprint: &str,
+    ) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::rebuild_instance(instance, fingerprint))
+            .await
+    }
+
+    pub async fn get_profile(&self, name: &str) -> Result<LxdProfile, LxdApiError> {
+        let path = format!("/1.0/profiles/{}", name);
+        self.request(Method::GET, &path, None::<()>).await
+    }
+
+    pub async fn get_network(&self, name: &str) -> Result<LxdNetwork, LxdApiError> {
+        let path = format!("/1.0/networks/{}", name);
+        self.request(Method::GET, &path, None::<()>).await
+    }
+
+    pub async fn list_networks(&self) -> Result<Vec<LxdNetwork>, LxdApiError> {
+        self.request(Method::GET, "/1.0/networks?recursion=1", None::<()>)
+            .await
+    }
+
+    pub async fn list_network_forwards(
+        &self,
+        network: &str,
+    ) -> Result<Vec<LxdNetworkForward>, LxdApiError> {
+        let path = format!("/1.0/networks/{}/forwards?recursion=1", network);
+        self.request(Method::GET, &path, None::<()>).await
+    }
+
+    pub async fn list_network_zones(&self) -> Result<Vec<LxdNetworkZone>, LxdApiError> {
+        self.request(Method::GET, "/1.0/network-zones?recursion=1", None::<()>)
+            .await
+    }
+
+    pub async fn list_zone_records(&self, zone: &str) -> Result<Vec<LxdZoneRecord>, LxdApiError> {
+        let path = format!("/1.0/network-zones/{}/records?recursion=1", zone);
+        self.request(Method::GET, &path, None::<()>).await
+    }
+
+    /// Send a change to a network zone or its records; these don't start
+    /// operations
+    pub async fn change_network_zone(&self, request: ApiRequest) -> Result<(), LxdApiError> {
+        self.send_request(request).await.map(|_| ())
+    }
+
+    /// Names of an instance's snapshots
+    pub async fn list_snapshots(&self, instance: &str) -> Result<Vec<String>, LxdApiError> {
+        self.list_names(&format!("/1.0/instances/{}/snapshots", instance))
+            .await
+    }
+
+    /// Names of the server's profiles
+    pub async fn list_profiles(&self) -> Result<Vec<String>, LxdApiError> {
+        self.list_names("/1.0/profiles").await
+    }
+
+    /// The server's profiles with their config and devices
+    pub async fn get_profiles(&self) -> Result<Vec<LxdProfile>, LxdApiError> {
+        self.request(Method::GET, "/1.0/profiles?recursion=1", None::<()>)
+            .await
+    }
+
+    /// Names of the server's projects
+    pub async fn list_projects(&self) -> Result<Vec<String>, LxdApiError> {
+        self.list_names("/1.0/projects").await
+    }
+
+    /// Last segment of each URL a collection endpoint lists
+    async fn list_names(&self, path: &str) -> Result<Vec<String>, LxdApiError> {
+        let urls: Vec<String> = self.request(Method::GET, path, None::<()>).await?;
+        Ok(urls
+            .iter()
+            .filter_map(|url| url.rsplit('/').next())
+            .map(str::to_string)
+            .collect())
+    }
+
+    pub async fn create_backup_async(&self, instance: &str) -> Result<String, LxdApiError> {
+        self.send_async(ApiRequest::create_backup(instance)).await
     }
 
     pub async fn get_operation(&self, operation_path: &str) -> Result<LxdOperation, LxdApiError> {
@@ -508,6 +1902,7 @@ impl LxdApiClient {
             .await
     }
 
+    #[allow(dead_code)]
     pub async fn get_operations(&self) -> Result<Vec<String>, LxdApiError> {
         let response: LxdResponse<serde_json::Value> = self
             .request_raw(Method::GET, "/1.0/operations", None::<()>)
@@ -540,3 +1935,51 @@ impl LxdApiClient {
         Ok(())
     }
 }
+
+/// Turn an LXD error response into an `ApiError`
+fn check_response(response: &LxdResponse<serde_json::Value>) -> Result<(), LxdApiError> {
+    let code = response.error_code.unwrap_or(0);
+    if code >= 400 {
+        return Err(api_error(
+            code,
+            response
+                .error
+                .clone()
+                .unwrap_or_else(|| "Unknown error".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+/// Error for a failed response, telling apart endpoints the server doesn't
+/// have: LXD answers an unknown route with a bare "not found" (a resource
+/// that doesn't exist names it, e.g. "Instance not found"), an older server
+/// may answer 501, and restricted projects say what they block
+fn api_error(code: i32, message: String) -> LxdApiError {
+    let lower = message.to_lowercase();
+    let unsupported = code == 501
+        || (code == 404 && message == "not found")
+        || lower.contains("not supported")
+        || (code == 403 && lower.contains("restrict"));
+    if unsupported {
+        LxdApiError::Unsupported(message)
+    } else {
+        LxdApiError::ApiError(message)
+    }
+}
+
+/// Extract the error message from a non-JSON endpoint's failure body
+fn error_from_body(body: &[u8]) -> LxdApiError {
+    match serde_json::from_slice::<LxdResponse<serde_json::Value>>(body) {
+        Ok(response) => api_error(
+            response.error_code.unwrap_or(0),
+            response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string()),
+        ),
+        Err(_) => LxdApiError::ApiError(String::from_utf8_lossy(body).into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests;
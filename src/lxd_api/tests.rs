@@ -0,0 +1,48 @@
+//! Tests of the API client against the fake LXD server
+
+use super::{LxdApiClient, LxdApiError};
+use crate::fake_lxd::FakeLxd;
+
+#[tokio::test]
+async fn requests_reuse_one_connection() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let client = LxdApiClient::unix(lxd.socket_path());
+
+    for _ in 0..5 {
+        assert_eq!(client.list_containers().await.unwrap().len(), 1);
+    }
+    client.get_container_state("web1").await.unwrap();
+    assert_eq!(lxd.connections(), 1);
+}
+
+#[tokio::test]
+async fn requests_run_concurrently() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_instance("web2", "Stopped", None);
+    let client = LxdApiClient::unix(lxd.socket_path());
+
+    let (web1, web2, list) = tokio::join!(
+        client.get_container_state("web1"),
+        client.get_container_state("web2"),
+        client.list_containers(),
+    );
+    assert_eq!(web1.unwrap().status, "Running");
+    assert_eq!(web2.unwrap().status, "Stopped");
+    assert_eq!(list.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn unknown_endpoints_are_unsupported() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    let client = LxdApiClient::unix(lxd.socket_path());
+
+    assert!(matches!(
+        client.list_backups("web1").await,
+        Err(LxdApiError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client.get_container_state("missing").await,
+        Err(LxdApiError::ApiError(_))
+    ));
+}
@@ -0,0 +1,132 @@
+//! Installation media for VMs
+//!
+//! Attaches an ISO, from a host path or an `iso` custom volume, as the
+//! `install` disk device and boots the VM from it ahead of the root disk.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Name of the device the ISO is attached as
+pub const DEVICE: &str = "install";
+
+/// Boots before the root disk, which has none
+pub const BOOT_PRIORITY: &str = "10";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Media {
+    File(String), // Absolute path on the server's host
+    Volume { pool: String, volume: String },
+}
+
+impl Media {
+    /// An absolute path, or `pool/volume`; None for anything else
+    pub fn parse(text: &str) -> Option<Media> {
+        let text = text.trim();
+        if text.starts_with('/') {
+            return (!text.ends_with('/')).then(|| Media::File(text.to_string()));
+        }
+        let (pool, volume) = text.split_once('/')?;
+        (!pool.is_empty() && !volume.is_empty() && !volume.contains('/')).then(|| Media::Volume {
+            pool: pool.to_string(),
+            volume: volume.to_string(),
+        })
+    }
+
+    /// The disk device attaching it, booted first
+    pub fn device(&self) -> Value {
+        serde_json::to_value(self.options(true)).unwrap_or_default()
+    }
+
+    fn options(&self, boot_first: bool) -> BTreeMap<String, String> {
+        let mut options = BTreeMap::from([("type".to_string(), "disk".to_string())]);
+        match self {
+            Media::File(path) => {
+                options.insert("source".to_string(), path.clone());
+            }
+            Media::Volume { pool, volume } => {
+                options.insert("pool".to_string(), pool.clone());
+                options.insert("source".to_string(), volume.clone());
+            }
+        }
+        if boot_first {
+            options.insert("boot.priority".to_string(), BOOT_PRIORITY.to_string());
+        }
+        options
+    }
+}
+
+/// Paths to an ISO, or pool/volume
+pub fn is_media_char(c: char) -> bool {
+    !c.is_control()
+}
+
+/// The ISO on the `install` device of an instance's own devices, and
+/// whether it boots first
+pub fn attached(devices: &BTreeMap<String, BTreeMap<String, String>>) -> Option<(Media, bool)> {
+    let device = devices.get(DEVICE)?;
+    let source = device.get("source")?;
+    let media = match device.get("pool") {
+        Some(pool) => Media::Volume {
+            pool: pool.clone(),
+            volume: source.clone(),
+        },
+        None => Media::File(source.clone()),
+    };
+    Some((media, device.contains_key("boot.priority")))
+}
+
+/// A change to the `install` device of a VM
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaChange {
+    Attach(Media), // Replacing what was attached, booted first
+    BootFirst(bool),
+    Detach,
+}
+
+impl MediaChange {
+    /// Make the change to an instance's own devices; false when there is
+    /// no ISO to change
+    pub fn apply(&self, devices: &mut BTreeMap<String, BTreeMap<String, String>>) -> bool {
+        match self {
+            MediaChange::Attach(media) => {
+                devices.insert(DEVICE.to_string(), media.options(true));
+                true
+            }
+            MediaChange::BootFirst(first) => {
+                let Some(device) = devices.get_mut(DEVICE) else {
+                    return false;
+                };
+                if *first {
+                    device.insert("boot.priority".to_string(), BOOT_PRIORITY.to_string());
+                } else {
+                    device.remove("boot.priority");
+                }
+                true
+            }
+            MediaChange::Detach => devices.remove(DEVICE).is_some(),
+        }
+    }
+
+    /// What it does to `container`, for operations and messages
+    pub fn describe(&self, container: &str) -> String {
+        match self {
+            MediaChange::Attach(media) => format!("Attach {} to '{}'", media, container),
+            MediaChange::BootFirst(true) => format!("Boot '{}' from its ISO", container),
+            MediaChange::BootFirst(false) => format!("Boot '{}' from its disk", container),
+            MediaChange::Detach => format!("Detach the ISO of '{}'", container),
+        }
+    }
+}
+
+impl fmt::Display for Media {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Media::File(path) => write!(f, "{}", path),
+            Media::Volume { pool, volume } => write!(f, "{}/{}", pool, volume),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
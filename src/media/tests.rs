@@ -0,0 +1,46 @@
+//! Tests of installation media
+
+use super::{attached, Media, MediaChange, DEVICE};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[test]
+fn reads_a_host_path_or_a_pool_volume() {
+    let file = Media::parse(" /var/lib/isos/debian-12.iso ").unwrap();
+    assert_eq!(
+        file.device(),
+        json!({"type": "disk", "source": "/var/lib/isos/debian-12.iso", "boot.priority": "10"})
+    );
+
+    let volume = Media::parse("default/debian-12").unwrap();
+    assert_eq!(volume.to_string(), "default/debian-12");
+    assert_eq!(volume.device()["pool"], "default");
+    assert_eq!(volume.device()["source"], "debian-12");
+
+    for invalid in ["debian-12.iso", "default/", "/var/lib/isos/", "a/b/c", ""] {
+        assert_eq!(Media::parse(invalid), None, "{}", invalid);
+    }
+}
+
+#[test]
+fn attaches_toggles_and_detaches_the_install_device() {
+    let mut devices = BTreeMap::new();
+    assert!(!MediaChange::BootFirst(true).apply(&mut devices));
+    assert!(!MediaChange::Detach.apply(&mut devices));
+
+    let iso = Media::parse("default/debian-12").unwrap();
+    assert!(MediaChange::Attach(iso.clone()).apply(&mut devices));
+    assert_eq!(attached(&devices), Some((iso.clone(), true)));
+
+    // Other options of the device survive the boot priority going
+    devices
+        .get_mut(DEVICE)
+        .unwrap()
+        .insert("readonly".to_string(), "true".to_string());
+    assert!(MediaChange::BootFirst(false).apply(&mut devices));
+    assert_eq!(attached(&devices), Some((iso, false)));
+    assert_eq!(devices[DEVICE]["readonly"], "true");
+
+    assert!(MediaChange::Detach.apply(&mut devices));
+    assert_eq!(attached(&devices), None);
+}
@@ -0,0 +1,196 @@
+//! LXD metrics
+//!
+//! Parses the OpenMetrics text served at `/1.0/metrics` and picks out the
+//! series of one instance that the state API lacks: CPU time by mode,
+//! the memory breakdown, OOM kills, filesystem space and disk I/O.
+
+use std::collections::BTreeMap;
+use thiserror::Error;
+use tokio::time::Duration;
+
+/// How often the watch view scrapes metrics; LXD caches them for about
+/// eight seconds anyway
+pub const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MetricsError {
+    #[error("Malformed metrics line {line}: {reason}")]
+    Malformed { line: usize, reason: &'static str },
+}
+
+/// One sample of a metric family
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+impl Series {
+    fn label(&self, name: &str) -> Option<&str> {
+        self.labels.get(name).map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filesystem {
+    pub mountpoint: String,
+    pub size: f64, // Bytes
+    pub available: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskIo {
+    pub device: String,
+    pub read: f64, // Bytes since the instance started
+    pub written: f64,
+}
+
+/// The metrics of one instance
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstanceMetrics {
+    pub cpu_seconds: BTreeMap<String, f64>, // By mode, summed over CPUs
+    pub memory_total: Option<f64>,
+    pub memory_available: Option<f64>,
+    pub memory_cached: Option<f64>,
+    pub oom_kills: Option<f64>,
+    pub filesystems: Vec<Filesystem>, // By mountpoint
+    pub disk_io: Vec<DiskIo>,         // By device
+}
+
+impl InstanceMetrics {
+    /// Pick the series of `instance` in the default project. None when the
+    /// scrape has nothing for it, as for a stopped instance.
+    pub fn of(series: &[Series], instance: &str) -> Option<Self> {
+        let mut metrics = InstanceMetrics::default();
+        let mut filesystems: BTreeMap<&str, Filesystem> = BTreeMap::new();
+        let mut disks: BTreeMap<&str, DiskIo> = BTreeMap::new();
+        let mut found = false;
+
+        for s in series.iter().filter(|s| {
+            s.label("name") == Some(instance) && s.label("project").is_none_or(|p| p == "default")
+        }) {
+            found = true;
+            match s.name.as_str() {
+                "lxd_cpu_seconds_total" => {
+                    let mode = s.label("mode").unwrap_or("total").to_string();
+                    *metrics.cpu_seconds.entry(mode).or_default() += s.value;
+                }
+                "lxd_memory_MemTotal_bytes" => metrics.memory_total = Some(s.value),
+                "lxd_memory_MemAvailable_bytes" => metrics.memory_available = Some(s.value),
+                "lxd_memory_Cached_bytes" => metrics.memory_cached = Some(s.value),
+                "lxd_memory_OOM_kills_total" => metrics.oom_kills = Some(s.value),
+                "lxd_filesystem_size_bytes" | "lxd_filesystem_avail_bytes" => {
+                    let Some(mountpoint) = s.label("mountpoint") else {
+                        continue;
+                    };
+                    let filesystem = filesystems.entry(mountpoint).or_insert(Filesystem {
+                        mountpoint: mountpoint.to_string(),
+                        size: 0.0,
+                        available: 0.0,
+                    });
+                    if s.name == "lxd_filesystem_size_bytes" {
+                        filesystem.size = s.value;
+                    } else {
+                        filesystem.available = s.value;
+                    }
+                }
+                "lxd_disk_read_bytes_total" | "lxd_disk_written_bytes_total" => {
+                    let Some(device) = s.label("device") else {
+                        continue;
+                    };
+                    let disk = disks.entry(device).or_insert(DiskIo {
+                        device: device.to_string(),
+                        read: 0.0,
+                        written: 0.0,
+                    });
+                    if s.name == "lxd_disk_read_bytes_total" {
+                        disk.read = s.value;
+                    } else {
+                        disk.written = s.value;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        metrics.filesystems = filesystems.into_values().collect();
+        metrics.disk_io = disks.into_values().collect();
+        found.then_some(metrics)
+    }
+}
+
+/// Parse an OpenMetrics exposition. Comments and blank lines are skipped;
+/// timestamps after the value are ignored.
+pub fn parse(text: &str) -> Result<Vec<Series>, MetricsError> {
+    let mut series = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let malformed = |reason| MetricsError::Malformed {
+            line: i + 1,
+            reason,
+        };
+
+        let name_end = line
+            .find(|c: char| c == '{' || c.is_whitespace())
+            .ok_or(malformed("missing value"))?;
+        let name = line[..name_end].to_string();
+        let mut rest = &line[name_end..];
+
+        let mut labels = BTreeMap::new();
+        if let Some(inner) = rest.strip_prefix('{') {
+            let (parsed, after) = parse_labels(inner).ok_or(malformed("bad labels"))?;
+            labels = parsed;
+            rest = after;
+        }
+
+        let value = rest
+            .split_whitespace()
+            .next()
+            .ok_or(malformed("missing value"))?;
+        let value = match value {
+            "+Inf" => f64::INFINITY,
+            "-Inf" => f64::NEG_INFINITY,
+            value => value.parse().map_err(|_| malformed("bad value"))?,
+        };
+        series.push(Series {
+            name,
+            labels,
+            value,
+        });
+    }
+    Ok(series)
+}
+
+/// Labels up to the closing brace, and the text after it
+fn parse_labels(mut text: &str) -> Option<(BTreeMap<String, String>, &str)> {
+    let mut labels = BTreeMap::new();
+    loop {
+        text = text.trim_start_matches([',', ' ']);
+        if let Some(rest) = text.strip_prefix('}') {
+            return Some((labels, rest));
+        }
+        let (name, rest) = text.split_once("=\"")?;
+
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+        let end = loop {
+            match chars.next()? {
+                (i, '"') => break i,
+                (_, '\\') => match chars.next()?.1 {
+                    'n' => value.push('\n'),
+                    c => value.push(c),
+                },
+                (_, c) => value.push(c),
+            }
+        };
+        labels.insert(name.trim().to_string(), value);
+        text = &rest[end + 1..];
+    }
+}
+
+#[cfg(test)]
+mod tests;
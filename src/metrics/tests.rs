@@ -0,0 +1,59 @@
+//! Tests of parsing LXD metrics
+
+use super::{parse, InstanceMetrics, MetricsError};
+
+const SCRAPE: &str = r#"# HELP lxd_cpu_seconds_total The total number of CPU time used in seconds.
+# TYPE lxd_cpu_seconds_total counter
+lxd_cpu_seconds_total{cpu="0",mode="user",name="web1",project="default",type="container"} 12.5
+lxd_cpu_seconds_total{cpu="1",mode="user",name="web1",project="default",type="container"} 2.5
+lxd_cpu_seconds_total{cpu="0",mode="system",name="web1",project="default",type="container"} 4
+lxd_cpu_seconds_total{cpu="0",mode="user",name="db1",project="default",type="container"} 99
+lxd_cpu_seconds_total{cpu="0",mode="user",name="web1",project="staging",type="container"} 99
+lxd_memory_MemTotal_bytes{name="web1",project="default",type="container"} 1.073741824e+09
+lxd_memory_MemAvailable_bytes{name="web1",project="default",type="container"} 536870912
+lxd_memory_OOM_kills_total{name="web1",project="default",type="container"} 1
+lxd_filesystem_size_bytes{device="/dev/sda1",fstype="ext4",mountpoint="/",name="web1",project="default",type="container"} 10737418240
+lxd_filesystem_avail_bytes{device="/dev/sda1",fstype="ext4",mountpoint="/",name="web1",project="default",type="container"} 3221225472
+lxd_disk_read_bytes_total{device="sda",name="web1",project="default",type="container"} 1048576
+lxd_disk_written_bytes_total{device="sda",name="web1",project="default",type="container"} 2097152
+lxd_warnings_total 3
+# EOF
+"#;
+
+#[test]
+fn picks_out_one_instance() {
+    let series = parse(SCRAPE).unwrap();
+    let metrics = InstanceMetrics::of(&series, "web1").unwrap();
+
+    assert_eq!(metrics.cpu_seconds["user"], 15.0);
+    assert_eq!(metrics.cpu_seconds["system"], 4.0);
+    assert_eq!(metrics.memory_total, Some(1073741824.0));
+    assert_eq!(metrics.memory_available, Some(536870912.0));
+    assert_eq!(metrics.memory_cached, None);
+    assert_eq!(metrics.oom_kills, Some(1.0));
+    assert_eq!(metrics.filesystems[0].mountpoint, "/");
+    assert_eq!(metrics.filesystems[0].available, 3221225472.0);
+    assert_eq!(metrics.disk_io[0].device, "sda");
+    assert_eq!(metrics.disk_io[0].written, 2097152.0);
+
+    assert!(InstanceMetrics::of(&series, "vm1").is_none());
+}
+
+#[test]
+fn parses_escaped_labels_and_timestamps() {
+    let series = parse(r#"up{job="a \"quoted\", job"} 1 1700000000000"#).unwrap();
+    assert_eq!(series[0].labels["job"], r#"a "quoted", job"#);
+    assert_eq!(series[0].value, 1.0);
+}
+
+#[test]
+fn reports_malformed_lines() {
+    assert_eq!(
+        parse("# TYPE up gauge\nup{job=\"a\"").unwrap_err(),
+        MetricsError::Malformed {
+            line: 2,
+            reason: "bad labels"
+        }
+    );
+    assert!(parse("up one").is_err());
+}
@@ -0,0 +1,76 @@
+//! Migration progress
+//!
+//! Reads the stage, bytes and rate of a transfer from an operation's
+//! `fs_progress` or `block_progress` metadata.
+
+use crate::alerts;
+use serde_json::Value;
+
+/// Metadata keys LXD reports a running transfer under
+const PROGRESS_KEYS: [&str; 2] = ["fs_progress", "block_progress"];
+
+/// How far a migration has got
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stage {
+    SnapshotSync(String), // Copying this snapshot
+    FinalSync,            // Copying the instance itself
+    Cutover,              // Everything is across; setting it up on the target
+}
+
+impl Stage {
+    pub fn describe(&self) -> String {
+        match self {
+            Stage::SnapshotSync(snapshot) => format!("Snapshot sync ({})", snapshot),
+            Stage::FinalSync => "Final sync".to_string(),
+            Stage::Cutover => "Cutover".to_string(),
+        }
+    }
+}
+
+/// The transfer an operation's metadata reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    pub stage: Stage,
+    pub percent: Option<i32>, // Of the volume being copied, when LXD knows its size
+    pub transferred: Option<i64>, // Bytes of it copied, when LXD doesn't
+    pub rate: Option<i64>,    // Bytes per second
+}
+
+impl Transfer {
+    /// Read the transfer from operation metadata; None when the operation
+    /// isn't moving any data
+    pub fn parse(metadata: &Value) -> Option<Transfer> {
+        let text = PROGRESS_KEYS
+            .iter()
+            .find_map(|key| metadata.get(key).and_then(Value::as_str))?;
+        let (volume, amount) = text.split_once(": ")?;
+        let (amount, rate) = match amount.split_once(" (") {
+            Some((amount, rate)) => (amount, rate.strip_suffix("/s)")),
+            None => (amount, None),
+        };
+
+        let percent = amount
+            .trim()
+            .strip_suffix('%')
+            .and_then(|p| p.parse::<i32>().ok())
+            .map(|p| p.clamp(0, 100));
+        let stage = match (volume.split_once('/'), percent) {
+            (Some((_, snapshot)), _) => Stage::SnapshotSync(snapshot.to_string()),
+            (None, Some(100)) => Stage::Cutover,
+            (None, _) => Stage::FinalSync,
+        };
+        let transferred = percent
+            .is_none()
+            .then(|| alerts::parse_size(amount))
+            .flatten();
+        (percent.is_some() || transferred.is_some()).then(|| Transfer {
+            stage,
+            percent,
+            transferred,
+            rate: rate.and_then(alerts::parse_size),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,42 @@
+//! Tests of migration progress
+
+use super::{Stage, Transfer};
+use serde_json::json;
+
+#[test]
+fn reads_the_stage_amount_and_rate_of_a_transfer() {
+    let snapshot = Transfer::parse(&json!({"fs_progress": "web1/snap0: 45% (12.50MB/s)"}));
+    assert_eq!(
+        snapshot,
+        Some(Transfer {
+            stage: Stage::SnapshotSync("snap0".to_string()),
+            percent: Some(45),
+            transferred: None,
+            rate: Some(12_500_000),
+        })
+    );
+
+    let instance = Transfer::parse(&json!({"block_progress": "vm1: 1.5GB (30MB/s)"})).unwrap();
+    assert_eq!(instance.stage, Stage::FinalSync);
+    assert_eq!(instance.percent, None);
+    assert_eq!(instance.transferred, Some(1_500_000_000));
+    assert_eq!(instance.rate, Some(30_000_000));
+
+    let done = Transfer::parse(&json!({"fs_progress": "web1: 100% (8.00MB/s)"})).unwrap();
+    assert_eq!(done.stage, Stage::Cutover);
+    assert_eq!(done.stage.describe(), "Cutover");
+}
+
+#[test]
+fn ignores_operations_that_move_no_data() {
+    assert_eq!(
+        Transfer::parse(&json!({"create_instance_from_image_unpack_progress": "Unpack: 45%"})),
+        None
+    );
+    assert_eq!(Transfer::parse(&json!({"fs_progress": "starting"})), None);
+    assert_eq!(
+        Transfer::parse(&json!({"fs_progress": "web1: waiting"})),
+        None
+    );
+    assert_eq!(Transfer::parse(&json!(null)), None);
+}
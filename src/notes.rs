@@ -0,0 +1,120 @@
+//! Container notes
+//!
+//! Markdown notes kept in `user.lxtui.notes`, edited in the user's editor
+//! and drawn in the watch view.
+
+/// Config key holding an instance's notes
+pub const CONFIG_KEY: &str = "user.lxtui.notes";
+
+/// A run of text on a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inline {
+    Text(String),
+    Bold(String),
+    Code(String),
+}
+
+/// A line of notes as shown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteLine {
+    Heading(Vec<Inline>),
+    Bullet { indent: usize, text: Vec<Inline> }, // Indent in levels of two spaces
+    Quote(Vec<Inline>),
+    Code(String), // A line of a fenced code block, as typed
+    Text(Vec<Inline>),
+    Blank,
+}
+
+/// Lines of `notes`, without the fences of code blocks. A block left open
+/// runs to the end.
+pub fn parse(notes: &str) -> Vec<NoteLine> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    for line in notes.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(NoteLine::Code(line.to_string()));
+            continue;
+        }
+
+        let indent = (line.len() - trimmed.len()) / 2;
+        let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+        lines.push(if trimmed.is_empty() {
+            NoteLine::Blank
+        } else if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            NoteLine::Heading(inline(trimmed[hashes..].trim()))
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            NoteLine::Bullet {
+                indent,
+                text: inline(item),
+            }
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            NoteLine::Quote(inline(quote.trim_start()))
+        } else {
+            NoteLine::Text(inline(line.trim_end()))
+        });
+    }
+    lines
+}
+
+/// Runs of `text` in `code` and **bold**; a marker without its closing
+/// pair is kept as typed
+pub fn inline(text: &str) -> Vec<Inline> {
+    let mut runs = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let marked = if let Some(after) = rest.strip_prefix("**") {
+            after
+                .find("**")
+                .filter(|end| *end > 0)
+                .map(|end| (Inline::Bold(after[..end].to_string()), &after[end + 2..]))
+        } else if let Some(after) = rest.strip_prefix('`') {
+            after
+                .find('`')
+                .filter(|end| *end > 0)
+                .map(|end| (Inline::Code(after[..end].to_string()), &after[end + 1..]))
+        } else {
+            None
+        };
+        match marked {
+            Some((run, after)) => {
+                if !plain.is_empty() {
+                    runs.push(Inline::Text(std::mem::take(&mut plain)));
+                }
+                runs.push(run);
+                rest = after;
+            }
+            None => {
+                // Past the whole marker, so ** isn't taken as two `*`
+                let step = if rest.starts_with("**") {
+                    2
+                } else {
+                    rest.chars().next().map_or(1, char::len_utf8)
+                };
+                plain.push_str(&rest[..step]);
+                rest = &rest[step..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        runs.push(Inline::Text(plain));
+    }
+    runs
+}
+
+/// The config value for notes as saved from the editor; empty removes the
+/// key
+pub fn value(text: &str) -> String {
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests;
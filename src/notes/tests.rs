@@ -0,0 +1,75 @@
+//! Tests of container notes
+
+use super::{inline, parse, value, Inline, NoteLine};
+
+fn text(s: &str) -> Inline {
+    Inline::Text(s.to_string())
+}
+
+#[test]
+fn runbook_lines() {
+    let notes = "\
+## Restarting
+Drain it first:
+- stop `nginx`
+  - wait for **all** workers
+> Never on a Friday
+
+```
+systemctl restart app
+  --force
+```
+# Heading, unlike #tag
+";
+    assert_eq!(
+        parse(notes),
+        vec![
+            NoteLine::Heading(vec![text("Restarting")]),
+            NoteLine::Text(vec![text("Drain it first:")]),
+            NoteLine::Bullet {
+                indent: 0,
+                text: vec![text("stop "), Inline::Code("nginx".to_string())],
+            },
+            NoteLine::Bullet {
+                indent: 1,
+                text: vec![
+                    text("wait for "),
+                    Inline::Bold("all".to_string()),
+                    text(" workers"),
+                ],
+            },
+            NoteLine::Quote(vec![text("Never on a Friday")]),
+            NoteLine::Blank,
+            NoteLine::Code("systemctl restart app".to_string()),
+            NoteLine::Code("  --force".to_string()),
+            NoteLine::Heading(vec![text("Heading, unlike #tag")]),
+        ]
+    );
+    assert_eq!(parse("#tag"), vec![NoteLine::Text(vec![text("#tag")])]);
+}
+
+#[test]
+fn unclosed_markers_are_kept() {
+    assert_eq!(inline("a ** b"), vec![text("a ** b")]);
+    assert_eq!(inline("`open"), vec![text("`open")]);
+    assert_eq!(inline("````"), vec![text("````")]);
+    assert_eq!(
+        inline("**bold** and `x`"),
+        vec![
+            Inline::Bold("bold".to_string()),
+            text(" and "),
+            Inline::Code("x".to_string()),
+        ]
+    );
+    // A code block left open runs to the end
+    assert_eq!(
+        parse("```\n# root prompt"),
+        vec![NoteLine::Code("# root prompt".to_string())]
+    );
+}
+
+#[test]
+fn saved_without_trailing_blank_lines() {
+    assert_eq!(value("# Notes\n\n"), "# Notes");
+    assert_eq!(value("\n \n"), "");
+}
@@ -0,0 +1,124 @@
+//! Operation notifications
+//!
+//! Rings the bell and runs the `[notifications]` command in the background
+//! when an operation finishes, describing it in environment variables.
+
+use crate::config::{NotificationsConfig, NotifyOn};
+use log::{debug, warn};
+use std::io::{self, Write};
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::time::Duration;
+
+/// A finished operation, as the notification command sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finished {
+    pub description: String,
+    pub container: Option<String>,
+    pub error: Option<String>, // None when it succeeded
+    pub duration: Option<Duration>,
+}
+
+impl Finished {
+    /// `LXTUI_*` variables passed to the command
+    pub fn environment(&self) -> Vec<(&'static str, String)> {
+        let status = if self.error.is_some() {
+            "failed"
+        } else {
+            "success"
+        };
+        vec![
+            ("LXTUI_OPERATION", self.description.clone()),
+            ("LXTUI_STATUS", status.to_string()),
+            (
+                "LXTUI_CONTAINER",
+                self.container.clone().unwrap_or_default(),
+            ),
+            ("LXTUI_ERROR", self.error.clone().unwrap_or_default()),
+            (
+                "LXTUI_DURATION",
+                self.duration.map_or(0, |d| d.as_secs()).to_string(),
+            ),
+        ]
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Notifier {
+    config: NotificationsConfig,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationsConfig) -> Self {
+        Notifier { config }
+    }
+
+    /// Whether `finished` is worth a notification
+    pub fn wanted(&self, finished: &Finished) -> bool {
+        let outcome = match self.config.on {
+            NotifyOn::Always => true,
+            NotifyOn::Failure => finished.error.is_some(),
+        };
+        let long_enough = finished
+            .duration
+            .is_none_or(|d| d.as_secs() >= self.config.min_duration_secs);
+        outcome && long_enough && (self.config.bell || self.config.command.is_some())
+    }
+
+    /// Ring the bell and start the command for an operation that finished.
+    /// Must be called inside a Tokio runtime.
+    pub fn notify(&self, finished: &Finished) {
+        if !self.wanted(finished) {
+            return;
+        }
+        if self.config.bell {
+            ring_bell();
+        }
+        if let Some(command) = &self.config.command {
+            run(command, finished);
+        }
+    }
+}
+
+fn ring_bell() {
+    let mut stdout = io::stdout();
+    if let Err(e) = stdout.write_all(b"\x07").and_then(|_| stdout.flush()) {
+        warn!("Failed to ring the bell: {}", e);
+    }
+}
+
+/// Start `command` and log how it ended; its output would corrupt the TUI,
+/// so it goes nowhere
+fn run(command: &str, finished: &Finished) {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(finished.environment())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("Failed to run the notification command: {}", e);
+            return;
+        }
+    };
+    let description = finished.description.clone();
+    tokio::spawn(async move {
+        match child.wait().await {
+            Ok(status) if status.success() => {
+                debug!("Notified about '{}'", description)
+            }
+            Ok(status) => warn!(
+                "Notification command for '{}' exited with {}",
+                description, status
+            ),
+            Err(e) => warn!("Notification command for '{}' failed: {}", description, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests;
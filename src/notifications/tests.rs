@@ -0,0 +1,68 @@
+//! Tests of choosing and running operation notifications
+
+use super::{Finished, Notifier};
+use crate::config::{NotificationsConfig, NotifyOn};
+use std::fs;
+use std::time::Instant;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+fn finished(error: Option<&str>, secs: u64) -> Finished {
+    Finished {
+        description: "Creating container web1".to_string(),
+        container: Some("web1".to_string()),
+        error: error.map(str::to_string),
+        duration: Some(Duration::from_secs(secs)),
+    }
+}
+
+#[test]
+fn notifies_about_the_chosen_operations() {
+    let quiet = Notifier::new(NotificationsConfig::default());
+    assert!(!quiet.wanted(&finished(None, 60)));
+
+    let always = Notifier::new(NotificationsConfig {
+        bell: true,
+        min_duration_secs: 10,
+        ..NotificationsConfig::default()
+    });
+    assert!(always.wanted(&finished(None, 60)));
+    assert!(always.wanted(&finished(Some("no space left"), 10)));
+    assert!(!always.wanted(&finished(None, 9)));
+
+    let failures = Notifier::new(NotificationsConfig {
+        command: Some("true".to_string()),
+        on: NotifyOn::Failure,
+        ..NotificationsConfig::default()
+    });
+    assert!(!failures.wanted(&finished(None, 60)));
+    assert!(failures.wanted(&finished(Some("no space left"), 0)));
+}
+
+#[tokio::test]
+async fn runs_the_command_with_the_operation_in_its_environment() {
+    let path = std::env::temp_dir().join(format!("lxtui-notify-{}", Uuid::new_v4()));
+    let notifier = Notifier::new(NotificationsConfig {
+        command: Some(format!(
+            "printf '%s|%s|%s|%s' \"$LXTUI_STATUS\" \"$LXTUI_CONTAINER\" \"$LXTUI_ERROR\" \
+             \"$LXTUI_DURATION\" > {}.tmp && mv {0}.tmp {0}",
+            path.display()
+        )),
+        ..NotificationsConfig::default()
+    });
+    notifier.notify(&finished(Some("no space left"), 42));
+
+    let started = Instant::now();
+    while !path.exists() {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "command never ran"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "failed|web1|no space left|42"
+    );
+    fs::remove_file(&path).unwrap();
+}
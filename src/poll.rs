@@ -0,0 +1,188 @@
+//! Polling scheduler
+//!
+//! Every timed request to the server draws from a token bucket of its own
+//! kind, and intervals are jittered so copies of lxtui don't poll in step.
+
+use crate::config::PollingConfig;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration, Instant};
+use uuid::Uuid;
+
+/// Most an interval is moved by, however the jitter is set
+const MAX_JITTER: f64 = 0.9;
+
+/// A kind of poll, limited on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    Operations, // Progress of operations lxtui started
+    Health,     // The background health check
+    Events,     // Reconnecting the event feed
+    Refresh,    // Listing the instances again
+    Views,      // Usage sampled by the watch, top and dashboard views and the ledger
+}
+
+impl Category {
+    pub const ALL: [Category; 5] = [
+        Category::Operations,
+        Category::Health,
+        Category::Events,
+        Category::Refresh,
+        Category::Views,
+    ];
+
+    fn per_minute(self, config: &PollingConfig) -> u32 {
+        match self {
+            Category::Operations => config.operations_per_minute,
+            Category::Health => config.health_per_minute,
+            Category::Events => config.events_per_minute,
+            Category::Refresh => config.refresh_per_minute,
+            Category::Views => config.views_per_minute,
+        }
+    }
+}
+
+/// Polls a category may make: refilled at its rate up to its burst
+#[derive(Debug, Clone)]
+struct Bucket {
+    rate: f64, // Polls a second; 0 for no limit
+    capacity: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: u32, burst: u32, now: Instant) -> Self {
+        let capacity = f64::from(burst.max(1));
+        Bucket {
+            rate: f64::from(per_minute) / 60.0,
+            capacity,
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = self.updated.max(now);
+    }
+
+    fn take(&mut self, now: Instant) -> bool {
+        if self.rate == 0.0 {
+            return true;
+        }
+        self.refill(now);
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    /// Time until the next poll is allowed
+    fn wait(&mut self, now: Instant) -> Duration {
+        if self.rate == 0.0 {
+            return Duration::ZERO;
+        }
+        self.refill(now);
+        Duration::from_secs_f64(((1.0 - self.tokens) / self.rate).max(0.0))
+    }
+}
+
+/// `interval` made longer or shorter by up to `jitter` of itself; `roll`
+/// from 0 to 1 picks where in that range
+pub fn spread(interval: Duration, jitter: f64, roll: f64) -> Duration {
+    let jitter = jitter.clamp(0.0, MAX_JITTER);
+    interval.mul_f64(1.0 + jitter * (2.0 * roll.clamp(0.0, 1.0) - 1.0))
+}
+
+/// A number from 0 to 1, from the random bits of a v4 UUID
+fn roll() -> f64 {
+    let bits = Uuid::new_v4().as_u128() as u64 & ((1 << 53) - 1);
+    bits as f64 / (1u64 << 53) as f64
+}
+
+#[derive(Debug)]
+struct Limits {
+    jitter: f64,
+    buckets: HashMap<Category, Bucket>,
+}
+
+impl Limits {
+    fn new(config: &PollingConfig, now: Instant) -> Self {
+        Limits {
+            jitter: f64::from(config.jitter_percent) / 100.0,
+            buckets: Category::ALL
+                .into_iter()
+                .map(|category| {
+                    let per_minute = category.per_minute(config);
+                    (category, Bucket::new(per_minute, config.burst, now))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The scheduler, shared by the event loop and the background tasks
+#[derive(Debug, Clone)]
+pub struct Poller {
+    limits: Arc<Mutex<Limits>>,
+}
+
+impl Default for Poller {
+    fn default() -> Self {
+        Poller::new(&PollingConfig::default())
+    }
+}
+
+impl Poller {
+    pub fn new(config: &PollingConfig) -> Self {
+        Poller {
+            limits: Arc::new(Mutex::new(Limits::new(config, Instant::now()))),
+        }
+    }
+
+    /// Apply new limits from now on, with every bucket full. Tasks already
+    /// running share them.
+    pub fn configure(&self, config: &PollingConfig) {
+        let mut limits = self.limits.lock().unwrap_or_else(|e| e.into_inner());
+        *limits = Limits::new(config, Instant::now());
+    }
+
+    /// `interval`, made longer or shorter at random by the jitter
+    pub fn jittered(&self, interval: Duration) -> Duration {
+        let jitter = self.limits.lock().unwrap_or_else(|e| e.into_inner()).jitter;
+        spread(interval, jitter, roll())
+    }
+
+    /// Take a poll of `category` if its rate allows one at `now`
+    pub fn try_take(&self, category: Category, now: Instant) -> bool {
+        let mut limits = self.limits.lock().unwrap_or_else(|e| e.into_inner());
+        limits
+            .buckets
+            .get_mut(&category)
+            .is_none_or(|bucket| bucket.take(now))
+    }
+
+    /// Wait until the rate of `category` allows a poll, and take it
+    pub async fn take(&self, category: Category) {
+        loop {
+            let wait = {
+                let now = Instant::now();
+                let mut limits = self.limits.lock().unwrap_or_else(|e| e.into_inner());
+                let Some(bucket) = limits.buckets.get_mut(&category) else {
+                    return;
+                };
+                if bucket.take(now) {
+                    return;
+                }
+                bucket.wait(now)
+            };
+            sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
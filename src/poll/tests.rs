@@ -0,0 +1,80 @@
+//! Tests of the polling scheduler
+
+use super::{spread, Bucket, Category, Poller};
+use crate::config::PollingConfig;
+use tokio::time::{Duration, Instant};
+
+#[test]
+fn a_bucket_allows_its_burst_then_its_rate() {
+    let start = Instant::now();
+    let mut bucket = Bucket::new(60, 3, start);
+    assert!((0..3).all(|_| bucket.take(start)));
+    assert!(!bucket.take(start));
+    assert_eq!(bucket.wait(start), Duration::from_secs(1));
+
+    // One a second comes back, and no more than the burst is saved up
+    assert!(!bucket.take(start + Duration::from_millis(900)));
+    assert!(bucket.take(start + Duration::from_secs(1)));
+    assert!(!bucket.take(start + Duration::from_secs(1)));
+    let later = start + Duration::from_secs(60);
+    assert_eq!((0..5).filter(|_| bucket.take(later)).count(), 3);
+}
+
+#[test]
+fn no_rate_means_no_limit() {
+    let start = Instant::now();
+    let mut bucket = Bucket::new(0, 1, start);
+    assert!((0..100).all(|_| bucket.take(start)));
+    assert_eq!(bucket.wait(start), Duration::ZERO);
+}
+
+#[test]
+fn jitter_moves_intervals_both_ways_within_bounds() {
+    let interval = Duration::from_secs(10);
+    assert_eq!(spread(interval, 0.2, 0.0), Duration::from_secs(8));
+    assert_eq!(spread(interval, 0.2, 0.5), interval);
+    assert_eq!(spread(interval, 0.2, 1.0), Duration::from_secs(12));
+    assert_eq!(spread(interval, 0.0, 1.0), interval);
+    // Never down to nothing, however it is set
+    assert_eq!(spread(interval, 5.0, 0.0), Duration::from_secs(1));
+
+    let poller = Poller::default();
+    let intervals: Vec<Duration> = (0..50).map(|_| poller.jittered(interval)).collect();
+    assert!(intervals
+        .iter()
+        .all(|i| (Duration::from_secs(8)..=Duration::from_secs(12)).contains(i)));
+    assert!(intervals.iter().any(|i| *i != intervals[0]));
+}
+
+#[test]
+fn categories_are_limited_apart() {
+    let config = PollingConfig {
+        burst: 1,
+        refresh_per_minute: 1,
+        ..PollingConfig::default()
+    };
+    let poller = Poller::new(&config);
+    let now = Instant::now();
+    assert!(poller.try_take(Category::Refresh, now));
+    assert!(!poller.try_take(Category::Refresh, now));
+    assert!(poller.try_take(Category::Health, now));
+
+    // New limits start full
+    poller.configure(&config);
+    assert!(poller.try_take(Category::Refresh, Instant::now()));
+}
+
+#[tokio::test]
+async fn take_waits_for_the_rate() {
+    let config = PollingConfig {
+        burst: 1,
+        health_per_minute: 600,
+        ..PollingConfig::default()
+    };
+    let poller = Poller::new(&config);
+    let start = Instant::now();
+    poller.take(Category::Health).await;
+    assert!(start.elapsed() < Duration::from_millis(50));
+    poller.take(Category::Health).await;
+    assert!(start.elapsed() >= Duration::from_millis(90));
+}
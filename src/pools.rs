@@ -0,0 +1,435 @@
+//! Storage pool maintenance
+//!
+//! Asks `zpool` and `btrfs` how the disks under ZFS and btrfs pools of the
+//! local server are doing, and starts scrubs through sudo or pkexec.
+
+use crate::helper;
+use crate::lxd_api::{LxdStoragePool, LxdStorageSpace, LxdWarning};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+/// How long a health check may take; a pool with a hung device can block
+/// `zpool status`
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// zpool states of a device that is out of use
+const DEVICE_DOWN: [&str; 4] = ["FAULTED", "UNAVAIL", "REMOVED", "OFFLINE"];
+
+/// How a pool's health is checked and scrubbed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tool {
+    Zfs { zpool: String },
+    Btrfs { mountpoint: PathBuf }, // Where LXD mounts the pool
+}
+
+impl Tool {
+    /// The tool for a pool of `driver`, None for drivers without one.
+    /// `lxd_dir` is where LXD keeps its pools.
+    pub fn for_pool(
+        driver: &str,
+        name: &str,
+        config: &HashMap<String, String>,
+        lxd_dir: &Path,
+    ) -> Option<Self> {
+        match driver {
+            "zfs" => {
+                // The pool may live in a dataset of a larger zpool
+                let dataset = config
+                    .get("zfs.pool_name")
+                    .filter(|d| !d.is_empty())
+                    .map_or(name, String::as_str);
+                let zpool = dataset.split('/').next().unwrap_or(dataset);
+                Some(Tool::Zfs {
+                    zpool: zpool.to_string(),
+                })
+            }
+            "btrfs" => Some(Tool::Btrfs {
+                mountpoint: lxd_dir.join("storage-pools").join(name),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn program(&self) -> &'static str {
+        match self {
+            Tool::Zfs { .. } => "zpool",
+            Tool::Btrfs { .. } => "btrfs",
+        }
+    }
+
+    /// Commands reporting the pool's health, as arguments, run one after
+    /// the other. btrfs needs root for device stats, so it goes through
+    /// `sudo -n` when given, which fails rather than prompting.
+    pub fn health_commands(&self, sudo: bool) -> Vec<Vec<String>> {
+        match self {
+            Tool::Zfs { zpool } => vec![vec![
+                "zpool".to_string(),
+                "status".to_string(),
+                "-v".to_string(),
+                zpool.clone(),
+            ]],
+            Tool::Btrfs { mountpoint } => [["device", "stats"], ["scrub", "status"]]
+                .iter()
+                .map(|subcommand| {
+                    let sudo = if sudo { &["sudo", "-n"][..] } else { &[] };
+                    let mut args: Vec<String> = sudo.iter().map(|a| a.to_string()).collect();
+                    args.push("btrfs".to_string());
+                    args.extend(subcommand.iter().map(|a| a.to_string()));
+                    args.push(mountpoint.display().to_string());
+                    args
+                })
+                .collect(),
+        }
+    }
+
+    /// Shell command starting a scrub, run through `escalate` if given
+    pub fn scrub_command(&self, escalate: Option<&str>) -> String {
+        let command = match self {
+            Tool::Zfs { zpool } => format!("zpool scrub {}", quoted(zpool)),
+            Tool::Btrfs { mountpoint } => format!(
+                "btrfs scrub start {}",
+                quoted(&mountpoint.display().to_string())
+            ),
+        };
+        match escalate {
+            Some(tool) => format!("{} {}", tool, command),
+            None => command,
+        }
+    }
+
+    /// Run the health check and read what it reports
+    pub async fn check(&self, sudo: bool) -> Result<Health, String> {
+        if !helper::in_path(self.program()) {
+            return Err(format!("{} isn't installed on this host", self.program()));
+        }
+        let commands = self.health_commands(sudo);
+        let run = async {
+            let mut stdout = String::new();
+            for args in &commands {
+                let command = args.join(" ");
+                let output = Command::new(&args[0])
+                    .args(&args[1..])
+                    .kill_on_drop(true)
+                    .output()
+                    .await
+                    .map_err(|e| format!("Failed to run '{}': {}", command, e))?;
+                let text = String::from_utf8_lossy(&output.stdout);
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let reason = stderr
+                        .lines()
+                        .chain(text.lines())
+                        .find(|l| !l.trim().is_empty());
+                    return Err(format!(
+                        "'{}' failed: {}",
+                        command,
+                        reason.unwrap_or("no output").trim()
+                    ));
+                }
+                stdout.push_str(&text);
+            }
+            Ok(stdout)
+        };
+        let stdout = tokio::time::timeout(CHECK_TIMEOUT, run)
+            .await
+            .map_err(|_| {
+                format!(
+                    "'{}' didn't finish within {} seconds",
+                    commands[0].join(" "),
+                    CHECK_TIMEOUT.as_secs()
+                )
+            })??;
+        Ok(match self {
+            Tool::Zfs { .. } => parse_zpool_status(&stdout),
+            Tool::Btrfs { .. } => parse_btrfs(&stdout),
+        })
+    }
+}
+
+/// `text` as one shell word, quoted unless it is plainly safe
+fn quoted(text: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./:@%+=,".contains(c);
+    if !text.is_empty() && text.chars().all(safe) {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+}
+
+/// Where LXD keeps its pools on this host: the snap's directory, Incus's,
+/// or the one of a package install
+pub fn lxd_dir() -> PathBuf {
+    let dir = if Path::new("/snap/bin/lxd").exists() {
+        "/var/snap/lxd/common/lxd"
+    } else if Path::new("/var/lib/incus/unix.socket").exists() {
+        "/var/lib/incus"
+    } else {
+        "/var/lib/lxd"
+    };
+    PathBuf::from(dir)
+}
+
+/// What the filesystem's tools say of a pool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Health {
+    pub state: String, // As the tool put it: ONLINE, DEGRADED, no errors...
+    pub healthy: bool,
+    pub scrub: Option<String>, // The scrub running, or the last one
+    pub errors: Vec<String>,   // Devices with errors, and damaged data
+}
+
+/// Read `zpool status -v`
+pub fn parse_zpool_status(text: &str) -> Health {
+    let mut state = String::new();
+    let mut scan: Vec<&str> = Vec::new();
+    let mut errors = Vec::new();
+    let mut section = "";
+    for line in text.lines() {
+        let trimmed = line.trim();
+        // Sections start with a "key:" flush with the others; continuation
+        // lines are indented with a tab
+        if let Some((key, value)) = trimmed.split_once(':').filter(|(key, _)| {
+            !line.starts_with('\t')
+                && !key.is_empty()
+                && key.chars().all(|c| c.is_ascii_alphabetic())
+        }) {
+            section = key;
+            let value = value.trim();
+            match key {
+                "state" => state = value.to_string(),
+                "scan" if !value.is_empty() => scan.push(value),
+                "errors" if value != "No known data errors" => errors.push(value.to_string()),
+                _ => {}
+            }
+            continue;
+        }
+        match section {
+            "scan" if !trimmed.is_empty() => scan.push(trimmed),
+            "errors" if !trimmed.is_empty() => errors.push(trimmed.to_string()),
+            "config" => {
+                let fields: Vec<&str> = trimmed.split_whitespace().collect();
+                let [name, device_state, read, write, cksum, note @ ..] = fields.as_slice() else {
+                    continue;
+                };
+                if *name == "NAME" {
+                    continue;
+                }
+                let counted = [read, write, cksum].iter().any(|count| **count != "0");
+                if counted || DEVICE_DOWN.contains(device_state) {
+                    let mut error = format!(
+                        "{} {}: {} read, {} write, {} checksum errors",
+                        name, device_state, read, write, cksum
+                    );
+                    if !note.is_empty() {
+                        error.push_str(&format!(" ({})", note.join(" ")));
+                    }
+                    errors.push(error);
+                }
+            }
+            _ => {}
+        }
+    }
+    Health {
+        healthy: state == "ONLINE" && errors.is_empty(),
+        state,
+        scrub: (!scan.is_empty()).then(|| scan.join("; ")),
+        errors,
+    }
+}
+
+/// Read `btrfs device stats` followed by `btrfs scrub status`
+pub fn parse_btrfs(text: &str) -> Health {
+    let mut errors = Vec::new();
+    let mut status = None;
+    let mut started = None;
+    let mut legacy = None; // "scrub started at ... and finished after ..."
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some((device, counter)) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("]."))
+        {
+            let mut fields = counter.split_whitespace();
+            if let (Some(name), Some(count)) = (fields.next(), fields.next()) {
+                if count != "0" {
+                    errors.push(format!("{}: {} {}", device, count, name));
+                }
+            }
+        } else if trimmed.starts_with("scrub started at") {
+            legacy = Some(trimmed);
+        } else if let Some((key, value)) = trimmed.split_once(':') {
+            let value = value.trim();
+            match key {
+                "Status" => status = Some(value),
+                "Scrub started" => started = Some(value),
+                "Error summary" if value != "no errors found" => {
+                    errors.push(format!("scrub found {}", value))
+                }
+                _ => {}
+            }
+        }
+    }
+    let scrub = match (status, started) {
+        (Some(status), Some(started)) => Some(format!("{}, started {}", status, started)),
+        (Some(status), None) => Some(status.to_string()),
+        _ => legacy.map(str::to_string),
+    };
+    Health {
+        state: if errors.is_empty() {
+            "no errors"
+        } else {
+            "errors"
+        }
+        .to_string(),
+        healthy: errors.is_empty(),
+        scrub,
+        errors,
+    }
+}
+
+/// A warning LXD raised against a pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolWarning {
+    pub message: String,
+    pub severity: String,
+    pub count: u64,
+    pub last_seen: String, // "YYYY-MM-DD HH:MM"
+}
+
+/// A storage pool as the view shows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pool {
+    pub name: String,
+    pub driver: String,
+    pub status: String, // LXD's, like Created or Unavailable
+    pub used: u64,      // Bytes
+    pub total: u64,     // Bytes; 0 when the pool's size couldn't be read
+    pub tool: Option<Tool>,
+    pub warnings: Vec<PoolWarning>, // Unresolved, the latest first
+}
+
+impl Pool {
+    pub fn new(
+        pool: LxdStoragePool,
+        space: LxdStorageSpace,
+        warnings: &[LxdWarning],
+        lxd_dir: &Path,
+    ) -> Self {
+        let url = format!("/1.0/storage-pools/{}", pool.name);
+        let mut warnings: Vec<PoolWarning> = warnings
+            .iter()
+            .filter(|w| w.status != "resolved")
+            .filter(|w| w.entity_url.split('?').next() == Some(url.as_str()))
+            .map(|w| PoolWarning {
+                message: w.last_message.clone(),
+                severity: w.severity.clone(),
+                count: w.count,
+                last_seen: w
+                    .last_seen_at
+                    .get(..16)
+                    .unwrap_or_default()
+                    .replace('T', " "),
+            })
+            .collect();
+        warnings.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        Pool {
+            tool: Tool::for_pool(&pool.driver, &pool.name, &pool.config, lxd_dir),
+            name: pool.name,
+            driver: pool.driver,
+            status: pool.status,
+            used: space.used,
+            total: space.total,
+            warnings,
+        }
+    }
+
+    /// Whether LXD or the pool's tools report a problem
+    pub fn troubled(&self, check: Option<&Result<Health, String>>) -> bool {
+        let lxd_ok = self.status.is_empty() || self.status == "Created";
+        !lxd_ok
+            || !self.warnings.is_empty()
+            || check.is_some_and(|check| check.as_ref().is_ok_and(|health| !health.healthy))
+    }
+}
+
+/// The pools view
+#[derive(Debug)]
+pub struct PoolsView {
+    pub pools: Vec<Pool>,
+    pub checks: HashMap<String, Result<Health, String>>, // By pool, for those with a tool
+    pub checking: HashSet<String>,                       // Pools whose check is running
+    pub local: bool, // The tools only reach the local server's pools
+    pub selected: usize,
+    pub error: Option<String>,     // Why the last load failed
+    pub status: Option<String>,    // Outcome of the last scrub
+    pub scrubbing: Option<String>, // Pool whose scrub command is running
+    tx: mpsc::UnboundedSender<(String, Result<Health, String>)>,
+    rx: mpsc::UnboundedReceiver<(String, Result<Health, String>)>,
+}
+
+impl PoolsView {
+    pub fn new(local: bool) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        PoolsView {
+            pools: Vec::new(),
+            checks: HashMap::new(),
+            checking: HashSet::new(),
+            local,
+            selected: 0,
+            error: None,
+            status: None,
+            scrubbing: None,
+            tx,
+            rx,
+        }
+    }
+
+    /// Where the checks report what they found
+    pub fn sender(&self) -> mpsc::UnboundedSender<(String, Result<Health, String>)> {
+        self.tx.clone()
+    }
+
+    /// Take in the checks that finished
+    pub fn drain(&mut self) {
+        while let Ok((name, check)) = self.rx.try_recv() {
+            self.checking.remove(&name);
+            if self.pools.iter().any(|p| p.name == name) {
+                self.checks.insert(name, check);
+            }
+        }
+    }
+
+    /// Take in a fresh listing, keeping the selected pool
+    pub fn set_pools(&mut self, pools: Vec<Pool>) {
+        let selected = self.selected_pool().map(|p| p.name.clone());
+        self.pools = pools;
+        self.error = None;
+        self.checks
+            .retain(|name, _| self.pools.iter().any(|p| &p.name == name));
+        self.selected = selected
+            .and_then(|name| self.pools.iter().position(|p| p.name == name))
+            .unwrap_or(0)
+            .min(self.pools.len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.pools.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.pools.len()
+        } else {
+            (self.selected + self.pools.len() - 1) % self.pools.len()
+        };
+    }
+
+    pub fn selected_pool(&self) -> Option<&Pool> {
+        self.pools.get(self.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests;
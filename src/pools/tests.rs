@@ -0,0 +1,236 @@
+//! Tests of storage pool maintenance
+
+use super::{parse_btrfs, parse_zpool_status, Pool, PoolsView, Tool};
+use crate::lxd_api::{LxdStoragePool, LxdStorageSpace, LxdWarning};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const DEGRADED: &str = "  pool: tank
+ state: DEGRADED
+status: One or more devices are faulted in response to persistent errors.
+\tSufficient replicas exist for the pool to continue functioning in a
+\tdegraded state.
+action: Replace the faulted device, or use 'zpool clear' to mark the device
+\trepaired.
+  scan: scrub in progress since Sun Mar  8 00:24:01 2026
+\t1.20G scanned at 410M/s, 300M issued at 100M/s, 4.00G total
+\t0B repaired, 7.32% done, 00:00:37 to go
+config:
+
+\tNAME        STATE     READ WRITE CKSUM
+\ttank        DEGRADED     0     0     0
+\t  mirror-0  DEGRADED     0     0     0
+\t    sda     ONLINE       0     0     0
+\t    sdb     FAULTED      3    12     0  too many errors
+
+errors: Permanent errors have been detected in the following files:
+
+        tank/lxd/containers/web1:<0x1>
+";
+
+fn pool(name: &str, driver: &str, config: &[(&str, &str)]) -> LxdStoragePool {
+    LxdStoragePool {
+        name: name.to_string(),
+        driver: driver.to_string(),
+        description: String::new(),
+        config: config
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        status: "Created".to_string(),
+    }
+}
+
+fn warning(entity_url: &str, status: &str, message: &str, last_seen_at: &str) -> LxdWarning {
+    LxdWarning {
+        status: status.to_string(),
+        severity: "moderate".to_string(),
+        count: 2,
+        last_seen_at: last_seen_at.to_string(),
+        last_message: message.to_string(),
+        entity_url: entity_url.to_string(),
+    }
+}
+
+#[test]
+fn degraded_zpool_with_a_scrub_running() {
+    let health = parse_zpool_status(DEGRADED);
+    assert_eq!(health.state, "DEGRADED");
+    assert!(!health.healthy);
+    assert_eq!(
+        health.scrub.as_deref(),
+        Some(
+            "scrub in progress since Sun Mar  8 00:24:01 2026; \
+             1.20G scanned at 410M/s, 300M issued at 100M/s, 4.00G total; \
+             0B repaired, 7.32% done, 00:00:37 to go"
+        )
+    );
+    assert_eq!(
+        health.errors,
+        [
+            "sdb FAULTED: 3 read, 12 write, 0 checksum errors (too many errors)",
+            "Permanent errors have been detected in the following files:",
+            "tank/lxd/containers/web1:<0x1>",
+        ]
+    );
+
+    let online = parse_zpool_status(
+        "  pool: tank\n state: ONLINE\n  scan: scrub repaired 0B in 00:01:02 with 0 errors \
+         on Sun Mar  8 00:25:03 2026\nconfig:\n\n\tNAME  STATE  READ WRITE CKSUM\n\
+         \ttank  ONLINE    0     0     0\n\nerrors: No known data errors\n",
+    );
+    assert!(online.healthy);
+    assert!(online.errors.is_empty());
+}
+
+#[test]
+fn btrfs_device_stats_and_scrub_status() {
+    let text = "[/dev/sdb].write_io_errs    0
+[/dev/sdb].read_io_errs     4
+[/dev/sdb].flush_io_errs    0
+[/dev/sdb].corruption_errs  1
+[/dev/sdb].generation_errs  0
+UUID:             8a8f6c1e-0000-4000-8000-000000000000
+Scrub started:    Sun Mar  8 00:25:03 2026
+Status:           finished
+Duration:         0:01:02
+Error summary:    csum=1
+";
+    let health = parse_btrfs(text);
+    assert!(!health.healthy);
+    assert_eq!(
+        health.scrub.as_deref(),
+        Some("finished, started Sun Mar  8 00:25:03 2026")
+    );
+    assert_eq!(
+        health.errors,
+        [
+            "/dev/sdb: 4 read_io_errs",
+            "/dev/sdb: 1 corruption_errs",
+            "scrub found csum=1"
+        ]
+    );
+
+    let clean = parse_btrfs(
+        "[/dev/sdb].read_io_errs 0\nscrub status for 8a8f\n\
+         \tscrub started at Sun Mar  8 00:25:03 2026 and finished after 00:01:02\n",
+    );
+    assert!(clean.healthy);
+    assert_eq!(clean.state, "no errors");
+    assert_eq!(
+        clean.scrub.as_deref(),
+        Some("scrub started at Sun Mar  8 00:25:03 2026 and finished after 00:01:02")
+    );
+}
+
+#[test]
+fn tools_of_zfs_and_btrfs_pools() {
+    let dir = Path::new("/var/lib/lxd");
+    let none = HashMap::new();
+    let dataset = HashMap::from([("zfs.pool_name".to_string(), "tank/lxd".to_string())]);
+    assert_eq!(
+        Tool::for_pool("zfs", "default", &dataset, dir),
+        Some(Tool::Zfs {
+            zpool: "tank".to_string()
+        })
+    );
+    let fast = Tool::for_pool("zfs", "fast", &none, dir).unwrap();
+    assert_eq!(
+        fast.health_commands(true),
+        [["zpool", "status", "-v", "fast"]]
+    );
+    assert_eq!(fast.scrub_command(Some("sudo")), "sudo zpool scrub fast");
+    let odd = Tool::Zfs {
+        zpool: "tank; rm -rf ~".to_string(),
+    };
+    assert_eq!(odd.scrub_command(None), "zpool scrub 'tank; rm -rf ~'");
+    let quote = Tool::Zfs {
+        zpool: "it's".to_string(),
+    };
+    assert_eq!(quote.scrub_command(None), "zpool scrub 'it'\\''s'");
+
+    let btrfs = Tool::for_pool("btrfs", "bulk", &none, dir).unwrap();
+    assert_eq!(
+        btrfs,
+        Tool::Btrfs {
+            mountpoint: PathBuf::from("/var/lib/lxd/storage-pools/bulk")
+        }
+    );
+    assert_eq!(
+        btrfs.health_commands(true),
+        [
+            [
+                "sudo",
+                "-n",
+                "btrfs",
+                "device",
+                "stats",
+                "/var/lib/lxd/storage-pools/bulk"
+            ],
+            [
+                "sudo",
+                "-n",
+                "btrfs",
+                "scrub",
+                "status",
+                "/var/lib/lxd/storage-pools/bulk"
+            ]
+        ]
+    );
+    assert_eq!(
+        btrfs.scrub_command(None),
+        "btrfs scrub start /var/lib/lxd/storage-pools/bulk"
+    );
+    assert_eq!(Tool::for_pool("dir", "default", &none, dir), None);
+}
+
+#[test]
+fn pools_keep_their_unresolved_warnings() {
+    let warnings = [
+        warning(
+            "/1.0/storage-pools/fast",
+            "new",
+            "Failed mounting",
+            "2026-03-08T00:10:00Z",
+        ),
+        warning(
+            "/1.0/storage-pools/fast",
+            "resolved",
+            "Old",
+            "2026-03-01T00:00:00Z",
+        ),
+        warning(
+            "/1.0/storage-pools/fast?project=default",
+            "acknowledged",
+            "Pool is 95% full",
+            "2026-03-09T12:30:45Z",
+        ),
+        warning(
+            "/1.0/storage-pools/faster",
+            "new",
+            "Other pool",
+            "2026-03-09T00:00:00Z",
+        ),
+    ];
+    let space = LxdStorageSpace { used: 1, total: 2 };
+    let fast = Pool::new(pool("fast", "zfs", &[]), space, &warnings, Path::new("/"));
+    let messages: Vec<&str> = fast.warnings.iter().map(|w| w.message.as_str()).collect();
+    assert_eq!(messages, ["Pool is 95% full", "Failed mounting"]);
+    assert_eq!(fast.warnings[0].last_seen, "2026-03-09 12:30");
+    assert!(fast.troubled(None));
+
+    let mut view = PoolsView::new(true);
+    view.set_pools(vec![
+        Pool::new(
+            pool("default", "dir", &[]),
+            LxdStorageSpace::default(),
+            &[],
+            Path::new("/"),
+        ),
+        fast.clone(),
+    ]);
+    assert!(!view.pools[0].troubled(None));
+    view.select(true);
+    view.set_pools(vec![fast]);
+    assert_eq!(view.selected_pool().unwrap().name, "fast");
+}
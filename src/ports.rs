@@ -0,0 +1,120 @@
+//! Published ports
+//!
+//! Reads proxy devices and network forwards into host→instance mappings for
+//! the Ports column and the watch view.
+
+use crate::lxd_api::LxdNetworkForward;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Host addresses that mean every address, left out of the listing
+const WILDCARDS: [&str; 3] = ["", "0.0.0.0", "[::]"];
+
+/// Traffic reaching the instance from the host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Port {
+    pub protocol: String, // tcp, udp or unix; "any" for a forward's default target
+    pub listen: String,   // Host side: "8080", "192.0.2.1:80" or a socket path
+    pub target: String,   // Instance side: "80" or a socket path
+}
+
+impl fmt::Display for Port {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}→{}", self.listen, self.target)?;
+        // Socket paths speak for themselves
+        if self.protocol != "tcp" && self.protocol != "unix" {
+            write!(f, "/{}", self.protocol)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ports an instance's proxy devices publish, in device name order
+pub fn proxied(devices: &HashMap<String, HashMap<String, String>>) -> Vec<Port> {
+    let mut devices: Vec<_> = devices.iter().collect();
+    devices.sort_by(|a, b| a.0.cmp(b.0));
+    devices
+        .into_iter()
+        .filter_map(|(_, device)| proxy(device))
+        .collect()
+}
+
+fn proxy(device: &HashMap<String, String>) -> Option<Port> {
+    if device.get("type").map(String::as_str) != Some("proxy") {
+        return None;
+    }
+    let (protocol, listen) = device.get("listen")?.split_once(':')?;
+    let (_, connect) = device.get("connect")?.split_once(':')?;
+    if protocol == "unix" {
+        return Some(Port {
+            protocol: protocol.to_string(),
+            listen: listen.to_string(),
+            target: connect.to_string(),
+        });
+    }
+    let listen = match listen.rsplit_once(':') {
+        Some((host, port)) if WILDCARDS.contains(&host) => port,
+        _ => listen,
+    };
+    // The instance side is nearly always its loopback address
+    let target = connect.rsplit_once(':').map_or(connect, |(_, port)| port);
+    Some(Port {
+        protocol: protocol.to_string(),
+        listen: listen.to_string(),
+        target: target.to_string(),
+    })
+}
+
+/// Ports the network forwards send on to one of `addresses`
+pub fn forwarded(forwards: &[LxdNetworkForward], addresses: &[String]) -> Vec<Port> {
+    let mut ports = Vec::new();
+    for forward in forwards {
+        let host = if forward.listen_address.contains(':') {
+            format!("[{}]", forward.listen_address)
+        } else {
+            forward.listen_address.clone()
+        };
+        for port in &forward.ports {
+            if !addresses.contains(&port.target_address) {
+                continue;
+            }
+            // Without a target port, traffic keeps the port it came in on
+            let target = if port.target_port.is_empty() {
+                &port.listen_port
+            } else {
+                &port.target_port
+            };
+            ports.push(Port {
+                protocol: port.protocol.clone(),
+                listen: format!("{}:{}", host, port.listen_port),
+                target: target.clone(),
+            });
+        }
+        // Whatever no port sends elsewhere goes to the default target
+        if forward
+            .config
+            .get("target_address")
+            .is_some_and(|target| addresses.contains(target))
+        {
+            ports.push(Port {
+                protocol: "any".to_string(),
+                listen: format!("{}:*", host),
+                target: "*".to_string(),
+            });
+        }
+    }
+    ports
+}
+
+/// The ports as one line, like "8080→80, 192.0.2.1:53→53/udp"
+pub fn summary(ports: &[Port]) -> String {
+    ports
+        .iter()
+        .map(Port::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests;
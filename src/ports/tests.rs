@@ -0,0 +1,85 @@
+//! Tests of published ports
+
+use super::{forwarded, proxied, summary};
+use crate::lxd_api::{LxdForwardPort, LxdNetworkForward};
+use std::collections::HashMap;
+
+fn device(options: &[(&str, &str)]) -> HashMap<String, String> {
+    options
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn reads_the_host_and_instance_side_of_proxy_devices() {
+    let devices = HashMap::from([
+        (
+            "web".to_string(),
+            device(&[
+                ("type", "proxy"),
+                ("listen", "tcp:0.0.0.0:8080"),
+                ("connect", "tcp:127.0.0.1:80"),
+            ]),
+        ),
+        (
+            "dns".to_string(),
+            device(&[
+                ("type", "proxy"),
+                ("listen", "udp:10.0.0.1:53"),
+                ("connect", "udp:127.0.0.1:5353"),
+            ]),
+        ),
+        (
+            "x11".to_string(),
+            device(&[
+                ("type", "proxy"),
+                ("listen", "unix:/tmp/.X11-unix/X0"),
+                ("connect", "unix:/tmp/.X11-unix/X0"),
+            ]),
+        ),
+        (
+            "eth0".to_string(),
+            device(&[("type", "nic"), ("network", "lxdbr0")]),
+        ),
+    ]);
+
+    assert_eq!(
+        summary(&proxied(&devices)),
+        "10.0.0.1:53→5353/udp, 8080→80, /tmp/.X11-unix/X0→/tmp/.X11-unix/X0"
+    );
+}
+
+#[test]
+fn keeps_the_forwarded_ports_that_reach_the_instance() {
+    let forward = LxdNetworkForward {
+        listen_address: "192.0.2.1".to_string(),
+        config: HashMap::from([("target_address".to_string(), "10.0.0.5".to_string())]),
+        ports: vec![
+            LxdForwardPort {
+                protocol: "tcp".to_string(),
+                listen_port: "443".to_string(),
+                target_address: "10.0.0.4".to_string(),
+                target_port: "8443".to_string(),
+            },
+            LxdForwardPort {
+                protocol: "udp".to_string(),
+                listen_port: "51820".to_string(),
+                target_address: "10.0.0.5".to_string(),
+                target_port: String::new(),
+            },
+        ],
+    };
+
+    assert_eq!(
+        summary(&forwarded(
+            std::slice::from_ref(&forward),
+            &["10.0.0.4".to_string()]
+        )),
+        "192.0.2.1:443→8443"
+    );
+    assert_eq!(
+        summary(&forwarded(&[forward], &["10.0.0.5".to_string()])),
+        "192.0.2.1:51820→51820/udp, 192.0.2.1:*→*/any"
+    );
+}
@@ -0,0 +1,120 @@
+//! Power schedules
+//!
+//! Scheduled starts and stops of instances, and the skips and holds from the
+//! container menu that leave them out until LXTUI exits.
+
+use crate::config::{ScheduleConfig, ScheduledAction};
+use crate::schedule::Schedule;
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+
+/// A scheduled start or stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub action: ScheduledAction,
+    pub at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Override {
+    Skip(NaiveDateTime), // The run due in that minute
+    Hold,
+}
+
+/// Whether `instance` has a start or stop schedule
+pub fn scheduled(schedules: &[(ScheduleConfig, Schedule)], instance: &str) -> bool {
+    schedules
+        .iter()
+        .any(|(job, _)| job.action.is_power() && job.instance == instance)
+}
+
+/// The first start or stop of `instance` scheduled after the minute
+/// containing `time`
+pub fn next_run(
+    schedules: &[(ScheduleConfig, Schedule)],
+    instance: &str,
+    time: &NaiveDateTime,
+) -> Option<Run> {
+    schedules
+        .iter()
+        .filter(|(job, _)| job.action.is_power() && job.instance == instance)
+        .filter_map(|(job, schedule)| {
+            Some(Run {
+                action: job.action,
+                at: schedule.next_after(time)?,
+            })
+        })
+        .min_by_key(|run| run.at)
+}
+
+/// The schedule column: the next start or stop, with the day when it isn't
+/// today, and whether it is skipped or held
+pub fn label(next: Option<Run>, overridden: Option<Override>, now: &NaiveDateTime) -> String {
+    if overridden == Some(Override::Hold) {
+        return "held".to_string();
+    }
+    let Some(run) = next else {
+        return "-".to_string();
+    };
+    let at = if run.at.date() == now.date() {
+        run.at.format("%H:%M").to_string()
+    } else {
+        run.at.format("%a %H:%M").to_string()
+    };
+    match overridden {
+        Some(Override::Skip(skipped)) if skipped == run.at => {
+            format!("skip {} {}", run.action.name(), at)
+        }
+        _ => format!("{} {}", run.action.name(), at),
+    }
+}
+
+/// Skips and holds, by instance as schedules name it
+#[derive(Debug, Default)]
+pub struct PowerOverrides {
+    overrides: HashMap<String, Override>,
+}
+
+impl PowerOverrides {
+    pub fn get(&self, instance: &str) -> Option<Override> {
+        self.overrides.get(instance).copied()
+    }
+
+    /// Skip `run`, or go back to running it when it is skipped already.
+    /// Lifts a hold. Returns whether `run` is skipped now.
+    pub fn toggle_skip(&mut self, instance: &str, run: &Run) -> bool {
+        if self.get(instance) == Some(Override::Skip(run.at)) {
+            self.overrides.remove(instance);
+            return false;
+        }
+        self.overrides
+            .insert(instance.to_string(), Override::Skip(run.at));
+        true
+    }
+
+    /// Hold `instance`, or lift its hold. Returns whether it is held now.
+    pub fn toggle_hold(&mut self, instance: &str) -> bool {
+        if self.get(instance) == Some(Override::Hold) {
+            self.overrides.remove(instance);
+            return false;
+        }
+        self.overrides.insert(instance.to_string(), Override::Hold);
+        true
+    }
+
+    /// Whether a start or stop of `instance` due in `minute` goes ahead. A
+    /// skip is used up by the run it skips, or dropped once that has passed.
+    pub fn allows(&mut self, instance: &str, minute: &NaiveDateTime) -> bool {
+        match self.get(instance) {
+            Some(Override::Hold) => false,
+            Some(Override::Skip(at)) if at <= *minute => {
+                self.overrides.remove(instance);
+                at != *minute
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
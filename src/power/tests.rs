@@ -0,0 +1,142 @@
+//! Tests of power schedules and their overrides
+
+use super::{label, next_run, scheduled, Override, PowerOverrides, Run};
+use crate::config::{ScheduleConfig, ScheduledAction};
+use crate::schedule::Schedule;
+use chrono::{NaiveDate, NaiveDateTime};
+
+fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    // 2026-03-09 is a Monday
+    NaiveDate::from_ymd_opt(2026, 3, day)
+        .unwrap()
+        .and_hms_opt(hour, minute, 0)
+        .unwrap()
+}
+
+fn job(instance: &str, action: ScheduledAction, schedule: &str) -> (ScheduleConfig, Schedule) {
+    (
+        ScheduleConfig {
+            instance: instance.to_string(),
+            action,
+            schedule: schedule.to_string(),
+        },
+        Schedule::parse(schedule).unwrap(),
+    )
+}
+
+/// Stop dev1 at 20:00 and start it at 08:00 on weekdays
+fn office_hours() -> Vec<(ScheduleConfig, Schedule)> {
+    vec![
+        job("dev1", ScheduledAction::Stop, "0 20 * * 1-5"),
+        job("dev1", ScheduledAction::Start, "0 8 * * 1-5"),
+        job("dev1", ScheduledAction::Snapshot, "30 * * * *"),
+        job("web1", ScheduledAction::Backup, "@daily"),
+    ]
+}
+
+#[test]
+fn schedule_finds_its_next_minute() {
+    let (_, weekdays) = job("dev1", ScheduledAction::Stop, "0 20 * * 1-5");
+    assert_eq!(weekdays.next_after(&at(9, 12, 0)), Some(at(9, 20, 0)));
+    // Not the minute it fires in itself
+    assert_eq!(weekdays.next_after(&at(9, 20, 0)), Some(at(10, 20, 0)));
+    // Friday evening to Monday
+    assert_eq!(weekdays.next_after(&at(13, 21, 0)), Some(at(16, 20, 0)));
+
+    let (_, monthly) = job("dev1", ScheduledAction::Stop, "15 3 1 * *");
+    assert_eq!(
+        monthly.next_after(&at(9, 12, 0)),
+        NaiveDate::from_ymd_opt(2026, 4, 1)
+            .unwrap()
+            .and_hms_opt(3, 15, 0)
+    );
+    let (_, never) = job("dev1", ScheduledAction::Stop, "0 0 31 2 *");
+    assert_eq!(never.next_after(&at(9, 12, 0)), None);
+}
+
+#[test]
+fn next_run_is_the_earliest_start_or_stop() {
+    let schedules = office_hours();
+    assert!(scheduled(&schedules, "dev1"));
+    assert!(!scheduled(&schedules, "web1"));
+
+    let stop = Run {
+        action: ScheduledAction::Stop,
+        at: at(9, 20, 0),
+    };
+    // Snapshots at half past don't count
+    assert_eq!(next_run(&schedules, "dev1", &at(9, 12, 0)), Some(stop));
+    assert_eq!(
+        next_run(&schedules, "dev1", &at(9, 21, 0)),
+        Some(Run {
+            action: ScheduledAction::Start,
+            at: at(10, 8, 0),
+        })
+    );
+    assert_eq!(next_run(&schedules, "web1", &at(9, 12, 0)), None);
+}
+
+#[test]
+fn label_shows_the_next_run_and_overrides() {
+    let now = at(9, 12, 0);
+    let stop = Run {
+        action: ScheduledAction::Stop,
+        at: at(9, 20, 0),
+    };
+    let start = Run {
+        action: ScheduledAction::Start,
+        at: at(10, 8, 0),
+    };
+    assert_eq!(label(Some(stop), None, &now), "stop 20:00");
+    assert_eq!(label(Some(start), None, &now), "start Tue 08:00");
+    assert_eq!(
+        label(Some(stop), Some(Override::Skip(stop.at)), &now),
+        "skip stop 20:00"
+    );
+    // A skip of a run gone by shows nothing
+    assert_eq!(
+        label(Some(start), Some(Override::Skip(stop.at)), &now),
+        "start Tue 08:00"
+    );
+    assert_eq!(label(Some(stop), Some(Override::Hold), &now), "held");
+    assert_eq!(label(None, None, &now), "-");
+}
+
+#[test]
+fn a_skip_lets_one_run_go_by() {
+    let mut overrides = PowerOverrides::default();
+    let stop = Run {
+        action: ScheduledAction::Stop,
+        at: at(9, 20, 0),
+    };
+
+    assert!(overrides.toggle_skip("dev1", &stop));
+    assert!(overrides.allows("dev1", &at(9, 19, 0)));
+    assert!(overrides.allows("web1", &at(9, 20, 0)));
+    assert!(!overrides.allows("dev1", &at(9, 20, 0)));
+    // Used up
+    assert_eq!(overrides.get("dev1"), None);
+    assert!(overrides.allows("dev1", &at(10, 20, 0)));
+
+    // Toggled back before it came
+    assert!(overrides.toggle_skip("dev1", &stop));
+    assert!(!overrides.toggle_skip("dev1", &stop));
+    assert!(overrides.allows("dev1", &at(9, 20, 0)));
+
+    // One that went by unchecked is dropped
+    overrides.toggle_skip("dev1", &stop);
+    assert!(overrides.allows("dev1", &at(10, 8, 0)));
+    assert_eq!(overrides.get("dev1"), None);
+}
+
+#[test]
+fn a_hold_leaves_the_instance_alone_until_lifted() {
+    let mut overrides = PowerOverrides::default();
+    assert!(overrides.toggle_hold("dev1"));
+    assert!(!overrides.allows("dev1", &at(9, 20, 0)));
+    assert!(!overrides.allows("dev1", &at(10, 8, 0)));
+    assert_eq!(overrides.get("dev1"), Some(Override::Hold));
+
+    assert!(!overrides.toggle_hold("dev1"));
+    assert!(overrides.allows("dev1", &at(9, 20, 0)));
+}
@@ -0,0 +1,118 @@
+//! Pre-flight checks before deleting an instance
+//!
+//! Reads the instance before the delete confirmation opens, so the modal
+//! can list why LXD would refuse the delete and what goes with the
+//! instance or stays behind.
+
+use crate::lxd_api::LxdContainer;
+use std::collections::HashMap;
+
+/// Config key that makes LXD refuse to delete an instance
+pub const PROTECTION_KEY: &str = "security.protection.delete";
+
+/// Snapshots named in a warning before the rest are counted
+const NAMED_SNAPSHOTS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    Protected,       // PROTECTION_KEY is set; removing it is offered
+    Blocker(String), // LXD will refuse the delete
+    Warning(String), // The delete goes ahead, but know this first
+}
+
+impl Check {
+    pub fn message(&self) -> &str {
+        match self {
+            Check::Protected => "security.protection.delete is set; LXD refuses to delete it",
+            Check::Blocker(message) | Check::Warning(message) => message,
+        }
+    }
+
+    pub fn blocks(&self) -> bool {
+        !matches!(self, Check::Warning(_))
+    }
+}
+
+/// Whether an instance's expanded config protects it from deletion
+pub fn protected(config: &HashMap<String, String>) -> bool {
+    config.get(PROTECTION_KEY).is_some_and(|v| v == "true")
+}
+
+/// Whether `instance` is protected, from its own config or a profile's
+pub fn instance_protected(instance: &LxdContainer) -> bool {
+    protected(
+        instance
+            .expanded_config
+            .as_ref()
+            .unwrap_or(&instance.config),
+    )
+}
+
+/// Checks for deleting `instance`, which has `snapshots`; blockers first
+pub fn delete_checks(instance: &LxdContainer, snapshots: &[String]) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if instance.status != "Stopped" {
+        checks.push(Check::Blocker(format!(
+            "It is {}; LXD only deletes stopped instances",
+            instance.status.to_lowercase()
+        )));
+    }
+    if instance_protected(instance) {
+        checks.push(Check::Protected);
+    }
+
+    if !snapshots.is_empty() {
+        let mut names = snapshots
+            .iter()
+            .take(NAMED_SNAPSHOTS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if snapshots.len() > NAMED_SNAPSHOTS {
+            names.push_str(&format!(" and {} more", snapshots.len() - NAMED_SNAPSHOTS));
+        }
+        let plural = if snapshots.len() == 1 { "" } else { "s" };
+        checks.push(Check::Warning(format!(
+            "{} snapshot{} deleted with it: {}",
+            snapshots.len(),
+            plural,
+            names
+        )));
+    }
+
+    let devices = instance
+        .expanded_devices
+        .as_ref()
+        .unwrap_or(&instance.devices);
+    for (source, pool, path) in custom_volumes(devices) {
+        checks.push(Check::Warning(format!(
+            "Custom volume {} on {} (at {}) is kept, detached",
+            source, pool, path
+        )));
+    }
+    checks
+}
+
+/// Source, pool and mount path of each custom storage volume attached as a
+/// disk, by device name. The root disk has a pool but no source, and a host
+/// directory's source is an absolute path.
+fn custom_volumes(devices: &HashMap<String, HashMap<String, String>>) -> Vec<(&str, &str, &str)> {
+    let mut names: Vec<&String> = devices.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let device = &devices[name];
+            let source = device.get("source")?;
+            if device.get("type").map(String::as_str) != Some("disk") || source.starts_with('/') {
+                return None;
+            }
+            let pool = device.get("pool")?;
+            let path = device.get("path").map_or("-", String::as_str);
+            Some((source.as_str(), pool.as_str(), path))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
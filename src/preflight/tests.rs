@@ -0,0 +1,51 @@
+//! Tests of checking an instance before it is deleted
+
+use super::{delete_checks, Check};
+use crate::compare::tests::instance;
+use serde_json::json;
+use std::collections::HashMap;
+
+#[test]
+fn a_stopped_instance_without_extras_deletes_cleanly() {
+    let mut web1 = instance("web1", &["default"], json!({}));
+    web1.status = "Stopped".to_string();
+    assert!(delete_checks(&web1, &[]).is_empty());
+}
+
+#[test]
+fn lists_what_blocks_the_delete_and_what_it_affects() {
+    let mut db1 = instance(
+        "db1",
+        &["default"],
+        json!({"security.protection.delete": "true"}),
+    );
+    let volume = |source: &str, path: &str| {
+        HashMap::from([
+            ("type".to_string(), "disk".to_string()),
+            ("pool".to_string(), "fast".to_string()),
+            ("source".to_string(), source.to_string()),
+            ("path".to_string(), path.to_string()),
+        ])
+    };
+    let devices = db1.expanded_devices.as_mut().unwrap();
+    devices.insert("data".to_string(), volume("pgdata", "/var/lib/postgresql"));
+    devices.insert("logs".to_string(), volume("/srv/logs", "/var/log"));
+    let snapshots: Vec<String> = ["snap0", "snap1", "snap2", "before-upgrade", "nightly"]
+        .map(str::to_string)
+        .to_vec();
+
+    assert_eq!(
+        delete_checks(&db1, &snapshots),
+        [
+            Check::Blocker("It is running; LXD only deletes stopped instances".to_string()),
+            Check::Protected,
+            Check::Warning(
+                "5 snapshots deleted with it: snap0, snap1, snap2 and 2 more".to_string()
+            ),
+            Check::Warning(
+                "Custom volume pgdata on fast (at /var/lib/postgresql) is kept, detached"
+                    .to_string()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,187 @@
+//! Container health probes
+//!
+//! Runs the `user.lxtui.healthcheck` command of running instances through
+//! LXD's exec API; exit status 0 within the timeout is healthy.
+
+use crate::config::ProbesConfig;
+use crate::lxc::{Container, LxcClient};
+use crate::theme::Tone;
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration, Instant};
+
+pub const CONFIG_KEY: &str = "user.lxtui.healthcheck";
+
+/// Lines of output kept from each probe, the last ones written
+const OUTPUT_LINES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    Healthy,
+    Unhealthy,
+}
+
+impl Health {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Health::Healthy => "Healthy",
+            Health::Unhealthy => "Unhealthy",
+        }
+    }
+
+    pub fn tone(&self) -> Tone {
+        match self {
+            Health::Healthy => Tone::Good,
+            Health::Unhealthy => Tone::Bad,
+        }
+    }
+}
+
+/// Outcome of one probe
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    pub health: Health,
+    pub output: String, // What the command wrote, or why it couldn't run
+    pub at: Instant,
+}
+
+/// Run `command` in the instance `name` and judge its health
+pub async fn run(client: LxcClient, name: &str, command: &str, limit: Duration) -> Probe {
+    let argv = ["sh".to_string(), "-c".to_string(), command.to_string()];
+    let (health, output) = match timeout(limit, client.exec_output(name, &argv)).await {
+        Ok(Ok(output)) => {
+            let health = if output.status == 0 {
+                Health::Healthy
+            } else {
+                Health::Unhealthy
+            };
+            let mut text = output.stdout + &output.stderr;
+            if output.status != 0 {
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&format!("(exit status {})", output.status));
+            }
+            (health, last_lines(&text))
+        }
+        Ok(Err(e)) => (Health::Unhealthy, e.to_string()),
+        Err(_) => (
+            Health::Unhealthy,
+            format!("No answer within {}s", limit.as_secs()),
+        ),
+    };
+    Probe {
+        health,
+        output,
+        at: Instant::now(),
+    }
+}
+
+fn last_lines(text: &str) -> String {
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(OUTPUT_LINES)..].join("\n")
+}
+
+/// Probes of every instance with a health check, by qualified name
+#[derive(Debug)]
+pub struct Probes {
+    config: ProbesConfig,
+    tx: mpsc::UnboundedSender<(String, Probe)>,
+    rx: mpsc::UnboundedReceiver<(String, Probe)>,
+    started: HashMap<String, Instant>, // When the latest probe of each began
+    running: HashSet<String>,
+    last: HashMap<String, Probe>,
+}
+
+impl Probes {
+    pub fn new(config: ProbesConfig) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Probes {
+            config,
+            tx,
+            rx,
+            started: HashMap::new(),
+            running: HashSet::new(),
+            last: HashMap::new(),
+        }
+    }
+
+    /// Longest a probe may take
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs.max(1))
+    }
+
+    /// The instances in a refreshed list due for a probe, with their
+    /// commands; they count as probed from `now`. Results of instances no
+    /// longer running or probed are dropped.
+    pub fn due(&mut self, containers: &[Container], now: Instant) -> Vec<(String, String)> {
+        let probed: HashMap<String, &str> = containers
+            .iter()
+            .filter(|c| c.status == "Running")
+            .filter_map(|c| Some((c.qualified_name(), c.healthcheck.as_deref()?)))
+            .collect();
+        self.last.retain(|name, _| probed.contains_key(name));
+        self.started.retain(|name, _| probed.contains_key(name));
+
+        let interval = Duration::from_secs(self.config.interval_secs.max(1));
+        let mut due = Vec::new();
+        for (name, command) in probed {
+            let waited = self
+                .started
+                .get(&name)
+                .is_none_or(|at| now.duration_since(*at) >= interval);
+            if waited && !self.running.contains(&name) {
+                self.started.insert(name.clone(), now);
+                self.running.insert(name.clone());
+                due.push((name, command.to_string()));
+            }
+        }
+        due.sort();
+        due
+    }
+
+    /// Where probes report their results
+    pub fn sender(&self) -> mpsc::UnboundedSender<(String, Probe)> {
+        self.tx.clone()
+    }
+
+    /// Take in finished probes, logging changes of health
+    pub fn drain(&mut self) {
+        while let Ok((name, probe)) = self.rx.try_recv() {
+            self.running.remove(&name);
+            // Stopped or no longer probed while the probe ran
+            if !self.started.contains_key(&name) {
+                continue;
+            }
+            let before = self.last.get(&name).map(|p| p.health);
+            if before != Some(probe.health) {
+                match probe.health {
+                    Health::Healthy => info!("{} is healthy", name),
+                    Health::Unhealthy => warn!("{} is unhealthy: {}", name, probe.output),
+                }
+            }
+            self.last.insert(name, probe);
+        }
+    }
+
+    /// The latest probe of an instance, by qualified name
+    pub fn get(&self, name: &str) -> Option<&Probe> {
+        self.last.get(name)
+    }
+
+    /// Instances whose latest probe failed, by qualified name
+    pub fn unhealthy(&self) -> Vec<(&str, &Probe)> {
+        let mut unhealthy: Vec<(&str, &Probe)> = self
+            .last
+            .iter()
+            .filter(|(_, probe)| probe.health == Health::Unhealthy)
+            .map(|(name, probe)| (name.as_str(), probe))
+            .collect();
+        unhealthy.sort_by_key(|(name, _)| *name);
+        unhealthy
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,107 @@
+//! Tests of scheduling health probes and running them against the fake
+//! LXD server
+
+use super::{run, Health, Probe, Probes};
+use crate::command::tests::container;
+use crate::config::ProbesConfig;
+use crate::fake_lxd::FakeLxd;
+use crate::lxc::Container;
+use tokio::time::{Duration, Instant};
+
+fn probed(name: &str, status: &str) -> Container {
+    Container {
+        healthcheck: Some("curl -fs localhost".to_string()),
+        ..container(name, status, "container")
+    }
+}
+
+fn probe(health: Health) -> Probe {
+    Probe {
+        health,
+        output: String::new(),
+        at: Instant::now(),
+    }
+}
+
+#[test]
+fn probes_running_instances_once_per_interval() {
+    let mut probes = Probes::new(ProbesConfig {
+        interval_secs: 30,
+        timeout_secs: 5,
+    });
+    let now = Instant::now();
+    let list = [
+        probed("web1", "Running"),
+        probed("web2", "Stopped"),
+        container("db1", "Running", "container"),
+    ];
+
+    let due = probes.due(&list, now);
+    assert_eq!(
+        due,
+        [("web1".to_string(), "curl -fs localhost".to_string())]
+    );
+
+    // Not again while it runs, nor before the interval is up
+    assert!(probes.due(&list, now + Duration::from_secs(40)).is_empty());
+    probes
+        .sender()
+        .send(("web1".to_string(), probe(Health::Healthy)))
+        .unwrap();
+    probes.drain();
+    assert_eq!(probes.get("web1").unwrap().health, Health::Healthy);
+    assert!(probes.due(&list, now + Duration::from_secs(10)).is_empty());
+    assert_eq!(probes.due(&list, now + Duration::from_secs(30)).len(), 1);
+}
+
+#[test]
+fn forgets_instances_that_stop() {
+    let mut probes = Probes::new(ProbesConfig::default());
+    let now = Instant::now();
+    probes.due(&[probed("web1", "Running")], now);
+    probes
+        .sender()
+        .send(("web1".to_string(), probe(Health::Unhealthy)))
+        .unwrap();
+    probes.drain();
+    assert!(probes.get("web1").is_some());
+
+    // A probe finishing after the instance stopped is dropped too
+    probes.due(&[probed("web1", "Running")], now + Duration::from_secs(60));
+    probes.due(&[probed("web1", "Stopped")], now + Duration::from_secs(61));
+    assert!(probes.get("web1").is_none());
+    probes
+        .sender()
+        .send(("web1".to_string(), probe(Health::Healthy)))
+        .unwrap();
+    probes.drain();
+    assert!(probes.get("web1").is_none());
+}
+
+#[tokio::test]
+async fn runs_the_command_through_exec() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_exec("web1", 0, "ok\n")
+        .with_instance("web2", "Running", None)
+        .with_exec("web2", 7, "connection refused\n")
+        .with_instance("web3", "Stopped", None);
+    let limit = Duration::from_secs(5);
+
+    let healthy = run(lxd.client(), "web1", "curl -fs localhost", limit).await;
+    assert_eq!(
+        (healthy.health, healthy.output.as_str()),
+        (Health::Healthy, "ok")
+    );
+
+    let unhealthy = run(lxd.client(), "web2", "curl -fs localhost", limit).await;
+    assert_eq!(unhealthy.health, Health::Unhealthy);
+    assert_eq!(unhealthy.output, "connection refused\n(exit status 7)");
+
+    let stopped = run(lxd.client(), "web3", "true", limit).await;
+    assert_eq!(stopped.health, Health::Unhealthy);
+    assert!(stopped.output.contains("not running"));
+
+    // The recorded output doesn't pile up on the server
+    assert!(lxd.exec_logs().is_empty());
+}
@@ -0,0 +1,210 @@
+//! Publishing instances as images
+//!
+//! Packs a stopped instance into an image, as `lxc publish` does, with its
+//! alias, expiry and properties edited as YAML first.
+
+use crate::edit;
+use crate::lxd_api::LxdContainer;
+use crate::store::StoredImage;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Properties filled in from the instance's `image.*` config
+pub const PROPERTIES: [&str; 3] = ["os", "release", "description"];
+
+const HEADER: &str = "\
+### Publishing an instance makes an image of it in the server's store.
+### Lines starting with '#' are ignored.
+###
+### alias: a name to create instances from the image by; empty for none
+### public: whether clients without a trust relationship may use it
+### expires: never, a date (2026-12-31), or a time from now (12h, 30d, 4w)
+###   after which LXD deletes the image
+###
+### Saving the file publishes the image; saving it unchanged or invalid
+### publishes nothing.
+";
+
+#[derive(Debug, Error)]
+pub enum PublishError {
+    #[error("Invalid YAML: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("'{0}' must be a single value, not a list or mapping")]
+    NotScalar(String),
+    #[error("Alias '{0}' can't contain spaces or ':'")]
+    InvalidAlias(String),
+    #[error("Expiry '{0}' isn't never, a date like 2026-12-31, or a time like 30d")]
+    InvalidExpiry(String),
+    #[error("Expiry {0} has already passed")]
+    PastExpiry(String),
+}
+
+/// The image to publish an instance as
+#[derive(Debug, Clone, PartialEq)]
+pub struct Publish {
+    pub alias: Option<String>,
+    pub public: bool,
+    pub expires_at: Option<DateTime<Local>>, // None never expires
+    pub properties: BTreeMap<String, String>,
+}
+
+/// The file as first written
+#[derive(Serialize)]
+struct Unedited {
+    alias: &'static str,
+    public: bool,
+    expires: &'static str,
+    properties: BTreeMap<String, String>,
+}
+
+/// The file as typed
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Edited {
+    #[serde(default)]
+    alias: Option<String>,
+    #[serde(default)]
+    public: bool,
+    #[serde(default)]
+    expires: Option<Value>,
+    #[serde(default)]
+    properties: BTreeMap<String, Value>,
+}
+
+impl Publish {
+    /// The file handed to the editor for publishing `instance`
+    pub fn to_yaml(instance: &LxdContainer) -> Result<String, PublishError> {
+        let properties = PROPERTIES
+            .iter()
+            .map(|property| {
+                let key = format!("image.{}", property);
+                let value = instance.config.get(&key).cloned().unwrap_or_default();
+                (property.to_string(), value)
+            })
+            .collect();
+        let file = Unedited {
+            alias: "",
+            public: false,
+            expires: "never",
+            properties,
+        };
+        Ok(format!("{}{}", HEADER, serde_yaml::to_string(&file)?))
+    }
+
+    /// Validate an edited file, taking times from now as from `now`. Empty
+    /// properties are left out.
+    pub fn parse(text: &str, now: DateTime<Local>) -> Result<Self, PublishError> {
+        let edited: Edited = serde_yaml::from_str(text)?;
+
+        let alias = edited
+            .alias
+            .map(|alias| alias.trim().to_string())
+            .filter(|alias| !alias.is_empty());
+        if let Some(alias) = alias
+            .as_ref()
+            .filter(|a| a.contains(|c: char| c.is_whitespace() || c == ':'))
+        {
+            return Err(PublishError::InvalidAlias(alias.clone()));
+        }
+
+        let expires = match edited.expires {
+            Some(value) => scalar("expires", value)?,
+            None => String::new(),
+        };
+        let expires_at = parse_expiry(&expires, now)?;
+
+        let mut properties = BTreeMap::new();
+        for (key, value) in edited.properties {
+            let value = scalar(&format!("properties.{}", key), value)?;
+            if !value.trim().is_empty() {
+                properties.insert(key, value);
+            }
+        }
+
+        Ok(Publish {
+            alias,
+            public: edited.public,
+            expires_at,
+            properties,
+        })
+    }
+}
+
+fn scalar(key: &str, value: Value) -> Result<String, PublishError> {
+    edit::scalar(key, value).map_err(|_| PublishError::NotScalar(key.to_string()))
+}
+
+/// When an image set to expire at `text` expires: never, at the start of a
+/// date, or a number of hours, days or weeks after `now`
+pub fn parse_expiry(
+    text: &str,
+    now: DateTime<Local>,
+) -> Result<Option<DateTime<Local>>, PublishError> {
+    let text = text.trim();
+    if text.is_empty() || text.eq_ignore_ascii_case("never") {
+        return Ok(None);
+    }
+    let invalid = || PublishError::InvalidExpiry(text.to_string());
+
+    let at = if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+            .ok_or_else(invalid)?
+    } else {
+        let unit = text.chars().last().ok_or_else(invalid)?;
+        let count: i64 = text[..text.len() - unit.len_utf8()]
+            .parse()
+            .map_err(|_| invalid())?;
+        let delta = match unit {
+            'h' => TimeDelta::try_hours(count),
+            'd' => TimeDelta::try_days(count),
+            'w' => TimeDelta::try_weeks(count),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+        now.checked_add_signed(delta).ok_or_else(invalid)?
+    };
+
+    if at <= now {
+        return Err(PublishError::PastExpiry(
+            at.format("%Y-%m-%d %H:%M").to_string(),
+        ));
+    }
+    Ok(Some(at))
+}
+
+/// Images that expire within `within` of `now`, soonest first
+pub fn expiring(
+    images: &[StoredImage],
+    now: DateTime<Local>,
+    within: TimeDelta,
+) -> Vec<&StoredImage> {
+    let mut expiring: Vec<&StoredImage> = images
+        .iter()
+        .filter(|image| image.expires_at.is_some_and(|at| at <= now + within))
+        .collect();
+    expiring.sort_by_key(|image| image.expires_at);
+    expiring
+}
+
+/// How long until an image expiring at `expires_at` is deleted, for the
+/// image store
+pub fn expiry_label(expires_at: Option<DateTime<FixedOffset>>, now: DateTime<Local>) -> String {
+    let Some(at) = expires_at else {
+        return "never".to_string();
+    };
+    let left = at.signed_duration_since(now);
+    if left <= TimeDelta::zero() {
+        "expired".to_string()
+    } else if left < TimeDelta::days(2) {
+        format!("in {}h", left.num_hours().max(1))
+    } else {
+        format!("in {}d", left.num_days())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,143 @@
+//! Tests of publishing instances as images
+
+use super::{expiring, expiry_label, parse_expiry, Publish, PublishError};
+use crate::compare::tests::instance;
+use crate::store::StoredImage;
+use chrono::{DateTime, Local, TimeDelta, TimeZone};
+use serde_json::json;
+
+fn now() -> DateTime<Local> {
+    Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap()
+}
+
+fn stored(fingerprint: &str, expires_in: Option<TimeDelta>) -> StoredImage {
+    StoredImage {
+        fingerprint: fingerprint.to_string(),
+        description: fingerprint.to_string(),
+        aliases: Vec::new(),
+        source: None,
+        auto_update: false,
+        cached: false,
+        size: 0,
+        created_at: String::new(),
+        expires_at: expires_in.map(|delta| (now() + delta).fixed_offset()),
+    }
+}
+
+#[test]
+fn file_starts_from_the_instances_image() {
+    let mut web = instance("web1", &["default"], json!({}));
+    web.config = [
+        ("image.os".to_string(), "Debian".to_string()),
+        ("image.release".to_string(), "12".to_string()),
+    ]
+    .into();
+    let text = Publish::to_yaml(&web).unwrap();
+    assert!(text.starts_with("### Publishing an instance"));
+    assert!(text.ends_with(
+        "alias: ''\npublic: false\nexpires: never\nproperties:\n  \
+         description: ''\n  os: Debian\n  release: '12'\n"
+    ));
+
+    // Saved as it was, nothing is set but the properties
+    let publish = Publish::parse(&text, now()).unwrap();
+    assert_eq!(publish.alias, None);
+    assert!(!publish.public);
+    assert_eq!(publish.expires_at, None);
+    assert_eq!(
+        publish.properties,
+        [
+            ("os".to_string(), "Debian".to_string()),
+            ("release".to_string(), "12".to_string())
+        ]
+        .into()
+    );
+}
+
+#[test]
+fn edited_file_names_the_image() {
+    let text = "alias: golden-web\npublic: true\nexpires: 2w\n\
+                properties:\n  release: 12\n  description: Web server\n";
+    let publish = Publish::parse(text, now()).unwrap();
+    assert_eq!(publish.alias.as_deref(), Some("golden-web"));
+    assert!(publish.public);
+    assert_eq!(publish.expires_at, Some(now() + TimeDelta::weeks(2)));
+    // Unquoted numbers are kept as LXD stores them
+    assert_eq!(publish.properties["release"], "12");
+
+    assert!(matches!(
+        Publish::parse("alias: golden web\n", now()),
+        Err(PublishError::InvalidAlias(_))
+    ));
+    assert!(matches!(
+        Publish::parse("alias: local:golden\n", now()),
+        Err(PublishError::InvalidAlias(_))
+    ));
+    assert!(matches!(
+        Publish::parse("properties:\n  os: [Debian]\n", now()),
+        Err(PublishError::NotScalar(key)) if key == "properties.os"
+    ));
+    assert!(matches!(
+        Publish::parse("aliases: [golden]\n", now()),
+        Err(PublishError::Yaml(_))
+    ));
+}
+
+#[test]
+fn expiry_is_a_date_or_a_time_from_now() {
+    assert_eq!(parse_expiry("never", now()).unwrap(), None);
+    assert_eq!(parse_expiry(" ", now()).unwrap(), None);
+    assert_eq!(
+        parse_expiry("12h", now()).unwrap(),
+        Some(now() + TimeDelta::hours(12))
+    );
+    assert_eq!(
+        parse_expiry("30d", now()).unwrap(),
+        Some(now() + TimeDelta::days(30))
+    );
+    assert_eq!(
+        parse_expiry("2026-12-31", now()).unwrap(),
+        Some(Local.with_ymd_and_hms(2026, 12, 31, 0, 0, 0).unwrap())
+    );
+
+    for invalid in ["soon", "30", "30m", "d", "2026-13-01"] {
+        assert!(
+            matches!(
+                parse_expiry(invalid, now()),
+                Err(PublishError::InvalidExpiry(_))
+            ),
+            "{}",
+            invalid
+        );
+    }
+    assert!(matches!(
+        parse_expiry("2026-03-10", now()),
+        Err(PublishError::PastExpiry(_))
+    ));
+    assert!(matches!(
+        parse_expiry("-1d", now()),
+        Err(PublishError::PastExpiry(_))
+    ));
+}
+
+#[test]
+fn images_expiring_within_the_warning_soonest_first() {
+    let images = vec![
+        stored("never", None),
+        stored("month", Some(TimeDelta::days(30))),
+        stored("week", Some(TimeDelta::days(6))),
+        stored("gone", Some(-TimeDelta::hours(1))),
+        stored("tomorrow", Some(TimeDelta::hours(20))),
+    ];
+    let soon: Vec<&str> = expiring(&images, now(), TimeDelta::days(7))
+        .iter()
+        .map(|image| image.fingerprint.as_str())
+        .collect();
+    assert_eq!(soon, ["gone", "tomorrow", "week"]);
+
+    let label = |image: &StoredImage| expiry_label(image.expires_at, now());
+    assert_eq!(label(&images[0]), "never");
+    assert_eq!(label(&images[1]), "in 30d");
+    assert_eq!(label(&images[3]), "expired");
+    assert_eq!(label(&images[4]), "in 20h");
+}
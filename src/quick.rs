@@ -0,0 +1,252 @@
+//! Quick exec actions
+//!
+//! One-key commands from `[[quick.actions]]`, or a one-off one, run with
+//! `sh -c` in every picked instance, with each instance's output kept.
+
+use crate::config::QuickAction;
+use crate::lxd_api::ExecOutput;
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+
+/// Lines of output kept from each instance, the last ones written
+const OUTPUT_LINES: usize = 2000;
+
+/// Asks for a one-off command to run in place of an action
+pub const COMMAND_KEY: char = '!';
+
+/// Keys the library answers itself, so an action bound to one only runs
+/// with Enter
+const RESERVED_KEYS: [char; 4] = ['j', 'k', 'q', COMMAND_KEY];
+
+/// How the command went in one instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Running,
+    Exited(i32),
+    Failed(String), // It couldn't be run
+    TimedOut(u64),  // Seconds waited
+    Cancelled,      // Stopped waiting for it; it may still be running there
+}
+
+/// An instance the command runs in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    pub name: String, // qualified name
+    pub outcome: Outcome,
+    pub output: String, // stdout, then stderr
+}
+
+/// An action run in the picked instances
+#[derive(Debug)]
+pub struct Run {
+    pub action: QuickAction,
+    pub targets: Vec<Target>,
+    pub operation_id: String, // UI operation covering every instance
+}
+
+impl Run {
+    pub fn finished(&self) -> bool {
+        self.targets.iter().all(|t| t.outcome != Outcome::Running)
+    }
+
+    /// The other instances the command ended in just as it did in the one at
+    /// `index`, with the same output
+    pub fn alike(&self, index: usize) -> Vec<&str> {
+        let Some(target) = self.targets.get(index) else {
+            return Vec::new();
+        };
+        if target.outcome == Outcome::Running {
+            return Vec::new();
+        }
+        self.targets
+            .iter()
+            .enumerate()
+            .filter(|(i, other)| {
+                *i != index && other.outcome == target.outcome && other.output == target.output
+            })
+            .map(|(_, other)| other.name.as_str())
+            .collect()
+    }
+
+    /// Count of instances the command succeeded in, and failed in
+    pub fn tally(&self) -> (usize, usize) {
+        let succeeded = |t: &&Target| t.outcome == Outcome::Exited(0);
+        let done = self
+            .targets
+            .iter()
+            .filter(|t| t.outcome != Outcome::Running);
+        let ok = done.clone().filter(succeeded).count();
+        (ok, done.count() - ok)
+    }
+}
+
+/// A warning for each action bound to a key the library keeps for itself
+pub fn shadowed(actions: &[QuickAction]) -> Vec<String> {
+    actions
+        .iter()
+        .filter(|action| RESERVED_KEYS.contains(&action.key))
+        .map(|action| {
+            format!(
+                "quick action {}: '{}' is taken by the library, use Enter",
+                action.name, action.key
+            )
+        })
+        .collect()
+}
+
+/// What an instance reports: the command's output, or how it ended without any
+pub type Report = Result<ExecOutput, Outcome>;
+
+/// The library and the results of the action last run from it
+#[derive(Debug)]
+pub struct QuickView {
+    pub actions: Vec<QuickAction>,
+    pub names: Vec<String>, // Instances to run in, qualified names
+    pub selected: usize,    // Action in the library, or instance in the results
+    pub scroll: usize,      // Lines of the selected instance's output scrolled past
+    pub expanded: bool,     // Output across the whole view, without the instances
+    pub run: Option<Run>,
+    tasks: Vec<AbortHandle>, // Waiting on each instance of the run
+    tx: mpsc::UnboundedSender<(String, Report)>,
+    rx: mpsc::UnboundedReceiver<(String, Report)>,
+}
+
+impl QuickView {
+    pub fn new(actions: Vec<QuickAction>, names: Vec<String>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        QuickView {
+            actions,
+            names,
+            selected: 0,
+            scroll: 0,
+            expanded: false,
+            run: None,
+            tasks: Vec::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Whether an action is still running in any instance
+    pub fn running(&self) -> bool {
+        self.run.as_ref().is_some_and(|run| !run.finished())
+    }
+
+    /// The action bound to `key`
+    pub fn action_for(&self, key: char) -> Option<usize> {
+        self.actions.iter().position(|a| a.key == key)
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        let count = match &self.run {
+            Some(run) => run.targets.len(),
+            None => self.actions.len(),
+        };
+        if count == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+        self.scroll = 0;
+    }
+
+    /// Scroll the selected instance's output by `delta` lines, stopping at
+    /// its last line
+    pub fn scroll_by(&mut self, delta: i32) {
+        let lines = self
+            .selected_target()
+            .map_or(0, |target| target.output.lines().count());
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(delta as isize)
+            .min(lines.saturating_sub(1));
+    }
+
+    pub fn selected_target(&self) -> Option<&Target> {
+        self.run.as_ref()?.targets.get(self.selected)
+    }
+
+    /// Show the results of `action` starting in every instance
+    pub fn start(&mut self, action: QuickAction, operation_id: String) {
+        let targets = self
+            .names
+            .iter()
+            .map(|name| Target {
+                name: name.clone(),
+                outcome: Outcome::Running,
+                output: String::new(),
+            })
+            .collect();
+        self.run = Some(Run {
+            action,
+            targets,
+            operation_id,
+        });
+        self.tasks.clear();
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    /// Where the instances report how the command went
+    pub fn sender(&self) -> mpsc::UnboundedSender<(String, Report)> {
+        self.tx.clone()
+    }
+
+    /// Keep the task waiting on an instance, so the run can be cancelled
+    pub fn track(&mut self, task: AbortHandle) {
+        self.tasks.push(task);
+    }
+
+    /// Stop waiting on the instances the command is still running in. True
+    /// when that finishes the run.
+    pub fn cancel(&mut self) -> bool {
+        self.drain();
+        let Some(run) = &mut self.run else {
+            return false;
+        };
+        if run.finished() {
+            return false;
+        }
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        for target in &mut run.targets {
+            if target.outcome == Outcome::Running {
+                target.outcome = Outcome::Cancelled;
+            }
+        }
+        true
+    }
+
+    /// Take in the instances that finished. True when that finishes the run.
+    pub fn drain(&mut self) -> bool {
+        let Some(run) = &mut self.run else {
+            return false;
+        };
+        let before = run.finished();
+        while let Ok((name, result)) = self.rx.try_recv() {
+            let Some(target) = run.targets.iter_mut().find(|t| t.name == name) else {
+                continue;
+            };
+            match result {
+                Ok(output) => {
+                    target.outcome = Outcome::Exited(output.status);
+                    target.output = last_lines(&(output.stdout + &output.stderr));
+                }
+                Err(outcome) => target.outcome = outcome,
+            }
+        }
+        !before && run.finished()
+    }
+}
+
+fn last_lines(text: &str) -> String {
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(OUTPUT_LINES)..].join("\n")
+}
+
+#[cfg(test)]
+mod tests;
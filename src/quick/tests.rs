@@ -0,0 +1,136 @@
+//! Tests of quick exec actions
+
+use super::{Outcome, QuickView, Report};
+use crate::config::{QuickAction, QuickConfig};
+use crate::lxd_api::ExecOutput;
+
+fn view(names: &[&str]) -> QuickView {
+    let names = names.iter().map(|n| n.to_string()).collect();
+    QuickView::new(QuickConfig::default().actions, names)
+}
+
+fn exited(status: i32, stdout: &str) -> Report {
+    Ok(ExecOutput {
+        status,
+        stdout: stdout.to_string(),
+        stderr: String::new(),
+    })
+}
+
+#[test]
+fn library_binds_actions_to_keys() {
+    let mut view = view(&["web1"]);
+    assert_eq!(view.action_for('d'), Some(2));
+    assert_eq!(view.action_for('x'), None);
+    assert!(view.actions[3].confirm);
+
+    view.select(false);
+    assert_eq!(view.selected, 3);
+    view.select(true);
+    assert_eq!(view.selected, 0);
+}
+
+#[test]
+fn run_finishes_once_every_instance_answers() {
+    let mut view = view(&["web1", "web2", "db1"]);
+    let disk = view.actions[2].clone();
+    view.start(disk, "op".to_string());
+    assert!(view.running());
+
+    let tx = view.sender();
+    tx.send(("web2".to_string(), exited(0, "/dev/sda1 10G\n")))
+        .unwrap();
+    tx.send((
+        "db1".to_string(),
+        Err(Outcome::Failed("Instance is not running".to_string())),
+    ))
+    .unwrap();
+    assert!(!view.drain());
+    assert_eq!(view.run.as_ref().unwrap().tally(), (1, 1));
+
+    tx.send(("web1".to_string(), exited(1, "df: /mnt: No such file\n")))
+        .unwrap();
+    assert!(view.drain());
+    assert!(!view.running());
+    // Reported once
+    assert!(!view.drain());
+
+    let run = view.run.as_ref().unwrap();
+    assert_eq!(run.tally(), (1, 2));
+    assert_eq!(run.targets[0].outcome, Outcome::Exited(1));
+    assert_eq!(run.targets[0].output, "df: /mnt: No such file");
+    assert_eq!(
+        run.targets[2].outcome,
+        Outcome::Failed("Instance is not running".to_string())
+    );
+}
+
+#[test]
+fn output_scrolls_within_the_selected_instance() {
+    let mut view = view(&["web1", "web2"]);
+    let journal = view.actions[1].clone();
+    view.start(journal, "op".to_string());
+    let lines: Vec<String> = (1..=30).map(|i| format!("line {}", i)).collect();
+    view.sender()
+        .send(("web1".to_string(), exited(0, &lines.join("\n"))))
+        .unwrap();
+    view.drain();
+
+    view.scroll_by(10);
+    assert_eq!(view.scroll, 10);
+    view.scroll_by(100);
+    assert_eq!(view.scroll, 29);
+    view.scroll_by(-100);
+    assert_eq!(view.scroll, 0);
+
+    // Moving to another instance starts at the top of its output
+    view.scroll_by(5);
+    view.select(true);
+    assert_eq!(view.selected_target().unwrap().name, "web2");
+    assert_eq!(view.scroll, 0);
+}
+
+#[test]
+fn instances_alike_share_an_outcome_and_output() {
+    let mut view = view(&["web1", "web2", "web3", "db1"]);
+    let disk = view.actions[2].clone();
+    view.start(disk, "op".to_string());
+    let tx = view.sender();
+    tx.send(("web1".to_string(), exited(0, "ok\n"))).unwrap();
+    tx.send(("web2".to_string(), exited(0, "ok"))).unwrap();
+    tx.send(("web3".to_string(), exited(1, "ok"))).unwrap();
+    view.drain();
+
+    let run = view.run.as_ref().unwrap();
+    assert_eq!(run.alike(0), ["web2"]);
+    assert_eq!(run.alike(1), ["web1"]);
+    assert!(run.alike(2).is_empty());
+    // Still running, so nothing is alike yet
+    assert!(run.alike(3).is_empty());
+}
+
+#[test]
+fn actions_on_keys_the_library_takes_are_warned_about() {
+    let mut actions = QuickConfig::default().actions;
+    actions.push(QuickAction {
+        name: "Shout".to_string(),
+        command: "wall hello".to_string(),
+        key: '!',
+        confirm: false,
+    });
+    actions.push(QuickAction {
+        name: "Quit".to_string(),
+        command: "poweroff".to_string(),
+        key: 'q',
+        confirm: true,
+    });
+
+    assert_eq!(
+        super::shadowed(&actions),
+        [
+            "quick action Shout: '!' is taken by the library, use Enter",
+            "quick action Quit: 'q' is taken by the library, use Enter",
+        ]
+    );
+    assert!(super::shadowed(&QuickConfig::default().actions).is_empty());
+}
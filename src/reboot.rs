@@ -0,0 +1,139 @@
+//! Reboot and evacuation behaviour
+//!
+//! Explains and checks `boot.autostart`, `volatile.last_state.power` and
+//! `cluster.evacuate` in the config editor.
+
+use crate::boot::{Autostart, AUTOSTART_KEY};
+use crate::lxd_api::LxdContainer;
+use std::collections::BTreeMap;
+
+pub const EVACUATE_KEY: &str = "cluster.evacuate";
+pub const LAST_POWER_KEY: &str = "volatile.last_state.power";
+
+/// What evacuating the instance's cluster member does with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evacuate {
+    Auto,
+    Migrate,
+    LiveMigrate,
+    Stop,
+}
+
+impl Evacuate {
+    pub const VALUES: &'static [&'static str] = &["auto", "migrate", "live-migrate", "stop"];
+
+    /// The setting `value` names; unset is `auto`
+    pub fn parse(value: Option<&str>) -> Option<Self> {
+        Some(match value.map(str::trim).unwrap_or("auto") {
+            "" | "auto" => Evacuate::Auto,
+            "migrate" => Evacuate::Migrate,
+            "live-migrate" => Evacuate::LiveMigrate,
+            "stop" => Evacuate::Stop,
+            _ => return None,
+        })
+    }
+
+    pub fn explain(self) -> &'static str {
+        match self {
+            Evacuate::Auto => "moved to another member if it can be, stopped otherwise",
+            Evacuate::Migrate => "stopped, moved to another member and started there",
+            Evacuate::LiveMigrate => "moved to another member while it runs",
+            Evacuate::Stop => "stopped where it is, and started again on restore",
+        }
+    }
+}
+
+/// Where a key's value comes from, for the notes
+fn source(own: &BTreeMap<String, String>, key: &str, value: Option<&String>) -> &'static str {
+    match value {
+        None => "unset",
+        Some(_) if own.contains_key(key) => "set here",
+        Some(_) => "from a profile",
+    }
+}
+
+/// Plain-language notes on what a host reboot and an evacuation do to
+/// `instance`, one line each, for the top of the config file
+pub fn notes(instance: &LxdContainer) -> Vec<String> {
+    let own: BTreeMap<String, String> = instance.config.clone().into_iter().collect();
+    let expanded = instance
+        .expanded_config
+        .as_ref()
+        .unwrap_or(&instance.config);
+
+    let mut lines = vec!["On host reboot:".to_string()];
+    let autostart = expanded.get(AUTOSTART_KEY);
+    let from = source(&own, AUTOSTART_KEY, autostart);
+    match Autostart::parse(autostart.map(String::as_str)) {
+        Autostart::Always => lines.push(format!(
+            "  starts again, as {} is true ({})",
+            AUTOSTART_KEY, from
+        )),
+        Autostart::Never => lines.push(format!(
+            "  stays stopped, as {} is false ({})",
+            AUTOSTART_KEY, from
+        )),
+        Autostart::LastState => {
+            lines.push(format!(
+                "  starts again only if it was running, as {} is unset.",
+                AUTOSTART_KEY
+            ));
+            let last = own.get(LAST_POWER_KEY).map(String::as_str);
+            lines.push(match last {
+                Some("RUNNING") => format!(
+                    "  It was: {} is RUNNING; set it to STOPPED to keep it down.",
+                    LAST_POWER_KEY
+                ),
+                Some(state) => format!(
+                    "  It wasn't: {} is {}; set it to RUNNING to start it.",
+                    LAST_POWER_KEY, state
+                ),
+                None => format!("  LXD sets {} when it stops the instance.", LAST_POWER_KEY),
+            });
+        }
+    }
+
+    lines.push("On evacuation of its cluster member:".to_string());
+    let evacuate = expanded.get(EVACUATE_KEY);
+    match Evacuate::parse(evacuate.map(String::as_str)) {
+        Some(setting) => lines.push(format!(
+            "  {}, as {} is {} ({})",
+            setting.explain(),
+            EVACUATE_KEY,
+            evacuate.map_or("auto", String::as_str),
+            source(&own, EVACUATE_KEY, evacuate)
+        )),
+        None => lines.push(format!(
+            "  refused by LXD: {} is {}",
+            EVACUATE_KEY,
+            evacuate.map_or("", String::as_str)
+        )),
+    }
+    lines.push(format!(
+        "Set {} to true or false, and {} to {}, under config.",
+        AUTOSTART_KEY,
+        EVACUATE_KEY,
+        Evacuate::VALUES.join(", ")
+    ));
+    lines
+}
+
+/// What `key` must be, when it is one of the keys explained and `value`
+/// won't do for it
+pub fn check(key: &str, value: &str) -> Option<String> {
+    let expected = match key {
+        AUTOSTART_KEY => "true or false",
+        EVACUATE_KEY => "auto, migrate, live-migrate or stop",
+        LAST_POWER_KEY => "RUNNING or STOPPED",
+        _ => return None,
+    };
+    let valid = match key {
+        AUTOSTART_KEY => value.is_empty() || Autostart::parse(Some(value)) != Autostart::LastState,
+        EVACUATE_KEY => value.is_empty() || Evacuate::parse(Some(value)).is_some(),
+        _ => matches!(value, "" | "RUNNING" | "STOPPED"),
+    };
+    (!valid).then(|| expected.to_string())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,87 @@
+//! Tests of the reboot and evacuation notes
+
+use super::{check, notes, Evacuate};
+use crate::compare::tests::instance;
+use serde_json::json;
+
+#[test]
+fn unset_autostart_follows_the_last_state() {
+    let mut web = instance("web1", &["default"], json!({}));
+    web.config = [(
+        "volatile.last_state.power".to_string(),
+        "RUNNING".to_string(),
+    )]
+    .into();
+    let notes = notes(&web);
+    assert_eq!(notes[0], "On host reboot:");
+    assert!(notes[1].contains("only if it was running"));
+    assert!(notes[2].contains("RUNNING; set it to STOPPED to keep it down"));
+    assert_eq!(notes[3], "On evacuation of its cluster member:");
+    assert_eq!(
+        notes[4],
+        "  moved to another member if it can be, stopped otherwise, \
+         as cluster.evacuate is auto (unset)"
+    );
+    assert!(notes[5].starts_with("Set boot.autostart to true or false"));
+
+    web.config.insert(
+        "volatile.last_state.power".to_string(),
+        "STOPPED".to_string(),
+    );
+    assert!(super::notes(&web)[2].contains("set it to RUNNING to start it"));
+}
+
+#[test]
+fn says_where_the_settings_come_from() {
+    let mut db = instance(
+        "db1",
+        &["default", "pinned"],
+        json!({"boot.autostart": "false", "cluster.evacuate": "stop"}),
+    );
+    db.config = [("cluster.evacuate".to_string(), "stop".to_string())].into();
+    let notes = notes(&db);
+    assert_eq!(
+        notes[1],
+        "  stays stopped, as boot.autostart is false (from a profile)"
+    );
+    assert_eq!(
+        notes[3],
+        "  stopped where it is, and started again on restore, \
+         as cluster.evacuate is stop (set here)"
+    );
+
+    let web = instance("web1", &["default"], json!({"boot.autostart": "on"}));
+    assert!(super::notes(&web)[1].starts_with("  starts again, as boot.autostart is true"));
+}
+
+#[test]
+fn evacuate_settings() {
+    assert_eq!(Evacuate::parse(None), Some(Evacuate::Auto));
+    assert_eq!(
+        Evacuate::parse(Some("live-migrate")),
+        Some(Evacuate::LiveMigrate)
+    );
+    assert_eq!(Evacuate::parse(Some("migrate")), Some(Evacuate::Migrate));
+    assert_eq!(Evacuate::parse(Some("move")), None);
+}
+
+#[test]
+fn checks_only_the_keys_explained() {
+    assert_eq!(check("boot.autostart", "true"), None);
+    assert_eq!(check("boot.autostart", ""), None);
+    assert_eq!(
+        check("boot.autostart", "sometimes").as_deref(),
+        Some("true or false")
+    );
+    assert_eq!(check("cluster.evacuate", "stop"), None);
+    assert_eq!(
+        check("cluster.evacuate", "move").as_deref(),
+        Some("auto, migrate, live-migrate or stop")
+    );
+    assert_eq!(check("volatile.last_state.power", "STOPPED"), None);
+    assert_eq!(
+        check("volatile.last_state.power", "off").as_deref(),
+        Some("RUNNING or STOPPED")
+    );
+    assert_eq!(check("limits.cpu", "lots"), None);
+}
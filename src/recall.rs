@@ -0,0 +1,119 @@
+//! Prompt history
+//!
+//! Readline-style history for each kind of prompt, saved across sessions;
+//! prompts for secrets keep none.
+
+use crate::config::PromptHistoryConfig;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RecallError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Invalid JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// Where Up and Down have got to in a prompt's history
+#[derive(Debug)]
+struct Browsing {
+    prompt: &'static str,
+    position: usize, // Index of the entry shown
+    draft: String,   // What was typed before stepping back
+}
+
+/// Entries typed at each prompt, oldest first
+#[derive(Debug)]
+pub struct PromptHistory {
+    path: PathBuf,
+    limit: usize, // Per prompt
+    pub entries: BTreeMap<String, Vec<String>>,
+    browsing: Option<Browsing>,
+}
+
+impl PromptHistory {
+    /// The configured history, or None when it is turned off
+    pub fn new(config: &PromptHistoryConfig) -> Option<Self> {
+        (config.limit > 0).then(|| PromptHistory {
+            path: config.history_path(),
+            limit: config.limit,
+            entries: BTreeMap::new(),
+            browsing: None,
+        })
+    }
+
+    pub fn load(&mut self) -> Result<(), RecallError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut entries: BTreeMap<String, Vec<String>> = serde_json::from_str(&text)?;
+        for entries in entries.values_mut() {
+            let excess = entries.len().saturating_sub(self.limit);
+            entries.drain(..excess);
+        }
+        self.entries = entries;
+        Ok(())
+    }
+
+    /// Add `entry` as the newest of `prompt`'s, dropping an earlier copy,
+    /// and save the history
+    pub fn record(&mut self, prompt: &str, entry: &str) -> Result<(), RecallError> {
+        self.browsing = None;
+        let entry = entry.trim();
+        if entry.is_empty() {
+            return Ok(());
+        }
+        let entries = self.entries.entry(prompt.to_string()).or_default();
+        entries.retain(|e| e != entry);
+        entries.push(entry.to_string());
+        let excess = entries.len().saturating_sub(self.limit);
+        entries.drain(..excess);
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.entries)?)?;
+        Ok(())
+    }
+
+    /// The entry before the one shown, keeping `typed` to come back to;
+    /// None at the oldest
+    pub fn previous(&mut self, prompt: &'static str, typed: &str) -> Option<String> {
+        let entries = self.entries.get(prompt)?;
+        if !matches!(&self.browsing, Some(browsing) if browsing.prompt == prompt) {
+            self.browsing = Some(Browsing {
+                prompt,
+                position: entries.len(),
+                draft: typed.to_string(),
+            });
+        }
+        let browsing = self.browsing.as_mut()?;
+        browsing.position = browsing.position.checked_sub(1)?;
+        entries.get(browsing.position).cloned()
+    }
+
+    /// The entry after the one shown, or what was typed before stepping
+    /// back once past the newest; None when not stepping through
+    pub fn next(&mut self, prompt: &'static str) -> Option<String> {
+        let browsing = self.browsing.as_mut().filter(|b| b.prompt == prompt)?;
+        browsing.position += 1;
+        match self.entries.get(prompt)?.get(browsing.position) {
+            Some(entry) => Some(entry.clone()),
+            None => self.browsing.take().map(|browsing| browsing.draft),
+        }
+    }
+
+    /// Forget where Up and Down had got to, as when a prompt closes
+    pub fn reset(&mut self) {
+        self.browsing = None;
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,59 @@
+//! Tests of the prompt history
+
+use super::PromptHistory;
+use crate::config::PromptHistoryConfig;
+
+fn history(limit: usize) -> (PromptHistory, PromptHistoryConfig) {
+    let config = PromptHistoryConfig {
+        limit,
+        path: Some(
+            std::env::temp_dir().join(format!("lxtui-prompts-{}.json", uuid::Uuid::new_v4())),
+        ),
+    };
+    (PromptHistory::new(&config).unwrap(), config)
+}
+
+#[test]
+fn steps_back_through_a_prompts_entries_and_returns_to_the_draft() {
+    let (mut history, _) = history(10);
+    history.record("command", "start web1").unwrap();
+    history.record("command", "stop db1").unwrap();
+    history.record("destination", "web2").unwrap();
+
+    assert_eq!(
+        history.previous("command", "sn").as_deref(),
+        Some("stop db1")
+    );
+    assert_eq!(
+        history.previous("command", "").as_deref(),
+        Some("start web1")
+    );
+    assert_eq!(history.previous("command", ""), None);
+    assert_eq!(history.next("command").as_deref(), Some("stop db1"));
+    assert_eq!(history.next("command").as_deref(), Some("sn"));
+    assert_eq!(history.next("command"), None);
+
+    // Each prompt has its own entries
+    assert_eq!(history.previous("destination", "").as_deref(), Some("web2"));
+    assert_eq!(history.previous("tags", ""), None);
+}
+
+#[test]
+fn keeps_one_copy_of_each_entry_up_to_the_limit_across_sessions() {
+    let (mut history, config) = history(2);
+    history.record("command", "start web1").unwrap();
+    history.record("command", "stop db1").unwrap();
+    history.record("command", "start web1").unwrap();
+    history.record("command", "  ").unwrap();
+    history.record("command", "delete vm1").unwrap();
+
+    let mut reloaded = PromptHistory::new(&config).unwrap();
+    reloaded.load().unwrap();
+    assert_eq!(reloaded.entries["command"], ["start web1", "delete vm1"]);
+
+    assert!(PromptHistory::new(&PromptHistoryConfig {
+        limit: 0,
+        path: None
+    })
+    .is_none());
+}
@@ -0,0 +1,107 @@
+//! Error reports
+//!
+//! Collects the context of a failed action into plain text that can be
+//! copied to the clipboard or saved to a file and pasted into a bug report.
+//! Other text, such as the editor remote snippet, goes through the same
+//! clipboard tools.
+
+use crate::config::Config;
+use crate::logging;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Clipboard commands tried in order, with their arguments
+const CLIPBOARD_COMMANDS: &[(&str, &[&str])] = &[
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("pbcopy", &[]),
+];
+
+#[derive(Debug, Clone)]
+pub struct ErrorReport {
+    pub action: String,
+    pub container: Option<String>,
+    pub remote: Option<String>,
+    pub request: Option<String>, // "PUT /1.0/instances/web1/state"
+    pub error: String,
+    pub lxd_version: Option<String>,
+    pub time: String,
+}
+
+impl ErrorReport {
+    pub fn to_text(&self) -> String {
+        let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+
+        format!(
+            "LXTUI error report\n\
+             ==================\n\
+             LXTUI version: {}\n\
+             LXD version:   {}\n\
+             Time:          {}\n\
+             Action:        {}\n\
+             Container:     {}\n\
+             Remote:        {}\n\
+             API request:   {}\n\
+             \n\
+             Error:\n\
+             {}\n",
+            env!("CARGO_PKG_VERSION"),
+            or_none(&self.lxd_version),
+            self.time,
+            self.action,
+            or_none(&self.container),
+            self.remote.as_deref().unwrap_or("local"),
+            or_none(&self.request),
+            self.error
+        )
+    }
+
+    /// Copy the report with the first clipboard tool that works, returning its name
+    pub fn copy_to_clipboard(&self) -> io::Result<&'static str> {
+        copy_to_clipboard(&self.to_text())
+    }
+
+    /// Save the report next to the log file, returning its path
+    pub fn save(&self) -> io::Result<PathBuf> {
+        let dir = logging::log_path()
+            .and_then(|path| path.parent())
+            .map(PathBuf::from)
+            .unwrap_or_else(Config::config_dir);
+        let stamp: String = self.time.chars().filter(char::is_ascii_digit).collect();
+        let path = dir.join(format!("error-report-{}.txt", stamp));
+
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(&path, self.to_text())?;
+        Ok(path)
+    }
+}
+
+/// Copy `text` with the first clipboard tool that works, returning its name
+pub fn copy_to_clipboard(text: &str) -> io::Result<&'static str> {
+    for (program, args) in CLIPBOARD_COMMANDS {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+
+        let written = child
+            .stdin
+            .take()
+            .map(|mut stdin| stdin.write_all(text.as_bytes()));
+        if matches!(written, Some(Ok(()))) && child.wait()?.success() {
+            return Ok(program);
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no clipboard tool found (install wl-copy, xclip or xsel)",
+    ))
+}
@@ -0,0 +1,113 @@
+//! Restart policy
+//!
+//! Instances with `user.lxtui.restart=always` in their config (or a
+//! profile's) are started again when they stop by themselves while LXTUI
+//! runs, as spotted by the crash check. One that keeps crashing is given up
+//! on after `MAX_RESTARTS` within `WINDOW`, rather than started in a loop.
+
+use chrono::{DateTime, Local};
+use log::warn;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+
+/// Config key holding an instance's restart policy
+pub const CONFIG_KEY: &str = "user.lxtui.restart";
+
+/// Restarts allowed within `WINDOW` before giving up
+pub const MAX_RESTARTS: usize = 5;
+pub const WINDOW: Duration = Duration::from_secs(600);
+
+/// Whether the value of `CONFIG_KEY` asks for restarts
+pub fn always(value: &str) -> bool {
+    value.trim().eq_ignore_ascii_case("always")
+}
+
+/// Restarts of one instance since LXTUI started
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub count: u32,
+    pub last: DateTime<Local>,
+    pub error: Option<String>, // Why the last restart failed
+    pub gave_up: bool,
+    recent: Vec<Instant>, // Within WINDOW of the last one
+}
+
+/// Outcome of a restart, with its operation id
+pub type RestartResult = (String, String, Result<(), String>); // (op_id, container, outcome)
+
+#[derive(Debug)]
+pub struct Restarts {
+    records: HashMap<String, Record>, // By qualified name
+    tx: mpsc::UnboundedSender<RestartResult>,
+    rx: mpsc::UnboundedReceiver<RestartResult>,
+}
+
+impl Restarts {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Restarts {
+            records: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Decide whether `container`, which just crashed, is restarted, and
+    /// count the restart when it is
+    pub fn restart(&mut self, container: &str, now: Instant) -> bool {
+        let record = self
+            .records
+            .entry(container.to_string())
+            .or_insert_with(|| Record {
+                count: 0,
+                last: Local::now(),
+                error: None,
+                gave_up: false,
+                recent: Vec::new(),
+            });
+        record.recent.retain(|at| now.duration_since(*at) < WINDOW);
+        if record.recent.len() >= MAX_RESTARTS {
+            if !record.gave_up {
+                warn!(
+                    "{} crashed {} times in {} minutes; not restarting it again",
+                    container,
+                    MAX_RESTARTS,
+                    WINDOW.as_secs() / 60
+                );
+            }
+            record.gave_up = true;
+            return false;
+        }
+        record.recent.push(now);
+        record.count += 1;
+        record.last = Local::now();
+        record.gave_up = false;
+        true
+    }
+
+    /// Where restarts report how they went
+    pub fn sender(&self) -> mpsc::UnboundedSender<RestartResult> {
+        self.tx.clone()
+    }
+
+    /// Restarts that finished since the last call; failures are noted on
+    /// their records
+    pub fn drain(&mut self) -> Vec<RestartResult> {
+        let mut finished = Vec::new();
+        while let Ok(result) = self.rx.try_recv() {
+            if let Some(record) = self.records.get_mut(&result.1) {
+                record.error = result.2.as_ref().err().cloned();
+            }
+            finished.push(result);
+        }
+        finished
+    }
+
+    pub fn get(&self, container: &str) -> Option<&Record> {
+        self.records.get(container)
+    }
+}
+
+#[cfg(test)]
+mod tests;
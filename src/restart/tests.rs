@@ -0,0 +1,52 @@
+//! Tests of restarting crashed instances
+
+use super::{always, Restarts, MAX_RESTARTS, WINDOW};
+use tokio::time::{Duration, Instant};
+
+#[test]
+fn reads_the_policy() {
+    assert!(always("always"));
+    assert!(always(" Always\n"));
+    assert!(!always("never"));
+    assert!(!always(""));
+}
+
+#[test]
+fn gives_up_on_an_instance_that_keeps_crashing() {
+    let mut restarts = Restarts::new();
+    let start = Instant::now();
+    for i in 0..MAX_RESTARTS {
+        assert!(restarts.restart("web1", start + Duration::from_secs(i as u64)));
+    }
+    assert!(!restarts.restart("web1", start + Duration::from_secs(60)));
+    let record = restarts.get("web1").unwrap();
+    assert_eq!(record.count, MAX_RESTARTS as u32);
+    assert!(record.gave_up);
+
+    // Once the crashes are far enough apart it is restarted again
+    assert!(restarts.restart("web1", start + WINDOW + Duration::from_secs(1)));
+    let record = restarts.get("web1").unwrap();
+    assert_eq!(record.count, MAX_RESTARTS as u32 + 1);
+    assert!(!record.gave_up);
+    assert!(restarts.get("db1").is_none());
+}
+
+#[test]
+fn notes_failed_restarts() {
+    let mut restarts = Restarts::new();
+    assert!(restarts.restart("web1", Instant::now()));
+    let sender = restarts.sender();
+    sender
+        .send((
+            "op-1".to_string(),
+            "web1".to_string(),
+            Err("Failed to start device \"eth0\"".to_string()),
+        ))
+        .unwrap();
+
+    assert_eq!(restarts.drain().len(), 1);
+    assert_eq!(
+        restarts.get("web1").unwrap().error.as_deref(),
+        Some("Failed to start device \"eth0\"")
+    );
+}
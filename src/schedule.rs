@@ -0,0 +1,150 @@
+//! Cron-style schedules
+//!
+//! Parses the five-field cron expressions (minute, hour, day of month,
+//! month, day of week) and `@daily`-style shortcuts used by scheduled
+//! backups and power schedules in the config file.
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ScheduleError {
+    #[error("Invalid schedule '{0}': expected 5 fields or a shortcut like @daily")]
+    FieldCount(String),
+    #[error("Invalid {field} field '{value}'")]
+    InvalidField { field: &'static str, value: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    minutes: u64,  // bits 0-59
+    hours: u32,    // bits 0-23
+    days: u32,     // bits 1-31
+    months: u16,   // bits 1-12
+    weekdays: u8,  // bits 0-6, Sunday = 0
+    any_day: bool, // day-of-month field was '*'
+    any_weekday: bool,
+}
+
+impl Schedule {
+    pub fn parse(expr: &str) -> Result<Self, ScheduleError> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(ScheduleError::FieldCount(expr.to_string()));
+        };
+
+        // Day of week accepts 7 as an alias for Sunday
+        let weekdays = parse_field(weekday, "day of week", 0, 7)?;
+        let weekdays = ((weekdays | (weekdays >> 7)) & 0x7f) as u8;
+
+        Ok(Schedule {
+            minutes: parse_field(minute, "minute", 0, 59)?,
+            hours: parse_field(hour, "hour", 0, 23)? as u32,
+            days: parse_field(day, "day of month", 1, 31)? as u32,
+            months: parse_field(month, "month", 1, 12)? as u16,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    /// Whether the schedule fires on `date` at all
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let day_matches = self.days & (1 << date.day()) != 0;
+        let weekday_matches = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+
+        // Like cron, when both day fields are restricted either one may match
+        let day_ok = match (self.any_day, self.any_weekday) {
+            (false, false) => day_matches || weekday_matches,
+            _ => day_matches && weekday_matches,
+        };
+
+        day_ok && self.months & (1 << date.month()) != 0
+    }
+
+    /// Whether the schedule fires during the minute containing `time`
+    pub fn matches(&self, time: &NaiveDateTime) -> bool {
+        self.matches_date(time.date())
+            && self.minutes & (1 << time.minute()) != 0
+            && self.hours & (1 << time.hour()) != 0
+    }
+
+    /// The first minute after the one containing `time` that the schedule
+    /// fires in, looking up to a year ahead
+    pub fn next_after(&self, time: &NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = time.date().and_hms_opt(time.hour(), time.minute(), 0)? + TimeDelta::minutes(1);
+        let mut date = start.date();
+        for _ in 0..=366 {
+            if self.matches_date(date) {
+                let fires = (0..24)
+                    .filter(|hour| self.hours & (1 << hour) != 0)
+                    .flat_map(|hour| {
+                        (0..60)
+                            .filter(|minute| self.minutes & (1 << minute) != 0)
+                            .map(move |minute| (hour, minute))
+                    })
+                    .filter_map(|(hour, minute)| date.and_hms_opt(hour, minute, 0))
+                    .find(|at| *at >= start);
+                if fires.is_some() {
+                    return fires;
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+/// Parse one cron field (`*`, `*/n`, `a`, `a-b`, `a-b/n`, comma lists) into a bitmask
+fn parse_field(value: &str, field: &'static str, min: u32, max: u32) -> Result<u64, ScheduleError> {
+    let invalid = || ScheduleError::InvalidField {
+        field,
+        value: value.to_string(),
+    };
+
+    let mut mask = 0u64;
+    for part in value.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (
+                a.parse().map_err(|_| invalid())?,
+                b.parse().map_err(|_| invalid())?,
+            )
+        } else {
+            let n = range.parse().map_err(|_| invalid())?;
+            // "5/15" means every 15 starting at 5
+            if part.contains('/') {
+                (n, max)
+            } else {
+                (n, n)
+            }
+        };
+
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for n in (start..=end).step_by(step as usize) {
+            mask |= 1 << n;
+        }
+    }
+
+    Ok(mask)
+}
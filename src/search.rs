@@ -0,0 +1,124 @@
+//! Config key search
+//!
+//! Lists the instances that set a key, or keys matching `prefix.*`, in their
+//! expanded config, with each value and whether a profile set it.
+
+use crate::lxd_api::LxdContainer;
+
+const VOLATILE: &str = "volatile.";
+
+/// Whether `c` may be typed into a key pattern
+pub fn is_pattern_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '*')
+}
+
+/// Whether `key` matches `pattern`: the key itself, or with a trailing `*`
+/// every key it starts
+pub fn matches(pattern: &str, key: &str) -> bool {
+    if key.starts_with(VOLATILE) && !pattern.starts_with(VOLATILE) {
+        return false;
+    }
+    match pattern.strip_suffix('*') {
+        Some(prefix) => key.starts_with(prefix),
+        None => key == pattern,
+    }
+}
+
+/// One instance having a matching key
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub instance: String,
+    pub key: String,
+    pub value: String,
+    pub from_profile: bool, // Not set on the instance itself
+}
+
+/// Every matching key of `instances`, by instance and then key
+pub fn search(pattern: &str, instances: &[LxdContainer]) -> Vec<Hit> {
+    let mut hits = Vec::new();
+    for instance in instances {
+        let config = instance
+            .expanded_config
+            .as_ref()
+            .unwrap_or(&instance.config);
+        for (key, value) in config {
+            if matches(pattern, key) {
+                hits.push(Hit {
+                    instance: instance.name.clone(),
+                    key: key.clone(),
+                    value: value.clone(),
+                    from_profile: !instance.config.contains_key(key),
+                });
+            }
+        }
+    }
+    hits.sort_by(|a, b| (&a.instance, &a.key).cmp(&(&b.instance, &b.key)));
+    hits
+}
+
+/// The key search view
+#[derive(Debug)]
+pub struct KeySearch {
+    pub pattern: String,
+    pub hits: Vec<Hit>,
+    pub instances: usize, // Searched
+    pub selected: usize,
+    pub error: Option<String>, // Why the instances couldn't be read
+}
+
+impl KeySearch {
+    pub fn new(pattern: String) -> Self {
+        KeySearch {
+            pattern,
+            hits: Vec::new(),
+            instances: 0,
+            selected: 0,
+            error: None,
+        }
+    }
+
+    pub fn set_instances(&mut self, instances: &[LxdContainer]) {
+        self.hits = search(&self.pattern, instances);
+        self.instances = instances.len();
+        self.error = None;
+        self.selected = self.selected.min(self.hits.len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.hits.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.hits.len()
+        } else {
+            (self.selected + self.hits.len() - 1) % self.hits.len()
+        };
+    }
+
+    pub fn selected_hit(&self) -> Option<&Hit> {
+        self.hits.get(self.selected)
+    }
+
+    /// Instances with at least one matching key
+    pub fn matched(&self) -> usize {
+        let mut names: Vec<&str> = self.hits.iter().map(|h| h.instance.as_str()).collect();
+        names.dedup();
+        names.len()
+    }
+
+    /// Each value found with how many keys have it, most common first
+    pub fn values(&self) -> Vec<(&str, usize)> {
+        let mut values: Vec<(&str, usize)> = Vec::new();
+        for hit in &self.hits {
+            match values.iter_mut().find(|(value, _)| *value == hit.value) {
+                Some((_, count)) => *count += 1,
+                None => values.push((&hit.value, 1)),
+            }
+        }
+        values.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests;
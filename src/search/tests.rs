@@ -0,0 +1,47 @@
+//! Tests of the config key search
+
+use super::{matches, search, KeySearch};
+use crate::compare::tests::instance;
+use serde_json::json;
+
+#[test]
+fn matches_a_key_or_the_keys_a_pattern_starts() {
+    assert!(matches("security.privileged", "security.privileged"));
+    assert!(!matches("security.privileged", "security.privileged.x"));
+    assert!(matches("security.*", "security.nesting"));
+    assert!(!matches("security.*", "limits.cpu"));
+
+    // Volatile keys only when asked for
+    assert!(!matches("*", "volatile.eth0.hwaddr"));
+    assert!(matches("volatile.*", "volatile.eth0.hwaddr"));
+}
+
+#[test]
+fn lists_each_instance_with_the_key_and_where_it_comes_from() {
+    let mut web1 = instance(
+        "web1",
+        &["default", "nesting"],
+        json!({"security.nesting": "true", "security.privileged": "true"}),
+    );
+    web1.config
+        .insert("security.privileged".to_string(), "true".to_string());
+    let db1 = instance("db1", &["default"], json!({"security.privileged": "false"}));
+    let cache = instance("cache", &["default"], json!({"limits.cpu": "2"}));
+    let instances = [web1, db1, cache];
+
+    let hits = search("security.privileged", &instances);
+    let found: Vec<(&str, &str, bool)> = hits
+        .iter()
+        .map(|h| (h.instance.as_str(), h.value.as_str(), h.from_profile))
+        .collect();
+    assert_eq!(found, [("db1", "false", true), ("web1", "true", false)]);
+
+    let mut view = KeySearch::new("security.*".to_string());
+    view.set_instances(&instances);
+    assert_eq!(view.instances, 3);
+    assert_eq!(view.hits.len(), 3);
+    assert_eq!(view.matched(), 2);
+    assert_eq!(view.values(), [("true", 2), ("false", 1)]);
+    view.select(false);
+    assert_eq!(view.selected_hit().unwrap().key, "security.privileged");
+}
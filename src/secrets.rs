@@ -0,0 +1,221 @@
+//! Secret storage
+//!
+//! Keeps the client key and OIDC tokens in the OS keyring through
+//! `secret-tool` or `security`, or in private files without one.
+
+use crate::config::{write_private, SecretStore, SecretsConfig};
+use crate::helper;
+use log::{info, warn};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// Service name the secrets are filed under in the keyring
+const SERVICE: &str = "lxtui";
+
+/// A place secrets can be kept outside the config directory
+pub trait Keyring: Send + Sync {
+    /// Name shown in logs and errors
+    fn name(&self) -> &'static str;
+    /// The secret stored for `account`, None if there is none
+    fn get(&self, account: &str) -> io::Result<Option<String>>;
+    fn set(&self, account: &str, secret: &str) -> io::Result<()>;
+}
+
+/// Where secrets are kept: the keyring, when enabled and available, with
+/// files as the fallback
+pub struct Secrets {
+    store: SecretStore,
+    keyring: Option<Box<dyn Keyring>>,
+}
+
+static SECRETS: OnceLock<Secrets> = OnceLock::new();
+
+/// Choose where secrets are kept, from the `[secrets]` section of the config
+/// file. Until this is called, secrets are kept in files.
+pub fn init(config: &SecretsConfig) {
+    let keyring = system_keyring();
+    match (&keyring, config.store) {
+        (_, SecretStore::File) => info!("Keeping secrets in files"),
+        (Some(keyring), _) => info!("Keeping secrets in the {}", keyring.name()),
+        (None, SecretStore::Keyring) => warn!("No keyring found; secrets can't be stored"),
+        (None, SecretStore::Auto) => info!("No keyring found; keeping secrets in files"),
+    }
+    let _ = SECRETS.set(Secrets::new(config.store, keyring));
+}
+
+fn secrets() -> &'static Secrets {
+    SECRETS.get_or_init(|| Secrets::new(SecretStore::File, None))
+}
+
+/// Read the secret for `account`, stored in the keyring or at `path`
+pub fn load(account: &str, path: &Path) -> io::Result<Vec<u8>> {
+    secrets().load(account, path)
+}
+
+/// Store the secret for `account` in the keyring, or at `path` without one
+pub fn save(account: &str, path: &Path, contents: &[u8]) -> io::Result<()> {
+    secrets().save(account, path, contents)
+}
+
+impl Secrets {
+    pub fn new(store: SecretStore, keyring: Option<Box<dyn Keyring>>) -> Self {
+        let keyring = match store {
+            SecretStore::File => None,
+            SecretStore::Auto | SecretStore::Keyring => keyring,
+        };
+        Secrets { store, keyring }
+    }
+
+    pub fn load(&self, account: &str, path: &Path) -> io::Result<Vec<u8>> {
+        if let Some(keyring) = &self.keyring {
+            match keyring.get(account) {
+                Ok(Some(secret)) => return Ok(secret.into_bytes()),
+                Ok(None) => {}
+                Err(e) if self.store == SecretStore::Auto => {
+                    warn!(
+                        "Failed to read {} from the {}: {}",
+                        account,
+                        keyring.name(),
+                        e
+                    )
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        fs::read(path)
+    }
+
+    pub fn save(&self, account: &str, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let Some(keyring) = &self.keyring else {
+            return match self.store {
+                SecretStore::Keyring => Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "no keyring found (install secret-tool, or set [secrets] store = \"file\")",
+                )),
+                _ => write_private(path, contents),
+            };
+        };
+
+        let stored = std::str::from_utf8(contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            .and_then(|secret| keyring.set(account, secret));
+        match stored {
+            Ok(()) => {
+                // Don't leave an older copy behind in plain text
+                match fs::remove_file(path) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                    _ => Ok(()),
+                }
+            }
+            Err(e) if self.store == SecretStore::Auto => {
+                warn!(
+                    "Failed to store {} in the {}, writing {} instead: {}",
+                    account,
+                    keyring.name(),
+                    path.display(),
+                    e
+                );
+                write_private(path, contents)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The keyring of this system, if its command-line tool is installed
+fn system_keyring() -> Option<Box<dyn Keyring>> {
+    if cfg!(target_os = "macos") {
+        helper::in_path("security").then(|| Box::new(Keychain) as Box<dyn Keyring>)
+    } else {
+        helper::in_path("secret-tool").then(|| Box::new(SecretService) as Box<dyn Keyring>)
+    }
+}
+
+/// The freedesktop Secret Service, through libsecret's `secret-tool`
+struct SecretService;
+
+impl Keyring for SecretService {
+    fn name(&self) -> &'static str {
+        "Secret Service"
+    }
+
+    fn get(&self, account: &str) -> io::Result<Option<String>> {
+        let output = Command::new("secret-tool")
+            .args(["lookup", "service", SERVICE, "account", account])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        // Exits non-zero with no output when nothing matches
+        Ok((output.status.success() && !output.stdout.is_empty())
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    fn set(&self, account: &str, secret: &str) -> io::Result<()> {
+        // The secret goes through stdin, so it never shows in `ps`
+        let label = format!("LXTUI {}", account);
+        run_with_input(
+            Command::new("secret-tool").args([
+                "store", "--label", &label, "service", SERVICE, "account", account,
+            ]),
+            secret,
+        )
+    }
+}
+
+/// The macOS login keychain, through `security`
+struct Keychain;
+
+impl Keyring for Keychain {
+    fn name(&self) -> &'static str {
+        "macOS keychain"
+    }
+
+    fn get(&self, account: &str) -> io::Result<Option<String>> {
+        let output = Command::new("security")
+            .args(["find-generic-password", "-s", SERVICE, "-a", account, "-w"])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        // Prints the password followed by a newline
+        Ok(output.status.success().then(|| {
+            let secret = String::from_utf8_lossy(&output.stdout);
+            secret.strip_suffix('\n').unwrap_or(&secret).to_string()
+        }))
+    }
+
+    fn set(&self, account: &str, secret: &str) -> io::Result<()> {
+        // Commands read by `security -i` don't show in `ps`; hex spares
+        // quoting the secret
+        let hex: String = secret.bytes().map(|b| format!("{:02x}", b)).collect();
+        let command = format!(
+            "add-generic-password -U -s {} -a {} -X {}\n",
+            SERVICE, account, hex
+        );
+        run_with_input(Command::new("security").arg("-i"), &command)
+    }
+}
+
+fn run_with_input(command: &mut Command, input: &str) -> io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,107 @@
+//! Tests of choosing between the keyring and files for secrets
+
+use super::{Keyring, Secrets};
+use crate::config::SecretStore;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// A keyring kept in memory, or one that fails every call
+#[derive(Clone, Default)]
+struct MemoryKeyring {
+    secrets: Arc<Mutex<HashMap<String, String>>>,
+    broken: bool,
+}
+
+impl Keyring for MemoryKeyring {
+    fn name(&self) -> &'static str {
+        "test keyring"
+    }
+
+    fn get(&self, account: &str) -> io::Result<Option<String>> {
+        if self.broken {
+            return Err(io::Error::other("locked"));
+        }
+        Ok(self.secrets.lock().unwrap().get(account).cloned())
+    }
+
+    fn set(&self, account: &str, secret: &str) -> io::Result<()> {
+        if self.broken {
+            return Err(io::Error::other("locked"));
+        }
+        self.secrets
+            .lock()
+            .unwrap()
+            .insert(account.to_string(), secret.to_string());
+        Ok(())
+    }
+}
+
+fn secret_file() -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("lxtui-secrets-{}", Uuid::new_v4()))
+        .join("client.key")
+}
+
+#[test]
+fn moves_secrets_from_files_to_the_keyring() {
+    let keyring = MemoryKeyring::default();
+    let secrets = Secrets::new(SecretStore::Auto, Some(Box::new(keyring.clone())));
+    let path = secret_file();
+
+    // A file from before the keyring is still read
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "old key").unwrap();
+    assert_eq!(secrets.load("client.key", &path).unwrap(), b"old key");
+
+    // Saving moves it to the keyring and removes the file
+    secrets.save("client.key", &path, b"new key").unwrap();
+    assert!(!path.exists());
+    assert_eq!(
+        keyring.secrets.lock().unwrap().get("client.key").unwrap(),
+        "new key"
+    );
+    assert_eq!(secrets.load("client.key", &path).unwrap(), b"new key");
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn falls_back_to_files_unless_the_keyring_is_required() {
+    let broken = MemoryKeyring {
+        broken: true,
+        ..MemoryKeyring::default()
+    };
+    let path = secret_file();
+
+    let auto = Secrets::new(SecretStore::Auto, Some(Box::new(broken.clone())));
+    auto.save("oidc/prod", &path, b"tokens").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"tokens");
+    assert_eq!(auto.load("oidc/prod", &path).unwrap(), b"tokens");
+
+    let required = Secrets::new(SecretStore::Keyring, Some(Box::new(broken)));
+    assert!(required.save("oidc/prod", &path, b"newer").is_err());
+    assert!(required.load("oidc/prod", &path).is_err());
+    assert!(Secrets::new(SecretStore::Keyring, None)
+        .save("oidc/prod", &path, b"newer")
+        .is_err());
+    assert_eq!(fs::read(&path).unwrap(), b"tokens");
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn file_store_ignores_the_keyring() {
+    let keyring = MemoryKeyring::default();
+    let secrets = Secrets::new(SecretStore::File, Some(Box::new(keyring.clone())));
+    let path = secret_file();
+
+    secrets.save("client.key", &path, b"key").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"key");
+    assert!(keyring.secrets.lock().unwrap().is_empty());
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,106 @@
+//! Details of the connected server
+//!
+//! Version and storage driver come from the `environment` section of
+//! `GET /1.0`. The API doesn't report a snap's channel, so for the local
+//! snap-installed server it is read from `snap list`.
+
+use crate::lxd_api::ServerInfo;
+use std::cmp::Ordering;
+use tokio::process::Command;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerDetails {
+    pub server: String, // "LXD" or "Incus"
+    pub version: String,
+    pub storage: Option<String>, // Driver and its version, e.g. "zfs 2.1.5"
+    pub channel: Option<String>, // Snap channel, e.g. "5.21/stable"
+    pub outdated: Option<String>, // Minimum version, when the server is older
+}
+
+impl ServerDetails {
+    pub fn new(info: &ServerInfo, minimum_version: &str) -> Self {
+        let environment = &info.environment;
+        let server = match environment.server.as_str() {
+            "" | "lxd" => "LXD".to_string(),
+            "incus" => "Incus".to_string(),
+            other => other.to_string(),
+        };
+        let storage = match (
+            environment.storage.as_str(),
+            environment.storage_version.as_str(),
+        ) {
+            ("", _) => None,
+            (driver, "") => Some(driver.to_string()),
+            (driver, version) => Some(format!("{} {}", driver, version)),
+        };
+        let outdated = (compare_versions(&environment.server_version, minimum_version)
+            == Ordering::Less)
+            .then(|| minimum_version.to_string());
+
+        ServerDetails {
+            server,
+            version: environment.server_version.clone(),
+            storage,
+            channel: None,
+            outdated,
+        }
+    }
+
+    /// One line for the system menu, e.g. "LXD 5.21.1 · zfs 2.1.5 · 5.21/stable"
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} {}", self.server, self.version)];
+        parts.extend(self.storage.clone());
+        parts.extend(self.channel.clone());
+        parts.join(" · ")
+    }
+}
+
+/// Compare dotted version numbers; missing or non-numeric parts count as 0,
+/// so "5.21" equals "5.21.0". An unknown (empty) version is never older.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    if a.is_empty() || b.is_empty() {
+        return Ordering::Equal;
+    }
+    let parts = |v: &str| -> Vec<u64> {
+        v.split('.')
+            .map(|part| {
+                let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse().unwrap_or(0)
+            })
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    let len = a.len().max(b.len());
+    let at = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    (0..len)
+        .map(|i| at(&a, i).cmp(&at(&b, i)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Channel the local snap tracks, if `snap` knows the package
+pub async fn snap_channel(package: &str) -> Option<String> {
+    let output = Command::new("snap")
+        .args(["list", package])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_snap_list(&String::from_utf8_lossy(&output.stdout), package)
+}
+
+/// The "Tracking" column of `snap list` output for `package`
+fn parse_snap_list(output: &str, package: &str) -> Option<String> {
+    let mut lines = output.lines();
+    let header: Vec<&str> = lines.next()?.split_whitespace().collect();
+    let column = header.iter().position(|h| *h == "Tracking")?;
+    lines
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|fields| fields.first() == Some(&package))
+        .and_then(|fields| fields.get(column).map(|c| c.to_string()))
+}
+
+#[cfg(test)]
+mod tests;
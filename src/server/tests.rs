@@ -0,0 +1,49 @@
+//! Tests of server details
+
+use super::{compare_versions, parse_snap_list, ServerDetails};
+use crate::lxd_api::ServerInfo;
+use serde_json::json;
+use std::cmp::Ordering;
+
+fn info(environment: serde_json::Value) -> ServerInfo {
+    serde_json::from_value(json!({ "auth": "trusted", "environment": environment })).unwrap()
+}
+
+#[test]
+fn compares_dotted_versions() {
+    assert_eq!(compare_versions("5.21.1", "5.0"), Ordering::Greater);
+    assert_eq!(compare_versions("4.0.9", "5.0"), Ordering::Less);
+    assert_eq!(compare_versions("5.21", "5.21.0"), Ordering::Equal);
+    assert_eq!(compare_versions("5.10", "5.9"), Ordering::Greater);
+    assert_eq!(compare_versions("", "5.0"), Ordering::Equal);
+}
+
+#[test]
+fn summarises_the_environment() {
+    let details = ServerDetails::new(
+        &info(json!({
+            "server": "incus",
+            "server_version": "6.0.1",
+            "storage": "zfs",
+            "storage_version": "2.2.2"
+        })),
+        "5.0",
+    );
+    assert_eq!(details.summary(), "Incus 6.0.1 · zfs 2.2.2");
+    assert_eq!(details.outdated, None);
+
+    let old = ServerDetails::new(&info(json!({ "server_version": "4.0.9" })), "5.0");
+    assert_eq!(old.summary(), "LXD 4.0.9");
+    assert_eq!(old.outdated.as_deref(), Some("5.0"));
+}
+
+#[test]
+fn reads_the_tracked_snap_channel() {
+    let output = "Name  Version      Rev    Tracking       Publisher   Notes\n\
+                  lxd   5.21.1-2d13  28463  5.21/stable    canonical✓  -\n";
+    assert_eq!(
+        parse_snap_list(output, "lxd").as_deref(),
+        Some("5.21/stable")
+    );
+    assert_eq!(parse_snap_list(output, "incus"), None);
+}
@@ -0,0 +1,153 @@
+//! Starting and restarting the local LXD service
+//!
+//! Runs `snap` or `systemctl` through sudo or pkexec with the TUI suspended,
+//! then waits for the API and shows the journal if it doesn't answer.
+
+use crate::helper;
+use std::fmt;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::time::Instant;
+
+/// How long LXD gets to answer after the command returns
+pub const START_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Journal lines shown when the service doesn't come up
+const JOURNAL_LINES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceAction {
+    Start,
+    Restart,
+}
+
+impl fmt::Display for ServiceAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ServiceAction::Start => "start",
+            ServiceAction::Restart => "restart",
+        })
+    }
+}
+
+/// How the local LXD is installed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Service {
+    Snap,
+    Systemd { unit: String },
+}
+
+impl Service {
+    /// The snap when `/snap/bin/lxd` exists, otherwise the systemd unit of
+    /// whichever of LXD and Incus has a socket
+    pub fn detect() -> Option<Self> {
+        if Path::new("/snap/bin/lxd").exists() {
+            return Some(Service::Snap);
+        }
+        if !helper::in_path("systemctl") {
+            return None;
+        }
+        let unit = if Path::new("/var/lib/incus/unix.socket").exists() {
+            "incus"
+        } else {
+            "lxd"
+        };
+        Some(Service::Systemd {
+            unit: unit.to_string(),
+        })
+    }
+
+    /// systemd unit the daemon runs as, for the journal
+    pub fn unit(&self) -> &str {
+        match self {
+            Service::Snap => "snap.lxd.daemon",
+            Service::Systemd { unit } => unit,
+        }
+    }
+
+    /// Shell command performing `action`, run through `escalate` if given
+    pub fn command(&self, action: ServiceAction, escalate: Option<&str>) -> String {
+        let command = match self {
+            Service::Snap => format!("snap {} lxd", action),
+            Service::Systemd { unit } => format!("systemctl {} {}", action, unit),
+        };
+        match escalate {
+            Some(tool) => format!("{} {}", tool, command),
+            None => command,
+        }
+    }
+
+    /// Last lines the daemon logged. Reading another unit's journal needs
+    /// the adm or systemd-journal group; without it this says so.
+    pub async fn journal(&self) -> String {
+        let output = Command::new("journalctl")
+            .args(["--no-pager", "-n", &JOURNAL_LINES.to_string(), "-u"])
+            .arg(self.unit())
+            .output()
+            .await;
+        match output {
+            Ok(output) => {
+                let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if text.is_empty() || text.starts_with("-- No entries --") {
+                    format!(
+                        "No journal entries readable; run 'sudo journalctl -u {}'",
+                        self.unit()
+                    )
+                } else {
+                    text
+                }
+            }
+            Err(e) => format!("Failed to run journalctl: {}", e),
+        }
+    }
+}
+
+/// A service command LXTUI ran, waiting for the API to answer
+#[derive(Debug, Clone)]
+pub struct ServiceControl {
+    pub service: Service,
+    pub action: ServiceAction,
+    pub command: String,
+    pub finished: Option<Instant>, // When the command returned
+    pub last_check: Option<Instant>,
+}
+
+impl ServiceControl {
+    pub fn new(service: Service, action: ServiceAction, command: String) -> Self {
+        ServiceControl {
+            service,
+            action,
+            command,
+            finished: None,
+            last_check: None,
+        }
+    }
+
+    /// Whether to ask the API again, once a second after the command
+    pub fn check_due(&self, now: Instant) -> bool {
+        self.finished.is_some()
+            && self
+                .last_check
+                .is_none_or(|last| now.duration_since(last) >= Duration::from_secs(1))
+    }
+
+    pub fn timed_out(&self, now: Instant) -> bool {
+        self.finished
+            .is_some_and(|finished| now.duration_since(finished) >= START_TIMEOUT)
+    }
+}
+
+/// Tool that runs the service command as root; None when already root
+pub fn escalation() -> Option<&'static str> {
+    let root = std::fs::metadata("/proc/self").is_ok_and(|meta| meta.uid() == 0);
+    if root {
+        None
+    } else {
+        helper::escalation_tool()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,22 @@
+//! Tests of the service commands
+
+use super::{Service, ServiceAction};
+
+#[test]
+fn builds_the_manager_command() {
+    let snap = Service::Snap;
+    assert_eq!(
+        snap.command(ServiceAction::Restart, Some("sudo")),
+        "sudo snap restart lxd"
+    );
+    assert_eq!(snap.unit(), "snap.lxd.daemon");
+
+    let systemd = Service::Systemd {
+        unit: "incus".to_string(),
+    };
+    assert_eq!(
+        systemd.command(ServiceAction::Start, None),
+        "systemctl start incus"
+    );
+    assert_eq!(systemd.unit(), "incus");
+}
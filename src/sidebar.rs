@@ -0,0 +1,147 @@
+//! Operations sidebar layout
+//!
+//! A long batch run fills the sidebar with dozens of operations. They can
+//! be grouped by container or by status, filtered down to the failures or
+//! to those still running, and the finished ones cleared away, so what
+//! needs attention stays in view. Within a group the newest come first.
+
+use crate::app::{OperationStatus, UserOperation};
+use std::collections::BTreeMap;
+
+/// Heading of operations not on one container, such as image downloads
+const NO_CONTAINER: &str = "Other";
+
+fn is_active(status: &OperationStatus) -> bool {
+    matches!(
+        status,
+        OperationStatus::Registered | OperationStatus::Running | OperationStatus::Retrying(_)
+    )
+}
+
+/// Which operations the sidebar lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filter {
+    #[default]
+    All,
+    Active,
+    Failed,
+}
+
+impl Filter {
+    pub fn next(self) -> Self {
+        match self {
+            Filter::All => Filter::Active,
+            Filter::Active => Filter::Failed,
+            Filter::Failed => Filter::All,
+        }
+    }
+
+    /// For the sidebar's title; None when every operation is listed
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            Filter::All => None,
+            Filter::Active => Some("active"),
+            Filter::Failed => Some("failed"),
+        }
+    }
+
+    pub fn shows(self, status: &OperationStatus) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Active => is_active(status),
+            Filter::Failed => matches!(status, OperationStatus::Failed(_)),
+        }
+    }
+}
+
+/// How the sidebar groups operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Grouping {
+    #[default]
+    None,
+    Container,
+    Status,
+}
+
+impl Grouping {
+    pub fn next(self) -> Self {
+        match self {
+            Grouping::None => Grouping::Container,
+            Grouping::Container => Grouping::Status,
+            Grouping::Status => Grouping::None,
+        }
+    }
+
+    /// For the sidebar's title; None when not grouped
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            Grouping::None => None,
+            Grouping::Container => Some("by container"),
+            Grouping::Status => Some("by status"),
+        }
+    }
+}
+
+/// Order and heading of a status group: what needs attention first
+fn status_group(status: &OperationStatus) -> (u8, &'static str) {
+    match status {
+        _ if is_active(status) => (0, "Active"),
+        OperationStatus::Failed(_) => (1, "Failed"),
+        OperationStatus::Cancelled => (2, "Cancelled"),
+        _ => (3, "Succeeded"),
+    }
+}
+
+/// Operations under one heading, as indices into the operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub title: Option<String>, // None when not grouped
+    pub operations: Vec<usize>,
+}
+
+/// The operations the filter shows, grouped, newest first in each group
+pub fn arrange(operations: &[UserOperation], filter: Filter, grouping: Grouping) -> Vec<Group> {
+    let shown = (0..operations.len())
+        .rev()
+        .filter(|&i| filter.shows(&operations[i].status));
+    let mut groups: BTreeMap<(u8, String), Vec<usize>> = BTreeMap::new();
+    for i in shown {
+        let operation = &operations[i];
+        let key = match grouping {
+            Grouping::None => (0, String::new()),
+            Grouping::Container => match &operation.container {
+                Some(container) => (0, container.clone()),
+                None => (1, NO_CONTAINER.to_string()),
+            },
+            Grouping::Status => {
+                let (rank, title) = status_group(&operation.status);
+                (rank, title.to_string())
+            }
+        };
+        groups.entry(key).or_default().push(i);
+    }
+    groups
+        .into_iter()
+        .map(|((_, title), operations)| Group {
+            title: (grouping != Grouping::None).then_some(title),
+            operations,
+        })
+        .collect()
+}
+
+/// The operations listed, top to bottom
+pub fn listed(operations: &[UserOperation], filter: Filter, grouping: Grouping) -> Vec<usize> {
+    arrange(operations, filter, grouping)
+        .into_iter()
+        .flat_map(|group| group.operations)
+        .collect()
+}
+
+/// Whether clearing takes `operation` out: it finished, and the filter
+/// shows it
+pub fn clears(operation: &UserOperation, filter: Filter) -> bool {
+    !is_active(&operation.status) && filter.shows(&operation.status)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,100 @@
+//! Tests of the operations sidebar layout
+
+use super::{arrange, clears, listed, Filter, Group, Grouping};
+use crate::app::{OperationStatus, UserOperation};
+
+fn operation(description: &str, container: Option<&str>, status: OperationStatus) -> UserOperation {
+    UserOperation {
+        id: description.to_string(),
+        description: description.to_string(),
+        container: container.map(str::to_string),
+        status,
+        started_at: None,
+        completed_at: None,
+        retry_count: 0,
+        lxd_operation: None,
+        retry: None,
+        finished_at: None,
+        kind: None,
+        duration: None,
+    }
+}
+
+/// Oldest first, as the app keeps them
+fn batch() -> Vec<UserOperation> {
+    vec![
+        operation("Start web1", Some("web1"), OperationStatus::Success),
+        operation(
+            "Start web2",
+            Some("web2"),
+            OperationStatus::Failed("no".into()),
+        ),
+        operation("Download image", None, OperationStatus::Success),
+        operation("Stop web1", Some("web1"), OperationStatus::Running),
+        operation("Start web2", Some("web2"), OperationStatus::Cancelled),
+    ]
+}
+
+fn titles(groups: &[Group]) -> Vec<Option<&str>> {
+    groups.iter().map(|g| g.title.as_deref()).collect()
+}
+
+#[test]
+fn ungrouped_lists_newest_first() {
+    let groups = arrange(&batch(), Filter::All, Grouping::None);
+    assert_eq!(
+        groups,
+        [Group {
+            title: None,
+            operations: vec![4, 3, 2, 1, 0],
+        }]
+    );
+}
+
+#[test]
+fn groups_by_container_with_the_rest_last() {
+    let groups = arrange(&batch(), Filter::All, Grouping::Container);
+    assert_eq!(titles(&groups), [Some("web1"), Some("web2"), Some("Other")]);
+    assert_eq!(groups[0].operations, [3, 0]);
+    assert_eq!(groups[1].operations, [4, 1]);
+    assert_eq!(
+        listed(&batch(), Filter::All, Grouping::Container),
+        [3, 0, 4, 1, 2]
+    );
+}
+
+#[test]
+fn groups_by_status_with_what_needs_attention_first() {
+    let groups = arrange(&batch(), Filter::All, Grouping::Status);
+    assert_eq!(
+        titles(&groups),
+        [
+            Some("Active"),
+            Some("Failed"),
+            Some("Cancelled"),
+            Some("Succeeded")
+        ]
+    );
+    assert_eq!(groups[3].operations, [2, 0]);
+}
+
+#[test]
+fn filters_and_clearing() {
+    let operations = batch();
+    assert_eq!(listed(&operations, Filter::Active, Grouping::None), [3]);
+    assert_eq!(
+        listed(&operations, Filter::Failed, Grouping::Container),
+        [1]
+    );
+    assert_eq!(Filter::All.next().next().next(), Filter::All);
+
+    // Clearing never takes what is still running, and only what is shown
+    let cleared = |filter| {
+        (0..operations.len())
+            .filter(|&i| clears(&operations[i], filter))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(cleared(Filter::All), [0, 1, 2, 4]);
+    assert_eq!(cleared(Filter::Failed), [1]);
+    assert!(cleared(Filter::Active).is_empty());
+}
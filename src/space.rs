@@ -0,0 +1,81 @@
+//! Free space guard for creates and clones
+//!
+//! A copy that runs out of room half way can leave its storage pool full
+//! and wedged. Before an instance is created or cloned, the pool it lands
+//! in is read and the space it will take is added to what is in use; past
+//! `max_percent` of the pool, the `[space]` guard asks first or refuses.
+
+use crate::config::{SpaceConfig, SpaceGuard};
+use crate::dashboard::PoolUsage;
+use crate::watch::format_bytes;
+use std::collections::HashMap;
+
+/// A pool with the space a new instance would take from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Projection {
+    pub pool: PoolUsage,
+    pub adding: u64, // Bytes; 0 when the size isn't known beforehand
+}
+
+impl Projection {
+    /// Share of the pool in use afterwards, None when its size is unknown
+    pub fn percent(&self) -> Option<f64> {
+        let pool = &self.pool;
+        (pool.total > 0).then(|| (pool.used + self.adding) as f64 * 100.0 / pool.total as f64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Fine,
+    Warn(String),  // Ask before going ahead
+    Block(String), // Refuse
+}
+
+pub fn judge(config: &SpaceConfig, projection: &Projection) -> Verdict {
+    let Some(percent) = projection.percent() else {
+        return Verdict::Fine;
+    };
+    if config.guard == SpaceGuard::Off || percent <= f64::from(config.max_percent) {
+        return Verdict::Fine;
+    }
+    let pool = &projection.pool;
+    let figures = format!(
+        "{} of {}",
+        format_bytes((pool.used + projection.adding) as f64),
+        format_bytes(pool.total as f64)
+    );
+    let message = if projection.adding > 0 {
+        format!(
+            "Pool '{}' would be {:.0}% full after adding about {} ({}), over the {}% limit",
+            pool.name,
+            percent,
+            format_bytes(projection.adding as f64),
+            figures,
+            config.max_percent
+        )
+    } else {
+        format!(
+            "Pool '{}' is already {:.0}% full ({}), over the {}% limit",
+            pool.name, percent, figures, config.max_percent
+        )
+    };
+    match config.guard {
+        SpaceGuard::Block => Verdict::Block(message),
+        _ => Verdict::Warn(message),
+    }
+}
+
+/// Pool of the root disk among `devices`
+pub fn root_pool(devices: &HashMap<String, HashMap<String, String>>) -> Option<String> {
+    devices
+        .values()
+        .find(|device| {
+            device.get("type").map(String::as_str) == Some("disk")
+                && device.get("path").map(String::as_str) == Some("/")
+        })
+        .and_then(|device| device.get("pool").cloned())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+//! Tests of judging free space before creates and clones
+
+use super::{judge, root_pool, Projection, Verdict};
+use crate::config::{SpaceConfig, SpaceGuard};
+use crate::dashboard::PoolUsage;
+use std::collections::HashMap;
+
+const GIB: u64 = 1 << 30;
+
+fn projection(used: u64, total: u64, adding: u64) -> Projection {
+    Projection {
+        pool: PoolUsage {
+            name: "fast".to_string(),
+            driver: "zfs".to_string(),
+            used,
+            total,
+        },
+        adding,
+    }
+}
+
+#[test]
+fn warns_or_blocks_past_the_limit() {
+    let warn = SpaceConfig::default();
+    assert_eq!(
+        judge(&warn, &projection(20 * GIB, 32 * GIB, 8 * GIB)),
+        Verdict::Fine
+    );
+    assert_eq!(
+        judge(&warn, &projection(28 * GIB, 32 * GIB, 2 * GIB)),
+        Verdict::Warn(
+            "Pool 'fast' would be 94% full after adding about 2.0 GiB \
+             (30.0 GiB of 32.0 GiB), over the 90% limit"
+                .to_string()
+        )
+    );
+
+    let block = SpaceConfig {
+        guard: SpaceGuard::Block,
+        max_percent: 80,
+    };
+    assert_eq!(
+        judge(&block, &projection(29 * GIB, 32 * GIB, 0)),
+        Verdict::Block(
+            "Pool 'fast' is already 91% full (29.0 GiB of 32.0 GiB), over the 80% limit"
+                .to_string()
+        )
+    );
+
+    let off = SpaceConfig {
+        guard: SpaceGuard::Off,
+        ..SpaceConfig::default()
+    };
+    assert_eq!(
+        judge(&off, &projection(32 * GIB, 32 * GIB, GIB)),
+        Verdict::Fine
+    );
+    // A pool of unknown size can't be judged
+    assert_eq!(judge(&block, &projection(GIB, 0, GIB)), Verdict::Fine);
+}
+
+#[test]
+fn finds_the_root_disk_pool() {
+    let device = |options: &[(&str, &str)]| -> HashMap<String, String> {
+        options
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    };
+    let mut devices = HashMap::from([
+        (
+            "data".to_string(),
+            device(&[("type", "disk"), ("path", "/srv"), ("pool", "slow")]),
+        ),
+        ("eth0".to_string(), device(&[("type", "nic")])),
+    ]);
+    assert_eq!(root_pool(&devices), None);
+
+    devices.insert(
+        "root".to_string(),
+        device(&[("type", "disk"), ("path", "/"), ("pool", "fast")]),
+    );
+    assert_eq!(root_pool(&devices).as_deref(), Some("fast"));
+}
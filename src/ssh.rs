@@ -0,0 +1,129 @@
+//! SSH tunnels
+//!
+//! Forwards a remote host's LXD Unix socket to a local socket with the
+//! system `ssh` client, for servers that expose SSH but not the HTTPS API.
+//! Authentication is left to ssh (keys, agent, `~/.ssh/config`); it runs in
+//! batch mode because the TUI owns the terminal and can't answer prompts.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use url::Url;
+use uuid::Uuid;
+
+/// LXD socket used on the remote host when the remote doesn't set one
+pub const DEFAULT_REMOTE_SOCKET: &str = "/var/snap/lxd/common/lxd/unix.socket";
+
+/// How long to wait for ssh to connect and create the forwarded socket
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Error)]
+pub enum SshError {
+    #[error("Invalid SSH address '{0}', expected ssh://[user@]host[:port]")]
+    InvalidUrl(String),
+    #[error("Failed to run ssh: {0}")]
+    Spawn(#[from] std::io::Error),
+    #[error("ssh exited: {0}")]
+    Exited(String),
+    #[error("Timed out waiting for the SSH tunnel to {0}")]
+    Timeout(String),
+}
+
+/// A running `ssh -L` process; dropping it closes the tunnel
+pub struct SshTunnel {
+    child: Child,
+    local_socket: PathBuf,
+}
+
+impl SshTunnel {
+    /// Start ssh and wait until the forwarded socket accepts connections.
+    /// This blocks for up to `CONNECT_TIMEOUT`.
+    pub fn open(url: &str, remote_socket: &str) -> Result<Self, SshError> {
+        let destination = parse_destination(url)?;
+        let local_socket = dirs::runtime_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(format!("lxtui-ssh-{}.sock", Uuid::new_v4()));
+
+        let mut command = Command::new("ssh");
+        command
+            .arg("-N")
+            .args(["-o", "BatchMode=yes"])
+            .args(["-o", "ExitOnForwardFailure=yes"])
+            .args(["-o", "StreamLocalBindUnlink=yes"])
+            .arg("-L")
+            .arg(format!("{}:{}", local_socket.display(), remote_socket))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        if let Some(port) = destination.port {
+            command.args(["-p", &port.to_string()]);
+        }
+        command.arg(&destination.host);
+
+        let mut tunnel = SshTunnel {
+            child: command.spawn()?,
+            local_socket,
+        };
+
+        let started = Instant::now();
+        while !tunnel.local_socket.exists() {
+            if tunnel.child.try_wait()?.is_some() {
+                let mut stderr = String::new();
+                if let Some(mut pipe) = tunnel.child.stderr.take() {
+                    let _ = pipe.read_to_string(&mut stderr);
+                }
+                return Err(SshError::Exited(stderr.trim().to_string()));
+            }
+            if started.elapsed() > CONNECT_TIMEOUT {
+                return Err(SshError::Timeout(destination.host));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        Ok(tunnel)
+    }
+
+    pub fn local_socket(&self) -> &Path {
+        &self.local_socket
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_file(&self.local_socket);
+    }
+}
+
+struct Destination {
+    host: String, // "user@host" or "host"
+    port: Option<u16>,
+}
+
+fn parse_destination(input: &str) -> Result<Destination, SshError> {
+    let invalid = || SshError::InvalidUrl(input.to_string());
+
+    let url = Url::parse(input.trim()).map_err(|_| invalid())?;
+    if url.scheme() != "ssh" {
+        return Err(invalid());
+    }
+    let host = url.host_str().ok_or_else(invalid)?;
+
+    let host = if url.username().is_empty() {
+        host.to_string()
+    } else {
+        format!("{}@{}", url.username(), host)
+    };
+    Ok(Destination {
+        host,
+        port: url.port(),
+    })
+}
+
+/// Whether a remote address should be reached over SSH
+pub fn is_ssh_url(url: &str) -> bool {
+    url.trim().starts_with("ssh://")
+}
@@ -0,0 +1,124 @@
+//! Image store
+//!
+//! The images copied into the server's store, with where they came from,
+//! their auto-update setting and refreshes to the newest build.
+
+use crate::images;
+use crate::lxd_api::LxdImage;
+use chrono::{DateTime, Datelike, FixedOffset};
+
+/// One image in the store
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredImage {
+    pub fingerprint: String,
+    pub description: String,
+    pub aliases: Vec<String>,
+    pub source: Option<String>, // Remote alias it was copied from, like images:debian/12
+    pub auto_update: bool,
+    pub cached: bool,
+    pub size: u64, // Bytes
+    pub created_at: String,
+    pub expires_at: Option<DateTime<FixedOffset>>, // None never expires
+}
+
+impl StoredImage {
+    pub fn new(image: &LxdImage) -> Self {
+        let aliases: Vec<String> = image.aliases.iter().map(|a| a.name.clone()).collect();
+        StoredImage {
+            fingerprint: image.fingerprint.clone(),
+            description: image
+                .properties
+                .get("description")
+                .filter(|d| !d.trim().is_empty())
+                .cloned()
+                .or_else(|| aliases.first().cloned())
+                .unwrap_or_else(|| image.fingerprint.chars().take(12).collect()),
+            aliases,
+            source: image
+                .update_source
+                .as_ref()
+                .map(|source| images::remote_alias(&source.server, &source.alias)),
+            auto_update: image.auto_update,
+            cached: image.cached,
+            size: image.size,
+            created_at: image.created_at.clone(),
+            // LXD reports year 1 for images that never expire
+            expires_at: DateTime::parse_from_rfc3339(&image.expires_at)
+                .ok()
+                .filter(|at| at.year() > 1),
+        }
+    }
+
+    /// Whether LXD can refresh it: only images copied from a remote can be
+    pub fn refreshable(&self) -> bool {
+        self.source.is_some()
+    }
+}
+
+/// The image store view: images copied from remotes first, then those
+/// published or imported locally
+#[derive(Debug)]
+pub struct ImageStore {
+    pub images: Vec<StoredImage>,
+    pub selected: usize,
+    pub error: Option<String>,  // Why the last load failed
+    pub status: Option<String>, // Outcome of the last change or refresh
+}
+
+impl ImageStore {
+    pub fn new() -> Self {
+        ImageStore {
+            images: Vec::new(),
+            selected: 0,
+            error: None,
+            status: None,
+        }
+    }
+
+    /// Take in a fresh listing, keeping the selected image. A refreshed
+    /// image has a new fingerprint, so it is followed by its aliases too.
+    pub fn set_images(&mut self, mut images: Vec<StoredImage>) {
+        let selected = self.selected_image().cloned();
+        images.sort_by(|a, b| {
+            (!a.refreshable(), &a.description, &a.fingerprint).cmp(&(
+                !b.refreshable(),
+                &b.description,
+                &b.fingerprint,
+            ))
+        });
+        self.images = images;
+        self.error = None;
+        self.selected = selected
+            .and_then(|selected| {
+                self.images
+                    .iter()
+                    .position(|i| i.fingerprint == selected.fingerprint)
+                    .or_else(|| {
+                        self.images.iter().position(|i| {
+                            i.aliases.iter().any(|a| selected.aliases.contains(a))
+                                || (i.source.is_some() && i.source == selected.source)
+                        })
+                    })
+            })
+            .unwrap_or(0)
+            .min(self.images.len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.images.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.images.len()
+        } else {
+            (self.selected + self.images.len() - 1) % self.images.len()
+        };
+    }
+
+    pub fn selected_image(&self) -> Option<&StoredImage> {
+        self.images.get(self.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,80 @@
+//! Tests of the image store view
+
+use super::{ImageStore, StoredImage};
+use crate::lxd_api::{LxdImage, LxdImageAlias, LxdImageSource};
+
+const IMAGES: &str = "https://images.linuxcontainers.org";
+
+fn image(fingerprint: &str, alias: &str, server: Option<&str>) -> StoredImage {
+    StoredImage::new(&LxdImage {
+        fingerprint: fingerprint.to_string(),
+        aliases: vec![LxdImageAlias {
+            name: alias.to_string(),
+            description: String::new(),
+            target: String::new(),
+        }],
+        update_source: server.map(|server| LxdImageSource {
+            alias: alias.to_string(),
+            server: server.to_string(),
+            protocol: "simplestreams".to_string(),
+        }),
+        ..LxdImage::default()
+    })
+}
+
+#[test]
+fn names_where_images_came_from() {
+    let debian = image(
+        "aaa111",
+        "debian/12",
+        Some("https://images.linuxcontainers.org/"),
+    );
+    assert_eq!(debian.description, "debian/12");
+    assert_eq!(debian.source.as_deref(), Some("images:debian/12"));
+    assert!(debian.refreshable());
+
+    let mirror = image("bbb222", "alpine/3.20", Some("https://mirror.example.net"));
+    assert_eq!(
+        mirror.source.as_deref(),
+        Some("https://mirror.example.net alpine/3.20")
+    );
+
+    // Published locally: nothing to refresh from, and no alias to go by
+    let published = StoredImage::new(&LxdImage {
+        fingerprint: "0123456789abcdef".to_string(),
+        ..LxdImage::default()
+    });
+    assert_eq!(published.description, "0123456789ab");
+    assert!(!published.refreshable());
+}
+
+#[test]
+fn lists_remote_images_first_and_follows_a_refresh() {
+    let mut store = ImageStore::new();
+    store.set_images(vec![
+        image("ccc333", "golden-web", None),
+        image("bbb222", "ubuntu/24.04", Some(IMAGES)),
+        image("aaa111", "debian/12", Some(IMAGES)),
+    ]);
+    let listed: Vec<&str> = store
+        .images
+        .iter()
+        .map(|i| i.description.as_str())
+        .collect();
+    assert_eq!(listed, ["debian/12", "ubuntu/24.04", "golden-web"]);
+
+    store.select(true);
+    assert_eq!(store.selected_image().unwrap().fingerprint, "bbb222");
+
+    // The refreshed build has a new fingerprint but the same alias
+    store.set_images(vec![
+        image("aaa111", "debian/12", Some(IMAGES)),
+        image("ddd444", "ubuntu/24.04", Some(IMAGES)),
+        image("ccc333", "golden-web", None),
+    ]);
+    assert_eq!(store.selected_image().unwrap().fingerprint, "ddd444");
+
+    store.select(false);
+    store.select(false);
+    assert_eq!(store.selected_image().unwrap().description, "golden-web");
+}
@@ -0,0 +1,215 @@
+//! Command-line subcommands
+//!
+//! `lxtui start web1` and friends run one operation through the same client
+//! the TUI uses, report how it went and exit, so LXTUI also works from
+//! scripts and the shell.
+
+use crate::completions::{self, Shell};
+use crate::config::{Config, InventoryFormat};
+use crate::estimate;
+use crate::lxc::{LxcClient, LxcError};
+use crate::Cli;
+use anyhow::{bail, Result};
+use clap::{CommandFactory, Subcommand};
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, ExitStatus};
+use tokio::time::{sleep, Duration};
+
+/// How often a running operation is checked
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Start an instance and wait until it runs
+    Start { name: String },
+    /// Stop an instance and wait until it has stopped
+    Stop { name: String },
+    /// Run a command in a local instance, or a shell without one
+    Exec {
+        name: String,
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// Snapshot an instance; LXD names it unless SNAPSHOT is given
+    Snapshot {
+        name: String,
+        snapshot: Option<String>,
+    },
+    /// Print the server's instances, profiles, networks, pools and images
+    Inventory {
+        /// Print JSON instead of YAML
+        #[arg(long)]
+        json: bool,
+        /// Leave out status, volatile keys and the time, for diffing
+        #[arg(long)]
+        stable: bool,
+    },
+    /// Print the completion script for SHELL
+    Completions { shell: Shell },
+    /// Print the man page, in roff
+    Man,
+}
+
+/// Run `action` and return the process exit code
+pub async fn run(action: Action, remote: Option<&str>) -> Result<i32> {
+    match &action {
+        Action::Exec { name, command } => {
+            if remote.is_some() || name.contains(':') {
+                bail!("shell access is only supported for local containers; use 'lxc exec <remote>:<name>'");
+            }
+            let status = exec(name, command)?;
+            return Ok(status.code().unwrap_or(1));
+        }
+        Action::Completions { shell } => {
+            print!("{}", completions::script(*shell, Cli::command()));
+            return Ok(0);
+        }
+        Action::Man => {
+            print!("{}", completions::man_page(Cli::command()));
+            return Ok(0);
+        }
+        Action::Inventory { json, stable } => {
+            let (client, _) = connect("", remote)?;
+            print!("{}", inventory(&client, remote, *json, *stable).await?);
+            return Ok(0);
+        }
+        _ => {}
+    }
+
+    let (client, name) = connect(action_target(&action), remote)?;
+    println!("{}", perform(&client, &action, &name).await?);
+    Ok(0)
+}
+
+fn action_target(action: &Action) -> &str {
+    match action {
+        Action::Start { name }
+        | Action::Stop { name }
+        | Action::Exec { name, .. }
+        | Action::Snapshot { name, .. } => name,
+        Action::Completions { .. } | Action::Man | Action::Inventory { .. } => {
+            unreachable!("needs no instance")
+        }
+    }
+}
+
+/// The client for a name qualified as "remote:name", or for `--remote`, or
+/// the local socket
+fn connect(target: &str, remote: Option<&str>) -> Result<(LxcClient, String)> {
+    let (remote, name) = match target.split_once(':') {
+        Some((remote, name)) => (Some(remote), name),
+        None => (remote, target),
+    };
+    let client = match remote {
+        Some(remote) => LxcClient::for_remote(Config::load()?.remote(remote)?)?,
+        None => LxcClient::local()?,
+    };
+    Ok((client, name.to_string()))
+}
+
+/// Carry out a start, stop or snapshot, returning what to print
+pub async fn perform(client: &LxcClient, action: &Action, name: &str) -> Result<String, LxcError> {
+    match action {
+        Action::Start { .. } => {
+            let operation = client.start_container_async(name).await?;
+            follow(client, &operation, &format!("Starting {}", name)).await?;
+            Ok(format!("Started {}", name))
+        }
+        Action::Stop { .. } => {
+            let operation = client.stop_container_async(name).await?;
+            follow(client, &operation, &format!("Stopping {}", name)).await?;
+            Ok(format!("Stopped {}", name))
+        }
+        Action::Snapshot { snapshot, .. } => {
+            client.create_snapshot(name, snapshot.as_deref()).await?;
+            Ok(match snapshot {
+                Some(snapshot) => format!("Created snapshot {} of {}", snapshot, name),
+                None => format!("Created snapshot of {}", name),
+            })
+        }
+        Action::Exec { .. } => unreachable!("exec runs through the lxc client"),
+        Action::Completions { .. } | Action::Man | Action::Inventory { .. } => {
+            unreachable!("needs no instance")
+        }
+    }
+}
+
+/// The inventory of `remote`, or of the local server, as text
+pub async fn inventory(
+    client: &LxcClient,
+    remote: Option<&str>,
+    json: bool,
+    stable: bool,
+) -> Result<String> {
+    let mut inventory = client.inventory(remote.unwrap_or("local"), stable).await?;
+    if !stable {
+        inventory.exported_at = Some(chrono::Local::now().to_rfc3339());
+    }
+    let format = if json {
+        InventoryFormat::Json
+    } else {
+        InventoryFormat::Yaml
+    };
+    Ok(inventory.render(format)?)
+}
+
+/// Wait for an LXD operation, showing its progress on a terminal
+async fn follow(client: &LxcClient, operation: &str, label: &str) -> Result<(), LxcError> {
+    let show_progress = io::stderr().is_terminal();
+    loop {
+        let status = client.get_lxd_operation(operation).await?;
+        match status.status_code {
+            200 => break,
+            400 | 401 => {
+                if show_progress {
+                    eprintln!();
+                }
+                let error = if status.err.is_empty() {
+                    status.status
+                } else {
+                    status.err
+                };
+                return Err(LxcError::ApiError(error));
+            }
+            _ => {
+                if show_progress {
+                    let progress = status.metadata.as_ref().and_then(estimate::progress);
+                    match progress {
+                        Some(percent) => eprint!("\r{}: {}%", label, percent),
+                        None => eprint!("\r{}...", label),
+                    }
+                    let _ = io::stderr().flush();
+                }
+                sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+    if show_progress {
+        eprint!("\r\x1b[K");
+    }
+    Ok(())
+}
+
+/// Run `command` in a local container through `lxc exec` on this terminal;
+/// without a command, open bash, falling back to sh
+pub fn exec(name: &str, command: &[String]) -> io::Result<ExitStatus> {
+    if !command.is_empty() {
+        return Command::new("lxc")
+            .args(["exec", name, "--"])
+            .args(command)
+            .status();
+    }
+
+    let status = Command::new("lxc")
+        .args(["exec", name, "--", "/bin/bash"])
+        .status()?;
+    if status.success() {
+        return Ok(status);
+    }
+    Command::new("lxc")
+        .args(["exec", name, "--", "/bin/sh"])
+        .status()
+}
+
+#[cfg(test)]
+mod tests;
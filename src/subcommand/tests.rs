@@ -0,0 +1,101 @@
+//! Tests of the command-line subcommands against the fake LXD server
+
+use super::{inventory, perform, Action};
+use crate::fake_lxd::FakeLxd;
+use crate::inventory::Inventory;
+use crate::Cli;
+use clap::Parser;
+
+#[test]
+fn parses_subcommands() {
+    let cli = Cli::try_parse_from(["lxtui", "exec", "web1", "--", "ls", "-l"]).unwrap();
+    assert_eq!(
+        cli.action,
+        Some(Action::Exec {
+            name: "web1".to_string(),
+            command: vec!["ls".to_string(), "-l".to_string()],
+        })
+    );
+
+    let cli = Cli::try_parse_from(["lxtui", "--remote", "prod", "stop", "db1"]).unwrap();
+    assert_eq!(cli.remote.as_deref(), Some("prod"));
+    assert_eq!(
+        cli.action,
+        Some(Action::Stop {
+            name: "db1".to_string()
+        })
+    );
+
+    let cli = Cli::try_parse_from(["lxtui", "inventory", "--stable"]).unwrap();
+    assert_eq!(
+        cli.action,
+        Some(Action::Inventory {
+            json: false,
+            stable: true
+        })
+    );
+
+    assert!(Cli::try_parse_from(["lxtui"]).unwrap().action.is_none());
+}
+
+#[tokio::test]
+async fn prints_the_inventory() {
+    let lxd = FakeLxd::start()
+        .with_instance("web1", "Running", None)
+        .with_config("web1", "volatile.eth0.hwaddr", "00:16:3e:00:00:01")
+        .with_profile("monitoring");
+    let client = lxd.client();
+
+    let yaml = inventory(&client, None, false, true).await.unwrap();
+    let parsed: Inventory = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(parsed.server, "local");
+    assert_eq!(parsed.exported_at, None);
+    assert!(parsed.instances["web1"].config.is_empty());
+    assert_eq!(
+        parsed.profiles.keys().collect::<Vec<_>>(),
+        ["default", "monitoring"]
+    );
+
+    let json = inventory(&client, Some("prod"), true, false).await.unwrap();
+    let parsed: Inventory = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.server, "prod");
+    assert!(parsed.exported_at.is_some());
+    assert_eq!(parsed.instances["web1"].status.as_deref(), Some("Running"));
+}
+
+#[tokio::test]
+async fn start_stop_and_snapshot() {
+    let lxd = FakeLxd::start().with_instance("web1", "Stopped", None);
+    let client = lxd.client();
+    let start = Action::Start {
+        name: "web1".to_string(),
+    };
+
+    assert_eq!(
+        perform(&client, &start, "web1").await.unwrap(),
+        "Started web1"
+    );
+    assert_eq!(lxd.status("web1").as_deref(), Some("Running"));
+
+    let snapshot = Action::Snapshot {
+        name: "web1".to_string(),
+        snapshot: Some("before".to_string()),
+    };
+    assert_eq!(
+        perform(&client, &snapshot, "web1").await.unwrap(),
+        "Created snapshot before of web1"
+    );
+    assert_eq!(lxd.snapshots("web1"), ["before"]);
+}
+
+#[tokio::test]
+async fn failures_are_returned() {
+    let lxd = FakeLxd::start().with_instance("web1", "Running", None);
+    lxd.fail_operations("Instance is busy");
+    let stop = Action::Stop {
+        name: "web1".to_string(),
+    };
+
+    let error = perform(&lxd.client(), &stop, "web1").await.unwrap_err();
+    assert!(error.to_string().contains("Instance is busy"));
+}
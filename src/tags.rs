@@ -0,0 +1,32 @@
+//! Container tags
+//!
+//! Tags live in the instance's `user.lxtui.tags` config key as a comma
+//! separated list, so they travel with the instance and show up in
+//! `lxc config show` as well.
+
+/// Config key holding an instance's tags
+pub const CONFIG_KEY: &str = "user.lxtui.tags";
+
+/// Tags in a config value or typed list, trimmed, without blanks or repeats
+pub fn parse(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// The config value for `tags`; empty removes the key
+pub fn join(tags: &[String]) -> String {
+    tags.join(",")
+}
+
+/// A character allowed in a typed tag list
+pub fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ',' | ' ')
+}
+
+#[cfg(test)]
+mod tests;
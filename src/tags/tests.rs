@@ -0,0 +1,16 @@
+//! Tests of parsing tag lists
+
+use super::{join, parse};
+
+#[test]
+fn parses_comma_separated_tags() {
+    assert_eq!(parse("web, prod ,,eu-west"), ["web", "prod", "eu-west"]);
+    assert_eq!(parse("web,Web,WEB"), ["web"]);
+    assert!(parse(" , ").is_empty());
+}
+
+#[test]
+fn joins_for_the_config_key() {
+    assert_eq!(join(&parse("web, prod")), "web,prod");
+    assert_eq!(join(&[]), "");
+}
@@ -0,0 +1,174 @@
+//! Image metadata and templates
+//!
+//! Lists the `metadata.yaml` and template files an instance would be
+//! published with, and opens them in the user's editor.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// When LXD renders a template
+pub const TRIGGERS: &[&str] = &["create", "copy", "start", "rename"];
+
+const HEADER: &str = "\
+### This is a YAML representation of the instance's image metadata.
+### Lines starting with '#' are ignored.
+###
+### Each template renders templates/<template> to the path it is listed
+### under, at the events in 'when': create, copy, start or rename.
+";
+
+#[derive(Debug, Error)]
+pub enum MetadataError {
+    #[error("Invalid YAML: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("Template for '{0}' names no file")]
+    NoFile(String),
+    #[error("Template for '{path}' runs on '{trigger}'; use create, copy, start or rename")]
+    UnknownTrigger { path: String, trigger: String },
+}
+
+/// `/1.0/instances/{name}/metadata`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InstanceMetadata {
+    #[serde(default)]
+    pub architecture: String,
+    #[serde(default)]
+    pub creation_date: i64,
+    #[serde(default)]
+    pub expiry_date: i64,
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+    #[serde(default)]
+    pub templates: BTreeMap<String, TemplateEntry>, // By the path rendered to
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateEntry {
+    #[serde(default)]
+    pub when: Vec<String>,
+    #[serde(default)]
+    pub create_only: bool, // Leave the path alone if it already exists
+    #[serde(default)]
+    pub template: String, // File under templates/
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+}
+
+impl InstanceMetadata {
+    /// The file handed to the editor
+    pub fn to_yaml(&self) -> Result<String, MetadataError> {
+        Ok(format!("{}{}", HEADER, serde_yaml::to_string(self)?))
+    }
+
+    /// Validate an edited file
+    pub fn parse(text: &str) -> Result<Self, MetadataError> {
+        let metadata: InstanceMetadata = serde_yaml::from_str(text)?;
+        for (path, entry) in &metadata.templates {
+            if entry.template.trim().is_empty() {
+                return Err(MetadataError::NoFile(path.clone()));
+            }
+            if let Some(trigger) = entry.when.iter().find(|w| !TRIGGERS.contains(&w.as_str())) {
+                return Err(MetadataError::UnknownTrigger {
+                    path: path.clone(),
+                    trigger: trigger.clone(),
+                });
+            }
+        }
+        Ok(metadata)
+    }
+
+    /// The body of the PUT request
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+/// A template file and where it renders to
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateFile {
+    pub name: String,
+    pub targets: Vec<(String, Vec<String>)>, // Path in the instance, triggers
+    pub exists: bool, // False when the metadata names a file the instance lacks
+}
+
+#[derive(Debug)]
+pub struct TemplatesView {
+    pub container: String, // qualified name
+    pub metadata: Option<InstanceMetadata>,
+    pub files: Vec<TemplateFile>,
+    pub selected: usize,
+    pub error: Option<String>,  // Why the last load failed
+    pub status: Option<String>, // What the last change did
+}
+
+impl TemplatesView {
+    pub fn new(container: String) -> Self {
+        TemplatesView {
+            container,
+            metadata: None,
+            files: Vec::new(),
+            selected: 0,
+            error: None,
+            status: None,
+        }
+    }
+
+    /// Take in the metadata and the template files the instance has
+    pub fn load(&mut self, metadata: InstanceMetadata, names: Vec<String>) {
+        let mut files: BTreeMap<String, TemplateFile> = names
+            .into_iter()
+            .map(|name| {
+                let file = TemplateFile {
+                    name: name.clone(),
+                    targets: Vec::new(),
+                    exists: true,
+                };
+                (name, file)
+            })
+            .collect();
+        for (path, entry) in &metadata.templates {
+            files
+                .entry(entry.template.clone())
+                .or_insert_with(|| TemplateFile {
+                    name: entry.template.clone(),
+                    targets: Vec::new(),
+                    exists: false,
+                })
+                .targets
+                .push((path.clone(), entry.when.clone()));
+        }
+
+        let selected = self.selected_file().map(|f| f.name.clone());
+        self.files = files.into_values().collect();
+        self.selected = selected
+            .and_then(|name| self.files.iter().position(|f| f.name == name))
+            .unwrap_or(0)
+            .min(self.files.len().saturating_sub(1));
+        self.metadata = Some(metadata);
+        self.error = None;
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.files.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.files.len()
+        } else {
+            (self.selected + self.files.len() - 1) % self.files.len()
+        };
+    }
+
+    pub fn selected_file(&self) -> Option<&TemplateFile> {
+        self.files.get(self.selected)
+    }
+}
+
+/// A character allowed in a new template's file name
+pub fn is_file_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,88 @@
+//! Tests of reading and validating image metadata
+
+use super::{InstanceMetadata, MetadataError, TemplatesView};
+
+const METADATA: &str = "
+architecture: x86_64
+creation_date: 1700000000
+properties:
+  os: Ubuntu
+  release: noble
+templates:
+  /etc/hostname:
+    when: [create, copy]
+    template: hostname.tpl
+  /etc/hosts:
+    when: [create, copy]
+    create_only: true
+    template: hosts.tpl
+  /etc/motd:
+    when: [start]
+    template: motd.tpl
+";
+
+#[test]
+fn round_trips_through_yaml() {
+    let metadata = InstanceMetadata::parse(METADATA).unwrap();
+    assert_eq!(metadata.properties["os"], "Ubuntu");
+    assert!(metadata.templates["/etc/hosts"].create_only);
+
+    let yaml = metadata.to_yaml().unwrap();
+    assert!(yaml.starts_with("###"));
+    assert_eq!(InstanceMetadata::parse(&yaml).unwrap(), metadata);
+}
+
+#[test]
+fn rejects_unknown_triggers() {
+    let text = METADATA.replace("when: [start]", "when: [boot]");
+    assert!(matches!(
+        InstanceMetadata::parse(&text),
+        Err(MetadataError::UnknownTrigger { path, trigger }) if path == "/etc/motd" && trigger == "boot"
+    ));
+}
+
+#[test]
+fn rejects_entries_without_a_file() {
+    let text = METADATA.replace("template: motd.tpl", "template: ''");
+    assert!(matches!(
+        InstanceMetadata::parse(&text),
+        Err(MetadataError::NoFile(path)) if path == "/etc/motd"
+    ));
+}
+
+#[test]
+fn lists_files_with_their_targets() {
+    let mut view = TemplatesView::new("web1".to_string());
+    view.load(
+        InstanceMetadata::parse(METADATA).unwrap(),
+        vec![
+            "hostname.tpl".to_string(),
+            "hosts.tpl".to_string(),
+            "unused.tpl".to_string(),
+        ],
+    );
+
+    let names: Vec<&str> = view.files.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["hostname.tpl", "hosts.tpl", "motd.tpl", "unused.tpl"]
+    );
+    assert_eq!(
+        view.files[0].targets,
+        [(
+            "/etc/hostname".to_string(),
+            vec!["create".to_string(), "copy".to_string()]
+        )]
+    );
+    // Named in the metadata but missing from the instance
+    assert!(!view.files[2].exists);
+    assert!(view.files[3].targets.is_empty());
+
+    // Reloading keeps the selected file
+    view.select(false);
+    view.load(
+        view.metadata.clone().unwrap(),
+        vec!["unused.tpl".to_string()],
+    );
+    assert_eq!(view.selected_file().unwrap().name, "unused.tpl");
+}
@@ -0,0 +1,130 @@
+//! Status colours and indicators
+//!
+//! The standard theme tells states apart by red, green and yellow alone.
+//! The high-contrast theme adds a shape or word to every state and draws
+//! them in bright colours that don't depend on telling red from green.
+
+use crate::app::OperationStatus;
+use ratatui::style::{Color, Modifier, Style};
+
+/// What a state means, whatever it looks like on screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    Good,
+    Bad,
+    Warning,
+}
+
+impl Tone {
+    /// Tone of an instance status such as "Running"
+    pub fn of_status(status: &str) -> Self {
+        match status {
+            "Running" => Tone::Good,
+            "Stopped" => Tone::Bad,
+            _ => Tone::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Standard,
+    HighContrast,
+}
+
+impl Theme {
+    pub fn new(high_contrast: bool) -> Self {
+        if high_contrast {
+            Theme::HighContrast
+        } else {
+            Theme::Standard
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Theme::Standard => Theme::HighContrast,
+            Theme::HighContrast => Theme::Standard,
+        }
+    }
+
+    pub fn style(self, tone: Tone) -> Style {
+        match (self, tone) {
+            (Theme::Standard, Tone::Good) => Style::default().fg(Color::Green),
+            (Theme::Standard, Tone::Bad) => Style::default().fg(Color::Red),
+            (Theme::Standard, Tone::Warning) => Style::default().fg(Color::Yellow),
+            (Theme::HighContrast, Tone::Good) => Style::default()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+            (Theme::HighContrast, Tone::Bad) => Style::default()
+                .fg(Color::LightMagenta)
+                .add_modifier(Modifier::BOLD),
+            (Theme::HighContrast, Tone::Warning) => Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        }
+    }
+
+    /// The selected row of a list
+    pub fn selected(self) -> Style {
+        match self {
+            Theme::Standard => Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+            Theme::HighContrast => Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    /// A row whose state just changed to one of this tone
+    pub fn flash(self, tone: Tone) -> Style {
+        self.style(tone).add_modifier(Modifier::REVERSED)
+    }
+
+    /// Shape put in front of a state in the high-contrast theme
+    fn shape(tone: Tone) -> &'static str {
+        match tone {
+            Tone::Good => "▲",
+            Tone::Bad => "■",
+            Tone::Warning => "◆",
+        }
+    }
+
+    /// `text` as shown for a state: unchanged in the standard theme, after
+    /// its shape in the high-contrast one
+    pub fn label(self, tone: Tone, text: &str) -> String {
+        match self {
+            Theme::Standard => text.to_string(),
+            Theme::HighContrast => format!("{} {}", Self::shape(tone), text),
+        }
+    }
+
+    /// Width an instance status column needs for `width` characters of text
+    pub fn status_width(self, width: usize) -> usize {
+        match self {
+            Theme::Standard => width,
+            Theme::HighContrast => width + 2,
+        }
+    }
+
+    /// Indicator in front of an operation in the sidebar
+    pub fn operation_icon(self, status: &OperationStatus) -> &'static str {
+        match (self, status) {
+            (Theme::Standard, OperationStatus::Registered) => "⏳",
+            (Theme::Standard, OperationStatus::Running) => "🚀",
+            (Theme::Standard, OperationStatus::Retrying(_)) => "🔄",
+            (Theme::Standard, OperationStatus::Success) => "✅",
+            (Theme::Standard, OperationStatus::Failed(_)) => "❌",
+            (Theme::Standard, OperationStatus::Cancelled) => "🚫",
+            (Theme::HighContrast, OperationStatus::Registered) => "[WAIT]",
+            (Theme::HighContrast, OperationStatus::Running) => "[RUN] ",
+            (Theme::HighContrast, OperationStatus::Retrying(_)) => "[RTRY]",
+            (Theme::HighContrast, OperationStatus::Success) => "[ OK ]",
+            (Theme::HighContrast, OperationStatus::Failed(_)) => "[FAIL]",
+            (Theme::HighContrast, OperationStatus::Cancelled) => "[STOP]",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,38 @@
+//! Tests of the status themes
+
+use super::{Theme, Tone};
+use crate::app::OperationStatus;
+
+#[test]
+fn high_contrast_marks_states_with_shapes() {
+    let theme = Theme::HighContrast;
+    assert_eq!(
+        theme.label(Tone::of_status("Running"), "Running"),
+        "▲ Running"
+    );
+    assert_eq!(
+        theme.label(Tone::of_status("Stopped"), "Stopped"),
+        "■ Stopped"
+    );
+    assert_eq!(theme.label(Tone::of_status("Frozen"), "Frozen"), "◆ Frozen");
+    assert_eq!(Theme::Standard.label(Tone::Good, "Running"), "Running");
+    assert_eq!(theme.toggled(), Theme::Standard);
+}
+
+#[test]
+fn high_contrast_operation_icons_are_words_of_one_width() {
+    let statuses = [
+        OperationStatus::Registered,
+        OperationStatus::Running,
+        OperationStatus::Retrying(1),
+        OperationStatus::Success,
+        OperationStatus::Failed("boom".to_string()),
+        OperationStatus::Cancelled,
+    ];
+    let icons: Vec<&str> = statuses
+        .iter()
+        .map(|s| Theme::HighContrast.operation_icon(s))
+        .collect();
+    assert!(icons.iter().all(|icon| icon.chars().count() == 6));
+    assert!(icons.iter().all(|icon| icon.is_ascii()));
+}
@@ -0,0 +1,166 @@
+//! Resource ranking view
+//!
+//! Samples the state of every running instance every few seconds and ranks
+//! them by CPU, memory or network throughput averaged over the most recent
+//! samples, to find the instance hogging a shared host.
+
+use crate::lxd_api::ContainerState;
+use crate::watch;
+use std::collections::{HashMap, VecDeque};
+use tokio::time::{Duration, Instant};
+
+/// How often instance states are sampled
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Samples averaged by default, and the most kept
+pub const DEFAULT_WINDOW: usize = 5;
+pub const MAX_WINDOW: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankBy {
+    Cpu,
+    Memory,
+    Network,
+}
+
+impl RankBy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RankBy::Cpu => "CPU",
+            RankBy::Memory => "memory",
+            RankBy::Network => "network",
+        }
+    }
+}
+
+/// Usage of one instance between two polls
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    pub cpu_percent: f64, // Of one CPU
+    pub memory: f64,      // Bytes in use
+    pub network: f64,     // Bytes per second received and sent
+}
+
+/// An instance's usage averaged over the window
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranked {
+    pub name: String, // qualified name
+    pub usage: Sample,
+    pub samples: usize,
+}
+
+#[derive(Debug)]
+pub struct Top {
+    pub rank_by: RankBy,
+    pub window: usize,
+    pub error: Option<String>, // Why the last poll failed
+    pub last_poll: Option<Instant>,
+    previous: HashMap<String, ContainerState>,
+    samples: HashMap<String, VecDeque<Sample>>,
+}
+
+impl Top {
+    pub fn new() -> Self {
+        Top {
+            rank_by: RankBy::Cpu,
+            window: DEFAULT_WINDOW,
+            error: None,
+            last_poll: None,
+            previous: HashMap::new(),
+            samples: HashMap::new(),
+        }
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        self.last_poll
+            .is_none_or(|last| now.duration_since(last) >= POLL_INTERVAL)
+    }
+
+    /// Average over more or fewer samples
+    pub fn resize_window(&mut self, delta: i32) {
+        self.window = (self.window as i32 + delta).clamp(1, MAX_WINDOW as i32) as usize;
+    }
+
+    /// Take in the states of the running instances, polled at `now`.
+    /// Instances no longer running are dropped.
+    pub fn record(&mut self, result: Result<Vec<(String, ContainerState)>, String>, now: Instant) {
+        let elapsed = self
+            .last_poll
+            .map(|last| now.duration_since(last).as_secs_f64())
+            .filter(|secs| *secs > 0.0);
+        self.last_poll = Some(now);
+
+        let states = match result {
+            Ok(states) => states,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+        self.error = None;
+
+        let mut previous = HashMap::new();
+        for (name, state) in states {
+            if state.status != "Running" {
+                continue;
+            }
+            if let (Some(before), Some(elapsed)) = (self.previous.get(&name), elapsed) {
+                let samples = self.samples.entry(name.clone()).or_default();
+                if samples.len() == MAX_WINDOW {
+                    samples.pop_front();
+                }
+                samples.push_back(sample(before, &state, elapsed));
+            }
+            previous.insert(name, state);
+        }
+        self.samples.retain(|name, _| previous.contains_key(name));
+        self.previous = previous;
+    }
+
+    /// Running instances, heaviest first
+    pub fn ranking(&self) -> Vec<Ranked> {
+        let mut ranked: Vec<Ranked> = self
+            .samples
+            .iter()
+            .filter(|(_, samples)| !samples.is_empty())
+            .map(|(name, samples)| {
+                let recent: Vec<&Sample> = samples.iter().rev().take(self.window).collect();
+                let count = recent.len() as f64;
+                let usage = Sample {
+                    cpu_percent: recent.iter().map(|s| s.cpu_percent).sum::<f64>() / count,
+                    memory: recent.iter().map(|s| s.memory).sum::<f64>() / count,
+                    network: recent.iter().map(|s| s.network).sum::<f64>() / count,
+                };
+                Ranked {
+                    name: name.clone(),
+                    usage,
+                    samples: recent.len(),
+                }
+            })
+            .collect();
+
+        let key = |r: &Ranked| match self.rank_by {
+            RankBy::Cpu => r.usage.cpu_percent,
+            RankBy::Memory => r.usage.memory,
+            RankBy::Network => r.usage.network,
+        };
+        ranked.sort_by(|a, b| key(b).total_cmp(&key(a)).then_with(|| a.name.cmp(&b.name)));
+        ranked
+    }
+}
+
+fn sample(before: &ContainerState, after: &ContainerState, elapsed: f64) -> Sample {
+    let network = watch::throughput(before, after, elapsed)
+        .iter()
+        .map(|(_, rate)| rate.rx + rate.tx)
+        .sum();
+
+    Sample {
+        cpu_percent: watch::cpu_percent(before, after, elapsed).unwrap_or_default(),
+        memory: after.memory.as_ref().map_or(0.0, |m| m.usage as f64),
+        network,
+    }
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,82 @@
+//! Tests of ranking instances by usage
+
+use super::{RankBy, Top};
+use crate::lxd_api::ContainerState;
+use crate::watch::tests::state;
+use tokio::time::{Duration, Instant};
+
+/// A running instance that has used `cpu_secs` of CPU, received `rx` bytes
+/// and holds `memory_mib` of memory
+fn running(cpu_secs: i64, rx: i64, memory_mib: i64) -> ContainerState {
+    let mut state = state("Running", 100, cpu_secs * 1_000_000_000, None, rx);
+    state.memory.as_mut().unwrap().usage = memory_mib << 20;
+    state
+}
+
+/// Record one poll per second of the given instances
+pub fn top_with(polls: &[Vec<(&str, ContainerState)>]) -> Top {
+    let start = Instant::now();
+    let mut top = Top::new();
+    for (i, states) in polls.iter().enumerate() {
+        let states = states
+            .iter()
+            .map(|(name, state)| (name.to_string(), state.clone()))
+            .collect();
+        top.record(Ok(states), start + Duration::from_secs(i as u64));
+    }
+    top
+}
+
+fn names(top: &Top) -> Vec<String> {
+    top.ranking().into_iter().map(|r| r.name).collect()
+}
+
+#[test]
+fn ranks_by_the_chosen_resource() {
+    let mut top = top_with(&[
+        vec![("web1", running(0, 0, 100)), ("db1", running(0, 0, 900))],
+        vec![
+            ("web1", running(1, 0, 100)),
+            ("db1", running(0, 1 << 20, 900)),
+        ],
+    ]);
+    assert_eq!(names(&top), ["web1", "db1"]);
+    assert_eq!(top.ranking()[0].usage.cpu_percent, 100.0);
+
+    top.rank_by = RankBy::Memory;
+    assert_eq!(names(&top), ["db1", "web1"]);
+
+    top.rank_by = RankBy::Network;
+    assert_eq!(names(&top), ["db1", "web1"]);
+    assert_eq!(top.ranking()[0].usage.network, (1 << 20) as f64);
+}
+
+#[test]
+fn averages_over_the_window() {
+    // Busy for one second, then idle for two
+    let mut top = top_with(&[
+        vec![("web1", running(0, 0, 1))],
+        vec![("web1", running(1, 0, 1))],
+        vec![("web1", running(1, 0, 1))],
+        vec![("web1", running(1, 0, 1))],
+    ]);
+    assert_eq!(top.ranking()[0].samples, 3);
+    assert!((top.ranking()[0].usage.cpu_percent - 100.0 / 3.0).abs() < 1e-9);
+
+    top.resize_window(-4);
+    assert_eq!(top.window, 1);
+    assert_eq!(top.ranking()[0].usage.cpu_percent, 0.0);
+}
+
+#[test]
+fn stopped_instances_drop_out() {
+    let top = top_with(&[
+        vec![("web1", running(0, 0, 1)), ("db1", running(0, 0, 1))],
+        vec![("web1", running(1, 0, 1)), ("db1", running(1, 0, 1))],
+        vec![
+            ("web1", running(2, 0, 1)),
+            ("db1", state("Stopped", 0, 0, None, 0)),
+        ],
+    ]);
+    assert_eq!(names(&top), ["web1"]);
+}
@@ -0,0 +1,134 @@
+//! First-run tour
+//!
+//! Steps through the list, the container menu, the wizard and the operations
+//! sidebar on the real screen, once, leaving a marker file when done.
+
+use crate::app::{CommandMenu, InputMode, WizardState};
+use std::io;
+use std::path::Path;
+
+/// The part of the screen a step is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    List,
+    Menu,
+    Wizard,
+    Sidebar,
+    Hints,
+}
+
+#[derive(Debug)]
+pub struct Step {
+    pub title: &'static str,
+    pub text: &'static [&'static str],
+    pub region: Region,
+}
+
+pub const STEPS: &[Step] = &[
+    Step {
+        title: "Welcome to LXTUI",
+        text: &[
+            "A short tour of the screens you will use most.",
+            "It runs on your real server but starts or changes nothing.",
+            "",
+            "Enter or → goes on, ← back, Esc ends the tour.",
+        ],
+        region: Region::List,
+    },
+    Step {
+        title: "Moving around",
+        text: &[
+            "The list shows every instance on the server.",
+            "j/k or ↑/↓ select one, / filters by name, status or tag,",
+            "and r refreshes. Space opens the system menu: remotes,",
+            "the dashboard and the LXD service.",
+        ],
+        region: Region::List,
+    },
+    Step {
+        title: "The container menu",
+        text: &[
+            "Enter on an instance opens its menu: start, stop, exec,",
+            "snapshots, backups and everything else done to one",
+            "instance. Each entry has a key of its own.",
+        ],
+        region: Region::Menu,
+    },
+    Step {
+        title: "Creating an instance",
+        text: &[
+            "n starts the wizard: a name, an image, container or VM,",
+            "then a last look at what will be created.",
+        ],
+        region: Region::Wizard,
+    },
+    Step {
+        title: "The operations sidebar",
+        text: &[
+            "Starts, backups and copies run in the background and are",
+            "listed here. o shows or hides it; Tab focuses it, where",
+            "Enter shows details and r retries a failure.",
+        ],
+        region: Region::Sidebar,
+    },
+    Step {
+        title: "Finding keys",
+        text: &[
+            "The bottom line always lists the keys of what is on screen.",
+            "? opens every binding, searchable with /. Press t there",
+            "to take this tour again.",
+        ],
+        region: Region::Hints,
+    },
+];
+
+impl Step {
+    /// What the screen shows under the step
+    pub fn scene(&self) -> InputMode {
+        match self.region {
+            Region::Menu => InputMode::CommandMenu(CommandMenu::Container),
+            Region::Wizard => InputMode::Wizard(WizardState::Name),
+            _ => InputMode::Normal,
+        }
+    }
+}
+
+/// A tour in progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tour {
+    pub step: usize,
+}
+
+impl Tour {
+    pub fn current(&self) -> &'static Step {
+        &STEPS[self.step.min(STEPS.len() - 1)]
+    }
+
+    /// Go to the next step; false when there is none
+    pub fn next(&mut self) -> bool {
+        if self.step + 1 >= STEPS.len() {
+            return false;
+        }
+        self.step += 1;
+        true
+    }
+
+    pub fn previous(&mut self) {
+        self.step = self.step.saturating_sub(1);
+    }
+}
+
+/// Whether the tour was taken or skipped before
+pub fn seen(path: &Path) -> bool {
+    path.exists()
+}
+
+pub fn mark_seen(path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, "")
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,42 @@
+//! Tests of the first-run tour
+
+use super::{mark_seen, seen, Region, Tour, STEPS};
+use crate::app::{CommandMenu, InputMode};
+use uuid::Uuid;
+
+#[test]
+fn steps_go_forward_and_back_within_the_tour() {
+    let mut tour = Tour::default();
+    assert_eq!(tour.current().title, "Welcome to LXTUI");
+    tour.previous();
+    assert_eq!(tour.step, 0);
+    while tour.next() {}
+    assert_eq!(tour.step, STEPS.len() - 1);
+    assert_eq!(tour.current().region, Region::Hints);
+    assert!(!tour.next());
+}
+
+#[test]
+fn menu_and_wizard_steps_open_them() {
+    let regions: Vec<Region> = STEPS.iter().map(|s| s.region).collect();
+    for region in [Region::List, Region::Menu, Region::Wizard, Region::Sidebar] {
+        assert!(regions.contains(&region));
+    }
+    let menu = STEPS.iter().find(|s| s.region == Region::Menu).unwrap();
+    assert!(matches!(
+        menu.scene(),
+        InputMode::CommandMenu(CommandMenu::Container)
+    ));
+    assert!(matches!(STEPS[0].scene(), InputMode::Normal));
+}
+
+#[test]
+fn seen_once_marked() {
+    let path = std::env::temp_dir()
+        .join(format!("lxtui-tour-{}", Uuid::new_v4()))
+        .join("tour-seen");
+    assert!(!seen(&path));
+    mark_seen(&path).unwrap();
+    assert!(seen(&path));
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
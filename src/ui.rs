@@ -4,16 +4,56 @@
 //! the main container list, modals, menus, and status displays.
 
 use crate::app::{
-    App, CommandMenu, ConfirmAction, InputCallback, InputMode, InputType, StatusModalType,
-    WizardState,
+    App, CommandMenu, ConfirmAction, InputCallback, InputMode, InputType, OperationStatus,
+    PendingAction, StatusModalType, Timing, UserOperation, WizardState,
 };
+use crate::audit::{Audit, Freshness};
+use crate::batch::{Outcome, ProfileBatch};
+use crate::boot::{Autostart, BootOrder};
+use crate::capacity::Capacity;
+use crate::clone::{self, CloneForm, Field};
+use crate::compare::{Comparison, Section};
+use crate::console::ConsoleView;
+use crate::convert::Conversion;
+use crate::dashboard::{self, Counts, Dashboard, PoolUsage};
+use crate::drift::{Change, DriftView};
+use crate::estimate::Estimate;
+use crate::features::Feature;
+use crate::field::TextField;
+use crate::idmap::{self, Entry, IdmapError, IdmapView};
+use crate::input;
+use crate::keymap;
+use crate::logging;
+use crate::metrics::InstanceMetrics;
+use crate::migration::Transfer;
+use crate::notes::{self, Inline, NoteLine};
+use crate::pools::{Pool, PoolsView};
+use crate::ports;
+use crate::power;
+use crate::preflight::Check;
+use crate::probe::Probe;
+use crate::publish;
+use crate::quick::{self, QuickView};
+use crate::restart;
+use crate::search::KeySearch;
+use crate::sidebar;
+use crate::store::ImageStore;
+use crate::templates::TemplatesView;
+use crate::theme::{Theme, Tone};
+use crate::top::{self, RankBy, Top};
+use crate::tour::{self, Region, Step};
+use crate::uptime::{self, Availability, Slot};
+use crate::vm::{Knob, VmSettings};
+use crate::watch::{self, Watch};
+use crate::zones::{self, Row, Zones};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
+use std::time::Duration;
 
 pub fn draw(frame: &mut Frame, app: &App) {
     // Main layout - simplified to 3 panels
@@ -30,34 +70,162 @@ pub fn draw(frame: &mut Frame, app: &App) {
     // Draw main UI components
     draw_title_and_status(frame, chunks[0], app);
 
-    // Check if we need to show operation sidebar
-    if app.show_operation_sidebar {
+    // The event feed takes the bottom of the main area
+    let main_area = if app.show_events {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(6), Constraint::Length(EVENT_PANEL_HEIGHT)])
+            .split(chunks[1]);
+        draw_event_panel(frame, parts[1], app);
+        parts[0]
+    } else {
+        chunks[1]
+    };
+
+    let touring = app.tour.as_ref().map(|tour| tour.current().region);
+    let mut screen = Screen {
+        list: main_area,
+        sidebar: None,
+        hints: chunks[2],
+    };
+
+    // The dashboard takes the place of the list until Enter drills into it
+    if let (InputMode::Dashboard, Some(dashboard)) = (&app.input_mode, &app.dashboard) {
+        draw_dashboard(frame, main_area, app, dashboard);
+    } else if app.show_operation_sidebar || touring == Some(Region::Sidebar) {
         let main_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
                 Constraint::Min(40),
                 Constraint::Length(30), // Sidebar width
             ])
-            .split(chunks[1]);
+            .split(main_area);
 
         draw_container_list(frame, main_chunks[0], app);
         draw_operation_sidebar(frame, main_chunks[1], app);
+        screen.list = main_chunks[0];
+        screen.sidebar = Some(main_chunks[1]);
     } else {
-        draw_container_list(frame, chunks[1], app);
+        draw_container_list(frame, main_area, app);
     }
 
     draw_command_hints(frame, chunks[2], app);
 
     // Draw modals and overlays based on input mode
-    match &app.input_mode {
+    draw_mode(frame, &app.input_mode, app);
+    if let (InputMode::Tour, Some(tour)) = (&app.input_mode, &app.tour) {
+        draw_mode(frame, &tour.current().scene(), app);
+        draw_tour(frame, tour.current(), &screen, app);
+    }
+
+    if app.show_debug {
+        draw_debug_overlay(frame, app);
+    }
+    if app.show_perf {
+        draw_perf_hud(frame, app);
+    }
+}
+
+/// Where the parts of the main screen were drawn
+struct Screen {
+    list: Rect,
+    sidebar: Option<Rect>,
+    hints: Rect,
+}
+
+/// Outline the part of the screen a tour step is about, and explain it in
+/// a box beside it
+fn draw_tour(frame: &mut Frame, step: &Step, screen: &Screen, app: &App) {
+    let full = frame.area();
+    let region = match step.region {
+        Region::List => screen.list,
+        Region::Menu => menu_area(full, &CommandMenu::Container, app),
+        Region::Wizard => wizard_area(full),
+        Region::Sidebar => screen.sidebar.unwrap_or(screen.list),
+        Region::Hints => screen.hints,
+    };
+    // Recolour its edges, keeping the borders and titles drawn there
+    let outline = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let buffer = frame.buffer_mut();
+    let bottom = region.y + region.height.saturating_sub(1);
+    let right = region.x + region.width.saturating_sub(1);
+    for edge in [
+        Rect::new(region.x, region.y, region.width, 1),
+        Rect::new(region.x, bottom, region.width, 1),
+        Rect::new(region.x, region.y, 1, region.height),
+        Rect::new(right, region.y, 1, region.height),
+    ] {
+        buffer.set_style(edge.intersection(buffer.area), outline);
+    }
+
+    let number = app.tour.map_or(0, |tour| tour.step) + 1;
+    let mut lines: Vec<Line> = step.text.iter().map(|line| Line::from(*line)).collect();
+    lines.push(Line::from(""));
+    lines.push(Line::styled(
+        format!(
+            "Step {} of {}  ·  Enter next  ← back  Esc end",
+            number,
+            tour::STEPS.len()
+        ),
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    // Beside the region: in the larger space above or below it, on the
+    // side away from it
+    let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).min(full.width);
+    let height = (lines.len() as u16 + 2).min(full.height);
+    let above = region.y.saturating_sub(full.y);
+    let below = (full.y + full.height).saturating_sub(region.y + region.height);
+    let y = if above >= height && above > below {
+        region.y - height
+    } else if below >= height {
+        region.y + region.height
+    } else {
+        // No room outside it: the bottom of the main area
+        screen.hints.y.saturating_sub(height)
+    };
+    let x = if region.x > full.x + full.width / 2 {
+        full.x
+    } else {
+        full.x + full.width - width
+    };
+    let area = Rect {
+        x,
+        y,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(" {} ", step.title))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Yellow)),
+        ),
+        area,
+    );
+}
+
+/// The modal or view `mode` shows over the list
+fn draw_mode(frame: &mut Frame, mode: &InputMode, app: &App) {
+    match mode {
         InputMode::CommandMenu(menu) => {
-            draw_command_menu(frame, menu, app.menu_selected);
+            draw_command_menu(frame, menu, app);
         }
         InputMode::StatusModal(modal_type) => {
             draw_status_modal(frame, modal_type, app);
         }
-        InputMode::Confirmation { message, action } => {
-            draw_confirmation_modal(frame, message, action);
+        InputMode::Confirmation {
+            message,
+            action,
+            typed_name,
+            checks,
+        } => {
+            draw_confirmation_modal(frame, message, action, typed_name.as_deref(), checks, app);
         }
         InputMode::Input {
             prompt,
@@ -75,16 +243,207 @@ pub fn draw(frame: &mut Frame, app: &App) {
         InputMode::Wizard(state) => {
             draw_wizard(frame, state, app);
         }
-        InputMode::Normal => {}
+        InputMode::Backups {
+            container,
+            confirm_delete,
+        } => {
+            draw_backups(frame, container, *confirm_delete, app);
+        }
+        InputMode::Help { query, searching } => {
+            draw_help(frame, query, *searching, app);
+        }
+        InputMode::Preview(action) => {
+            draw_preview(frame, action, app);
+        }
+        InputMode::SpaceWarning { message, .. } => {
+            draw_space_warning(frame, message);
+        }
+        InputMode::CloneOptions(form) => {
+            draw_clone_options(frame, form, app);
+        }
+        InputMode::Log => {
+            draw_log(frame, app);
+        }
+        InputMode::Watch => {
+            if let Some(watch) = &app.watch {
+                let metrics_unavailable = app.unavailable.reason(Feature::Metrics).is_some();
+                let probe = app.probes.get(&watch.container);
+                let restarts = app.restarts.get(&watch.container);
+                let now = chrono::Local::now();
+                let uptime = app.uptime.as_ref().and_then(|uptime| {
+                    let availability = uptime.availability(&watch.container, now)?;
+                    Some((
+                        availability,
+                        uptime.strip(&watch.container, now, UPTIME_SLOTS),
+                    ))
+                });
+                draw_watch(
+                    frame,
+                    watch,
+                    app.theme,
+                    metrics_unavailable,
+                    probe,
+                    restarts,
+                    uptime,
+                );
+            }
+        }
+        InputMode::Console => {
+            if let Some(console) = &app.console {
+                draw_console(frame, console);
+            }
+        }
+        InputMode::Top => {
+            if let Some(top) = &app.top {
+                draw_top(frame, top);
+            }
+        }
+        InputMode::BootOrder => {
+            if let Some(order) = &app.boot_order {
+                let server = app.active_remote.as_deref().unwrap_or("local");
+                draw_boot_order(frame, order, server, app.theme);
+            }
+        }
+        InputMode::Audit { confirm_rebuild } => {
+            if let Some(audit) = &app.audit {
+                let server = app.active_remote.as_deref().unwrap_or("local");
+                draw_audit(frame, audit, *confirm_rebuild, server);
+            }
+        }
+        InputMode::Zones { confirm_delete } => {
+            if let Some(view) = &app.zones {
+                let server = app.active_remote.as_deref().unwrap_or("local");
+                draw_zones(frame, view, *confirm_delete, server);
+            }
+        }
+        InputMode::ImageStore => {
+            if let Some(view) = &app.image_store {
+                let server = app.active_remote.as_deref().unwrap_or("local");
+                draw_image_store(frame, view, server, app.expiry_warning);
+            }
+        }
+        InputMode::Pools { confirm_scrub } => {
+            if let Some(view) = &app.pools {
+                let server = app.active_remote.as_deref().unwrap_or("local");
+                draw_pools(frame, view, *confirm_scrub, server);
+            }
+        }
+        InputMode::Capacity => {
+            if let Some(view) = &app.capacity {
+                let server = app.active_remote.as_deref().unwrap_or("local");
+                draw_capacity(frame, view, server);
+            }
+        }
+        InputMode::Templates => {
+            if let Some(view) = &app.templates {
+                draw_templates(frame, view);
+            }
+        }
+        InputMode::VmSettings => {
+            if let Some(view) = &app.vm_settings {
+                draw_vm_settings(frame, view);
+            }
+        }
+        InputMode::Idmap => {
+            if let Some(view) = &app.idmap {
+                draw_idmap(frame, view);
+            }
+        }
+        InputMode::Drift => {
+            if let Some(view) = &app.drift {
+                draw_drift(frame, view);
+            }
+        }
+        InputMode::KeySearch => {
+            if let Some(view) = &app.key_search {
+                draw_key_search(frame, view);
+            }
+        }
+        InputMode::Profiles => {
+            if let Some(view) = &app.profile_batch {
+                draw_profiles(frame, view);
+            }
+        }
+        InputMode::Quick { confirm } => {
+            if let Some(view) = &app.quick {
+                draw_quick(frame, view, *confirm);
+            }
+        }
+        InputMode::Recreate => {
+            if let Some(plan) = &app.recreate {
+                draw_recreate(frame, plan);
+            }
+        }
+        InputMode::Compare => {
+            if let Some(compare) = &app.compare {
+                draw_compare(frame, compare, app.theme);
+            }
+        }
+        InputMode::OperationDetails { id, scroll } => {
+            if let Some(operation) = app.user_operations.iter().find(|op| op.id == *id) {
+                let transfer = app
+                    .operation_transfer(operation)
+                    .map(|transfer| (transfer, app.operation_estimate(operation)));
+                draw_operation_details(frame, operation, transfer, *scroll, app.theme);
+            }
+        }
+        InputMode::Normal | InputMode::Operations | InputMode::Dashboard | InputMode::Tour => {}
     }
 }
 
+/// Frame, event loop and refresh timings in the bottom-right corner
+fn draw_perf_hud(frame: &mut Frame, app: &App) {
+    let full = frame.area();
+    let width = 40.min(full.width);
+    let height = 6.min(full.height);
+    let area = Rect {
+        x: full.x + full.width - width,
+        y: full.y + full.height.saturating_sub(height + 2), // Above the command hints
+        width,
+        height,
+    };
+    frame.render_widget(Clear, area);
+
+    let row = |name: &str, timing: &Timing| {
+        Line::from(vec![
+            Span::styled(format!(" {:<9}", name), Style::default().fg(Color::Cyan)),
+            Span::raw(format!(
+                "{:>8.1} ms  max {:>8.1} ms",
+                timing.last.as_secs_f64() * 1000.0,
+                timing.max.as_secs_f64() * 1000.0
+            )),
+        ])
+    };
+    let lines = vec![
+        row("Draw", &app.perf.draw),
+        row("Loop", &app.perf.tick),
+        row("Key", &app.perf.key),
+        row("Refresh", &app.perf.refresh),
+    ];
+
+    let hud = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Performance (F11) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .border_type(BorderType::Rounded),
+    );
+    frame.render_widget(hud, area);
+}
+
 fn draw_title_and_status(frame: &mut Frame, area: Rect, app: &App) {
-    let container_count = app.containers.try_read().map(|c| c.len()).unwrap_or(0);
-    let lxd_status = if app.lxd_status {
-        "Running"
-    } else {
-        "Not Running"
+    let container_count = app
+        .containers
+        .try_read()
+        .map(|c| match app.filter {
+            Some(_) => format!("{}/{}", app.visible(&c).len(), c.len()),
+            None => c.len().to_string(),
+        })
+        .unwrap_or_else(|_| "0".to_string());
+    let lxd_status = match (app.lxd_status, app.heartbeat.latency()) {
+        (true, Some(latency)) => format!("Running ({} ms)", latency.as_millis()),
+        (true, None) => "Running".to_string(),
+        (false, _) => "Not Running".to_string(),
     };
     let _lxd_color = if app.lxd_status {
         Color::Green
@@ -92,16 +451,75 @@ fn draw_title_and_status(frame: &mut Frame, area: Rect, app: &App) {
         Color::Red
     };
 
-    let status_text = if app.active_operation_count > 0 {
-        format!("⚡ {} operations active", app.active_operation_count)
+    // Operations running in the background, with how far along they are
+    let status_text = match app.operations_progress() {
+        (0, _) => match (app.notice(), &app.schedule_warning) {
+            (Some(notice), _) => notice.to_string(),
+            (None, Some(warning)) => format!("⚠ {}", warning),
+            (None, None) => "⚡ Ready".to_string(),
+        },
+        (count, progress) => {
+            let ops = if count == 1 { "op" } else { "ops" };
+            match progress {
+                Some(percent) => format!("⚡ {} {}, {}%", count, ops, percent),
+                None => format!("⚡ {} {}", count, ops),
+            }
+        }
+    };
+
+    let remote = if app.all_remotes {
+        "all remotes"
     } else {
-        "⚡ Ready".to_string()
+        app.active_remote.as_deref().unwrap_or("local")
+    };
+    // Several remotes listed at once have no one accent
+    let accent = (!app.all_remotes)
+        .then(|| app.accents.of_remote(remote))
+        .flatten();
+    let remote = match (app.preview_requests, app.read_only) {
+        (_, true) => format!("{} (read-only)", remote),
+        (true, false) => format!("{} (preview)", remote),
+        (false, false) => remote.to_string(),
     };
 
-    let title_text = format!(
-        " LXTUI │ {} containers │ LXD: {} │ {} ",
-        container_count, lxd_status, status_text
-    );
+    let alerts = match app.alerts.count() {
+        0 => String::new(),
+        1 => "│ ⚠ 1 alert ".to_string(),
+        n => format!("│ ⚠ {} alerts ", n),
+    };
+    let alerts = match app.expiring_images.len() {
+        0 => alerts,
+        1 => format!("{}│ ⚠ 1 image expiring ", alerts),
+        n => format!("{}│ ⚠ {} images expiring ", alerts, n),
+    };
+    let alerts = match app.unreachable_remotes.as_slice() {
+        [] => alerts,
+        [remote] => format!("{}│ ⚠ {} unreachable ", alerts, remote),
+        remotes => format!("{}│ ⚠ {} remotes unreachable ", alerts, remotes.len()),
+    };
+    let alerts = match app.config_warnings.len() {
+        0 => alerts,
+        1 => format!("{}│ ⚠ 1 config warning ", alerts),
+        n => format!("{}│ ⚠ {} config warnings ", alerts, n),
+    };
+
+    let workspace = match app.workspace.and_then(|i| app.workspaces.get(i)) {
+        Some(workspace) => format!("{} │ ", workspace.name),
+        None => String::new(),
+    };
+
+    let remote_style = match accent {
+        Some(accent) => accent_badge(accent),
+        None => Style::default(),
+    };
+    let title_text = Line::from(vec![
+        Span::raw(" LXTUI │ "),
+        Span::styled(remote, remote_style),
+        Span::raw(format!(
+            " │ {}{} containers │ LXD: {} │ {} {}",
+            workspace, container_count, lxd_status, status_text, alerts
+        )),
+    ]);
 
     let title = Paragraph::new(title_text)
         .style(Style::default().fg(Color::White).bg(Color::DarkGray))
@@ -116,15 +534,64 @@ fn draw_title_and_status(frame: &mut Frame, area: Rect, app: &App) {
     frame.render_widget(title, area);
 }
 
+/// Text drawn on a remote's or project's accent colour
+fn accent_badge(accent: Color) -> Style {
+    Style::default()
+        .fg(Color::Black)
+        .bg(accent)
+        .add_modifier(Modifier::BOLD)
+}
+
+/// Badge colour of a tag; the same tag always gets the same colour
+fn tag_color(tag: &str) -> Color {
+    const COLORS: [Color; 6] = [
+        Color::Cyan,
+        Color::Green,
+        Color::Yellow,
+        Color::Magenta,
+        Color::Blue,
+        Color::LightRed,
+    ];
+    let hash = tag.to_lowercase().bytes().fold(0usize, |hash, b| {
+        hash.wrapping_mul(31).wrapping_add(b as usize)
+    });
+    COLORS[hash % COLORS.len()]
+}
+
+/// Characters of the image column before it is cut short
+const IMAGE_WIDTH: usize = 32;
+
+/// Characters of the ports column before it is cut short
+const PORTS_WIDTH: usize = 20;
+
+/// Characters of the schedule column, enough for `skip start Tue 08:00`
+const SCHEDULE_WIDTH: usize = 20;
+
 fn draw_container_list(frame: &mut Frame, area: Rect, app: &App) {
     let containers = if let Ok(containers) = app.containers.try_read() {
-        containers.clone()
+        app.visible(&containers)
     } else {
         Vec::new()
     };
 
+    let mut title = match &app.filter {
+        Some(filter) => format!(" Containers [{}] ", filter),
+        None => " Containers ".to_string(),
+    };
+    if let Some(sort) = app.sort {
+        title.push_str(&format!("(sorted by {}) ", sort));
+    }
+    if app.group_by_tag {
+        title.push_str("(by tag) ");
+    }
+
     if containers.is_empty() {
-        let empty_msg = Paragraph::new("No containers found. Press Space for commands.")
+        let text = if app.filter.is_some() {
+            "No containers match the filter. Type :filter to clear it."
+        } else {
+            "No containers found. Press Space for commands."
+        };
+        let empty_msg = Paragraph::new(text)
             .style(Style::default().fg(Color::DarkGray))
             .alignment(Alignment::Center)
             .block(
@@ -132,24 +599,31 @@ fn draw_container_list(frame: &mut Frame, area: Rect, app: &App) {
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(Color::White))
                     .border_type(BorderType::Rounded)
-                    .title(" Containers "),
+                    .title(title),
             );
 
         frame.render_widget(empty_msg, area);
         return;
     }
 
+    let status_width = app.theme.status_width(10);
+    // Only while some listed container has a health check
+    let health_column = containers.iter().any(|c| c.healthcheck.is_some());
+    let health_width = app.theme.status_width(9);
+    // Likewise while some listed container publishes a port
+    let ports_column = containers.iter().any(|c| !c.ports.is_empty());
+    // And while some listed container has a start or stop schedule
+    let schedule_names: Vec<String> = containers.iter().map(|c| app.schedule_name(c)).collect();
+    let schedule_column = schedule_names
+        .iter()
+        .any(|name| power::scheduled(&app.schedules, name));
+    let now = chrono::Local::now().naive_local();
     let containers_list: Vec<ListItem> = containers
         .iter()
         .enumerate()
         .map(|(i, container)| {
-            let status_color = match container.status.as_str() {
-                "Running" => Color::Green,
-                "Stopped" => Color::Red,
-                _ => Color::Yellow,
-            };
-
-            let status_style = Style::default().fg(status_color);
+            let tone = Tone::of_status(&container.status);
+            let status = app.theme.label(tone, &container.status);
 
             let ip = container
                 .ipv4
@@ -157,26 +631,147 @@ fn draw_container_list(frame: &mut Frame, area: Rect, app: &App) {
                 .cloned()
                 .unwrap_or_else(|| "-".to_string());
 
-            let content = vec![Line::from(vec![
+            let mut spans = Vec::new();
+            if app.all_remotes {
+                let remote = container.remote.as_deref().unwrap_or("-");
+                match app.accents.of_remote(remote) {
+                    Some(accent) => spans.extend([
+                        Span::styled(format!("{:12}", remote), accent_badge(accent)),
+                        Span::raw(" "),
+                    ]),
+                    None => spans.push(Span::styled(
+                        format!("{:12} ", remote),
+                        Style::default().fg(Color::Magenta),
+                    )),
+                }
+            }
+            spans.extend([
                 Span::raw(format!("{:20} ", container.name)),
-                Span::styled(format!("{:10} ", container.status), status_style),
-                Span::raw(format!("{:15} ", ip)),
-                Span::raw(&container.container_type),
-            ])];
+                Span::styled(
+                    format!("{:width$} ", status, width = status_width),
+                    app.theme.style(tone),
+                ),
+            ]);
+            if health_column {
+                let probe = app.probes.get(&container.qualified_name());
+                spans.push(match probe {
+                    Some(probe) => Span::styled(
+                        format!(
+                            "{:width$} ",
+                            app.theme.label(probe.health.tone(), probe.health.label()),
+                            width = health_width
+                        ),
+                        app.theme.style(probe.health.tone()),
+                    ),
+                    None => Span::styled(
+                        format!("{:width$} ", "-", width = health_width),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                });
+            }
+            spans.push(Span::raw(format!("{:15} ", ip)));
+            if ports_column {
+                let mut ports = if container.ports.is_empty() {
+                    "-".to_string()
+                } else {
+                    ports::summary(&container.ports)
+                };
+                if ports.chars().count() > PORTS_WIDTH {
+                    ports = ports.chars().take(PORTS_WIDTH - 1).collect::<String>() + "…";
+                }
+                spans.push(Span::styled(
+                    format!("{:width$} ", ports, width = PORTS_WIDTH),
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+            if schedule_column {
+                let name = &schedule_names[i];
+                let overridden = app.power_overrides.get(name);
+                let label = power::label(
+                    power::next_run(&app.schedules, name, &now),
+                    overridden,
+                    &now,
+                );
+                spans.push(Span::styled(
+                    format!("{:width$} ", label, width = SCHEDULE_WIDTH),
+                    if overridden.is_some() && label != "-" {
+                        app.theme.style(Tone::Warning)
+                    } else {
+                        Style::default().fg(Color::Gray)
+                    },
+                ));
+            }
+            if app.image_column {
+                let mut image = container.image_label().unwrap_or_else(|| "-".to_string());
+                if image.chars().count() > IMAGE_WIDTH {
+                    image = image.chars().take(IMAGE_WIDTH - 1).collect::<String>() + "…";
+                }
+                spans.extend([
+                    Span::raw(format!("{:15} ", container.container_type)),
+                    Span::styled(
+                        format!("{:width$}", image, width = IMAGE_WIDTH),
+                        Style::default().fg(Color::Gray),
+                    ),
+                ]);
+            } else {
+                spans.push(Span::raw(&container.container_type));
+            }
+            if container.protected {
+                spans.push(Span::styled(" 🔒", Style::default().fg(Color::Yellow)));
+            }
+            for tag in &container.tags {
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(
+                    format!(" {} ", tag),
+                    Style::default().fg(Color::Black).bg(tag_color(tag)),
+                ));
+            }
+            if app.compare_marks.contains(&container.qualified_name()) {
+                spans.push(Span::styled(
+                    "  [compare]",
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            if app.picked.contains(&container.qualified_name()) {
+                spans.push(Span::styled(
+                    "  [picked]",
+                    Style::default().fg(Color::Magenta),
+                ));
+            }
+            let alert = app.alerts.get(&container.qualified_name());
+            if let Some(alert) = alert {
+                spans.push(Span::styled(
+                    format!("  ⚠ {}", alert.message),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ));
+            }
+            let content = vec![Line::from(spans)];
 
             if i == app.selected {
-                ListItem::new(content).style(
-                    Style::default()
-                        .bg(Color::DarkGray)
-                        .add_modifier(Modifier::BOLD),
-                )
+                ListItem::new(content).style(app.theme.selected())
+            } else if let Some(tone) = app.flash_tone(&container.qualified_name()) {
+                ListItem::new(content).style(app.theme.flash(tone))
+            } else if alert.is_some() {
+                ListItem::new(content).style(match app.theme {
+                    Theme::Standard => Style::default().fg(Color::LightRed),
+                    Theme::HighContrast => app.theme.style(Tone::Bad),
+                })
             } else {
                 ListItem::new(content)
             }
         })
         .collect();
 
-    let header = Line::from(vec![
+    let mut header_spans = Vec::new();
+    if app.all_remotes {
+        header_spans.push(Span::styled(
+            "Remote       ",
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Cyan),
+        ));
+    }
+    header_spans.extend([
         Span::styled(
             "Name                 ",
             Style::default()
@@ -184,32 +779,66 @@ fn draw_container_list(frame: &mut Frame, area: Rect, app: &App) {
                 .fg(Color::Cyan),
         ),
         Span::styled(
-            "Status     ",
+            format!("{:width$} ", "Status", width = status_width),
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .fg(Color::Cyan),
         ),
-        Span::styled(
-            "IPv4            ",
+    ]);
+    if health_column {
+        header_spans.push(Span::styled(
+            format!("{:width$} ", "Health", width = health_width),
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .fg(Color::Cyan),
-        ),
-        Span::styled(
-            "Type",
+        ));
+    }
+    header_spans.push(Span::styled(
+        "IPv4            ",
+        Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(Color::Cyan),
+    ));
+    if ports_column {
+        header_spans.push(Span::styled(
+            format!("{:width$} ", "Ports", width = PORTS_WIDTH),
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .fg(Color::Cyan),
-        ),
-    ]);
+        ));
+    }
+    if schedule_column {
+        header_spans.push(Span::styled(
+            format!("{:width$} ", "Schedule", width = SCHEDULE_WIDTH),
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Cyan),
+        ));
+    }
+    header_spans.push(Span::styled(
+        if app.image_column {
+            "Type            Image"
+        } else {
+            "Type"
+        },
+        Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(Color::Cyan),
+    ));
+    let header = Line::from(header_spans);
 
+    let active = app.active_remote.as_deref().unwrap_or("local");
+    let border = match app.accents.of_remote(active) {
+        Some(accent) if !app.all_remotes => accent,
+        _ => Color::White,
+    };
     let containers_widget = List::new(containers_list)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::White))
+                .border_style(Style::default().fg(border))
                 .border_type(BorderType::Rounded)
-                .title(" Containers "),
+                .title(title),
         )
         .style(Style::default().fg(Color::White));
 
@@ -228,7 +857,9 @@ fn draw_container_list(frame: &mut Frame, area: Rect, app: &App) {
         height: area.height.saturating_sub(1),
     };
 
-    frame.render_widget(containers_widget, list_area);
+    // A fresh offset each frame keeps the selection visible at any height
+    let mut list_state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(containers_widget, list_area, &mut list_state);
 }
 
 fn draw_command_hints(frame: &mut Frame, area: Rect, app: &App) {
@@ -261,29 +892,78 @@ fn draw_command_hints(frame: &mut Frame, area: Rect, app: &App) {
                 Span::raw("Back"),
             ])]
         }
-        InputMode::Confirmation { .. } => {
+        InputMode::Confirmation { checks, .. } if checks.contains(&Check::Protected) => {
             vec![Line::from(vec![
-                Span::styled("[Enter/Y] ", Style::default().fg(Color::Green)),
-                Span::raw("Confirm  "),
+                Span::styled("[P] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Remove Protection  "),
                 Span::styled("[Esc/N] ", Style::default().fg(Color::Red)),
                 Span::raw("Cancel"),
             ])]
         }
-        InputMode::Input { .. } => {
+        InputMode::Confirmation {
+            typed_name: Some(_),
+            ..
+        } => {
             vec![Line::from(vec![
                 Span::styled("[Enter] ", Style::default().fg(Color::Green)),
-                Span::raw("Submit  "),
+                Span::raw("Confirm  "),
                 Span::styled("[Esc] ", Style::default().fg(Color::Red)),
                 Span::raw("Cancel"),
             ])]
         }
+        InputMode::Confirmation { .. } => {
+            vec![Line::from(vec![
+                Span::styled("[Enter/Y] ", Style::default().fg(Color::Green)),
+                Span::raw("Confirm  "),
+                Span::styled("[Esc/N] ", Style::default().fg(Color::Red)),
+                Span::raw("Cancel"),
+            ])]
+        }
+        InputMode::Input {
+            callback_action, ..
+        } => {
+            let mut spans = vec![
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Submit  "),
+            ];
+            if callback_action.history().is_some() {
+                spans.push(Span::styled("[↑/↓] ", Style::default().fg(Color::Yellow)));
+                spans.push(Span::raw("History  "));
+            }
+            spans.push(Span::styled("[Esc] ", Style::default().fg(Color::Red)));
+            spans.push(Span::raw("Cancel"));
+            vec![Line::from(spans)]
+        }
         InputMode::StatusModal(modal_type) => match modal_type {
+            StatusModalType::Progress {
+                confirm_cancel: true,
+                ..
+            } => {
+                vec![Line::from(vec![
+                    Span::styled("[y] ", Style::default().fg(Color::Red)),
+                    Span::raw("Cancel Operation  "),
+                    Span::styled("[Any Key] ", Style::default().fg(Color::Yellow)),
+                    Span::raw("Keep Running"),
+                ])]
+            }
             StatusModalType::Progress { .. } => {
                 vec![Line::from(vec![
-                    Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                    Span::styled("[b/Esc] ", Style::default().fg(Color::Cyan)),
+                    Span::raw("Run in Background  "),
+                    Span::styled("[c] ", Style::default().fg(Color::Red)),
                     Span::raw("Cancel Operation"),
                 ])]
             }
+            StatusModalType::Error { .. } => {
+                vec![Line::from(vec![
+                    Span::styled("[c] ", Style::default().fg(Color::Green)),
+                    Span::raw("Copy Report  "),
+                    Span::styled("[w] ", Style::default().fg(Color::Yellow)),
+                    Span::raw("Save Report  "),
+                    Span::styled("[Any Key] ", Style::default().fg(Color::Yellow)),
+                    Span::raw("Close"),
+                ])]
+            }
             _ => {
                 vec![Line::from(vec![
                     Span::styled("[Any Key] ", Style::default().fg(Color::Yellow)),
@@ -291,25 +971,414 @@ fn draw_command_hints(frame: &mut Frame, area: Rect, app: &App) {
                 ])]
             }
         },
-        InputMode::Wizard(_) => {
+        InputMode::SpaceWarning { .. } => {
             vec![Line::from(vec![
-                Span::styled("[Tab] ", Style::default().fg(Color::Yellow)),
+                Span::styled("[Enter/Y] ", Style::default().fg(Color::Green)),
+                Span::raw("Go Ahead  "),
+                Span::styled("[Esc/N] ", Style::default().fg(Color::Red)),
+                Span::raw("Cancel"),
+            ])]
+        }
+        InputMode::Preview(_) => {
+            vec![Line::from(vec![
+                Span::styled("[Enter/s] ", Style::default().fg(Color::Green)),
+                Span::raw("Send  "),
+                Span::styled("[Esc/n] ", Style::default().fg(Color::Red)),
+                Span::raw("Cancel"),
+            ])]
+        }
+        InputMode::Help {
+            searching: true, ..
+        } => {
+            vec![Line::from(vec![
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Keep Filter  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Clear Filter"),
+            ])]
+        }
+        InputMode::Help { .. } => {
+            vec![Line::from(vec![
+                Span::styled("[j/k PgUp/PgDn] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Scroll  "),
+                Span::styled("[/] ", Style::default().fg(Color::Cyan)),
+                Span::raw("Search  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Log => {
+            vec![Line::from(vec![
+                Span::styled("[j/k PgUp/PgDn] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Scroll  "),
+                Span::styled("[g/G] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Top/Bottom  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Console => {
+            vec![Line::from(vec![
+                Span::styled("[j/k PgUp/PgDn] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Scroll  "),
+                Span::styled("[Space] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Pause  "),
+                Span::styled("[/ n/N] ", Style::default().fg(Color::Cyan)),
+                Span::raw("Search  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Tour => {
+            vec![Line::from(vec![
+                Span::styled("[Enter/→] ", Style::default().fg(Color::Green)),
                 Span::raw("Next  "),
-                Span::styled("[Shift+Tab] ", Style::default().fg(Color::Yellow)),
-                Span::raw("Previous  "),
+                Span::styled("[←] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Back  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("End Tour"),
+            ])]
+        }
+        InputMode::Operations => {
+            vec![Line::from(vec![
+                Span::styled("[j/k ↑/↓] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Navigate  "),
                 Span::styled("[Enter] ", Style::default().fg(Color::Green)),
-                Span::raw("Confirm  "),
+                Span::raw("Details  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Retry  "),
+                Span::styled("[f/g] ", Style::default().fg(Color::Cyan)),
+                Span::raw("Filter/Group  "),
+                Span::styled("[c] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Clear  "),
+                Span::styled("[Esc/Tab] ", Style::default().fg(Color::Red)),
+                Span::raw("Back"),
+            ])]
+        }
+        InputMode::OperationDetails { .. } => {
+            vec![Line::from(vec![
+                Span::styled("[j/k PgUp/PgDn] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Scroll  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Retry  "),
                 Span::styled("[Esc] ", Style::default().fg(Color::Red)),
-                Span::raw("Cancel"),
+                Span::raw("Back"),
             ])]
         }
-    };
-
-    let hints_widget = Paragraph::new(hints)
-        .block(
-            Block::default()
-                .borders(Borders::TOP)
-                .border_style(Style::default().fg(Color::DarkGray)),
+        InputMode::Watch => {
+            vec![Line::from(vec![
+                Span::styled("[c] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Clear Events  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Top => {
+            vec![Line::from(vec![
+                Span::styled("[c/m/n] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Rank by CPU/Memory/Network  "),
+                Span::styled("[+/-] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Window  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Dashboard => {
+            vec![Line::from(vec![
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Container List  "),
+                Span::styled("[q] ", Style::default().fg(Color::Red)),
+                Span::raw("Quit"),
+            ])]
+        }
+        InputMode::BootOrder => {
+            vec![Line::from(vec![
+                Span::styled("[a] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Autostart  "),
+                Span::styled("[p/d] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Priority/Delay  "),
+                Span::styled("[s] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Sort  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Audit {
+            confirm_rebuild: true,
+        } => {
+            vec![Line::from(vec![
+                Span::styled("[y] ", Style::default().fg(Color::Green)),
+                Span::raw("Rebuild  "),
+                Span::styled("[any key] ", Style::default().fg(Color::Red)),
+                Span::raw("Cancel"),
+            ])]
+        }
+        InputMode::Audit { .. } => {
+            vec![Line::from(vec![
+                Span::styled("[b] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Rebuild from latest  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Zones {
+            confirm_delete: true,
+        } => {
+            vec![Line::from(vec![
+                Span::styled("[y] ", Style::default().fg(Color::Green)),
+                Span::raw("Delete  "),
+                Span::styled("[any key] ", Style::default().fg(Color::Red)),
+                Span::raw("Cancel"),
+            ])]
+        }
+        InputMode::Zones { .. } => {
+            vec![Line::from(vec![
+                Span::styled("[a] ", Style::default().fg(Color::Yellow)),
+                Span::raw("New Zone  "),
+                Span::styled("[n] ", Style::default().fg(Color::Yellow)),
+                Span::raw("New Record  "),
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Edit  "),
+                Span::styled("[d] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Delete  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::ImageStore => {
+            vec![Line::from(vec![
+                Span::styled("[a] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Auto-update  "),
+                Span::styled("[u] ", Style::default().fg(Color::Green)),
+                Span::raw("Update Now  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Pools {
+            confirm_scrub: true,
+        } => {
+            vec![Line::from(vec![
+                Span::styled("[y] ", Style::default().fg(Color::Green)),
+                Span::raw("Scrub  "),
+                Span::styled("[any key] ", Style::default().fg(Color::Red)),
+                Span::raw("Cancel"),
+            ])]
+        }
+        InputMode::Pools { .. } => {
+            vec![Line::from(vec![
+                Span::styled("[j/k] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Select  "),
+                Span::styled("[s] ", Style::default().fg(Color::Green)),
+                Span::raw("Scrub  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Capacity => {
+            vec![Line::from(vec![
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Templates => {
+            vec![Line::from(vec![
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Edit  "),
+                Span::styled("[m] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Metadata  "),
+                Span::styled("[n] ", Style::default().fg(Color::Yellow)),
+                Span::raw("New  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::VmSettings => {
+            vec![Line::from(vec![
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Change  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Drift => {
+            vec![Line::from(vec![
+                Span::styled("[o] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Open  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::KeySearch => {
+            vec![Line::from(vec![
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Show  "),
+                Span::styled("[/] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Search  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Profiles => {
+            vec![Line::from(vec![
+                Span::styled("[a] ", Style::default().fg(Color::Green)),
+                Span::raw("Add  "),
+                Span::styled("[d] ", Style::default().fg(Color::Red)),
+                Span::raw("Remove  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Quick { .. } => {
+            let keys: &[(&str, &str, Color)] =
+                if app.quick.as_ref().is_some_and(|view| view.running()) {
+                    &[
+                        ("[j/k] ", "Navigate  ", Color::Yellow),
+                        ("[PgUp/PgDn] ", "Scroll  ", Color::Yellow),
+                        ("[Enter] ", "Expand  ", Color::Yellow),
+                        ("[Esc] ", "Cancel  ", Color::Red),
+                        ("[q] ", "Close", Color::Red),
+                    ]
+                } else if app.quick.as_ref().is_some_and(|view| view.run.is_some()) {
+                    &[
+                        ("[j/k] ", "Navigate  ", Color::Yellow),
+                        ("[PgUp/PgDn] ", "Scroll  ", Color::Yellow),
+                        ("[Enter] ", "Expand  ", Color::Yellow),
+                        ("[r] ", "Run Again  ", Color::Green),
+                        ("[Esc] ", "Close", Color::Red),
+                    ]
+                } else {
+                    &[
+                        ("[j/k] ", "Navigate  ", Color::Yellow),
+                        ("[key/Enter] ", "Run  ", Color::Green),
+                        ("[!] ", "Command  ", Color::Green),
+                        ("[Esc] ", "Close", Color::Red),
+                    ]
+                };
+            let spans = keys
+                .iter()
+                .flat_map(|(key, label, colour)| {
+                    [
+                        Span::styled(*key, Style::default().fg(*colour)),
+                        Span::raw(*label),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            vec![Line::from(spans)]
+        }
+        InputMode::Recreate => {
+            vec![Line::from(vec![
+                Span::styled("[n] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Name  "),
+                Span::styled("[i] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Image  "),
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Recreate  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Idmap => {
+            vec![Line::from(vec![
+                Span::styled("[a] ", Style::default().fg(Color::Green)),
+                Span::raw("Add  "),
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Edit  "),
+                Span::styled("[d] ", Style::default().fg(Color::Red)),
+                Span::raw("Delete  "),
+                Span::styled("[i] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Isolated  "),
+                Span::styled("[s] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Shift  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::CloneOptions(_) => {
+            vec![Line::from(vec![
+                Span::styled("[j/k ↑/↓] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Navigate  "),
+                Span::styled("[Space] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Toggle  "),
+                Span::styled("[←/→] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Choose  "),
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Clone  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Cancel"),
+            ])]
+        }
+        InputMode::Compare => {
+            vec![Line::from(vec![
+                Span::styled("[j/k PgUp/PgDn] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Scroll  "),
+                Span::styled("[d] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Differences Only  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Backups { .. } => {
+            vec![Line::from(vec![
+                Span::styled("[j/k ↑/↓] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Navigate  "),
+                Span::styled("[n] ", Style::default().fg(Color::Green)),
+                Span::raw("New  "),
+                Span::styled("[w] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Download  "),
+                Span::styled("[d] ", Style::default().fg(Color::Red)),
+                Span::raw("Delete  "),
+                Span::styled("[r] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Reload  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Close"),
+            ])]
+        }
+        InputMode::Wizard(_) => {
+            vec![Line::from(vec![
+                Span::styled("[Tab] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Next  "),
+                Span::styled("[Shift+Tab] ", Style::default().fg(Color::Yellow)),
+                Span::raw("Previous  "),
+                Span::styled("[Enter] ", Style::default().fg(Color::Green)),
+                Span::raw("Confirm  "),
+                Span::styled("[Esc] ", Style::default().fg(Color::Red)),
+                Span::raw("Cancel"),
+            ])]
+        }
+    };
+
+    let hints_widget = Paragraph::new(hints)
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_style(Style::default().fg(Color::DarkGray)),
         )
         .alignment(Alignment::Center);
 
@@ -317,533 +1386,3973 @@ fn draw_command_hints(frame: &mut Frame, area: Rect, app: &App) {
 }
 
 fn draw_operation_sidebar(frame: &mut Frame, area: Rect, app: &App) {
-    let mut content = Vec::new();
+    let focused = matches!(
+        app.input_mode,
+        InputMode::Operations | InputMode::OperationDetails { .. }
+    );
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(if focused {
+            Color::Cyan
+        } else {
+            Color::DarkGray
+        }))
+        .title(operations_title(app));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
     // Active operations
-    if app.active_operation_count > 0 {
-        content.push(Line::from(vec![Span::styled(
+    let list_area = if app.active_operation_count > 0 {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(1)])
+            .split(inner);
+        let header = Line::from(vec![Span::styled(
             "Active Operations",
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
-        )]));
+        )]);
+        frame.render_widget(Paragraph::new(header), parts[0]);
+        parts[1]
+    } else {
+        inner
+    };
+
+    if app.user_operations.is_empty() {
+        frame.render_widget(Paragraph::new("No operations yet"), list_area);
+        return;
+    }
+    let groups = sidebar::arrange(
+        &app.user_operations,
+        app.operation_filter,
+        app.operation_grouping,
+    );
+    if groups.is_empty() {
+        frame.render_widget(Paragraph::new("No operations match the filter"), list_area);
+        return;
+    }
+
+    // Newest first in each group; the details modal has the full text
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut items: Vec<ListItem> = Vec::new();
+    let mut rows = Vec::new(); // Row of each listed operation
+    for group in &groups {
+        if let Some(title) = &group.title {
+            items.push(ListItem::new(Line::styled(
+                format!("{} ({})", title, group.operations.len()),
+                heading,
+            )));
+        }
+        for &index in &group.operations {
+            rows.push(items.len());
+            items.push(operation_item(app, &app.user_operations[index]));
+        }
+    }
+
+    let selected = focused
+        .then(|| rows.get(app.operation_selected).copied())
+        .flatten();
+    frame.render_stateful_widget(
+        List::new(items).highlight_style(app.theme.selected()),
+        list_area,
+        &mut ListState::default().with_selected(selected),
+    );
+}
+
+/// " Operations ", with the filter and grouping when set
+fn operations_title(app: &App) -> String {
+    let labels: Vec<&str> = [app.operation_filter.label(), app.operation_grouping.label()]
+        .into_iter()
+        .flatten()
+        .collect();
+    if labels.is_empty() {
+        " Operations ".to_string()
+    } else {
+        format!(" Operations · {} ", labels.join(" · "))
+    }
+}
+
+/// One operation's row: its icon, description and how long it took
+fn operation_item(app: &App, op: &UserOperation) -> ListItem<'static> {
+    let duration = if let Some(started) = op.started_at {
+        if let Some(completed) = op.completed_at {
+            format!(" ({}s)", (completed - started).as_secs())
+        } else {
+            format!(" ({}s)", started.elapsed().as_secs())
+        }
+    } else if let Some(finished) = op.finished_at {
+        // From an earlier session: when rather than how long
+        let today = chrono::Local::now().date_naive();
+        let format = if finished.date_naive() == today {
+            " (%H:%M)"
+        } else {
+            " (%b %d)"
+        };
+        finished.format(format).to_string()
+    } else {
+        String::new()
+    };
+
+    let line = match &op.status {
+        OperationStatus::Retrying(_) => {
+            format!(
+                "{} {} (retry {})",
+                app.theme.operation_icon(&op.status),
+                op.description,
+                op.retry_count
+            )
+        }
+        _ => format!(
+            "{} {}{}",
+            app.theme.operation_icon(&op.status),
+            op.description,
+            duration
+        ),
+    };
+    ListItem::new(line)
+}
+
+fn draw_operation_details(
+    frame: &mut Frame,
+    operation: &UserOperation,
+    transfer: Option<(Transfer, Option<Estimate>)>,
+    scroll: u16,
+    theme: Theme,
+) {
+    let area = centered_rect(70, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let label =
+        |name: &str| Span::styled(format!("{:<13}", name), Style::default().fg(Color::Cyan));
+    let heading = |text: &'static str| {
+        Line::styled(
+            text,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+
+    let status = match &operation.status {
+        OperationStatus::Registered => "Registered".to_string(),
+        OperationStatus::Running => "Running".to_string(),
+        OperationStatus::Retrying(n) => format!("Retrying ({}/3)", n),
+        OperationStatus::Success => "Succeeded".to_string(),
+        OperationStatus::Failed(_) => "Failed".to_string(),
+        OperationStatus::Cancelled => "Cancelled".to_string(),
+    };
+    let duration = match (operation.started_at, operation.completed_at) {
+        (Some(started), Some(completed)) => format!("{}s", (completed - started).as_secs()),
+        (Some(started), None) => format!("{}s so far", started.elapsed().as_secs()),
+        _ => "-".to_string(),
+    };
+
+    let mut content = vec![
+        Line::from(vec![
+            label("Description"),
+            Span::raw(&operation.description),
+        ]),
+        Line::from(vec![
+            label("Container"),
+            Span::raw(operation.container.as_deref().unwrap_or("-")),
+        ]),
+        Line::from(vec![
+            label("Status"),
+            Span::raw(format!(
+                "{} {}",
+                theme.operation_icon(&operation.status),
+                status
+            )),
+        ]),
+        Line::from(vec![label("Duration"), Span::raw(duration)]),
+        Line::from(vec![
+            label("Finished"),
+            Span::raw(operation.finished_at.map_or("-".to_string(), |at| {
+                at.format("%Y-%m-%d %H:%M:%S").to_string()
+            })),
+        ]),
+        Line::from(vec![
+            label("Retries"),
+            Span::raw(operation.retry_count.to_string()),
+        ]),
+    ];
+
+    if let Some((transfer, estimate)) = transfer {
         content.push(Line::from(""));
+        content.push(heading("Migration"));
+        content.push(Line::from(vec![
+            label("Stage"),
+            Span::raw(transfer.stage.describe()),
+        ]));
+        let copied = match (transfer.percent, transfer.transferred) {
+            (Some(percent), _) => format!("{}% of this volume", percent),
+            (None, Some(bytes)) => watch::format_bytes(bytes as f64),
+            (None, None) => "-".to_string(),
+        };
+        content.push(Line::from(vec![label("Copied"), Span::raw(copied)]));
+        content.push(Line::from(vec![
+            label("Rate"),
+            Span::raw(transfer.rate.map_or("-".to_string(), |rate| {
+                format!("{}/s", watch::format_bytes(rate as f64))
+            })),
+        ]));
+        let completion = match estimate {
+            Some(Estimate::Remaining(left)) => format!(
+                "{}, around {}",
+                Estimate::Remaining(left).describe(),
+                (chrono::Local::now() + left).format("%H:%M:%S")
+            ),
+            Some(estimate) => estimate.describe(),
+            None => "-".to_string(),
+        };
+        content.push(Line::from(vec![label("Completion"), Span::raw(completion)]));
     }
 
-    // Recent operations
-    let recent_ops: Vec<_> = app.user_operations.iter().rev().take(10).collect();
-    if !recent_ops.is_empty() {
-        for op in recent_ops {
-            let status_icon = match &op.status {
-                crate::app::OperationStatus::Registered => "⏳",
-                crate::app::OperationStatus::Running => "🚀",
-                crate::app::OperationStatus::Retrying(_) => "🔄",
-                crate::app::OperationStatus::Success => "✅",
-                crate::app::OperationStatus::Failed(_) => "❌",
-                crate::app::OperationStatus::Cancelled => "🚫",
-            };
+    if let OperationStatus::Failed(error) = &operation.status {
+        content.push(Line::from(""));
+        content.push(heading("Error"));
+        content.extend(
+            error
+                .lines()
+                .map(|line| Line::styled(line.to_string(), Style::default().fg(Color::Red))),
+        );
+    }
 
-            let duration = if let Some(started) = op.started_at {
-                if let Some(completed) = op.completed_at {
-                    format!(" ({}s)", (completed - started).as_secs())
-                } else {
-                    format!(" ({}s)", started.elapsed().as_secs())
+    content.push(Line::from(""));
+    content.push(heading("LXD Operation"));
+    match &operation.lxd_operation {
+        Some(lxd) => {
+            content.push(Line::from(vec![label("ID"), Span::raw(&lxd.id)]));
+            content.push(Line::from(vec![label("Class"), Span::raw(&lxd.class)]));
+            content.push(Line::from(vec![
+                label("Status"),
+                Span::raw(format!("{} ({})", lxd.status, lxd.status_code)),
+            ]));
+            content.push(Line::from(vec![
+                label("Created"),
+                Span::raw(&lxd.created_at),
+            ]));
+            content.push(Line::from(vec![
+                label("Updated"),
+                Span::raw(&lxd.updated_at),
+            ]));
+            if !lxd.location.is_empty() {
+                content.push(Line::from(vec![
+                    label("Location"),
+                    Span::raw(&lxd.location),
+                ]));
+            }
+            if !lxd.err.is_empty() {
+                content.push(Line::from(vec![label("Error"), Span::raw(&lxd.err)]));
+            }
+            for (name, value) in [("Resources", &lxd.resources), ("Metadata", &lxd.metadata)] {
+                if let Some(value) = value.as_ref().filter(|v| !v.is_null()) {
+                    content.push(Line::from(label(name)));
+                    let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+                    content.extend(pretty.lines().map(|line| Line::from(format!("  {}", line))));
                 }
-            } else {
-                String::new()
+            }
+        }
+        None => content.push(Line::styled(
+            "Not an LXD operation, or LXD has not reported on it yet",
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+
+    let block = Block::default()
+        .title(" Operation Details ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Rows of the event feed panel, borders included
+const EVENT_PANEL_HEIGHT: u16 = 8;
+
+fn draw_event_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let rows = area.height.saturating_sub(2) as usize;
+    let mut lines: Vec<Line> = app
+        .events
+        .events
+        .iter()
+        .rev()
+        .take(rows)
+        .map(|event| {
+            let entity = match &event.remote {
+                Some(remote) => format!("{}:{}", remote, event.entity),
+                None => event.entity.clone(),
             };
+            let mut spans = vec![
+                Span::styled(
+                    event.at.format("%H:%M:%S  ").to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{:<28}", event.action),
+                    app.theme.style(event_tone(&event.action)),
+                ),
+                Span::raw(format!(" {}", entity)),
+            ];
+            if let Some(requestor) = &event.requestor {
+                spans.push(Span::styled(
+                    format!("  by {}", requestor),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::styled(
+            "Waiting for events...",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(" Events ");
+    if let Some(error) = &app.events.error {
+        block = block.title_bottom(Line::styled(
+            format!(" {} ", error),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn event_tone(action: &str) -> Tone {
+    if action.ends_with("-deleted") || action.ends_with("-stopped") || action.ends_with("-shutdown")
+    {
+        Tone::Bad
+    } else if action.ends_with("-created") || action.ends_with("-started") {
+        Tone::Good
+    } else {
+        Tone::Warning
+    }
+}
+
+/// Smallest modal size kept when the terminal shrinks, space permitting
+const MIN_MODAL_WIDTH: u16 = 40;
+const MIN_MODAL_HEIGHT: u16 = 10;
+
+/// `percent` of `total`, raised to `min` but never larger than `total`
+fn modal_extent(total: u16, percent: u16, min: u16) -> u16 {
+    let extent = (total as u32 * percent as u32 / 100) as u16;
+    extent.max(min).min(total)
+}
+
+fn centered_rect(width_percent: u16, height_percent: u16, r: Rect) -> Rect {
+    let width = modal_extent(r.width, width_percent, MIN_MODAL_WIDTH);
+    let height = modal_extent(r.height, height_percent, MIN_MODAL_HEIGHT);
+
+    Rect {
+        x: r.x + (r.width - width) / 2,
+        y: r.y + (r.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Lines above a menu's entries: a blank one, and the server's fingerprint
+/// when authenticating
+fn menu_header(menu: &CommandMenu, app: &App) -> usize {
+    match (menu, &app.pending_remote) {
+        (CommandMenu::RemoteAuth, Some(_)) => 3,
+        _ => 1,
+    }
+}
+
+/// Entries of `menu` as key, label and description. Custom actions from
+/// the config file go before Esc in the container menu.
+fn menu_rows<'a>(menu: &CommandMenu, app: &'a App) -> Vec<(String, &'a str, &'a str)> {
+    let items = match menu {
+        CommandMenu::Closed | CommandMenu::Main => return Vec::new(),
+        CommandMenu::Container => keymap::CONTAINER_MENU,
+        CommandMenu::System => keymap::SYSTEM_MENU,
+        CommandMenu::RemoteAuth => keymap::REMOTE_AUTH_MENU,
+    };
+    let (esc, built_in) = items.split_last().expect("menus end with Esc");
+    let mut rows: Vec<(String, &str, &str)> = built_in
+        .iter()
+        .map(|b| (b.keys.to_string(), b.action, b.description))
+        .collect();
+    if let CommandMenu::Container = menu {
+        rows.extend(app.custom_actions.iter().map(|a| {
+            let key = a.key.map(String::from).unwrap_or_else(|| "-".to_string());
+            (key, a.name.as_str(), a.command.as_str())
+        }));
+    }
+    rows.push((esc.keys.to_string(), esc.action, esc.description));
+    rows
+}
+
+/// Where `menu` is drawn: tall enough for every entry and the hint below
+/// them, as far as the screen allows
+fn menu_area(full: Rect, menu: &CommandMenu, app: &App) -> Rect {
+    let lines = menu_header(menu, app) + menu_rows(menu, app).len() + 1;
+    let width = modal_extent(full.width, 70, MIN_MODAL_WIDTH);
+    let height = (lines as u16 + 2).clamp(
+        MIN_MODAL_HEIGHT.min(full.height),
+        modal_extent(full.height, 90, MIN_MODAL_HEIGHT),
+    );
+    Rect {
+        x: full.x + (full.width - width) / 2,
+        y: full.y + (full.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+fn draw_command_menu(frame: &mut Frame, menu: &CommandMenu, app: &App) {
+    let selected = app.menu_selected;
+    let area = menu_area(frame.area(), menu, app);
+    frame.render_widget(Clear, area);
+
+    let title = match menu {
+        CommandMenu::Closed | CommandMenu::Main => return,
+        CommandMenu::Container => " Container Actions ",
+        CommandMenu::System => " System Menu ",
+        CommandMenu::RemoteAuth => " Authenticate Remote ",
+    };
+
+    let mut content = vec![Line::from("")];
+
+    // Let the user check the pinned certificate before authenticating
+    if let (CommandMenu::RemoteAuth, Some(pending)) = (menu, &app.pending_remote) {
+        content.push(Line::from(vec![
+            Span::styled(
+                " Server fingerprint: ",
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                pending.fingerprint.clone(),
+                Style::default().fg(Color::White),
+            ),
+        ]));
+        content.push(Line::from(""));
+    }
+
+    let rows = menu_rows(menu, app);
+    // Labels line up after the longest key
+    let key_width = rows
+        .iter()
+        .map(|r| r.0.chars().count() + 3)
+        .max()
+        .unwrap_or(0);
+    // Skip the "Esc" option when counting (it's always last)
+    let selectable_items = rows.len() - 1;
+
+    // On a short screen, scroll so the selected entry stays in view
+    let total = rows.len();
+    let visible = (area.height as usize)
+        .saturating_sub(2 + content.len() + 1)
+        .max(1);
+    let skip = selected.min(total - 1).saturating_sub(visible - 1);
+
+    for (idx, (key, label, desc)) in rows.into_iter().enumerate().skip(skip).take(visible) {
+        // Don't highlight Esc option
+        let is_selected = idx < selectable_items && idx == selected;
+
+        // Entries for features the server lacks are greyed out with its
+        // answer, and so are the ones read-only mode or the deployment's
+        // permissions disable
+        let action = match menu {
+            CommandMenu::Container => input::container_menu_action(idx, app.custom_actions.len()),
+            CommandMenu::System => input::system_menu_action(idx),
+            _ => None,
+        };
+        let unavailable = match menu {
+            _ if app.read_only && action.as_ref().is_some_and(|a| a.mutates()) => {
+                Some("read-only mode")
+            }
+            _ if action
+                .as_ref()
+                .and_then(|a| a.permission())
+                .is_some_and(|p| !app.permissions.permits(p)) =>
+            {
+                Some("not permitted")
+            }
+            CommandMenu::Container => {
+                Feature::of_menu_item(label).and_then(|feature| app.unavailable.reason(feature))
+            }
+            _ => None,
+        };
+
+        if let Some(reason) = unavailable {
+            let grey = Style::default().fg(Color::DarkGray);
+            content.push(Line::from(vec![
+                Span::styled(if is_selected { " ▶ " } else { "   " }, grey),
+                Span::styled(
+                    format!("{:<width$}", format!("[{}] ", key), width = key_width),
+                    grey,
+                ),
+                Span::styled(format!("{:<20}", label), grey),
+                Span::styled(format!("Unavailable: {}", reason), grey),
+            ]));
+        } else if is_selected {
+            // Highlighted selection with arrow indicator
+            content.push(Line::from(vec![
+                Span::styled(
+                    " ▶ ",
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{:<width$}", format!("[{}] ", key), width = key_width),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{:<20}", label),
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(desc.to_string(), Style::default().fg(Color::White)),
+            ]));
+        } else {
+            // Normal item
+            content.push(Line::from(vec![
+                Span::styled("   ", Style::default()), // Space for arrow
+                Span::styled(
+                    format!("{:<width$}", format!("[{}] ", key), width = key_width),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("{:<20}", label), Style::default().fg(Color::White)),
+                Span::styled(desc.to_string(), Style::default().fg(Color::DarkGray)),
+            ]));
+        }
+    }
+
+    // Add navigation hint at the bottom
+    content.push(Line::from(vec![
+        Span::styled(" Use ", Style::default().fg(Color::DarkGray)),
+        Span::styled("↑/↓ or j/k", Style::default().fg(Color::Cyan)),
+        Span::styled(" to navigate, ", Style::default().fg(Color::DarkGray)),
+        Span::styled("Enter", Style::default().fg(Color::Cyan)),
+        Span::styled(" to select", Style::default().fg(Color::DarkGray)),
+    ]));
+
+    let mut block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+
+    // The server's version, storage driver and channel, and whether it's too old
+    if let (CommandMenu::System, Some(details)) = (menu, &app.server_details) {
+        let mut spans = vec![Span::styled(
+            format!(" {} ", details.summary()),
+            Style::default().fg(Color::White),
+        )];
+        if let Some(minimum) = &details.outdated {
+            spans.push(Span::styled(
+                format!("⚠ older than {} ", minimum),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        block = block.title_bottom(Line::from(spans));
+    }
+    if visible < total {
+        block = block.title_bottom(
+            Line::from(format!(
+                " {}-{} of {} ",
+                skip + 1,
+                (skip + visible).min(total),
+                total
+            ))
+            .right_aligned(),
+        );
+    }
+
+    let paragraph = Paragraph::new(content).block(block);
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_status_modal(frame: &mut Frame, modal_type: &StatusModalType, app: &App) {
+    let area = centered_rect(70, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    match modal_type {
+        StatusModalType::Info {
+            message,
+            auto_close,
+            ..
+        } => {
+            draw_info_modal(frame, area, message, *auto_close);
+        }
+        StatusModalType::Progress {
+            operation_id,
+            confirm_cancel,
+        } => {
+            if let Some(operation) = app.user_operations.iter().find(|op| op.id == *operation_id) {
+                let progress = app
+                    .lxd_operations
+                    .get(operation_id)
+                    .and_then(|tracker| tracker.progress);
+                let estimate = app.operation_estimate(operation);
+                draw_progress_modal(
+                    frame,
+                    area,
+                    operation,
+                    progress,
+                    estimate,
+                    *confirm_cancel,
+                    app.reduced_motion,
+                );
+            }
+        }
+        StatusModalType::Error {
+            title,
+            details,
+            suggestions,
+            ..
+        } => {
+            draw_error_modal(frame, area, title, details, suggestions, app.theme);
+        }
+        StatusModalType::Success {
+            message,
+            started_at,
+        } => {
+            let closes_in = app
+                .dismiss_timeout()
+                .map(|timeout| timeout.saturating_sub(started_at.elapsed()));
+            draw_success_modal(frame, area, message, closes_in, app.theme);
+        }
+    }
+}
+
+fn draw_info_modal(frame: &mut Frame, area: Rect, message: &str, auto_close: bool) {
+    let block = Block::default()
+        .title(" Information ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Blue))
+        .border_type(BorderType::Rounded);
+
+    let mut lines: Vec<Line> = vec![Line::from("")];
+    for line in message.lines() {
+        lines.push(Line::from(line));
+    }
+    lines.push(Line::from(""));
+
+    if !auto_close {
+        lines.push(Line::from(vec![Span::styled(
+            "Press any key to continue",
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        )]));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Time left and LXD's progress, when either is known
+fn estimate_line(progress: Option<i32>, estimate: Option<Estimate>) -> Line<'static> {
+    let text = match (estimate, progress) {
+        (Some(estimate), Some(progress)) => format!("{} ({}%)", estimate.describe(), progress),
+        (Some(estimate), None) => estimate.describe(),
+        (None, Some(progress)) => format!("{}% done", progress),
+        (None, None) => String::new(),
+    };
+    let color = match estimate {
+        Some(Estimate::LongerThanUsual) => Color::Yellow,
+        _ => Color::White,
+    };
+    Line::from(Span::styled(text, Style::default().fg(color)))
+}
+
+fn draw_progress_modal(
+    frame: &mut Frame,
+    area: Rect,
+    operation: &crate::app::UserOperation,
+    progress: Option<i32>,
+    estimate: Option<Estimate>,
+    confirm_cancel: bool,
+    reduced_motion: bool,
+) {
+    let elapsed_secs = if let Some(started) = operation.started_at {
+        started.elapsed().as_secs()
+    } else {
+        0
+    };
+
+    let spinner = match elapsed_secs % 4 {
+        0 => "⠋",
+        1 => "⠙",
+        2 => "⠹",
+        _ => "⠸",
+    };
+
+    let status_line = match &operation.status {
+        crate::app::OperationStatus::Registered => "⏳ Preparing...".to_string(),
+        crate::app::OperationStatus::Running if reduced_motion => "In Progress".to_string(),
+        crate::app::OperationStatus::Running => format!("{} In Progress...", spinner),
+        crate::app::OperationStatus::Retrying(count) => {
+            format!("🔄 Retrying... (attempt {}/3)", count)
+        }
+        _ => "Processing...".to_string(),
+    };
+
+    let block = Block::default()
+        .title(" Operation Progress ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+
+    let key = |key: &'static str, color: Color| {
+        Span::styled(key, Style::default().fg(color).add_modifier(Modifier::BOLD))
+    };
+    let dim = |text: &'static str| Span::styled(text, Style::default().fg(Color::DarkGray));
+    let keys = if confirm_cancel {
+        Line::from(vec![
+            Span::styled("Cancel this operation? ", Style::default().fg(Color::Red)),
+            key("y", Color::Red),
+            dim(" to cancel, any other key to keep it running"),
+        ])
+    } else {
+        Line::from(vec![
+            dim("Press "),
+            key("b", Color::Cyan),
+            dim(" or "),
+            key("Esc", Color::Cyan),
+            dim(" to run in background, "),
+            key("c", Color::Red),
+            dim(" to cancel"),
+        ])
+    };
+
+    let content = vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            &operation.description,
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            status_line,
+            Style::default().fg(Color::Cyan),
+        )]),
+        Line::from(""),
+        Line::from(format!("Elapsed: {} seconds", elapsed_secs)),
+        estimate_line(progress, estimate),
+        Line::from(""),
+        keys,
+    ];
+
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_error_modal(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    details: &str,
+    suggestions: &[String],
+    theme: Theme,
+) {
+    let icon = match theme {
+        Theme::Standard => "❌",
+        Theme::HighContrast => "[ERROR]",
+    };
+    let block = Block::default()
+        .title(format!(" {} {} ", icon, title))
+        .borders(Borders::ALL)
+        .border_style(theme.style(Tone::Bad))
+        .border_type(BorderType::Rounded);
+
+    let mut content = vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Error Details:",
+            theme.style(Tone::Bad).add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+    ];
+
+    // Add error details
+    for line in details.lines() {
+        content.push(Line::from(vec![Span::styled(
+            line,
+            Style::default().fg(Color::White),
+        )]));
+    }
+
+    if !suggestions.is_empty() {
+        content.push(Line::from(""));
+        content.push(Line::from(vec![Span::styled(
+            "Suggestions:",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]));
+        content.push(Line::from(""));
+
+        for suggestion in suggestions {
+            content.push(Line::from(vec![
+                Span::styled("• ", Style::default().fg(Color::Yellow)),
+                Span::raw(suggestion),
+            ]));
+        }
+    }
+
+    content.push(Line::from(""));
+    content.push(Line::from(vec![Span::styled(
+        "Press c to copy an error report, w to save it, any other key to continue",
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC),
+    )]));
+
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_success_modal(
+    frame: &mut Frame,
+    area: Rect,
+    message: &str,
+    closes_in: Option<Duration>,
+    theme: Theme,
+) {
+    let title = match theme {
+        Theme::Standard => " ✅ Success ",
+        Theme::HighContrast => " [OK] Success ",
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(theme.style(Tone::Good))
+        .border_type(BorderType::Rounded);
+
+    let content = vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            message,
+            theme.style(Tone::Good).add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            match closes_in {
+                // Round up so it never reads 0s while still open
+                Some(left) => format!(
+                    "(Closes in {}s, or press any key)",
+                    left.as_millis().div_ceil(1000)
+                ),
+                None => "(Press any key to continue)".to_string(),
+            },
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        )]),
+    ];
+
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_confirmation_modal(
+    frame: &mut Frame,
+    message: &str,
+    action: &ConfirmAction,
+    typed_name: Option<&str>,
+    checks: &[Check],
+    app: &App,
+) {
+    let input = &app.input_buffer;
+    let height = if checks.is_empty() { 30 } else { 50 };
+    let area = centered_rect(60, height, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = match action {
+        ConfirmAction::StartContainer(_) => " Start Container ",
+        ConfirmAction::StopContainer(_) => " Stop Container ",
+        ConfirmAction::RestartContainer(_) => " Restart Container ",
+        ConfirmAction::DeleteContainer(_) => " ⚠️  Delete Container ",
+    };
+
+    // The remote's accent says where this is about to happen
+    let remote = app.remote_of(action.container_name());
+    let mut block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    block = match app.accents.of_remote(remote) {
+        Some(accent) => block
+            .title(Span::styled(
+                format!(" on {} ", remote),
+                accent_badge(accent),
+            ))
+            .border_style(Style::default().fg(accent)),
+        None => block.border_style(Style::default().fg(Color::Yellow)),
+    };
+
+    let mut content = vec![Line::from(""), Line::from(message)];
+    if !checks.is_empty() {
+        content.push(Line::from(""));
+    }
+    for check in checks {
+        let (tone, kind) = if check.blocks() {
+            (Tone::Bad, "Blocked")
+        } else {
+            (Tone::Warning, "Warning")
+        };
+        content.push(Line::styled(
+            format!("{}: {}", app.theme.label(tone, kind), check.message()),
+            app.theme.style(tone),
+        ));
+    }
+    content.push(Line::from(""));
+
+    if checks.contains(&Check::Protected) {
+        content.push(Line::from(vec![
+            Span::styled("Press ", Style::default().fg(Color::White)),
+            Span::styled(
+                "P",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                " to remove the protection and ask again, or ",
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(
+                "Esc/N",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" to cancel", Style::default().fg(Color::White)),
+        ]));
+    } else if let Some(name) = typed_name {
+        let input_color = if input.as_str() == name {
+            Color::Green
+        } else {
+            Color::Yellow
+        };
+        content.extend([
+            Line::from(vec![
+                Span::styled("Type ", Style::default().fg(Color::White)),
+                Span::styled(
+                    name.to_string(),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(" to confirm:", Style::default().fg(Color::White)),
+            ]),
+            Line::from(field_spans(input, Style::default().fg(input_color))),
+        ]);
+    } else {
+        content.push(Line::from(vec![
+            Span::styled("Press ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Enter/Y",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" to confirm or ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Esc/N",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" to cancel", Style::default().fg(Color::White)),
+        ]));
+    }
+
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
+
+/// The text of a field with its cursor: an underscore at the end, or the
+/// character under it shown reversed
+fn field_spans(field: &TextField, style: Style) -> Vec<Span<'static>> {
+    let (before, after) = field.split();
+    let mut chars = after.chars();
+    let Some(under) = chars.next() else {
+        return vec![Span::styled(format!("{}_", before), style)];
+    };
+    vec![
+        Span::styled(before.to_string(), style),
+        Span::styled(under.to_string(), style.add_modifier(Modifier::REVERSED)),
+        Span::styled(chars.as_str().to_string(), style),
+    ]
+}
+
+fn draw_input_modal(
+    frame: &mut Frame,
+    prompt: &str,
+    input: &TextField,
+    input_type: &InputType,
+    callback: &InputCallback,
+) {
+    let area = centered_rect(60, 20, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = match callback {
+        InputCallback::CloneContainer(_) => " Clone Container ",
+        InputCallback::RefreshCopy(_) => " Refresh Copy ",
+        InputCallback::SetTags(_) => " Tags ",
+        InputCallback::AttachMedia(_) => " Installation Media ",
+        InputCallback::SetVmSetting { .. } => " VM Settings ",
+        InputCallback::IdmapLine { .. } => " ID Mapping ",
+        InputCallback::RecreateName | InputCallback::RecreateImage => " Recreate ",
+        InputCallback::SearchConfigKey => " Key Search ",
+        InputCallback::DriftFile => " Check Drift ",
+        InputCallback::QuickCommand => " Quick Exec ",
+        InputCallback::SetBootConfig { .. } => " Boot Order ",
+        InputCallback::NewTemplate(_) => " Templates ",
+        InputCallback::NewZone
+        | InputCallback::NewZoneRecord(_)
+        | InputCallback::EditZoneRecord { .. } => " DNS Zones ",
+        InputCallback::CreateContainer => " New Container ",
+        InputCallback::AddRemoteName
+        | InputCallback::AddRemoteUrl(_)
+        | InputCallback::AddRemoteToken => " Add Remote ",
+        InputCallback::Command => " Command ",
+    };
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+
+    let hint = match input_type {
+        InputType::ContainerName => "Container names must be alphanumeric with dashes allowed",
+        InputType::ImageName => "Enter image name (e.g., ubuntu:22.04)",
+        InputType::Url => "e.g. lxd.example.com or https://10.0.0.5:8443",
+        InputType::Token => "Generate one on the server with 'lxc config trust add'",
+        InputType::Command => "e.g. start web1, snapshot db1 pre-upgrade, filter status=Running",
+        InputType::Tags => "e.g. web, prod; leave empty to remove all tags",
+        InputType::Number => "A whole number; 0 is the default",
+        InputType::FileName => "e.g. motd.tpl; list it in the metadata to render it",
+        InputType::DomainName => "A DNS name; networks publish into it by dns.zone.forward",
+        InputType::ZoneEntries => "Separate entries with commas: A 10.0.0.5, AAAA fd42::5",
+        InputType::Media => "e.g. /var/lib/isos/debian-12.iso or default/debian-12",
+        InputType::CpuSet => "4 CPUs, or 0-3,8 to pin; leave empty for the default",
+        InputType::IdmapEntry => "both 1000 1000, or uid 50-59 500-509 (host, then container)",
+        InputType::ConfigKey => "Keys set by profiles count; volatile.* only when named",
+        InputType::Path => "A YAML or JSON file written by Export Inventory",
+    };
+
+    let content = vec![
+        Line::from(""),
+        Line::from(prompt),
+        Line::from(""),
+        Line::from(field_spans(input, Style::default())),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            hint,
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        )]),
+    ];
+
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_backups(frame: &mut Frame, container: &str, confirm_delete: bool, app: &App) {
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Backups: {} ", container))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Header
+            Constraint::Min(1),    // Backup list
+            Constraint::Length(1), // Status / confirmation
+        ])
+        .split(inner);
+
+    let header_style = Style::default()
+        .add_modifier(Modifier::BOLD)
+        .fg(Color::Cyan);
+    let header = Line::from(vec![
+        Span::styled(format!("{:20} ", "Name"), header_style),
+        Span::styled(format!("{:20} ", "Created"), header_style),
+        Span::styled("Expires", header_style),
+    ]);
+    frame.render_widget(Paragraph::new(header), chunks[0]);
+
+    if app.backups.is_empty() {
+        let empty = Paragraph::new("No backups yet. Press n to create one.")
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center);
+        frame.render_widget(empty, chunks[1]);
+    } else {
+        let items: Vec<ListItem> = app
+            .backups
+            .iter()
+            .enumerate()
+            .map(|(i, backup)| {
+                let content = Line::from(vec![
+                    Span::raw(format!("{:20} ", backup.name)),
+                    Span::raw(format!("{:20} ", backup.created_at)),
+                    Span::styled(
+                        backup.expires_at.as_deref().unwrap_or("never"),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]);
+                if i == app.backup_selected {
+                    ListItem::new(content).style(
+                        Style::default()
+                            .bg(Color::DarkGray)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    ListItem::new(content)
+                }
+            })
+            .collect();
+
+        frame.render_stateful_widget(
+            List::new(items).style(Style::default().fg(Color::White)),
+            chunks[1],
+            &mut ListState::default().with_selected(Some(app.backup_selected)),
+        );
+    }
+
+    let footer = if confirm_delete {
+        let name = app
+            .backups
+            .get(app.backup_selected)
+            .map(|b| b.name.as_str())
+            .unwrap_or_default();
+        Line::from(Span::styled(
+            format!("Delete backup '{}'? [y] Yes  [any key] No", name),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))
+    } else {
+        Line::from(Span::styled(
+            app.message.clone().unwrap_or_default(),
+            Style::default().fg(Color::Green),
+        ))
+    };
+    frame.render_widget(Paragraph::new(footer), chunks[2]);
+}
+
+/// The pool a create or clone lands in would get too full
+fn draw_space_warning(frame: &mut Frame, message: &str) {
+    let area = centered_rect(60, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Low Disk Space ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .border_type(BorderType::Rounded);
+
+    let content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            message.to_string(),
+            Style::default().fg(Color::Yellow),
+        )),
+        Line::from(""),
+        Line::from("A copy that runs out of room can leave the pool full."),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Press ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Enter/Y",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" to go ahead or ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Esc/N",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" to cancel", Style::default().fg(Color::White)),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_preview(frame: &mut Frame, action: &PendingAction, app: &App) {
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let (remote, request) = app.api_request(action);
+
+    let block = Block::default()
+        .title(" API Preview ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta))
+        .border_type(BorderType::Rounded);
+
+    let mut content = vec![
+        Line::from(vec![
+            Span::styled("Remote: ", Style::default().fg(Color::DarkGray)),
+            Span::raw(remote),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                format!("{} ", request.method),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(request.path.clone(), Style::default().fg(Color::White)),
+        ]),
+        Line::from(""),
+    ];
+
+    match &request.body {
+        Some(body) => {
+            let pretty = serde_json::to_string_pretty(body).unwrap_or_default();
+            content.extend(pretty.lines().map(|line| {
+                Line::from(Span::styled(
+                    line.to_string(),
+                    Style::default().fg(Color::Cyan),
+                ))
+            }));
+        }
+        None => content.push(Line::from(Span::styled(
+            "(no request body)",
+            Style::default().fg(Color::DarkGray),
+        ))),
+    }
+
+    if matches!(action, PendingAction::CreateContainer) {
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(
+            "The new instance is started with a follow-up PUT to its state endpoint.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    frame.render_widget(Paragraph::new(content).block(block), area);
+}
+
+/// Checkboxes and pickers for how a clone differs from its source
+fn draw_clone_options(frame: &mut Frame, form: &CloneForm, app: &App) {
+    let area = centered_rect(60, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(
+            " Clone '{}' to '{}' ",
+            form.source, form.destination
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+
+    let checkbox = |checked: bool| if checked { "[x]" } else { "[ ]" };
+    let picker = |choice: &Option<String>, choices: &[String]| match (choice, choices.is_empty()) {
+        (Some(choice), _) => format!("◀ {} ▶", choice),
+        (None, false) => "◀ same as source ▶".to_string(),
+        (None, true) => "same as source (none listed)".to_string(),
+    };
+
+    let options = &form.options;
+    let remote = app.remote_of(&form.source);
+    let mut content = vec![Line::from("")];
+    for (index, field) in clone::FIELDS.iter().enumerate() {
+        let (label, value) = match field {
+            Field::InstanceOnly => (
+                "Copy without snapshots",
+                checkbox(options.instance_only).to_string(),
+            ),
+            Field::Ephemeral => (
+                "Ephemeral (deleted when stopped)",
+                checkbox(options.ephemeral).to_string(),
+            ),
+            Field::Pool => ("Storage pool", picker(&options.pool, &form.pools)),
+            Field::Project => ("Project", picker(&options.project, &form.projects)),
+        };
+        let selected = index == form.field;
+        let (arrow, style) = if selected {
+            (
+                " ▶ ",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            ("   ", Style::default().fg(Color::White))
+        };
+        // A project with an accent shows it, so copies into prod stand out
+        let accent = match (field, &options.project) {
+            (Field::Project, Some(project)) => app.accents.of(remote, project),
+            _ => None,
+        };
+        content.push(Line::from(vec![
+            Span::styled(arrow, style),
+            Span::styled(format!("{:<34}", label), style),
+            Span::styled(
+                value,
+                accent.map_or(Style::default().fg(Color::Yellow), accent_badge),
+            ),
+        ]));
+        content.push(Line::from(""));
+    }
+
+    frame.render_widget(Paragraph::new(content).block(block), area);
+}
+
+fn draw_help(frame: &mut Frame, query: &str, searching: bool, app: &App) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Help - Keyboard Shortcuts ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Search bar
+            Constraint::Min(1),    // Bindings
+        ])
+        .split(inner);
+
+    let search = if searching {
+        Line::from(vec![
+            Span::styled(" Search: ", Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{}_", query)),
+        ])
+    } else if query.is_empty() {
+        Line::from(Span::styled(
+            " Press / to search",
+            Style::default().fg(Color::DarkGray),
+        ))
+    } else {
+        Line::from(vec![
+            Span::styled(" Filter: ", Style::default().fg(Color::DarkGray)),
+            Span::raw(query.to_string()),
+        ])
+    };
+    frame.render_widget(Paragraph::new(search), chunks[0]);
+
+    let mut lines = Vec::new();
+    for (group, bindings) in keymap::search(query) {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", group.title),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        for binding in bindings {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("   {:<14}", binding.keys),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(
+                    format!("{:<20}", binding.action),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(binding.description, Style::default().fg(Color::DarkGray)),
+            ]));
+        }
+        lines.push(Line::from(""));
+    }
+
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No key bindings match your search",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).scroll((app.help_scroll, 0)),
+        chunks[1],
+    );
+}
+
+/// Internal state for diagnosing problems without restarting with RUST_LOG
+fn draw_debug_overlay(frame: &mut Frame, app: &App) {
+    let full = frame.area();
+    let area = Rect {
+        x: full.x + full.width / 2,
+        y: full.y,
+        width: full.width - full.width / 2,
+        height: full.height,
+    };
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Debug (F12) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let label = Style::default().fg(Color::Magenta);
+    let pending: Vec<&UserOperation> = app
+        .user_operations
+        .iter()
+        .filter(|op| {
+            matches!(
+                op.status,
+                OperationStatus::Registered
+                    | OperationStatus::Running
+                    | OperationStatus::Retrying(_)
+            )
+        })
+        .collect();
+
+    // Wrap the mode by hand so the remaining space for log lines is known
+    let mode = format!("{:?}", app.input_mode);
+    let mode: Vec<char> = mode.chars().collect();
+    let mut lines = vec![Line::from(Span::styled("Mode:", label))];
+    for chunk in mode.chunks(inner.width.max(1) as usize) {
+        lines.push(Line::from(chunk.iter().collect::<String>()));
+    }
+
+    lines.extend([
+        Line::from(vec![
+            Span::styled("Operations: ", label),
+            Span::raw(format!(
+                "{} pending, {} LXD tracked, {} background tasks",
+                pending.len(),
+                app.lxd_operations.len(),
+                app.background_tasks.len()
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled("Queues: ", label),
+            Span::raw(format!(
+                "task results {}, schedule results {}, OIDC login {}",
+                app.task_result_rx.len(),
+                app.schedule_rx.len(),
+                if app.oidc_login.is_some() {
+                    "waiting"
+                } else {
+                    "idle"
+                }
+            )),
+        ]),
+    ]);
+
+    for op in &pending {
+        let elapsed = op.started_at.map_or(0, |t| t.elapsed().as_secs());
+        lines.push(Line::from(format!(
+            "  {} ({:?}, {}s)",
+            op.description, op.status, elapsed
+        )));
+    }
+    for tracker in app.lxd_operations.values() {
+        lines.push(Line::from(format!(
+            "  {} {} -> {} (status {})",
+            tracker.action, tracker.container_name, tracker.lxd_operation_path, tracker.status_code
+        )));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Recent log:", label)));
+
+    let header_height = (lines.len() as u16).min(inner.height);
+    frame.render_widget(
+        Paragraph::new(lines),
+        Rect {
+            height: header_height,
+            ..inner
+        },
+    );
+
+    let log_area = Rect {
+        y: inner.y + header_height,
+        height: inner.height - header_height,
+        ..inner
+    };
+    let recent = logging::recent_lines();
+    let log_lines: Vec<Line> = recent
+        .iter()
+        .skip(recent.len().saturating_sub(log_area.height as usize))
+        .map(|line| {
+            Line::from(Span::styled(
+                line.as_str(),
+                Style::default().fg(Color::Gray),
+            ))
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(log_lines), log_area);
+}
+
+const LOG_VIEW_PERCENT: u16 = 90;
+
+/// Number of log lines visible in the log view on a terminal `height` rows tall
+pub fn log_page_height(height: u16) -> u16 {
+    modal_extent(height, LOG_VIEW_PERCENT, MIN_MODAL_HEIGHT).saturating_sub(2)
+}
+
+fn draw_log(frame: &mut Frame, app: &App) {
+    let area = centered_rect(LOG_VIEW_PERCENT, LOG_VIEW_PERCENT, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = match logging::log_path() {
+        Some(path) => format!(" Log - {} ", path.display()),
+        None => " Log ".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let max = app.log_lines.len().saturating_sub(inner.height as usize);
+    let scroll = app
+        .log_scroll
+        .map_or(max, |scroll| (scroll as usize).min(max));
+
+    let lines: Vec<Line> = if app.log_lines.is_empty() {
+        vec![Line::from(Span::styled(
+            " The log is empty",
+            Style::default().fg(Color::DarkGray),
+        ))]
+    } else {
+        app.log_lines
+            .iter()
+            .skip(scroll)
+            .take(inner.height as usize)
+            .map(|line| {
+                let color = match line.split_whitespace().nth(2) {
+                    Some("ERROR") => Color::Red,
+                    Some("WARN") => Color::Yellow,
+                    Some("DEBUG") | Some("TRACE") => Color::DarkGray,
+                    _ => Color::White,
+                };
+                Line::from(Span::styled(line.as_str(), Style::default().fg(color)))
+            })
+            .collect()
+    };
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_console(frame: &mut Frame, console: &ConsoleView) {
+    let area = centered_rect(LOG_VIEW_PERCENT, LOG_VIEW_PERCENT, frame.area());
+    frame.render_widget(Clear, area);
+
+    let state = match &console.captured {
+        Some((status, at)) => format!(
+            "captured when {} at {}",
+            status.to_lowercase(),
+            at.format("%Y-%m-%d %H:%M:%S")
+        ),
+        None if console.paused => "paused".to_string(),
+        None if console.scroll.is_some() => "scrolled".to_string(),
+        None => "following".to_string(),
+    };
+    let mut block = Block::default()
+        .title(format!(" Console: {} ({}) ", console.container, state))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    if console.searching {
+        block = block.title_bottom(format!(" /{}_ ", console.query));
+    } else if console.no_match {
+        block = block.title_bottom(Line::styled(
+            format!(" No match for '{}' ", console.query),
+            Style::default().fg(Color::Yellow),
+        ));
+    } else if let Some(error) = &console.error {
+        block = block.title_bottom(Line::styled(
+            format!(" {} ", error),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if console.lines.is_empty() {
+        let text = if console.error.is_some() || console.last_poll.is_some() {
+            " The console log is empty"
+        } else {
+            " Loading..."
+        };
+        frame.render_widget(
+            Paragraph::new(Span::styled(text, Style::default().fg(Color::DarkGray))),
+            inner,
+        );
+        return;
+    }
+
+    let page = inner.height as usize;
+    let query = console.query.to_lowercase();
+    let lines: Vec<Line> = console
+        .lines
+        .iter()
+        .skip(console.top(page))
+        .take(page)
+        .map(|line| highlight(line, &query))
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// `line` with case-insensitive matches of `query` highlighted
+fn highlight<'a>(line: &'a str, query: &str) -> Line<'a> {
+    let lower = line.to_lowercase();
+    // Lowercasing can change byte lengths; only highlight when it doesn't
+    if query.is_empty() || lower.len() != line.len() {
+        return Line::from(line);
+    }
+
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (index, matched) in lower.match_indices(query) {
+        spans.push(Span::raw(&line[start..index]));
+        spans.push(Span::styled(
+            &line[index..index + matched.len()],
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ));
+        start = index + matched.len();
+    }
+    spans.push(Span::raw(&line[start..]));
+    Line::from(spans)
+}
+
+/// Series from the metrics endpoint the state API lacks
+fn metrics_lines(metrics: &InstanceMetrics, label: Style) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    let cpu: Vec<String> = metrics
+        .cpu_seconds
+        .iter()
+        .filter(|(_, seconds)| **seconds > 0.0)
+        .map(|(mode, seconds)| format!("{} {:.1}s", mode, seconds))
+        .collect();
+    if !cpu.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled(" CPU time   ", label),
+            Span::raw(cpu.join("  ")),
+        ]));
+    }
+
+    let mut memory = Vec::new();
+    if let Some(total) = metrics.memory_total {
+        memory.push(format!("{} total", watch::format_bytes(total)));
+    }
+    if let Some(available) = metrics.memory_available {
+        memory.push(format!("{} available", watch::format_bytes(available)));
+    }
+    if let Some(cached) = metrics.memory_cached {
+        memory.push(format!("{} cached", watch::format_bytes(cached)));
+    }
+    if let Some(kills) = metrics.oom_kills.filter(|kills| *kills > 0.0) {
+        memory.push(format!("{} OOM kills", kills));
+    }
+    if !memory.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled(" Memory     ", label),
+            Span::raw(memory.join(", ")),
+        ]));
+    }
+
+    for filesystem in &metrics.filesystems {
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {:<11}", filesystem.mountpoint), label),
+            Span::raw(format!(
+                "{} free of {}",
+                watch::format_bytes(filesystem.available),
+                watch::format_bytes(filesystem.size)
+            )),
+        ]));
+    }
+
+    for disk in &metrics.disk_io {
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {:<11}", disk.device), label),
+            Span::raw(format!(
+                "{} read, {} written",
+                watch::format_bytes(disk.read),
+                watch::format_bytes(disk.written)
+            )),
+        ]));
+    }
+
+    lines
+}
+
+/// How the watched instance's week went, and a strip of each part of it
+fn uptime_lines(availability: &Availability, slots: &[Slot], theme: Theme) -> Vec<Line<'static>> {
+    let label = Style::default().fg(Color::DarkGray);
+    let tone = match availability.percent {
+        p if p >= 99.0 => Tone::Good,
+        p if p >= 90.0 => Tone::Warning,
+        _ => Tone::Bad,
+    };
+    let span = match availability.since {
+        Some(since) if since.date_naive() == chrono::Local::now().date_naive() => {
+            format!("since {}", since.format("%H:%M"))
+        }
+        Some(since) => format!("since {}", since.format("%a %H:%M")),
+        None => "this week".to_string(),
+    };
+    let restarts = if availability.restarts == 1 {
+        "restart"
+    } else {
+        "restarts"
+    };
+    let mut strip = vec![Span::raw("            ")];
+    strip.extend(slots.iter().map(|slot| match slot {
+        Slot::Up => Span::styled("█", theme.style(Tone::Good)),
+        Slot::Partly => Span::styled("▄", theme.style(Tone::Warning)),
+        Slot::Down => Span::styled("▁", theme.style(Tone::Bad)),
+        Slot::Unknown => Span::styled("·", label),
+    }));
+    strip.push(Span::styled(
+        format!("  last {} days", uptime::WINDOW.num_days()),
+        label,
+    ));
+    vec![
+        Line::from(vec![
+            Span::styled(" Uptime     ", label),
+            Span::styled(
+                format!("up {:.1}%", availability.percent),
+                theme.style(tone),
+            ),
+            Span::raw(format!(" {}, {} {}", span, availability.restarts, restarts)),
+        ]),
+        Line::from(strip),
+    ]
+}
+
+/// Restart policy of a watched instance and how often it has been used
+fn restart_line(always: bool, record: Option<&restart::Record>, theme: Theme) -> Line<'static> {
+    let label = Style::default().fg(Color::DarkGray);
+    let mut spans = vec![
+        Span::styled(" Restart    ", label),
+        Span::raw(if always { "always" } else { "never" }),
+    ];
+    match record {
+        Some(record) if record.gave_up => spans.push(Span::styled(
+            format!(
+                "   gave up after {} crashes in {} minutes",
+                restart::MAX_RESTARTS,
+                restart::WINDOW.as_secs() / 60
+            ),
+            theme.style(Tone::Bad),
+        )),
+        Some(record) => {
+            let times = if record.count == 1 { "time" } else { "times" };
+            spans.push(Span::styled(
+                format!(
+                    "   restarted {} {}, last at {}",
+                    record.count,
+                    times,
+                    record.last.format("%H:%M:%S")
+                ),
+                theme.style(Tone::Warning),
+            ));
+        }
+        None => spans.push(Span::styled("   not restarted yet", label)),
+    }
+    if let Some(error) = record.and_then(|r| r.error.as_ref()) {
+        spans.push(Span::styled(
+            format!("   failed: {}", error),
+            theme.style(Tone::Bad),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Parts of the week in the watch view's uptime strip, six hours each
+const UPTIME_SLOTS: u32 = 28;
+
+/// Spans of a run of notes text, on top of `base`
+fn note_spans(runs: Vec<Inline>, base: Style) -> Vec<Span<'static>> {
+    runs.into_iter()
+        .map(|run| match run {
+            Inline::Text(text) => Span::styled(text, base),
+            Inline::Bold(text) => Span::styled(text, base.add_modifier(Modifier::BOLD)),
+            Inline::Code(text) => Span::styled(text, base.fg(Color::Yellow)),
+        })
+        .collect()
+}
+
+/// An instance's notes, with their Markdown rendered
+fn notes_lines(text: &str, label: Style) -> Vec<Line<'static>> {
+    notes::parse(text)
+        .into_iter()
+        .map(|line| {
+            let (prefix, runs, base) = match line {
+                NoteLine::Heading(runs) => (
+                    Span::raw(" "),
+                    runs,
+                    Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                ),
+                NoteLine::Bullet { indent, text } => (
+                    Span::raw(format!(" {}• ", "  ".repeat(indent))),
+                    text,
+                    Style::default(),
+                ),
+                NoteLine::Quote(runs) => (
+                    Span::styled(" │ ", label),
+                    runs,
+                    Style::default().add_modifier(Modifier::ITALIC),
+                ),
+                NoteLine::Code(code) => {
+                    return Line::from(vec![
+                        Span::raw("   "),
+                        Span::styled(code, Style::default().fg(Color::Yellow)),
+                    ])
+                }
+                NoteLine::Text(runs) => (Span::raw(" "), runs, Style::default()),
+                NoteLine::Blank => return Line::from(""),
+            };
+            let mut spans = vec![prefix];
+            spans.extend(note_spans(runs, base));
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn draw_watch(
+    frame: &mut Frame,
+    watch: &Watch,
+    theme: Theme,
+    metrics_unavailable: bool,
+    probe: Option<&Probe>,
+    restarts: Option<&restart::Record>,
+    uptime: Option<(Availability, Vec<Slot>)>,
+) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Watch: {} ", watch.container))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let label = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![Line::from("")];
+
+    match &watch.state {
+        Some(state) => {
+            let tone = Tone::of_status(&state.status);
+            lines.push(Line::from(vec![
+                Span::styled(" Status     ", label),
+                Span::styled(theme.label(tone, &state.status), theme.style(tone)),
+                Span::styled("   PID ", label),
+                Span::raw(state.pid.to_string()),
+                Span::styled("   Processes ", label),
+                Span::raw(state.processes.to_string()),
+            ]));
+            if let Some(image) = &watch.image {
+                lines.push(Line::from(vec![
+                    Span::styled(" Image      ", label),
+                    Span::raw(image.clone()),
+                ]));
+            }
+            if watch.restart || restarts.is_some() {
+                lines.push(restart_line(watch.restart, restarts, theme));
+            }
+            if let Some((availability, slots)) = uptime {
+                lines.extend(uptime_lines(&availability, &slots, theme));
+            }
+
+            let cpu = watch
+                .cpu_percent
+                .map_or("-".to_string(), |cpu| format!("{:.1}%", cpu));
+            let memory = state.memory.as_ref().map_or("-".to_string(), |m| {
+                format!(
+                    "{} (peak {})",
+                    watch::format_bytes(m.usage as f64),
+                    watch::format_bytes(m.usage_peak as f64)
+                )
+            });
+            lines.push(Line::from(vec![
+                Span::styled(" CPU        ", label),
+                Span::raw(format!("{:<10}", cpu)),
+                Span::styled("Memory ", label),
+                Span::raw(memory),
+            ]));
+
+            let mut disks: Vec<String> = state
+                .disk
+                .iter()
+                .flatten()
+                .map(|(name, disk)| format!("{} {}", name, watch::format_bytes(disk.usage as f64)))
+                .collect();
+            disks.sort();
+            if !disks.is_empty() {
+                lines.push(Line::from(vec![
+                    Span::styled(" Disk       ", label),
+                    Span::raw(disks.join(", ")),
+                ]));
+            }
+
+            let mut interfaces: Vec<_> = state.network.iter().flatten().collect();
+            interfaces.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, interface) in interfaces.into_iter().filter(|(name, _)| *name != "lo") {
+                let addresses: Vec<&str> = interface
+                    .addresses
+                    .iter()
+                    .filter(|a| a.scope == "global")
+                    .map(|a| a.address.as_str())
+                    .collect();
+                let mut spans = vec![
+                    Span::styled(format!(" {:<11}", name), label),
+                    Span::raw(if addresses.is_empty() {
+                        "-".to_string()
+                    } else {
+                        addresses.join(", ")
+                    }),
+                ];
+                if let Some((_, rate)) = watch.throughput.iter().find(|(n, _)| n == name) {
+                    spans.push(Span::styled(
+                        format!(
+                            "   ↓ {}/s ↑ {}/s",
+                            watch::format_bytes(rate.rx),
+                            watch::format_bytes(rate.tx)
+                        ),
+                        Style::default().fg(Color::Cyan),
+                    ));
+                }
+                lines.push(Line::from(spans));
+            }
+            if !watch.dns_names.is_empty() {
+                lines.push(Line::from(vec![
+                    Span::styled(" DNS        ", label),
+                    Span::raw(watch.dns_names.join(", ")),
+                ]));
+            }
+            for (i, port) in watch.ports.iter().enumerate() {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        if i == 0 {
+                            " Ports      "
+                        } else {
+                            "            "
+                        },
+                        label,
+                    ),
+                    Span::raw(port.to_string()),
+                ]));
+            }
+        }
+        None => lines.push(Line::from(Span::styled(" Loading...", label))),
+    }
+
+    if let Some(error) = &watch.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    if let Some(notes) = &watch.notes {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            " Notes",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(notes_lines(notes, label));
+    }
+
+    if let Some(command) = &watch.healthcheck {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            " Health",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        let mut spans = vec![Span::styled(" Probe      ", label)];
+        match probe {
+            Some(probe) => {
+                let tone = probe.health.tone();
+                spans.push(Span::styled(
+                    theme.label(tone, probe.health.label()),
+                    theme.style(tone),
+                ));
+                spans.push(Span::styled(
+                    format!("   {}s ago", probe.at.elapsed().as_secs()),
+                    label,
+                ));
+            }
+            None => spans.push(Span::raw("Not run yet")),
+        }
+        spans.push(Span::styled(format!("   {}", command), label));
+        lines.push(Line::from(spans));
+        for line in probe.iter().flat_map(|probe| probe.output.lines()) {
+            lines.push(Line::from(Span::raw(format!("            {}", line))));
+        }
+    }
+
+    if watch.metrics.is_some() || watch.metrics_error.is_some() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            " Metrics",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+    }
+    if let Some(metrics) = &watch.metrics {
+        lines.extend(metrics_lines(metrics, label));
+    }
+    if let Some(error) = &watch.metrics_error {
+        // Greyed out once the server is known not to offer metrics
+        let colour = if metrics_unavailable {
+            Color::DarkGray
+        } else {
+            Color::Red
+        };
+        lines.push(Line::from(Span::styled(
+            format!(" Metrics unavailable: {}", error),
+            Style::default().fg(colour),
+        )));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        " Events",
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )));
+
+    // Newest events last, keeping the most recent ones in view
+    let room = (inner.height as usize).saturating_sub(lines.len());
+    let skip = watch.events.len().saturating_sub(room);
+    for event in watch.events.iter().skip(skip) {
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {} ", event.at.format("%H:%M:%S")), label),
+            Span::raw(event.message.clone()),
+        ]));
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_top(frame: &mut Frame, top: &Top) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(
+            " Top: running containers by {} ",
+            top.rank_by.label()
+        ))
+        .title_bottom(
+            Line::from(format!(
+                " averaged over {} sample{} ({}s apart) ",
+                top.window,
+                if top.window == 1 { "" } else { "s" },
+                top::POLL_INTERVAL.as_secs()
+            ))
+            .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let heading = |text: &str, rank_by: Option<RankBy>| {
+        let style = if rank_by == Some(top.rank_by) {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+        } else {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        };
+        Span::styled(text.to_string(), style)
+    };
+    let mut lines = vec![Line::from(vec![
+        heading(" #   ", None),
+        heading(&format!("{:<28}", "Name"), None),
+        heading(&format!("{:>8}", "CPU"), Some(RankBy::Cpu)),
+        Span::raw("  "),
+        heading(&format!("{:>12}", "Memory"), Some(RankBy::Memory)),
+        Span::raw("  "),
+        heading(&format!("{:>14}", "Network"), Some(RankBy::Network)),
+    ])];
+
+    if let Some(error) = &top.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let ranking = top.ranking();
+    if ranking.is_empty() && top.error.is_none() {
+        lines.push(Line::from(Span::styled(
+            " Sampling...",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for (i, ranked) in ranking.iter().enumerate() {
+        let name: String = ranked.name.chars().take(27).collect();
+        let style = if i == 0 {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(" {:<4}", i + 1),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(format!("{:<28}", name), style),
+            Span::styled(format!("{:>7.1}%", ranked.usage.cpu_percent), style),
+            Span::raw("  "),
+            Span::styled(
+                format!("{:>12}", watch::format_bytes(ranked.usage.memory)),
+                style,
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("{:>12}/s", watch::format_bytes(ranked.usage.network)),
+                style,
+            ),
+        ]));
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Width of the bar drawn for each storage pool
+const POOL_BAR_WIDTH: usize = 10;
+
+fn draw_dashboard(frame: &mut Frame, area: Rect, app: &App, dashboard: &Dashboard) {
+    let server = if app.all_remotes {
+        "all remotes"
+    } else {
+        app.active_remote.as_deref().unwrap_or("local")
+    };
+    let block = Block::default()
+        .title(format!(" Host at a glance: {} ", server))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(inner);
+    let heading = |text: String| {
+        Line::styled(
+            text,
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    let quiet =
+        |text: &str| Line::styled(format!("   {}", text), Style::default().fg(Color::DarkGray));
+    let theme = app.theme;
+
+    // Instances, warnings and events on the left
+    let counts = app
+        .containers
+        .try_read()
+        .map(|containers| Counts::of(&containers))
+        .unwrap_or_default();
+    let mut states = vec![
+        Span::raw("   "),
+        Span::styled(
+            theme.label(Tone::Good, &format!("{} running", counts.running)),
+            theme.style(Tone::Good),
+        ),
+        Span::raw("   "),
+        Span::styled(
+            theme.label(Tone::Bad, &format!("{} stopped", counts.stopped)),
+            theme.style(Tone::Bad),
+        ),
+    ];
+    if counts.other > 0 {
+        states.push(Span::raw("   "));
+        states.push(Span::styled(
+            theme.label(Tone::Warning, &format!("{} other", counts.other)),
+            theme.style(Tone::Warning),
+        ));
+    }
+    let mut left = vec![heading(" Instances".to_string()), Line::from(states)];
+
+    let warnings = dashboard::warnings(app);
+    left.push(Line::raw(""));
+    left.push(heading(format!(" Warnings ({})", warnings.len())));
+    if warnings.is_empty() {
+        left.push(quiet("None"));
+    }
+    for warning in &warnings {
+        left.push(Line::styled(
+            format!("   {}", theme.label(Tone::Warning, warning)),
+            theme.style(Tone::Warning),
+        ));
+    }
+
+    left.push(Line::raw(""));
+    left.push(heading(" Recent events".to_string()));
+    if app.events.events.is_empty() {
+        left.push(quiet("Waiting for events..."));
+    }
+    for event in app
+        .events
+        .events
+        .iter()
+        .rev()
+        .take(dashboard::RECENT_EVENTS)
+    {
+        let entity = match &event.remote {
+            Some(remote) => format!("{}:{}", remote, event.entity),
+            None => event.entity.clone(),
+        };
+        left.push(Line::from(vec![
+            Span::styled(
+                event.at.format("   %H:%M:%S  ").to_string(),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(event.action.clone(), theme.style(event_tone(&event.action))),
+            Span::raw(format!(" {}", entity)),
+        ]));
+    }
+    frame.render_widget(Paragraph::new(left), columns[0]);
+
+    // Usage on the right
+    let mut right = Vec::new();
+    match &app.top {
+        Some(top) => {
+            right.push(heading(format!(
+                " Top consumers by {}",
+                top.rank_by.label()
+            )));
+            let ranking = top.ranking();
+            if let Some(error) = &top.error {
+                right.push(Line::styled(
+                    format!("   {}", error),
+                    Style::default().fg(Color::Red),
+                ));
+            } else if ranking.is_empty() {
+                right.push(quiet("Sampling..."));
+            }
+            for ranked in ranking.iter().take(dashboard::TOP_CONSUMERS) {
+                let name: String = ranked.name.chars().take(20).collect();
+                right.push(Line::raw(format!(
+                    "   {:<20} {:>6.1}%  {:>10}",
+                    name,
+                    ranked.usage.cpu_percent,
+                    watch::format_bytes(ranked.usage.memory)
+                )));
+            }
+        }
+        None => right.push(heading(" Top consumers".to_string())),
+    }
+
+    right.push(Line::raw(""));
+    right.push(heading(format!(
+        " Storage pools on {}",
+        app.active_remote.as_deref().unwrap_or("local")
+    )));
+    if let Some(error) = &dashboard.error {
+        right.push(Line::styled(
+            format!("   {}", error),
+            Style::default().fg(Color::Red),
+        ));
+    } else if dashboard.pools.is_empty() {
+        right.push(quiet("None"));
+    }
+    for pool in &dashboard.pools {
+        right.push(pool_line(pool, theme));
+    }
+    frame.render_widget(Paragraph::new(right), columns[1]);
+}
+
+/// A storage pool's name and driver with a bar of the space used and
+/// what is left
+fn pool_line(pool: &PoolUsage, theme: Theme) -> Line<'static> {
+    let name = format!("   {:<16} ", format!("{} ({})", pool.name, pool.driver));
+    let Some(fraction) = pool.fraction() else {
+        return Line::from(vec![
+            Span::raw(name),
+            Span::styled("size unknown", Style::default().fg(Color::DarkGray)),
+        ]);
+    };
+    let tone = if fraction >= 0.9 {
+        Tone::Bad
+    } else if fraction >= 0.75 {
+        Tone::Warning
+    } else {
+        Tone::Good
+    };
+    let filled = ((fraction * POOL_BAR_WIDTH as f64).round() as usize).min(POOL_BAR_WIDTH);
+    Line::from(vec![
+        Span::raw(name),
+        Span::styled("█".repeat(filled), theme.style(tone)),
+        Span::styled(
+            "░".repeat(POOL_BAR_WIDTH - filled),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(format!(" {:>3.0}%", fraction * 100.0), theme.style(tone)),
+        Span::raw(format!(
+            "  {} free",
+            watch::format_bytes(pool.total.saturating_sub(pool.used) as f64)
+        )),
+    ])
+}
+
+fn draw_boot_order(frame: &mut Frame, order: &BootOrder, server: &str, theme: Theme) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Boot order on {} ", server))
+        .title_bottom(Line::from(format!(" sorted by {} ", order.sort.label())).right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            " {:<4}{:<24}{:<12}{:>9}{:>8}{:>12}",
+            "#", "Name", "Autostart", "Priority", "Delay", "Starts at"
+        ),
+        heading,
+    ))];
+
+    if let Some(error) = &order.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    } else if order.entries.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No containers",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    // Keep the selection in view below the heading
+    let rows = (inner.height as usize).saturating_sub(lines.len()).max(1);
+    let skip = order.selected.saturating_sub(rows - 1);
+    for (i, entry) in order.entries.iter().enumerate().skip(skip).take(rows) {
+        let name: String = entry.name.chars().take(23).collect();
+        let (position, starts_at) = match order.start_position(&entry.name) {
+            Some((position, after)) => (position.to_string(), format!("+{}s", after)),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        let autostart_style = match entry.autostart {
+            Autostart::Always => theme.style(Tone::Good),
+            Autostart::Never => Style::default().fg(Color::DarkGray),
+            Autostart::LastState => Style::default(),
+        };
+        let mut line = Line::from(vec![
+            Span::styled(
+                format!(" {:<4}", position),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw(format!("{:<24}", name)),
+            Span::styled(format!("{:<12}", entry.autostart.label()), autostart_style),
+            Span::raw(format!(
+                "{:>9}{:>7}s{:>12}",
+                entry.priority, entry.delay, starts_at
+            )),
+        ]);
+        if i == order.selected {
+            line = line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        lines.push(line);
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_audit(frame: &mut Frame, audit: &Audit, confirm_rebuild: bool, server: &str) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut block = Block::default()
+        .title(format!(" Outdated images on {} ", server))
+        .title_bottom(
+            Line::from(format!(
+                " {} outdated, {} current, {} unknown ",
+                audit.entries.len(),
+                audit.current,
+                audit.unknown
+            ))
+            .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    if let Some(status) = &audit.status {
+        block = block.title_bottom(Line::from(format!(" {} ", status)));
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            " {:<16}{:<22}{:<14}{:<14}{}",
+            "Name", "Image", "Current", "Latest", "Built"
+        ),
+        heading,
+    ))];
+
+    if let Some(error) = &audit.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    } else if audit.entries.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " Every instance is on the latest image in the store",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    // Keep the selection in view below the heading and above the prompt
+    let rows = (inner.height as usize)
+        .saturating_sub(lines.len() + 2)
+        .max(1);
+    let skip = audit.selected.saturating_sub(rows - 1);
+    for (i, entry) in audit.entries.iter().enumerate().skip(skip).take(rows) {
+        let Freshness::Outdated {
+            fingerprint,
+            created_at,
+        } = &entry.freshness
+        else {
+            continue;
+        };
+        let name: String = entry.name.chars().take(15).collect();
+        let image: String = entry.image.chars().take(21).collect();
+        let current: String = entry.base_image.chars().take(12).collect();
+        let latest: String = fingerprint.chars().take(12).collect();
+        let built: String = created_at.chars().take(10).collect();
+        let mut line = Line::from(vec![
+            Span::raw(format!(" {:<16}{:<22}", name, image)),
+            Span::styled(
+                format!("{:<14}", current),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw(format!("{:<14}{}", latest, built)),
+        ]);
+        if i == audit.selected {
+            line = line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        lines.push(line);
+    }
+
+    if confirm_rebuild {
+        if let Some(entry) = audit.selected_entry() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!(
+                    " Replace the root disk of '{}' with the latest image? [y] Yes  [any key] No",
+                    entry.name
+                ),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )));
+        }
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_image_store(
+    frame: &mut Frame,
+    view: &ImageStore,
+    server: &str,
+    warning: chrono::TimeDelta,
+) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let updating = view.images.iter().filter(|i| i.auto_update).count();
+    let mut block = Block::default()
+        .title(format!(" Images on {} ", server))
+        .title_bottom(
+            Line::from(format!(
+                " {} images, {} auto-updating ",
+                view.images.len(),
+                updating
+            ))
+            .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    if let Some(status) = &view.status {
+        block = block.title_bottom(Line::from(format!(" {} ", status)));
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            " {:<21}{:<18}{:<6}{:<11}{:<10}{}",
+            "Image", "Source", "Auto", "Size", "Expires", "Built"
+        ),
+        heading,
+    ))];
+
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    } else if view.images.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No images are stored on this server",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let now = chrono::Local::now();
+    let rows = (inner.height as usize).saturating_sub(lines.len()).max(1);
+    let skip = view.selected.saturating_sub(rows - 1);
+    for (i, image) in view.images.iter().enumerate().skip(skip).take(rows) {
+        let description: String = image.description.chars().take(20).collect();
+        let (source, auto) = match &image.source {
+            Some(source) => (
+                source.chars().take(17).collect(),
+                if image.auto_update {
+                    Span::styled(format!("{:<6}", "on"), Style::default().fg(Color::Green))
+                } else {
+                    Span::styled(format!("{:<6}", "off"), Style::default().fg(Color::Yellow))
+                },
+            ),
+            None => (
+                "local".to_string(),
+                Span::styled(format!("{:<6}", "-"), Style::default().fg(Color::DarkGray)),
+            ),
+        };
+        let expires = format!("{:<10}", publish::expiry_label(image.expires_at, now));
+        let expires = match image.expires_at {
+            Some(at) if at <= now => Span::styled(expires, Style::default().fg(Color::Red)),
+            Some(at) if !warning.is_zero() && at <= now + warning => {
+                Span::styled(expires, Style::default().fg(Color::Yellow))
+            }
+            _ => Span::raw(expires),
+        };
+        let built: String = image.created_at.chars().take(10).collect();
+        let mut line = Line::from(vec![
+            Span::raw(format!(" {:<21}{:<18}", description, source)),
+            auto,
+            Span::raw(format!("{:<11}", watch::format_bytes(image.size as f64))),
+            expires,
+            Span::raw(built),
+        ]);
+        if i == view.selected {
+            line = line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        lines.push(line);
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_pools(frame: &mut Frame, view: &PoolsView, confirm_scrub: bool, server: &str) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let troubled = view
+        .pools
+        .iter()
+        .filter(|p| p.troubled(view.checks.get(&p.name)))
+        .count();
+    let mut block = Block::default()
+        .title(format!(" Storage pools on {} ", server))
+        .title_bottom(
+            Line::from(format!(
+                " {} pools, {} with problems ",
+                view.pools.len(),
+                troubled
+            ))
+            .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    if let Some(status) = &view.status {
+        block = block.title_bottom(Line::from(format!(" {} ", status)));
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            " {:<21}{:<9}{:<13}{:<22}{}",
+            "Pool", "Driver", "LXD", "Used", "Health"
+        ),
+        heading,
+    ))];
+
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    } else if view.pools.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No storage pools on this server",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (i, pool) in view.pools.iter().enumerate() {
+        let name: String = pool.name.chars().take(20).collect();
+        let lxd = if pool.status.is_empty() {
+            "-"
+        } else {
+            pool.status.as_str()
+        };
+        let lxd = if lxd == "Created" || lxd == "-" {
+            Span::raw(format!("{:<13}", lxd))
+        } else {
+            Span::styled(format!("{:<13}", lxd), Style::default().fg(Color::Red))
+        };
+        let used = if pool.total > 0 {
+            format!(
+                "{} of {}",
+                watch::format_bytes(pool.used as f64),
+                watch::format_bytes(pool.total as f64)
+            )
+        } else {
+            watch::format_bytes(pool.used as f64)
+        };
+        let mut line = Line::from(vec![
+            Span::raw(format!(" {:<21}{:<9}", name, pool.driver)),
+            lxd,
+            Span::raw(format!("{:<22}", used)),
+            pool_health(pool, view),
+        ]);
+        if i == view.selected {
+            line = line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        lines.push(line);
+    }
+
+    if let Some(pool) = view.selected_pool() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(format!(" {}", pool.name), heading)));
+        match view.checks.get(&pool.name) {
+            Some(Ok(health)) => {
+                let scrub = health.scrub.as_deref().unwrap_or("never scrubbed");
+                lines.push(Line::from(format!(" Scrub: {}", scrub)));
+                for error in &health.errors {
+                    lines.push(Line::from(Span::styled(
+                        format!(" {}", error),
+                        Style::default().fg(Color::Red),
+                    )));
+                }
+            }
+            Some(Err(e)) => lines.push(Line::from(Span::styled(
+                format!(" {}", e),
+                Style::default().fg(Color::Yellow),
+            ))),
+            None if view.checking.contains(&pool.name) => lines.push(Line::from(Span::styled(
+                format!(
+                    " Checking with {}...",
+                    pool.tool.as_ref().map_or("", |t| t.program())
+                ),
+                Style::default().fg(Color::DarkGray),
+            ))),
+            None if pool.tool.is_none() => lines.push(Line::from(Span::styled(
+                format!(" {} pools report no health beyond LXD's", pool.driver),
+                Style::default().fg(Color::DarkGray),
+            ))),
+            None => lines.push(Line::from(Span::styled(
+                " Health is only checked for pools of the local server",
+                Style::default().fg(Color::DarkGray),
+            ))),
+        }
+        if pool.warnings.is_empty() {
+            lines.push(Line::from(Span::styled(
+                " No warnings from LXD",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        for warning in &pool.warnings {
+            let color = match warning.severity.as_str() {
+                "high" => Color::Red,
+                _ => Color::Yellow,
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" {} {} ", warning.last_seen, warning.severity),
+                    Style::default().fg(color),
+                ),
+                Span::raw(format!("{} (x{})", warning.message, warning.count)),
+            ]));
+        }
+    }
+
+    if confirm_scrub {
+        if let Some(pool) = view.selected_pool() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!(
+                    " Scrub '{}'? It slows the pool while it runs. [y] Yes  [any key] No",
+                    pool.name
+                ),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )));
+        }
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// A pool's health as its tools report it, or "-" without them
+fn pool_health(pool: &Pool, view: &PoolsView) -> Span<'static> {
+    match view.checks.get(&pool.name) {
+        Some(Ok(health)) if health.healthy => {
+            Span::styled(health.state.clone(), Style::default().fg(Color::Green))
+        }
+        Some(Ok(health)) => Span::styled(health.state.clone(), Style::default().fg(Color::Red)),
+        Some(Err(_)) => Span::styled("unknown", Style::default().fg(Color::Yellow)),
+        None if view.checking.contains(&pool.name) => {
+            Span::styled("checking...", Style::default().fg(Color::DarkGray))
+        }
+        None => Span::styled("-", Style::default().fg(Color::DarkGray)),
+    }
+}
+
+fn draw_capacity(frame: &mut Frame, view: &Capacity, server: &str) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Capacity of {} ", server))
+        .title_bottom(
+            Line::from(format!(" {} instances ", view.reservations.len())).right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let host = view.host;
+    let share = |reserved: u64, total: u64| match total {
+        0 => "-".to_string(),
+        total => format!("{}%", reserved * 100 / total),
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(" {:<16}{:>6}{:>7}{:>12}{:>7}", "", "CPUs", "", "Memory", ""),
+            heading,
+        )),
+        Line::from(format!(
+            " {:<16}{:>6}{:>7}{:>12}",
+            "Host",
+            host.cpus,
+            "",
+            watch::format_bytes(host.memory as f64)
+        )),
+    ];
+    for (label, totals) in [("Running", view.running()), ("All instances", view.all())] {
+        let (left, tone) = match host.memory.checked_sub(totals.memory) {
+            Some(free) => (
+                format!("{} free", watch::format_bytes(free as f64)),
+                Color::Green,
+            ),
+            None => (
+                format!(
+                    "{} over",
+                    watch::format_bytes((totals.memory - host.memory) as f64)
+                ),
+                Color::Red,
+            ),
+        };
+        let mut spans = vec![
+            Span::raw(format!(
+                " {:<16}{:>6}{:>7}{:>12}{:>7}  ",
+                label,
+                totals.cpus,
+                share(totals.cpus, host.cpus),
+                watch::format_bytes(totals.memory as f64),
+                share(totals.memory, host.memory)
+            )),
+            Span::styled(left, Style::default().fg(tone)),
+        ];
+        if totals.overcommits(host) {
+            spans.push(Span::styled(
+                "  Overcommitted",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    let unlimited = view.all().unlimited;
+    if unlimited > 0 {
+        lines.push(Line::from(Span::styled(
+            format!(
+                " {} without a CPU or memory limit may use the whole host",
+                if unlimited == 1 {
+                    "1 instance".to_string()
+                } else {
+                    format!("{} instances", unlimited)
+                }
+            ),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!(
+            " {:<16}{:<17}{:<9}{:>6}{:>12}",
+            "Name", "Type", "State", "CPUs", "Memory"
+        ),
+        heading,
+    )));
+
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    } else if view.reservations.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No instances on this server",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let rows = (inner.height as usize).saturating_sub(lines.len()).max(1);
+    let skip = view.selected.saturating_sub(rows - 1);
+    for (i, reservation) in view.reservations.iter().enumerate().skip(skip).take(rows) {
+        let name: String = reservation.name.chars().take(15).collect();
+        let kind = if reservation.virtual_machine {
+            "virtual-machine"
+        } else {
+            "container"
+        };
+        let cpus = reservation
+            .cpus
+            .map_or("-".to_string(), |cpus| cpus.to_string());
+        let memory = reservation
+            .memory
+            .map_or("-".to_string(), |bytes| watch::format_bytes(bytes as f64));
+        let mut line = Line::from(vec![
+            Span::raw(format!(" {:<16}{:<17}", name, kind)),
+            Span::styled(
+                format!(
+                    "{:<9}",
+                    if reservation.running {
+                        "Running"
+                    } else {
+                        "Stopped"
+                    }
+                ),
+                Style::default().fg(if reservation.running {
+                    Color::Green
+                } else {
+                    Color::DarkGray
+                }),
+            ),
+            Span::raw(format!("{:>6}{:>12}", cpus, memory)),
+        ]);
+        if i == view.selected {
+            line = line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        lines.push(line);
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_zones(frame: &mut Frame, view: &Zones, confirm_delete: bool, server: &str) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let records: usize = view.zones.iter().map(|zone| zone.records.len()).sum();
+    let mut block = Block::default()
+        .title(format!(" DNS zones on {} ", server))
+        .title_bottom(
+            Line::from(format!(" {} zones, {} records ", view.zones.len(), records))
+                .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    if let Some(status) = &view.status {
+        block = block.title_bottom(Line::from(format!(" {} ", status)));
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            " {:<32}{:<10}{}",
+            "Zone / Record", "Kind", "Networks / Entries"
+        ),
+        heading,
+    ))];
 
-            let line = match &op.status {
-                crate::app::OperationStatus::Failed(err) if !err.is_empty() => {
-                    format!("{} {}{}", status_icon, op.description, duration)
-                }
-                crate::app::OperationStatus::Retrying(_) => {
-                    format!(
-                        "{} {} (retry {})",
-                        status_icon, op.description, op.retry_count
-                    )
-                }
-                _ => format!("{} {}{}", status_icon, op.description, duration),
-            };
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    } else if view.zones.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No zones on this server; press a to create one",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
 
-            content.push(Line::from(line));
+    // Keep the selection in view below the heading and above the prompt
+    let rows = view.rows();
+    let height = (inner.height as usize)
+        .saturating_sub(lines.len() + 2)
+        .max(1);
+    let skip = view.selected.saturating_sub(height - 1);
+    for (i, row) in rows.iter().enumerate().skip(skip).take(height) {
+        let mut line = match *row {
+            Row::Zone(z) => {
+                let zone = &view.zones[z];
+                let name: String = zone.name.chars().take(31).collect();
+                let networks = if zone.networks.is_empty() {
+                    "-".to_string()
+                } else {
+                    zone.networks.join(", ")
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {:<32}", name),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("{:<10}", zone.kind().name()),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(networks),
+                ])
+            }
+            Row::Record(z, r) => {
+                let record = &view.zones[z].records[r];
+                let name: String = record.name.chars().take(39).collect();
+                Line::from(vec![
+                    Span::raw(format!("   {:<40}", name)),
+                    Span::raw(zones::format_entries(&record.entries)),
+                ])
+            }
+        };
+        if i == view.selected {
+            line = line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
         }
-    } else {
-        content.push(Line::from("No operations yet"));
+        lines.push(line);
     }
 
-    let sidebar = Paragraph::new(content)
-        .block(
-            Block::default()
-                .borders(Borders::LEFT)
-                .border_style(Style::default().fg(Color::DarkGray))
-                .title(" Operations "),
-        )
-        .wrap(Wrap { trim: true });
-
-    frame.render_widget(sidebar, area);
-}
-
-fn centered_rect(width_percent: u16, height_percent: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - height_percent) / 2),
-            Constraint::Percentage(height_percent),
-            Constraint::Percentage((100 - height_percent) / 2),
-        ])
-        .split(r);
+    if confirm_delete {
+        let question = match view.selected_row() {
+            Some(Row::Zone(z)) => {
+                let zone = &view.zones[z];
+                format!(
+                    " Delete zone '{}' and its {} records? [y] Yes  [any key] No",
+                    zone.name,
+                    zone.records.len()
+                )
+            }
+            Some(Row::Record(z, r)) => format!(
+                " Delete record '{}.{}'? [y] Yes  [any key] No",
+                view.zones[z].records[r].name, view.zones[z].name
+            ),
+            None => String::new(),
+        };
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            question,
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+    }
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - width_percent) / 2),
-            Constraint::Percentage(width_percent),
-            Constraint::Percentage((100 - width_percent) / 2),
-        ])
-        .split(popup_layout[1])[1]
+    frame.render_widget(Paragraph::new(lines), inner);
 }
 
-fn draw_command_menu(frame: &mut Frame, menu: &CommandMenu, selected: usize) {
-    let area = centered_rect(60, 40, frame.area());
+fn draw_templates(frame: &mut Frame, view: &TemplatesView) {
+    let area = centered_rect(80, 80, frame.area());
     frame.render_widget(Clear, area);
 
-    let (title, items) = match menu {
-        CommandMenu::Closed | CommandMenu::Main => return,
-        CommandMenu::Container => (
-            " Container Actions ",
-            vec![
-                (
-                    "Enter/s",
-                    "Smart Action",
-                    "Start if stopped, Stop if running",
-                ),
-                ("1", "Start Container", "Start the selected container"),
-                ("2", "Stop Container", "Stop the selected container"),
-                ("3", "Restart Container", "Restart the selected container"),
-                ("4", "Delete Container", "Delete the selected container"),
-                ("5", "Clone Container", "Create a copy of the container"),
-                ("e", "Exec Shell", "Open shell in running container"),
-                ("Esc", "Cancel", "Return to container list"),
-            ],
-        ),
-        CommandMenu::System => (
-            " System Menu ",
-            vec![
-                ("1/r", "Refresh List", "Reload container list"),
-                ("2/l", "Check LXD Service", "Ensure LXD service is running"),
-                ("3/n", "New Container", "Create a new container"),
-                ("4/o", "Toggle Operations", "Show/hide operations sidebar"),
-                ("5/h", "Help", "Show keyboard shortcuts"),
-                ("6/q", "Quit", "Exit LXTUI"),
-                ("Esc", "Cancel", "Return to container list"),
-            ],
-        ),
-    };
-
-    let mut content = vec![Line::from("")];
+    let mut block = Block::default()
+        .title(format!(" Templates: {} ", view.container))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    if let Some(status) = &view.status {
+        block = block.title_bottom(Line::from(format!(" {} ", status)).right_aligned());
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    // Skip the "Esc" option when counting (it's always last)
-    let selectable_items = items.len() - 1;
+    let mut lines = Vec::new();
+    if let Some(metadata) = &view.metadata {
+        let mut properties: Vec<String> = metadata
+            .properties
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        if properties.is_empty() {
+            properties.push("no properties".to_string());
+        }
+        lines.push(Line::from(vec![
+            Span::styled(
+                " Metadata: ",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(properties.join(", ")),
+        ]));
+        lines.push(Line::from(""));
+    }
 
-    for (idx, (key, label, desc)) in items.iter().enumerate() {
-        // Don't highlight Esc option
-        let is_selected = idx < selectable_items && idx == selected;
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    } else if view.files.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No templates; press n to add one",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
 
-        if is_selected {
-            // Highlighted selection with arrow indicator
-            content.push(Line::from(vec![
-                Span::styled(
-                    " ▶ ",
-                    Style::default()
-                        .fg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(
-                    format!("[{}] ", key),
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(
-                    format!("{:<20}", label),
-                    Style::default()
-                        .fg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(desc.to_string(), Style::default().fg(Color::White)),
-            ]));
+    let mut selected_line = 0;
+    for (i, file) in view.files.iter().enumerate() {
+        let style = if i == view.selected {
+            selected_line = lines.len();
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD)
         } else {
-            // Normal item
-            content.push(Line::from(vec![
-                Span::styled("   ", Style::default()), // Space for arrow
-                Span::styled(
-                    format!("[{}] ", key),
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(format!("{:<20}", label), Style::default().fg(Color::White)),
-                Span::styled(desc.to_string(), Style::default().fg(Color::DarkGray)),
+            Style::default()
+        };
+        let mut spans = vec![Span::styled(format!(" {}", file.name), style)];
+        if !file.exists {
+            spans.push(Span::styled("  (missing)", Style::default().fg(Color::Red)));
+        }
+        lines.push(Line::from(spans));
+        if file.targets.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "     not rendered anywhere",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        for (path, when) in &file.targets {
+            lines.push(Line::from(vec![
+                Span::raw(format!("     → {:<28}", path)),
+                Span::styled(when.join(", "), Style::default().fg(Color::DarkGray)),
             ]));
         }
-        content.push(Line::from(""));
     }
 
-    // Add navigation hint at the bottom
-    content.push(Line::from(vec![
-        Span::styled(" Use ", Style::default().fg(Color::DarkGray)),
-        Span::styled("↑/↓ or j/k", Style::default().fg(Color::Cyan)),
-        Span::styled(" to navigate, ", Style::default().fg(Color::DarkGray)),
-        Span::styled("Enter", Style::default().fg(Color::Cyan)),
-        Span::styled(" to select", Style::default().fg(Color::DarkGray)),
-    ]));
+    // Keep the selected file and its first target in view
+    let scroll = (selected_line + 2).saturating_sub(inner.height as usize) as u16;
+    frame.render_widget(Paragraph::new(lines).scroll((scroll, 0)), inner);
+}
+
+fn draw_drift(frame: &mut Frame, view: &DriftView) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
 
+    let file = view
+        .file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| view.file.display().to_string());
     let block = Block::default()
-        .title(title)
+        .title(format!(" Drift: {} ", file))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan))
         .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .wrap(Wrap { trim: true });
+    let grey = Style::default().fg(Color::DarkGray);
+    let green = Style::default().fg(Color::Green);
+    let red = Style::default().fg(Color::Red);
+    let yellow = Style::default().fg(Color::Yellow);
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
 
-    frame.render_widget(paragraph, area);
-}
+    let mut lines = Vec::new();
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(format!(" {}", error), red)));
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+        return;
+    }
 
-fn draw_status_modal(frame: &mut Frame, modal_type: &StatusModalType, app: &App) {
-    let area = centered_rect(70, 50, frame.area());
-    frame.render_widget(Clear, area);
+    let exported = match &view.exported_at {
+        Some(at) => format!(" Exported from {} at {}", view.exported_from, at),
+        None => format!(" Exported from {}", view.exported_from),
+    };
+    lines.push(Line::from(vec![
+        Span::styled(exported, grey),
+        Span::styled(format!(", compared with {}", view.server), grey),
+    ]));
+    if view.exported_from != view.server {
+        lines.push(Line::from(Span::styled(
+            " Another server's export: expect everything to differ",
+            yellow,
+        )));
+    }
+    let (added, removed, changed) = view.tally();
+    lines.push(if view.drifts.is_empty() {
+        Line::from(Span::styled(
+            " No drift: the server matches the export",
+            green,
+        ))
+    } else {
+        Line::from(Span::styled(
+            format!(
+                " {} added, {} removed, {} changed since",
+                added, removed, changed
+            ),
+            heading,
+        ))
+    });
 
-    match modal_type {
-        StatusModalType::Info {
-            message,
-            auto_close,
-        } => {
-            draw_info_modal(frame, area, message, *auto_close);
+    let mut section = None;
+    let mut selected_line = 0;
+    for (i, drift) in view.drifts.iter().enumerate() {
+        if section != Some(drift.section) {
+            section = Some(drift.section);
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!(" {}", drift.section.name()),
+                heading,
+            )));
         }
-        StatusModalType::Progress { operation_id } => {
-            if let Some(operation) = app.user_operations.iter().find(|op| op.id == *operation_id) {
-                draw_progress_modal(frame, area, operation);
-            }
-        }
-        StatusModalType::Error {
-            title,
-            details,
-            suggestions,
-        } => {
-            draw_error_modal(frame, area, title, details, suggestions);
+        let (mark, style) = match drift.change {
+            Change::Added => ("+", green),
+            Change::Removed => ("-", red),
+            Change::Changed(_) => ("~", yellow),
+        };
+        let mut line = Line::from(vec![
+            Span::styled(format!("  {} ", mark), style),
+            Span::raw(drift.name.clone()),
+        ]);
+        if i == view.selected {
+            selected_line = lines.len();
+            line = line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
         }
-        StatusModalType::Success {
-            message,
-            started_at,
-        } => {
-            draw_success_modal(frame, area, message, started_at);
+        lines.push(line);
+        if let Change::Changed(differences) = &drift.change {
+            for difference in differences {
+                let value =
+                    |value: &Option<String>| value.clone().unwrap_or_else(|| "(unset)".to_string());
+                lines.push(Line::from(vec![
+                    Span::styled(format!("      {:<32}", difference.path), grey),
+                    Span::styled(value(&difference.then), red),
+                    Span::styled(" → ", grey),
+                    Span::styled(value(&difference.now), green),
+                ]));
+            }
         }
     }
+
+    // Keep the selected entry and the start of its changes in view
+    let scroll = (selected_line + 3).saturating_sub(inner.height as usize) as u16;
+    frame.render_widget(Paragraph::new(lines).scroll((scroll, 0)), inner);
 }
 
-fn draw_info_modal(frame: &mut Frame, area: Rect, message: &str, auto_close: bool) {
+fn draw_key_search(frame: &mut Frame, view: &KeySearch) {
+    let area = centered_rect(80, 70, frame.area());
+    frame.render_widget(Clear, area);
+
     let block = Block::default()
-        .title(" Information ")
+        .title(format!(" Key search: {} ", view.pattern))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Blue))
+        .border_style(Style::default().fg(Color::Cyan))
         .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    let mut lines: Vec<Line> = vec![Line::from("")];
-    for line in message.lines() {
-        lines.push(Line::from(line));
+    let grey = Style::default().fg(Color::DarkGray);
+    let mut lines = Vec::new();
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    } else {
+        lines.push(Line::from(Span::styled(
+            format!(" Set on {} of {} instances", view.matched(), view.instances),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        let values: Vec<String> = view
+            .values()
+            .iter()
+            .map(|(value, count)| format!("{} ×{}", one_line(value), count))
+            .collect();
+        if !values.is_empty() {
+            lines.push(Line::from(Span::styled(
+                format!(" {}", values.join("  ")),
+                grey,
+            )));
+        }
     }
     lines.push(Line::from(""));
 
-    if !auto_close {
-        lines.push(Line::from(vec![Span::styled(
-            "Press any key to continue",
-            Style::default()
-                .fg(Color::DarkGray)
-                .add_modifier(Modifier::ITALIC),
-        )]));
+    let heading = lines.len();
+    let height = (inner.height as usize).saturating_sub(heading).max(1);
+    let skip = view.selected.saturating_sub(height - 1);
+    for (i, hit) in view.hits.iter().enumerate().skip(skip).take(height) {
+        let name: String = hit.instance.chars().take(19).collect();
+        let key: String = hit.key.chars().take(31).collect();
+        let mut spans = vec![
+            Span::raw(format!(" {:<20}", name)),
+            Span::styled(format!("{:<32}", key), grey),
+            Span::raw(one_line(&hit.value)),
+        ];
+        if hit.from_profile {
+            spans.push(Span::styled("  (profile)", grey));
+        }
+        let mut line = Line::from(spans);
+        if i == view.selected {
+            line = line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        lines.push(line);
     }
 
-    let paragraph = Paragraph::new(lines)
-        .block(block)
-        .alignment(Alignment::Center)
-        .wrap(Wrap { trim: true });
+    frame.render_widget(Paragraph::new(lines), inner);
+}
 
-    frame.render_widget(paragraph, area);
+/// The first line of a config value, marked when there are more
+fn one_line(value: &str) -> String {
+    match value.trim().split_once('\n') {
+        Some((first, _)) => format!("{} …", first),
+        None => value.trim().to_string(),
+    }
 }
 
-fn draw_progress_modal(frame: &mut Frame, area: Rect, operation: &crate::app::UserOperation) {
-    let elapsed_secs = if let Some(started) = operation.started_at {
-        started.elapsed().as_secs()
-    } else {
-        0
-    };
+fn draw_profiles(frame: &mut Frame, view: &ProfileBatch) {
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
 
-    let spinner = match elapsed_secs % 4 {
-        0 => "⠋",
-        1 => "⠙",
-        2 => "⠹",
-        _ => "⠸",
+    let title = match &view.targets[..] {
+        [target] => format!(" Profiles: {} ", target.name),
+        targets => format!(" Profiles: {} instances ", targets.len()),
     };
+    let mut block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .border_type(BorderType::Rounded);
+    if let Some(status) = &view.status {
+        block = block.title_bottom(Line::from(format!(" {} ", status)).right_aligned());
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    let status_line = match &operation.status {
-        crate::app::OperationStatus::Registered => format!("⏳ Preparing..."),
-        crate::app::OperationStatus::Running => format!("{} In Progress...", spinner),
-        crate::app::OperationStatus::Retrying(count) => {
-            format!("🔄 Retrying... (attempt {}/3)", count)
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let grey = Style::default().fg(Color::DarkGray);
+    let red = Style::default().fg(Color::Red);
+
+    let mut lines = vec![Line::from(Span::styled(" Profile", heading))];
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(format!(" {}", error), red)));
+    }
+    for (i, profile) in view.profiles.iter().enumerate() {
+        let holders = view
+            .targets
+            .iter()
+            .filter(|t| t.profiles.as_ref().is_ok_and(|p| p.contains(profile)))
+            .count();
+        let mut line = Line::from(vec![
+            Span::raw(format!(" {:<24}", profile)),
+            Span::styled(format!("on {} of {}", holders, view.targets.len()), grey),
+        ]);
+        if i == view.selected {
+            line = line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
         }
-        _ => format!("Processing..."),
-    };
+        lines.push(line);
+    }
 
-    let block = Block::default()
-        .title(" Operation Progress ")
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(" Instances", heading)));
+    for target in &view.targets {
+        let (mark, style) = match &target.outcome {
+            Some(Outcome::Changed) => ("✓", Style::default().fg(Color::Green)),
+            Some(Outcome::Unchanged) => ("–", grey),
+            Some(Outcome::Failed(_)) => ("✗", red),
+            None => (" ", Style::default()),
+        };
+        let mut spans = vec![
+            Span::styled(format!(" {} ", mark), style),
+            Span::raw(format!("{:<24}", target.name)),
+        ];
+        match (&target.outcome, &target.profiles) {
+            (Some(Outcome::Failed(e)), _) | (_, Err(e)) => spans.push(Span::styled(e.clone(), red)),
+            (_, Ok(profiles)) if profiles.is_empty() => {
+                spans.push(Span::styled("no profiles", grey))
+            }
+            (_, Ok(profiles)) => spans.push(Span::styled(profiles.join(", "), grey)),
+        }
+        lines.push(Line::from(spans));
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Width of the instance column in the quick exec results
+const QUICK_NAMES_WIDTH: u16 = 28;
+
+fn draw_quick(frame: &mut Frame, view: &QuickView, confirm: Option<usize>) {
+    let area = centered_rect(85, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = match &view.names[..] {
+        [name] => format!(" Quick Exec: {} ", name),
+        names => format!(" Quick Exec: {} instances ", names.len()),
+    };
+    let mut block = Block::default()
+        .title(title)
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan))
         .border_type(BorderType::Rounded);
+    if let Some(run) = &view.run {
+        let (ok, failed) = run.tally();
+        let running = run.targets.len() - ok - failed;
+        let mut tally = format!(" {}: {} ✓  {} ✗ ", run.action.name, ok, failed);
+        if running > 0 {
+            tally.push_str(&format!(" {} running ", running));
+        }
+        block = block.title_bottom(Line::from(tally).right_aligned());
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    let content = vec![
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            &operation.description,
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            status_line,
-            Style::default().fg(Color::Cyan),
-        )]),
-        Line::from(""),
-        Line::from(format!("Elapsed: {} seconds", elapsed_secs)),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("Press ", Style::default().fg(Color::DarkGray)),
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let grey = Style::default().fg(Color::DarkGray);
+    let red = Style::default().fg(Color::Red);
+    let highlight = Style::default()
+        .bg(Color::DarkGray)
+        .add_modifier(Modifier::BOLD);
+
+    let Some(run) = &view.run else {
+        let mut lines = vec![Line::from(Span::styled(
+            format!(" {:<6}{:<24}{}", "Key", "Action", "Command"),
+            heading,
+        ))];
+        if view.actions.is_empty() {
+            lines.push(Line::from(Span::styled(
+                " No actions; add [[quick.actions]] to the config file",
+                grey,
+            )));
+        }
+        let command_row = Line::from(vec![
             Span::styled(
-                "Esc",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                format!(" {:<6}", quick::COMMAND_KEY),
+                Style::default().fg(Color::Yellow),
             ),
-            Span::styled(" to cancel", Style::default().fg(Color::DarkGray)),
-        ]),
-    ];
+            Span::raw(format!("{:<24}", "Command…")),
+            Span::styled("Type a command to run once", grey),
+        ]);
+        for (i, action) in view.actions.iter().enumerate() {
+            let name: String = action.name.chars().take(23).collect();
+            let mut line = Line::from(vec![
+                Span::styled(
+                    format!(" {:<6}", action.key),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(format!("{:<24}", name)),
+                Span::styled(action.command.clone(), grey),
+            ]);
+            if i == view.selected {
+                line = line.style(highlight);
+            }
+            lines.push(line);
+        }
+        lines.push(command_row);
+        if let Some(action) = confirm.and_then(|i| view.actions.get(i)) {
+            let instances = match &view.names[..] {
+                [name] => format!("'{}'", name),
+                names => format!("{} instances", names.len()),
+            };
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!(
+                    " Run '{}' in {}? [y] Yes  [any key] No",
+                    action.name, instances
+                ),
+                red.add_modifier(Modifier::BOLD),
+            )));
+        }
+        frame.render_widget(Paragraph::new(lines), inner);
+        return;
+    };
 
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .alignment(Alignment::Center)
-        .wrap(Wrap { trim: true });
+    // Expanded, the output takes the instances' column too
+    let names_width = if view.expanded { 0 } else { QUICK_NAMES_WIDTH };
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(names_width), Constraint::Min(0)])
+        .split(inner);
+
+    let mut names = vec![Line::from(Span::styled(" Instance", heading))];
+    for (i, target) in run.targets.iter().enumerate() {
+        let (mark, style) = match &target.outcome {
+            quick::Outcome::Running => ("…", Style::default().fg(Color::Yellow)),
+            quick::Outcome::Exited(0) => ("✓", Style::default().fg(Color::Green)),
+            quick::Outcome::Exited(_)
+            | quick::Outcome::Failed(_)
+            | quick::Outcome::TimedOut(_)
+            | quick::Outcome::Cancelled => ("✗", red),
+        };
+        let width = QUICK_NAMES_WIDTH as usize - 4;
+        let name: String = target.name.chars().take(width).collect();
+        let mut line = Line::from(vec![
+            Span::styled(format!(" {} ", mark), style),
+            Span::raw(name),
+        ]);
+        if i == view.selected {
+            line = line.style(highlight);
+        }
+        names.push(line);
+    }
+    frame.render_widget(Paragraph::new(names), columns[0]);
 
-    frame.render_widget(paragraph, area);
+    let Some(target) = view.selected_target() else {
+        return;
+    };
+    let status = match &target.outcome {
+        quick::Outcome::Running => Span::styled("running", Style::default().fg(Color::Yellow)),
+        quick::Outcome::Exited(0) => {
+            Span::styled("exit status 0", Style::default().fg(Color::Green))
+        }
+        quick::Outcome::Exited(status) => Span::styled(format!("exit status {}", status), red),
+        quick::Outcome::Failed(_) => Span::styled("couldn't run", red),
+        quick::Outcome::TimedOut(secs) => Span::styled(format!("no answer within {}s", secs), red),
+        quick::Outcome::Cancelled => Span::styled("cancelled", red),
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::styled(format!("{}  ", target.name), heading),
+        status,
+        Span::styled(format!("  $ {}", run.action.command), grey),
+    ])];
+    let alike = run.alike(view.selected);
+    if !alike.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("Same in {}", alike.join(", ")),
+            Style::default().fg(Color::Cyan),
+        )));
+    }
+    if let quick::Outcome::Failed(e) = &target.outcome {
+        lines.push(Line::from(Span::styled(e.clone(), red)));
+    }
+    lines.extend(
+        target
+            .output
+            .lines()
+            .skip(view.scroll)
+            .map(|line| Line::from(line.to_string())),
+    );
+    frame.render_widget(Paragraph::new(lines), columns[1]);
 }
 
-fn draw_error_modal(
-    frame: &mut Frame,
-    area: Rect,
-    title: &str,
-    details: &str,
-    suggestions: &[String],
-) {
-    let block = Block::default()
-        .title(format!(" ❌ {} ", title))
+fn draw_recreate(frame: &mut Frame, plan: &Conversion) {
+    let area = centered_rect(80, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut block = Block::default()
+        .title(format!(
+            " Recreate {}'s config as a {} ",
+            plan.source,
+            plan.kind()
+        ))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Red))
+        .border_style(Style::default().fg(Color::Cyan))
         .border_type(BorderType::Rounded);
+    if let Some(status) = &plan.status {
+        block = block.title_bottom(Line::from(format!(" {} ", status)).right_aligned());
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    let mut content = vec![
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let grey = Style::default().fg(Color::DarkGray);
+    let yellow = Style::default().fg(Color::Yellow);
+    let field = |label: &str, value: Span<'static>| {
+        Line::from(vec![Span::styled(format!(" {:<12}", label), grey), value])
+    };
+    let names = |names: Vec<&String>| {
+        if names.is_empty() {
+            "none".to_string()
+        } else {
+            names
+                .iter()
+                .map(|n| n.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    };
+
+    let mut lines = vec![
+        field("Name", Span::raw(plan.name.clone())),
+        field(
+            "Image",
+            if plan.image.is_empty() {
+                Span::styled("none found; press i to choose one", yellow)
+            } else {
+                Span::raw(plan.image.clone())
+            },
+        ),
+        field(
+            "Snapshot",
+            Span::raw(format!("{} of {}, first", plan.snapshot, plan.source)),
+        ),
+        field("Profiles", Span::raw(names(plan.profiles.iter().collect()))),
         Line::from(""),
-        Line::from(vec![Span::styled(
-            "Error Details:",
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-        )]),
+        Line::from(Span::styled(" Carried over", heading)),
+        field("Config", Span::raw(names(plan.config.keys().collect()))),
+        field("Devices", Span::raw(names(plan.devices.keys().collect()))),
+        field("Volumes", Span::raw(names(plan.volumes.iter().collect()))),
         Line::from(""),
+        Line::from(Span::styled(" Left behind", heading)),
+        field(
+            "Root disk",
+            Span::raw("its files; the new one starts from a fresh image"),
+        ),
     ];
-
-    // Add error details
-    for line in details.lines() {
-        content.push(Line::from(vec![Span::styled(
-            line,
-            Style::default().fg(Color::White),
-        )]));
+    for (what, why) in &plan.dropped {
+        lines.push(Line::from(vec![
+            Span::raw(format!(" {:<32}", what)),
+            Span::styled(*why, grey),
+        ]));
     }
 
-    if !suggestions.is_empty() {
-        content.push(Line::from(""));
-        content.push(Line::from(vec![Span::styled(
-            "Suggestions:",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        )]));
-        content.push(Line::from(""));
-
-        for suggestion in suggestions {
-            content.push(Line::from(vec![
-                Span::styled("• ", Style::default().fg(Color::Yellow)),
-                Span::raw(suggestion),
-            ]));
-        }
+    if !plan.volumes.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!(
+                " Both mount the same volumes: stop {} before starting {}",
+                plan.source, plan.name
+            ),
+            yellow,
+        )));
+    } else if plan.running {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!(
+                " {} keeps running; the {} is created stopped",
+                plan.source,
+                plan.kind()
+            ),
+            grey,
+        )));
     }
 
-    content.push(Line::from(""));
-    content.push(Line::from(vec![Span::styled(
-        "Press any key to continue",
-        Style::default()
-            .fg(Color::DarkGray)
-            .add_modifier(Modifier::ITALIC),
-    )]));
-
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .alignment(Alignment::Left)
-        .wrap(Wrap { trim: true });
-
-    frame.render_widget(paragraph, area);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
 }
 
-fn draw_success_modal(
-    frame: &mut Frame,
-    area: Rect,
-    message: &str,
-    _started_at: &tokio::time::Instant,
-) {
-    let block = Block::default()
-        .title(" ✅ Success ")
+fn draw_idmap(frame: &mut Frame, view: &IdmapView) {
+    let area = centered_rect(80, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut block = Block::default()
+        .title(format!(" ID mapping: {} ", view.container))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Green))
+        .border_style(Style::default().fg(Color::Cyan))
         .border_type(BorderType::Rounded);
+    if let Some(status) = &view.status {
+        block = block.title_bottom(Line::from(format!(" {} ", status)).right_aligned());
+    } else if view.restart_needed {
+        block = block.title_bottom(
+            Line::from(" Changed: the new map applies when it next starts ").right_aligned(),
+        );
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    let content = vec![
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            message,
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "(Press any key to continue)",
-            Style::default()
-                .fg(Color::DarkGray)
-                .add_modifier(Modifier::ITALIC),
-        )]),
-    ];
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let grey = Style::default().fg(Color::DarkGray);
+    let red = Style::default().fg(Color::Red);
+    let selected = Style::default()
+        .bg(Color::DarkGray)
+        .add_modifier(Modifier::BOLD);
 
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .alignment(Alignment::Center)
-        .wrap(Wrap { trim: true });
+    let mut lines = Vec::new();
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(format!(" {}", error), red)));
+    }
+    if view.privileged {
+        lines.push(Line::from(Span::styled(
+            " Privileged: its IDs are the host's, and raw.idmap is ignored",
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    lines.push(Line::from(vec![
+        Span::raw(" Isolated range: "),
+        Span::raw(if view.isolated { "on" } else { "off" }),
+        Span::styled(format!("  ({})", idmap::ISOLATED_KEY), grey),
+    ]));
+    lines.push(Line::from(""));
 
-    frame.render_widget(paragraph, area);
+    lines.push(Line::from(Span::styled(
+        format!(" {:<24}{}", idmap::RAW_KEY, "Note"),
+        heading,
+    )));
+    if view.lines.is_empty() {
+        lines.push(Line::from(Span::styled(" No extra IDs mapped", grey)));
+    }
+    for (i, text) in view.lines.iter().enumerate() {
+        let mut line = match Entry::parse(text, i + 1) {
+            Ok(_) => Line::from(format!(" {:<24}", text)),
+            Err(e) => Line::from(vec![
+                Span::styled(format!(" {:<24}", text), red),
+                Span::styled(e.to_string(), red),
+            ]),
+        };
+        if i == view.selected {
+            line = line.style(selected);
+        }
+        lines.push(line);
+    }
+    // Lines that read fine alone may still map an ID twice
+    if let Err(e @ IdmapError::Overlap { .. }) = idmap::check(&view.lines) {
+        lines.push(Line::from(Span::styled(format!(" {}", e), red)));
+    }
+    lines.push(Line::from(""));
+
+    lines.push(Line::from(Span::styled(
+        format!(" {:<16}{:<32}{}", "Disk", "Source", "Shift"),
+        heading,
+    )));
+    if view.disks.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No host directories mounted",
+            grey,
+        )));
+    }
+    for (i, disk) in view.disks.iter().enumerate() {
+        let mut line = Line::from(format!(
+            " {:<16}{:<32}{}",
+            disk.name,
+            disk.source,
+            if disk.shift { "on" } else { "off" }
+        ));
+        if view.lines.len() + i == view.selected {
+            line = line.style(selected);
+        }
+        lines.push(line);
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner);
 }
 
-fn draw_confirmation_modal(frame: &mut Frame, message: &str, action: &ConfirmAction) {
-    let area = centered_rect(60, 30, frame.area());
+fn draw_vm_settings(frame: &mut Frame, view: &VmSettings) {
+    let area = centered_rect(80, 60, frame.area());
     frame.render_widget(Clear, area);
 
-    let title = match action {
-        ConfirmAction::StartContainer(_) => " Start Container ",
-        ConfirmAction::StopContainer(_) => " Stop Container ",
-        ConfirmAction::RestartContainer(_) => " Restart Container ",
-        ConfirmAction::DeleteContainer(_) => " ⚠️  Delete Container ",
-    };
-
-    let block = Block::default()
-        .title(title)
+    let mut block = Block::default()
+        .title(format!(" VM settings: {} ", view.container))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow))
+        .border_style(Style::default().fg(Color::Cyan))
         .border_type(BorderType::Rounded);
+    if let Some(status) = &view.status {
+        block = block.title_bottom(Line::from(format!(" {} ", status)).right_aligned());
+    } else if view.running {
+        block = block.title_bottom(
+            Line::from(" Running: most changes apply when it next starts ").right_aligned(),
+        );
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    let content = vec![
-        Line::from(""),
-        Line::from(message),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("Press ", Style::default().fg(Color::White)),
-            Span::styled(
-                "Enter/Y",
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let grey = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(" {:<20}{:<12}{:<26}{}", "Setting", "Value", "Key", "Note"),
+        heading,
+    ))];
+
+    if let Some(error) = &view.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    for (i, setting) in view.settings.iter().enumerate() {
+        let value = if setting.knob.is_toggle() {
+            if setting.is_on() {
+                "on"
+            } else {
+                "off"
+            }
+        } else {
+            setting.value.as_str()
+        };
+        let key = setting.knob.key().unwrap_or("tpm device");
+        let note = match &setting.missing {
+            Some(_) => "unsupported",
+            None if setting.knob == Knob::Vsock => "read only",
+            None if setting.inherited => "default or profile",
+            None => "",
+        };
+        let style = if setting.missing.is_some() {
+            grey
+        } else {
+            Style::default()
+        };
+        let mut line = Line::from(vec![
+            Span::styled(format!(" {:<20}", setting.knob.label()), style),
+            Span::styled(format!("{:<12}", value), style),
+            Span::styled(format!("{:<26}", key), grey),
+            Span::styled(note, grey),
+        ]);
+        if i == view.selected {
+            line = line.style(
                 Style::default()
-                    .fg(Color::Green)
+                    .bg(Color::DarkGray)
                     .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" to confirm or ", Style::default().fg(Color::White)),
-            Span::styled(
-                "Esc/N",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" to cancel", Style::default().fg(Color::White)),
-        ]),
-    ];
-
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .alignment(Alignment::Center)
-        .wrap(Wrap { trim: true });
+            );
+        }
+        lines.push(line);
+    }
 
-    frame.render_widget(paragraph, area);
+    frame.render_widget(Paragraph::new(lines), inner);
 }
 
-fn draw_input_modal(
-    frame: &mut Frame,
-    prompt: &str,
-    input: &str,
-    input_type: &InputType,
-    callback: &InputCallback,
-) {
-    let area = centered_rect(60, 20, frame.area());
+fn draw_compare(frame: &mut Frame, compare: &Comparison, theme: Theme) {
+    let area = centered_rect(90, 80, frame.area());
     frame.render_widget(Clear, area);
 
-    let title = match callback {
-        InputCallback::CloneContainer(_) => " Clone Container ",
-        InputCallback::CreateContainer => " New Container ",
+    let differences = match compare.difference_count() {
+        1 => " 1 difference ".to_string(),
+        n => format!(" {} differences ", n),
     };
-
     let block = Block::default()
-        .title(title)
+        .title(format!(" Compare: {} ↔ {} ", compare.left, compare.right))
+        .title_bottom(Line::from(differences).right_aligned())
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan))
         .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    let hint = match input_type {
-        InputType::ContainerName => "Container names must be alphanumeric with dashes allowed",
-        InputType::ImageName => "Enter image name (e.g., ubuntu:22.04)",
+    // Setting names get a third of the width, each instance the rest
+    let key_width = (inner.width as usize / 3).max(1);
+    let value_width = ((inner.width as usize).saturating_sub(key_width + 1) / 2).max(1);
+    let cell = |text: &str, width: usize| {
+        let text: String = text.chars().take(width.saturating_sub(1)).collect();
+        format!("{:<width$}", text, width = width)
     };
 
-    let content = vec![
-        Line::from(""),
-        Line::from(prompt),
-        Line::from(""),
-        Line::from(format!("{}_", input)),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            hint,
+    let bold = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(vec![
+        Span::styled(cell(" Setting", key_width + 1), bold),
+        Span::styled(cell(&compare.left, value_width), bold),
+        Span::styled(cell(&compare.right, value_width), bold),
+    ])];
+
+    let rows = compare.visible_rows();
+    if rows.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No differences",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let mut section: Option<Section> = None;
+    for row in rows.into_iter().skip(compare.scroll as usize) {
+        if section != Some(row.section) {
+            section = Some(row.section);
+            lines.push(Line::from(Span::styled(
+                format!(" {}", row.section.title()),
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        let style = if row.differs() {
+            theme.style(Tone::Warning)
+        } else {
             Style::default()
-                .fg(Color::DarkGray)
-                .add_modifier(Modifier::ITALIC),
-        )]),
-    ];
+        };
+        // Differences are marked by more than colour in high contrast
+        let marker = match theme {
+            Theme::HighContrast if row.differs() => " ≠ ",
+            _ => "   ",
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                cell(&format!("{}{}", marker, row.key), key_width + 1),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(cell(row.left.as_deref().unwrap_or("-"), value_width), style),
+            Span::styled(
+                cell(row.right.as_deref().unwrap_or("-"), value_width),
+                style,
+            ),
+        ]));
+    }
 
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .wrap(Wrap { trim: true });
+    frame.render_widget(Paragraph::new(lines), inner);
+}
 
-    frame.render_widget(paragraph, area);
+fn wizard_area(full: Rect) -> Rect {
+    centered_rect(70, 60, full)
 }
 
 fn draw_wizard(frame: &mut Frame, state: &WizardState, app: &App) {
-    let area = centered_rect(70, 60, frame.area());
+    let area = wizard_area(frame.area());
     frame.render_widget(Clear, area);
 
     match state {
-        WizardState::Name => draw_wizard_name(frame, area, &app.input_buffer),
+        WizardState::Name => draw_wizard_name(frame, area, app),
         WizardState::SelectImage => draw_wizard_image(frame, area, app),
         WizardState::SelectType => draw_wizard_type(frame, area, app),
+        WizardState::Media => draw_wizard_media(frame, area, app),
         WizardState::Confirm => draw_wizard_confirm(frame, area, app),
     }
 }
 
-fn draw_wizard_name(frame: &mut Frame, area: Rect, input: &str) {
+fn draw_wizard_name(frame: &mut Frame, area: Rect, app: &App) {
     let block = Block::default()
         .title(" New Container - Step 1: Name ")
         .borders(Borders::ALL)
@@ -853,9 +5362,23 @@ fn draw_wizard_name(frame: &mut Frame, area: Rect, input: &str) {
     let text = vec![
         Line::from("Enter a name for your new container:"),
         Line::from(""),
-        Line::from(format!("Name: {}_", input)),
+        Line::from(
+            [
+                vec![Span::raw("Name: ")],
+                field_spans(&app.input_buffer, Style::default()),
+            ]
+            .concat(),
+        ),
         Line::from(""),
         Line::from("Container names must be alphanumeric with dashes allowed."),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "Enter skips to the last step with the defaults ({}), Tab picks the image and type.",
+                app.wizard_data.image
+            ),
+            Style::default().fg(Color::DarkGray),
+        )),
     ];
 
     let paragraph = Paragraph::new(text)
@@ -867,35 +5390,68 @@ fn draw_wizard_name(frame: &mut Frame, area: Rect, input: &str) {
 }
 
 fn draw_wizard_image(frame: &mut Frame, area: Rect, app: &App) {
-    let block = Block::default()
+    let mut block = Block::default()
         .title(" New Container - Step 2: Select Image ")
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Green))
         .border_type(BorderType::Rounded);
+    if app.images.searching() {
+        block = block.title_bottom(format!(
+            " Searching {} images... ",
+            app.images.search_server
+        ));
+    } else if let Some(error) = &app.images.search_error {
+        block = block.title_bottom(format!(" Image search failed: {} ", error));
+    }
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner);
+
+    frame.render_widget(
+        Paragraph::new(format!("Image: {}_", app.wizard_data.image_query))
+            .style(Style::default().fg(Color::White)),
+        chunks[0],
+    );
 
     let items: Vec<ListItem> = app
-        .available_images
+        .image_choices()
         .iter()
         .enumerate()
-        .map(|(i, image)| {
-            let content = format!("{} - {}", image.alias, image.description);
+        .map(|(i, choice)| {
+            let mut spans = vec![Span::raw(choice.alias.clone())];
+            if !choice.description.is_empty() {
+                spans.push(Span::raw(format!(" - {}", choice.description)));
+            }
+            if !choice.source.label().is_empty() {
+                spans.push(Span::styled(
+                    format!("  ({})", choice.source.label()),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            let item = ListItem::new(Line::from(spans));
             if i == app.wizard_data.selected_image_index {
-                ListItem::new(content).style(
+                item.style(
                     Style::default()
                         .bg(Color::DarkGray)
                         .add_modifier(Modifier::BOLD),
                 )
             } else {
-                ListItem::new(content)
+                item
             }
         })
         .collect();
 
-    let list = List::new(items)
-        .block(block)
-        .style(Style::default().fg(Color::White));
+    let list = List::new(items).style(Style::default().fg(Color::White));
 
-    frame.render_widget(list, area);
+    frame.render_stateful_widget(
+        list,
+        chunks[1],
+        &mut ListState::default().with_selected(Some(app.wizard_data.selected_image_index)),
+    );
 }
 
 fn draw_wizard_type(frame: &mut Frame, area: Rect, app: &App) {
@@ -947,6 +5503,48 @@ fn draw_wizard_type(frame: &mut Frame, area: Rect, app: &App) {
     frame.render_widget(paragraph, area);
 }
 
+fn draw_wizard_media(frame: &mut Frame, area: Rect, app: &App) {
+    let mut block = Block::default()
+        .title(" New Container - Step 4: Installation Media ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Green))
+        .border_type(BorderType::Rounded);
+    if let Some(error) = &app.wizard_data.media_error {
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {} ", error),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let text = vec![
+        Line::from("The VM starts empty. ISO to boot it from, if any:"),
+        Line::from(""),
+        Line::from(
+            [
+                vec![Span::raw("ISO: ")],
+                field_spans(&app.input_buffer, Style::default()),
+            ]
+            .concat(),
+        ),
+        Line::from(""),
+        Line::from(
+            "An absolute path on the server's host, or pool/volume for a custom ISO volume.",
+        ),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Leave it empty to attach media later. Enter or Tab continues.",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(Color::White))
+        .block(block)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
+
 fn draw_wizard_confirm(frame: &mut Frame, area: Rect, app: &App) {
     let block = Block::default()
         .title(" New Container - Confirm ")
@@ -960,15 +5558,56 @@ fn draw_wizard_confirm(frame: &mut Frame, area: Rect, app: &App) {
         "Container"
     };
 
-    let text = vec![
+    let defaults = &app.create_defaults;
+    let or_profiles = |value: &Option<String>| {
+        value
+            .clone()
+            .unwrap_or_else(|| "from the profiles".to_string())
+    };
+    let mut text = vec![
         Line::from("Review your container configuration:"),
         Line::from(""),
-        Line::from(format!("  Name:  {}", app.wizard_data.name)),
-        Line::from(format!("  Image: {}", app.wizard_data.image)),
-        Line::from(format!("  Type:  {}", container_type)),
+        Line::from(format!("  Name:     {}", app.wizard_data.name)),
+        Line::from(if app.wizard_data.is_empty() {
+            "  Image:    none (empty)".to_string()
+        } else {
+            format!("  Image:    {}", app.wizard_data.image)
+        }),
+        Line::from(format!("  Type:     {}", container_type)),
+        Line::from(format!(
+            "  Profiles: {}",
+            if defaults.profiles.is_empty() {
+                "default".to_string()
+            } else {
+                defaults.profiles.join(", ")
+            }
+        )),
+    ];
+    if let Some(media) = app
+        .wizard_data
+        .media
+        .as_ref()
+        .filter(|_| app.wizard_data.is_vm)
+    {
+        text.push(Line::from(format!("  ISO:      {} (boots first)", media)));
+    }
+    if !defaults.limits.is_empty() {
+        text.push(Line::from(format!(
+            "  Limits:   {}",
+            defaults
+                .limits
+                .iter()
+                .map(|(key, value)| format!("{} {}", key, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    text.extend([
+        Line::from(format!("  Pool:     {}", or_profiles(&defaults.pool))),
+        Line::from(format!("  Network:  {}", or_profiles(&defaults.network))),
         Line::from(""),
         Line::from("Press Enter to create or Esc to cancel"),
-    ];
+    ]);
 
     let paragraph = Paragraph::new(text)
         .style(Style::default().fg(Color::White))
@@ -977,3 +5616,6 @@ fn draw_wizard_confirm(frame: &mut Frame, area: Rect, app: &App) {
 
     frame.render_widget(paragraph, area);
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,1277 @@
+//! Snapshot tests for the UI
+//!
+//! Each test renders one screen from fixture state into a `TestBackend` and
+//! compares the text of the buffer with a golden file in
+//! `tests/fixtures/ui`. After an intended layout change, regenerate the
+//! golden files with `UPDATE_SNAPSHOTS=1 cargo test` and review the diff.
+
+use super::draw;
+use crate::accent::Accents;
+use crate::app::{
+    App, CommandMenu, ConfirmAction, InputCallback, InputMode, InputType, LxdOperationTracker,
+    OperationStatus, PendingAction, WizardState,
+};
+use crate::audit::{Audit, AuditEntry, Freshness};
+use crate::batch::ProfileBatch;
+use crate::boot::{BootEntry, BootOrder};
+use crate::capacity::{Capacity, Host, Reservation};
+use crate::clone::CloneForm;
+use crate::command::tests;
+use crate::compare::tests::instance;
+use crate::compare::Comparison;
+use crate::config::{ImagesConfig, QuickConfig, ScheduleConfig, ScheduledAction, UptimeConfig};
+use crate::convert::Conversion;
+use crate::dashboard::{self, Dashboard, PoolUsage};
+use crate::drift::tests::{exported, instance as exported_instance};
+use crate::drift::DriftView;
+use crate::events::LifecycleEvent;
+use crate::idmap::{Disk, IdmapView};
+use crate::images::{ImageChoice, ImageSource, RecentImages};
+use crate::keymap;
+use crate::lxc::{Container, LxcClient};
+use crate::lxd_api::{ExecOutput, LxdApiClient, LxdNetworkZone};
+use crate::metrics::{self, InstanceMetrics};
+use crate::pools::{self, Pool, PoolWarning, PoolsView, Tool};
+use crate::ports::Port;
+use crate::preflight::Check;
+use crate::probe::{Health, Probe};
+use crate::quick::{self, QuickView};
+use crate::schedule::Schedule;
+use crate::search::KeySearch;
+use crate::server::ServerDetails;
+use crate::sidebar::{Filter, Grouping};
+use crate::store::{ImageStore, StoredImage};
+use crate::templates::{InstanceMetadata, TemplatesView};
+use crate::theme::Theme;
+use crate::top::tests::top_with;
+use crate::uptime::{Transition, Uptime};
+use crate::vm::{self, VmSettings};
+use crate::watch::tests::state;
+use crate::watch::Watch;
+use crate::zones::{self, Zone, Zones};
+use chrono::TimeZone;
+use ratatui::{backend::TestBackend, style::Color, Terminal};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tokio::time::{Duration, Instant};
+
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 30;
+
+fn container(name: &str, status: &str, ipv4: &[&str], kind: &str) -> Container {
+    Container {
+        ipv4: ipv4.iter().map(|ip| ip.to_string()).collect(),
+        ..tests::container(name, status, kind)
+    }
+}
+
+/// An app with three containers and a client that is never connected
+fn fixture_app() -> App {
+    let client = LxcClient::from_api(LxdApiClient::unix("/nonexistent/lxd.socket".to_string()));
+    let mut app = App::new(client);
+    app.lxd_status = true;
+    app.load_available_images();
+    *app.containers.try_write().unwrap() = vec![
+        container("web1", "Running", &["10.0.0.10"], "container"),
+        container("db1", "Stopped", &[], "container"),
+        container("vm1", "Running", &["10.0.0.12"], "virtual-machine"),
+    ];
+    app
+}
+
+/// Render `app` and return the buffer as text, one line per row
+fn render_sized(app: &App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|frame| draw(frame, app)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        let row: String = (0..buffer.area.width)
+            .map(|x| buffer.cell((x, y)).map_or(" ", |cell| cell.symbol()))
+            .collect();
+        text.push_str(row.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+fn assert_snapshot(name: &str, app: &App) {
+    assert_snapshot_sized(name, app, WIDTH, HEIGHT);
+}
+
+fn assert_snapshot_sized(name: &str, app: &App, width: u16, height: u16) {
+    let actual = render_sized(app, width, height);
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/ui")
+        .join(format!("{}.txt", name));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}; run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "snapshot {} changed\n--- expected\n{}--- actual\n{}",
+        name,
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn container_list() {
+    let mut app = fixture_app();
+    app.selected = 1;
+    assert_snapshot("container_list", &app);
+}
+
+#[test]
+fn high_contrast_container_list() {
+    let mut app = fixture_app();
+    app.theme = Theme::HighContrast;
+    app.selected = 1;
+    assert_snapshot("high_contrast_container_list", &app);
+}
+
+#[test]
+fn empty_container_list() {
+    let app = fixture_app();
+    app.containers.try_write().unwrap().clear();
+    assert_snapshot("empty_container_list", &app);
+}
+
+#[test]
+fn container_menu() {
+    let mut app = fixture_app();
+    app.show_command_menu(CommandMenu::Container);
+    assert_snapshot_sized("container_menu", &app, WIDTH, 40);
+}
+
+#[test]
+fn container_menu_scrolls_to_the_selection() {
+    let mut app = fixture_app();
+    app.show_command_menu(CommandMenu::Container);
+    let items = keymap::CONTAINER_MENU.len() - 1;
+    for _ in 0..items - 1 {
+        app.menu_next(items);
+    }
+    assert_snapshot("container_menu_scrolled", &app);
+}
+
+#[test]
+fn read_only_system_menu() {
+    let mut app = fixture_app();
+    app.read_only = true;
+    app.show_command_menu(CommandMenu::System);
+    assert_snapshot("read_only_system_menu", &app);
+}
+
+#[test]
+fn system_menu() {
+    let mut app = fixture_app();
+    app.show_command_menu(CommandMenu::System);
+    app.menu_selected = 2;
+    app.server_details = Some(ServerDetails {
+        server: "LXD".to_string(),
+        version: "4.0.9".to_string(),
+        storage: Some("zfs 2.1.5".to_string()),
+        channel: Some("4.0/stable".to_string()),
+        outdated: Some("5.0".to_string()),
+    });
+    assert_snapshot("system_menu", &app);
+}
+
+#[test]
+fn delete_confirmation() {
+    let mut app = fixture_app();
+    app.show_confirm_dialog(
+        "Delete container 'db1'? This action cannot be undone!".to_string(),
+        ConfirmAction::DeleteContainer("db1".to_string()),
+        Vec::new(),
+    );
+    assert_snapshot("delete_confirmation", &app);
+}
+
+#[test]
+fn delete_preflight() {
+    let mut app = fixture_app();
+    app.show_confirm_dialog(
+        "Delete container 'db1'? This action cannot be undone!".to_string(),
+        ConfirmAction::DeleteContainer("db1".to_string()),
+        vec![
+            Check::Blocker("It is running; LXD only deletes stopped instances".to_string()),
+            Check::Warning("2 snapshots deleted with it: snap0, snap1".to_string()),
+            Check::Warning(
+                "Custom volume pgdata on fast (at /var/lib/postgresql) is kept, detached"
+                    .to_string(),
+            ),
+        ],
+    );
+    assert_snapshot("delete_preflight", &app);
+}
+
+#[test]
+fn protected_delete() {
+    let mut app = fixture_app();
+    app.containers.try_write().unwrap()[1].protected = true;
+    app.show_confirm_dialog(
+        "Delete container 'db1'? This action cannot be undone!".to_string(),
+        ConfirmAction::DeleteContainer("db1".to_string()),
+        vec![Check::Protected],
+    );
+    assert_snapshot("protected_delete", &app);
+}
+
+#[test]
+fn accents_mark_the_remote() {
+    let mut app = fixture_app();
+    app.active_remote = Some("prod".to_string());
+    app.accents = Accents::new(&[("prod".to_string(), "red".to_string())].into());
+    app.show_confirm_dialog(
+        "Delete container 'db1'? This action cannot be undone!".to_string(),
+        ConfirmAction::DeleteContainer("db1".to_string()),
+        Vec::new(),
+    );
+    assert_snapshot("accent_delete_confirmation", &app);
+
+    // The remote's name in the title bar is drawn on its accent
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal.draw(|frame| draw(frame, &app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let title: String = (0..WIDTH)
+        .map(|x| buffer.cell((x, 1)).unwrap().symbol())
+        .collect();
+    let x = title.chars().position(|c| c == 'p').unwrap() as u16;
+    assert_eq!(buffer.cell((x, 1)).unwrap().bg, Color::Red);
+}
+
+#[test]
+fn unreachable_remotes_are_flagged_in_the_title() {
+    let mut app = fixture_app();
+    app.unreachable_remotes = vec!["away".to_string()];
+    let title = |app: &App| {
+        let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..WIDTH)
+            .map(|x| buffer.cell((x, 1)).unwrap().symbol().to_string())
+            .collect::<String>()
+    };
+    assert!(title(&app).contains("│ ⚠ away unreachable"));
+
+    app.unreachable_remotes.push("edge".to_string());
+    assert!(title(&app).contains("│ ⚠ 2 remotes unreachable"));
+}
+
+#[test]
+fn config_warnings_are_counted_in_the_title() {
+    let mut app = fixture_app();
+    app.config_warnings = vec![
+        "quick action Shout: '!' is taken by the library, use Enter".to_string(),
+        "workspace prod: unknown sort key".to_string(),
+    ];
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal.draw(|frame| draw(frame, &app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let title: String = (0..WIDTH)
+        .map(|x| buffer.cell((x, 1)).unwrap().symbol())
+        .collect();
+    assert!(title.contains("│ ⚠ 2 config warnings"));
+    assert!(
+        dashboard::warnings(&app).contains(&"Config: workspace prod: unknown sort key".to_string())
+    );
+}
+
+#[test]
+fn space_warning() {
+    let mut app = fixture_app();
+    app.input_mode = InputMode::SpaceWarning {
+        action: PendingAction::CreateContainer,
+        message: "Pool 'fast' would be 97% full after adding about 2.0 GiB \
+                  (31.0 GiB of 32.0 GiB), over the 90% limit"
+            .to_string(),
+    };
+    assert_snapshot("space_warning", &app);
+}
+
+#[test]
+fn error_modal() {
+    let mut app = fixture_app();
+    app.show_error(
+        "Failed to start 'db1'".to_string(),
+        "API error: Instance is already running".to_string(),
+        vec!["Check if LXD is running".to_string()],
+    );
+    assert_snapshot("error_modal", &app);
+}
+
+#[test]
+fn clone_name_input() {
+    let mut app = fixture_app();
+    app.input_mode = InputMode::Input {
+        prompt: "Name for the copy of 'web1':".to_string(),
+        input_type: InputType::ContainerName,
+        callback_action: InputCallback::CloneContainer("web1".to_string()),
+    };
+    app.input_buffer.set("web2");
+    assert_snapshot("clone_name_input", &app);
+}
+
+#[test]
+fn clone_options() {
+    let mut app = fixture_app();
+    let mut form = CloneForm::new(
+        "web1".to_string(),
+        "web2".to_string(),
+        vec!["default".to_string(), "fast".to_string()],
+        vec!["default".to_string()],
+    );
+    form.toggle();
+    form.field = 2;
+    form.cycle(false);
+    app.input_mode = InputMode::CloneOptions(form);
+    assert_snapshot("clone_options", &app);
+}
+
+#[test]
+fn help_screen() {
+    let mut app = fixture_app();
+    app.show_help();
+    assert_snapshot("help_screen", &app);
+}
+
+#[test]
+fn wizard_steps() {
+    let mut app = fixture_app();
+    app.start_new_container_wizard();
+    app.input_buffer.set("test1");
+    assert_snapshot("wizard_name", &app);
+
+    app.wizard_data.name = "test1".to_string();
+    app.wizard_data.selected_image_index = 1;
+    app.input_mode = InputMode::Wizard(WizardState::SelectImage);
+    assert_snapshot("wizard_image", &app);
+
+    app.input_mode = InputMode::Wizard(WizardState::SelectType);
+    assert_snapshot("wizard_type", &app);
+
+    app.wizard_data.image = app.available_images[1].alias.clone();
+    app.input_mode = InputMode::Wizard(WizardState::Confirm);
+    assert_snapshot("wizard_confirm", &app);
+
+    // An empty VM asks for an ISO to boot
+    app.wizard_data.image = "none".to_string();
+    app.wizard_data.is_vm = true;
+    app.input_buffer.set("/var/lib/isos/debian-12.iso");
+    app.input_mode = InputMode::Wizard(WizardState::Media);
+    assert_snapshot("wizard_media", &app);
+
+    app.input_buffer.set("debian-12.iso");
+    app.wizard_data.media_error =
+        Some("An ISO is an absolute path on the server or a pool/volume".to_string());
+    assert_snapshot("wizard_media_refused", &app);
+}
+
+#[test]
+fn wizard_image_search() {
+    let mut app = fixture_app();
+    let mut recent = RecentImages::new(&ImagesConfig::default()).unwrap();
+    recent.aliases = vec!["ubuntu:24.04".to_string(), "images:debian/12".to_string()];
+    app.images.recent = Some(recent);
+    app.images.local = vec![ImageChoice::new(
+        "golden-web",
+        "Published from web1",
+        ImageSource::Local,
+    )];
+    app.images.remote = vec![
+        ImageChoice::new("images:debian/12", "Debian bookworm", ImageSource::Remote),
+        ImageChoice::new("images:debian/13", "Debian trixie", ImageSource::Remote),
+    ];
+    app.start_new_container_wizard();
+    app.input_mode = InputMode::Wizard(WizardState::SelectImage);
+    assert_snapshot("wizard_image_recent", &app);
+
+    app.wizard_data.image_query = "deb".to_string();
+    app.wizard_data.selected_image_index = 2;
+    assert_snapshot("wizard_image_search", &app);
+}
+
+#[test]
+fn image_column() {
+    let mut app = fixture_app();
+    app.image_column = true;
+    {
+        let mut containers = app.containers.try_write().unwrap();
+        containers[0].image = Some("Ubuntu noble amd64 (20240101_07:42)".to_string());
+        containers[1].base_image = Some("8d2e6c1f0a9b4e7d3c5a".to_string());
+    }
+    assert_snapshot("image_column", &app);
+}
+
+#[test]
+fn ports_column() {
+    let app = fixture_app();
+    {
+        let mut containers = app.containers.try_write().unwrap();
+        let port = |listen: &str, target: &str, protocol: &str| Port {
+            protocol: protocol.to_string(),
+            listen: listen.to_string(),
+            target: target.to_string(),
+        };
+        containers[0].ports = vec![port("8080", "80", "tcp"), port("8443", "443", "tcp")];
+        containers[2].ports = vec![port("192.0.2.1:53", "53", "udp")];
+    }
+    assert_snapshot("ports_column", &app);
+}
+
+#[test]
+fn schedule_column() {
+    let mut app = fixture_app();
+    // Due on 31 February, so never: the column has nothing to time
+    for (name, action) in [
+        ("web1", ScheduledAction::Stop),
+        ("db1", ScheduledAction::Start),
+    ] {
+        let job = ScheduleConfig {
+            instance: name.to_string(),
+            action,
+            schedule: "0 8 31 2 *".to_string(),
+        };
+        app.schedules
+            .push((job, Schedule::parse("0 8 31 2 *").unwrap()));
+    }
+    app.power_overrides.toggle_hold("web1");
+    assert_snapshot("schedule_column", &app);
+}
+
+#[test]
+fn health_column() {
+    let mut app = fixture_app();
+    {
+        let mut containers = app.containers.try_write().unwrap();
+        for container in containers.iter_mut() {
+            container.healthcheck = Some("systemctl is-system-running".to_string());
+        }
+        app.probes.due(&containers, Instant::now());
+    }
+    let probe = |health| Probe {
+        health,
+        output: String::new(),
+        at: Instant::now(),
+    };
+    let results = app.probes.sender();
+    results
+        .send(("web1".to_string(), probe(Health::Healthy)))
+        .unwrap();
+    results
+        .send(("vm1".to_string(), probe(Health::Unhealthy)))
+        .unwrap();
+    app.probes.drain();
+    assert_snapshot("health_column", &app);
+}
+
+#[test]
+fn watch_view() {
+    let mut app = fixture_app();
+    let start = Instant::now();
+    let mut watch = Watch::new("web1".to_string());
+    watch.image = Some("Ubuntu noble amd64 (20240101_07:42)".to_string());
+    watch.restart = true;
+    watch.dns_names = vec!["web1.lxd".to_string(), "web1.lxd.example.net".to_string()];
+    watch.record(Ok(state("Running", 100, 0, Some("10.0.0.10"), 0)), start);
+    watch.record(
+        Ok(state(
+            "Running",
+            200,
+            250_000_000,
+            Some("10.0.0.10"),
+            10_240,
+        )),
+        start + Duration::from_secs(1),
+    );
+    let scrape = metrics::parse(concat!(
+        r#"lxd_cpu_seconds_total{cpu="0",mode="user",name="web1"} 12.5"#,
+        "\n",
+        r#"lxd_cpu_seconds_total{cpu="0",mode="system",name="web1"} 4"#,
+        "\n",
+        r#"lxd_memory_MemTotal_bytes{name="web1"} 1073741824"#,
+        "\n",
+        r#"lxd_memory_MemAvailable_bytes{name="web1"} 536870912"#,
+        "\n",
+        r#"lxd_filesystem_size_bytes{mountpoint="/",name="web1"} 10737418240"#,
+        "\n",
+        r#"lxd_filesystem_avail_bytes{mountpoint="/",name="web1"} 3221225472"#,
+    ))
+    .unwrap();
+    watch.record_metrics(Ok(InstanceMetrics::of(&scrape, "web1")), start);
+    let at = chrono::Local
+        .with_ymd_and_hms(2024, 1, 1, 12, 0, 0)
+        .unwrap();
+    for event in &mut watch.events {
+        event.at = at;
+    }
+    app.watch = Some(watch);
+    app.input_mode = InputMode::Watch;
+    assert_snapshot("watch_view", &app);
+}
+
+#[test]
+fn watch_view_uptime() {
+    let mut app = fixture_app();
+    let mut watch = Watch::new("web1".to_string());
+    watch.record(
+        Ok(state("Running", 100, 0, Some("10.0.0.10"), 0)),
+        Instant::now(),
+    );
+    for event in &mut watch.events {
+        event.at = chrono::Local
+            .with_ymd_and_hms(2024, 1, 1, 12, 0, 0)
+            .unwrap();
+    }
+    app.watch = Some(watch);
+    app.input_mode = InputMode::Watch;
+
+    // Down for 84 minutes two days ago, in the middle of a part of the strip
+    let now = chrono::Local::now();
+    let down = now - chrono::TimeDelta::days(2) - chrono::TimeDelta::hours(3);
+    let mut uptime = Uptime::new(&UptimeConfig::default()).unwrap();
+    uptime.timelines.insert(
+        "web1".to_string(),
+        vec![
+            Transition {
+                at: now - chrono::TimeDelta::days(10),
+                up: true,
+            },
+            Transition {
+                at: down,
+                up: false,
+            },
+            Transition {
+                at: down + chrono::TimeDelta::minutes(84),
+                up: true,
+            },
+        ],
+    );
+    app.uptime = Some(uptime);
+    assert_snapshot("watch_view_uptime", &app);
+}
+
+#[test]
+fn watch_view_notes() {
+    let mut app = fixture_app();
+    let mut watch = Watch::new("web1".to_string());
+    watch.record(
+        Ok(state("Running", 100, 0, Some("10.0.0.10"), 0)),
+        Instant::now(),
+    );
+    for event in &mut watch.events {
+        event.at = chrono::Local
+            .with_ymd_and_hms(2024, 1, 1, 12, 0, 0)
+            .unwrap();
+    }
+    watch.notes = Some(
+        "## Restarting\nDrain it **first**:\n- stop `nginx`\n  - wait for workers\n\
+         > Never on a Friday\n```\nsystemctl restart app\n```"
+            .to_string(),
+    );
+    app.watch = Some(watch);
+    app.input_mode = InputMode::Watch;
+    assert_snapshot("watch_view_notes", &app);
+}
+
+#[test]
+fn event_panel() {
+    let mut app = fixture_app();
+    app.show_events = true;
+    let at = chrono::Local
+        .with_ymd_and_hms(2024, 1, 1, 12, 0, 0)
+        .unwrap();
+    let event = |action: &str, entity: &str, requestor: Option<&str>| LifecycleEvent {
+        at,
+        remote: None,
+        action: action.to_string(),
+        entity: entity.to_string(),
+        requestor: requestor.map(str::to_string),
+    };
+    app.events.events = [
+        event("instance-created", "web2", Some("alice (tls)")),
+        event("instance-started", "web2", Some("alice (tls)")),
+        event("instance-snapshot-created", "db1/nightly", None),
+        event("instance-stopped", "vm1", Some("root (unix)")),
+    ]
+    .into();
+    assert_snapshot("event_panel", &app);
+}
+
+#[test]
+fn operations_sidebar_grouped_by_status() {
+    let mut app = fixture_app();
+    for (name, status) in [
+        ("web1", OperationStatus::Success),
+        ("db1", OperationStatus::Failed("no space".to_string())),
+        ("vm1", OperationStatus::Running),
+        ("web1", OperationStatus::Cancelled),
+        ("db1", OperationStatus::Success),
+    ] {
+        let id = app.register_operation(format!("Start '{}'", name), Some(name.to_string()));
+        app.user_operations
+            .iter_mut()
+            .find(|op| op.id == id)
+            .unwrap()
+            .status = status;
+    }
+    app.focus_operations();
+    app.arrange_operations(Filter::All, Grouping::Status);
+    app.select_operation(true);
+    assert_snapshot("operations_sidebar_grouped", &app);
+}
+
+#[test]
+fn tour_steps_outline_the_real_screen() {
+    let mut app = fixture_app();
+    app.start_tour();
+    app.next_tour_step();
+    app.next_tour_step();
+    assert_snapshot("tour_container_menu", &app);
+
+    app.next_tour_step();
+    app.next_tour_step();
+    assert_snapshot("tour_sidebar", &app);
+}
+
+#[test]
+fn title_bar_counts_background_operations() {
+    let mut app = fixture_app();
+    for (name, progress) in [("web1", Some(40)), ("db1", Some(60)), ("vm1", None)] {
+        let id = app.register_operation(format!("Backup '{}'", name), Some(name.to_string()));
+        app.start_operation(&id);
+        app.lxd_operations.insert(
+            id.clone(),
+            LxdOperationTracker {
+                ui_operation_id: id,
+                lxd_operation_path: format!("/1.0/operations/{}", name),
+                description: format!("Backup '{}'", name),
+                container_name: name.to_string(),
+                action: "backup".to_string(),
+                started_at: Instant::now(),
+                next_check: Instant::now(),
+                status_code: 103,
+                progress,
+                success_message: String::new(),
+            },
+        );
+    }
+    // Only the operations with a known percentage count towards it
+    assert_snapshot("title_operations", &app);
+}
+
+#[test]
+fn progress_asks_before_cancelling() {
+    let mut app = fixture_app();
+    let id = app.register_operation("Backup 'db1'".to_string(), Some("db1".to_string()));
+    app.show_progress(&id);
+    assert_snapshot("progress", &app);
+
+    app.confirm_cancel_progress(true);
+    assert_snapshot("progress_cancel", &app);
+}
+
+#[test]
+fn operation_details() {
+    let mut app = fixture_app();
+    let id = app.register_operation("Start container 'db1'".to_string(), Some("db1".to_string()));
+    app.complete_operation(
+        &id,
+        false,
+        Some("Failed to start device \"eth0\": Parent device \"lxdbr1\" doesn't exist".to_string()),
+    );
+    app.user_operations[0].lxd_operation = Some(
+        serde_json::from_value(serde_json::json!({
+            "id": "6916c8a6-9b7d-4abd-90b3-aedfec7f6cb1",
+            "class": "task",
+            "description": "Starting instance",
+            "created_at": "2024-01-01T12:00:00Z",
+            "updated_at": "2024-01-01T12:00:01Z",
+            "status": "Failure",
+            "status_code": 400,
+            "resources": {"instances": ["/1.0/instances/db1"]},
+            "metadata": null,
+            "may_cancel": false,
+            "err": "Failed to start device \"eth0\"",
+            "location": "none",
+        }))
+        .unwrap(),
+    );
+    app.user_operations[0].finished_at = Some(
+        chrono::Local
+            .with_ymd_and_hms(2024, 1, 1, 12, 0, 1)
+            .unwrap(),
+    );
+    app.focus_operations();
+    app.input_mode = InputMode::OperationDetails { id, scroll: 0 };
+    assert_snapshot("operation_details", &app);
+}
+
+#[test]
+fn compare_view() {
+    let mut app = fixture_app();
+    let staging = instance(
+        "web1",
+        &["default"],
+        serde_json::json!({"limits.memory": "2GiB", "image.os": "Ubuntu", "image.release": "jammy"}),
+    );
+    let prod = instance(
+        "vm1",
+        &["default", "prod"],
+        serde_json::json!({"limits.memory": "8GiB", "limits.cpu": "4", "image.os": "Ubuntu", "image.release": "noble"}),
+    );
+    app.compare = Some(Comparison::new(
+        "web1".to_string(),
+        "vm1".to_string(),
+        &staging,
+        &prod,
+    ));
+    app.input_mode = InputMode::Compare;
+    assert_snapshot("compare_view", &app);
+}
+
+#[test]
+fn top_view() {
+    let mut app = fixture_app();
+    let running =
+        |cpu_secs: i64, rx: i64| state("Running", 100, cpu_secs * 1_000_000_000, None, rx);
+    app.top = Some(top_with(&[
+        vec![("web1", running(0, 0)), ("vm1", running(0, 0))],
+        vec![("web1", running(1, 0)), ("vm1", running(0, 5 << 20))],
+    ]));
+    app.input_mode = InputMode::Top;
+    assert_snapshot("top_view", &app);
+}
+
+#[test]
+fn dashboard() {
+    let mut app = fixture_app();
+    let running =
+        |cpu_secs: i64, rx: i64| state("Running", 100, cpu_secs * 1_000_000_000, None, rx);
+    app.top = Some(top_with(&[
+        vec![("web1", running(0, 0)), ("vm1", running(0, 0))],
+        vec![("web1", running(1, 0)), ("vm1", running(0, 5 << 20))],
+    ]));
+    let mut dashboard = Dashboard::new();
+    let pool = |name: &str, driver: &str, used: u64, total: u64| PoolUsage {
+        name: name.to_string(),
+        driver: driver.to_string(),
+        used,
+        total,
+    };
+    dashboard.record(
+        Ok(vec![
+            pool("default", "dir", 10 << 30, 40 << 30),
+            pool("fast", "zfs", 29 << 30, 32 << 30),
+            pool("remote", "ceph", 0, 0),
+        ]),
+        Instant::now(),
+    );
+    app.dashboard = Some(dashboard);
+    app.schedule_warning = Some("web1: invalid schedule".to_string());
+    let at = chrono::Local
+        .with_ymd_and_hms(2024, 1, 1, 12, 0, 0)
+        .unwrap();
+    app.events.events = [LifecycleEvent {
+        at,
+        remote: None,
+        action: "instance-stopped".to_string(),
+        entity: "db1".to_string(),
+        requestor: Some("root (unix)".to_string()),
+    }]
+    .into();
+    app.input_mode = InputMode::Dashboard;
+    assert_snapshot("dashboard", &app);
+}
+
+#[test]
+fn boot_order_view() {
+    let mut app = fixture_app();
+    let config = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    };
+    let mut order = BootOrder::new();
+    order.set_entries(vec![
+        BootEntry::from_config(
+            "web1",
+            true,
+            &config(&[("boot.autostart", "true"), ("boot.autostart.delay", "5")]),
+        ),
+        BootEntry::from_config(
+            "db1",
+            false,
+            &config(&[
+                ("boot.autostart", "true"),
+                ("boot.autostart.priority", "10"),
+                ("boot.autostart.delay", "20"),
+            ]),
+        ),
+        BootEntry::from_config("vm1", true, &config(&[("boot.autostart", "false")])),
+    ]);
+    order.select(true);
+    app.boot_order = Some(order);
+    app.input_mode = InputMode::BootOrder;
+    assert_snapshot("boot_order_view", &app);
+}
+
+#[test]
+fn audit_view() {
+    let mut app = fixture_app();
+    let outdated = |name: &str, running: bool| AuditEntry {
+        name: name.to_string(),
+        running,
+        image: "Ubuntu noble amd64 (20240401_07:42)".to_string(),
+        base_image: "8d2e6c1f0a9b4e7d3c5a".to_string(),
+        freshness: Freshness::Outdated {
+            fingerprint: "5c4b3a2918f7e6d5c4b3".to_string(),
+            created_at: "2024-06-01T07:42:00Z".to_string(),
+        },
+    };
+    let mut audit = Audit::new();
+    audit.set_entries(vec![
+        outdated("web1", false),
+        outdated("web2", true),
+        AuditEntry {
+            freshness: Freshness::Current,
+            ..outdated("db1", false)
+        },
+    ]);
+    audit.status = Some("Rebuilt 'app1' from image 5c4b3a2918f7".to_string());
+    app.audit = Some(audit);
+    app.input_mode = InputMode::Audit {
+        confirm_rebuild: true,
+    };
+    assert_snapshot("audit_view", &app);
+}
+
+#[test]
+fn zones_view() {
+    let mut app = fixture_app();
+    let zone = |name: &str, used_by: &[&str], records: &[&str]| {
+        Zone::new(
+            LxdNetworkZone {
+                name: name.to_string(),
+                description: String::new(),
+                used_by: used_by
+                    .iter()
+                    .map(|network| format!("/1.0/networks/{}", network))
+                    .collect(),
+            },
+            records
+                .iter()
+                .map(|record| zones::parse_record(record).unwrap())
+                .collect(),
+        )
+    };
+    let mut view = Zones::new();
+    view.set_zones(vec![
+        zone("0.0.10.in-addr.arpa", &["lxdbr0"], &[]),
+        zone(
+            "lxd.example.net",
+            &["lxdbr0", "lxdbr1"],
+            &[
+                "www A 10.0.0.10, AAAA fd42::10",
+                "mail MX 10 mx.example.net.",
+            ],
+        ),
+        zone("lab.example.net", &[], &["gw A 10.1.0.1"]),
+    ]);
+    view.select(true);
+    view.select(true);
+    view.status = Some("Created record 'www.lxd.example.net'".to_string());
+    app.zones = Some(view);
+    app.input_mode = InputMode::Zones {
+        confirm_delete: true,
+    };
+    assert_snapshot("zones_view", &app);
+}
+
+#[test]
+fn image_store_view() {
+    let mut app = fixture_app();
+    let image =
+        |fingerprint: &str, description: &str, source: Option<&str>, auto_update| StoredImage {
+            fingerprint: fingerprint.to_string(),
+            description: description.to_string(),
+            aliases: Vec::new(),
+            source: source.map(str::to_string),
+            auto_update,
+            cached: true,
+            size: 132 << 20,
+            created_at: "2024-10-01T04:12:00Z".to_string(),
+            expires_at: None,
+        };
+    // Published to expire in three days
+    let golden = StoredImage {
+        expires_at: Some((chrono::Local::now() + chrono::TimeDelta::hours(73)).fixed_offset()),
+        ..image("ccc333", "golden-web", None, false)
+    };
+    let mut view = ImageStore::new();
+    view.set_images(vec![
+        image(
+            "aaa111",
+            "Debian bookworm amd64",
+            Some("images:debian/12"),
+            true,
+        ),
+        image("bbb222", "Ubuntu noble amd64", Some("ubuntu:24.04"), false),
+        golden.clone(),
+    ]);
+    app.expiring_images = vec![golden];
+    view.select(true);
+    view.status = Some("Refreshed image 'Debian bookworm amd64'".to_string());
+    app.image_store = Some(view);
+    app.input_mode = InputMode::ImageStore;
+    assert_snapshot("image_store_view", &app);
+}
+
+#[test]
+fn pools_view() {
+    let mut app = fixture_app();
+    let pool = |name: &str, driver: &str, used: u64, tool| Pool {
+        name: name.to_string(),
+        driver: driver.to_string(),
+        status: "Created".to_string(),
+        used: used << 30,
+        total: 100 << 30,
+        tool,
+        warnings: Vec::new(),
+    };
+    let mut view = PoolsView::new(true);
+    view.set_pools(vec![
+        pool("default", "dir", 12, None),
+        Pool {
+            warnings: vec![PoolWarning {
+                message: "Pool is degraded".to_string(),
+                severity: "high".to_string(),
+                count: 3,
+                last_seen: "2024-06-01 08:30".to_string(),
+            }],
+            ..pool(
+                "fast",
+                "zfs",
+                61,
+                Some(Tool::Zfs {
+                    zpool: "fast".to_string(),
+                }),
+            )
+        },
+        pool(
+            "slow",
+            "btrfs",
+            40,
+            Some(Tool::Btrfs {
+                mountpoint: "/var/lib/lxd/storage-pools/slow".into(),
+            }),
+        ),
+    ]);
+    view.checks.insert(
+        "fast".to_string(),
+        Ok(pools::Health {
+            state: "DEGRADED".to_string(),
+            healthy: false,
+            scrub: Some(
+                "scrub repaired 0B in 00:02:11 with 0 errors on Sun Jun  2 00:26:12 2024"
+                    .to_string(),
+            ),
+            errors: vec![
+                "sdb FAULTED: 0 read, 0 write, 0 checksum errors (too many errors)".to_string(),
+            ],
+        }),
+    );
+    view.checks.insert(
+        "slow".to_string(),
+        Err("btrfs isn't installed on this host".to_string()),
+    );
+    view.select(true);
+    view.status = Some("Started scrubbing 'fast'".to_string());
+    app.pools = Some(view);
+    app.input_mode = InputMode::Pools {
+        confirm_scrub: true,
+    };
+    assert_snapshot("pools_view", &app);
+}
+
+#[test]
+fn capacity_view() {
+    let mut app = fixture_app();
+    let reservation =
+        |name: &str, virtual_machine, running, cpus, memory: Option<u64>| Reservation {
+            name: name.to_string(),
+            virtual_machine,
+            running,
+            cpus,
+            memory: memory.map(|gib| gib << 30),
+        };
+    let mut view = Capacity::new();
+    view.set_reservations(
+        Host {
+            cpus: 8,
+            memory: 16 << 30,
+        },
+        vec![
+            reservation("web1", false, true, None, Some(2)),
+            reservation("db1", false, false, Some(2), Some(6)),
+            reservation("vm1", true, true, Some(4), Some(8)),
+            reservation("vm2", true, false, Some(6), Some(1)),
+        ],
+    );
+    app.capacity = Some(view);
+    app.input_mode = InputMode::Capacity;
+    assert_snapshot("capacity_view", &app);
+}
+
+#[test]
+fn vm_settings_view() {
+    let mut app = fixture_app();
+    let config = HashMap::from([("limits.cpu".to_string(), "0-3".to_string())]);
+    let mut expanded = config.clone();
+    expanded.insert("volatile.vsock_id".to_string(), "42".to_string());
+    let mut view = VmSettings::new("vm1".to_string());
+    view.set_settings(true, vm::settings(&config, &expanded, &HashMap::new(), &[]));
+    view.select(true);
+    app.vm_settings = Some(view);
+    app.input_mode = InputMode::VmSettings;
+    assert_snapshot("vm_settings_view", &app);
+}
+
+#[test]
+fn recreate_view() {
+    let mut app = fixture_app();
+    let mut web1 = instance("web1", &["default"], serde_json::json!({}));
+    web1.config = HashMap::from([
+        ("image.os".to_string(), "Debian".to_string()),
+        ("image.release".to_string(), "bookworm".to_string()),
+        ("limits.memory".to_string(), "2GiB".to_string()),
+        ("security.nesting".to_string(), "true".to_string()),
+    ]);
+    web1.devices = HashMap::from([(
+        "data".to_string(),
+        HashMap::from([
+            ("type".to_string(), "disk".to_string()),
+            ("pool".to_string(), "fast".to_string()),
+            ("source".to_string(), "web-data".to_string()),
+            ("path".to_string(), "/srv".to_string()),
+        ]),
+    )]);
+    let plan = Conversion::plan(
+        &web1,
+        "web1".to_string(),
+        "before-recreate-20240101-1200".to_string(),
+    );
+    app.recreate = Some(plan);
+    app.input_mode = InputMode::Recreate;
+    assert_snapshot("recreate_view", &app);
+}
+
+#[test]
+fn key_search_view() {
+    let mut app = fixture_app();
+    let mut web1 = instance(
+        "web1",
+        &["default"],
+        serde_json::json!({"security.privileged": "true", "raw.lxc": "lxc.apparmor.profile=unconfined\nlxc.mount.auto=proc:rw"}),
+    );
+    web1.config
+        .insert("security.privileged".to_string(), "true".to_string());
+    let db1 = instance(
+        "db1",
+        &["default", "trusted"],
+        serde_json::json!({"security.privileged": "true", "security.nesting": "true"}),
+    );
+    let mut view = KeySearch::new("security.*".to_string());
+    view.set_instances(&[
+        web1,
+        db1,
+        instance("cache", &["default"], serde_json::json!({})),
+    ]);
+    app.key_search = Some(view);
+    app.input_mode = InputMode::KeySearch;
+    assert_snapshot("key_search_view", &app);
+}
+
+#[test]
+fn drift_view() {
+    let mut app = fixture_app();
+    let then = exported();
+    let mut now = exported();
+    now.server = "local".to_string();
+    now.instances.remove("db1");
+    now.instances.insert(
+        "web2".to_string(),
+        exported_instance(&[("limits.cpu", "1")], "Running"),
+    );
+    let web1 = now.instances.get_mut("web1").unwrap();
+    web1.config
+        .insert("limits.cpu".to_string(), "4".to_string());
+    web1.config
+        .insert("boot.autostart".to_string(), "true".to_string());
+    now.profiles.get_mut("default").unwrap().description = "Default profile".to_string();
+    let mut view = DriftView::new(
+        PathBuf::from("/home/user/.local/state/lxtui/inventory/inventory-prod.yaml"),
+        "local".to_string(),
+    );
+    view.set_inventories(&then, &now);
+    view.select(true);
+    app.drift = Some(view);
+    app.input_mode = InputMode::Drift;
+    assert_snapshot("drift_view", &app);
+}
+
+#[test]
+fn profiles_view() {
+    let mut app = fixture_app();
+    let mut view = ProfileBatch::new(vec![
+        "web1".to_string(),
+        "web2".to_string(),
+        "prod:db1".to_string(),
+    ]);
+    view.set_profiles(vec!["default".to_string(), "monitoring".to_string()]);
+    view.targets[0].profiles = Ok(vec!["default".to_string()]);
+    view.targets[1].profiles = Ok(vec!["default".to_string(), "monitoring".to_string()]);
+    view.targets[2].profiles = Err("Instance not found".to_string());
+    view.select(true);
+    view.finish(vec![(
+        "web1".to_string(),
+        Ok(vec!["default".to_string(), "monitoring".to_string()]),
+    )]);
+    view.status = Some("Added 'monitoring' to 1, 1 unchanged".to_string());
+    app.picked = vec!["web1".to_string(), "web2".to_string()];
+    app.profile_batch = Some(view);
+    app.input_mode = InputMode::Profiles;
+    assert_snapshot("profiles_view", &app);
+}
+
+fn quick_view(names: &[&str]) -> QuickView {
+    let names = names.iter().map(|n| n.to_string()).collect();
+    QuickView::new(QuickConfig::default().actions, names)
+}
+
+#[test]
+fn quick_library_view() {
+    let mut app = fixture_app();
+    let mut view = quick_view(&["web1", "web2", "prod:db1"]);
+    view.selected = 3;
+    app.quick = Some(view);
+    app.input_mode = InputMode::Quick { confirm: Some(3) };
+    assert_snapshot("quick_library_view", &app);
+}
+
+#[test]
+fn quick_results_view() {
+    let mut app = fixture_app();
+    let mut view = quick_view(&["web1", "web2", "prod:db1"]);
+    let disk = view.actions[2].clone();
+    view.start(disk, "op".to_string());
+    let tx = view.sender();
+    let df = "Filesystem      Size  Used Avail Use% Mounted on\n\
+              /dev/sda1        10G  4.0G  6.0G  40% /\n\
+              tmpfs           2.0G     0  2.0G   0% /dev/shm\n";
+    let output = ExecOutput {
+        status: 0,
+        stdout: df.to_string(),
+        stderr: String::new(),
+    };
+    tx.send(("web1".to_string(), Ok(output))).unwrap();
+    tx.send((
+        "web2".to_string(),
+        Err(quick::Outcome::Failed(
+            "Instance is not running".to_string(),
+        )),
+    ))
+    .unwrap();
+    view.drain();
+    app.quick = Some(view);
+    app.input_mode = InputMode::Quick { confirm: None };
+    assert_snapshot("quick_results_view", &app);
+}
+
+#[test]
+fn quick_results_expanded() {
+    let mut app = fixture_app();
+    let mut view = quick_view(&["web1", "web2", "prod:db1"]);
+    let disk = view.actions[2].clone();
+    view.start(disk, "op".to_string());
+    let tx = view.sender();
+    for name in ["web1", "web2", "prod:db1"] {
+        let output = ExecOutput {
+            status: 0,
+            stdout: "/dev/sda1        10G  4.0G  6.0G  40% /\n".to_string(),
+            stderr: String::new(),
+        };
+        tx.send((name.to_string(), Ok(output))).unwrap();
+    }
+    view.drain();
+    view.expanded = true;
+    app.quick = Some(view);
+    app.input_mode = InputMode::Quick { confirm: None };
+    assert_snapshot("quick_results_expanded", &app);
+}
+
+#[test]
+fn idmap_view() {
+    let mut app = fixture_app();
+    let mut view = IdmapView::new("web1".to_string());
+    view.lines = vec![
+        "both 1000 1000".to_string(),
+        "uid 50-59 500-505".to_string(),
+    ];
+    view.disks = vec![Disk {
+        name: "data".to_string(),
+        source: "/srv/data".to_string(),
+        shift: true,
+    }];
+    view.running = true;
+    view.restart_needed = true;
+    view.select(true);
+    app.idmap = Some(view);
+    app.input_mode = InputMode::Idmap;
+    assert_snapshot("idmap_view", &app);
+}
+
+#[test]
+fn templates_view() {
+    let mut app = fixture_app();
+    let metadata: InstanceMetadata = serde_yaml::from_str(
+        "
+properties:
+  os: Ubuntu
+  release: noble
+templates:
+  /etc/hostname:
+    when: [create, copy]
+    template: hostname.tpl
+  /etc/hosts:
+    when: [create, copy]
+    template: hosts.tpl
+",
+    )
+    .unwrap();
+    let mut view = TemplatesView::new("web1".to_string());
+    view.load(
+        metadata,
+        vec!["hostname.tpl".to_string(), "unused.tpl".to_string()],
+    );
+    view.status = Some("Saved hostname.tpl".to_string());
+    app.templates = Some(view);
+    app.input_mode = InputMode::Templates;
+    assert_snapshot("templates_view", &app);
+}
@@ -0,0 +1,95 @@
+//! Undo and redo of the list view
+//!
+//! A stack of `ViewState`s taken around every action; nothing on the server
+//! is ever undone.
+
+use crate::filter::ContainerFilter;
+use crate::workspace::SortKey;
+
+/// Most changes kept to undo
+const LIMIT: usize = 100;
+
+/// What the list shows and how, as far as undo is concerned
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewState {
+    pub filter: Option<ContainerFilter>,
+    pub sort: Option<SortKey>,
+    pub group_by_tag: bool,
+    pub workspace: Option<usize>,
+    pub compare_marks: Vec<String>,
+    pub picked: Vec<String>,
+    pub image_column: bool,
+    pub show_sidebar: bool,
+    pub show_events: bool,
+}
+
+impl ViewState {
+    /// Names of what differs from `other`, for the status line. The
+    /// workspace goes unnamed, as switching one changes the rest.
+    pub fn changes(&self, other: &ViewState) -> Vec<&'static str> {
+        [
+            (self.filter != other.filter, "filter"),
+            (self.sort != other.sort, "sort"),
+            (self.group_by_tag != other.group_by_tag, "grouping"),
+            (self.compare_marks != other.compare_marks, "marks"),
+            (self.picked != other.picked, "picks"),
+            (self.image_column != other.image_column, "columns"),
+            (self.show_sidebar != other.show_sidebar, "sidebar"),
+            (self.show_events != other.show_events, "event feed"),
+        ]
+        .into_iter()
+        .filter_map(|(changed, name)| changed.then_some(name))
+        .collect()
+    }
+}
+
+/// A view to go back to, with the row that was selected in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub view: ViewState,
+    pub selected: usize,
+}
+
+/// Views undone and redone, newest last
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl UndoStack {
+    /// Keep `before`, the view an action just changed. A command run from
+    /// the command line changes the view inside the action that submitted
+    /// it, so the same view arrives twice and is kept once.
+    pub fn record(&mut self, before: Snapshot) {
+        if self
+            .undo
+            .last()
+            .is_some_and(|last| last.view == before.view)
+        {
+            return;
+        }
+        self.undo.push(before);
+        if self.undo.len() > LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// The view to go back to from `current`, which can then be redone
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// The view last undone, going back to `current` on the next undo
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,57 @@
+//! Tests of undo and redo of the list view
+
+use super::{Snapshot, UndoStack, ViewState};
+use crate::workspace::SortKey;
+
+fn view(group_by_tag: bool, sort: Option<SortKey>) -> Snapshot {
+    Snapshot {
+        view: ViewState {
+            filter: None,
+            sort,
+            group_by_tag,
+            workspace: None,
+            compare_marks: Vec::new(),
+            picked: Vec::new(),
+            image_column: false,
+            show_sidebar: true,
+            show_events: false,
+        },
+        selected: 0,
+    }
+}
+
+#[test]
+fn undoes_and_redoes_in_turn_until_a_new_change() {
+    let (plain, grouped, sorted) = (
+        view(false, None),
+        view(true, None),
+        view(true, Some(SortKey::Name)),
+    );
+    let mut stack = UndoStack::default();
+    stack.record(plain.clone());
+    stack.record(grouped.clone());
+
+    assert_eq!(stack.undo(sorted.clone()), Some(grouped.clone()));
+    assert_eq!(stack.undo(grouped.clone()), Some(plain.clone()));
+    assert_eq!(stack.undo(plain.clone()), None);
+    assert_eq!(stack.redo(plain.clone()), Some(grouped.clone()));
+    assert_eq!(stack.redo(grouped.clone()), Some(sorted.clone()));
+    assert_eq!(stack.redo(sorted.clone()), None);
+
+    // A fresh change after undoing drops what could be redone
+    assert_eq!(stack.undo(sorted), Some(grouped.clone()));
+    stack.record(grouped.clone());
+    assert_eq!(stack.redo(plain), None);
+}
+
+#[test]
+fn names_what_changed_and_keeps_a_nested_change_once() {
+    let (plain, sorted) = (view(false, None), view(true, Some(SortKey::Status)));
+    assert_eq!(plain.view.changes(&sorted.view), ["sort", "grouping"]);
+
+    let mut stack = UndoStack::default();
+    stack.record(plain.clone());
+    stack.record(plain.clone());
+    assert_eq!(stack.undo(sorted.clone()), Some(plain.clone()));
+    assert_eq!(stack.undo(plain), None);
+}
@@ -0,0 +1,247 @@
+//! Uptime history
+//!
+//! A saved timeline of the state changes LXTUI sees per instance, summed
+//! into a week's availability for the watch view. Only what LXTUI saw is
+//! known; time before an instance was first seen counts as unknown.
+
+use crate::config::UptimeConfig;
+use crate::events::LifecycleEvent;
+use crate::lxc::Container;
+use chrono::{DateTime, Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// The span the availability figure and strip cover
+pub const WINDOW: TimeDelta = TimeDelta::days(7);
+
+/// Changes kept for each instance, however recent
+const MAX_TRANSITIONS: usize = 1000;
+
+/// A restart event this soon after the instance was seen coming back up
+/// is that same restart
+const SAME_RESTART: TimeDelta = TimeDelta::minutes(1);
+
+#[derive(Debug, Error)]
+pub enum UptimeError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Invalid JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// An instance seen going up or down
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub at: DateTime<Local>,
+    pub up: bool,
+}
+
+/// How one stretch of the window went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    Up,
+    Partly, // Down for some of it
+    Down,
+    Unknown, // Before the instance was first seen
+}
+
+/// An instance's uptime over the window
+#[derive(Debug, Clone, PartialEq)]
+pub struct Availability {
+    pub percent: f64, // Of the time known
+    pub restarts: usize,
+    pub since: Option<DateTime<Local>>, // First seen, when within the window
+}
+
+/// Time up and time known between `from` and `to`
+fn time_up(
+    timeline: &[Transition],
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+) -> (TimeDelta, TimeDelta) {
+    let mut up = TimeDelta::zero();
+    let mut known = TimeDelta::zero();
+    for (i, transition) in timeline.iter().enumerate() {
+        let start = transition.at.max(from);
+        let end = timeline.get(i + 1).map_or(to, |next| next.at).min(to);
+        if end <= start {
+            continue;
+        }
+        known += end - start;
+        if transition.up {
+            up += end - start;
+        }
+    }
+    (up, known)
+}
+
+/// Whether a lifecycle action brings an instance up or takes it down;
+/// None for those that do neither
+fn state_after(action: &str) -> Option<bool> {
+    match action {
+        "instance-started" | "instance-resumed" => Some(true),
+        "instance-stopped" | "instance-shutdown" | "instance-paused" => Some(false),
+        _ => None,
+    }
+}
+
+/// The state changes of each instance, by qualified name, oldest first
+#[derive(Debug)]
+pub struct Uptime {
+    path: PathBuf,
+    retention: TimeDelta,
+    pub timelines: BTreeMap<String, Vec<Transition>>,
+}
+
+impl Uptime {
+    /// The configured history, or None when it is turned off
+    pub fn new(config: &UptimeConfig) -> Option<Self> {
+        (config.retention_days > 0).then(|| Uptime {
+            path: config.uptime_path(),
+            retention: TimeDelta::days(config.retention_days.into()),
+            timelines: BTreeMap::new(),
+        })
+    }
+
+    pub fn load(&mut self, now: DateTime<Local>) -> Result<(), UptimeError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        self.timelines = serde_json::from_str(&text)?;
+        self.prune(now, None);
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<(), UptimeError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&self.timelines)?)?;
+        Ok(())
+    }
+
+    /// Note `name` as up or down from `at`. Returns whether that is a
+    /// change.
+    fn note(&mut self, name: &str, up: bool, at: DateTime<Local>) -> bool {
+        let timeline = self.timelines.entry(name.to_string()).or_default();
+        let at = match timeline.last() {
+            Some(last) if last.up == up => return false,
+            Some(last) => at.max(last.at), // Events can arrive after the list showed them
+            None => at,
+        };
+        timeline.push(Transition { at, up });
+        let excess = timeline.len().saturating_sub(MAX_TRANSITIONS);
+        timeline.drain(..excess);
+        true
+    }
+
+    /// Note the state of each instance in a refreshed list, forgetting
+    /// instances gone for longer than the retention period. Returns
+    /// whether anything changed.
+    pub fn observe(&mut self, containers: &[Container], now: DateTime<Local>) -> bool {
+        let mut changed = false;
+        let mut listed = HashSet::new();
+        for container in containers {
+            let name = container.qualified_name();
+            changed |= self.note(&name, container.status == "Running", now);
+            listed.insert(name);
+        }
+        changed | self.prune(now, Some(&listed))
+    }
+
+    /// Note what a lifecycle event says, at the time it happened. A restart
+    /// is quick enough to fall between two refreshes, so it is noted as a
+    /// stop and start. Returns whether anything changed.
+    pub fn record_event(&mut self, event: &LifecycleEvent) -> bool {
+        let name = event.qualified_entity();
+        if event.action != "instance-restarted" {
+            return state_after(&event.action).is_some_and(|up| self.note(&name, up, event.at));
+        }
+        match self.timelines.get(&name).and_then(|t| t.last()) {
+            Some(last) if last.up && event.at - last.at < SAME_RESTART => false,
+            Some(last) if !last.up => self.note(&name, true, event.at),
+            _ => {
+                self.note(&name, false, event.at);
+                self.note(&name, true, event.at)
+            }
+        }
+    }
+
+    /// Drop changes from before the retention period, keeping the last one
+    /// of them as the state it began in. Instances not `listed` whose last
+    /// change is that old go entirely. Returns whether anything was dropped.
+    fn prune(&mut self, now: DateTime<Local>, listed: Option<&HashSet<String>>) -> bool {
+        let cutoff = now - self.retention;
+        let mut pruned = false;
+        for timeline in self.timelines.values_mut() {
+            let old = timeline.iter().filter(|t| t.at < cutoff).count();
+            if old > 1 {
+                timeline.drain(..old - 1);
+                pruned = true;
+            }
+        }
+        if let Some(listed) = listed {
+            let before = self.timelines.len();
+            self.timelines.retain(|name, timeline| {
+                listed.contains(name) || timeline.last().is_some_and(|t| t.at >= cutoff)
+            });
+            pruned |= self.timelines.len() != before;
+        }
+        pruned
+    }
+
+    /// `name`'s uptime over the window up to `now`; None when it was never
+    /// seen
+    pub fn availability(&self, name: &str, now: DateTime<Local>) -> Option<Availability> {
+        let timeline = self.timelines.get(name).filter(|t| !t.is_empty())?;
+        let from = now - WINDOW;
+        let (up, known) = time_up(timeline, from, now);
+        let percent = if known > TimeDelta::zero() {
+            up.num_milliseconds() as f64 * 100.0 / known.num_milliseconds() as f64
+        } else if timeline.last().is_some_and(|t| t.up) {
+            100.0
+        } else {
+            0.0
+        };
+        // Coming back up after having been up before
+        let restarts = timeline
+            .windows(3)
+            .filter(|w| w[0].up && !w[1].up && w[2].up && w[2].at >= from)
+            .count();
+        Some(Availability {
+            percent,
+            restarts,
+            since: Some(timeline[0].at).filter(|at| *at > from),
+        })
+    }
+
+    /// The window up to `now` in `count` equal stretches, oldest first
+    pub fn strip(&self, name: &str, now: DateTime<Local>, count: u32) -> Vec<Slot> {
+        let timeline = self.timelines.get(name).map_or(&[][..], Vec::as_slice);
+        let step = WINDOW / count.max(1) as i32;
+        (0..count)
+            .map(|i| {
+                let from = now - WINDOW + step * i as i32;
+                let (up, known) = time_up(timeline, from, from + step);
+                if known.is_zero() {
+                    Slot::Unknown
+                } else if up == known {
+                    Slot::Up
+                } else if up.is_zero() {
+                    Slot::Down
+                } else {
+                    Slot::Partly
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests;
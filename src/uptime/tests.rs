@@ -0,0 +1,249 @@
+//! Tests of the uptime history
+
+use super::{Slot, Transition, Uptime, WINDOW};
+use crate::command::tests::container;
+use crate::config::UptimeConfig;
+use crate::events::LifecycleEvent;
+use chrono::{DateTime, Local, TimeDelta, TimeZone};
+use std::fs;
+use uuid::Uuid;
+
+fn uptime(retention_days: u32) -> (Uptime, std::path::PathBuf) {
+    let dir = std::env::temp_dir().join(format!("lxtui-uptime-{}", Uuid::new_v4()));
+    let config = UptimeConfig {
+        path: Some(dir.join("uptime.json")),
+        retention_days,
+    };
+    (Uptime::new(&config).unwrap(), dir)
+}
+
+fn now() -> DateTime<Local> {
+    Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap()
+}
+
+fn event(action: &str, name: &str, at: DateTime<Local>) -> LifecycleEvent {
+    LifecycleEvent {
+        at,
+        remote: None,
+        action: action.to_string(),
+        entity: name.to_string(),
+        requestor: None,
+    }
+}
+
+fn transition(days_ago: i64, up: bool) -> Transition {
+    Transition {
+        at: now() - TimeDelta::days(days_ago),
+        up,
+    }
+}
+
+#[test]
+fn turned_off_with_no_retention() {
+    assert!(Uptime::new(&UptimeConfig {
+        path: None,
+        retention_days: 0,
+    })
+    .is_none());
+}
+
+#[test]
+fn notes_only_changes_of_state() {
+    let (mut uptime, _) = uptime(30);
+    let start = now() - TimeDelta::hours(3);
+
+    assert!(uptime.observe(&[container("web1", "Running", "container")], start));
+    assert!(!uptime.observe(
+        &[container("web1", "Running", "container")],
+        start + TimeDelta::hours(1)
+    ));
+    assert!(uptime.observe(
+        &[container("web1", "Stopped", "container")],
+        start + TimeDelta::hours(2)
+    ));
+
+    let ups: Vec<bool> = uptime.timelines["web1"].iter().map(|t| t.up).collect();
+    assert_eq!(ups, vec![true, false]);
+    // Frozen counts as down
+    assert!(!uptime.observe(
+        &[container("web1", "Frozen", "container")],
+        start + TimeDelta::hours(3)
+    ));
+}
+
+#[test]
+fn availability_covers_the_time_known_this_week() {
+    let (mut uptime, _) = uptime(30);
+    uptime.timelines.insert(
+        "web1".to_string(),
+        vec![
+            transition(10, true),
+            Transition {
+                at: now() - TimeDelta::days(2),
+                up: false,
+            },
+            Transition {
+                at: now() - TimeDelta::days(2) + TimeDelta::minutes(84),
+                up: true,
+            },
+        ],
+    );
+
+    let availability = uptime.availability("web1", now()).unwrap();
+    // 84 minutes down in a week
+    assert!((availability.percent - 99.17).abs() < 0.01);
+    assert_eq!(availability.restarts, 1);
+    assert_eq!(availability.since, None);
+
+    uptime
+        .timelines
+        .insert("db1".to_string(), vec![transition(1, false)]);
+    let availability = uptime.availability("db1", now()).unwrap();
+    assert_eq!(availability.percent, 0.0);
+    assert_eq!(availability.restarts, 0);
+    assert_eq!(availability.since, Some(now() - TimeDelta::days(1)));
+
+    assert!(uptime.availability("vm1", now()).is_none());
+}
+
+#[test]
+fn restart_events_count_once() {
+    let (mut uptime, _) = uptime(30);
+    let start = now() - TimeDelta::hours(1);
+    uptime.observe(&[container("web1", "Running", "container")], start);
+
+    // Too quick for a refresh to see it stopped
+    assert!(uptime.record_event(&event(
+        "instance-restarted",
+        "web1",
+        start + TimeDelta::minutes(10)
+    )));
+    // The next refresh sees it running, as before
+    assert!(!uptime.observe(
+        &[container("web1", "Running", "container")],
+        start + TimeDelta::minutes(11)
+    ));
+
+    // A refresh saw this one stopped and back before the event came in
+    uptime.observe(
+        &[container("web1", "Stopped", "container")],
+        start + TimeDelta::minutes(20),
+    );
+    uptime.observe(
+        &[container("web1", "Running", "container")],
+        start + TimeDelta::minutes(21),
+    );
+    assert!(!uptime.record_event(&event(
+        "instance-restarted",
+        "web1",
+        start + TimeDelta::minutes(21)
+    )));
+
+    assert_eq!(uptime.availability("web1", now()).unwrap().restarts, 2);
+    assert!(!uptime.record_event(&event("instance-snapshot-created", "web1/snap0", now())));
+}
+
+#[test]
+fn events_are_noted_at_the_time_they_happened() {
+    let (mut uptime, _) = uptime(30);
+    let start = now() - TimeDelta::hours(1);
+    uptime.observe(&[container("web1", "Running", "container")], start);
+
+    let mut stopped = event("instance-stopped", "web1", start + TimeDelta::minutes(30));
+    stopped.remote = Some("prod".to_string());
+    assert!(uptime.record_event(&stopped));
+    assert_eq!(uptime.timelines["web1"].len(), 1);
+    assert_eq!(uptime.timelines["prod:web1"][0].at, stopped.at);
+
+    assert!(uptime.record_event(&event(
+        "instance-stopped",
+        "web1",
+        start + TimeDelta::minutes(30)
+    )));
+    assert_eq!(
+        uptime.timelines["web1"].last().unwrap().at,
+        start + TimeDelta::minutes(30)
+    );
+}
+
+#[test]
+fn strip_shows_each_stretch_of_the_week() {
+    let (mut uptime, _) = uptime(30);
+    uptime.timelines.insert(
+        "web1".to_string(),
+        vec![
+            transition(5, true),
+            Transition {
+                at: now() - TimeDelta::days(3) - TimeDelta::hours(12),
+                up: false,
+            },
+            transition(2, true),
+        ],
+    );
+
+    assert_eq!(
+        uptime.strip("web1", now(), 7),
+        vec![
+            Slot::Unknown,
+            Slot::Unknown,
+            Slot::Up,
+            Slot::Partly,
+            Slot::Down,
+            Slot::Up,
+            Slot::Up,
+        ]
+    );
+    assert_eq!(uptime.strip("vm1", now(), 2), vec![Slot::Unknown; 2]);
+    assert_eq!(WINDOW, TimeDelta::days(7));
+}
+
+#[test]
+fn old_changes_and_gone_instances_are_forgotten() {
+    let (mut uptime, _) = uptime(7);
+    uptime.timelines.insert(
+        "web1".to_string(),
+        vec![
+            transition(20, true),
+            transition(15, false),
+            transition(10, true),
+            transition(1, false),
+        ],
+    );
+    uptime
+        .timelines
+        .insert("old1".to_string(), vec![transition(9, false)]);
+    uptime
+        .timelines
+        .insert("new1".to_string(), vec![transition(3, true)]);
+
+    assert!(uptime.observe(&[container("web1", "Stopped", "container")], now()));
+
+    // The last change before the cutoff says how the week began
+    assert_eq!(
+        uptime.timelines["web1"],
+        vec![transition(10, true), transition(1, false)]
+    );
+    assert!(!uptime.timelines.contains_key("old1"));
+    assert!(uptime.timelines.contains_key("new1"));
+}
+
+#[test]
+fn saved_and_loaded_across_sessions() {
+    let (mut saved, dir) = uptime(7);
+    saved.observe(
+        &[
+            container("web1", "Running", "container"),
+            container("db1", "Stopped", "container"),
+        ],
+        now() - TimeDelta::days(1),
+    );
+    saved.save().unwrap();
+
+    let (mut loaded, _) = uptime(7);
+    loaded.path = dir.join("uptime.json");
+    loaded.load(now()).unwrap();
+    assert_eq!(loaded.timelines, saved.timelines);
+
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(loaded.load(now()).is_ok());
+}
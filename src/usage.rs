@@ -0,0 +1,233 @@
+//! Usage reports
+//!
+//! A ledger of each instance's CPU time, memory and disk space, sampled from
+//! the metrics endpoint while LXTUI runs and written out as CSV.
+
+use crate::config::UsageConfig;
+use crate::metrics::{InstanceMetrics, Series};
+use chrono::{DateTime, Local, SecondsFormat};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::time::{Duration, Instant};
+
+/// CPU modes in which the CPU wasn't working for the instance
+const IDLE_MODES: [&str; 3] = ["idle", "iowait", "steal"];
+
+const HEADER: &str = "server,instance,from,to,observed_seconds,cpu_seconds,\
+                      cpu_seconds_since_start,memory_average_bytes,memory_peak_bytes,\
+                      memory_gib_hours,disk_bytes,disk_peak_bytes";
+
+/// What an instance had used when the metrics were read
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Reading {
+    pub cpu_seconds: f64,    // Busy, since it started, summed over CPUs
+    pub memory: Option<f64>, // Bytes in use
+    pub disk: Option<f64>,   // Bytes used on its filesystems
+}
+
+impl Reading {
+    pub fn of(metrics: &InstanceMetrics) -> Self {
+        let cpu_seconds = metrics
+            .cpu_seconds
+            .iter()
+            .filter(|(mode, _)| !IDLE_MODES.contains(&mode.as_str()))
+            .map(|(_, seconds)| seconds)
+            .sum();
+        let memory = metrics
+            .memory_total
+            .zip(metrics.memory_available)
+            .map(|(total, available)| (total - available).max(0.0));
+        let disk = (!metrics.filesystems.is_empty()).then(|| {
+            metrics
+                .filesystems
+                .iter()
+                .map(|fs| (fs.size - fs.available).max(0.0))
+                .sum()
+        });
+        Reading {
+            cpu_seconds,
+            memory,
+            disk,
+        }
+    }
+}
+
+/// Readings of every instance in the default project of a scrape, by name
+pub fn readings(series: &[Series]) -> BTreeMap<String, Reading> {
+    let names: BTreeSet<&str> = series
+        .iter()
+        .filter(|s| s.labels.get("project").is_none_or(|p| p == "default"))
+        .filter_map(|s| s.labels.get("name").map(String::as_str))
+        .collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let metrics = InstanceMetrics::of(series, name)?;
+            Some((name.to_string(), Reading::of(&metrics)))
+        })
+        .collect()
+}
+
+/// What one instance used this session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Account {
+    pub first_seen: Option<DateTime<Local>>,
+    pub last_seen: Option<DateTime<Local>>,
+    pub observed: f64,            // Seconds between samples it was seen in
+    pub cpu_seconds: f64,         // Busy in those seconds
+    pub cpu_since_start: f64,     // The latest reading
+    pub memory_byte_seconds: f64, // Memory in use over those seconds
+    pub memory_peak: f64,
+    pub disk: f64, // Bytes, the latest reading
+    pub disk_peak: f64,
+    last: Option<(DateTime<Local>, Reading)>, // None once it went unseen
+}
+
+impl Account {
+    /// Bytes of memory in use on average, or at the one sample taken
+    pub fn memory_average(&self) -> f64 {
+        if self.observed > 0.0 {
+            self.memory_byte_seconds / self.observed
+        } else {
+            self.memory_peak
+        }
+    }
+
+    fn record(&mut self, reading: Reading, at: DateTime<Local>) {
+        if let Some((before, previous)) = self.last {
+            let seconds = (at - before).as_seconds_f64().max(0.0);
+            self.observed += seconds;
+            // A counter lower than before began again when the instance restarted
+            self.cpu_seconds += if reading.cpu_seconds >= previous.cpu_seconds {
+                reading.cpu_seconds - previous.cpu_seconds
+            } else {
+                reading.cpu_seconds
+            };
+            self.memory_byte_seconds += reading.memory.unwrap_or(0.0) * seconds;
+        }
+        self.first_seen.get_or_insert(at);
+        self.last_seen = Some(at);
+        self.cpu_since_start = reading.cpu_seconds;
+        if let Some(memory) = reading.memory {
+            self.memory_peak = self.memory_peak.max(memory);
+        }
+        if let Some(disk) = reading.disk {
+            self.disk = disk;
+            self.disk_peak = self.disk_peak.max(disk);
+        }
+        self.last = Some((at, reading));
+    }
+}
+
+/// What every instance sampled used this session
+#[derive(Debug)]
+pub struct Ledger {
+    accounts: BTreeMap<(String, String), Account>, // By server and instance
+    interval: Option<Duration>,                    // None samples only for a report
+    last_sample: Option<Instant>,
+}
+
+impl Ledger {
+    pub fn new(config: &UsageConfig) -> Self {
+        Ledger {
+            accounts: BTreeMap::new(),
+            interval: (config.sample_seconds > 0)
+                .then(|| Duration::from_secs(config.sample_seconds)),
+            last_sample: None,
+        }
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        self.interval.is_some_and(|interval| {
+            self.last_sample
+                .is_none_or(|last| now.duration_since(last) >= interval)
+        })
+    }
+
+    pub fn sampled(&mut self, now: Instant) {
+        self.last_sample = Some(now);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Take in the readings of `server`'s instances at `at`. Instances of the
+    /// server not read are taken to have stopped, so the time until they are
+    /// seen again doesn't count.
+    pub fn record(
+        &mut self,
+        server: &str,
+        readings: &BTreeMap<String, Reading>,
+        at: DateTime<Local>,
+    ) {
+        for ((account_server, instance), account) in &mut self.accounts {
+            if account_server == server && !readings.contains_key(instance) {
+                account.last = None;
+            }
+        }
+        for (instance, reading) in readings {
+            self.accounts
+                .entry((server.to_string(), instance.clone()))
+                .or_default()
+                .record(*reading, at);
+        }
+    }
+
+    /// The report, a row for each instance sampled
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", HEADER);
+        for ((server, instance), account) in &self.accounts {
+            let time = |at: Option<DateTime<Local>>| {
+                at.map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, false))
+                    .unwrap_or_default()
+            };
+            let fields = [
+                field(server),
+                field(instance),
+                time(account.first_seen),
+                time(account.last_seen),
+                format!("{:.0}", account.observed),
+                format!("{:.3}", account.cpu_seconds),
+                format!("{:.3}", account.cpu_since_start),
+                format!("{:.0}", account.memory_average()),
+                format!("{:.0}", account.memory_peak),
+                format!(
+                    "{:.4}",
+                    account.memory_byte_seconds / (1u64 << 30) as f64 / 3600.0
+                ),
+                format!("{:.0}", account.disk),
+                format!("{:.0}", account.disk_peak),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Write the report into `dir`, in a file named after `stamp`,
+    /// returning its path
+    pub fn save(&self, dir: &Path, stamp: &str) -> io::Result<PathBuf> {
+        let path = dir.join(format!("usage-{}.csv", stamp));
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&path, self.to_csv())?;
+        Ok(path)
+    }
+}
+
+/// A CSV field, quoted when it holds a comma, quote or line break
+fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests;
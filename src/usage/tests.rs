@@ -0,0 +1,129 @@
+//! Tests of usage reports
+
+use super::{readings, Account, Ledger, Reading};
+use crate::config::UsageConfig;
+use crate::metrics::parse;
+use chrono::{DateTime, Local, TimeDelta, TimeZone};
+use std::collections::BTreeMap;
+use tokio::time::{Duration, Instant};
+
+const SCRAPE: &str = r#"lxd_cpu_seconds_total{cpu="0",mode="user",name="web1",project="default",type="container"} 12.5
+lxd_cpu_seconds_total{cpu="0",mode="system",name="web1",project="default",type="container"} 4
+lxd_cpu_seconds_total{cpu="0",mode="idle",name="vm1",project="default",type="virtual-machine"} 500
+lxd_cpu_seconds_total{cpu="0",mode="user",name="vm1",project="default",type="virtual-machine"} 20
+lxd_cpu_seconds_total{cpu="0",mode="user",name="web1",project="staging",type="container"} 99
+lxd_memory_MemTotal_bytes{name="web1",project="default",type="container"} 1073741824
+lxd_memory_MemAvailable_bytes{name="web1",project="default",type="container"} 536870912
+lxd_filesystem_size_bytes{device="/dev/sda1",fstype="ext4",mountpoint="/",name="web1",project="default",type="container"} 10737418240
+lxd_filesystem_avail_bytes{device="/dev/sda1",fstype="ext4",mountpoint="/",name="web1",project="default",type="container"} 3221225472
+lxd_warnings_total 3
+"#;
+
+fn at(minutes: i64) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap() + TimeDelta::minutes(minutes)
+}
+
+fn reading(cpu_seconds: f64, memory: f64) -> BTreeMap<String, Reading> {
+    [(
+        "web1".to_string(),
+        Reading {
+            cpu_seconds,
+            memory: Some(memory),
+            disk: Some(1000.0),
+        },
+    )]
+    .into()
+}
+
+fn web1(ledger: &Ledger) -> &Account {
+    &ledger.accounts[&("local".to_string(), "web1".to_string())]
+}
+
+#[test]
+fn busy_cpu_memory_and_disk_of_each_instance() {
+    let readings = readings(&parse(SCRAPE).unwrap());
+    assert_eq!(readings.len(), 2);
+    assert_eq!(
+        readings["web1"],
+        Reading {
+            cpu_seconds: 16.5,
+            memory: Some(536870912.0),
+            disk: Some(7516192768.0),
+        }
+    );
+    // Idle time isn't used, and a VM without memory series has none
+    assert_eq!(readings["vm1"].cpu_seconds, 20.0);
+    assert_eq!(readings["vm1"].memory, None);
+}
+
+#[test]
+fn only_time_between_samples_counts() {
+    let mut ledger = Ledger::new(&UsageConfig::default());
+    ledger.record("local", &reading(100.0, 2048.0), at(0));
+    assert_eq!(web1(&ledger).cpu_seconds, 0.0);
+    assert_eq!(web1(&ledger).cpu_since_start, 100.0);
+    assert_eq!(web1(&ledger).memory_average(), 2048.0);
+
+    ledger.record("local", &reading(130.0, 1024.0), at(1));
+    // Restarted since, so the counter began again
+    ledger.record("local", &reading(5.0, 1024.0), at(2));
+    let account = web1(&ledger);
+    assert_eq!(account.observed, 120.0);
+    assert_eq!(account.cpu_seconds, 35.0);
+    assert_eq!(account.memory_average(), 1024.0);
+    assert_eq!(account.memory_peak, 2048.0);
+
+    // Stopped at the next sample, so the hour until it is seen again is left out
+    ledger.record("local", &BTreeMap::new(), at(3));
+    ledger.record("local", &reading(10.0, 1024.0), at(63));
+    ledger.record("other", &BTreeMap::new(), at(64));
+    assert_eq!(web1(&ledger).observed, 120.0);
+    assert_eq!(web1(&ledger).cpu_seconds, 35.0);
+    assert_eq!(web1(&ledger).last_seen, Some(at(63)));
+}
+
+#[test]
+fn report_has_a_row_for_each_instance() {
+    let gib = (1u64 << 30) as f64;
+    let mut ledger = Ledger::new(&UsageConfig::default());
+    ledger.record("local", &reading(100.0, 2.0 * gib), at(0));
+    ledger.record("local", &reading(160.5, 2.0 * gib), at(30));
+    ledger.record("lab, east", &reading(1.0, 0.0), at(30));
+
+    let csv = ledger.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "server,instance,from,to,observed_seconds,cpu_seconds,cpu_seconds_since_start,\
+         memory_average_bytes,memory_peak_bytes,memory_gib_hours,disk_bytes,disk_peak_bytes"
+    );
+    let from = at(0).to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+    let to = at(30).to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+    assert_eq!(
+        lines[1],
+        format!("\"lab, east\",web1,{to},{to},0,0.000,1.000,0,0,0.0000,1000,1000")
+    );
+    assert_eq!(
+        lines[2],
+        format!(
+            "local,web1,{from},{to},1800,60.500,160.500,2147483648,2147483648,1.0000,1000,1000"
+        )
+    );
+    assert_eq!(lines.len(), 3);
+}
+
+#[test]
+fn sampling_follows_the_configured_interval() {
+    let now = Instant::now();
+    let mut ledger = Ledger::new(&UsageConfig::default());
+    assert!(ledger.due(now));
+    ledger.sampled(now);
+    assert!(!ledger.due(now + Duration::from_secs(59)));
+    assert!(ledger.due(now + Duration::from_secs(60)));
+
+    let reports_only = Ledger::new(&UsageConfig {
+        sample_seconds: 0,
+        dir: None,
+    });
+    assert!(!reports_only.due(now));
+}
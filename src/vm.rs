@@ -0,0 +1,232 @@
+//! VM settings
+//!
+//! Lists and edits the settings only a VM has: CPUs and pinning, hugepages,
+//! secure boot, stateful migration, a TPM and the read-only vsock ID.
+
+use crate::capacity;
+use crate::edit::InstanceConfig;
+use std::collections::{BTreeMap, HashMap};
+
+/// Name of the TPM device added by the view
+pub const TPM_DEVICE: &str = "tpm";
+
+/// One VM-only setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Knob {
+    Cpus,
+    Hugepages,
+    SecureBoot,
+    StatefulMigration,
+    Tpm,
+    Vsock,
+}
+
+impl Knob {
+    pub const ALL: [Knob; 6] = [
+        Knob::Cpus,
+        Knob::Hugepages,
+        Knob::SecureBoot,
+        Knob::StatefulMigration,
+        Knob::Tpm,
+        Knob::Vsock,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Knob::Cpus => "CPUs",
+            Knob::Hugepages => "Hugepages",
+            Knob::SecureBoot => "Secure boot",
+            Knob::StatefulMigration => "Stateful migration",
+            Knob::Tpm => "TPM",
+            Knob::Vsock => "vsock ID",
+        }
+    }
+
+    /// Config key holding it; the TPM is a device instead
+    pub fn key(self) -> Option<&'static str> {
+        match self {
+            Knob::Cpus => Some("limits.cpu"),
+            Knob::Hugepages => Some("limits.memory.hugepages"),
+            Knob::SecureBoot => Some("security.secureboot"),
+            Knob::StatefulMigration => Some("migration.stateful"),
+            Knob::Tpm => None,
+            Knob::Vsock => Some("volatile.vsock_id"),
+        }
+    }
+
+    /// API extension the server must have for it
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            Knob::StatefulMigration => Some("migration_stateful"),
+            Knob::Tpm => Some("tpm_device_type"),
+            _ => None,
+        }
+    }
+
+    /// Whether it is switched on and off rather than typed
+    pub fn is_toggle(self) -> bool {
+        !matches!(self, Knob::Cpus | Knob::Vsock)
+    }
+
+    /// Whether a running VM picks a change up without a restart. LXD
+    /// hotplugs a new CPU count, but not a pinned set.
+    pub fn is_live(self, value: &str) -> bool {
+        self == Knob::Cpus && !value.contains(['-', ','])
+    }
+
+    /// Value in effect when the key is unset
+    fn default(self) -> &'static str {
+        match self {
+            Knob::Cpus => "1",
+            Knob::SecureBoot => "true",
+            Knob::Hugepages | Knob::StatefulMigration | Knob::Tpm => "false",
+            Knob::Vsock => "",
+        }
+    }
+
+    /// Set `value` in a VM's config; an empty value unsets the key, and for
+    /// the TPM "true" adds the device and anything else removes it
+    pub fn apply(self, config: &mut InstanceConfig, value: &str) {
+        match self.key() {
+            Some(key) if value.is_empty() => {
+                config.config.remove(key);
+            }
+            Some(key) => {
+                config.config.insert(key.to_string(), value.to_string());
+            }
+            None if value == "true" => {
+                let device = BTreeMap::from([("type".to_string(), "tpm".to_string())]);
+                config.devices.insert(TPM_DEVICE.to_string(), device);
+            }
+            None => config.devices.retain(|_, d| !is_tpm(d)),
+        }
+    }
+}
+
+fn is_tpm(device: &BTreeMap<String, String>) -> bool {
+    device.get("type").map(String::as_str) == Some("tpm")
+}
+
+/// Whether a boolean config value is on, as LXD reads it
+fn is_true(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "true" | "1" | "yes" | "on"
+    )
+}
+
+/// A setting as the VM has it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+    pub knob: Knob,
+    pub value: String,           // Effective, from the VM's profiles too
+    pub inherited: bool,         // Not set on the VM itself
+    pub missing: Option<String>, // The API extension the server lacks
+}
+
+impl Setting {
+    /// Whether a toggle is on
+    pub fn is_on(&self) -> bool {
+        is_true(&self.value)
+    }
+
+    /// The value switching a toggle gives
+    pub fn toggled(&self) -> &'static str {
+        if self.is_on() {
+            "false"
+        } else {
+            "true"
+        }
+    }
+}
+
+/// The settings of one VM, from its own and expanded config and devices,
+/// against the extensions its server advertises
+pub fn settings(
+    config: &HashMap<String, String>,
+    expanded_config: &HashMap<String, String>,
+    expanded_devices: &HashMap<String, HashMap<String, String>>,
+    extensions: &[String],
+) -> Vec<Setting> {
+    Knob::ALL
+        .iter()
+        .map(|&knob| {
+            let (value, inherited) = match knob.key() {
+                Some(key) => match expanded_config.get(key) {
+                    Some(value) => (value.clone(), !config.contains_key(key)),
+                    None => (knob.default().to_string(), true),
+                },
+                None => {
+                    let attached = expanded_devices
+                        .values()
+                        .any(|d| d.get("type").map(String::as_str) == Some("tpm"));
+                    (attached.to_string(), false)
+                }
+            };
+            let missing = knob
+                .extension()
+                .filter(|extension| !extensions.iter().any(|e| e == extension))
+                .map(str::to_string);
+            Setting {
+                knob,
+                value,
+                inherited,
+                missing,
+            }
+        })
+        .collect()
+}
+
+/// Check a typed CPU limit; empty unsets it
+pub fn valid_cpus(text: &str) -> bool {
+    text.is_empty() || capacity::cpu_count(text).is_some_and(|count| count > 0)
+}
+
+/// The VM settings view
+#[derive(Debug)]
+pub struct VmSettings {
+    pub container: String, // qualified name
+    pub running: bool,
+    pub settings: Vec<Setting>,
+    pub selected: usize,
+    pub status: Option<String>, // What the last change did, or why it wasn't made
+    pub error: Option<String>,  // Why the last load failed
+}
+
+impl VmSettings {
+    pub fn new(container: String) -> Self {
+        VmSettings {
+            container,
+            running: false,
+            settings: Vec::new(),
+            selected: 0,
+            status: None,
+            error: None,
+        }
+    }
+
+    pub fn set_settings(&mut self, running: bool, settings: Vec<Setting>) {
+        self.running = running;
+        self.settings = settings;
+        self.error = None;
+        self.selected = self.selected.min(self.settings.len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        if self.settings.is_empty() {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % self.settings.len()
+        } else {
+            (self.selected + self.settings.len() - 1) % self.settings.len()
+        };
+    }
+
+    pub fn selected_setting(&self) -> Option<&Setting> {
+        self.settings.get(self.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,83 @@
+//! Tests of VM settings
+
+use super::{settings, valid_cpus, Knob, TPM_DEVICE};
+use crate::edit::InstanceConfig;
+use std::collections::{BTreeMap, HashMap};
+
+fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn reads_settings_from_the_vm_and_its_profiles() {
+    let config = map(&[("limits.cpu", "0-3")]);
+    let expanded = map(&[
+        ("limits.cpu", "0-3"),
+        ("security.secureboot", "false"),
+        ("volatile.vsock_id", "42"),
+    ]);
+    let devices = HashMap::from([("vtpm".to_string(), map(&[("type", "tpm")]))]);
+    let extensions = vec!["tpm_device_type".to_string()];
+
+    let settings = settings(&config, &expanded, &devices, &extensions);
+    let shown: Vec<(&str, &str, bool)> = settings
+        .iter()
+        .map(|s| (s.knob.label(), s.value.as_str(), s.inherited))
+        .collect();
+    assert_eq!(
+        shown,
+        [
+            ("CPUs", "0-3", false),
+            ("Hugepages", "false", true),
+            ("Secure boot", "false", true), // From a profile
+            ("Stateful migration", "false", true),
+            ("TPM", "true", false),
+            ("vsock ID", "42", true),
+        ]
+    );
+    assert!(!settings[2].is_on());
+    assert_eq!(settings[2].toggled(), "true");
+
+    // Offered only where the server has the extension
+    assert_eq!(settings[3].missing.as_deref(), Some("migration_stateful"));
+    assert_eq!(settings[4].missing, None);
+}
+
+#[test]
+fn changes_config_keys_and_the_tpm_device() {
+    let mut config = InstanceConfig {
+        architecture: "x86_64".to_string(),
+        config: BTreeMap::from([("limits.cpu".to_string(), "2".to_string())]),
+        devices: BTreeMap::new(),
+        ephemeral: false,
+        profiles: Vec::new(),
+        stateful: false,
+        description: String::new(),
+    };
+
+    Knob::Hugepages.apply(&mut config, "true");
+    Knob::Cpus.apply(&mut config, "");
+    assert_eq!(
+        config.config,
+        BTreeMap::from([("limits.memory.hugepages".to_string(), "true".to_string())])
+    );
+
+    Knob::Tpm.apply(&mut config, "true");
+    assert_eq!(config.devices[TPM_DEVICE]["type"], "tpm");
+    Knob::Tpm.apply(&mut config, "false");
+    assert!(config.devices.is_empty());
+}
+
+#[test]
+fn cpus_are_a_count_or_a_set() {
+    assert!(valid_cpus("4"));
+    assert!(valid_cpus("0-3,8"));
+    assert!(valid_cpus(""));
+    assert!(!valid_cpus("0"));
+    assert!(!valid_cpus("four"));
+    assert!(Knob::Cpus.is_live("4"));
+    assert!(!Knob::Cpus.is_live("0-3"));
+}
@@ -0,0 +1,250 @@
+//! Watch view for a single container
+//!
+//! Polls the instance state once a second and keeps what the view shows:
+//! the latest state, CPU and network rates worked out from the counters,
+//! and an event list built by comparing each poll with the one before it.
+//! Series only the metrics endpoint has are scraped less often.
+
+use crate::lxd_api::ContainerState;
+use crate::metrics::{self, InstanceMetrics};
+use crate::ports::Port;
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use tokio::time::{Duration, Instant};
+
+/// How often the watched container is polled
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Events kept in the view; older ones are dropped
+const MAX_EVENTS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct WatchEvent {
+    pub at: DateTime<Local>,
+    pub message: String,
+}
+
+/// Bytes per second received and sent on one interface
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Throughput {
+    pub rx: f64,
+    pub tx: f64,
+}
+
+#[derive(Debug)]
+pub struct Watch {
+    pub container: String, // qualified name
+    pub state: Option<ContainerState>,
+    pub image: Option<String>,                 // Created from, as listed
+    pub healthcheck: Option<String>,           // Probe command, as listed
+    pub restart: bool,                         // Restart policy, as listed
+    pub ports: Vec<Port>,                      // Published ports, as listed
+    pub notes: Option<String>,                 // As listed
+    pub dns_names: Vec<String>,                // Names it resolves as, found on opening
+    pub error: Option<String>,                 // Why the last poll failed
+    pub cpu_percent: Option<f64>,              // Of one CPU, since the previous poll
+    pub throughput: Vec<(String, Throughput)>, // Per interface, since the previous poll
+    pub events: VecDeque<WatchEvent>,
+    pub last_poll: Option<Instant>,
+    pub metrics: Option<InstanceMetrics>,
+    pub metrics_error: Option<String>, // Why the last scrape failed
+    pub last_scrape: Option<Instant>,
+}
+
+impl Watch {
+    pub fn new(container: String) -> Self {
+        Watch {
+            container,
+            state: None,
+            image: None,
+            healthcheck: None,
+            restart: false,
+            ports: Vec::new(),
+            notes: None,
+            dns_names: Vec::new(),
+            error: None,
+            cpu_percent: None,
+            throughput: Vec::new(),
+            events: VecDeque::new(),
+            last_poll: None,
+            metrics: None,
+            metrics_error: None,
+            last_scrape: None,
+        }
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        self.last_poll
+            .is_none_or(|last| now.duration_since(last) >= POLL_INTERVAL)
+    }
+
+    pub fn scrape_due(&self, now: Instant) -> bool {
+        self.last_scrape
+            .is_none_or(|last| now.duration_since(last) >= metrics::POLL_INTERVAL)
+    }
+
+    /// Take in the result of a metrics scrape made at `now`. Metrics from
+    /// an earlier scrape stay shown when one fails.
+    pub fn record_metrics(
+        &mut self,
+        result: Result<Option<InstanceMetrics>, String>,
+        now: Instant,
+    ) {
+        self.last_scrape = Some(now);
+        match result {
+            Ok(metrics) => {
+                self.metrics = metrics;
+                self.metrics_error = None;
+            }
+            Err(e) => self.metrics_error = Some(e),
+        }
+    }
+
+    /// Take in the result of a poll made at `now`
+    pub fn record(&mut self, result: Result<ContainerState, String>, now: Instant) {
+        let elapsed = self
+            .last_poll
+            .map(|last| now.duration_since(last).as_secs_f64())
+            .filter(|secs| *secs > 0.0);
+        self.last_poll = Some(now);
+
+        let state = match result {
+            Ok(state) => state,
+            Err(e) => {
+                if self.error.is_none() {
+                    self.event(format!("State unavailable: {}", e));
+                }
+                self.error = Some(e);
+                self.cpu_percent = None;
+                self.throughput.clear();
+                return;
+            }
+        };
+        if self.error.take().is_some() {
+            self.event("State available again".to_string());
+        }
+
+        match (self.state.take(), elapsed) {
+            (Some(previous), Some(elapsed)) => {
+                self.compare(&previous, &state);
+                self.cpu_percent = cpu_percent(&previous, &state, elapsed);
+                self.throughput = throughput(&previous, &state, elapsed);
+            }
+            (Some(previous), None) => self.compare(&previous, &state),
+            (None, _) => self.event(format!("Watching ({})", state.status)),
+        }
+        self.state = Some(state);
+    }
+
+    /// Record the lifecycle changes between two polls
+    fn compare(&mut self, previous: &ContainerState, state: &ContainerState) {
+        if previous.status != state.status {
+            self.event(format!("{} → {}", previous.status, state.status));
+        } else if previous.pid != 0 && state.pid != 0 && previous.pid != state.pid {
+            // Still running but with a new init process: it restarted in between
+            self.event(format!(
+                "Restarted (init PID {} → {})",
+                previous.pid, state.pid
+            ));
+        }
+
+        let before = addresses(previous);
+        let after = addresses(state);
+        for address in after.iter().filter(|a| !before.contains(a)) {
+            self.event(format!("{} up", address));
+        }
+        for address in before.iter().filter(|a| !after.contains(a)) {
+            self.event(format!("{} down", address));
+        }
+    }
+
+    fn event(&mut self, message: String) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(WatchEvent {
+            at: Local::now(),
+            message,
+        });
+    }
+}
+
+/// Percent of one CPU used between two polls `elapsed` seconds apart
+pub fn cpu_percent(previous: &ContainerState, state: &ContainerState, elapsed: f64) -> Option<f64> {
+    let usage = |state: &ContainerState| state.cpu.as_ref().map(|cpu| cpu.usage);
+    // CPU time is in nanoseconds
+    usage(previous)
+        .zip(usage(state))
+        .filter(|(before, after)| after >= before)
+        .map(|(before, after)| (after - before) as f64 / (elapsed * 1e7))
+}
+
+/// Global addresses as "eth0 10.0.0.10", sorted
+fn addresses(state: &ContainerState) -> Vec<String> {
+    let mut addresses: Vec<String> = state
+        .network
+        .iter()
+        .flatten()
+        .filter(|(name, _)| *name != "lo")
+        .flat_map(|(name, interface)| {
+            interface
+                .addresses
+                .iter()
+                .filter(|a| a.scope == "global")
+                .map(move |a| format!("{} {}", name, a.address))
+        })
+        .collect();
+    addresses.sort();
+    addresses
+}
+
+/// Per-interface rates between two polls `elapsed` seconds apart
+pub fn throughput(
+    previous: &ContainerState,
+    state: &ContainerState,
+    elapsed: f64,
+) -> Vec<(String, Throughput)> {
+    let (Some(before), Some(after)) = (&previous.network, &state.network) else {
+        return Vec::new();
+    };
+
+    let rate = |before: Option<&i64>, after: Option<&i64>| match (before, after) {
+        (Some(before), Some(after)) if after >= before => (after - before) as f64 / elapsed,
+        _ => 0.0,
+    };
+
+    let mut rates: Vec<(String, Throughput)> = after
+        .iter()
+        .filter(|(name, _)| *name != "lo")
+        .filter_map(|(name, interface)| {
+            let old = &before.get(name)?.counters;
+            let new = &interface.counters;
+            let throughput = Throughput {
+                rx: rate(old.get("bytes_received"), new.get("bytes_received")),
+                tx: rate(old.get("bytes_sent"), new.get("bytes_sent")),
+            };
+            Some((name.clone(), throughput))
+        })
+        .collect();
+    rates.sort_by(|a, b| a.0.cmp(&b.0));
+    rates
+}
+
+/// Human-readable size in binary units, e.g. "12.3 MiB"
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", value as i64)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+pub mod tests;
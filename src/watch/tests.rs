@@ -0,0 +1,107 @@
+//! Tests of the watch view's rates and lifecycle events
+
+use super::{format_bytes, Throughput, Watch};
+use crate::lxd_api::ContainerState;
+use serde_json::json;
+use tokio::time::{Duration, Instant};
+
+/// State of a container with one interface; `pid` 0 means stopped
+pub fn state(status: &str, pid: i64, cpu_ns: i64, ip: Option<&str>, rx: i64) -> ContainerState {
+    let addresses: Vec<_> = ip
+        .into_iter()
+        .map(|ip| json!({"address": ip, "family": "inet", "netmask": "24", "scope": "global"}))
+        .collect();
+    serde_json::from_value(json!({
+        "status": status,
+        "status_code": 103,
+        "network": {
+            "eth0": {
+                "addresses": addresses,
+                "counters": {"bytes_received": rx, "bytes_sent": 0},
+                "hwaddr": "00:16:3e:00:00:01",
+                "mtu": 1500,
+                "state": "up",
+                "type": "broadcast",
+            }
+        },
+        "pid": pid,
+        "processes": 10,
+        "cpu": {"usage": cpu_ns},
+        "memory": {"usage": 1 << 20, "usage_peak": 2 << 20, "swap_usage": 0, "swap_usage_peak": 0},
+        "disk": {"root": {"usage": 3_i64 << 30}},
+    }))
+    .unwrap()
+}
+
+fn messages(watch: &Watch) -> Vec<&str> {
+    watch.events.iter().map(|e| e.message.as_str()).collect()
+}
+
+#[test]
+fn rates_come_from_counter_deltas() {
+    let start = Instant::now();
+    let mut watch = Watch::new("web1".to_string());
+    watch.record(Ok(state("Running", 100, 0, Some("10.0.0.10"), 0)), start);
+    assert_eq!(watch.cpu_percent, None);
+
+    // Half a CPU second and 2 KiB over two seconds
+    let later = start + Duration::from_secs(2);
+    watch.record(
+        Ok(state(
+            "Running",
+            100,
+            1_000_000_000,
+            Some("10.0.0.10"),
+            4096,
+        )),
+        later,
+    );
+    assert_eq!(watch.cpu_percent, Some(50.0));
+    assert_eq!(
+        watch.throughput,
+        vec![(
+            "eth0".to_string(),
+            Throughput {
+                rx: 2048.0,
+                tx: 0.0
+            }
+        )]
+    );
+    assert!(!watch.due(later + Duration::from_millis(500)));
+    assert!(watch.due(later + Duration::from_secs(1)));
+}
+
+#[test]
+fn lifecycle_changes_become_events() {
+    let start = Instant::now();
+    let tick = |n| start + Duration::from_secs(n);
+    let mut watch = Watch::new("web1".to_string());
+
+    watch.record(Ok(state("Running", 100, 0, Some("10.0.0.10"), 0)), tick(0));
+    watch.record(Ok(state("Running", 200, 0, None, 0)), tick(1));
+    watch.record(Ok(state("Stopped", 0, 0, None, 0)), tick(2));
+    watch.record(Err("connection refused".to_string()), tick(3));
+    watch.record(Err("connection refused".to_string()), tick(4));
+    watch.record(Ok(state("Running", 300, 0, Some("10.0.0.11"), 0)), tick(5));
+
+    assert_eq!(
+        messages(&watch),
+        vec![
+            "Watching (Running)",
+            "Restarted (init PID 100 → 200)",
+            "eth0 10.0.0.10 down",
+            "Running → Stopped",
+            "State unavailable: connection refused",
+            "State available again",
+            "Stopped → Running",
+            "eth0 10.0.0.11 up",
+        ]
+    );
+}
+
+#[test]
+fn sizes_use_binary_units() {
+    assert_eq!(format_bytes(512.0), "512 B");
+    assert_eq!(format_bytes(1536.0), "1.5 KiB");
+    assert_eq!(format_bytes(3.0 * 1024.0 * 1024.0 * 1024.0), "3.0 GiB");
+}
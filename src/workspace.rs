@@ -0,0 +1,103 @@
+//! Saved workspaces
+//!
+//! A workspace is a named filter, sort order and grouping from the config
+//! file, e.g. "prod web" for `tag=prod name=web` sorted by status. Number
+//! keys switch between them.
+
+use crate::config::WorkspaceConfig;
+use crate::filter::{ContainerFilter, FilterError};
+use crate::lxc::Container;
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WorkspaceError {
+    #[error(transparent)]
+    Filter(#[from] FilterError),
+    #[error("Unknown sort '{0}' (use name, status, type or remote)")]
+    UnknownSort(String),
+}
+
+/// Order of the container list; without one, the server's order is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Status,
+    Type,
+    Remote,
+}
+
+impl SortKey {
+    pub fn parse(text: &str) -> Result<Self, WorkspaceError> {
+        match text.to_lowercase().as_str() {
+            "name" => Ok(SortKey::Name),
+            "status" | "state" => Ok(SortKey::Status),
+            "type" => Ok(SortKey::Type),
+            "remote" => Ok(SortKey::Remote),
+            _ => Err(WorkspaceError::UnknownSort(text.to_string())),
+        }
+    }
+
+    /// Sort stably, so containers with equal keys keep their order
+    pub fn sort(self, containers: &mut [Container]) {
+        containers.sort_by_cached_key(|c| match self {
+            SortKey::Name => c.name.to_lowercase(),
+            SortKey::Status => c.status.to_lowercase(),
+            SortKey::Type => c.container_type.clone(),
+            // The local socket first
+            SortKey::Remote => c.remote.as_deref().unwrap_or("").to_lowercase(),
+        });
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SortKey::Name => "name",
+            SortKey::Status => "status",
+            SortKey::Type => "type",
+            SortKey::Remote => "remote",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub name: String,
+    pub filter: Option<ContainerFilter>,
+    pub sort: Option<SortKey>,
+    pub group_by_tag: bool,
+}
+
+impl Workspace {
+    /// Check a workspace from the config file
+    pub fn from_config(config: &WorkspaceConfig) -> Result<Self, WorkspaceError> {
+        let filter = match config.filter.trim() {
+            "" => None,
+            text => Some(ContainerFilter::parse(text)?),
+        };
+        let sort = config.sort.as_deref().map(SortKey::parse).transpose()?;
+        Ok(Workspace {
+            name: config.name.clone(),
+            filter,
+            sort,
+            group_by_tag: config.group_by_tag,
+        })
+    }
+
+    pub fn to_config(&self) -> WorkspaceConfig {
+        WorkspaceConfig {
+            name: self.name.clone(),
+            filter: self
+                .filter
+                .as_ref()
+                .map(|f| f.to_string())
+                .unwrap_or_default(),
+            sort: self.sort.map(|s| s.to_string()),
+            group_by_tag: self.group_by_tag,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,58 @@
+//! Tests of workspace parsing and sorting
+
+use super::{SortKey, Workspace, WorkspaceError};
+use crate::command::tests::container;
+use crate::config::WorkspaceConfig;
+
+fn config(filter: &str, sort: Option<&str>) -> WorkspaceConfig {
+    WorkspaceConfig {
+        name: "prod web".to_string(),
+        filter: filter.to_string(),
+        sort: sort.map(str::to_string),
+        group_by_tag: true,
+    }
+}
+
+#[test]
+fn round_trips_through_config() {
+    let config = config("tag=prod name=web", Some("status"));
+    let workspace = Workspace::from_config(&config).unwrap();
+    assert_eq!(workspace.sort, Some(SortKey::Status));
+    assert!(workspace.group_by_tag);
+    assert_eq!(workspace.to_config(), config);
+
+    let everything = Workspace::from_config(&self::config(" ", None)).unwrap();
+    assert_eq!(everything.filter, None);
+    assert_eq!(everything.sort, None);
+}
+
+#[test]
+fn rejects_invalid_workspaces() {
+    assert!(matches!(
+        Workspace::from_config(&config("colour=red", None)),
+        Err(WorkspaceError::Filter(_))
+    ));
+    assert!(matches!(
+        Workspace::from_config(&config("", Some("age"))),
+        Err(WorkspaceError::UnknownSort(key)) if key == "age"
+    ));
+}
+
+#[test]
+fn sorts_stably() {
+    let mut containers = vec![
+        container("web2", "Stopped", "container"),
+        container("db1", "Running", "virtual-machine"),
+        container("Web1", "Running", "container"),
+    ];
+    SortKey::Status.sort(&mut containers);
+    let names: Vec<&str> = containers.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["db1", "Web1", "web2"]);
+
+    SortKey::Name.sort(&mut containers);
+    let names: Vec<&str> = containers.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["db1", "Web1", "web2"]);
+
+    SortKey::Type.sort(&mut containers);
+    assert_eq!(containers[2].name, "db1");
+}
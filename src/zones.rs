@@ -0,0 +1,343 @@
+//! Network zones
+//!
+//! The server's DNS zones from `/1.0/network-zones`, the networks publishing
+//! into each, and their records.
+
+use crate::lxd_api::{ApiRequest, LxdNetwork, LxdNetworkZone, LxdZoneEntry, LxdZoneRecord};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Network key naming the forward zones a network publishes into
+pub const FORWARD_KEY: &str = "dns.zone.forward";
+
+/// Domain a managed bridge's own DNS answers for when `dns.domain` isn't set
+const DEFAULT_DOMAIN: &str = "lxd";
+
+/// Record types LXD accepts in zone entries
+pub const RECORD_TYPES: [&str; 9] = ["A", "AAAA", "CAA", "CNAME", "MX", "NS", "PTR", "SRV", "TXT"];
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RecordError {
+    #[error("Give the record's name, then its entries, like www A 10.0.0.5")]
+    MissingName,
+    #[error("Give at least one entry, like A 10.0.0.5")]
+    NoEntries,
+    #[error("The {0} entry needs a value")]
+    MissingValue(String),
+    #[error("'{0}' is not a record type LXD serves")]
+    UnknownType(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZoneKind {
+    Forward,
+    Reverse, // Maps addresses back to names
+}
+
+impl ZoneKind {
+    pub fn of(zone: &str) -> Self {
+        let zone = zone.trim_end_matches('.');
+        if zone.ends_with(".in-addr.arpa") || zone.ends_with(".ip6.arpa") {
+            ZoneKind::Reverse
+        } else {
+            ZoneKind::Forward
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ZoneKind::Forward => "forward",
+            ZoneKind::Reverse => "reverse",
+        }
+    }
+}
+
+/// A zone with its records
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub description: String,
+    pub networks: Vec<String>, // Publishing into it
+    pub records: Vec<LxdZoneRecord>,
+}
+
+impl Zone {
+    pub fn new(zone: LxdNetworkZone, mut records: Vec<LxdZoneRecord>) -> Self {
+        records.sort_by(|a, b| a.name.cmp(&b.name));
+        Zone {
+            networks: zone
+                .used_by
+                .iter()
+                .filter_map(|url| url.strip_prefix("/1.0/networks/"))
+                .map(|network| network.split('?').next().unwrap_or(network).to_string())
+                .collect(),
+            name: zone.name,
+            description: zone.description,
+            records,
+        }
+    }
+
+    pub fn kind(&self) -> ZoneKind {
+        ZoneKind::of(&self.name)
+    }
+}
+
+/// An edit made in the zones view
+#[derive(Debug, Clone)]
+pub enum ZoneChange {
+    CreateZone(String),
+    DeleteZone(String),
+    CreateRecord { zone: String, record: LxdZoneRecord },
+    UpdateRecord { zone: String, record: LxdZoneRecord },
+    DeleteRecord { zone: String, record: String },
+}
+
+impl ZoneChange {
+    pub fn request(&self) -> ApiRequest {
+        match self {
+            ZoneChange::CreateZone(zone) => ApiRequest::create_network_zone(zone),
+            ZoneChange::DeleteZone(zone) => ApiRequest::delete_network_zone(zone),
+            ZoneChange::CreateRecord { zone, record } => {
+                ApiRequest::create_zone_record(zone, record)
+            }
+            ZoneChange::UpdateRecord { zone, record } => {
+                ApiRequest::update_zone_record(zone, record)
+            }
+            ZoneChange::DeleteRecord { zone, record } => {
+                ApiRequest::delete_zone_record(zone, record)
+            }
+        }
+    }
+
+    /// Status once the change is made, like "Created zone 'x'"
+    pub fn done(&self) -> String {
+        let (_, past, what) = self.parts();
+        format!("{} {}", past, what)
+    }
+
+    /// Title of the error when the change fails
+    pub fn failed(&self) -> String {
+        let (verb, _, what) = self.parts();
+        format!("Failed to {} {}", verb, what)
+    }
+
+    fn parts(&self) -> (&'static str, &'static str, String) {
+        match self {
+            ZoneChange::CreateZone(zone) => ("create", "Created", format!("zone '{}'", zone)),
+            ZoneChange::DeleteZone(zone) => ("delete", "Deleted", format!("zone '{}'", zone)),
+            ZoneChange::CreateRecord { zone, record } => (
+                "create",
+                "Created",
+                format!("record '{}.{}'", record.name, zone),
+            ),
+            ZoneChange::UpdateRecord { zone, record } => (
+                "update",
+                "Updated",
+                format!("record '{}.{}'", record.name, zone),
+            ),
+            ZoneChange::DeleteRecord { zone, record } => {
+                ("delete", "Deleted", format!("record '{}.{}'", record, zone))
+            }
+        }
+    }
+}
+
+/// A line of the zones view: a zone, or one of its records
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Row {
+    Zone(usize),
+    Record(usize, usize), // Zone, record
+}
+
+/// The zones view: every zone on the server with its records beneath
+#[derive(Debug)]
+pub struct Zones {
+    pub zones: Vec<Zone>,
+    pub selected: usize,        // Index into `rows()`
+    pub error: Option<String>,  // Why the last load failed
+    pub status: Option<String>, // Outcome of the last change
+}
+
+impl Zones {
+    pub fn new() -> Self {
+        Zones {
+            zones: Vec::new(),
+            selected: 0,
+            error: None,
+            status: None,
+        }
+    }
+
+    /// Take in freshly loaded zones, keeping the selected zone or record
+    pub fn set_zones(&mut self, mut zones: Vec<Zone>) {
+        let selected = self.selected_names();
+        // Forward zones first, as those are the ones usually edited
+        zones.sort_by_key(|zone| (zone.kind() == ZoneKind::Reverse, zone.name.clone()));
+        self.zones = zones;
+        self.error = None;
+        let rows = self.rows();
+        self.selected = selected
+            .and_then(|(zone, record)| {
+                // A deleted record leaves its zone selected
+                let position = |record: Option<&str>| {
+                    rows.iter()
+                        .position(|row| self.names(*row) == (zone.as_str(), record))
+                };
+                position(record.as_deref()).or_else(|| position(None))
+            })
+            .unwrap_or(0)
+            .min(rows.len().saturating_sub(1));
+    }
+
+    pub fn rows(&self) -> Vec<Row> {
+        self.zones
+            .iter()
+            .enumerate()
+            .flat_map(|(z, zone)| {
+                std::iter::once(Row::Zone(z))
+                    .chain((0..zone.records.len()).map(move |r| Row::Record(z, r)))
+            })
+            .collect()
+    }
+
+    pub fn select(&mut self, forward: bool) {
+        let rows = self.rows().len();
+        if rows == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % rows
+        } else {
+            (self.selected + rows - 1) % rows
+        };
+    }
+
+    pub fn selected_row(&self) -> Option<Row> {
+        self.rows().get(self.selected).copied()
+    }
+
+    /// Zone of the selected row, whether it is the zone or a record in it
+    pub fn selected_zone(&self) -> Option<&Zone> {
+        match self.selected_row()? {
+            Row::Zone(z) | Row::Record(z, _) => self.zones.get(z),
+        }
+    }
+
+    pub fn selected_record(&self) -> Option<(&Zone, &LxdZoneRecord)> {
+        match self.selected_row()? {
+            Row::Record(z, r) => {
+                let zone = self.zones.get(z)?;
+                Some((zone, zone.records.get(r)?))
+            }
+            Row::Zone(_) => None,
+        }
+    }
+
+    fn names(&self, row: Row) -> (&str, Option<&str>) {
+        match row {
+            Row::Zone(z) => (self.zones[z].name.as_str(), None),
+            Row::Record(z, r) => (
+                self.zones[z].name.as_str(),
+                Some(self.zones[z].records[r].name.as_str()),
+            ),
+        }
+    }
+
+    fn selected_names(&self) -> Option<(String, Option<String>)> {
+        let (zone, record) = self.names(self.selected_row()?);
+        Some((zone.to_string(), record.map(str::to_string)))
+    }
+}
+
+/// Read a new record typed as its name then its entries, like
+/// `www A 10.0.0.5, AAAA fd42::5`
+pub fn parse_record(text: &str) -> Result<LxdZoneRecord, RecordError> {
+    let text = text.trim();
+    let (name, entries) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    if name.is_empty() {
+        return Err(RecordError::MissingName);
+    }
+    Ok(LxdZoneRecord {
+        name: name.to_string(),
+        description: String::new(),
+        entries: parse_entries(entries)?,
+        config: HashMap::new(),
+    })
+}
+
+/// Read comma-separated entries, each a type and a value, like
+/// `A 10.0.0.5, MX 10 mail.example.net.`
+pub fn parse_entries(text: &str) -> Result<Vec<LxdZoneEntry>, RecordError> {
+    let entries = text
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (record_type, value) = entry.split_once(char::is_whitespace).unwrap_or((entry, ""));
+            let record_type = record_type.to_ascii_uppercase();
+            if !RECORD_TYPES.contains(&record_type.as_str()) {
+                return Err(RecordError::UnknownType(record_type));
+            }
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(RecordError::MissingValue(record_type));
+            }
+            Ok(LxdZoneEntry {
+                record_type,
+                value: value.to_string(),
+                ttl: None,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if entries.is_empty() {
+        return Err(RecordError::NoEntries);
+    }
+    Ok(entries)
+}
+
+/// Entries as `parse_entries` reads them
+pub fn format_entries(entries: &[LxdZoneEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| format!("{} {}", entry.record_type, entry.value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Names `instance` resolves as on the networks it is attached to: the
+/// bridge's own domain and each forward zone the network publishes into
+pub fn dns_names(instance: &str, networks: &[LxdNetwork]) -> Vec<String> {
+    let mut names = Vec::new();
+    for network in networks {
+        let mut domains = Vec::new();
+        if network.managed && network.network_type == "bridge" {
+            domains.push(
+                network
+                    .config
+                    .get("dns.domain")
+                    .map(|domain| domain.trim())
+                    .filter(|domain| !domain.is_empty())
+                    .unwrap_or(DEFAULT_DOMAIN),
+            );
+        }
+        domains.extend(
+            network
+                .config
+                .get(FORWARD_KEY)
+                .into_iter()
+                .flat_map(|zones| zones.split(','))
+                .map(str::trim)
+                .filter(|zone| !zone.is_empty()),
+        );
+        for domain in domains {
+            let name = format!("{}.{}", instance, domain.trim_end_matches('.'));
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,145 @@
+//! Tests of the network zones view and record parsing
+
+use super::{dns_names, format_entries, parse_record, RecordError, Row, Zone, ZoneKind, Zones};
+use crate::lxd_api::{LxdNetwork, LxdNetworkZone, LxdZoneEntry, LxdZoneRecord};
+use std::collections::HashMap;
+
+fn zone(name: &str, records: &[&str]) -> Zone {
+    Zone::new(
+        LxdNetworkZone {
+            name: name.to_string(),
+            description: String::new(),
+            used_by: vec!["/1.0/networks/lxdbr0?project=default".to_string()],
+        },
+        records
+            .iter()
+            .map(|record| parse_record(&format!("{} A 10.0.0.5", record)).unwrap())
+            .collect(),
+    )
+}
+
+fn entry(record_type: &str, value: &str) -> LxdZoneEntry {
+    LxdZoneEntry {
+        record_type: record_type.to_string(),
+        value: value.to_string(),
+        ttl: None,
+    }
+}
+
+#[test]
+fn reads_records_as_typed() {
+    assert_eq!(
+        parse_record("www a 10.0.0.5, AAAA fd42::5 ,MX 10 mail.example.net."),
+        Ok(LxdZoneRecord {
+            name: "www".to_string(),
+            description: String::new(),
+            entries: vec![
+                entry("A", "10.0.0.5"),
+                entry("AAAA", "fd42::5"),
+                entry("MX", "10 mail.example.net."),
+            ],
+            config: HashMap::new(),
+        })
+    );
+    let record = parse_record("mail MX 10 mail.example.net., TXT v=spf1 -all").unwrap();
+    assert_eq!(
+        format_entries(&record.entries),
+        "MX 10 mail.example.net., TXT v=spf1 -all"
+    );
+
+    assert_eq!(parse_record("  "), Err(RecordError::MissingName));
+    assert_eq!(parse_record("www"), Err(RecordError::NoEntries));
+    assert_eq!(
+        parse_record("www A"),
+        Err(RecordError::MissingValue("A".to_string()))
+    );
+    assert_eq!(
+        parse_record("www HINFO x86"),
+        Err(RecordError::UnknownType("HINFO".to_string()))
+    );
+}
+
+#[test]
+fn lists_forward_zones_first_and_keeps_the_selection() {
+    assert_eq!(ZoneKind::of("0.0.10.in-addr.arpa."), ZoneKind::Reverse);
+    assert_eq!(ZoneKind::of("lxd.example.net"), ZoneKind::Forward);
+
+    let mut zones = Zones::new();
+    zones.set_zones(vec![
+        zone("0.0.10.in-addr.arpa", &[]),
+        zone("lxd.example.net", &["www", "mail"]),
+    ]);
+    assert_eq!(zones.zones[0].name, "lxd.example.net");
+    assert_eq!(zones.zones[0].networks, ["lxdbr0"]);
+    assert_eq!(
+        zones.rows(),
+        [
+            Row::Zone(0),
+            Row::Record(0, 0),
+            Row::Record(0, 1),
+            Row::Zone(1)
+        ]
+    );
+
+    zones.select(true);
+    zones.select(true);
+    let (zone_name, record) = zones.selected_record().unwrap();
+    assert_eq!(
+        (zone_name.name.as_str(), record.name.as_str()),
+        ("lxd.example.net", "www")
+    );
+
+    // Another record sorting first leaves www selected
+    zones.set_zones(vec![
+        zone("lxd.example.net", &["www", "mail", "api"]),
+        zone("0.0.10.in-addr.arpa", &[]),
+    ]);
+    assert_eq!(zones.selected_record().unwrap().1.name, "www");
+
+    // Once www is gone its zone is selected
+    zones.set_zones(vec![zone("lxd.example.net", &["mail"])]);
+    assert_eq!(zones.selected_row(), Some(Row::Zone(0)));
+    zones.select(false);
+    assert_eq!(zones.selected_row(), Some(Row::Record(0, 0)));
+    assert_eq!(zones.selected_zone().unwrap().name, "lxd.example.net");
+}
+
+#[test]
+fn names_instances_by_domain_and_forward_zones() {
+    let network = |name: &str, network_type: &str, config: &[(&str, &str)]| LxdNetwork {
+        name: name.to_string(),
+        network_type: network_type.to_string(),
+        managed: true,
+        description: String::new(),
+        config: config
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>(),
+    };
+    let networks = [
+        network(
+            "lxdbr0",
+            "bridge",
+            &[("dns.zone.forward", "lxd.example.net, internal.example.net.")],
+        ),
+        network(
+            "lxdbr1",
+            "bridge",
+            &[
+                ("dns.domain", "lab"),
+                ("dns.zone.forward", "lxd.example.net"),
+            ],
+        ),
+        network("ovn0", "ovn", &[]),
+    ];
+    assert_eq!(
+        dns_names("web1", &networks),
+        [
+            "web1.lxd",
+            "web1.lxd.example.net",
+            "web1.internal.example.net",
+            "web1.lab"
+        ]
+    );
+    assert!(dns_names("web1", &networks[2..]).is_empty());
+}